@@ -29,6 +29,7 @@ use crate::{
     batch::BatchHash,
     constants::{ACC_LEN, DIGEST_LEN, MAX_AGG_SNARKS},
     core::{assign_batch_hashes, extract_proof_and_instances_with_pairing_check},
+    error::BatchError,
     util::parse_hash_digest_cells,
     ConfigParams,
 };
@@ -36,11 +37,13 @@ use crate::{
 use super::AggregationConfig;
 
 /// Aggregation circuit that does not re-expose any public inputs from aggregated snarks
+///
+/// N_SNARKS is the number of snarks aggregated in a batch, which defaults to MAX_AGG_SNARKS.
 #[derive(Clone)]
-pub struct AggregationCircuit {
+pub struct AggregationCircuit<const N_SNARKS: usize = MAX_AGG_SNARKS> {
     pub svk: KzgSuccinctVerifyingKey<G1Affine>,
     // the input snarks for the aggregation circuit
-    // it is padded already so it will have a fixed length of MAX_AGG_SNARKS
+    // it is padded already so it will have a fixed length of N_SNARKS
     pub snarks_with_padding: Vec<SnarkWitness>,
     // the public instance for this circuit consists of
     // - an accumulator (12 elements)
@@ -50,38 +53,49 @@ pub struct AggregationCircuit {
     pub as_proof: Value<Vec<u8>>,
     // batch hash circuit for which the snarks are generated
     // the chunks in this batch are also padded already
-    pub batch_hash: BatchHash,
+    pub batch_hash: BatchHash<N_SNARKS>,
 }
 
-impl AggregationCircuit {
+impl<const N_SNARKS: usize> AggregationCircuit<N_SNARKS> {
     pub fn new(
         params: &ParamsKZG<Bn256>,
         snarks_with_padding: &[Snark],
         rng: impl Rng + Send,
-        batch_hash: BatchHash,
-    ) -> Result<Self, snark_verifier::Error> {
+        batch_hash: BatchHash<N_SNARKS>,
+    ) -> Result<Self, BatchError> {
         let timer = start_timer!(|| "generate aggregation circuit");
 
+        if snarks_with_padding.len() != N_SNARKS {
+            return Err(BatchError::InvalidNumberOfChunks {
+                expected: N_SNARKS,
+                actual: snarks_with_padding.len(),
+            });
+        }
+
         // sanity check: snarks's public input matches chunk_hashes
-        for (chunk, snark) in batch_hash
+        for (index, (chunk, snark)) in batch_hash
             .chunks_with_padding
             .iter()
             .zip(snarks_with_padding.iter())
+            .enumerate()
         {
             let chunk_hash_bytes = chunk.public_input_hash();
-            let snark_hash_bytes = &snark.instances[0];
-
-            assert_eq!(snark_hash_bytes.len(), ACC_LEN + DIGEST_LEN);
-
-            for i in 0..DIGEST_LEN {
-                // for each snark,
-                //  first 12 elements are accumulator
-                //  next 32 elements are public_input_hash
-                //  accumulator + public_input_hash = snark public input
-                assert_eq!(
-                    Fr::from(chunk_hash_bytes.as_bytes()[i] as u64),
-                    snark_hash_bytes[i + ACC_LEN]
-                );
+            let snark_hash_bytes = match snark.instances.first() {
+                Some(instance) if instance.len() == ACC_LEN + DIGEST_LEN => instance,
+                _ => return Err(BatchError::SnarkInstanceMismatch(index)),
+            };
+
+            // for each snark,
+            //  first 12 elements are accumulator
+            //  next 32 elements are public_input_hash
+            //  accumulator + public_input_hash = snark public input
+            if chunk_hash_bytes
+                .as_bytes()
+                .iter()
+                .zip(&snark_hash_bytes[ACC_LEN..])
+                .any(|(&byte, &value)| Fr::from(byte as u64) != value)
+            {
+                return Err(BatchError::SnarkInstanceMismatch(index));
             }
         }
 
@@ -117,7 +131,7 @@ impl AggregationCircuit {
     }
 }
 
-impl Circuit<Fr> for AggregationCircuit {
+impl<const N_SNARKS: usize> Circuit<Fr> for AggregationCircuit<N_SNARKS> {
     type Config = (AggregationConfig, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
     fn without_witnesses(&self) -> Self {
@@ -230,7 +244,7 @@ impl Circuit<Fr> for AggregationCircuit {
                 },
             )?;
 
-            assert_eq!(snark_inputs.len(), MAX_AGG_SNARKS * DIGEST_LEN);
+            assert_eq!(snark_inputs.len(), N_SNARKS * DIGEST_LEN);
             (accumulator_instances, snark_inputs)
         };
         end_timer!(timer);
@@ -251,14 +265,10 @@ impl Circuit<Fr> for AggregationCircuit {
             let timer = start_timer!(|| "extract hash");
             // orders:
             // - batch_public_input_hash
-            // - chunk\[i\].piHash for i in \[0, MAX_AGG_SNARKS)
+            // - chunk\[i\].piHash for i in \[0, N_SNARKS)
             // - batch_data_hash_preimage
             let preimages = self.batch_hash.extract_hash_preimages();
-            assert_eq!(
-                preimages.len(),
-                MAX_AGG_SNARKS + 2,
-                "error extracting preimages"
-            );
+            assert_eq!(preimages.len(), N_SNARKS + 2, "error extracting preimages");
             end_timer!(timer);
 
             let timer = start_timer!(|| ("assign hash cells").to_string());
@@ -268,7 +278,7 @@ impl Circuit<Fr> for AggregationCircuit {
                 .iter()
                 .map(|chunk| !chunk.is_padding)
                 .collect::<Vec<_>>();
-            let hash_digest_cells = assign_batch_hashes(
+            let hash_digest_cells = assign_batch_hashes::<N_SNARKS>(
                 &config,
                 &mut layouter,
                 challenges,
@@ -281,7 +291,7 @@ impl Circuit<Fr> for AggregationCircuit {
         };
         // digests
         let (batch_pi_hash_digest, chunk_pi_hash_digests, _potential_batch_data_hash_digest) =
-            parse_hash_digest_cells(&hash_digest_cells);
+            parse_hash_digest_cells::<N_SNARKS>(&hash_digest_cells);
 
         // ==============================================
         // step 3: assert public inputs to the snarks are correct
@@ -313,7 +323,7 @@ impl Circuit<Fr> for AggregationCircuit {
                     return Ok(());
                 }
 
-                for i in 0..MAX_AGG_SNARKS {
+                for i in 0..N_SNARKS {
                     for j in 0..4 {
                         for k in 0..8 {
                             let mut t1 = Fr::default();
@@ -377,7 +387,7 @@ impl Circuit<Fr> for AggregationCircuit {
     }
 }
 
-impl CircuitExt<Fr> for AggregationCircuit {
+impl<const N_SNARKS: usize> CircuitExt<Fr> for AggregationCircuit<N_SNARKS> {
     fn num_instance(&self) -> Vec<usize> {
         // 12 elements from accumulator
         // 32 elements from batch's public_input_hash
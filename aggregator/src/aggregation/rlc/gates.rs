@@ -16,22 +16,13 @@ impl RlcConfig {
         region.assign_fixed(|| "const zero", self.fixed, 0, || Value::known(Fr::zero()))?;
         region.assign_fixed(|| "const one", self.fixed, 1, || Value::known(Fr::one()))?;
         region.assign_fixed(|| "const two", self.fixed, 2, || Value::known(Fr::from(2)))?;
-        region.assign_fixed(|| "const five", self.fixed, 3, || Value::known(Fr::from(5)))?;
-        region.assign_fixed(|| "const nine", self.fixed, 4, || Value::known(Fr::from(9)))?;
-        region.assign_fixed(|| "const 13", self.fixed, 5, || Value::known(Fr::from(13)))?;
-        region.assign_fixed(|| "const 32", self.fixed, 6, || Value::known(Fr::from(32)))?;
+        region.assign_fixed(|| "const 32", self.fixed, 3, || Value::known(Fr::from(32)))?;
         region.assign_fixed(
             || "const 136",
             self.fixed,
-            7,
+            4,
             || Value::known(Fr::from(136)),
         )?;
-        region.assign_fixed(
-            || "const 2^32",
-            self.fixed,
-            8,
-            || Value::known(Fr::from(1 << 32)),
-        )?;
         Ok(())
     }
 
@@ -62,38 +53,11 @@ impl RlcConfig {
         }
     }
 
-    #[inline]
-    pub(crate) fn five_cell(&self, region_index: RegionIndex) -> Cell {
-        Cell {
-            region_index,
-            row_offset: 3,
-            column: self.fixed.into(),
-        }
-    }
-
-    #[inline]
-    pub(crate) fn nine_cell(&self, region_index: RegionIndex) -> Cell {
-        Cell {
-            region_index,
-            row_offset: 4,
-            column: self.fixed.into(),
-        }
-    }
-
-    #[inline]
-    pub(crate) fn thirteen_cell(&self, region_index: RegionIndex) -> Cell {
-        Cell {
-            region_index,
-            row_offset: 5,
-            column: self.fixed.into(),
-        }
-    }
-
     #[inline]
     pub(crate) fn thirty_two_cell(&self, region_index: RegionIndex) -> Cell {
         Cell {
             region_index,
-            row_offset: 6,
+            row_offset: 3,
             column: self.fixed.into(),
         }
     }
@@ -101,16 +65,7 @@ impl RlcConfig {
     pub(crate) fn one_hundred_and_thirty_six_cell(&self, region_index: RegionIndex) -> Cell {
         Cell {
             region_index,
-            row_offset: 7,
-            column: self.fixed.into(),
-        }
-    }
-
-    #[inline]
-    pub(crate) fn two_to_thirty_two_cell(&self, region_index: RegionIndex) -> Cell {
-        Cell {
-            region_index,
-            row_offset: 8,
+            row_offset: 4,
             column: self.fixed.into(),
         }
     }
@@ -455,30 +410,6 @@ impl RlcConfig {
         Ok(bit_cells)
     }
 
-    // return a boolean if a is smaller than b
-    // requires that both a and b are less than 32 bits
-    pub(crate) fn is_smaller_than(
-        &self,
-        region: &mut Region<Fr>,
-        a: &AssignedCell<Fr, Fr>,
-        b: &AssignedCell<Fr, Fr>,
-        offset: &mut usize,
-    ) -> Result<AssignedCell<Fr, Fr>, Error> {
-        // when a and b are both small (as in our use case)
-        // if a >= b, c = 2^32 + (a-b) will be >= 2^32
-        // we bit decompose c and check the 33-th bit
-        let two_to_thirty_two = self.load_private(region, &Fr::from(1 << 32), offset)?;
-        let two_to_thirty_two_cell =
-            self.two_to_thirty_two_cell(two_to_thirty_two.cell().region_index);
-        region.constrain_equal(two_to_thirty_two_cell, two_to_thirty_two.cell())?;
-
-        let ca = self.add(region, &two_to_thirty_two, a, offset)?;
-        let c = self.sub(region, &ca, b, offset)?;
-        let bits = self.decomposition(region, &c, 33, offset)?;
-        let res = self.not(region, &bits[32], offset)?;
-        Ok(res)
-    }
-
     // return a boolean if a ?= 0
     #[allow(dead_code)]
     pub(crate) fn is_zero(
@@ -4,38 +4,53 @@
 use eth_types::{Field, H256};
 use ethers_core::utils::keccak256;
 
-use crate::constants::MAX_AGG_SNARKS;
+use crate::{constants::MAX_AGG_SNARKS, error::BatchError};
 
 use super::chunk::ChunkHash;
 
-#[derive(Default, Debug, Clone)]
-/// A batch is a set of MAX_AGG_SNARKS num of continuous chunks
+#[derive(Debug, Clone)]
+/// A batch is a set of N_SNARKS num of continuous chunks
 /// - the first k chunks are from real traces
-/// - the last (#MAX_AGG_SNARKS-k) chunks are from empty traces
+/// - the last (#N_SNARKS-k) chunks are from empty traces
 /// A BatchHash consists of 2 hashes.
 /// - batch_pi_hash   := keccak(chain_id || chunk_0.prev_state_root || chunk_k-1.post_state_root ||
 ///   chunk_k-1.withdraw_root || batch_data_hash)
 /// - batch_data_hash := keccak(chunk_0.data_hash || ... || chunk_k-1.data_hash)
-pub struct BatchHash {
+///
+/// N_SNARKS is the batch size, which defaults to MAX_AGG_SNARKS. Each batch size has its own
+/// aggregation circuit and thus its own verifying key.
+pub struct BatchHash<const N_SNARKS: usize = MAX_AGG_SNARKS> {
     pub(crate) chain_id: u64,
     // chunks with padding.
     // - the first [0..number_of_valid_chunks) are real ones
-    // - the last [number_of_valid_chunks, MAX_AGG_SNARKS) are padding
-    pub(crate) chunks_with_padding: [ChunkHash; MAX_AGG_SNARKS],
+    // - the last [number_of_valid_chunks, N_SNARKS) are padding
+    pub(crate) chunks_with_padding: [ChunkHash; N_SNARKS],
     pub(crate) data_hash: H256,
     pub(crate) public_input_hash: H256,
     pub(crate) number_of_valid_chunks: usize,
 }
 
-impl BatchHash {
-    /// Build Batch hash from an ordered list of #MAX_AGG_SNARKS of chunks.
-    #[allow(dead_code)]
-    pub fn construct(chunks_with_padding: &[ChunkHash]) -> Self {
-        assert_eq!(
-            chunks_with_padding.len(),
-            MAX_AGG_SNARKS,
-            "input chunk slice does not match MAX_AGG_SNARKS"
-        );
+impl<const N_SNARKS: usize> Default for BatchHash<N_SNARKS> {
+    fn default() -> Self {
+        Self {
+            chain_id: 0,
+            chunks_with_padding: [ChunkHash::default(); N_SNARKS],
+            data_hash: H256::default(),
+            public_input_hash: H256::default(),
+            number_of_valid_chunks: 0,
+        }
+    }
+}
+
+impl<const N_SNARKS: usize> BatchHash<N_SNARKS> {
+    /// Build Batch hash from an ordered list of #N_SNARKS of chunks.
+    pub fn construct(chunks_with_padding: &[ChunkHash]) -> Result<Self, BatchError> {
+        if chunks_with_padding.len() != N_SNARKS {
+            return Err(BatchError::InvalidNumberOfChunks {
+                expected: N_SNARKS,
+                actual: chunks_with_padding.len(),
+            });
+        }
 
         let number_of_valid_chunks = match chunks_with_padding
             .iter()
@@ -43,13 +58,12 @@ impl BatchHash {
             .find(|(_index, chunk)| chunk.is_padding)
         {
             Some((index, _)) => index,
-            None => MAX_AGG_SNARKS,
+            None => N_SNARKS,
         };
 
-        assert_ne!(
-            number_of_valid_chunks, 0,
-            "input chunk slice does not contain real chunks"
-        );
+        if number_of_valid_chunks == 0 {
+            return Err(BatchError::NoRealChunk);
+        }
         log::trace!("build a Batch with {number_of_valid_chunks} real chunks");
 
         log::trace!("chunks with padding");
@@ -60,34 +74,34 @@ impl BatchHash {
         // ========================
         // sanity checks
         // ========================
-        // todo: return errors instead
-        for i in 0..MAX_AGG_SNARKS - 1 {
-            assert_eq!(
-                chunks_with_padding[i].chain_id,
-                chunks_with_padding[i + 1].chain_id,
-            );
-            if chunks_with_padding[i + 1].is_padding {
-                assert_eq!(
-                    chunks_with_padding[i + 1].data_hash,
-                    chunks_with_padding[i].data_hash
-                );
-                assert_eq!(
-                    chunks_with_padding[i + 1].prev_state_root,
-                    chunks_with_padding[i].prev_state_root
-                );
-                assert_eq!(
-                    chunks_with_padding[i + 1].post_state_root,
-                    chunks_with_padding[i].post_state_root
-                );
-                assert_eq!(
-                    chunks_with_padding[i + 1].withdraw_root,
-                    chunks_with_padding[i].withdraw_root
-                );
+        for i in 0..N_SNARKS - 1 {
+            let (chunk, next) = (&chunks_with_padding[i], &chunks_with_padding[i + 1]);
+            if chunk.chain_id != next.chain_id {
+                return Err(BatchError::ChainIdMismatch {
+                    index: i + 1,
+                    expected: chunk.chain_id,
+                    actual: next.chain_id,
+                });
+            }
+            if next.is_padding {
+                if next.data_hash != chunk.data_hash
+                    || next.prev_state_root != chunk.prev_state_root
+                    || next.post_state_root != chunk.post_state_root
+                    || next.withdraw_root != chunk.withdraw_root
+                {
+                    return Err(BatchError::InvalidPaddingChunk(i + 1));
+                }
             } else {
-                assert_eq!(
-                    chunks_with_padding[i].post_state_root,
-                    chunks_with_padding[i + 1].prev_state_root,
-                );
+                if chunk.is_padding {
+                    return Err(BatchError::RealChunkAfterPadding(i + 1));
+                }
+                if chunk.post_state_root != next.prev_state_root {
+                    return Err(BatchError::StateRootMismatch {
+                        index: i + 1,
+                        expected: chunk.post_state_root,
+                        actual: next.prev_state_root,
+                    });
+                }
             }
         }
 
@@ -111,32 +125,44 @@ impl BatchHash {
         let preimage = [
             chunks_with_padding[0].chain_id.to_be_bytes().as_ref(),
             chunks_with_padding[0].prev_state_root.as_bytes(),
-            chunks_with_padding[MAX_AGG_SNARKS - 1]
-                .post_state_root
-                .as_bytes(),
-            chunks_with_padding[MAX_AGG_SNARKS - 1]
-                .withdraw_root
-                .as_bytes(),
+            chunks_with_padding[N_SNARKS - 1].post_state_root.as_bytes(),
+            chunks_with_padding[N_SNARKS - 1].withdraw_root.as_bytes(),
             data_hash.as_slice(),
         ]
         .concat();
         let public_input_hash = keccak256(preimage);
 
-        Self {
+        Ok(Self {
             chain_id: chunks_with_padding[0].chain_id,
             chunks_with_padding: chunks_with_padding.try_into().unwrap(), // safe unwrap
             data_hash: data_hash.into(),
             public_input_hash: public_input_hash.into(),
             number_of_valid_chunks,
+        })
+    }
+
+    /// Return the batch as a chunk of a bundle, i.e., a batch of continuous batches.
+    ///
+    /// The public input hash of a batch is built in the same way as the one of a chunk, with the
+    /// batch's data hash in place of the chunk's data hash. Therefore the (compressed) snark of
+    /// this batch can be aggregated as a chunk snark by the AggregationCircuit.
+    pub fn to_chunk_hash(&self) -> ChunkHash {
+        ChunkHash {
+            chain_id: self.chain_id,
+            prev_state_root: self.chunks_with_padding[0].prev_state_root,
+            post_state_root: self.chunks_with_padding[N_SNARKS - 1].post_state_root,
+            withdraw_root: self.chunks_with_padding[N_SNARKS - 1].withdraw_root,
+            data_hash: self.data_hash,
+            is_padding: false,
         }
     }
 
     /// Extract all the hash inputs that will ever be used.
-    /// There are MAX_AGG_SNARKS + 2 hashes.
+    /// There are N_SNARKS + 2 hashes.
     ///
     /// orders:
     /// - batch_public_input_hash
-    /// - chunk\[i\].piHash for i in \[0, MAX_AGG_SNARKS)
+    /// - chunk\[i\].piHash for i in \[0, N_SNARKS)
     /// - batch_data_hash_preimage
     pub(crate) fn extract_hash_preimages(&self) -> Vec<Vec<u8>> {
         let mut res = vec![];
@@ -151,10 +177,10 @@ impl BatchHash {
         let batch_public_input_hash_preimage = [
             self.chain_id.to_be_bytes().as_ref(),
             self.chunks_with_padding[0].prev_state_root.as_bytes(),
-            self.chunks_with_padding[MAX_AGG_SNARKS - 1]
+            self.chunks_with_padding[N_SNARKS - 1]
                 .post_state_root
                 .as_bytes(),
-            self.chunks_with_padding[MAX_AGG_SNARKS - 1]
+            self.chunks_with_padding[N_SNARKS - 1]
                 .withdraw_root
                 .as_bytes(),
             self.data_hash.as_bytes(),
@@ -162,7 +188,7 @@ impl BatchHash {
         .concat();
         res.push(batch_public_input_hash_preimage);
 
-        // compute piHash for each chunk for i in [0..MAX_AGG_SNARKS)
+        // compute piHash for each chunk for i in [0..N_SNARKS)
         // chunk[i].piHash =
         // keccak(
         //        chain id ||
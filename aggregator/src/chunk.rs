@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 use std::iter;
 use zkevm_circuits::witness::Block;
 
+use crate::error::BatchError;
+
 #[derive(Default, Debug, Clone, Copy, Deserialize, Serialize)]
 /// A chunk is a set of continuous blocks.
 /// A ChunkHash consists of 4 hashes, representing the changes incurred by this chunk of blocks:
@@ -32,39 +34,53 @@ pub struct ChunkHash {
 
 impl ChunkHash {
     /// Construct by a witness block.
-    pub fn from_witness_block(block: &Block<Fr>, is_padding: bool) -> Self {
+    pub fn from_witness_block(block: &Block<Fr>, is_padding: bool) -> Result<Self, BatchError> {
         // <https://github.com/scroll-tech/zkevm-circuits/blob/25dd32aa316ec842ffe79bb8efe9f05f86edc33e/bus-mapping/src/circuit_input_builder.rs#L690>
 
         let mut total_l1_popped = block.start_l1_queue_index;
         log::debug!("chunk-hash: start_l1_queue_index = {}", total_l1_popped);
-        let data_bytes = iter::empty()
-            // .chain(block_headers.iter().flat_map(|(&block_num, block)| {
-            .chain(block.context.ctxs.iter().flat_map(|(b_num, b_ctx)| {
-                let num_l2_txs = block
-                    .txs
-                    .iter()
-                    .filter(|tx| !tx.tx_type.is_l1_msg() && tx.block_number == *b_num)
-                    .count() as u64;
-                let num_l1_msgs = block
-                    .txs
-                    .iter()
-                    .filter(|tx| tx.tx_type.is_l1_msg() && tx.block_number == *b_num)
-                    // tx.nonce alias for queue_index for l1 msg tx
-                    .map(|tx| tx.nonce)
-                    .max()
-                    .map_or(0, |max_queue_index| max_queue_index - total_l1_popped + 1);
-                total_l1_popped += num_l1_msgs;
+        let mut data_bytes = vec![];
+        for (b_num, b_ctx) in block.context.ctxs.iter() {
+            let num_l2_txs = block
+                .txs
+                .iter()
+                .filter(|tx| !tx.tx_type.is_l1_msg() && tx.block_number == *b_num)
+                .count() as u64;
+            let num_l1_msgs = match block
+                .txs
+                .iter()
+                .filter(|tx| tx.tx_type.is_l1_msg() && tx.block_number == *b_num)
+                // tx.nonce alias for queue_index for l1 msg tx
+                .map(|tx| tx.nonce)
+                .max()
+            {
+                Some(max_queue_index) if max_queue_index < total_l1_popped => {
+                    return Err(BatchError::InvalidL1QueueIndex {
+                        block_number: *b_num,
+                        queue_index: max_queue_index,
+                        total_l1_popped,
+                    });
+                }
+                Some(max_queue_index) => max_queue_index - total_l1_popped + 1,
+                None => 0,
+            };
+            total_l1_popped += num_l1_msgs;
 
-                let num_txs = (num_l2_txs + num_l1_msgs) as u16;
-                log::debug!(
-                    "chunk-hash: [block {}] total_l1_popped = {}, num_l1_msgs = {}, num_l2_txs = {}, num_txs = {}",
-                    b_num,
-                    total_l1_popped,
-                    num_l1_msgs,
-                    num_l2_txs,
-                    num_txs,
-                );
+            let num_txs = num_l2_txs + num_l1_msgs;
+            log::debug!(
+                "chunk-hash: [block {}] total_l1_popped = {}, num_l1_msgs = {}, num_l2_txs = {}, num_txs = {}",
+                b_num,
+                total_l1_popped,
+                num_l1_msgs,
+                num_l2_txs,
+                num_txs,
+            );
+            let num_txs = u16::try_from(num_txs).map_err(|_| BatchError::TooManyTxs {
+                block_number: *b_num,
+                num_txs,
+            })?;
 
+            data_bytes.extend(
                 iter::empty()
                     // Block Values
                     .chain(b_ctx.number.as_u64().to_be_bytes())
@@ -72,10 +88,11 @@ impl ChunkHash {
                     .chain(b_ctx.base_fee.to_be_bytes())
                     .chain(b_ctx.gas_limit.to_be_bytes())
                     .chain(num_txs.to_be_bytes())
-            }))
-            // Tx Hashes
-            .chain(block.txs.iter().flat_map(|tx| tx.hash.to_fixed_bytes()))
-            .collect::<Vec<u8>>();
+                    .chain(b_ctx.blob_base_fee.to_be_bytes()),
+            );
+        }
+        // Tx Hashes
+        data_bytes.extend(block.txs.iter().flat_map(|tx| tx.hash.to_fixed_bytes()));
 
         let data_hash = H256(keccak256(data_bytes));
         log::debug!(
@@ -90,14 +107,14 @@ impl ChunkHash {
             .map(|(_, b_ctx)| b_ctx.eth_block.state_root)
             .unwrap_or(H256(block.prev_state_root.to_be_bytes()));
 
-        Self {
+        Ok(Self {
             chain_id: block.chain_id,
             prev_state_root: H256(block.prev_state_root.to_be_bytes()),
             post_state_root,
             withdraw_root: H256(block.withdraw_root.to_be_bytes()),
             data_hash,
             is_padding,
-        }
+        })
     }
 
     /// Sample a chunk hash from random (for testing)
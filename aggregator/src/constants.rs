@@ -44,8 +44,14 @@ pub(crate) const LIMBS: usize = 3;
 /// number of bits in each limb in the ECC chip
 pub(crate) const BITS: usize = 88;
 
-/// Max number of snarks to be aggregated in a chunk.
+/// Default number of snarks to be aggregated in a batch,
+/// i.e., the default batch size of BatchHash and AggregationCircuit.
 /// If the input size is less than this, dummy snarks
 /// will be padded.
-// TODO: update me(?)
 pub const MAX_AGG_SNARKS: usize = 15;
+
+/// Default number of batch snarks to be aggregated in a bundle,
+/// i.e., a batch of continuous batches.
+/// If the input size is less than this, dummy snarks
+/// will be padded.
+pub const MAX_AGG_BATCHES: usize = 4;
@@ -33,10 +33,11 @@ use zkevm_circuits::{
 };
 
 use crate::{
-    constants::{CHAIN_ID_LEN, DIGEST_LEN, INPUT_LEN_PER_ROUND, LOG_DEGREE, MAX_AGG_SNARKS},
+    constants::{CHAIN_ID_LEN, DIGEST_LEN, INPUT_LEN_PER_ROUND, LOG_DEGREE},
     util::{
-        assert_conditional_equal, assert_equal, assert_exist, get_indices, get_max_keccak_updates,
-        parse_hash_digest_cells, parse_hash_preimage_cells, parse_pi_hash_rlc_cells,
+        assert_conditional_equal, assert_equal, assert_exist, get_data_hash_keccak_updates,
+        get_data_hash_round_start, get_indices, get_max_keccak_updates, parse_hash_digest_cells,
+        parse_hash_preimage_cells, parse_pi_hash_rlc_cells,
     },
     AggregationConfig, RlcConfig, BITS, CHUNK_DATA_HASH_INDEX, LIMBS, POST_STATE_ROOT_INDEX,
     PREV_STATE_ROOT_INDEX, WITHDRAW_ROOT_INDEX,
@@ -165,26 +166,26 @@ pub(crate) struct ExtractedHashCells {
 // 1. batch_data_hash digest is reused for public input hash
 // 2. batch_pi_hash used same roots as chunk_pi_hash
 // 2.1. batch_pi_hash and chunk[0] use a same prev_state_root
-// 2.2. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same post_state_root
-// 2.3. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same withdraw_root
+// 2.2. batch_pi_hash and chunk[N_SNARKS-1] use a same post_state_root
+// 2.3. batch_pi_hash and chunk[N_SNARKS-1] use a same withdraw_root
 // 3. batch_data_hash and chunk[i].pi_hash use a same chunk[i].data_hash when chunk[i] is not padded
 // 4. chunks are continuous: they are linked via the state roots
 // 5. batch and all its chunks use a same chain id
 // 6. chunk[i]'s chunk_pi_hash_rlc_cells == chunk[i-1].chunk_pi_hash_rlc_cells when chunk[i] is
 // padded
 // 7. the hash input length are correct
-// - first MAX_AGG_SNARKS + 1 hashes all have 136 bytes input
+// - first N_SNARKS + 1 hashes all have 136 bytes input
 // - batch's data_hash length is 32 * number_of_valid_snarks
 // 8. batch data hash is correct w.r.t. its RLCs
 // 9. is_final_cells are set correctly
-pub(crate) fn assign_batch_hashes(
+pub(crate) fn assign_batch_hashes<const N_SNARKS: usize>(
     config: &AggregationConfig,
     layouter: &mut impl Layouter<Fr>,
     challenges: Challenges<Value<Fr>>,
     chunks_are_valid: &[bool],
     preimages: &[Vec<u8>],
 ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
-    let extracted_hash_cells = extract_hash_cells(
+    let extracted_hash_cells = extract_hash_cells::<N_SNARKS>(
         &config.keccak_circuit_config,
         layouter,
         challenges,
@@ -192,10 +193,10 @@ pub(crate) fn assign_batch_hashes(
     )?;
     // 2. batch_pi_hash used same roots as chunk_pi_hash
     // 2.1. batch_pi_hash and chunk[0] use a same prev_state_root
-    // 2.2. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same post_state_root
-    // 2.3. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same withdraw_root
+    // 2.2. batch_pi_hash and chunk[N_SNARKS-1] use a same post_state_root
+    // 2.3. batch_pi_hash and chunk[N_SNARKS-1] use a same withdraw_root
     // 5. batch and all its chunks use a same chain id
-    copy_constraints(layouter, &extracted_hash_cells.hash_input_cells)?;
+    copy_constraints::<N_SNARKS>(layouter, &extracted_hash_cells.hash_input_cells)?;
 
     // 1. batch_data_hash digest is reused for public input hash
     // 3. batch_data_hash and chunk[i].pi_hash use a same chunk[i].data_hash when chunk[i] is not
@@ -204,11 +205,11 @@ pub(crate) fn assign_batch_hashes(
     // 6. chunk[i]'s chunk_pi_hash_rlc_cells == chunk[i-1].chunk_pi_hash_rlc_cells when chunk[i] is
     // padded
     // 7. the hash input length are correct
-    // - first MAX_AGG_SNARKS + 1 hashes all have 136 bytes input
+    // - first N_SNARKS + 1 hashes all have 136 bytes input
     // - batch's data_hash length is 32 * number_of_valid_snarks
     // 8. batch data hash is correct w.r.t. its RLCs
     // 9. is_final_cells are set correctly
-    conditional_constraints(
+    conditional_constraints::<N_SNARKS>(
         &config.rlc_config,
         layouter,
         challenges,
@@ -219,7 +220,7 @@ pub(crate) fn assign_batch_hashes(
     Ok(extracted_hash_cells.hash_output_cells)
 }
 
-pub(crate) fn extract_hash_cells(
+pub(crate) fn extract_hash_cells<const N_SNARKS: usize>(
     keccak_config: &KeccakCircuitConfig<Fr>,
     layouter: &mut impl Layouter<Fr>,
     challenges: Challenges<Value<Fr>>,
@@ -227,7 +228,7 @@ pub(crate) fn extract_hash_cells(
 ) -> Result<ExtractedHashCells, Error> {
     let mut is_first_time = true;
     let keccak_capacity = KeccakCircuit::<Fr>::capacity_for_row(1 << LOG_DEGREE);
-    let max_keccak_updates = get_max_keccak_updates(MAX_AGG_SNARKS);
+    let max_keccak_updates = get_max_keccak_updates(N_SNARKS);
     let keccak_f_rows = keccak_packed_multi::get_num_rows_per_update();
 
     let timer = start_timer!(|| ("multi keccak").to_string());
@@ -250,7 +251,7 @@ pub(crate) fn extract_hash_cells(
     end_timer!(timer);
 
     // extract the indices of the rows for which the preimage and the digest cells lie in
-    let (preimage_indices, digest_indices) = get_indices(preimages);
+    let (preimage_indices, digest_indices) = get_indices::<N_SNARKS>(preimages);
 
     let extracted_hash_cells = layouter
         .assign_region(
@@ -316,7 +317,10 @@ pub(crate) fn extract_hash_cells(
                     hash_input_cells.len(),
                     max_keccak_updates * INPUT_LEN_PER_ROUND
                 );
-                assert_eq!(hash_output_cells.len(), (MAX_AGG_SNARKS + 5) * DIGEST_LEN);
+                assert_eq!(
+                    hash_output_cells.len(),
+                    (N_SNARKS + 1 + get_data_hash_keccak_updates(N_SNARKS)) * DIGEST_LEN
+                );
 
                 keccak_config
                     .keccak_table
@@ -343,10 +347,10 @@ pub(crate) fn extract_hash_cells(
 // Assert the following constraints
 // 2. batch_pi_hash used same roots as chunk_pi_hash
 // 2.1. batch_pi_hash and chunk[0] use a same prev_state_root
-// 2.2. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same post_state_root
-// 2.3. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same withdraw_root
+// 2.2. batch_pi_hash and chunk[N_SNARKS-1] use a same post_state_root
+// 2.3. batch_pi_hash and chunk[N_SNARKS-1] use a same withdraw_root
 // 5. batch and all its chunks use a same chain id
-fn copy_constraints(
+fn copy_constraints<const N_SNARKS: usize>(
     layouter: &mut impl Layouter<Fr>,
     hash_input_cells: &[AssignedCell<Fr, Fr>],
 ) -> Result<(), Error> {
@@ -370,7 +374,7 @@ fn copy_constraints(
                     batch_pi_hash_preimage,
                     chunk_pi_hash_preimages,
                     _potential_batch_data_hash_preimage,
-                ) = parse_hash_preimage_cells(hash_input_cells);
+                ) = parse_hash_preimage_cells::<N_SNARKS>(hash_input_cells);
 
                 // ====================================================
                 // Constraint the relations between hash preimages
@@ -419,35 +423,33 @@ fn copy_constraints(
                     // sanity check
                     assert_equal(
                         &batch_pi_hash_preimage[i + POST_STATE_ROOT_INDEX],
-                        &chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + POST_STATE_ROOT_INDEX],
+                        &chunk_pi_hash_preimages[N_SNARKS - 1][i + POST_STATE_ROOT_INDEX],
                         format!(
                             "chunk and batch's post_state_root do not match: {:?} {:?}",
                             &batch_pi_hash_preimage[i + POST_STATE_ROOT_INDEX].value(),
-                            &chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + POST_STATE_ROOT_INDEX]
+                            &chunk_pi_hash_preimages[N_SNARKS - 1][i + POST_STATE_ROOT_INDEX]
                                 .value(),
                         )
                         .as_str(),
                     )?;
                     region.constrain_equal(
                         batch_pi_hash_preimage[i + POST_STATE_ROOT_INDEX].cell(),
-                        chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + POST_STATE_ROOT_INDEX]
-                            .cell(),
+                        chunk_pi_hash_preimages[N_SNARKS - 1][i + POST_STATE_ROOT_INDEX].cell(),
                     )?;
                     // 2.3 chunk[k-1].withdraw_root
                     assert_equal(
                         &batch_pi_hash_preimage[i + WITHDRAW_ROOT_INDEX],
-                        &chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + WITHDRAW_ROOT_INDEX],
+                        &chunk_pi_hash_preimages[N_SNARKS - 1][i + WITHDRAW_ROOT_INDEX],
                         format!(
                             "chunk and batch's withdraw_root do not match: {:?} {:?}",
                             &batch_pi_hash_preimage[i + WITHDRAW_ROOT_INDEX].value(),
-                            &chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + WITHDRAW_ROOT_INDEX]
-                                .value(),
+                            &chunk_pi_hash_preimages[N_SNARKS - 1][i + WITHDRAW_ROOT_INDEX].value(),
                         )
                         .as_str(),
                     )?;
                     region.constrain_equal(
                         batch_pi_hash_preimage[i + WITHDRAW_ROOT_INDEX].cell(),
-                        chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + WITHDRAW_ROOT_INDEX].cell(),
+                        chunk_pi_hash_preimages[N_SNARKS - 1][i + WITHDRAW_ROOT_INDEX].cell(),
                     )?;
                 }
 
@@ -487,11 +489,11 @@ fn copy_constraints(
 // 6. chunk[i]'s chunk_pi_hash_rlc_cells == chunk[i-1].chunk_pi_hash_rlc_cells when chunk[i] is
 // padded
 // 7. the hash input length are correct
-// - first MAX_AGG_SNARKS + 1 hashes all have 136 bytes input
+// - first N_SNARKS + 1 hashes all have 136 bytes input
 // - batch's data_hash length is 32 * number_of_valid_snarks
 // 8. batch data hash is correct w.r.t. its RLCs
 // 9. is_final_cells are set correctly
-pub(crate) fn conditional_constraints(
+pub(crate) fn conditional_constraints<const N_SNARKS: usize>(
     rlc_config: &RlcConfig,
     layouter: &mut impl Layouter<Fr>,
     challenges: Challenges<Value<Fr>>,
@@ -536,86 +538,47 @@ pub(crate) fn conditional_constraints(
 
                 log::trace!("number of valid chunks: {:?}", num_valid_snarks.value());
                 //
-                // if the num_of_valid_snarks <= 4, which only needs 1 keccak-f round. Therefore
-                // the batch's data hash (input, len, data_rlc, output_rlc) are in the first 300
-                // keccak rows;
-                //
-                // else if the num_of_valid_snarks <= 8, which needs
-                // 2 keccak-f rounds. Therefore the batch's data hash (input, len, data_rlc,
-                // output_rlc) are in the 2nd 300 keccak rows;
-                //
-                // else if the
-                // num_of_valid_snarks <= 12, which needs 3 keccak-f rounds. Therefore the batch's
-                // data hash (input, len, data_rlc, output_rlc) are in the 3rd 300 keccak rows;
-                //
-                // else if the
-                // num_of_valid_snarks <= 16, which needs 4 keccak-f rounds. Therefore the batch's
-                // data hash (input, len, data_rlc, output_rlc) are in the 4th 300 keccak rows;
+                // the batch's data hash (input, len, data_rlc, output_rlc) lies in the keccak-f
+                // round #(32 * num_of_valid_snarks / 136) of the data hash, since each round
+                // absorbs 136 bytes, i.e., 4.25 chunks;
                 //
-                // the following flag is build to indicate which row the final data_rlc exists
+                // the following flags are build to indicate which round the final data_rlc
+                // exists, e.g.
                 //
                 // #valid snarks | offset of data hash | flags
-                // 1,2,3,4       | 0                   | 1, 0, 0, 0
-                // 5,6,7,8       | 32                  | 0, 1, 0, 0
-                // 9,10,11,12    | 64                  | 0, 0, 1, 0
-                // 13,14,15,16   | 96                  | 0, 0, 0, 1
-
-                let five = {
-                    let five = rlc_config.load_private(&mut region, &Fr::from(5), &mut offset)?;
-                    let five_cell = rlc_config.five_cell(five.cell().region_index);
-                    region.constrain_equal(five_cell, five.cell())?;
-                    five
-                };
-                let nine = {
-                    let nine = rlc_config.load_private(&mut region, &Fr::from(9), &mut offset)?;
-                    let nine_cell = rlc_config.nine_cell(nine.cell().region_index);
-                    region.constrain_equal(nine_cell, nine.cell())?;
-                    nine
-                };
-                let thirteen = {
-                    let thirteen =
-                        rlc_config.load_private(&mut region, &Fr::from(13), &mut offset)?;
-                    let thirteen_cell = rlc_config.thirteen_cell(thirteen.cell().region_index);
-                    region.constrain_equal(thirteen_cell, thirteen.cell())?;
-                    thirteen
-                };
-
-                let smaller_or_eq_4 = rlc_config.is_smaller_than(
-                    &mut region,
-                    &num_valid_snarks,
-                    &five,
-                    &mut offset,
-                )?;
-                let greater_than_4 = rlc_config.not(&mut region, &smaller_or_eq_4, &mut offset)?;
-                let smaller_or_eq_8 = rlc_config.is_smaller_than(
-                    &mut region,
-                    &num_valid_snarks,
-                    &nine,
-                    &mut offset,
-                )?;
-                let greater_than_8 = rlc_config.not(&mut region, &smaller_or_eq_8, &mut offset)?;
-                let smaller_or_eq_12 = rlc_config.is_smaller_than(
-                    &mut region,
-                    &num_valid_snarks,
-                    &thirteen,
-                    &mut offset,
-                )?;
-                let greater_than_12 =
-                    rlc_config.not(&mut region, &smaller_or_eq_12, &mut offset)?;
-
-                let flag1 = smaller_or_eq_4;
-                let flag2 =
-                    rlc_config.mul(&mut region, &greater_than_4, &smaller_or_eq_8, &mut offset)?;
-                let flag3 =
-                    rlc_config.mul(&mut region, &greater_than_8, &smaller_or_eq_12, &mut offset)?;
-                let flag4 = greater_than_12;
+                // 1,2,3,4       | 0                   | 1, 0, 0, 0, 0
+                // 5,6,7,8       | 32                  | 0, 1, 0, 0, 0
+                // 9,10,11,12    | 64                  | 0, 0, 1, 0, 0
+                // 13,14,15,16   | 96                  | 0, 0, 0, 1, 0
+                // 17,...,21     | 128                 | 0, 0, 0, 0, 1
+                //
+                // as the chunk_is_valid_cells are 1s followed by 0s, the flag of round r is
+                //   chunk_is_valid[start(r) - 1] - chunk_is_valid[start(r+1) - 1]
+                // where start(r) is the least number of valid snarks that ends in round r
+                let num_data_hash_rounds = get_data_hash_keccak_updates(N_SNARKS);
+                let data_hash_flags = (0..num_data_hash_rounds)
+                    .map(|round| -> Result<_, halo2_proofs::plonk::Error> {
+                        let start = &chunk_is_valid_cells[get_data_hash_round_start(round) - 1];
+                        let next_start = get_data_hash_round_start(round + 1);
+                        if next_start > N_SNARKS {
+                            Ok(start.clone())
+                        } else {
+                            rlc_config.sub(
+                                &mut region,
+                                start,
+                                &chunk_is_valid_cells[next_start - 1],
+                                &mut offset,
+                            )
+                        }
+                    })
+                    .collect::<Result<Vec<_>, halo2_proofs::plonk::Error>>()?;
 
                 log::trace!(
-                    "flags: {:?} {:?} {:?} {:?}",
-                    flag1.value(),
-                    flag2.value(),
-                    flag3.value(),
-                    flag4.value()
+                    "flags: {:?}",
+                    data_hash_flags
+                        .iter()
+                        .map(|flag| flag.value())
+                        .collect::<Vec<_>>()
                 );
                 // ====================================================
                 // parse the hashes
@@ -625,25 +588,20 @@ pub(crate) fn conditional_constraints(
                     batch_pi_hash_preimage,
                     chunk_pi_hash_preimages,
                     potential_batch_data_hash_preimage,
-                ) = parse_hash_preimage_cells(hash_input_cells);
+                ) = parse_hash_preimage_cells::<N_SNARKS>(hash_input_cells);
 
                 // digests
                 let (
                     _batch_pi_hash_digest,
                     _chunk_pi_hash_digests,
                     potential_batch_data_hash_digest,
-                ) = parse_hash_digest_cells(hash_output_cells);
+                ) = parse_hash_digest_cells::<N_SNARKS>(hash_output_cells);
                 // ====================================================
                 // start the actual statements
                 // ====================================================
                 //
                 // 1 batch_data_hash digest is reused for public input hash
                 //
-                // the following part of the code is hard coded for the case where
-                //   MAX_AGG_SNARKS <= 10
-                // in theory it may support up to 12 SNARKS (not tested)
-                // more SNARKs beyond 12 will require a revamp of the circuit
-                //
                 // public input hash is build as
                 //  keccak(
                 //      chain_id ||
@@ -654,56 +612,30 @@ pub(crate) fn conditional_constraints(
                 //
                 // batchDataHash = keccak(chunk[0].dataHash || ... || chunk[k-1].dataHash)
                 //
-                //
-                // #valid snarks | offset of data hash | flags
-                // 1,2,3,4       | 0                   | 1, 0, 0, 0
-                // 5,6,7,8       | 32                  | 0, 1, 0, 0
-                // 9,10,11,12    | 64                  | 0, 0, 1, 0
-                // 13,14,15,16   | 96                  | 0, 0, 0, 1
+                // the digest of the data hash is selected by the flags built above
                 for i in 0..4 {
                     for j in 0..8 {
+                        let candidates = (0..num_data_hash_rounds)
+                            .map(|round| {
+                                potential_batch_data_hash_digest
+                                    [(3 - i) * 8 + j + round * DIGEST_LEN]
+                                    .clone()
+                            })
+                            .collect::<Vec<_>>();
                         // sanity check
                         assert_exist(
                             &batch_pi_hash_preimage[i * 8 + j + CHUNK_DATA_HASH_INDEX],
-                            &[
-                                potential_batch_data_hash_digest[(3 - i) * 8 + j].clone(),
-                                potential_batch_data_hash_digest[(3 - i) * 8 + j + 32].clone(),
-                                potential_batch_data_hash_digest[(3 - i) * 8 + j + 64].clone(),
-                                potential_batch_data_hash_digest[(3 - i) * 8 + j + 96].clone(),
-                            ],
+                            &candidates,
                         )?;
                         // assert
                         // batch_pi_hash_preimage[i * 8 + j + CHUNK_DATA_HASH_INDEX]
-                        // = flag1 * potential_batch_data_hash_digest[(3 - i) * 8 + j]
-                        // + flag2 * potential_batch_data_hash_digest[(3 - i) * 8 + j + 32]
-                        // + flag3 * potential_batch_data_hash_digest[(3 - i) * 8 + j + 64]
-                        // + flag4 * potential_batch_data_hash_digest[(3 - i) * 8 + j + 96]
-
-                        let rhs = rlc_config.mul(
-                            &mut region,
-                            &flag1,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j],
-                            &mut offset,
-                        )?;
-                        let rhs = rlc_config.mul_add(
-                            &mut region,
-                            &flag2,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j + 32],
-                            &rhs,
-                            &mut offset,
-                        )?;
-                        let rhs = rlc_config.mul_add(
-                            &mut region,
-                            &flag3,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j + 64],
-                            &rhs,
-                            &mut offset,
-                        )?;
-                        let rhs = rlc_config.mul_add(
+                        // = sum_r flag_r * potential_batch_data_hash_digest[(3 - i) * 8 + j + 32 *
+                        // r]
+                        let rhs = select_by_flags(
+                            rlc_config,
                             &mut region,
-                            &flag4,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j + 96],
-                            &rhs,
+                            &data_hash_flags,
+                            &candidates,
                             &mut offset,
                         )?;
 
@@ -726,7 +658,7 @@ pub(crate) fn conditional_constraints(
                 //        chunk[i].postStateRoot ||
                 //        chunk[i].withdrawRoot  ||
                 //        chunk[i].datahash)
-                for i in 0..MAX_AGG_SNARKS {
+                for i in 0..N_SNARKS {
                     for j in 0..DIGEST_LEN {
                         assert_conditional_equal(
                             &chunk_pi_hash_preimages[i][j + CHUNK_DATA_HASH_INDEX],
@@ -751,7 +683,7 @@ pub(crate) fn conditional_constraints(
                 }
 
                 // 4  __valid__ chunks are continuous: they are linked via the state roots
-                for i in 0..MAX_AGG_SNARKS - 1 {
+                for i in 0..N_SNARKS - 1 {
                     for j in 0..DIGEST_LEN {
                         // sanity check
                         assert_conditional_equal(
@@ -783,9 +715,9 @@ pub(crate) fn conditional_constraints(
                     .map(|chunk_is_valid| rlc_config.not(&mut region, chunk_is_valid, &mut offset))
                     .collect::<Result<Vec<_>, halo2_proofs::plonk::Error>>()?;
 
-                let chunk_pi_hash_rlc_cells = parse_pi_hash_rlc_cells(data_rlc_cells);
+                let chunk_pi_hash_rlc_cells = parse_pi_hash_rlc_cells::<N_SNARKS>(data_rlc_cells);
 
-                for i in 1..MAX_AGG_SNARKS {
+                for i in 1..N_SNARKS {
                     rlc_config.conditional_enforce_equal(
                         &mut region,
                         chunk_pi_hash_rlc_cells[i - 1],
@@ -805,14 +737,14 @@ pub(crate) fn conditional_constraints(
                 }
 
                 // 7. the hash input length are correct
-                // - first MAX_AGG_SNARKS + 1 hashes all have 136 bytes input
+                // - first N_SNARKS + 1 hashes all have 136 bytes input
                 // - batch's data_hash length is 32 * number_of_valid_snarks
 
-                // - first MAX_AGG_SNARKS + 1 hashes all have 136 bytes input
+                // - first N_SNARKS + 1 hashes all have 136 bytes input
                 hash_input_len_cells
                     .iter()
                     .skip(1)
-                    .take((MAX_AGG_SNARKS + 1) * 2)
+                    .take((N_SNARKS + 1) * 2)
                     .chunks(2)
                     .into_iter()
                     .try_for_each(|chunk| {
@@ -831,60 +763,25 @@ pub(crate) fn conditional_constraints(
                 let data_hash_inputs_len =
                     rlc_config.mul(&mut region, &num_valid_snarks, &const32, &mut offset)?;
 
+                // the data hash of the batch is the last keccak, after batch_pi_hash and all
+                // the chunk_pi_hashes, each of which takes 2 rounds
+                let data_hash_round_index = N_SNARKS * 2 + 3;
+                let data_hash_len_candidates = &hash_input_len_cells
+                    [data_hash_round_index..data_hash_round_index + num_data_hash_rounds];
+
                 // sanity check
-                assert_exist(
-                    &data_hash_inputs_len,
-                    &[
-                        hash_input_len_cells[MAX_AGG_SNARKS * 2 + 3].clone(),
-                        hash_input_len_cells[MAX_AGG_SNARKS * 2 + 4].clone(),
-                        hash_input_len_cells[MAX_AGG_SNARKS * 2 + 5].clone(),
-                        hash_input_len_cells[MAX_AGG_SNARKS * 2 + 6].clone(),
-                    ],
-                )?;
+                assert_exist(&data_hash_inputs_len, data_hash_len_candidates)?;
 
                 log::trace!("data_hash_inputs: {:?}", data_hash_inputs_len.value());
-                log::trace!(
-                    "candidate 1: {:?}",
-                    hash_input_len_cells[MAX_AGG_SNARKS * 2 + 3].value()
-                );
-                log::trace!(
-                    "candidate 2: {:?}",
-                    hash_input_len_cells[MAX_AGG_SNARKS * 2 + 4].value()
-                );
-                log::trace!(
-                    "candidate 3: {:?}",
-                    hash_input_len_cells[MAX_AGG_SNARKS * 2 + 5].value()
-                );
-                log::trace!(
-                    "candidate 4: {:?}",
-                    hash_input_len_cells[MAX_AGG_SNARKS * 2 + 6].value()
-                );
+                for (i, candidate) in data_hash_len_candidates.iter().enumerate() {
+                    log::trace!("candidate {}: {:?}", i + 1, candidate.value());
+                }
 
-                let mut data_hash_inputs_len_rec = rlc_config.mul(
+                let data_hash_inputs_len_rec = select_by_flags(
+                    rlc_config,
                     &mut region,
-                    &hash_input_len_cells[MAX_AGG_SNARKS * 2 + 3],
-                    &flag1,
-                    &mut offset,
-                )?;
-                data_hash_inputs_len_rec = rlc_config.mul_add(
-                    &mut region,
-                    &hash_input_len_cells[MAX_AGG_SNARKS * 2 + 4],
-                    &flag2,
-                    &data_hash_inputs_len_rec,
-                    &mut offset,
-                )?;
-                data_hash_inputs_len_rec = rlc_config.mul_add(
-                    &mut region,
-                    &hash_input_len_cells[MAX_AGG_SNARKS * 2 + 5],
-                    &flag3,
-                    &data_hash_inputs_len_rec,
-                    &mut offset,
-                )?;
-                data_hash_inputs_len_rec = rlc_config.mul_add(
-                    &mut region,
-                    &hash_input_len_cells[MAX_AGG_SNARKS * 2 + 6],
-                    &flag4,
-                    &data_hash_inputs_len_rec,
+                    &data_hash_flags,
+                    data_hash_len_candidates,
                     &mut offset,
                 )?;
 
@@ -917,64 +814,29 @@ pub(crate) fn conditional_constraints(
 
                 let rlc_cell = rlc_config.rlc_with_flag(
                     &mut region,
-                    potential_batch_data_hash_preimage[..DIGEST_LEN * MAX_AGG_SNARKS].as_ref(),
+                    potential_batch_data_hash_preimage[..DIGEST_LEN * N_SNARKS].as_ref(),
                     &challenge_cell,
                     &flags,
                     &mut offset,
                 )?;
 
-                assert_exist(
-                    &rlc_cell,
-                    &[
-                        data_rlc_cells[MAX_AGG_SNARKS * 2 + 3].clone(),
-                        data_rlc_cells[MAX_AGG_SNARKS * 2 + 4].clone(),
-                        data_rlc_cells[MAX_AGG_SNARKS * 2 + 5].clone(),
-                        data_rlc_cells[MAX_AGG_SNARKS * 2 + 6].clone(),
-                    ],
-                )?;
+                let data_rlc_candidates = &data_rlc_cells
+                    [data_hash_round_index..data_hash_round_index + num_data_hash_rounds];
+                assert_exist(&rlc_cell, data_rlc_candidates)?;
                 log::trace!("rlc from chip {:?}", rlc_cell.value());
-                log::trace!(
-                    "rlc from table {:?}",
-                    data_rlc_cells[MAX_AGG_SNARKS * 2 + 3].value()
-                );
-                log::trace!(
-                    "rlc from table {:?}",
-                    data_rlc_cells[MAX_AGG_SNARKS * 2 + 4].value()
-                );
-                log::trace!(
-                    "rlc from table {:?}",
-                    data_rlc_cells[MAX_AGG_SNARKS * 2 + 5].value()
-                );
+                for candidate in data_rlc_candidates.iter() {
+                    log::trace!("rlc from table {:?}", candidate.value());
+                }
 
                 // assertion
-                let t1 = rlc_config.sub(
-                    &mut region,
-                    &rlc_cell,
-                    &data_rlc_cells[MAX_AGG_SNARKS * 2 + 3],
-                    &mut offset,
-                )?;
-                let t2 = rlc_config.sub(
-                    &mut region,
-                    &rlc_cell,
-                    &data_rlc_cells[MAX_AGG_SNARKS * 2 + 4],
-                    &mut offset,
-                )?;
-                let t3 = rlc_config.sub(
-                    &mut region,
-                    &rlc_cell,
-                    &data_rlc_cells[MAX_AGG_SNARKS * 2 + 5],
-                    &mut offset,
-                )?;
-                let t4 = rlc_config.sub(
-                    &mut region,
-                    &rlc_cell,
-                    &data_rlc_cells[MAX_AGG_SNARKS * 2 + 6],
-                    &mut offset,
-                )?;
-                let t1t2 = rlc_config.mul(&mut region, &t1, &t2, &mut offset)?;
-                let t1t2t3 = rlc_config.mul(&mut region, &t1t2, &t3, &mut offset)?;
-                let t1t2t3t4 = rlc_config.mul(&mut region, &t1t2t3, &t4, &mut offset)?;
-                rlc_config.enforce_zero(&mut region, &t1t2t3t4)?;
+                // (rlc - data_rlc[0]) * ... * (rlc - data_rlc[num_data_hash_rounds - 1]) == 0
+                let mut product =
+                    rlc_config.sub(&mut region, &rlc_cell, &data_rlc_candidates[0], &mut offset)?;
+                for candidate in data_rlc_candidates.iter().skip(1) {
+                    let diff = rlc_config.sub(&mut region, &rlc_cell, candidate, &mut offset)?;
+                    product = rlc_config.mul(&mut region, &product, &diff, &mut offset)?;
+                }
+                rlc_config.enforce_zero(&mut region, &product)?;
 
                 // 9. is_final_cells are set correctly
                 // the is_final_cells are set as
@@ -988,26 +850,26 @@ pub(crate) fn conditional_constraints(
                 // 5                         | 0     | third keccak:
                 // 6                         | 1     |   chunk[1].pi_hash use 2 rounds
                 // ...
-                // 2*(MAX_AGG_SNARKS) + 1    | 0     | MAX_AGG_SNARKS+1's keccak
-                // 2*(MAX_AGG_SNARKS) + 2    | 1     |   chunk[MAX_AGG_SNARKS].pi_hash use 2 rounds
-                // 2*(MAX_AGG_SNARKS) + 3    | a     | MAX_AGG_SNARKS+2's keccak
-                // 2*(MAX_AGG_SNARKS) + 4    | b     |   batch_data_hash may use 1, 2, 3
-                // 2*(MAX_AGG_SNARKS) + 5    | c     |   or 4 rounds
-                // 2*(MAX_AGG_SNARKS) + 6    | d     |
+                // 2*(N_SNARKS) + 1          | 0     | N_SNARKS+1's keccak
+                // 2*(N_SNARKS) + 2          | 1     |   chunk[N_SNARKS].pi_hash use 2 rounds
+                // 2*(N_SNARKS) + 3          | a_0   | N_SNARKS+2's keccak
+                // 2*(N_SNARKS) + 4          | a_1   |   batch_data_hash may use 1, 2, ...
+                // ...                       | ...   |   or num_data_hash_rounds rounds
                 //
-                // so a,b,c are constrained as follows
+                // so a_0, a_1, ... are constrained as follows
                 //
-                // #valid snarks | flags        | a | b | c | d
-                // 1,2,3,4       | 1, 0, 0, 0   | 1 | - | - | -
-                // 5,6,7,8       | 0, 1, 0, 0   | 0 | 1 | - | -
-                // 9,10,11,12    | 0, 0, 1, 0   | 0 | 0 | 1 | -
-                // 13,14,15,16   | 0, 0, 0, 1   | 0 | 0 | 0 | 1
-
-                // first MAX_AGG_SNARKS + 1 keccak
+                // #valid snarks | flags          | a_0 | a_1 | a_2 | a_3 | a_4
+                // 1,2,3,4       | 1, 0, 0, 0, 0  | 1   | -   | -   | -   | -
+                // 5,6,7,8       | 0, 1, 0, 0, 0  | 0   | 1   | -   | -   | -
+                // 9,10,11,12    | 0, 0, 1, 0, 0  | 0   | 0   | 1   | -   | -
+                // 13,14,15,16   | 0, 0, 0, 1, 0  | 0   | 0   | 0   | 1   | -
+                // 17,...,21     | 0, 0, 0, 0, 1  | 0   | 0   | 0   | 0   | 1
+
+                // first N_SNARKS + 1 keccak
                 for mut chunk in is_final_cells
                     .iter()
                     .skip(1)
-                    .take((MAX_AGG_SNARKS + 1) * 2)
+                    .take((N_SNARKS + 1) * 2)
                     .into_iter()
                     .chunks(2)
                     .into_iter()
@@ -1025,15 +887,15 @@ pub(crate) fn conditional_constraints(
                     )?;
                 }
                 // last keccak
-                // we constrain a * flag1 + b * flag2 + c * flag3 + d * flag4 == 1
-                let a = &is_final_cells[2 * (MAX_AGG_SNARKS) + 3];
-                let b = &is_final_cells[2 * (MAX_AGG_SNARKS) + 4];
-                let c = &is_final_cells[2 * (MAX_AGG_SNARKS) + 5];
-                let d = &is_final_cells[2 * (MAX_AGG_SNARKS) + 6];
-                let mut left = rlc_config.mul(&mut region, a, &flag1, &mut offset)?;
-                left = rlc_config.mul_add(&mut region, b, &flag2, &left, &mut offset)?;
-                left = rlc_config.mul_add(&mut region, c, &flag3, &left, &mut offset)?;
-                left = rlc_config.mul_add(&mut region, d, &flag4, &left, &mut offset)?;
+                // we constrain a_0 * flag_0 + a_1 * flag_1 + ... == 1
+                let left = select_by_flags(
+                    rlc_config,
+                    &mut region,
+                    &data_hash_flags,
+                    &is_final_cells
+                        [data_hash_round_index..data_hash_round_index + num_data_hash_rounds],
+                    &mut offset,
+                )?;
                 region
                     .constrain_equal(left.cell(), rlc_config.one_cell(left.cell().region_index))?;
 
@@ -1045,6 +907,27 @@ pub(crate) fn conditional_constraints(
     Ok(())
 }
 
+/// Input a list of binary flags, at most one of which is set, and the same number of
+/// candidates.
+///
+/// Return a cell for the candidate selected by the flags, i.e., sum_i flags\[i\] * candidates\[i\]
+fn select_by_flags(
+    rlc_config: &RlcConfig,
+    region: &mut Region<Fr>,
+    flags: &[AssignedCell<Fr, Fr>],
+    candidates: &[AssignedCell<Fr, Fr>],
+    offset: &mut usize,
+) -> Result<AssignedCell<Fr, Fr>, halo2_proofs::plonk::Error> {
+    assert!(!flags.is_empty());
+    assert_eq!(flags.len(), candidates.len());
+
+    let mut res = rlc_config.mul(region, &flags[0], &candidates[0], offset)?;
+    for (flag, candidate) in flags.iter().zip(candidates.iter()).skip(1) {
+        res = rlc_config.mul_add(region, flag, candidate, &res, offset)?;
+    }
+    Ok(res)
+}
+
 /// Input a list of flags whether the snark is valid
 ///
 /// Assert the following relations on the flags:
@@ -0,0 +1,86 @@
+//! Error module for the aggregator crate
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use eth_types::H256;
+use std::error::Error as StdError;
+
+/// Error type for invalid batches and failures when aggregating their chunks.
+#[derive(Debug)]
+pub enum BatchError {
+    /// The number of chunks (or snarks) does not match the batch size.
+    InvalidNumberOfChunks {
+        /// expected number of chunks
+        expected: usize,
+        /// actual number of chunks
+        actual: usize,
+    },
+    /// The batch does not contain any real chunk.
+    NoRealChunk,
+    /// The number of batches (or snarks) does not fit in the bundle size.
+    InvalidNumberOfBatches {
+        /// max number of batches
+        expected: usize,
+        /// actual number of batches
+        actual: usize,
+    },
+    /// The bundle does not contain any batch.
+    NoRealBatch,
+    /// A real chunk follows a padding chunk.
+    RealChunkAfterPadding(usize),
+    /// The chain id of the chunk differs from the one of the previous chunk.
+    ChainIdMismatch {
+        /// index of the chunk
+        index: usize,
+        /// chain id of the previous chunk
+        expected: u64,
+        /// chain id of the chunk
+        actual: u64,
+    },
+    /// The padding chunk is not a copy of the previous chunk.
+    InvalidPaddingChunk(usize),
+    /// The prev state root of the chunk is not the post state root of the
+    /// previous chunk.
+    StateRootMismatch {
+        /// index of the chunk
+        index: usize,
+        /// post state root of the previous chunk
+        expected: H256,
+        /// prev state root of the chunk
+        actual: H256,
+    },
+    /// The number of txs of a block does not fit in the chunk data.
+    TooManyTxs {
+        /// block number
+        block_number: u64,
+        /// number of txs (and popped l1 messages) of the block
+        num_txs: u64,
+    },
+    /// The queue index of a l1 message is below the start queue index of the
+    /// block.
+    InvalidL1QueueIndex {
+        /// block number
+        block_number: u64,
+        /// max queue index of the l1 messages in the block
+        queue_index: u64,
+        /// number of l1 messages popped before the block
+        total_l1_popped: u64,
+    },
+    /// The public input of the snark does not match the chunk hash.
+    SnarkInstanceMismatch(usize),
+    /// Error from snark verifier.
+    SnarkVerifier(snark_verifier::Error),
+}
+
+impl From<snark_verifier::Error> for BatchError {
+    fn from(err: snark_verifier::Error) -> Self {
+        BatchError::SnarkVerifier(err)
+    }
+}
+
+impl Display for BatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{self:?}")
+    }
+}
+
+impl StdError for BatchError {}
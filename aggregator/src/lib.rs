@@ -12,6 +12,8 @@ mod compression;
 mod constants;
 /// Core module for circuit assignment
 mod core;
+/// Errors of batch construction and aggregation
+mod error;
 /// Parameters for compression circuit
 mod param;
 /// utilities
@@ -25,6 +27,7 @@ pub use aggregation::*;
 pub use batch::BatchHash;
 pub use chunk::ChunkHash;
 pub use compression::*;
-pub use constants::MAX_AGG_SNARKS;
 pub(crate) use constants::*;
+pub use constants::{MAX_AGG_BATCHES, MAX_AGG_SNARKS};
+pub use error::BatchError;
 pub use param::*;
@@ -1,14 +1,24 @@
 use std::{fs, path::Path, process};
 
 use ark_std::{end_timer, start_timer, test_rng};
-use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr, poly::commitment::Params};
+use halo2_proofs::{
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
 use itertools::Itertools;
+use rand::Rng;
 use snark_verifier::loader::halo2::halo2_ecc::halo2_base::utils::fs::gen_srs;
 use snark_verifier_sdk::{gen_pk, gen_snark_shplonk, verify_snark_shplonk, CircuitExt};
 
 use crate::{
-    aggregation::AggregationCircuit, batch::BatchHash, constants::MAX_AGG_SNARKS, layer_0,
-    tests::mock_chunk::MockChunkCircuit, ChunkHash,
+    aggregation::AggregationCircuit,
+    batch::BatchHash,
+    constants::{MAX_AGG_BATCHES, MAX_AGG_SNARKS},
+    error::BatchError,
+    layer_0,
+    tests::mock_chunk::MockChunkCircuit,
+    ChunkHash,
 };
 
 #[test]
@@ -18,12 +28,49 @@ fn test_aggregation_circuit() {
     let k = 20;
 
     // This set up requires one round of keccak for chunk's data hash
-    let circuit = build_new_aggregation_circuit(2);
+    let circuit = build_new_aggregation_circuit::<MAX_AGG_SNARKS>(2);
+    let instance = circuit.instances();
+    let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+    mock_prover.assert_satisfied_par();
+}
+
+#[test]
+fn test_aggregation_circuit_small_batch() {
+    let k = 20;
+
+    // A batch of 4 chunks, 3 of which are real ones
+    let circuit = build_new_aggregation_circuit::<4>(3);
     let instance = circuit.instances();
     let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
     mock_prover.assert_satisfied_par();
 }
 
+#[test]
+fn test_bundle_aggregation_circuit() {
+    let k = 20;
+
+    // A bundle of MAX_AGG_BATCHES batches, 2 of which are real ones
+    let circuit = build_new_bundle_circuit(2);
+    let instance = circuit.instances();
+    let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+    mock_prover.assert_satisfied_par();
+}
+
+#[ignore = "it takes too much time"]
+#[test]
+fn test_aggregation_circuit_large_batch() {
+    let k = 21;
+
+    // A batch of 32 chunks, whose data hash is finalized in the 5th (17 and 21 real chunks),
+    // 6th (22 real chunks) or 8th (32 real chunks) keccak round
+    for num_real_chunks in [17, 21, 22, 32] {
+        let circuit = build_new_aggregation_circuit::<32>(num_real_chunks);
+        let instance = circuit.instances();
+        let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+        mock_prover.assert_satisfied_par();
+    }
+}
+
 #[ignore = "it takes too much time"]
 #[test]
 fn test_aggregation_circuit_all_possible_num_snarks() {
@@ -34,7 +81,7 @@ fn test_aggregation_circuit_all_possible_num_snarks() {
     for i in 1..=MAX_AGG_SNARKS {
         println!("{i} real chunks and {} padded chunks", MAX_AGG_SNARKS - i);
         // This set up requires one round of keccak for chunk's data hash
-        let circuit = build_new_aggregation_circuit(i);
+        let circuit = build_new_aggregation_circuit::<MAX_AGG_SNARKS>(i);
         let instance = circuit.instances();
         let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
         mock_prover.assert_satisfied_par();
@@ -54,7 +101,7 @@ fn test_aggregation_circuit_full() {
     fs::create_dir(path).unwrap();
 
     // This set up requires one round of keccak for chunk's data hash
-    let circuit = build_new_aggregation_circuit(2);
+    let circuit = build_new_aggregation_circuit::<MAX_AGG_SNARKS>(2);
     let instance = circuit.instances();
     let mock_prover = MockProver::<Fr>::run(25, &circuit, instance).unwrap();
     mock_prover.assert_satisfied_par();
@@ -90,7 +137,120 @@ fn test_aggregation_circuit_full() {
     log::trace!("finished verification for circuit");
 }
 
-fn build_new_aggregation_circuit(num_real_chunks: usize) -> AggregationCircuit {
+#[test]
+fn test_batch_hash_construct_errors() {
+    let mut rng = test_rng();
+    let num_real_chunks = 2;
+
+    let mut chunks = (0..num_real_chunks)
+        .map(|_| ChunkHash::mock_random_chunk_hash_for_testing(&mut rng))
+        .collect_vec();
+    chunks[1].prev_state_root = chunks[0].post_state_root;
+    let padded_chunk = ChunkHash::mock_padded_chunk_hash_for_testing(&chunks[1]);
+    let chunks_with_padding =
+        [chunks, vec![padded_chunk; MAX_AGG_SNARKS - num_real_chunks]].concat();
+    assert!(BatchHash::<MAX_AGG_SNARKS>::construct(&chunks_with_padding).is_ok());
+    assert!(BatchHash::<4>::construct(&chunks_with_padding[..4]).is_ok());
+
+    assert!(matches!(
+        BatchHash::<MAX_AGG_SNARKS>::construct(&chunks_with_padding[1..]),
+        Err(BatchError::InvalidNumberOfChunks { .. })
+    ));
+    assert!(matches!(
+        BatchHash::<4>::construct(&chunks_with_padding),
+        Err(BatchError::InvalidNumberOfChunks { expected: 4, .. })
+    ));
+
+    let mut chunks = chunks_with_padding.clone();
+    chunks[1].prev_state_root = chunks[1].post_state_root;
+    assert!(matches!(
+        BatchHash::<MAX_AGG_SNARKS>::construct(&chunks),
+        Err(BatchError::StateRootMismatch { index: 1, .. })
+    ));
+
+    let mut chunks = chunks_with_padding.clone();
+    chunks[1].chain_id += 1;
+    assert!(matches!(
+        BatchHash::<MAX_AGG_SNARKS>::construct(&chunks),
+        Err(BatchError::ChainIdMismatch { index: 1, .. })
+    ));
+
+    let mut chunks = chunks_with_padding.clone();
+    chunks[MAX_AGG_SNARKS - 1].data_hash = chunks[0].data_hash;
+    assert!(matches!(
+        BatchHash::<MAX_AGG_SNARKS>::construct(&chunks),
+        Err(BatchError::InvalidPaddingChunk(_))
+    ));
+
+    let mut chunks = chunks_with_padding;
+    chunks[0].is_padding = true;
+    assert!(matches!(
+        BatchHash::<MAX_AGG_SNARKS>::construct(&chunks),
+        Err(BatchError::NoRealChunk)
+    ));
+}
+
+#[test]
+fn test_bundle_hash_construct() {
+    let mut rng = test_rng();
+    let num_real_batches = 2;
+
+    // each batch consists of 2 real chunks
+    let mut chunks = (0..num_real_batches * 2)
+        .map(|_| ChunkHash::mock_random_chunk_hash_for_testing(&mut rng))
+        .collect_vec();
+    for i in 0..chunks.len() - 1 {
+        chunks[i + 1].prev_state_root = chunks[i].post_state_root;
+    }
+    let batches = chunks
+        .chunks(2)
+        .map(|chunks| {
+            let padded_chunk = ChunkHash::mock_padded_chunk_hash_for_testing(&chunks[1]);
+            let chunks_with_padding =
+                [chunks.to_vec(), vec![padded_chunk; MAX_AGG_SNARKS - 2]].concat();
+            BatchHash::<MAX_AGG_SNARKS>::construct(&chunks_with_padding).unwrap()
+        })
+        .collect_vec();
+
+    // a batch has the same public input hash as its chunk in the bundle
+    for batch in batches.iter() {
+        assert_eq!(
+            batch.to_chunk_hash().public_input_hash(),
+            batch.public_input_hash
+        );
+    }
+
+    let batch_chunks = batches.iter().map(BatchHash::to_chunk_hash).collect_vec();
+    let padded_batch_chunk =
+        ChunkHash::mock_padded_chunk_hash_for_testing(&batch_chunks[num_real_batches - 1]);
+    let batch_chunks_with_padding = [
+        batch_chunks.clone(),
+        vec![padded_batch_chunk; MAX_AGG_BATCHES - num_real_batches],
+    ]
+    .concat();
+    let bundle = BatchHash::<MAX_AGG_BATCHES>::construct(&batch_chunks_with_padding).unwrap();
+    assert_eq!(bundle.number_of_valid_chunks, num_real_batches);
+    assert_eq!(
+        bundle.to_chunk_hash().prev_state_root,
+        chunks[0].prev_state_root
+    );
+    assert_eq!(
+        bundle.to_chunk_hash().post_state_root,
+        chunks[chunks.len() - 1].post_state_root
+    );
+
+    // the batches of a bundle must be continuous
+    let mut batch_chunks = batch_chunks_with_padding;
+    batch_chunks.swap(0, 1);
+    assert!(matches!(
+        BatchHash::<MAX_AGG_BATCHES>::construct(&batch_chunks),
+        Err(BatchError::StateRootMismatch { index: 1, .. })
+    ));
+}
+
+fn build_new_aggregation_circuit<const N_SNARKS: usize>(
+    num_real_chunks: usize,
+) -> AggregationCircuit<N_SNARKS> {
     // inner circuit: Mock circuit
     let k0 = 8;
 
@@ -107,10 +267,58 @@ fn build_new_aggregation_circuit(num_real_chunks: usize) -> AggregationCircuit {
         ChunkHash::mock_padded_chunk_hash_for_testing(&chunks_without_padding[num_real_chunks - 1]);
     let chunks_with_padding = [
         chunks_without_padding,
-        vec![padded_chunk; MAX_AGG_SNARKS - num_real_chunks],
+        vec![padded_chunk; N_SNARKS - num_real_chunks],
     ]
     .concat();
 
+    aggregate_mock_snarks(k0, &params, chunks_with_padding, num_real_chunks, rng)
+}
+
+/// Build the bundle (layer-5) circuit aggregating the mock snarks of the batches (layer-4), each
+/// of which consists of 2 real chunks.
+fn build_new_bundle_circuit(num_real_batches: usize) -> AggregationCircuit<MAX_AGG_BATCHES> {
+    let k0 = 8;
+
+    let mut rng = test_rng();
+    let params = gen_srs(k0);
+
+    let mut chunks = (0..num_real_batches * 2)
+        .map(|_| ChunkHash::mock_random_chunk_hash_for_testing(&mut rng))
+        .collect_vec();
+    for i in 0..chunks.len() - 1 {
+        chunks[i + 1].prev_state_root = chunks[i].post_state_root;
+    }
+    let batches_without_padding = chunks
+        .chunks(2)
+        .map(|chunks| {
+            let padded_chunk = ChunkHash::mock_padded_chunk_hash_for_testing(&chunks[1]);
+            let chunks_with_padding =
+                [chunks.to_vec(), vec![padded_chunk; MAX_AGG_SNARKS - 2]].concat();
+            BatchHash::<MAX_AGG_SNARKS>::construct(&chunks_with_padding)
+                .unwrap()
+                .to_chunk_hash()
+        })
+        .collect_vec();
+    let padded_batch = ChunkHash::mock_padded_chunk_hash_for_testing(
+        &batches_without_padding[num_real_batches - 1],
+    );
+    let batches_with_padding = [
+        batches_without_padding,
+        vec![padded_batch; MAX_AGG_BATCHES - num_real_batches],
+    ]
+    .concat();
+
+    aggregate_mock_snarks(k0, &params, batches_with_padding, num_real_batches, rng)
+}
+
+/// Aggregate the mock snarks of the chunks, where the padded ones reuse the last real snark.
+fn aggregate_mock_snarks<const N_SNARKS: usize>(
+    k0: u32,
+    params: &ParamsKZG<Bn256>,
+    chunks_with_padding: Vec<ChunkHash>,
+    num_real_chunks: usize,
+    rng: impl Rng + Send,
+) -> AggregationCircuit<N_SNARKS> {
     // ==========================
     // real chunks
     // ==========================
@@ -129,16 +337,15 @@ fn build_new_aggregation_circuit(num_real_chunks: usize) -> AggregationCircuit {
     // ==========================
     // padded chunks
     // ==========================
-    let padded_snarks =
-        { vec![real_snarks.last().unwrap().clone(); MAX_AGG_SNARKS - num_real_chunks] };
+    let padded_snarks = { vec![real_snarks.last().unwrap().clone(); N_SNARKS - num_real_chunks] };
 
     // ==========================
     // batch
     // ==========================
-    let batch_hash = BatchHash::construct(&chunks_with_padding);
+    let batch_hash = BatchHash::construct(&chunks_with_padding).unwrap();
 
     AggregationCircuit::new(
-        &params,
+        params,
         [real_snarks, padded_snarks].concat().as_ref(),
         rng,
         batch_hash,
@@ -147,29 +147,6 @@ impl Circuit<Fr> for ArithTestCircuit {
                         config.decomposition(&mut region, &tmp, 64, &mut offset)?;
                     }
                 }
-                // unit test: is smaller than
-                {
-                    for _ in 0..10 {
-                        let a = Fr::from(rng.next_u32() as u64);
-                        let b = Fr::from(rng.next_u32() as u64);
-                        let c = if a < b { Fr::one() } else { Fr::zero() };
-                        let a = config.load_private(&mut region, &a, &mut offset)?;
-                        let b = config.load_private(&mut region, &b, &mut offset)?;
-                        let c = config.load_private(&mut region, &c, &mut offset)?;
-                        let c_rec = config.is_smaller_than(&mut region, &a, &b, &mut offset)?;
-                        region.constrain_equal(c.cell(), c_rec.cell())?;
-                    }
-
-                    // equality check
-                    let a = Fr::from(rng.next_u32() as u64);
-                    let b = a;
-                    let c = Fr::zero();
-                    let a = config.load_private(&mut region, &a, &mut offset)?;
-                    let b = config.load_private(&mut region, &b, &mut offset)?;
-                    let c = config.load_private(&mut region, &c, &mut offset)?;
-                    let c_rec = config.is_smaller_than(&mut region, &a, &b, &mut offset)?;
-                    region.constrain_equal(c.cell(), c_rec.cell())?;
-                }
                 // unit test: is zero
                 {
                     let should_be_false = config.is_zero(&mut region, &f1, &mut offset)?;
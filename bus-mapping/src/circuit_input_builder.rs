@@ -18,12 +18,13 @@ use crate::{
     error::Error,
     evm::opcodes::{gen_associated_ops, gen_associated_steps},
     operation::{self, CallContextField, Operation, RWCounter, StartOp, StorageOp, RW},
-    precompile::is_precompiled,
+    precompile::{is_point_evaluation, is_precompiled, DEFAULT_MODEXP_SIZE_LIMIT},
     rpc::GethClient,
     state_db::{self, CodeDB, StateDB},
     util::{hash_code_keccak, KECCAK_CODE_HASH_EMPTY},
 };
 pub use access::{Access, AccessSet, AccessValue, CodeSource};
+use block::BlockSteps;
 pub use block::{Block, BlockContext};
 pub use call::{Call, CallContext, CallKind};
 use core::fmt::Debug;
@@ -32,13 +33,14 @@ use eth_types::{
     evm_types::{GasCost, OpcodeId},
     geth_types,
     sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
-    Address, GethExecStep, GethExecTrace, ToBigEndian, ToWord, Word, H256,
+    Address, GethExecStep, GethExecTrace, ToAddress, ToBigEndian, ToWord, Word, H256,
 };
 use ethers_providers::JsonRpcClient;
 pub use execution::{
-    BigModExp, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder, CopyStep, EcAddOp,
-    EcMulOp, EcPairingOp, EcPairingPair, ExecState, ExecStep, ExpEvent, ExpStep, NumberOrHash,
-    PrecompileEvent, PrecompileEvents, N_BYTES_PER_PAIR, N_PAIRING_PER_OP, SHA256,
+    BigModExp, Blake2F, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder, CopyStep,
+    EcAddOp, EcMulOp, EcPairingOp, EcPairingPair, ExecState, ExecStep, ExpEvent, ExpStep,
+    NumberOrHash, PrecompileEvent, PrecompileEvents, Ripemd160, N_BYTES_PER_PAIR, N_PAIRING_PER_OP,
+    SHA256,
 };
 use hex::decode_to_slice;
 
@@ -49,8 +51,9 @@ use itertools::Itertools;
 use log::warn;
 #[cfg(feature = "scroll")]
 use mpt_zktrie::state::ZktrieState;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     iter,
 };
 pub use transaction::{
@@ -58,7 +61,7 @@ pub use transaction::{
 };
 
 /// Setup parameters for ECC-related precompile calls.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PrecompileEcParams {
     /// Maximum number of EcAdd ops supported in one block.
     pub ec_add: usize,
@@ -79,7 +82,7 @@ impl Default for PrecompileEcParams {
 }
 
 /// Circuit Setup Parameters
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CircuitsParams {
     /// Maximum number of rw operations in the state circuit (RwTable length /
     /// number of rows). This must be at least the number of rw operations
@@ -124,6 +127,15 @@ pub struct CircuitsParams {
     /// then if there is 1 ecPairing in the input, we will return 500_000 as the "row usage"
     /// for the ec circuit.
     pub max_vertical_circuit_rows: usize,
+    /// Maximum byte length of the base, exponent and modulus of modexp, at most
+    /// `MAX_MODEXP_SIZE_LIMIT`. Calls with longer operands are invalid inputs, so the EVM
+    /// circuit must be configured with the same limit.
+    #[serde(default = "default_max_modexp_size")]
+    pub max_modexp_size: usize,
+}
+
+fn default_max_modexp_size() -> usize {
+    DEFAULT_MODEXP_SIZE_LIMIT
 }
 
 impl Default for CircuitsParams {
@@ -146,6 +158,7 @@ impl Default for CircuitsParams {
             max_vertical_circuit_rows: 0,
             max_rlp_rows: 1000,
             max_ec_ops: PrecompileEcParams::default(),
+            max_modexp_size: DEFAULT_MODEXP_SIZE_LIMIT,
         }
     }
 }
@@ -168,7 +181,7 @@ impl Default for CircuitsParams {
 /// [`OpcodeId`](crate::evm::OpcodeId)s used in each `ExecTrace` step so that
 /// the State Proof witnesses are already generated on a structured manner and
 /// ready to be added into the State circuit.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CircuitInputBuilder {
     /// StateDB key-value DB
     pub sdb: StateDB,
@@ -183,6 +196,31 @@ pub struct CircuitInputBuilder {
     pub mpt_init_state: Option<ZktrieState>,
 }
 
+/// Snapshot of a [`CircuitInputBuilder`], to roll back the block traces handled after it.
+/// Before the building is finalized, the witness of the block is only appended, so just the
+/// lengths of it are kept together with the state db, the code hashes, the block context and
+/// the block fields which are overwritten by each block trace.
+#[derive(Debug)]
+pub struct CircuitInputBuilderSnapshot {
+    sdb: StateDB,
+    code_hashes: HashSet<H256>,
+    rwc: RWCounter,
+    call_map: HashMap<usize, (usize, usize)>,
+    cumulative_gas_used: u64,
+    headers: BTreeMap<u64, BlockHead>,
+    withdraw_root: Word,
+    prev_withdraw_root: Word,
+    start_l1_queue_index: u64,
+    code: HashMap<H256, Vec<u8>>,
+    block_steps: BlockSteps,
+    num_txs: usize,
+    num_operations: [usize; 12],
+    num_copy_events: usize,
+    num_sha3_inputs: usize,
+    num_exp_events: usize,
+    num_precompile_events: usize,
+}
+
 impl<'a> CircuitInputBuilder {
     /// Create a new CircuitInputBuilder from the given `eth_block` and
     /// `constants`.
@@ -211,6 +249,56 @@ impl<'a> CircuitInputBuilder {
         Self::new(sdb, code_db, &Block::from_headers(headers, circuits_params))
     }
 
+    /// Take a snapshot of the builder, which must not be finalized before restoring it.
+    pub fn snapshot(&self) -> CircuitInputBuilderSnapshot {
+        CircuitInputBuilderSnapshot {
+            sdb: self.sdb.clone(),
+            code_hashes: self.code_db.0.keys().copied().collect(),
+            rwc: self.block_ctx.rwc,
+            call_map: self.block_ctx.call_map.clone(),
+            cumulative_gas_used: self.block_ctx.cumulative_gas_used,
+            headers: self.block.headers.clone(),
+            withdraw_root: self.block.withdraw_root,
+            prev_withdraw_root: self.block.prev_withdraw_root,
+            start_l1_queue_index: self.block.start_l1_queue_index,
+            code: self.block.code.clone(),
+            block_steps: self.block.block_steps.clone(),
+            num_txs: self.block.txs.len(),
+            num_operations: self.block.container.lens(),
+            num_copy_events: self.block.copy_events.len(),
+            num_sha3_inputs: self.block.sha3_inputs.len(),
+            num_exp_events: self.block.exp_events.len(),
+            num_precompile_events: self.block.precompile_events.events.len(),
+        }
+    }
+
+    /// Roll back the block traces handled after the snapshot was taken from this builder.
+    /// The zktrie state is kept, since it only gains the proof nodes of the same initial root.
+    pub fn restore(&mut self, snapshot: CircuitInputBuilderSnapshot) {
+        self.sdb = snapshot.sdb;
+        self.code_db
+            .0
+            .retain(|code_hash, _| snapshot.code_hashes.contains(code_hash));
+        self.block_ctx.rwc = snapshot.rwc;
+        self.block_ctx.call_map = snapshot.call_map;
+        self.block_ctx.cumulative_gas_used = snapshot.cumulative_gas_used;
+        self.block.headers = snapshot.headers;
+        self.block.withdraw_root = snapshot.withdraw_root;
+        self.block.prev_withdraw_root = snapshot.prev_withdraw_root;
+        self.block.start_l1_queue_index = snapshot.start_l1_queue_index;
+        self.block.code = snapshot.code;
+        self.block.block_steps = snapshot.block_steps;
+        self.block.txs.truncate(snapshot.num_txs);
+        self.block.container.truncate(snapshot.num_operations);
+        self.block.copy_events.truncate(snapshot.num_copy_events);
+        self.block.sha3_inputs.truncate(snapshot.num_sha3_inputs);
+        self.block.exp_events.truncate(snapshot.num_exp_events);
+        self.block
+            .precompile_events
+            .events
+            .truncate(snapshot.num_precompile_events);
+    }
+
     /// Obtain a mutable reference to the state that the `CircuitInputBuilder`
     /// maintains, contextualized to a particular transaction and a
     /// particular execution step in that transaction.
@@ -453,6 +541,10 @@ impl<'a> CircuitInputBuilder {
         );
         log::debug!("tx_receipt num: {}", self.block.container.tx_receipt.len());
         log::debug!("tx_log num: {}", self.block.container.tx_log.len());
+        log::debug!(
+            "transient_storage num: {}",
+            self.block.container.transient_storage.len()
+        );
         log::debug!("start num: {}", self.block.container.start.len());
     }
 
@@ -552,6 +644,14 @@ impl<'a> CircuitInputBuilder {
     ) -> Result<(), Error> {
         let mut tx = self.new_tx(eth_tx, !geth_trace.failed)?;
 
+        // The point evaluation precompile is treated as a plain account by bus-mapping and the
+        // circuits, which diverges from geth once it's active.
+        if self.block.fork_rules(tx.block_num).is_cancun {
+            if let Some(address) = point_evaluation_access(eth_tx, geth_trace) {
+                return Err(Error::UnsupportedPrecompile(address));
+            }
+        }
+
         // Sanity check for transaction L1 fee.
         let tx_l1_fee = if tx.tx_type.is_l1_msg() {
             0
@@ -669,7 +769,7 @@ impl<'a> CircuitInputBuilder {
                             "".to_string()
                         }
                     )
-                } else if matches!(geth_step.op, OpcodeId::SSTORE) {
+                } else if matches!(geth_step.op, OpcodeId::SSTORE | OpcodeId::TSTORE) {
                     format!(
                         "{:?} {:?} {:?}",
                         state_ref.call().map(|c| c.address),
@@ -813,7 +913,9 @@ pub fn get_dummy_tx_hash() -> H256 {
     H256(tx_hash)
 }
 
-fn keccak_inputs_pi_circuit(
+/// Generate the keccak inputs required by the PI Circuit from the block headers and the
+/// transactions.
+pub fn keccak_inputs_pi_circuit(
     chain_id: u64,
     start_l1_queue_index: u64,
     prev_state_root: Word,
@@ -878,10 +980,26 @@ fn keccak_inputs_pi_circuit(
         .chain(withdraw_trie_root.to_be_bytes())
         .chain(data_hash.to_fixed_bytes())
         .collect::<Vec<u8>>();
+    debug_assert_eq!(
+        keccak_inputs_pi_circuit_lens(block_headers.len(), transactions.len()),
+        [data_bytes.len(), pi_bytes.len()]
+    );
 
     vec![data_bytes, pi_bytes]
 }
 
+/// Lengths of the keccak inputs required by the PI Circuit, i.e. the data bytes of the block
+/// headers and tx hashes, and the PI bytes, which are computed without hashing the txs.
+pub fn keccak_inputs_pi_circuit_lens(num_blocks: usize, num_txs: usize) -> [usize; 2] {
+    // number, timestamp, base fee, gas limit and number of txs of each block
+    let block_values_len = 8 + 8 + 32 + 8 + 2;
+    let data_bytes_len = num_blocks * block_values_len + num_txs * 32;
+    // chain id, state roots, withdraw root and data hash
+    let pi_bytes_len = 8 + 4 * 32;
+
+    [data_bytes_len, pi_bytes_len]
+}
+
 /// Generate the keccak inputs required by the Tx Circuit from the transactions.
 pub fn keccak_inputs_tx_circuit(txs: &[geth_types::Transaction]) -> Result<Vec<Vec<u8>>, Error> {
     let mut inputs = Vec::new();
@@ -964,6 +1082,33 @@ pub struct BuilderClient<P: JsonRpcClient> {
     circuits_params: CircuitsParams,
 }
 
+/// Return the address of the point evaluation precompile if the tx is sent to it, or calls or
+/// accesses it in any step.
+fn point_evaluation_access(
+    eth_tx: &eth_types::Transaction,
+    geth_trace: &GethExecTrace,
+) -> Option<Address> {
+    let step_address = |step: &GethExecStep| {
+        let nth = match step.op {
+            OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+                1
+            }
+            OpcodeId::BALANCE
+            | OpcodeId::EXTCODESIZE
+            | OpcodeId::EXTCODECOPY
+            | OpcodeId::EXTCODEHASH
+            | OpcodeId::SELFDESTRUCT => 0,
+            _ => return None,
+        };
+        step.stack.nth_last(nth).ok().map(|word| word.to_address())
+    };
+    eth_tx
+        .to
+        .into_iter()
+        .chain(geth_trace.struct_logs.iter().filter_map(step_address))
+        .find(is_point_evaluation)
+}
+
 /// Get State Accesses from TxExecTraces
 pub fn get_state_accesses(
     eth_block: &EthBlock,
@@ -9,11 +9,14 @@ use crate::{
     operation::{OperationContainer, RWCounter},
     Error,
 };
-use eth_types::{Address, Hash, ToWord, Word};
+use eth_types::{
+    evm_types::{block_utils::calc_blob_base_fee, ForkRules, ForkSchedule, FORK_SCHEDULE},
+    Address, Hash, ToWord, Word, U64,
+};
 use std::collections::{BTreeMap, HashMap};
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BlockContext {
     /// Used to track the global counter in every operation in the block.
     /// Contains the next available value.
@@ -88,6 +91,8 @@ pub struct BlockHead {
     pub difficulty: Word,
     /// base fee
     pub base_fee: Word,
+    /// blob base fee (EIP-4844)
+    pub blob_base_fee: Word,
     /// start l1 queue index
     pub start_l1_queue_index: u64,
     /// Original block from geth
@@ -131,6 +136,7 @@ impl BlockHead {
                 eth_block.difficulty
             },
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            blob_base_fee: Self::blob_base_fee(eth_block),
             eth_block: eth_block.clone(),
         })
     }
@@ -173,9 +179,21 @@ impl BlockHead {
                 eth_block.difficulty
             },
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            blob_base_fee: Self::blob_base_fee(eth_block),
             eth_block: eth_block.clone(),
         })
     }
+
+    /// Blob base fee derived from the excess blob gas of the block, which
+    /// ethers keeps in the `other` fields of the block.
+    fn blob_base_fee(eth_block: &eth_types::Block<eth_types::Transaction>) -> Word {
+        let excess_blob_gas = eth_block
+            .other
+            .get_deserialized::<U64>("excessBlobGas")
+            .and_then(Result::ok)
+            .unwrap_or_default();
+        calc_blob_base_fee(excess_blob_gas.as_u64())
+    }
 }
 
 /// Circuit Input related to a block.
@@ -210,6 +228,8 @@ pub struct Block {
     pub chain_id: u64,
     /// start_l1_queue_index
     pub start_l1_queue_index: u64,
+    /// Hard fork schedule deciding the EVM rules of each block
+    pub fork_schedule: ForkSchedule,
     /// IO to/from the precompiled contract calls.
     pub precompile_events: PrecompileEvents,
     /// circuit capacity counter
@@ -238,6 +258,7 @@ impl Block {
                 .map(|b| (b.number.as_u64(), b.clone()))
                 .collect::<BTreeMap<_, _>>(),
             circuits_params,
+            fork_schedule: FORK_SCHEDULE.clone(),
             ..Default::default()
         }
     }
@@ -262,6 +283,7 @@ impl Block {
             exp_events: Vec::new(),
             chain_id,
             circuits_params,
+            fork_schedule: FORK_SCHEDULE.clone(),
             ..Default::default()
         };
         let info = BlockHead::new(chain_id, history_hashes, eth_block)?;
@@ -292,6 +314,7 @@ impl Block {
             chain_id,
             start_l1_queue_index,
             circuits_params,
+            fork_schedule: FORK_SCHEDULE.clone(),
             ..Default::default()
         };
         let info = BlockHead::new_with_l1_queue_index(
@@ -314,6 +337,15 @@ impl Block {
         self.chain_id
     }
 
+    /// Return the rules in effect for the block with the given number.
+    pub fn fork_rules(&self, block_num: u64) -> ForkRules {
+        let timestamp = self
+            .headers
+            .get(&block_num)
+            .map_or(0, |header| header.timestamp.low_u64());
+        self.fork_schedule.rules(block_num, timestamp)
+    }
+
     /// Return if the relax mode
     pub fn is_relaxed(&self) -> bool {
         self.relax_mode
@@ -10,7 +10,7 @@ use crate::{
     error::{ExecError, OogError},
     exec_trace::OperationRef,
     operation::RWCounter,
-    precompile::{PrecompileAuxData, PrecompileCalls},
+    precompile::{PrecompileAuxData, PrecompileCalls, BLAKE2F_INPUT_LEN, BLAKE2F_OUTPUT_LEN},
 };
 use eth_types::{
     evm_types::{memory::MemoryWordRange, Gas, GasCost, MemoryAddress, OpcodeId, ProgramCounter},
@@ -27,6 +27,7 @@ use halo2_proofs::{
     },
     plonk::Expression,
 };
+use serde::{Deserialize, Serialize};
 
 /// An execution step of the EVM.
 #[derive(Clone, Debug)]
@@ -204,7 +205,7 @@ impl ExecState {
 }
 
 /// Defines the various source/destination types for a copy event.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CopyDataType {
     /// When we need to pad the Copy rows of the circuit up to a certain maximum
     /// with rows that are not "useful".
@@ -221,6 +222,10 @@ pub enum CopyDataType {
     /// scenario where we wish to accumulate the value (RLC) over all rows.
     /// This is used for Copy Lookup from SHA3 opcode verification.
     RlcAcc,
+    /// When the source and destination are both the memory of the same call,
+    /// as in MCOPY (EIP-5656). All the source words are read before any
+    /// destination word is written, so the two ranges may overlap.
+    MemoryCopy,
     /// When copy event is access-list addresses (EIP-2930), source is tx-table
     /// and destination is rw-table.
     AccessListAddresses,
@@ -230,9 +235,11 @@ pub enum CopyDataType {
 }
 impl CopyDataType {
     /// How many bits are necessary to represent a copy data type.
-    pub const N_BITS: usize = 3usize;
+    pub const N_BITS: usize = 4usize;
 }
-const NUM_COPY_DATA_TYPES: usize = 6usize;
+// The access-list types don't appear in the copy circuit, so they are left out of the
+// iterator, whose index of each type must match its discriminant.
+const NUM_COPY_DATA_TYPES: usize = 7usize;
 pub struct CopyDataTypeIter {
     idx: usize,
     back_idx: usize,
@@ -247,6 +254,7 @@ impl CopyDataTypeIter {
             3usize => Some(CopyDataType::TxCalldata),
             4usize => Some(CopyDataType::TxLog),
             5usize => Some(CopyDataType::RlcAcc),
+            6usize => Some(CopyDataType::MemoryCopy),
             _ => None,
         }
     }
@@ -313,8 +321,9 @@ impl From<CopyDataType> for usize {
             CopyDataType::TxCalldata => 3,
             CopyDataType::TxLog => 4,
             CopyDataType::RlcAcc => 5,
-            CopyDataType::AccessListAddresses => 6,
-            CopyDataType::AccessListStorageKeys => 7,
+            CopyDataType::MemoryCopy => 6,
+            CopyDataType::AccessListAddresses => 7,
+            CopyDataType::AccessListStorageKeys => 8,
         }
     }
 }
@@ -328,8 +337,9 @@ impl From<&CopyDataType> for u64 {
             CopyDataType::TxCalldata => 3,
             CopyDataType::TxLog => 4,
             CopyDataType::RlcAcc => 5,
-            CopyDataType::AccessListAddresses => 6,
-            CopyDataType::AccessListStorageKeys => 7,
+            CopyDataType::MemoryCopy => 6,
+            CopyDataType::AccessListAddresses => 7,
+            CopyDataType::AccessListStorageKeys => 8,
         }
     }
 }
@@ -355,7 +365,7 @@ pub struct CopyStep {
 }
 
 /// Defines an enum type that can hold either a number or a hash value.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NumberOrHash {
     /// Variant to indicate a number value.
     Number(usize),
@@ -374,7 +384,7 @@ pub enum NumberOrHash {
 ///
 /// Additionally, when the destination is memory, `bytes_write_prev` holds the memory content
 /// *before* the write.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct CopyBytes {
     /// Represents the list of (bytes, is_code, mask) copied during this copy event
     pub bytes: Vec<(u8, bool, bool)>,
@@ -404,7 +414,7 @@ impl CopyBytes {
 /// Defines a copy event associated with EVM opcodes such as CALLDATACOPY,
 /// CODECOPY, CREATE, etc. More information:
 /// <https://github.com/privacy-scaling-explorations/zkevm-specs/blob/master/specs/copy-proof.md>.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CopyEvent {
     /// Represents the start address at the source of the copy event.
     pub src_addr: u64,
@@ -446,12 +456,18 @@ impl CopyEvent {
 
     /// Whether the source performs RW lookups in the state circuit.
     pub fn is_source_rw(&self) -> bool {
-        self.src_type == CopyDataType::Memory
+        matches!(
+            self.src_type,
+            CopyDataType::Memory | CopyDataType::MemoryCopy
+        )
     }
 
     /// Whether the destination performs RW lookups in the state circuit.
     pub fn is_destination_rw(&self) -> bool {
-        self.dst_type == CopyDataType::Memory || self.dst_type == CopyDataType::TxLog
+        matches!(
+            self.dst_type,
+            CopyDataType::Memory | CopyDataType::MemoryCopy | CopyDataType::TxLog
+        )
     }
 
     /// Whether the RLC of data must be computed.
@@ -792,7 +808,7 @@ where
 }
 
 /// Intermediary multiplication step, representing `a * b == d (mod 2^256)`
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ExpStep {
     /// First multiplicand.
     pub a: Word,
@@ -813,7 +829,7 @@ impl From<(Word, Word, Word)> for ExpStep {
 }
 
 /// Event representating an exponentiation `a ^ b == d (mod 2^256)`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExpEvent {
     /// Base `a` for the exponentiation.
     pub base: Word,
@@ -841,7 +857,7 @@ impl Default for ExpEvent {
 }
 
 /// I/Os from all precompiled contract calls in a block.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PrecompileEvents {
     /// All events.
     pub events: Vec<PrecompileEvent>,
@@ -932,10 +948,38 @@ impl PrecompileEvents {
             .cloned()
             .collect()
     }
+    /// Get all RIPEMD-160 events.
+    pub fn get_ripemd160_events(&self) -> Vec<Ripemd160> {
+        self.events
+            .iter()
+            .filter_map(|e| {
+                if let PrecompileEvent::Ripemd160(op) = e {
+                    Some(op)
+                } else {
+                    None
+                }
+            })
+            .cloned()
+            .collect()
+    }
+    /// Get all Blake2F events.
+    pub fn get_blake2f_events(&self) -> Vec<Blake2F> {
+        self.events
+            .iter()
+            .filter_map(|e| {
+                if let PrecompileEvent::Blake2F(op) = e {
+                    Some(op)
+                } else {
+                    None
+                }
+            })
+            .cloned()
+            .collect()
+    }
 }
 
 /// I/O from a precompiled contract call.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum PrecompileEvent {
     /// Represents the I/O from Ecrecover call.
     Ecrecover(SignData),
@@ -949,6 +993,10 @@ pub enum PrecompileEvent {
     ModExp(BigModExp),
     /// Represents the I/O from SHA256 call.
     SHA256(SHA256),
+    /// Represents the I/O from RIPEMD-160 call.
+    Ripemd160(Ripemd160),
+    /// Represents the I/O from Blake2F call.
+    Blake2F(Blake2F),
 }
 
 impl Default for PrecompileEvent {
@@ -958,7 +1006,7 @@ impl Default for PrecompileEvent {
 }
 
 /// EcAdd operation: P + Q = R
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EcAddOp {
     /// EVM input for first operand to EcAdd.
     pub p: (U256, U256),
@@ -1060,7 +1108,7 @@ impl EcAddOp {
 }
 
 /// EcMul operation: s.P = R
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EcMulOp {
     /// The EVM inputs to the G1 point.
     pub p: (U256, U256),
@@ -1176,7 +1224,7 @@ pub const N_PAIRING_PER_OP: usize = 4;
 pub const N_BYTES_PER_PAIR: usize = 192;
 
 /// Pair of (G1, G2).
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcPairingPair {
     /// EVM inputs for the G1 point.
     pub g1_point: (U256, U256),
@@ -1278,7 +1326,7 @@ impl EcPairingPair {
 }
 
 /// EcPairing operation
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcPairingOp {
     /// tuples of G1 and G2 points supplied to the ECC circuit.
     pub pairs: [EcPairingPair; N_PAIRING_PER_OP],
@@ -1372,35 +1420,115 @@ impl EcPairingOp {
     }
 }
 
-/// Event representating an exponentiation `a ^ b == d (mod m)` in precompile modexp.
-#[derive(Clone, Debug)]
+/// Event representating an exponentiation `a ^ b == d (mod m)` in precompile modexp. All the
+/// values are big-endian bytes of the lengths specified in the call data.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct BigModExp {
     /// Base `a` for the exponentiation.
-    pub base: Word,
+    pub base: Vec<u8>,
     /// Exponent `b` for the exponentiation.
-    pub exponent: Word,
+    pub exponent: Vec<u8>,
     /// Modulus `m`
-    pub modulus: Word,
-    /// Mod exponentiation result.
-    pub result: Word,
+    pub modulus: Vec<u8>,
+    /// Mod exponentiation result, of the same length as the modulus.
+    pub result: Vec<u8>,
 }
 
-impl Default for BigModExp {
-    fn default() -> Self {
-        Self {
-            modulus: 1.into(),
-            base: Default::default(),
-            exponent: Default::default(),
-            result: Default::default(),
-        }
+impl BigModExp {
+    /// The number of significant bits of the exponent.
+    pub fn exponent_bit_len(&self) -> usize {
+        self.exponent
+            .iter()
+            .position(|&byte| byte != 0)
+            .map_or(0, |i| {
+                8 * (self.exponent.len() - i) - self.exponent[i].leading_zeros() as usize
+            })
     }
 }
 
 /// Event representating an SHA256 hash in precompile sha256.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SHA256 {
     /// input bytes
     pub input: Vec<u8>,
     /// digest
     pub digest: [u8; 32],
 }
+
+/// Event representating a RIPEMD-160 hash in precompile ripemd160.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Ripemd160 {
+    /// input bytes
+    pub input: Vec<u8>,
+    /// digest
+    pub digest: [u8; 20],
+}
+
+/// Event representating a call to the BLAKE2 compression function F in precompile blake2f.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Blake2F {
+    /// Number of rounds
+    pub rounds: u32,
+    /// State vector
+    pub h: [u64; 8],
+    /// Message block vector
+    pub m: [u64; 16],
+    /// Offset counters
+    pub t: [u64; 2],
+    /// Final block indicator flag
+    pub f: bool,
+    /// Output state vector
+    pub output: [u64; 8],
+}
+
+impl Blake2F {
+    /// Parse the event from the input and output bytes of a successful call, i.e.
+    /// the input is 213 bytes long and the final block indicator flag is 0 or 1.
+    pub fn new(input: &[u8], output: &[u8]) -> Self {
+        assert_eq!(
+            input.len(),
+            BLAKE2F_INPUT_LEN,
+            "invalid blake2f input length"
+        );
+        assert_eq!(
+            output.len(),
+            BLAKE2F_OUTPUT_LEN,
+            "invalid blake2f output length"
+        );
+        assert!(
+            input[212] <= 1,
+            "invalid blake2f final block indicator flag"
+        );
+
+        let words = |bytes: &[u8]| {
+            bytes
+                .chunks_exact(8)
+                .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+                .collect::<Vec<_>>()
+        };
+        Self {
+            rounds: u32::from_be_bytes(input[0..4].try_into().unwrap()),
+            h: words(&input[4..68]).try_into().unwrap(),
+            m: words(&input[68..196]).try_into().unwrap(),
+            t: words(&input[196..212]).try_into().unwrap(),
+            f: input[212] == 1,
+            output: words(output).try_into().unwrap(),
+        }
+    }
+
+    /// The 213 bytes passed as input to the precompile call.
+    pub fn input_bytes(&self) -> Vec<u8> {
+        std::iter::empty()
+            .chain(self.rounds.to_be_bytes())
+            .chain(self.h.iter().flat_map(|w| w.to_le_bytes()))
+            .chain(self.m.iter().flat_map(|w| w.to_le_bytes()))
+            .chain(self.t.iter().flat_map(|w| w.to_le_bytes()))
+            .chain(std::iter::once(self.f as u8))
+            .collect()
+    }
+
+    /// The 64 bytes returned from the precompile call.
+    pub fn output_bytes(&self) -> Vec<u8> {
+        self.output.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+}
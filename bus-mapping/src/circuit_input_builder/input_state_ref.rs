@@ -16,10 +16,10 @@ use crate::{
     exec_trace::OperationRef,
     operation::{
         AccountField, AccountOp, CallContextField, CallContextOp, MemoryOp, Op, OpEnum, Operation,
-        StackOp, Target, TxAccessListAccountOp, TxLogField, TxLogOp, TxReceiptField, TxReceiptOp,
-        RW,
+        StackOp, Target, TransientStorageField, TxAccessListAccountOp, TxLogField, TxLogOp,
+        TxReceiptField, TxReceiptOp, RW,
     },
-    precompile::{is_precompiled, PrecompileCalls},
+    precompile::{is_precompiled, PrecompileCalls, LAST_PRECOMPILE_ADDRESS},
     state_db::{CodeDB, StateDB},
     Error,
 };
@@ -28,7 +28,8 @@ use eth_types::{
     evm_types::{
         gas_utils::memory_expansion_gas_cost,
         memory::{MemoryRange, MemoryWordRange},
-        Gas, GasCost, Memory, MemoryAddress, MemoryRef, OpcodeId, StackAddress, MAX_CODE_SIZE,
+        ForkRules, Gas, GasCost, Memory, MemoryAddress, MemoryRef, OpcodeId, StackAddress,
+        MAX_CODE_SIZE,
     },
     Address, Bytecode, GethExecStep, ToAddress, ToBigEndian, ToWord, Word, H256, U256,
 };
@@ -799,6 +800,11 @@ impl<'a> CircuitInputStateRef<'a> {
         )
     }
 
+    /// Return the rules in effect for the block of the current transaction.
+    pub fn fork_rules(&self) -> ForkRules {
+        self.block.fork_rules(self.tx.block_num)
+    }
+
     /// Fetch and return code for the given code hash from the code DB.
     pub fn code(&self, code_hash: H256) -> Result<Vec<u8>, Error> {
         self.code_db
@@ -958,7 +964,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// Check if address is a precompiled or not.
     pub fn is_precompiled(&self, address: &Address) -> bool {
-        address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19])
+        address.0[0..19] == [0u8; 19] && (1..=LAST_PRECOMPILE_ADDRESS).contains(&address.0[19])
     }
 
     /// Parse [`Call`] from a *CALL*/CREATE* step.
@@ -1107,6 +1113,14 @@ impl<'a> CircuitInputStateRef<'a> {
                     None
                 }
             }
+            OperationRef(Target::TransientStorage, idx) => {
+                let operation = &self.block.container.transient_storage[*idx];
+                if operation.rw().is_write() && operation.reversible() {
+                    Some(OpEnum::TransientStorage(operation.op().reverse()))
+                } else {
+                    None
+                }
+            }
             _ => None,
         }
     }
@@ -1117,6 +1131,16 @@ impl<'a> CircuitInputStateRef<'a> {
             OpEnum::Storage(op) => {
                 self.sdb.set_storage(&op.address, &op.key, &op.value);
             }
+            OpEnum::TransientStorage(op) => match op.field {
+                TransientStorageField::Slot => {
+                    self.sdb
+                        .set_transient_storage(&op.address, &op.key, &op.value);
+                }
+                TransientStorageField::AccountCreated => {
+                    self.sdb
+                        .set_account_created(&op.address, !op.value.is_zero());
+                }
+            },
             OpEnum::TxAccessListAccount(op) => {
                 if !op.is_warm_prev && op.is_warm {
                     self.sdb.add_account_to_access_list(op.address);
@@ -1559,6 +1583,7 @@ impl<'a> CircuitInputStateRef<'a> {
                     OpcodeId::RETURNDATACOPY => Some(ExecError::ReturnDataOutOfBounds),
                     // Break write protection (CALL with value will be handled below)
                     OpcodeId::SSTORE
+                    | OpcodeId::TSTORE
                     | OpcodeId::CREATE
                     | OpcodeId::CREATE2
                     | OpcodeId::SELFDESTRUCT
@@ -1721,9 +1746,7 @@ impl<'a> CircuitInputStateRef<'a> {
                 if is_precompiled(&code_address) {
                     let precompile_call: PrecompileCalls = code_address[19].into();
                     match precompile_call {
-                        PrecompileCalls::Sha256
-                        | PrecompileCalls::Ripemd160
-                        | PrecompileCalls::Blake2F => {
+                        PrecompileCalls::Sha256 | PrecompileCalls::Ripemd160 => {
                             // Log the precompile address and gas left. Since this failure is mainly
                             // caused by out of gas.
                             log::trace!(
@@ -2100,6 +2123,77 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok((read_steps, write_steps, prev_bytes))
     }
 
+    /// Generate the copy steps of MCOPY. The source and destination ranges are
+    /// in the memory of the current call and may overlap, so all the source
+    /// words are read before the first destination word is written.
+    pub(crate) fn gen_copy_steps_for_memory_copy(
+        &mut self,
+        exec_step: &mut ExecStep,
+        src_addr: impl Into<MemoryAddress>,
+        dst_addr: impl Into<MemoryAddress>,
+        copy_length: impl Into<MemoryAddress>,
+    ) -> Result<(CopyEventSteps, CopyEventSteps, Vec<u8>), Error> {
+        let copy_length = copy_length.into().0;
+        if copy_length == 0 {
+            return Ok((vec![], vec![], vec![]));
+        }
+        let src_addr = src_addr.into().0;
+        let dst_addr = dst_addr.into().0;
+
+        let call_ctx = self.call_ctx_mut()?;
+        // Memory is expanded to cover both the source and the destination.
+        call_ctx
+            .memory
+            .extend_for_range(src_addr.into(), copy_length.into());
+        // Take a snapshot of the source before any word is overwritten.
+        let src_data = call_ctx.memory.0.clone();
+        let (src_range, dst_range, write_slot_bytes) = combine_copy_slot_bytes(
+            src_addr,
+            dst_addr,
+            copy_length,
+            src_data.as_slice(),
+            &mut call_ctx.memory,
+        );
+        let read_slot_bytes = call_ctx.memory.read_chunk(src_range);
+
+        let read_steps = CopyEventStepsBuilder::memory_range(src_range)
+            .source(read_slot_bytes.as_slice())
+            .build();
+        let write_steps = CopyEventStepsBuilder::memory_range(dst_range)
+            .source(write_slot_bytes.as_slice())
+            .build();
+
+        let call_id = self.call()?.call_id;
+        let mut src_chunk_index = src_range.start_slot().0;
+        for read_chunk in read_slot_bytes.chunks(32) {
+            self.push_op(
+                exec_step,
+                RW::READ,
+                MemoryOp::new(
+                    call_id,
+                    src_chunk_index.into(),
+                    Word::from_big_endian(read_chunk),
+                ),
+            )?;
+            trace!("read chunk: {call_id} {src_chunk_index} {read_chunk:?}");
+            src_chunk_index += 32;
+        }
+
+        let mut dst_chunk_index = dst_range.start_slot().0;
+        let mut prev_bytes: Vec<u8> = vec![];
+        for write_chunk in write_slot_bytes.chunks(32) {
+            self.write_chunk_for_copy_step(
+                exec_step,
+                write_chunk,
+                dst_chunk_index,
+                &mut prev_bytes,
+            )?;
+            dst_chunk_index += 32;
+        }
+
+        Ok((read_steps, write_steps, prev_bytes))
+    }
+
     pub(crate) fn gen_copy_steps_for_log(
         &mut self,
         exec_step: &mut ExecStep,
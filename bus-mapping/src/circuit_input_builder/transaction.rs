@@ -5,10 +5,11 @@ use std::collections::BTreeMap;
 use eth_types::{
     evm_types::{gas_utils::tx_data_gas_cost, Memory, OpcodeId},
     geth_types,
-    geth_types::{get_rlp_unsigned, TxType},
+    geth_types::{get_blob_fields, get_rlp_signed, get_rlp_unsigned, TxType},
     AccessList, Address, GethExecTrace, Signature, Word, H256,
 };
 use ethers_core::utils::get_contract_address;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     l2_predeployed::l1_gas_price_oracle,
@@ -238,6 +239,10 @@ pub struct Transaction {
     pub l1_fee_committed: TxL1Fee,
     /// EIP2930
     pub access_list: Option<AccessList>,
+    /// EIP4844: max fee per blob gas
+    pub max_fee_per_blob_gas: Word,
+    /// EIP4844: blob versioned hashes
+    pub blob_versioned_hashes: Vec<H256>,
     /// Calls made in the transaction
     pub(crate) calls: Vec<Call>,
     /// Execution steps
@@ -263,6 +268,8 @@ impl From<&Transaction> for geth_types::Transaction {
             rlp_unsigned_bytes: tx.rlp_unsigned_bytes.clone(),
             rlp_bytes: tx.rlp_bytes.clone(),
             tx_type: tx.tx_type,
+            max_fee_per_blob_gas: tx.max_fee_per_blob_gas,
+            blob_versioned_hashes: tx.blob_versioned_hashes.clone(),
             ..Default::default()
         }
     }
@@ -297,6 +304,8 @@ impl Transaction {
             l1_fee: Default::default(),
             l1_fee_committed: Default::default(),
             access_list: None,
+            max_fee_per_blob_gas: Word::zero(),
+            blob_versioned_hashes: vec![],
         }
     }
 
@@ -384,11 +393,13 @@ impl Transaction {
             l1_fee_committed
         );
 
+        let (max_fee_per_blob_gas, blob_versioned_hashes) = get_blob_fields(eth_tx);
+
         Ok(Self {
             block_num: eth_tx.block_number.unwrap().as_u64(),
             hash: eth_tx.hash,
             tx_type,
-            rlp_bytes: eth_tx.rlp().to_vec(),
+            rlp_bytes: get_rlp_signed(eth_tx),
             rlp_unsigned_bytes: get_rlp_unsigned(eth_tx),
             nonce: eth_tx.nonce.as_u64(),
             gas: eth_tx.gas.as_u64(),
@@ -410,6 +421,8 @@ impl Transaction {
             l1_fee,
             l1_fee_committed,
             access_list: eth_tx.access_list.clone(),
+            max_fee_per_blob_gas,
+            blob_versioned_hashes,
         })
     }
 
@@ -495,7 +508,7 @@ impl Transaction {
 }
 
 /// Transaction L1 fee for L1GasPriceOracle contract
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TxL1Fee {
     /// L1 base fee
     pub base_fee: u64,
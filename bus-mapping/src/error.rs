@@ -39,6 +39,9 @@ pub enum Error {
     ExecutionError(ExecError),
     /// Internal Code error
     InternalError(&'static str),
+    /// A tx calls or accesses a precompile that is active in the fork of its block but not
+    /// supported by the circuits, i.e. the point evaluation precompile from cancun.
+    UnsupportedPrecompile(Address),
 }
 
 impl From<eth_types::Error> for Error {
@@ -94,6 +97,7 @@ pub enum OogError {
     /// Out of Gas for Precompile.
     /// ecrecover/ecadd/ecmul/ecpairing/identity oog can should be handled by this.
     /// modexp oog is handled inside modexp gadget.
+    /// blake2f oog is handled inside blake2f gadget.
     /// disabled precompiles are handled by PrecompileFailedGadget.
     Precompile,
     /// Out of Gas for CREATE and CREATE2
@@ -4,9 +4,10 @@ use crate::{
     },
     l2_predeployed::l1_gas_price_oracle,
     operation::{
-        AccountField, AccountOp, CallContextField, StorageOp, TxReceiptField, TxRefundOp, RW,
+        AccountField, AccountOp, CallContextField, StorageOp, TransientStorageOp, TxReceiptField,
+        TxRefundOp, RW,
     },
-    precompile::is_precompiled,
+    precompile::{is_precompiled, LAST_PRECOMPILE_ADDRESS},
     state_db::CodeDB,
     Error,
 };
@@ -143,8 +144,8 @@ pub fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep,
     )?;
 
     // Add precompile contract address to access list
-    for address in 1..=9 {
-        let address = eth_types::Address::from_low_u64_be(address);
+    for address in 1..=LAST_PRECOMPILE_ADDRESS {
+        let address = eth_types::Address::from_low_u64_be(address.into());
         let is_warm_prev = !state.sdb.add_account_to_access_list(address);
         state.tx_accesslist_account_write(
             &mut exec_step,
@@ -156,19 +157,18 @@ pub fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep,
     }
 
     // Add caller, callee and coinbase (only for Shanghai) to access list.
-    #[cfg(feature = "shanghai")]
-    let accessed_addresses = [
-        call.caller_address,
-        call.address,
-        state
-            .block
-            .headers
-            .get(&state.tx.block_num)
-            .unwrap()
-            .coinbase,
-    ];
-    #[cfg(not(feature = "shanghai"))]
-    let accessed_addresses = [call.caller_address, call.address];
+    let fork_rules = state.fork_rules();
+    let mut accessed_addresses = vec![call.caller_address, call.address];
+    if fork_rules.is_shanghai {
+        accessed_addresses.push(
+            state
+                .block
+                .headers
+                .get(&state.tx.block_num)
+                .unwrap()
+                .coinbase,
+        );
+    }
     for address in accessed_addresses {
         let is_warm_prev = !state.sdb.add_account_to_access_list(address);
         state.tx_accesslist_account_write(
@@ -181,14 +181,12 @@ pub fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep,
     }
 
     // Calculate gas cost of init code only for EIP-3860 of Shanghai.
-    #[cfg(feature = "shanghai")]
     let init_code_gas_cost = if state.tx.is_create() {
-        (state.tx.input.len() as u64 + 31) / 32 * eth_types::evm_types::INIT_CODE_WORD_GAS
+        (state.tx.input.len() as u64 + 31) / 32
+            * eth_types::evm_types::init_code_word_gas(&fork_rules)
     } else {
         0
     };
-    #[cfg(not(feature = "shanghai"))]
-    let init_code_gas_cost = 0;
 
     // Calculate intrinsic gas cost
     let call_data_gas_cost = tx_data_gas_cost(&state.tx.input);
@@ -337,6 +335,16 @@ pub fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep,
                     value_prev: 0.into(),
                 },
             )?;
+            // EIP 6780, mark callee as created in this tx for `SELFDESTRUCT`
+            state.push_op_reversible(
+                &mut exec_step,
+                TransientStorageOp::new_account_created(
+                    state.tx_ctx.id(),
+                    call.address,
+                    true,
+                    false,
+                ),
+            )?;
             for (field, value) in [
                 (CallContextField::Depth, call.depth.into()),
                 (
@@ -0,0 +1,155 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::CallContextField,
+    Error,
+};
+use eth_types::{GethExecStep, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::BLOBHASH`](crate::evm::OpcodeId::BLOBHASH)
+/// `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Blobhash;
+
+impl Opcode for Blobhash {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::TxId,
+            Word::from(state.tx_ctx.id()),
+        )?;
+
+        let index = geth_step.stack.last()?;
+        let stack_position = geth_step.stack.last_filled();
+        state.stack_read(&mut exec_step, stack_position, index)?;
+
+        // Out of range indices push zero.
+        let blob_hash = if index < Word::from(state.tx.blob_versioned_hashes.len()) {
+            state.tx.blob_versioned_hashes[index.as_usize()].to_word()
+        } else {
+            Word::zero()
+        };
+        if let Ok(blob_hash_from_stack) = geth_steps[1].stack.last() {
+            if blob_hash != blob_hash_from_stack {
+                panic!("inconsistent blobhash: local tx {blob_hash:?}, result {blob_hash_from_stack:?} at index {index:?}");
+            }
+        }
+
+        state.stack_write(&mut exec_step, stack_position, blob_hash)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod blobhash_tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{CallContextOp, StackOp, RW},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::{GethData, TxType},
+        H256,
+    };
+    use mock::test_ctx::{helpers::*, TestContext};
+    use pretty_assertions::assert_eq;
+
+    fn test_ok(index: u64, blob_hashes: Vec<H256>) {
+        let code = bytecode! {
+            PUSH32(index)
+            BLOBHASH
+            STOP
+        };
+        let expected = blob_hashes
+            .get(index as usize)
+            .map(|hash| hash.to_word())
+            .unwrap_or_default();
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .transaction_type(TxType::Eip4844 as u64)
+                    .blob_versioned_hashes(blob_hashes.clone());
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::BLOBHASH))
+            .unwrap();
+
+        let call_context_op =
+            &builder.block.container.call_context[step.bus_mapping_instance[0].as_usize()];
+        assert_eq!(
+            (call_context_op.rw(), call_context_op.op()),
+            (
+                RW::READ,
+                &CallContextOp::new(1, CallContextField::TxId, Word::from(0x01)),
+            )
+        );
+
+        assert_eq!(
+            [1, 2]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|operation| (operation.rw(), operation.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(1, StackAddress::from(1023), Word::from(index))
+                ),
+                (
+                    RW::WRITE,
+                    &StackOp::new(1, StackAddress::from(1023), expected)
+                )
+            ]
+        );
+    }
+
+    fn versioned_hashes(n: u8) -> Vec<H256> {
+        (0..n)
+            .map(|i| {
+                let mut hash = [i + 1; 32];
+                hash[0] = 0x01;
+                H256(hash)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn blobhash_opcode_impl_in_range() {
+        test_ok(0, versioned_hashes(2));
+        test_ok(1, versioned_hashes(2));
+    }
+
+    #[test]
+    fn blobhash_opcode_impl_out_of_range() {
+        test_ok(2, versioned_hashes(2));
+        test_ok(0, vec![]);
+    }
+}
@@ -9,7 +9,7 @@ use crate::{
         precompiles::gen_associated_ops as precompile_associated_ops,
     },
     operation::{AccountField, CallContextField, TxAccessListAccountOp},
-    precompile::{execute_precompiled, is_precompiled, PrecompileCalls},
+    precompile::{execute_precompiled, is_precompiled, ModExpAuxData, PrecompileCalls},
     state_db::CodeDB,
     Error,
 };
@@ -275,6 +275,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                         &[]
                     },
                     callee_gas_left_with_stipend,
+                    state.block.circuits_params.max_modexp_size,
                 );
 
                 // mutate the callee memory by at least the precompile call's result that will be
@@ -354,7 +355,16 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                 // rws also handle prev bytes internally.
                 let rw_counter_start = state.block_ctx.rwc;
                 let input_bytes = if callee_call.call_data_length > 0 {
-                    let n_input_bytes = if let Some(input_len) = precompile_call.input_len() {
+                    let n_input_bytes = if precompile_call == PrecompileCalls::Modexp {
+                        // modexp takes only the bytes specified by its length header
+                        let caller_memory = &state.caller_ctx()?.memory;
+                        let header_len = min(96, args_length);
+                        ModExpAuxData::input_len(
+                            &caller_memory.0[args_offset..args_offset + header_len],
+                            callee_call.call_data_length as usize,
+                            state.block.circuits_params.max_modexp_size,
+                        )
+                    } else if let Some(input_len) = precompile_call.input_len() {
                         min(input_len, callee_call.call_data_length as usize)
                     } else {
                         callee_call.call_data_length as usize
@@ -457,8 +467,14 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                 } else {
                     None
                 };
-                // modexp's oog error is handled in ModExpGadget
-                if has_oog_err && precompile_call != PrecompileCalls::Modexp {
+                // modexp's and blake2f's oog errors are handled in ModExpGadget and
+                // Blake2FGadget respectively
+                if has_oog_err
+                    && !matches!(
+                        precompile_call,
+                        PrecompileCalls::Modexp | PrecompileCalls::Blake2F
+                    )
+                {
                     log::debug!(
                         "precompile call ({:?}) runs out of gas: callee_gas_left_with_stipend = {}",
                         precompile_call,
@@ -771,9 +787,6 @@ pub mod tests {
                 address: Word::from(0x3),
                 stack_value: vec![(
                     Word::from(0x20),
-                    #[cfg(feature = "scroll")]
-                    Word::zero(),
-                    #[cfg(not(feature = "scroll"))]
                     word!("2c0c45d3ecab80fe060e5f1d7057cd2f8de5e557"),
                 )],
                 ..Default::default()
@@ -1030,4 +1043,37 @@ pub mod tests {
             }
         }
     }
+
+    #[cfg(feature = "cancun")]
+    #[test]
+    fn test_point_evaluation_call_unsupported() {
+        use crate::{mock::BlockData, precompile::POINT_EVALUATION_ADDRESS, Error};
+        use eth_types::{geth_types::GethData, Address};
+        use mock::{
+            test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+            TestContext,
+        };
+
+        let call = PrecompileCallArgs {
+            name: "point evaluation",
+            address: Word::from(POINT_EVALUATION_ADDRESS),
+            ..Default::default()
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(call.with_call_op(OpcodeId::STATICCALL)),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let result = builder.handle_block(&block.eth_block, &block.geth_traces);
+        assert!(matches!(
+            result,
+            Err(Error::UnsupportedPrecompile(address))
+                if address == Address::from_low_u64_be(POINT_EVALUATION_ADDRESS as u64)
+        ));
+    }
 }
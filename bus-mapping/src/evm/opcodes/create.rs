@@ -5,7 +5,7 @@ use crate::{
     },
     error::{ContractAddressCollisionError, ExecError},
     evm::{Opcode, OpcodeId},
-    operation::{AccountField, AccountOp, CallContextField},
+    operation::{AccountField, AccountOp, CallContextField, TransientStorageOp},
     state_db::CodeDB,
     Error,
 };
@@ -277,6 +277,17 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
                     value_prev: 0.into(),
                 },
             )?;
+            // EIP 6780, mark callee as created in this tx for `SELFDESTRUCT`
+            let is_created_prev = state.sdb.is_account_created(&callee.address);
+            state.push_op_reversible(
+                &mut exec_step,
+                TransientStorageOp::new_account_created(
+                    state.tx_ctx.id(),
+                    callee.address,
+                    true,
+                    is_created_prev,
+                ),
+            )?;
 
             if length > 0 {
                 for (field, value) in [
@@ -0,0 +1,213 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    evm::{Opcode, OpcodeId},
+    operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word, U256};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the out of gas error of
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorOOGSelfDestruct;
+
+impl Opcode for ErrorOOGSelfDestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::SelfDestruct));
+
+        assert_eq!(geth_step.op, OpcodeId::SELFDESTRUCT);
+        // Read beneficiary address from stack.
+        let beneficiary_word = geth_step.stack.last()?;
+        let beneficiary = beneficiary_word.to_address();
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            beneficiary_word,
+        )?;
+
+        // Read transaction ID and callee address from call context.
+        let call_id = state.call()?.call_id;
+        let address = state.call()?.address;
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::TxId,
+            U256::from(state.tx_ctx.id()),
+        )?;
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::CalleeAddress,
+            address.to_word(),
+        )?;
+
+        // Read `is_warm` state of the beneficiary.
+        let is_warm = state.sdb.check_account_in_access_list(&beneficiary);
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TxAccessListAccountOp {
+                tx_id: state.tx_ctx.id(),
+                address: beneficiary,
+                is_warm,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        // Read beneficiary code hash (0 if it doesn't exist) and the balance
+        // of the current account, which determine the new account cost.
+        let beneficiary_account = state.sdb.get_account(&beneficiary).1;
+        let beneficiary_code_hash = if beneficiary_account.is_empty() {
+            Word::zero()
+        } else {
+            beneficiary_account.code_hash.to_word()
+        };
+        state.account_read(
+            &mut exec_step,
+            beneficiary,
+            AccountField::CodeHash,
+            beneficiary_code_hash,
+        )?;
+        let balance = state.sdb.get_account(&address).1.balance;
+        state.account_read(&mut exec_step, address, AccountField::Balance, balance)?;
+
+        // common error handling
+        state.handle_return(&mut [&mut exec_step], geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod oog_self_destruct_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::{ExecError, OogError},
+        mock::BlockData,
+        operation::{AccountField, AccountOp, StackOp, RW},
+    };
+    use eth_types::{
+        address, bytecode,
+        evm_types::{GasCost, OpcodeId},
+        geth_types::GethData,
+        Bytecode, ToWord, Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_oog_self_destruct() {
+        test_ok(true, false);
+        test_ok(false, false);
+        test_ok(true, true);
+        test_ok(false, true);
+    }
+
+    fn test_ok(exists: bool, is_warm: bool) {
+        let address = address!("0x0000000000000000000000000000000000000010");
+        let beneficiary = address!("0xaabbccddee000000000000000000000000000000");
+
+        // Read the balance of the beneficiary first to make it warm.
+        let mut code = Bytecode::default();
+        if is_warm {
+            code.append(&bytecode! {
+                PUSH20(beneficiary.to_word())
+                BALANCE
+                POP
+            });
+        }
+        code.append(&bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        });
+
+        let value = Word::from(1u64 << 20);
+        // Enough gas for the pushes and the warm BALANCE, but always one less
+        // than the cold SELFDESTRUCT cost.
+        let gas = GasCost::TX.0
+            + if is_warm {
+                OpcodeId::PUSH20.constant_gas_cost().0
+                    + GasCost::COLD_ACCOUNT_ACCESS.0
+                    + OpcodeId::POP.constant_gas_cost().0
+            } else {
+                0
+            }
+            + OpcodeId::PUSH20.constant_gas_cost().0
+            + GasCost::SELFDESTRUCT.0
+            - 1;
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(address).balance(value).code(code.clone());
+                if exists {
+                    accs[1].address(beneficiary).balance(Word::from(800u64));
+                } else {
+                    accs[1]
+                        .address(address!("0x0000000000000000000000000000000000000020"))
+                        .balance(Word::from(1u64 << 20));
+                }
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[2].address)
+                    .gas(gas.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx_id = 1;
+        let transaction = &builder.block.txs()[tx_id - 1];
+        let call_id = transaction.calls()[0].call_id;
+
+        let step = transaction
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SELFDESTRUCT))
+            .unwrap();
+
+        // check expected error occurs
+        assert_eq!(step.error, Some(ExecError::OutOfGas(OogError::SelfDestruct)));
+
+        let container = builder.block.container.clone();
+        let operation = &container.stack[step.bus_mapping_instance[0].as_usize()];
+        assert_eq!(operation.rw(), RW::READ);
+        assert_eq!(
+            operation.op(),
+            &StackOp {
+                call_id,
+                address: 1023.into(),
+                value: beneficiary.to_word(),
+            }
+        );
+
+        let operation = &container.account[step.bus_mapping_instance[5].as_usize()];
+        assert_eq!(operation.rw(), RW::READ);
+        assert_eq!(
+            operation.op(),
+            &AccountOp {
+                address,
+                field: AccountField::Balance,
+                value,
+                value_prev: value,
+            }
+        );
+    }
+}
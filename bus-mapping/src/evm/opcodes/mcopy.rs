@@ -0,0 +1,225 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{
+        CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+    },
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::MCOPY`](crate::evm::OpcodeId::MCOPY)
+/// `OpcodeId`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Mcopy;
+
+impl Opcode for Mcopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_steps = vec![gen_mcopy_step(state, geth_step)?];
+
+        let copy_event = gen_copy_event(state, geth_step, &mut exec_steps[0])?;
+        state.push_copy(&mut exec_steps[0], copy_event);
+        Ok(exec_steps)
+    }
+}
+
+fn gen_mcopy_step(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+) -> Result<ExecStep, Error> {
+    let mut exec_step = state.new_step(geth_step)?;
+
+    let dst_offset = geth_step.stack.last()?;
+    let src_offset = geth_step.stack.nth_last(1)?;
+    let length = geth_step.stack.nth_last(2)?;
+
+    state.stack_read(&mut exec_step, geth_step.stack.last_filled(), dst_offset)?;
+    state.stack_read(
+        &mut exec_step,
+        geth_step.stack.nth_last_filled(1),
+        src_offset,
+    )?;
+    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), length)?;
+
+    Ok(exec_step)
+}
+
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+    exec_step: &mut ExecStep,
+) -> Result<CopyEvent, Error> {
+    let rw_counter_start = state.block_ctx.rwc;
+
+    let dst_offset = geth_step.stack.last()?;
+    let src_offset = geth_step.stack.nth_last(1)?;
+    let length = geth_step.stack.nth_last(2)?;
+
+    // Offsets are only meaningful when there is something to copy, otherwise they
+    // may be arbitrarily large.
+    let length = length.as_u64();
+    let (dst_addr, src_addr) = if length == 0 {
+        (0, 0)
+    } else {
+        (dst_offset.as_u64(), src_offset.as_u64())
+    };
+
+    let (read_steps, write_steps, prev_bytes) =
+        state.gen_copy_steps_for_memory_copy(exec_step, src_addr, dst_addr, length)?;
+
+    let call_id = state.call()?.call_id;
+    Ok(CopyEvent {
+        src_type: CopyDataType::MemoryCopy,
+        src_id: NumberOrHash::Number(call_id),
+        src_addr,
+        src_addr_end: src_addr + length,
+        dst_type: CopyDataType::MemoryCopy,
+        dst_id: NumberOrHash::Number(call_id),
+        dst_addr,
+        log_id: None,
+        rw_counter_start,
+        copy_bytes: CopyBytes::new(read_steps, Some(write_steps), Some(prev_bytes)),
+    })
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod mcopy_tests {
+    use crate::{
+        circuit_input_builder::{CopyDataType, ExecState, NumberOrHash},
+        mock::BlockData,
+        operation::{MemoryOp, StackOp, RW},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Word,
+    };
+    use mock::test_ctx::{helpers::*, TestContext};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn mcopy_opcode_overlapping() {
+        let (dst_offset, src_offset, length) = (0x10usize, 0x00usize, 0x40usize);
+        let words = [Word::from(0x0102u64), Word::MAX];
+        let code = bytecode! {
+            PUSH32(words[0])
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(words[1])
+            PUSH1(0x20)
+            MSTORE
+            PUSH1(length)
+            PUSH1(src_offset)
+            PUSH1(dst_offset)
+            MCOPY
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::MCOPY))
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[step.call_index].call_id;
+
+        // 3 stack reads + 3 memory word reads + 3 memory word writes.
+        assert_eq!(step.bus_mapping_instance.len(), 9);
+
+        assert_eq!(
+            [0, 1, 2]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|operation| (operation.rw(), operation.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1021), Word::from(dst_offset))
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1022), Word::from(src_offset))
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1023), Word::from(length))
+                ),
+            ]
+        );
+
+        // All the source words are read before the first destination word is written.
+        let mut original_memory = [0u8; 0x60];
+        words[0].to_big_endian(&mut original_memory[0x00..0x20]);
+        words[1].to_big_endian(&mut original_memory[0x20..0x40]);
+        let mut final_memory = original_memory;
+        final_memory.copy_within(src_offset..src_offset + length, dst_offset);
+
+        let memory_ops = (3..9)
+            .map(|idx| &builder.block.container.memory[step.bus_mapping_instance[idx].as_usize()])
+            .map(|operation| (operation.rw(), operation.op().clone()))
+            .collect::<Vec<_>>();
+        let expected_ops = (0..3)
+            .map(|i| {
+                (
+                    RW::READ,
+                    MemoryOp::new(
+                        call_id,
+                        (i * 32).into(),
+                        Word::from_big_endian(&original_memory[i * 32..(i + 1) * 32]),
+                    ),
+                )
+            })
+            .chain((0..3).map(|i| {
+                (
+                    RW::WRITE,
+                    MemoryOp::new_write(
+                        call_id,
+                        (i * 32).into(),
+                        Word::from_big_endian(&final_memory[i * 32..(i + 1) * 32]),
+                        Word::from_big_endian(&original_memory[i * 32..(i + 1) * 32]),
+                    ),
+                )
+            }))
+            .collect::<Vec<_>>();
+        assert_eq!(memory_ops, expected_ops);
+
+        let copy_events = builder.block.copy_events.clone();
+        assert_eq!(copy_events.len(), 1);
+        assert_eq!(copy_events[0].src_type, CopyDataType::MemoryCopy);
+        assert_eq!(copy_events[0].dst_type, CopyDataType::MemoryCopy);
+        assert_eq!(copy_events[0].src_id, NumberOrHash::Number(call_id));
+        assert_eq!(copy_events[0].dst_id, NumberOrHash::Number(call_id));
+        assert_eq!(copy_events[0].rw_counter_delta(), 6);
+
+        let copied = copy_events[0]
+            .copy_bytes
+            .aux_bytes
+            .as_ref()
+            .unwrap()
+            .iter()
+            .filter(|(_, _, mask)| !mask)
+            .map(|(value, _, _)| *value)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            copied,
+            original_memory[src_offset..src_offset + length].to_vec()
+        );
+    }
+}
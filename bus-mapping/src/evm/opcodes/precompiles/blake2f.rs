@@ -0,0 +1,29 @@
+use crate::{
+    circuit_input_builder::{Blake2F, PrecompileEvent},
+    precompile::PrecompileAuxData,
+};
+
+pub(crate) fn opt_data(
+    input_bytes: &[u8],
+    output_bytes: &[u8],
+    return_bytes: &[u8],
+) -> (Option<PrecompileEvent>, Option<PrecompileAuxData>) {
+    let aux_data = PrecompileAuxData::Blake2F {
+        input_bytes: input_bytes.to_vec(),
+        output_bytes: output_bytes.to_vec(),
+        return_bytes: return_bytes.to_vec(),
+    };
+    // the output is empty on invalid input (wrong length or final block indicator flag) and
+    // on out of gas, where the compression does not need to be verified.
+    if output_bytes.is_empty() {
+        (None, Some(aux_data))
+    } else {
+        (
+            Some(PrecompileEvent::Blake2F(Blake2F::new(
+                input_bytes,
+                output_bytes,
+            ))),
+            Some(aux_data),
+        )
+    }
+}
@@ -2,19 +2,21 @@ use eth_types::{GethExecStep, ToWord, Word};
 
 use crate::{
     circuit_input_builder::{
-        Call, CircuitInputStateRef, ExecState, ExecStep, PrecompileEvent, SHA256,
+        Call, CircuitInputStateRef, ExecState, ExecStep, PrecompileEvent, Ripemd160, SHA256,
     },
     operation::CallContextField,
-    precompile::{PrecompileAuxData, PrecompileCalls},
+    precompile::{PrecompileAuxData, PrecompileCalls, RIPEMD160_DIGEST_LEN},
     Error,
 };
 
+mod blake2f;
 mod ec_add;
 mod ec_mul;
 mod ec_pairing;
 mod ecrecover;
 mod modexp;
 
+use blake2f::opt_data as opt_data_blake2f;
 use ec_add::opt_data as opt_data_ec_add;
 use ec_mul::opt_data as opt_data_ec_mul;
 use ec_pairing::opt_data as opt_data_ec_pairing;
@@ -43,7 +45,13 @@ pub fn gen_associated_ops(
         PrecompileCalls::Bn128Pairing => {
             opt_data_ec_pairing(input_bytes, output_bytes, return_bytes)
         }
-        PrecompileCalls::Modexp => opt_data_modexp(input_bytes, output_bytes, return_bytes),
+        PrecompileCalls::Modexp => opt_data_modexp(
+            input_bytes,
+            output_bytes,
+            return_bytes,
+            state.block.circuits_params.max_modexp_size,
+        ),
+        PrecompileCalls::Blake2F => opt_data_blake2f(input_bytes, output_bytes, return_bytes),
         PrecompileCalls::Identity => (
             None,
             Some(PrecompileAuxData::Identity {
@@ -69,17 +77,23 @@ pub fn gen_associated_ops(
                 return_bytes: return_bytes.to_vec(),
             }),
         ),
-        _ => {
-            log::warn!("precompile {:?} unsupported in circuits", precompile);
-            (
-                None,
-                Some(PrecompileAuxData::Base {
-                    input_bytes: input_bytes.to_vec(),
-                    output_bytes: output_bytes.to_vec(),
-                    return_bytes: return_bytes.to_vec(),
-                }),
-            )
-        }
+        PrecompileCalls::Ripemd160 => (
+            if output_bytes.is_empty() {
+                None
+            } else {
+                Some(PrecompileEvent::Ripemd160(Ripemd160 {
+                    input: input_bytes.to_vec(),
+                    digest: output_bytes[32 - RIPEMD160_DIGEST_LEN..]
+                        .try_into()
+                        .expect("output bytes must be 32 bytes"),
+                }))
+            },
+            Some(PrecompileAuxData::Ripemd160 {
+                input_bytes: input_bytes.to_vec(),
+                output_bytes: output_bytes.to_vec(),
+                return_bytes: return_bytes.to_vec(),
+            }),
+        ),
     };
     log::trace!("precompile event {opt_event:?}, aux data {aux_data:?}");
     if let Some(event) = opt_event {
@@ -3,20 +3,19 @@ use crate::{
     precompile::{ModExpAuxData, PrecompileAuxData},
 };
 
-use eth_types::Word;
-
 pub(crate) fn opt_data(
     input_bytes: &[u8],
     output_bytes: &[u8],
     return_bytes: &[u8],
+    size_limit: usize,
 ) -> (Option<PrecompileEvent>, Option<PrecompileAuxData>) {
-    let aux_data = ModExpAuxData::new(input_bytes, output_bytes, return_bytes);
+    let aux_data = ModExpAuxData::new(input_bytes, output_bytes, return_bytes, size_limit);
     if aux_data.valid {
         let event = BigModExp {
-            base: Word::from_big_endian(&aux_data.inputs[0]),
-            exponent: Word::from_big_endian(&aux_data.inputs[1]),
-            modulus: Word::from_big_endian(&aux_data.inputs[2]),
-            result: Word::from_big_endian(&aux_data.output),
+            base: aux_data.inputs[0].clone(),
+            exponent: aux_data.inputs[1].clone(),
+            modulus: aux_data.inputs[2].clone(),
+            result: aux_data.output.clone(),
         };
         (
             Some(PrecompileEvent::ModExp(event)),
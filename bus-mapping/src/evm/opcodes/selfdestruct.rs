@@ -0,0 +1,547 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{
+        AccountField, AccountOp, CallContextField, TransientStorageOp, TxAccessListAccountOp, RW,
+    },
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word, U256};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT) `OpcodeId`.
+///
+/// The whole balance of the current account is transferred to the
+/// beneficiary (or burnt if the beneficiary is the account itself), then the
+/// account is deleted by resetting its nonce and setting its code hash to 0,
+/// which is how non-existing accounts are encoded in the rw table.
+///
+/// After Cancun (EIP-6780), the account is only deleted if it is created in
+/// the same transaction. Otherwise only its balance is transferred, and kept
+/// if the beneficiary is the account itself.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Selfdestruct;
+
+impl Opcode for Selfdestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        // Read beneficiary address from stack.
+        let beneficiary_word = geth_step.stack.last()?;
+        let beneficiary = beneficiary_word.to_address();
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            beneficiary_word,
+        )?;
+
+        let call = state.call()?.clone();
+        let address = call.address;
+
+        // Read transaction id, callee address, is_success,
+        // rw_counter_end_of_reversion and is_persistent from call context.
+        for (field, value) in [
+            (CallContextField::TxId, U256::from(state.tx_ctx.id())),
+            (CallContextField::CalleeAddress, address.to_word()),
+            (CallContextField::IsSuccess, U256::one()),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                U256::from(call.rw_counter_end_of_reversion as u64),
+            ),
+            (
+                CallContextField::IsPersistent,
+                U256::from(call.is_persistent as u64),
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value)?;
+        }
+
+        // Update transaction access list for the beneficiary.
+        let is_warm = state.sdb.check_account_in_access_list(&beneficiary);
+        state.push_op_reversible(
+            &mut exec_step,
+            TxAccessListAccountOp {
+                tx_id: state.tx_ctx.id(),
+                address: beneficiary,
+                is_warm: true,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        // Read beneficiary code hash to determine if it exists.
+        let beneficiary_account = state.sdb.get_account(&beneficiary).1;
+        let beneficiary_exists = !beneficiary_account.is_empty();
+        let beneficiary_code_hash = if beneficiary_exists {
+            beneficiary_account.code_hash.to_word()
+        } else {
+            Word::zero()
+        };
+        state.account_read(
+            &mut exec_step,
+            beneficiary,
+            AccountField::CodeHash,
+            beneficiary_code_hash,
+        )?;
+
+        let (found, account) = state.sdb.get_account(&address);
+        if !found {
+            return Err(Error::AccountNotFound(address));
+        }
+        let account = account.clone();
+        let value = account.balance;
+
+        // Read whether the account is created in this tx (EIP-6780).
+        let is_created = state.sdb.is_account_created(&address);
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TransientStorageOp::new_account_created(
+                state.tx_ctx.id(),
+                address,
+                is_created,
+                is_created,
+            ),
+        )?;
+        let is_deleted = !state.fork_rules().is_cancun || is_created;
+        log::trace!(
+            "self destruct, address {:?} beneficiary {:?} value {:?} is_deleted {}",
+            address,
+            beneficiary,
+            value,
+            is_deleted
+        );
+
+        // The balance is emptied, and burnt if the beneficiary is the account
+        // itself, unless the account is kept.
+        let is_self = beneficiary == address;
+        state.push_op_reversible(
+            &mut exec_step,
+            AccountOp {
+                address,
+                field: AccountField::Balance,
+                value: if is_self && !is_deleted {
+                    value
+                } else {
+                    Word::zero()
+                },
+                value_prev: value,
+            },
+        )?;
+        if !is_self {
+            state.transfer_to(
+                &mut exec_step,
+                beneficiary,
+                beneficiary_exists,
+                false,
+                value,
+                true,
+            )?;
+        }
+
+        if is_deleted {
+            // Delete the account. Its code hash is written last so that the
+            // intermediate StateDB account is never empty when the nonce is reset.
+            state.push_op_reversible(
+                &mut exec_step,
+                AccountOp {
+                    address,
+                    field: AccountField::Nonce,
+                    value: Word::zero(),
+                    value_prev: account.nonce,
+                },
+            )?;
+            state.push_op_reversible(
+                &mut exec_step,
+                AccountOp {
+                    address,
+                    field: AccountField::CodeHash,
+                    value: Word::zero(),
+                    value_prev: account.code_hash.to_word(),
+                },
+            )?;
+            // The storage and code are only wiped if the deletion is persistent, otherwise the
+            // reversible account ops above are reverted with the call.
+            if call.is_persistent {
+                state.sdb.destruct_account(address);
+            }
+        }
+
+        state.handle_return(&mut [&mut exec_step], geth_steps, !call.is_root)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod selfdestruct_tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{CallContextOp, StackOp, RW},
+        state_db::{Account, CodeDB},
+    };
+    use eth_types::{
+        address, bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_selfdestruct_to_existing_beneficiary() {
+        test_ok(true, false);
+    }
+
+    #[test]
+    fn test_selfdestruct_to_non_existing_beneficiary() {
+        test_ok(false, false);
+    }
+
+    #[test]
+    fn test_selfdestruct_to_self() {
+        test_ok(true, true);
+    }
+
+    fn test_ok(beneficiary_exists: bool, is_self: bool) {
+        // The account isn't created in the tx, so it is kept after Cancun.
+        let is_deleted = !cfg!(feature = "cancun");
+        let address = address!("0x0000000000000000000000000000000000000010");
+        let beneficiary = if is_self {
+            address
+        } else {
+            address!("0xaabbccddee000000000000000000000000000000")
+        };
+        let code = bytecode! {
+            .op_selfdestruct(beneficiary)
+        };
+        let value = Word::from(1u64 << 20);
+        let beneficiary_balance = Word::from(800u64);
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(address).balance(value).code(code.clone());
+                if beneficiary_exists && !is_self {
+                    accs[1].address(beneficiary).balance(beneficiary_balance);
+                } else {
+                    accs[1]
+                        .address(address!("0x0000000000000000000000000000000000000020"))
+                        .balance(Word::from(1u64 << 20));
+                }
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx_id = 1;
+        let transaction = &builder.block.txs()[tx_id - 1];
+        let call_id = transaction.calls()[0].call_id;
+
+        let indices = transaction
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SELFDESTRUCT))
+            .unwrap()
+            .bus_mapping_instance
+            .clone();
+
+        let container = builder.block.container;
+
+        let operation = &container.stack[indices[0].as_usize()];
+        assert_eq!(operation.rw(), RW::READ);
+        assert_eq!(
+            operation.op(),
+            &StackOp {
+                call_id,
+                address: StackAddress::from(1023u32),
+                value: beneficiary.to_word()
+            }
+        );
+
+        for (idx, (field, value)) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::CalleeAddress, address.to_word()),
+            (CallContextField::IsSuccess, U256::one()),
+            (CallContextField::RwCounterEndOfReversion, U256::zero()),
+            (CallContextField::IsPersistent, U256::one()),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let operation = &container.call_context[indices[1 + idx].as_usize()];
+            assert_eq!(operation.rw(), RW::READ);
+            assert_eq!(
+                operation.op(),
+                &CallContextOp {
+                    call_id,
+                    field,
+                    value
+                }
+            );
+        }
+
+        let operation = &container.tx_access_list_account[indices[6].as_usize()];
+        assert_eq!(operation.rw(), RW::WRITE);
+        assert_eq!(
+            operation.op(),
+            &TxAccessListAccountOp {
+                tx_id,
+                address: beneficiary,
+                is_warm: true,
+                is_warm_prev: is_self,
+            }
+        );
+
+        let code_hash = CodeDB::hash(&code.to_vec()).to_word();
+        let beneficiary_code_hash = if is_self {
+            code_hash
+        } else if beneficiary_exists {
+            CodeDB::empty_code_hash().to_word()
+        } else {
+            Word::zero()
+        };
+        let operation = &container.account[indices[7].as_usize()];
+        assert_eq!(operation.rw(), RW::READ);
+        assert_eq!(
+            operation.op(),
+            &AccountOp {
+                address: beneficiary,
+                field: AccountField::CodeHash,
+                value: beneficiary_code_hash,
+                value_prev: beneficiary_code_hash,
+            }
+        );
+
+        let operation = &container.transient_storage[indices[8].as_usize()];
+        assert_eq!(operation.rw(), RW::READ);
+        assert_eq!(
+            operation.op(),
+            &TransientStorageOp::new_account_created(tx_id, address, false, false)
+        );
+
+        let operation = &container.account[indices[9].as_usize()];
+        assert_eq!(operation.rw(), RW::WRITE);
+        assert_eq!(
+            operation.op(),
+            &AccountOp {
+                address,
+                field: AccountField::Balance,
+                value: if is_self && !is_deleted {
+                    value
+                } else {
+                    Word::zero()
+                },
+                value_prev: value,
+            }
+        );
+
+        // Beneficiary creation (code hash read + write) and balance update.
+        let mut idx = 10;
+        if !is_self {
+            if !beneficiary_exists {
+                idx += 2;
+            }
+            let balance_prev = if beneficiary_exists {
+                beneficiary_balance
+            } else {
+                Word::zero()
+            };
+            let operation = &container.account[indices[idx].as_usize()];
+            assert_eq!(
+                operation.op(),
+                &AccountOp {
+                    address: beneficiary,
+                    field: AccountField::Balance,
+                    value: balance_prev + value,
+                    value_prev: balance_prev,
+                }
+            );
+            idx += 1;
+        }
+
+        if is_deleted {
+            for (field, value_prev) in [
+                (AccountField::Nonce, Word::zero()),
+                (AccountField::CodeHash, code_hash),
+            ] {
+                let operation = &container.account[indices[idx].as_usize()];
+                assert_eq!(operation.rw(), RW::WRITE);
+                assert_eq!(
+                    operation.op(),
+                    &AccountOp {
+                        address,
+                        field,
+                        value: Word::zero(),
+                        value_prev,
+                    }
+                );
+                idx += 1;
+            }
+        }
+        assert_eq!(idx, indices.len());
+
+        // The account is left empty in the StateDB, or keeps its code after Cancun.
+        let (found, account) = builder.sdb.get_account(&address);
+        assert!(found);
+        if is_deleted {
+            assert!(account.is_empty());
+        } else {
+            assert_eq!(account.code_hash, CodeDB::hash(&code.to_vec()));
+            assert_eq!(account.balance, if is_self { value } else { Word::zero() });
+        }
+        if !is_self {
+            let beneficiary_account: &Account = builder.sdb.get_account(&beneficiary).1;
+            assert_eq!(
+                beneficiary_account.balance,
+                value
+                    + if beneficiary_exists {
+                        beneficiary_balance
+                    } else {
+                        Word::zero()
+                    }
+            );
+        }
+    }
+
+    #[cfg(feature = "cancun")]
+    #[test]
+    fn test_selfdestruct_created_in_same_tx() {
+        let beneficiary = address!("0xaabbccddee000000000000000000000000000000");
+        let sender = address!("0x0000000000000000000000000000000000cafe01");
+        // The init code of the creation tx self-destructs, so the account is
+        // created in the same tx and deleted after Cancun.
+        let init_code = bytecode! {
+            .op_selfdestruct(beneficiary)
+        };
+        let value = Word::from(1u64 << 10);
+
+        let block: GethData = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(sender).balance(Word::from(1u64 << 20));
+            },
+            |mut txs, _accs| {
+                txs[0].from(sender).value(value).input(init_code.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx_id = 1;
+        let transaction = &builder.block.txs()[tx_id - 1];
+        let address = transaction.calls()[0].address;
+        let indices = transaction
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SELFDESTRUCT))
+            .unwrap()
+            .bus_mapping_instance
+            .clone();
+
+        let container = builder.block.container;
+        let operation = &container.transient_storage[indices[8].as_usize()];
+        assert_eq!(operation.rw(), RW::READ);
+        assert_eq!(
+            operation.op(),
+            &TransientStorageOp::new_account_created(tx_id, address, true, true)
+        );
+        // The nonce and code hash are reset.
+        let operation = &container.account[indices[indices.len() - 1].as_usize()];
+        assert_eq!(operation.rw(), RW::WRITE);
+        assert_eq!(operation.op().field, AccountField::CodeHash);
+        assert_eq!(operation.op().value, Word::zero());
+
+        let (_, account) = builder.sdb.get_account(&address);
+        assert!(account.is_empty());
+        assert_eq!(builder.sdb.get_account(&beneficiary).1.balance, value);
+    }
+
+    #[test]
+    fn test_selfdestruct_reverted() {
+        let address = address!("0x0000000000000000000000000000000000000010");
+        let caller = address!("0x0000000000000000000000000000000000000020");
+        let code = bytecode! {
+            .op_selfdestruct(caller)
+        };
+        // Call the contract which self-destructs, then revert.
+        let caller_code = bytecode! {
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH20(address.to_word())
+            GAS
+            CALL
+            PUSH1(0)
+            PUSH1(0)
+            REVERT
+        };
+        let value = Word::from(1u64 << 20);
+        let (storage_key, storage_value) = (Word::one(), Word::from(0xcafeu64));
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address)
+                    .balance(value)
+                    .code(code.clone())
+                    .storage(vec![(storage_key, storage_value)].into_iter());
+                accs[1]
+                    .address(caller)
+                    .balance(Word::from(1u64 << 20))
+                    .code(caller_code);
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000cafe01"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[2].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        // The account is restored with its code and storage.
+        let (found, account) = builder.sdb.get_account(&address);
+        assert!(found);
+        assert_eq!(account.balance, value);
+        assert_eq!(account.code_hash, CodeDB::hash(&code.to_vec()));
+        assert_eq!(
+            builder.sdb.get_storage(&address, &storage_key).1,
+            &storage_value
+        );
+    }
+}
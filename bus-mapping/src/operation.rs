@@ -9,6 +9,7 @@ pub use eth_types::evm_types::{MemoryAddress, StackAddress};
 
 use core::{cmp::Ordering, fmt, fmt::Debug};
 use eth_types::{Address, Word};
+use serde::{Deserialize, Serialize};
 use std::mem::swap;
 
 /// Marker that defines whether an Operation performs a `READ` or a `WRITE`.
@@ -35,7 +36,7 @@ impl RW {
 /// Wrapper type over `usize` which represents the global counter. The purpose
 /// of the `RWCounter` is to enforce that each Opcode/Instruction and Operation
 /// is unique and just executed once.
-#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct RWCounter(pub usize);
 
 impl fmt::Debug for RWCounter {
@@ -106,6 +107,8 @@ pub enum Target {
     TxReceipt,
     /// Means the target of the operation is the TxLog.
     TxLog,
+    /// Means the target of the operation is the TransientStorage.
+    TransientStorage,
 }
 
 /// Trait used for Operation Kinds.
@@ -392,6 +395,120 @@ impl Ord for StorageOp {
     }
 }
 
+/// Field of a [`TransientStorageOp`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TransientStorageField {
+    /// Transient storage slot (EIP-1153)
+    Slot,
+    /// Whether the account is created in the current tx (EIP-6780), stored
+    /// at key 0
+    AccountCreated,
+}
+
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the transient
+/// storage (EIP-1153) implied by a `TLOAD` or `TSTORE` step, or of the flag of
+/// accounts created in the tx implied by a `CREATE*`, `SELFDESTRUCT` or
+/// creation `BeginTx` step. Transient storage is discarded at the end of every
+/// transaction.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TransientStorageOp {
+    /// Transaction ID: Transaction index in the block starting at 1.
+    pub tx_id: usize,
+    /// Account Address
+    pub address: Address,
+    /// Transient Storage Field
+    pub field: TransientStorageField,
+    /// Transient Storage Key
+    pub key: Word,
+    /// Transient Storage Value after the operation
+    pub value: Word,
+    /// Transient Storage Value before the operation
+    pub value_prev: Word,
+}
+
+impl fmt::Debug for TransientStorageOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TransientStorageOp { ")?;
+        f.write_fmt(format_args!(
+            "tx_id: {:?}, addr: {:?}, field: {:?}, key: {:?}, val_prev: 0x{:x}, val: 0x{:x}",
+            self.tx_id, self.address, self.field, self.key, self.value_prev, self.value
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl TransientStorageOp {
+    /// Create a new instance of a `TransientStorageOp` from it's components.
+    pub const fn new(
+        tx_id: usize,
+        address: Address,
+        key: Word,
+        value: Word,
+        value_prev: Word,
+    ) -> TransientStorageOp {
+        TransientStorageOp {
+            tx_id,
+            address,
+            field: TransientStorageField::Slot,
+            key,
+            value,
+            value_prev,
+        }
+    }
+
+    /// Create a new instance of a `TransientStorageOp` on the flag of an
+    /// account created in the current tx.
+    pub fn new_account_created(
+        tx_id: usize,
+        address: Address,
+        value: bool,
+        value_prev: bool,
+    ) -> TransientStorageOp {
+        TransientStorageOp {
+            tx_id,
+            address,
+            field: TransientStorageField::AccountCreated,
+            key: Word::zero(),
+            value: Word::from(value as u64),
+            value_prev: Word::from(value_prev as u64),
+        }
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::TransientStorage
+    }
+}
+
+impl Op for TransientStorageOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TransientStorage(self)
+    }
+
+    fn reverse(&self) -> Self {
+        let mut rev = self.clone();
+        swap(&mut rev.value, &mut rev.value_prev);
+        rev
+    }
+}
+
+impl PartialOrd for TransientStorageOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TransientStorageOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.tx_id, &self.address, &self.field, &self.key).cmp(&(
+            &other.tx_id,
+            &other.address,
+            &other.field,
+            &other.key,
+        ))
+    }
+}
+
 /// Represents a change in the Account AccessList implied by a `BeginTx`,
 /// `EXTCODECOPY`, `EXTCODESIZE`, `EXTCODEHASH` `BALANCE`, `SELFDESTRUCT`,
 /// `*CALL`* or `CREATE*` step.
@@ -947,6 +1064,8 @@ pub enum OpEnum {
     TxReceipt(TxReceiptOp),
     /// TxLog
     TxLog(TxLogOp),
+    /// TransientStorage
+    TransientStorage(TransientStorageOp),
     /// Start
     Start(StartOp),
 }
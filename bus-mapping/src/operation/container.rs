@@ -1,7 +1,7 @@
 use super::{
     AccountOp, CallContextOp, MemoryOp, Op, OpEnum, Operation, RWCounter, StackOp, StartOp,
-    StorageOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxLogOp, TxReceiptOp,
-    TxRefundOp, RW,
+    StorageOp, Target, TransientStorageOp, TxAccessListAccountOp, TxAccessListAccountStorageOp,
+    TxLogOp, TxReceiptOp, TxRefundOp, RW,
 };
 use crate::exec_trace::OperationRef;
 use itertools::Itertools;
@@ -42,6 +42,8 @@ pub struct OperationContainer {
     pub tx_receipt: Vec<Operation<TxReceiptOp>>,
     /// Operations of TxLogOp
     pub tx_log: Vec<Operation<TxLogOp>>,
+    /// Operations of TransientStorageOp
+    pub transient_storage: Vec<Operation<TransientStorageOp>>,
     /// Operations of Start
     pub start: Vec<Operation<StartOp>>,
 }
@@ -67,10 +69,46 @@ impl OperationContainer {
             call_context: Vec::new(),
             tx_receipt: Vec::new(),
             tx_log: Vec::new(),
+            transient_storage: Vec::new(),
             start: Vec::new(),
         }
     }
 
+    /// Numbers of the operations of each target, to truncate the container back to them.
+    pub fn lens(&self) -> [usize; 12] {
+        [
+            self.memory.len(),
+            self.stack.len(),
+            self.storage.len(),
+            self.tx_access_list_account.len(),
+            self.tx_access_list_account_storage.len(),
+            self.tx_refund.len(),
+            self.account.len(),
+            self.call_context.len(),
+            self.tx_receipt.len(),
+            self.tx_log.len(),
+            self.transient_storage.len(),
+            self.start.len(),
+        ]
+    }
+
+    /// Drop the operations inserted after the container had the `lens` operations of each
+    /// target, as returned by [`OperationContainer::lens`].
+    pub fn truncate(&mut self, lens: [usize; 12]) {
+        self.memory.truncate(lens[0]);
+        self.stack.truncate(lens[1]);
+        self.storage.truncate(lens[2]);
+        self.tx_access_list_account.truncate(lens[3]);
+        self.tx_access_list_account_storage.truncate(lens[4]);
+        self.tx_refund.truncate(lens[5]);
+        self.account.truncate(lens[6]);
+        self.call_context.truncate(lens[7]);
+        self.tx_receipt.truncate(lens[8]);
+        self.tx_log.truncate(lens[9]);
+        self.transient_storage.truncate(lens[10]);
+        self.start.truncate(lens[11]);
+    }
+
     /// Inserts an [`Operation`] into the  container returning a lightweight
     /// reference to it in the form of an [`OperationRef`] which points to the
     /// location of the inserted operation inside the corresponding container
@@ -161,6 +199,14 @@ impl OperationContainer {
                 self.tx_log.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::TxLog, self.tx_log.len() - 1))
             }
+            OpEnum::TransientStorage(op) => {
+                self.transient_storage.push(if reversible {
+                    Operation::new_reversible(rwc, rw, op)
+                } else {
+                    Operation::new(rwc, rw, op)
+                });
+                OperationRef::from((Target::TransientStorage, self.transient_storage.len() - 1))
+            }
             OpEnum::Start(op) => {
                 self.start.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::Start, self.start.len() - 1))
@@ -185,6 +231,12 @@ impl OperationContainer {
     pub fn sorted_storage(&self) -> Vec<Operation<StorageOp>> {
         self.storage.iter().sorted().cloned().collect()
     }
+
+    /// Returns a sorted vector of all of the [`TransientStorageOp`]s contained
+    /// inside of the container.
+    pub fn sorted_transient_storage(&self) -> Vec<Operation<TransientStorageOp>> {
+        self.transient_storage.iter().sorted().cloned().collect()
+    }
 }
 
 #[cfg(test)]
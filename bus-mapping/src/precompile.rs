@@ -1,22 +1,36 @@
 //! precompile helpers
 
 use eth_types::{evm_types::GasCost, Address, ToBigEndian, Word};
+use num::{BigUint, Zero};
 use revm_precompile::{Precompile, PrecompileError, Precompiles};
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
 use crate::circuit_input_builder::{EcMulOp, EcPairingOp, N_BYTES_PER_PAIR, N_PAIRING_PER_OP};
 
+/// Address of the last precompiled contract. The point evaluation precompile (0x0a) of cancun is
+/// not supported, and blocks touching it are rejected with `Error::UnsupportedPrecompile`.
+pub const LAST_PRECOMPILE_ADDRESS: u8 = PrecompileCalls::Blake2F as u8;
+
+/// Address of the point evaluation precompile of cancun.
+pub const POINT_EVALUATION_ADDRESS: u8 = 0x0a;
+
+/// Check if address is the point evaluation precompile, which is active from cancun but not
+/// supported by the circuits.
+pub fn is_point_evaluation(address: &Address) -> bool {
+    address.0[0..19] == [0u8; 19] && address.0[19] == POINT_EVALUATION_ADDRESS
+}
+
 /// Check if address is a precompiled or not.
 pub fn is_precompiled(address: &Address) -> bool {
-    Precompiles::berlin()
-        .get(address.as_fixed_bytes())
-        .is_some()
+    address.0[0..19] == [0u8; 19] && (1..=LAST_PRECOMPILE_ADDRESS).contains(&address.0[19])
 }
 
 pub(crate) fn execute_precompiled(
     address: &Address,
     input: &[u8],
     gas: u64,
+    modexp_size_limit: usize,
 ) -> (Vec<u8>, u64, bool) {
     let Some(Precompile::Standard(precompile_fn)) = Precompiles::berlin()
         .get(address.as_fixed_bytes())  else {
@@ -33,9 +47,6 @@ pub(crate) fn execute_precompiled(
                 // Revm behavior is different from scroll evm,
                 // so we need to override the behavior of invalid input
                 match PrecompileCalls::from(address.0[19]) {
-                    PrecompileCalls::Blake2F | PrecompileCalls::Ripemd160 => {
-                        (vec![], gas, false, false)
-                    }
                     PrecompileCalls::Bn128Pairing => {
                         if input.len() > N_PAIRING_PER_OP * N_BYTES_PER_PAIR {
                             (vec![], gas, false, false)
@@ -44,7 +55,8 @@ pub(crate) fn execute_precompiled(
                         }
                     }
                     PrecompileCalls::Modexp => {
-                        let (input_valid, [_, _, modulus_len]) = ModExpAuxData::check_input(input);
+                        let (input_valid, [_, _, modulus_len]) =
+                            ModExpAuxData::check_input(input, modexp_size_limit);
                         if input_valid {
                             // detect some edge cases like modulus = 0
                             assert_eq!(modulus_len.as_usize(), return_value.len());
@@ -158,19 +170,19 @@ impl PrecompileCalls {
         (*self).into()
     }
 
-    /// Maximum length of input bytes considered for the precompile call.
+    /// Maximum length of input bytes considered for the precompile call. The length taken by
+    /// modexp depends on its length header, see [`ModExpAuxData::input_len`].
     pub fn input_len(&self) -> Option<usize> {
         match self {
             Self::Ecrecover | Self::Bn128Add => Some(128),
             Self::Bn128Mul => Some(96),
-            Self::Modexp => Some(MODEXP_INPUT_LIMIT),
             _ => None,
         }
     }
 }
 
 /// Auxiliary data for Ecrecover
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcrecoverAuxData {
     /// Keccak hash of the message being signed.
     pub msg_hash: Word,
@@ -226,24 +238,31 @@ impl EcrecoverAuxData {
     }
 }
 
-/// size limit of modexp
-pub const MODEXP_SIZE_LIMIT: usize = 32;
-/// size of input limit
-pub const MODEXP_INPUT_LIMIT: usize = 192;
+/// default size limit of modexp, i.e. the maximum byte length of base, exponent and modulus
+pub const DEFAULT_MODEXP_SIZE_LIMIT: usize = 512;
+/// upper bound of the modexp size limit, as the modexp gadget represents the input len
+/// `96 + 3 * limit` in 11 bits
+pub const MAX_MODEXP_SIZE_LIMIT: usize = 650;
+/// size of input limit of modexp for the given size limit, i.e. the length header and the
+/// base, exponent and modulus of the maximum size
+pub const fn modexp_input_limit(size_limit: usize) -> usize {
+    96 + 3 * size_limit
+}
 
 /// Auxiliary data for Modexp
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ModExpAuxData {
     /// The specified len of inputs: [base, exp, modulus]
     pub input_lens: [Word; 3],
-    /// Input value [base, exp, modulus], limited to SIZE_LIMIT
-    pub inputs: [[u8; MODEXP_SIZE_LIMIT]; 3],
+    /// Input value [base, exp, modulus] as big-endian bytes of the specified lens, empty if the
+    /// input is invalid
+    pub inputs: [Vec<u8>; 3],
     /// Input valid.
     pub valid: bool,
     /// len of output, limited to lens of moduls, but can be 0
     pub output_len: usize,
-    /// output of modexp.
-    pub output: [u8; MODEXP_SIZE_LIMIT],
+    /// result of modexp, of the len of modulus, empty if the input is invalid
+    pub output: Vec<u8>,
     /// Input to the modexp call.
     pub input_bytes: Vec<u8>,
     /// Output bytes from the modexp call.
@@ -253,27 +272,17 @@ pub struct ModExpAuxData {
 }
 
 impl ModExpAuxData {
-    /// If mem is smaller than U256, left pad zero
-    /// Or else, keep the least 32bytes.
-    fn parse_memory_to_value(mem: &[u8]) -> [u8; MODEXP_SIZE_LIMIT] {
-        let mut value_bytes = [0u8; MODEXP_SIZE_LIMIT];
-        if !mem.is_empty() {
-            let copy_len = mem.len().min(MODEXP_SIZE_LIMIT);
-            let src_offset = mem.len() - copy_len;
-            let dst_offset = MODEXP_SIZE_LIMIT - copy_len;
-            value_bytes.as_mut_slice()[dst_offset..].copy_from_slice(&mem[src_offset..]);
-        }
-        value_bytes
-    }
-
-    /// check input
-    pub fn check_input(input: &[u8]) -> (bool, [Word; 3]) {
-        let mut i = input.chunks(32);
-        let base_len = Word::from_big_endian(i.next().unwrap_or(&[]));
-        let exp_len = Word::from_big_endian(i.next().unwrap_or(&[]));
-        let modulus_len = Word::from_big_endian(i.next().unwrap_or(&[]));
-
-        let limit = Word::from(MODEXP_SIZE_LIMIT);
+    /// check input against the size limit, i.e. `CircuitsParams::max_modexp_size`
+    pub fn check_input(input: &[u8], size_limit: usize) -> (bool, [Word; 3]) {
+        // the header is right padded with zeros if the input is shorter than 96 bytes
+        let mut header = input[..input.len().min(96)].to_vec();
+        header.resize(96, 0);
+        let mut i = header.chunks(32);
+        let base_len = Word::from_big_endian(i.next().unwrap());
+        let exp_len = Word::from_big_endian(i.next().unwrap());
+        let modulus_len = Word::from_big_endian(i.next().unwrap());
+
+        let limit = Word::from(size_limit);
 
         let input_valid = base_len <= limit && exp_len <= limit && modulus_len <= limit;
         log::debug!("modexp base_len {base_len} exp_len {exp_len} modulus_len {modulus_len}");
@@ -283,45 +292,69 @@ impl ModExpAuxData {
         (input_valid, [base_len, exp_len, modulus_len])
     }
 
-    /// Create a new instance of modexp auxiliary data.
-    pub fn new(input: &[u8], output: &[u8], return_bytes: &[u8]) -> Self {
-        let mut resized_input = input.to_vec();
-
-        let (input_valid, [base_len, exp_len, modulus_len]) = Self::check_input(input);
+    /// The number of bytes taken by modexp from `call_data_len` bytes of call data, given the
+    /// length header in the first 96 bytes of the call data. Only the header is taken for an
+    /// invalid input.
+    pub fn input_len(header: &[u8], call_data_len: usize, size_limit: usize) -> usize {
+        let (input_valid, input_lens) = Self::check_input(header, size_limit);
+        let expected_len = 96
+            + if input_valid {
+                input_lens.iter().map(Word::as_usize).sum::<usize>()
+            } else {
+                0
+            };
+        expected_len.min(call_data_len)
+    }
 
-        let base_mem_len = if input_valid { base_len.as_usize() } else { 0 };
-        let exp_mem_len = if input_valid { exp_len.as_usize() } else { 0 };
-        let modulus_mem_len = if input_valid {
-            modulus_len.as_usize()
+    /// Compute `base ^ exp (mod modulus)` as big-endian bytes of the len of modulus. The result is
+    /// zero for a zero modulus.
+    pub fn modexp(base: &[u8], exp: &[u8], modulus: &[u8]) -> Vec<u8> {
+        let modulus_value = BigUint::from_bytes_be(modulus);
+        let result = if modulus_value.is_zero() {
+            BigUint::zero()
         } else {
-            0
+            BigUint::from_bytes_be(base).modpow(&BigUint::from_bytes_be(exp), &modulus_value)
         };
+        let result = result.to_bytes_be();
 
-        let (base, exp, modulus) = if base_mem_len == 0 && modulus_mem_len == 0 {
-            // special case
-            ([0u8; 32], [0u8; 32], [0u8; 32])
-        } else {
-            // In non scroll mode, this can be dangerous.
-            // If base and mod are all 0, exp can be very huge.
-            resized_input.resize(96 + base_mem_len + exp_mem_len + modulus_mem_len, 0);
-            let mut cur_input_begin = &resized_input[96..];
+        let mut output = vec![0u8; modulus.len()];
+        if !result.iter().all(|&byte| byte == 0) {
+            output[modulus.len() - result.len()..].copy_from_slice(&result);
+        }
+        output
+    }
+
+    /// Create a new instance of modexp auxiliary data.
+    pub fn new(input: &[u8], output: &[u8], return_bytes: &[u8], size_limit: usize) -> Self {
+        let (input_valid, [base_len, exp_len, modulus_len]) = Self::check_input(input, size_limit);
+
+        let (inputs, result) = if input_valid {
+            let input_lens = [base_len, exp_len, modulus_len].map(|len| len.as_usize());
+            // the bytes beyond the call data are zero
+            let mut resized_input = input.to_vec();
+            resized_input.resize(96 + input_lens.iter().sum::<usize>(), 0);
 
-            let base = Self::parse_memory_to_value(&cur_input_begin[..base_mem_len]);
-            cur_input_begin = &cur_input_begin[base_mem_len..];
-            let exp = Self::parse_memory_to_value(&cur_input_begin[..exp_mem_len]);
-            cur_input_begin = &cur_input_begin[exp_mem_len..];
-            let modulus = Self::parse_memory_to_value(&cur_input_begin[..modulus_mem_len]);
-            (base, exp, modulus)
+            let mut cur_input_begin = &resized_input[96..];
+            let inputs = input_lens.map(|len| {
+                let value = cur_input_begin[..len].to_vec();
+                cur_input_begin = &cur_input_begin[len..];
+                value
+            });
+            let result = Self::modexp(&inputs[0], &inputs[1], &inputs[2]);
+            if !output.is_empty() {
+                assert_eq!(output, result, "modexp result mismatch");
+            }
+            (inputs, result)
+        } else {
+            (Default::default(), vec![])
         };
-        let output_len = output.len();
-        let output = Self::parse_memory_to_value(output);
 
         Self {
             valid: input_valid,
             input_lens: [base_len, exp_len, modulus_len],
-            inputs: [base, exp, modulus],
-            output,
-            output_len,
+            inputs,
+            output: result,
+            output_len: output.len(),
             input_bytes: input.to_vec(),
             output_bytes: output.to_vec(),
             return_bytes: return_bytes.to_vec(),
@@ -329,8 +362,16 @@ impl ModExpAuxData {
     }
 }
 
+/// size of ripemd160 digest, which is left-padded with zeros to 32 bytes in the output
+pub const RIPEMD160_DIGEST_LEN: usize = 20;
+
+/// size of blake2f input
+pub const BLAKE2F_INPUT_LEN: usize = 213;
+/// size of blake2f output
+pub const BLAKE2F_OUTPUT_LEN: usize = 64;
+
 /// Auxiliary data for EcAdd, i.e. P + Q = R
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcAddAuxData {
     /// x co-ordinate of the first point.
     pub p_x: Word,
@@ -375,7 +416,7 @@ impl EcAddAuxData {
 }
 
 /// Auxiliary data for EcMul, i.e. s * P = R
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcMulAuxData {
     /// x co-ordinate of the point.
     pub p_x: Word,
@@ -421,31 +462,9 @@ impl EcMulAuxData {
     }
 }
 
-/// Auxiliary data for EcPairing.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct EcPairingAuxData(pub EcPairingOp);
-
-/// Erroneous bytes passed to the EcPairing precompile call.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum EcPairingError {
-    /// the calldatalength passed to EcPairing precompile call is expected to be:
-    /// 1. len(input) <= 768
-    /// 2. len(input) % 192 == 0
-    InvalidInputLen(Vec<u8>),
-}
-
 /// Auxiliary data attached to an internal state for precompile verification.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PrecompileAuxData {
-    /// Base precompile (used for SHA256, RIPEMD-160 and BLAKE2F).
-    Base {
-        /// input bytes to the identity call.
-        input_bytes: Vec<u8>,
-        /// output bytes from the identity call.
-        output_bytes: Vec<u8>,
-        /// bytes returned back to the caller from the identity call.
-        return_bytes: Vec<u8>,
-    },
     /// Identity.
     Identity {
         /// input bytes to the identity call.
@@ -464,6 +483,24 @@ pub enum PrecompileAuxData {
         /// bytes returned back to the caller from the sha256 call.
         return_bytes: Vec<u8>,
     },
+    /// RIPEMD-160
+    Ripemd160 {
+        /// input bytes to the ripemd160 call.
+        input_bytes: Vec<u8>,
+        /// output bytes from the ripemd160 call.
+        output_bytes: Vec<u8>,
+        /// bytes returned back to the caller from the ripemd160 call.
+        return_bytes: Vec<u8>,
+    },
+    /// Blake2F
+    Blake2F {
+        /// input bytes to the blake2f call.
+        input_bytes: Vec<u8>,
+        /// output bytes from the blake2f call.
+        output_bytes: Vec<u8>,
+        /// bytes returned back to the caller from the blake2f call.
+        return_bytes: Vec<u8>,
+    },
     /// Ecrecover.
     Ecrecover(EcrecoverAuxData),
     /// Modexp.
@@ -24,15 +24,9 @@ lazy_static! {
 const VALUE_ZERO: Word = Word::zero();
 
 /// Memory storage for contract code by code hash.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CodeDB(pub HashMap<Hash, Vec<u8>>);
 
-impl Clone for CodeDB {
-    fn clone(&self) -> Self {
-        CodeDB(self.0.clone())
-    }
-}
-
 impl Default for CodeDB {
     fn default() -> Self {
         Self::new()
@@ -135,6 +129,11 @@ pub struct StateDB {
     // state before current transaction, to calculate gas cost for some opcodes like sstore.
     // So both dirty storage and committed storage are needed.
     dirty_storage: HashMap<(Address, Word), Word>,
+    // Transient storage (EIP-1153), which is discarded in `commit_tx`.
+    transient_storage: HashMap<(Address, Word), Word>,
+    // Accounts created in the current transaction, which `SELFDESTRUCT` still deletes after
+    // EIP-6780. Discarded in `commit_tx`.
+    created_accounts: HashSet<Address>,
     // Accounts that have been through `SELFDESTRUCT` under the situation that `is_persistent` is
     // `true`. These accounts will be reset once `commit_tx` is called.
     destructed_account: HashSet<Address>,
@@ -250,6 +249,36 @@ impl StateDB {
         self.dirty_storage.insert((*addr, *key), *value);
     }
 
+    /// Get a reference to the transient storage value at `addr` and `key`.
+    /// Returns false and a zero [`Word`] when the `key` wasn't written in the
+    /// current transaction.
+    pub fn get_transient_storage(&self, addr: &Address, key: &Word) -> (bool, &Word) {
+        match self.transient_storage.get(&(*addr, *key)) {
+            Some(v) => (true, v),
+            None => (false, &VALUE_ZERO),
+        }
+    }
+
+    /// Set transient storage value at `addr` and `key`.
+    pub fn set_transient_storage(&mut self, addr: &Address, key: &Word, value: &Word) {
+        self.transient_storage.insert((*addr, *key), *value);
+    }
+
+    /// Returns true if the account at `addr` is created in the current
+    /// transaction.
+    pub fn is_account_created(&self, addr: &Address) -> bool {
+        self.created_accounts.contains(addr)
+    }
+
+    /// Set whether the account at `addr` is created in the current transaction.
+    pub fn set_account_created(&mut self, addr: &Address, created: bool) {
+        if created {
+            self.created_accounts.insert(*addr);
+        } else {
+            self.created_accounts.remove(addr);
+        }
+    }
+
     /// Get balance of account with the given address.
     pub fn get_balance(&self, addr: &Address) -> Word {
         let (_, account) = self.get_account(addr);
@@ -323,7 +352,8 @@ impl StateDB {
         self.refund = value;
     }
 
-    /// Clear access list and refund, and commit dirty storage.
+    /// Clear access list, refund and transient storage, and commit dirty
+    /// storage.
     /// It should be invoked before processing
     /// with new transaction with the same [`StateDB`].
     pub fn commit_tx(&mut self) {
@@ -334,6 +364,8 @@ impl StateDB {
             *ptr = value;
         }
         self.dirty_storage = HashMap::new();
+        self.transient_storage = HashMap::new();
+        self.created_accounts = HashSet::new();
         self.touched_account = HashSet::new();
         for addr in self.destructed_account.clone() {
             let (_, account) = self.get_account_mut(&addr);
@@ -402,4 +434,31 @@ mod statedb_tests {
         assert!(found);
         assert_eq!(value, &Word::from(102));
     }
+
+    #[test]
+    fn transient_storage_cleared_on_commit() {
+        let addr = address!("0x0000000000000000000000000000000000000001");
+        let mut statedb = StateDB::new();
+
+        let (found, value) = statedb.get_transient_storage(&addr, &Word::from(1));
+        assert!(!found);
+        assert_eq!(value, &Word::zero());
+
+        statedb.set_transient_storage(&addr, &Word::from(1), &Word::from(42));
+        let (found, value) = statedb.get_transient_storage(&addr, &Word::from(1));
+        assert!(found);
+        assert_eq!(value, &Word::from(42));
+        // Transient storage is not visible through persistent storage.
+        let (found, _) = statedb.get_storage(&addr, &Word::from(1));
+        assert!(!found);
+
+        statedb.set_account_created(&addr, true);
+        assert!(statedb.is_account_created(&addr));
+
+        statedb.commit_tx();
+        let (found, value) = statedb.get_transient_storage(&addr, &Word::from(1));
+        assert!(!found);
+        assert_eq!(value, &Word::zero());
+        assert!(!statedb.is_account_created(&addr));
+    }
 }
@@ -231,7 +231,7 @@ impl FromStr for OpcodeWithData {
                 return Err(err());
             }
 
-            // Parse `PUSH0` below only for shanghai (otherwise as an invalid opcode).
+            // `PUSH0` has no data and is parsed below as a plain opcode.
             if n > 0 {
                 let value = if n_value[1].starts_with("0x") {
                     Word::from_str_radix(&n_value[1][2..], 16)
@@ -547,7 +547,7 @@ impl_other_opcodes! {
     (op_staticcall, STATICCALL, gas: G, address: A, args_offset: AO, args_size: AS, ret_offset: RO, ret_size: RS),
     (op_revert, REVERT, offset: O, size: S),
     // (op_invalid, INVALID), ignored
-    // (op_selfdestruct, SELFDESTRUCT), ignored
+    (op_selfdestruct, SELFDESTRUCT, address: A),
 }
 
 #[cfg(test)]
@@ -595,7 +595,6 @@ mod tests {
         assert_eq!(code.code, code2.code);
     }
 
-    #[cfg(feature = "shanghai")]
     #[test]
     fn test_asm_disasm_for_shanghai() {
         let code = bytecode! {
@@ -4,12 +4,14 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 
 pub mod block_utils;
+pub mod forks;
 pub mod gas_utils;
 pub mod memory;
 pub mod opcode_ids;
 pub mod stack;
 pub mod storage;
 
+pub use forks::{Fork, ForkActivation, ForkRules, ForkSchedule, FORK_SCHEDULE};
 pub use memory::{Memory, MemoryAddress, MemoryRef};
 pub use opcode_ids::OpcodeId;
 pub use stack::{Stack, StackAddress};
@@ -72,38 +74,56 @@ pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: usize = 5;
 /// Gas stipend when CALL or CALLCODE is attached with value.
 pub const GAS_STIPEND_CALL_WITH_VALUE: u64 = 2300;
 
-#[cfg(feature = "shanghai")]
-mod gas_create {
-    // For EIP-3860, there are 2 special gas cost constraints in geth
-    // [gasCreate2Eip3860](https://github.com/ethereum/go-ethereum/blob/eb83e7c54021573eaceb14236af3a7a8c64f6027/core/vm/gas_table.go#L321)
-    // (similar for CREATE).
-    // 1. size <= 49152 (MaxInitCodeSize)
-    // 2. gasCost = memoryGasCost + (2 + 6) * ((size + 31) / 32) should not
-    //    overflow for Uint64.
-    // No need to constrain the second condition, since the maximum gas cost
-    // cannot overflow for Uint64 (36028809887100925 calculated by
-    // `memorySize = 0x1FFFFFFFE0` and `size = 49152`) if the first condition is
-    // satisfied.
-
-    /// Maximum init code size to permit in a creation transaction and create instructions.
-    pub const MAX_INIT_CODE_SIZE: u64 = 2 * super::MAX_CODE_SIZE;
-    /// Once per word of the init code when creating a contract.
-    pub const INIT_CODE_WORD_GAS: u64 = 2;
-    /// Gas per code word for CREATE.
-    pub const CREATE_GAS_PER_CODE_WORD: u64 = INIT_CODE_WORD_GAS;
-    /// Gas per code word for CREATE2.
-    pub const CREATE2_GAS_PER_CODE_WORD: u64 = INIT_CODE_WORD_GAS + super::GasCost::COPY_SHA3.0;
+// For EIP-3860, there are 2 special gas cost constraints in geth
+// [gasCreate2Eip3860](https://github.com/ethereum/go-ethereum/blob/eb83e7c54021573eaceb14236af3a7a8c64f6027/core/vm/gas_table.go#L321)
+// (similar for CREATE).
+// 1. size <= 49152 (MaxInitCodeSize)
+// 2. gasCost = memoryGasCost + (2 + 6) * ((size + 31) / 32) should not
+//    overflow for Uint64.
+// No need to constrain the second condition, since the maximum gas cost
+// cannot overflow for Uint64 (36028809887100925 calculated by
+// `memorySize = 0x1FFFFFFFE0` and `size = 49152`) if the first condition is
+// satisfied.
+
+/// Once per word of the init code when creating a contract (EIP-3860).
+pub const INIT_CODE_WORD_GAS: u64 = 2;
+
+/// Maximum init code size to permit in a creation transaction and create
+/// instructions, which is 0x1FFFFFFFE0 if not EIP-3860.
+pub const fn max_init_code_size(rules: &ForkRules) -> u64 {
+    if rules.is_shanghai {
+        2 * MAX_CODE_SIZE
+    } else {
+        MAX_EXPANDED_MEMORY_ADDRESS
+    }
+}
+
+/// Gas per word of the init code in a creation transaction, which is 0 if not
+/// EIP-3860.
+pub const fn init_code_word_gas(rules: &ForkRules) -> u64 {
+    if rules.is_shanghai {
+        INIT_CODE_WORD_GAS
+    } else {
+        0
+    }
 }
-#[cfg(not(feature = "shanghai"))]
-mod gas_create {
-    /// Maximum init code size (0x1FFFFFFFE0) if not EIP-3860.
-    pub use super::MAX_EXPANDED_MEMORY_ADDRESS as MAX_INIT_CODE_SIZE;
-    /// Gas per code word for CREATE if not EIP-3860.
-    pub const CREATE_GAS_PER_CODE_WORD: u64 = 0;
-    /// Gas per code word for CREATE2 if not EIP-3860.
-    pub const CREATE2_GAS_PER_CODE_WORD: u64 = super::GasCost::COPY_SHA3.0;
+
+/// Gas per code word for CREATE.
+pub const fn create_gas_per_code_word(rules: &ForkRules) -> u64 {
+    init_code_word_gas(rules)
 }
-pub use gas_create::*;
+
+/// Gas per code word for CREATE2.
+pub const fn create2_gas_per_code_word(rules: &ForkRules) -> u64 {
+    init_code_word_gas(rules) + GasCost::COPY_SHA3.0
+}
+
+/// Maximum init code size under [`ForkRules::DEFAULT`].
+pub const MAX_INIT_CODE_SIZE: u64 = max_init_code_size(&ForkRules::DEFAULT);
+/// Gas per code word for CREATE under [`ForkRules::DEFAULT`].
+pub const CREATE_GAS_PER_CODE_WORD: u64 = create_gas_per_code_word(&ForkRules::DEFAULT);
+/// Gas per code word for CREATE2 under [`ForkRules::DEFAULT`].
+pub const CREATE2_GAS_PER_CODE_WORD: u64 = create2_gas_per_code_word(&ForkRules::DEFAULT);
 
 /// Defines the gas consumption.
 #[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -205,6 +225,8 @@ impl GasCost {
     pub const PRECOMPILE_MODEXP_MIN: Self = Self(200);
     /// Base gas cost for precompile call: BLAKE2F
     pub const PRECOMPILE_BLAKE2F: Self = Self(0);
+    /// Per-round gas cost for precompile call: BLAKE2F
+    pub const PRECOMPILE_BLAKE2F_PER_ROUND: Self = Self(1);
     /// Gas cost per address in tx access list (EIP 2930)
     pub const ACCESS_LIST_PER_ADDRESS: Self = Self(2400);
     /// Gas cost per storage key in tx access list (EIP 2930)
@@ -1,11 +1,20 @@
 //! Helper constants and utility functions for block
 
-use crate::{U256, U64};
+use crate::{U256, U512, U64};
 use ethers_core::utils::keccak256;
 
 /// Maximum range of previous blocks allowed inside BLOCKHASH opcode
 pub const NUM_PREV_BLOCK_ALLOWED: u64 = 256;
 
+/// Maximum number of blobs a single EIP-4844 tx can carry
+pub const MAX_BLOBS_PER_TX: usize = 6;
+/// Version byte of a blob versioned hash, i.e. the first byte of the hash (EIP-4844)
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+/// Minimum blob base fee (EIP-4844)
+pub const MIN_BLOB_BASE_FEE: u64 = 1;
+/// Blob base fee update fraction (EIP-4844)
+pub const BLOB_BASE_FEE_UPDATE_FRACTION: u64 = 3338477;
+
 /// Calculate block hash by chain ID and block number (only for scroll).
 /// Return a pair of input and output.
 pub fn calculate_block_hash(chain_id: u64, block_number: U256) -> (Vec<u8>, U256) {
@@ -27,3 +36,36 @@ pub fn is_valid_block_number(block_number: U256, current_block_number: U256) ->
                 .checked_sub(NUM_PREV_BLOCK_ALLOWED.into())
                 .unwrap_or_default()
 }
+
+/// Calculate the blob base fee from the excess blob gas of a block, as
+/// `fake_exponential(MIN_BLOB_BASE_FEE, excess_blob_gas, BLOB_BASE_FEE_UPDATE_FRACTION)`
+/// defined in EIP-4844.
+pub fn calc_blob_base_fee(excess_blob_gas: u64) -> U256 {
+    let factor = U512::from(MIN_BLOB_BASE_FEE);
+    let numerator = U512::from(excess_blob_gas);
+    let denominator = U512::from(BLOB_BASE_FEE_UPDATE_FRACTION);
+
+    let mut i = U512::one();
+    let mut output = U512::zero();
+    let mut numerator_accum = factor * denominator;
+    while !numerator_accum.is_zero() {
+        output += numerator_accum;
+        numerator_accum = numerator_accum * numerator / (denominator * i);
+        i += U512::one();
+    }
+
+    U256::try_from(output / denominator).unwrap_or(U256::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_base_fee() {
+        assert_eq!(calc_blob_base_fee(0), U256::one());
+        assert_eq!(calc_blob_base_fee(2314057), U256::one());
+        assert_eq!(calc_blob_base_fee(2314058), U256::from(2));
+        assert_eq!(calc_blob_base_fee(10 * 1024 * 1024), U256::from(23));
+    }
+}
@@ -0,0 +1,233 @@
+//! Hard fork schedule, which decides the EVM rules in effect for each block.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+/// Hard forks whose rules are supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Fork {
+    /// Berlin (EIP-2929, EIP-2930)
+    Berlin,
+    /// London (EIP-1559, EIP-3198, EIP-3529)
+    London,
+    /// Scroll Archimedes
+    Archimedes,
+    /// Shanghai (EIP-3651, EIP-3855, EIP-3860)
+    Shanghai,
+    /// Cancun (EIP-1153, EIP-4844, EIP-5656, EIP-7516)
+    Cancun,
+}
+
+/// The condition under which a fork becomes active.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForkActivation {
+    /// Active from this block number on.
+    Block(u64),
+    /// Active from this block timestamp on.
+    Timestamp(u64),
+}
+
+impl ForkActivation {
+    /// Returns `true` if the fork is active for a block with the given number
+    /// and timestamp.
+    pub fn is_active(&self, number: u64, timestamp: u64) -> bool {
+        match *self {
+            Self::Block(block) => number >= block,
+            Self::Timestamp(time) => timestamp >= time,
+        }
+    }
+
+    /// Returns `true` if the fork is active from the genesis block on.
+    pub fn is_genesis(&self) -> bool {
+        matches!(self, Self::Block(0) | Self::Timestamp(0))
+    }
+}
+
+/// Activation of each fork, `None` for a fork that is never activated.
+///
+/// The default schedule activates at genesis every fork enabled by the cargo
+/// features (`shanghai`, `cancun` and `scroll` for Archimedes).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkSchedule {
+    /// Berlin activation
+    pub berlin: Option<ForkActivation>,
+    /// London activation
+    pub london: Option<ForkActivation>,
+    /// Scroll Archimedes activation
+    pub archimedes: Option<ForkActivation>,
+    /// Shanghai activation
+    pub shanghai: Option<ForkActivation>,
+    /// Cancun activation
+    pub cancun: Option<ForkActivation>,
+}
+
+impl Default for ForkSchedule {
+    fn default() -> Self {
+        let genesis = |enabled: bool| enabled.then_some(ForkActivation::Block(0));
+        Self {
+            berlin: genesis(true),
+            london: genesis(true),
+            archimedes: genesis(cfg!(feature = "scroll")),
+            shanghai: genesis(cfg!(feature = "shanghai")),
+            cancun: genesis(cfg!(feature = "cancun")),
+        }
+    }
+}
+
+impl ForkSchedule {
+    /// Returns the activation of the fork.
+    pub fn activation(&self, fork: Fork) -> Option<ForkActivation> {
+        match fork {
+            Fork::Berlin => self.berlin,
+            Fork::London => self.london,
+            Fork::Archimedes => self.archimedes,
+            Fork::Shanghai => self.shanghai,
+            Fork::Cancun => self.cancun,
+        }
+    }
+
+    /// Sets the activation of the fork.
+    pub fn with_activation(mut self, fork: Fork, activation: Option<ForkActivation>) -> Self {
+        let field = match fork {
+            Fork::Berlin => &mut self.berlin,
+            Fork::London => &mut self.london,
+            Fork::Archimedes => &mut self.archimedes,
+            Fork::Shanghai => &mut self.shanghai,
+            Fork::Cancun => &mut self.cancun,
+        };
+        *field = activation;
+        self
+    }
+
+    /// Returns `true` if the fork is active for a block with the given number
+    /// and timestamp.
+    pub fn is_active(&self, fork: Fork, number: u64, timestamp: u64) -> bool {
+        self.activation(fork)
+            .map_or(false, |activation| activation.is_active(number, timestamp))
+    }
+
+    /// Returns the rules in effect for a block with the given number and
+    /// timestamp.
+    pub fn rules(&self, number: u64, timestamp: u64) -> ForkRules {
+        ForkRules {
+            is_berlin: self.is_active(Fork::Berlin, number, timestamp),
+            is_london: self.is_active(Fork::London, number, timestamp),
+            is_archimedes: self.is_active(Fork::Archimedes, number, timestamp),
+            is_shanghai: self.is_active(Fork::Shanghai, number, timestamp),
+            is_cancun: self.is_active(Fork::Cancun, number, timestamp),
+        }
+    }
+
+    /// Returns the rules in effect for the genesis block, which are the most
+    /// restrictive ones of the schedule.
+    pub fn genesis_rules(&self) -> ForkRules {
+        self.rules(0, 0)
+    }
+
+    /// Returns the rules in effect once every fork of the schedule is active,
+    /// which are the least restrictive ones of the schedule.
+    pub fn latest_rules(&self) -> ForkRules {
+        self.rules(u64::MAX, u64::MAX)
+    }
+}
+
+/// Fork schedule of the chain being proven, which the circuit input builder and the circuits
+/// are configured with. It is read as json from the `FORK_SCHEDULE` env var, e.g.
+/// `{"berlin":{"block":0},"london":{"block":0},"shanghai":{"timestamp":1681338455}}`, and falls
+/// back to [`ForkSchedule::default`].
+pub static FORK_SCHEDULE: Lazy<ForkSchedule> = Lazy::new(|| match std::env::var("FORK_SCHEDULE") {
+    Ok(schedule) => serde_json::from_str(&schedule)
+        .unwrap_or_else(|err| panic!("FORK_SCHEDULE is not a valid fork schedule: {err}")),
+    Err(_) => ForkSchedule::default(),
+});
+
+/// The rules in effect for a block, see [`ForkSchedule::rules`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkRules {
+    /// Berlin is active
+    pub is_berlin: bool,
+    /// London is active
+    pub is_london: bool,
+    /// Scroll Archimedes is active
+    pub is_archimedes: bool,
+    /// Shanghai is active
+    pub is_shanghai: bool,
+    /// Cancun is active
+    pub is_cancun: bool,
+}
+
+impl ForkRules {
+    /// Rules of the default [`ForkSchedule`], which are the same for every
+    /// block.
+    pub const DEFAULT: Self = Self {
+        is_berlin: true,
+        is_london: true,
+        is_archimedes: cfg!(feature = "scroll"),
+        is_shanghai: cfg!(feature = "shanghai"),
+        is_cancun: cfg!(feature = "cancun"),
+    };
+
+    /// Returns `true` if the fork is active.
+    pub const fn is_active(&self, fork: Fork) -> bool {
+        match fork {
+            Fork::Berlin => self.is_berlin,
+            Fork::London => self.is_london,
+            Fork::Archimedes => self.is_archimedes,
+            Fork::Shanghai => self.is_shanghai,
+            Fork::Cancun => self.is_cancun,
+        }
+    }
+}
+
+impl Default for ForkRules {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod forks_tests {
+    use super::*;
+
+    #[test]
+    fn default_schedule_matches_default_rules() {
+        let schedule = ForkSchedule::default();
+        assert_eq!(schedule.genesis_rules(), ForkRules::DEFAULT);
+        assert_eq!(schedule.rules(1_000_000, 1_700_000_000), ForkRules::DEFAULT);
+    }
+
+    #[test]
+    fn rules_switch_at_fork_boundary() {
+        let schedule = ForkSchedule::default()
+            .with_activation(Fork::Shanghai, Some(ForkActivation::Timestamp(1000)))
+            .with_activation(Fork::Cancun, Some(ForkActivation::Block(20)));
+
+        assert!(!schedule.rules(10, 999).is_shanghai);
+        assert!(schedule.rules(10, 1000).is_shanghai);
+        assert!(!schedule.rules(19, 2000).is_cancun);
+        assert!(schedule.rules(20, 2000).is_cancun);
+        assert!(schedule.rules(0, 0).is_london);
+
+        let schedule = schedule.with_activation(Fork::Cancun, None);
+        assert!(!schedule.rules(u64::MAX, u64::MAX).is_cancun);
+    }
+
+    #[test]
+    fn parse_schedule_from_json() {
+        let schedule: ForkSchedule = serde_json::from_str(
+            r#"{"berlin":{"block":0},"london":{"block":0},"shanghai":{"timestamp":1000}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            schedule,
+            ForkSchedule {
+                berlin: Some(ForkActivation::Block(0)),
+                london: Some(ForkActivation::Block(0)),
+                archimedes: None,
+                shanghai: Some(ForkActivation::Timestamp(1000)),
+                cancun: None,
+            }
+        );
+    }
+}
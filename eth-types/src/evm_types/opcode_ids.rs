@@ -1,5 +1,8 @@
 //! Doc this
-use crate::{error::Error, evm_types::GasCost};
+use crate::{
+    error::Error,
+    evm_types::{Fork, ForkRules, GasCost},
+};
 use core::fmt::Debug;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -93,6 +96,12 @@ pub enum OpcodeId {
     MSIZE,
     /// `JUMPDEST`
     JUMPDEST,
+    /// `TLOAD`
+    TLOAD,
+    /// `TSTORE`
+    TSTORE,
+    /// `MCOPY`
+    MCOPY,
 
     // PUSHn
     /// `PUSH0`
@@ -281,6 +290,10 @@ pub enum OpcodeId {
     SELFBALANCE,
     /// `BASEFEE`
     BASEFEE,
+    /// `BLOBHASH`
+    BLOBHASH,
+    /// `BLOBBASEFEE`
+    BLOBBASEFEE,
     /// `SLOAD`
     SLOAD,
     /// `SSTORE`
@@ -317,16 +330,10 @@ pub enum OpcodeId {
 }
 
 impl OpcodeId {
-    #[cfg(feature = "shanghai")]
     /// Returns `true` if the `OpcodeId` is a `PUSHn` (including `PUSH0`).
     pub fn is_push(&self) -> bool {
         self.as_u8() >= Self::PUSH0.as_u8() && self.as_u8() <= Self::PUSH32.as_u8()
     }
-    #[cfg(not(feature = "shanghai"))]
-    /// Returns `true` if the `OpcodeId` is a `PUSHn`.
-    pub fn is_push(&self) -> bool {
-        self.as_u8() >= Self::PUSH1.as_u8() && self.as_u8() <= Self::PUSH32.as_u8()
-    }
 
     /// Returns `true` if the `OpcodeId` is a `PUSH1` .. `PUSH32` (excluding `PUSH0`).
     pub fn is_push_with_data(&self) -> bool {
@@ -425,6 +432,9 @@ impl OpcodeId {
             OpcodeId::PC => 0x58u8,
             OpcodeId::MSIZE => 0x59u8,
             OpcodeId::JUMPDEST => 0x5bu8,
+            OpcodeId::TLOAD => 0x5cu8,
+            OpcodeId::TSTORE => 0x5du8,
+            OpcodeId::MCOPY => 0x5eu8,
             OpcodeId::PUSH0 => 0x5fu8,
             OpcodeId::PUSH1 => 0x60u8,
             OpcodeId::PUSH2 => 0x61u8,
@@ -514,6 +524,8 @@ impl OpcodeId {
             OpcodeId::CHAINID => 0x46u8,
             OpcodeId::SELFBALANCE => 0x47u8,
             OpcodeId::BASEFEE => 0x48u8,
+            OpcodeId::BLOBHASH => 0x49u8,
+            OpcodeId::BLOBBASEFEE => 0x4au8,
             OpcodeId::SLOAD => 0x54u8,
             OpcodeId::SSTORE => 0x55u8,
             OpcodeId::GAS => 0x5au8,
@@ -592,6 +604,8 @@ impl OpcodeId {
             OpcodeId::CHAINID => GasCost::QUICK,
             OpcodeId::SELFBALANCE => GasCost::FAST,
             OpcodeId::BASEFEE => GasCost::QUICK,
+            OpcodeId::BLOBHASH => GasCost::FASTEST,
+            OpcodeId::BLOBBASEFEE => GasCost::QUICK,
             OpcodeId::POP => GasCost::QUICK,
             OpcodeId::MLOAD => GasCost::FASTEST,
             OpcodeId::MSTORE => GasCost::FASTEST,
@@ -604,6 +618,9 @@ impl OpcodeId {
             OpcodeId::MSIZE => GasCost::QUICK,
             OpcodeId::GAS => GasCost::QUICK,
             OpcodeId::JUMPDEST => GasCost::ONE,
+            OpcodeId::TLOAD => GasCost::WARM_ACCESS,
+            OpcodeId::TSTORE => GasCost::WARM_ACCESS,
+            OpcodeId::MCOPY => GasCost::FASTEST,
             OpcodeId::PUSH0 => GasCost::QUICK,
             OpcodeId::PUSH1 => GasCost::FASTEST,
             OpcodeId::PUSH2 => GasCost::FASTEST,
@@ -756,6 +773,8 @@ impl OpcodeId {
             OpcodeId::CHAINID => (1, 1024),
             OpcodeId::SELFBALANCE => (1, 1024),
             OpcodeId::BASEFEE => (1, 1024),
+            OpcodeId::BLOBHASH => (0, 1023),
+            OpcodeId::BLOBBASEFEE => (1, 1024),
             OpcodeId::POP => (0, 1023),
             OpcodeId::MLOAD => (0, 1023),
             OpcodeId::MSTORE => (0, 1022),
@@ -768,6 +787,9 @@ impl OpcodeId {
             OpcodeId::MSIZE => (1, 1024),
             OpcodeId::GAS => (1, 1024),
             OpcodeId::JUMPDEST => (0, 1024),
+            OpcodeId::TLOAD => (0, 1023),
+            OpcodeId::TSTORE => (0, 1022),
+            OpcodeId::MCOPY => (0, 1021),
             OpcodeId::PUSH0 => (1, 1024),
             OpcodeId::PUSH1 => (1, 1024),
             OpcodeId::PUSH2 => (1, 1024),
@@ -866,6 +888,7 @@ impl OpcodeId {
                 | OpcodeId::RETURNDATACOPY
                 | OpcodeId::CODECOPY
                 | OpcodeId::EXTCODECOPY
+                | OpcodeId::MCOPY
         )
     }
 
@@ -905,24 +928,48 @@ impl OpcodeId {
         }
     }
 
-    /// Returns the all valid opcodes.
-    pub fn valid_opcodes() -> Vec<Self> {
-        (u8::MIN..=u8::MAX).fold(vec![], |mut acc, val| {
-            if !matches!(val.into(), Self::INVALID(_)) {
-                acc.push(val.into());
+    /// Returns the fork which introduced the opcode, or `None` if it is
+    /// available in every supported fork.
+    pub fn fork(&self) -> Option<Fork> {
+        match self {
+            Self::BASEFEE => Some(Fork::London),
+            Self::PUSH0 => Some(Fork::Shanghai),
+            Self::TLOAD | Self::TSTORE | Self::MCOPY | Self::BLOBHASH | Self::BLOBBASEFEE => {
+                Some(Fork::Cancun)
             }
-            acc
-        })
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the opcode is defined under the given rules.
+    pub fn is_enabled(&self, rules: &ForkRules) -> bool {
+        !matches!(self, Self::INVALID(_)) && self.fork().map_or(true, |fork| rules.is_active(fork))
+    }
+
+    /// Returns the all valid opcodes under the given rules.
+    pub fn valid_opcodes_in(rules: &ForkRules) -> Vec<Self> {
+        (u8::MIN..=u8::MAX)
+            .map(Self::from)
+            .filter(|op| op.is_enabled(rules))
+            .collect()
+    }
+
+    /// Returns the all invalid opcodes under the given rules.
+    pub fn invalid_opcodes_in(rules: &ForkRules) -> Vec<Self> {
+        (u8::MIN..=u8::MAX)
+            .filter(|&val| !Self::from(val).is_enabled(rules))
+            .map(Self::INVALID)
+            .collect()
     }
 
-    /// Returns the all invalid opcodes.
+    /// Returns the all valid opcodes under [`ForkRules::DEFAULT`].
+    pub fn valid_opcodes() -> Vec<Self> {
+        Self::valid_opcodes_in(&ForkRules::DEFAULT)
+    }
+
+    /// Returns the all invalid opcodes under [`ForkRules::DEFAULT`].
     pub fn invalid_opcodes() -> Vec<Self> {
-        (u8::MIN..=u8::MAX).fold(vec![], |mut acc, val| {
-            if matches!(val.into(), Self::INVALID(_)) {
-                acc.push(Self::INVALID(val));
-            }
-            acc
-        })
+        Self::invalid_opcodes_in(&ForkRules::DEFAULT)
     }
 }
 
@@ -969,7 +1016,9 @@ impl From<u8> for OpcodeId {
             0x58u8 => OpcodeId::PC,
             0x59u8 => OpcodeId::MSIZE,
             0x5bu8 => OpcodeId::JUMPDEST,
-            #[cfg(feature = "shanghai")]
+            0x5cu8 => OpcodeId::TLOAD,
+            0x5du8 => OpcodeId::TSTORE,
+            0x5eu8 => OpcodeId::MCOPY,
             0x5fu8 => OpcodeId::PUSH0,
             0x60u8 => OpcodeId::PUSH1,
             0x61u8 => OpcodeId::PUSH2,
@@ -1060,6 +1109,8 @@ impl From<u8> for OpcodeId {
             0x47u8 => OpcodeId::SELFBALANCE,
             #[cfg(not(feature = "scroll"))]
             0x48u8 => OpcodeId::BASEFEE,
+            0x49u8 => OpcodeId::BLOBHASH,
+            0x4au8 => OpcodeId::BLOBBASEFEE,
             0x54u8 => OpcodeId::SLOAD,
             0x55u8 => OpcodeId::SSTORE,
             0x5au8 => OpcodeId::GAS,
@@ -1126,10 +1177,10 @@ impl FromStr for OpcodeId {
             "PC" => OpcodeId::PC,
             "MSIZE" => OpcodeId::MSIZE,
             "JUMPDEST" => OpcodeId::JUMPDEST,
-            #[cfg(feature = "shanghai")]
+            "TLOAD" => OpcodeId::TLOAD,
+            "TSTORE" => OpcodeId::TSTORE,
+            "MCOPY" => OpcodeId::MCOPY,
             "PUSH0" => OpcodeId::PUSH0,
-            #[cfg(not(feature = "shanghai"))]
-            "PUSH0" => OpcodeId::INVALID(0x5f),
             "PUSH1" => OpcodeId::PUSH1,
             "PUSH2" => OpcodeId::PUSH2,
             "PUSH3" => OpcodeId::PUSH3,
@@ -1239,8 +1290,8 @@ impl FromStr for OpcodeId {
             "BASEFEE" => OpcodeId::BASEFEE,
             #[cfg(feature = "scroll")]
             "BASEFEE" => OpcodeId::INVALID(0x48),
-            "TLOAD" => OpcodeId::INVALID(0xb3),
-            "TSTORE" => OpcodeId::INVALID(0xb4),
+            "BLOBHASH" => OpcodeId::BLOBHASH,
+            "BLOBBASEFEE" => OpcodeId::BLOBBASEFEE,
             _ => {
                 // Parse an invalid opcode value as reported by geth
                 lazy_static! {
@@ -1282,13 +1333,7 @@ mod opcode_ids_tests {
 
     #[test]
     fn push_n() {
-        #[cfg(feature = "shanghai")]
         assert!(matches!(OpcodeId::push_n(0), Ok(OpcodeId::PUSH0)));
-        #[cfg(not(feature = "shanghai"))]
-        assert!(matches!(
-            OpcodeId::push_n(0),
-            Err(Error::InvalidOpConversion)
-        ));
         assert!(matches!(OpcodeId::push_n(1), Ok(OpcodeId::PUSH1)));
         assert!(matches!(OpcodeId::push_n(10), Ok(OpcodeId::PUSH10)));
         assert!(matches!(
@@ -1299,10 +1344,7 @@ mod opcode_ids_tests {
 
     #[test]
     fn postfix() {
-        #[cfg(feature = "shanghai")]
         assert_eq!(OpcodeId::PUSH0.postfix(), Some(0));
-        #[cfg(not(feature = "shanghai"))]
-        assert_eq!(OpcodeId::PUSH0.postfix(), None);
         assert_eq!(OpcodeId::PUSH1.postfix(), Some(1));
         assert_eq!(OpcodeId::PUSH10.postfix(), Some(10));
         assert_eq!(OpcodeId::LOG2.postfix(), Some(2));
@@ -1317,4 +1359,65 @@ mod opcode_ids_tests {
         assert_eq!(OpcodeId::LOG2.data_len(), 0);
         assert_eq!(OpcodeId::CALLCODE.data_len(), 0);
     }
+
+    #[test]
+    fn transient_storage_opcodes() {
+        assert_eq!(OpcodeId::from(0x5c), OpcodeId::TLOAD);
+        assert_eq!(OpcodeId::from(0x5d), OpcodeId::TSTORE);
+        assert_eq!(OpcodeId::from_str("TSTORE").unwrap(), OpcodeId::TSTORE);
+        assert_eq!(OpcodeId::TLOAD.as_u8(), 0x5c);
+        assert_eq!(OpcodeId::TSTORE.as_u8(), 0x5d);
+    }
+
+    #[test]
+    fn mcopy_opcode() {
+        assert_eq!(OpcodeId::from(0x5e), OpcodeId::MCOPY);
+        assert_eq!(OpcodeId::from_str("MCOPY").unwrap(), OpcodeId::MCOPY);
+        assert_eq!(OpcodeId::MCOPY.as_u8(), 0x5e);
+        assert!(OpcodeId::MCOPY.has_memory_access());
+    }
+
+    #[test]
+    fn blob_opcodes() {
+        assert_eq!(OpcodeId::from(0x49), OpcodeId::BLOBHASH);
+        assert_eq!(OpcodeId::from(0x4a), OpcodeId::BLOBBASEFEE);
+        assert_eq!(OpcodeId::from_str("BLOBHASH").unwrap(), OpcodeId::BLOBHASH);
+        assert_eq!(OpcodeId::BLOBHASH.as_u8(), 0x49);
+        assert_eq!(OpcodeId::BLOBBASEFEE.as_u8(), 0x4a);
+    }
+
+    #[test]
+    fn opcodes_by_fork() {
+        let london = ForkRules {
+            is_berlin: true,
+            is_london: true,
+            is_archimedes: false,
+            is_shanghai: false,
+            is_cancun: false,
+        };
+        let shanghai = ForkRules {
+            is_shanghai: true,
+            ..london
+        };
+        let cancun = ForkRules {
+            is_cancun: true,
+            ..shanghai
+        };
+
+        assert!(!OpcodeId::PUSH0.is_enabled(&london));
+        assert!(OpcodeId::PUSH0.is_enabled(&shanghai));
+        assert!(!OpcodeId::MCOPY.is_enabled(&shanghai));
+        assert!(OpcodeId::MCOPY.is_enabled(&cancun));
+        assert!(OpcodeId::ADD.is_enabled(&london));
+        assert!(!OpcodeId::INVALID(0xfe).is_enabled(&cancun));
+
+        let invalid_in_london = OpcodeId::invalid_opcodes_in(&london);
+        assert!(invalid_in_london.contains(&OpcodeId::INVALID(0x5f)));
+        assert!(invalid_in_london.contains(&OpcodeId::INVALID(0x49)));
+        assert!(!OpcodeId::invalid_opcodes_in(&cancun).contains(&OpcodeId::INVALID(0x5f)));
+        assert_eq!(
+            OpcodeId::valid_opcodes_in(&cancun).len() + OpcodeId::invalid_opcodes_in(&cancun).len(),
+            256
+        );
+    }
 }
@@ -5,21 +5,24 @@ use crate::{
     AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash, ToBigEndian, ToLittleEndian,
     Word, U64,
 };
-use ethers_core::types::{
-    transaction::eip2718::TypedTransaction, Eip1559TransactionRequest, Eip2930TransactionRequest,
-    NameOrAddress, TransactionRequest, H256,
+use ethers_core::{
+    types::{
+        transaction::eip2718::TypedTransaction, Eip1559TransactionRequest,
+        Eip2930TransactionRequest, NameOrAddress, OtherFields, TransactionRequest, H256,
+    },
+    utils::rlp::RlpStream,
 };
 use halo2_proofs::halo2curves::{group::ff::PrimeField, secp256k1::Fq};
 use num::Integer;
 use num_bigint::BigUint;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Serialize, Serializer};
 use serde_with::serde_as;
 use sha3::{Digest, Keccak256};
 use std::collections::HashMap;
 use strum_macros::EnumIter;
 
 /// Tx type
-#[derive(Default, Debug, Copy, Clone, EnumIter, Serialize, PartialEq, Eq)]
+#[derive(Default, Debug, Copy, Clone, EnumIter, Serialize, PartialEq, Eq, Deserialize)]
 pub enum TxType {
     /// EIP 155 tx
     #[default]
@@ -32,6 +35,8 @@ pub enum TxType {
     Eip2930,
     /// L1 Message tx
     L1Msg,
+    /// EIP 4844 tx
+    Eip4844,
 }
 
 impl From<TxType> for usize {
@@ -62,11 +67,17 @@ impl TxType {
         matches!(*self, Self::Eip2930)
     }
 
+    /// If this type is EIP-4844 or not
+    pub fn is_eip4844_tx(&self) -> bool {
+        matches!(*self, Self::Eip4844)
+    }
+
     /// Get the type of transaction
     pub fn get_tx_type(tx: &crate::Transaction) -> Self {
         match tx.transaction_type {
             Some(x) if x == U64::from(1) => Self::Eip2930,
             Some(x) if x == U64::from(2) => Self::Eip1559,
+            Some(x) if x == U64::from(3) => Self::Eip4844,
             Some(x) if x == U64::from(0x7e) => Self::L1Msg,
             _ => {
                 if cfg!(feature = "scroll") {
@@ -104,6 +115,10 @@ impl TxType {
                 assert!(v <= 1);
                 v
             }
+            TxType::Eip4844 => {
+                assert!(v <= 1);
+                v
+            }
             TxType::L1Msg => {
                 unreachable!("L1 msg does not have signature")
             }
@@ -139,6 +154,7 @@ pub fn get_rlp_unsigned(tx: &crate::Transaction) -> Vec<u8> {
             let typed_tx: TypedTransaction = tx.into();
             typed_tx.rlp().to_vec()
         }
+        TxType::Eip4844 => rlp_eip4844(tx, false),
         TxType::L1Msg => {
             // L1 msg does not have signature
             vec![]
@@ -146,9 +162,66 @@ pub fn get_rlp_unsigned(tx: &crate::Transaction) -> Vec<u8> {
     }
 }
 
+/// Get the signed RLP bytes of a transaction
+pub fn get_rlp_signed(tx: &crate::Transaction) -> Vec<u8> {
+    match TxType::get_tx_type(tx) {
+        // ethers does not know about blob txs yet.
+        TxType::Eip4844 => rlp_eip4844(tx, true),
+        _ => tx.rlp().to_vec(),
+    }
+}
+
+/// Get the EIP-4844 fields (max_fee_per_blob_gas, blob_versioned_hashes) of a
+/// transaction. ethers keeps them in the `other` fields of the transaction.
+pub fn get_blob_fields(tx: &crate::Transaction) -> (Word, Vec<H256>) {
+    let max_fee_per_blob_gas = tx
+        .other
+        .get_deserialized::<Word>("maxFeePerBlobGas")
+        .and_then(Result::ok)
+        .unwrap_or_default();
+    let blob_versioned_hashes = tx
+        .other
+        .get_deserialized::<Vec<H256>>("blobVersionedHashes")
+        .and_then(Result::ok)
+        .unwrap_or_default();
+
+    (max_fee_per_blob_gas, blob_versioned_hashes)
+}
+
+/// RLP encoding of an EIP-4844 tx, i.e.
+/// 0x03 || rlp([chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas,
+/// gas_limit, to, value, data, access_list, max_fee_per_blob_gas,
+/// blob_versioned_hashes, (y_parity, r, s)])
+fn rlp_eip4844(tx: &crate::Transaction, signed: bool) -> Vec<u8> {
+    let (max_fee_per_blob_gas, blob_versioned_hashes) = get_blob_fields(tx);
+
+    let mut stream = RlpStream::new();
+    stream.begin_unbounded_list();
+    stream.append(&tx.chain_id.unwrap_or_default());
+    stream.append(&tx.nonce);
+    stream.append(&tx.max_priority_fee_per_gas.unwrap_or_default());
+    stream.append(&tx.max_fee_per_gas.unwrap_or_default());
+    stream.append(&tx.gas);
+    // blob txs can not be contract creations
+    stream.append(&tx.to.unwrap_or_default());
+    stream.append(&tx.value);
+    stream.append(&tx.input);
+    stream.append(&tx.access_list.clone().unwrap_or_default());
+    stream.append(&max_fee_per_blob_gas);
+    stream.append_list(&blob_versioned_hashes);
+    if signed {
+        stream.append(&tx.v);
+        stream.append(&tx.r);
+        stream.append(&tx.s);
+    }
+    stream.finalize_unbounded_list();
+
+    [&[TxType::Eip4844 as u8], stream.out().as_ref()].concat()
+}
+
 /// Definition of all of the data related to an account.
 #[serde_as]
-#[derive(PartialEq, Eq, Debug, Default, Clone, Serialize)]
+#[derive(PartialEq, Eq, Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Account {
     /// Address
     pub address: Address,
@@ -265,6 +338,10 @@ pub struct Transaction {
     pub call_data: Bytes,
     /// Access list
     pub access_list: Option<AccessList>,
+    /// Max fee per blob gas (EIP-4844)
+    pub max_fee_per_blob_gas: Word,
+    /// Blob versioned hashes (EIP-4844)
+    pub blob_versioned_hashes: Vec<H256>,
 
     /// "v" value of the transaction signature
     pub v: u64,
@@ -284,6 +361,21 @@ pub struct Transaction {
 
 impl From<&Transaction> for crate::Transaction {
     fn from(tx: &Transaction) -> crate::Transaction {
+        let (transaction_type, other) = if tx.tx_type.is_eip4844_tx() {
+            let mut other = OtherFields::default();
+            other.insert(
+                "maxFeePerBlobGas".to_string(),
+                serde_json::to_value(tx.max_fee_per_blob_gas).unwrap(),
+            );
+            other.insert(
+                "blobVersionedHashes".to_string(),
+                serde_json::to_value(&tx.blob_versioned_hashes).unwrap(),
+            );
+            (Some(U64::from(3)), other)
+        } else {
+            (None, OtherFields::default())
+        };
+
         crate::Transaction {
             from: tx.from,
             to: tx.to,
@@ -299,6 +391,8 @@ impl From<&Transaction> for crate::Transaction {
             r: tx.r,
             s: tx.s,
             hash: tx.hash,
+            transaction_type,
+            other,
             ..Default::default()
         }
     }
@@ -306,6 +400,7 @@ impl From<&Transaction> for crate::Transaction {
 
 impl From<&crate::Transaction> for Transaction {
     fn from(tx: &crate::Transaction) -> Transaction {
+        let (max_fee_per_blob_gas, blob_versioned_hashes) = get_blob_fields(tx);
         Transaction {
             tx_type: TxType::get_tx_type(tx),
             from: tx.from,
@@ -318,10 +413,12 @@ impl From<&crate::Transaction> for Transaction {
             gas_tip_cap: tx.max_fee_per_gas.unwrap_or_default(),
             call_data: tx.input.clone(),
             access_list: tx.access_list.clone(),
+            max_fee_per_blob_gas,
+            blob_versioned_hashes,
             v: tx.v.as_u64(),
             r: tx.r,
             s: tx.s,
-            rlp_bytes: tx.rlp().to_vec(),
+            rlp_bytes: get_rlp_signed(tx),
             rlp_unsigned_bytes: get_rlp_unsigned(tx),
             hash: tx.hash,
         }
@@ -373,7 +470,7 @@ impl Transaction {
 }
 
 /// GethData is a type that contains all the information of a Ethereum block
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct GethData {
     /// chain id
     pub chain_id: u64,
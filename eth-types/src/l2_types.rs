@@ -2,9 +2,10 @@
 
 use crate::{
     evm_types::{Gas, GasCost, OpcodeId, ProgramCounter},
+    geth_types::TxType,
     Block, GethExecError, GethExecStep, GethExecTrace, Hash, Transaction, Word, H256,
 };
-use ethers_core::types::{Address, Bytes, U256, U64};
+use ethers_core::types::{Address, Bytes, OtherFields, U256, U64};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -110,6 +111,12 @@ pub struct TransactionTrace {
     pub r: U256,
     /// signature s
     pub s: U256,
+    /// max fee per blob gas (EIP-4844 only)
+    #[serde(rename = "maxFeePerBlobGas", default)]
+    pub max_fee_per_blob_gas: U256,
+    /// blob versioned hashes (EIP-4844 only)
+    #[serde(rename = "blobVersionedHashes", default)]
+    pub blob_versioned_hashes: Vec<H256>,
 }
 
 impl TransactionTrace {
@@ -120,6 +127,18 @@ impl TransactionTrace {
         block_number: Option<U64>,
         transaction_index: Option<U64>,
     ) -> Transaction {
+        let mut other = OtherFields::default();
+        if self.type_ == TxType::Eip4844 as u8 {
+            other.insert(
+                "maxFeePerBlobGas".to_string(),
+                serde_json::to_value(self.max_fee_per_blob_gas).unwrap(),
+            );
+            other.insert(
+                "blobVersionedHashes".to_string(),
+                serde_json::to_value(&self.blob_versioned_hashes).unwrap(),
+            );
+        }
+
         Transaction {
             hash: self.tx_hash,
             nonce: U256::from(self.nonce),
@@ -140,7 +159,7 @@ impl TransactionTrace {
             max_priority_fee_per_gas: None,
             max_fee_per_gas: None,
             chain_id: Some(self.chain_id),
-            other: Default::default(),
+            other,
         }
     }
 }
@@ -28,6 +28,7 @@ use halo2_proofs::{
 };
 use lazy_static::lazy_static;
 use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
 use sha3::digest::generic_array::GenericArray;
 use subtle::CtOption;
 
@@ -53,7 +54,7 @@ pub fn sign(randomness: Fq, sk: Fq, msg_hash: Fq) -> (Fq, Fq, u8) {
 
 /// Signature data required by the SignVerify Chip as input to verify a
 /// signature.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SignData {
     /// Secp256k1 signature point (r, s, v)
     /// v must be 0 or 1
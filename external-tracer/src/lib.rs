@@ -3,6 +3,7 @@
 #[cfg(feature = "scroll")]
 use eth_types::l2_types::BlockTrace;
 use eth_types::{
+    evm_types::{ForkActivation, ForkSchedule},
     geth_types::{Account, BlockConstants, Transaction},
     Address, Error, GethExecTrace, Word,
 };
@@ -76,6 +77,8 @@ pub struct ChainConfig {
     /// Shanghai switch time (nil = no fork, 0 = already on shanghai)
     /// Scroll EVM use the name `ShanghaiBlock` instead
     pub shanghai_time: Option<u64>,
+    /// Cancun switch time (nil = no fork, 0 = already on cancun)
+    pub cancun_time: Option<u64>,
     /// TerminalTotalDifficulty is the amount of total difficulty reached by
     /// the network that triggers the consensus upgrade.
     pub terminal_total_difficulty: Option<u64>,
@@ -91,10 +94,38 @@ impl ChainConfig {
         Self {
             archimedes_block: None,
             shanghai_time: Some(0),
+            cancun_time: None,
             terminal_total_difficulty: Some(0),
             terminal_total_difficulty_passed: true,
         }
     }
+
+    /// Create a chain config for Cancun fork.
+    pub fn cancun() -> Self {
+        Self {
+            cancun_time: Some(0),
+            ..Self::shanghai()
+        }
+    }
+
+    /// Create a chain config following the fork schedule, or `None` for the
+    /// tracer's default (pre-Shanghai) config.
+    pub fn from_fork_schedule(schedule: &ForkSchedule) -> Option<Self> {
+        let value = |activation: Option<ForkActivation>| {
+            activation.map(|activation| match activation {
+                ForkActivation::Block(value) | ForkActivation::Timestamp(value) => value,
+            })
+        };
+        let shanghai_time = value(schedule.shanghai);
+        let config = Self {
+            archimedes_block: value(schedule.archimedes),
+            shanghai_time,
+            cancun_time: value(schedule.cancun),
+            terminal_total_difficulty: shanghai_time.map(|_| 0),
+            terminal_total_difficulty_passed: shanghai_time.is_some(),
+        };
+        (config.archimedes_block.is_some() || shanghai_time.is_some()).then_some(config)
+    }
 }
 
 /// Creates a trace for the specified config
@@ -123,6 +154,7 @@ pub fn l2trace(config: &TraceConfig) -> Result<BlockTrace, Error> {
         l2_config.chain_config = Some(ChainConfig {
             archimedes_block: Some(0),
             shanghai_time: None,
+            cancun_time: None,
             terminal_total_difficulty: None,
             terminal_total_difficulty_passed: false,
         });
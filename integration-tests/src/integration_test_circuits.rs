@@ -4,6 +4,7 @@ use bus_mapping::{
         BuilderClient, CircuitInputBuilder, CircuitsParams, PrecompileEcParams,
     },
     mock::BlockData,
+    precompile::DEFAULT_MODEXP_SIZE_LIMIT,
 };
 use eth_types::geth_types::GethData;
 use halo2_proofs::{
@@ -95,6 +96,7 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
         ec_mul: MAX_EC_MUL,
         ec_pairing: MAX_EC_PAIRING,
     },
+    max_modexp_size: DEFAULT_MODEXP_SIZE_LIMIT,
 };
 
 const EVM_CIRCUIT_DEGREE: u32 = 18;
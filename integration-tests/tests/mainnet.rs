@@ -1,5 +1,6 @@
 use bus_mapping::{
     circuit_input_builder::{keccak_inputs, BuilderClient, CircuitsParams, PrecompileEcParams},
+    precompile::DEFAULT_MODEXP_SIZE_LIMIT,
     util::read_env_var,
     Error::JSONRpcError,
 };
@@ -45,6 +46,7 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
         ec_mul: 10,
         ec_pairing: 4,
     },
+    max_modexp_size: DEFAULT_MODEXP_SIZE_LIMIT,
 };
 
 #[tokio::test]
@@ -24,6 +24,7 @@ pub struct MockBlock {
     gas_used: Word,
     gas_limit: Word,
     base_fee_per_gas: Word,
+    excess_blob_gas: Option<u64>,
     extra_data: Bytes,
     logs_bloom: Option<Bloom>,
     timestamp: Word,
@@ -55,6 +56,7 @@ impl Default for MockBlock {
             gas_used: Word::zero(),
             gas_limit: *MOCK_GASLIMIT,
             base_fee_per_gas: *MOCK_BASEFEE,
+            excess_blob_gas: None,
             extra_data: Bytes::default(),
             logs_bloom: None,
             timestamp: Word::from(123456789u64),
@@ -73,6 +75,7 @@ impl Default for MockBlock {
 
 impl From<MockBlock> for Block<Transaction> {
     fn from(mut mock: MockBlock) -> Self {
+        let other = mock.other_fields();
         Block {
             hash: mock.hash.or_else(|| Some(Hash::default())),
             parent_hash: mock.parent_hash,
@@ -106,7 +109,7 @@ impl From<MockBlock> for Block<Transaction> {
             mix_hash: Some(mock.mix_hash),
             nonce: Some(mock.nonce),
             base_fee_per_gas: Some(mock.base_fee_per_gas),
-            other: OtherFields::default(),
+            other,
             withdrawals: None,
             withdrawals_root: None,
         }
@@ -115,6 +118,7 @@ impl From<MockBlock> for Block<Transaction> {
 
 impl From<MockBlock> for Block<()> {
     fn from(mock: MockBlock) -> Self {
+        let other = mock.other_fields();
         Block {
             hash: mock.hash.or_else(|| Some(Hash::default())),
             parent_hash: mock.parent_hash,
@@ -138,7 +142,7 @@ impl From<MockBlock> for Block<()> {
             mix_hash: Some(mock.mix_hash),
             nonce: Some(mock.nonce),
             base_fee_per_gas: Some(mock.base_fee_per_gas),
-            other: OtherFields::default(),
+            other,
             withdrawals: None,
             withdrawals_root: None,
         }
@@ -146,6 +150,18 @@ impl From<MockBlock> for Block<()> {
 }
 
 impl MockBlock {
+    /// Fields of the block which are not part of the ethers block type.
+    fn other_fields(&self) -> OtherFields {
+        let mut other = OtherFields::default();
+        if let Some(excess_blob_gas) = self.excess_blob_gas {
+            other.insert(
+                "excessBlobGas".to_string(),
+                serde_json::to_value(U64::from(excess_blob_gas)).unwrap(),
+            );
+        }
+        other
+    }
+
     /// TODO: This should be computed based on the fields of the block by
     /// default unless `Some(hash)` is specified on build process.
     pub fn hash(&mut self, hash: Hash) -> &mut Self {
@@ -213,6 +229,12 @@ impl MockBlock {
         self
     }
 
+    /// Set excess_blob_gas field for the MockBlock.
+    pub fn excess_blob_gas(&mut self, excess_blob_gas: u64) -> &mut Self {
+        self.excess_blob_gas = Some(excess_blob_gas);
+        self
+    }
+
     /// Set extra_data field for the MockBlock.
     pub fn extra_data(&mut self, extra_data: Bytes) -> &mut Self {
         self.extra_data = extra_data;
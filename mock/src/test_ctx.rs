@@ -287,10 +287,9 @@ pub fn gen_trace_config(
             .map(eth_types::geth_types::Transaction::from)
             .collect(),
         logger_config,
-        #[cfg(feature = "shanghai")]
-        chain_config: Some(external_tracer::ChainConfig::shanghai()),
-        #[cfg(not(feature = "shanghai"))]
-        chain_config: None,
+        chain_config: external_tracer::ChainConfig::from_fork_schedule(
+            &eth_types::evm_types::FORK_SCHEDULE,
+        ),
         #[cfg(feature = "scroll")]
         l1_queue_index: 0,
     })
@@ -2,12 +2,13 @@
 
 use super::{MOCK_ACCOUNTS, MOCK_CHAIN_ID, MOCK_GASPRICE};
 use eth_types::{
-    geth_types::Transaction as GethTransaction, word, AccessList, Address, Bytes, Hash,
-    Transaction, Word, U64,
+    geth_types::{get_rlp_signed, get_rlp_unsigned, Transaction as GethTransaction, TxType},
+    word, AccessList, Address, Bytes, Hash, Transaction, Word, H256, U64,
 };
 use ethers_core::{
     rand::{CryptoRng, RngCore},
     types::{OtherFields, TransactionRequest},
+    utils::keccak256,
 };
 use ethers_signers::{LocalWallet, Signer};
 use lazy_static::lazy_static;
@@ -160,6 +161,8 @@ pub struct MockTransaction {
     pub access_list: AccessList,
     pub max_priority_fee_per_gas: Word,
     pub max_fee_per_gas: Word,
+    pub max_fee_per_blob_gas: Word,
+    pub blob_versioned_hashes: Vec<Hash>,
     pub chain_id: u64,
 }
 
@@ -185,6 +188,8 @@ impl Default for MockTransaction {
             access_list: AccessList::default(),
             max_priority_fee_per_gas: Word::zero(),
             max_fee_per_gas: Word::zero(),
+            max_fee_per_blob_gas: Word::zero(),
+            blob_versioned_hashes: Vec::new(),
             chain_id: *MOCK_CHAIN_ID,
         }
     }
@@ -192,6 +197,18 @@ impl Default for MockTransaction {
 
 impl From<MockTransaction> for Transaction {
     fn from(mock: MockTransaction) -> Self {
+        let mut other = OtherFields::default();
+        if mock.transaction_type == U64::from(TxType::Eip4844 as u64) {
+            other.insert(
+                "maxFeePerBlobGas".to_string(),
+                serde_json::to_value(mock.max_fee_per_blob_gas).unwrap(),
+            );
+            other.insert(
+                "blobVersionedHashes".to_string(),
+                serde_json::to_value(&mock.blob_versioned_hashes).unwrap(),
+            );
+        }
+
         Transaction {
             hash: mock.hash.unwrap_or_default(),
             nonce: mock.nonce,
@@ -212,7 +229,7 @@ impl From<MockTransaction> for Transaction {
             max_priority_fee_per_gas: Some(mock.max_priority_fee_per_gas),
             max_fee_per_gas: Some(mock.max_fee_per_gas),
             chain_id: Some(mock.chain_id.into()),
-            other: OtherFields::default(),
+            other,
         }
     }
 }
@@ -323,6 +340,18 @@ impl MockTransaction {
         self
     }
 
+    /// Set max_fee_per_blob_gas field for the MockTransaction.
+    pub fn max_fee_per_blob_gas(&mut self, max_fee_per_blob_gas: Word) -> &mut Self {
+        self.max_fee_per_blob_gas = max_fee_per_blob_gas;
+        self
+    }
+
+    /// Set blob_versioned_hashes field for the MockTransaction.
+    pub fn blob_versioned_hashes(&mut self, blob_versioned_hashes: Vec<Hash>) -> &mut Self {
+        self.blob_versioned_hashes = blob_versioned_hashes;
+        self
+    }
+
     /// Set chain_id field for the MockTransaction.
     pub fn chain_id(&mut self, chain_id: u64) -> &mut Self {
         self.chain_id = chain_id;
@@ -332,6 +361,10 @@ impl MockTransaction {
     /// Consumes the mutable ref to the MockTransaction returning the structure
     /// by value.
     pub fn build(&mut self) -> Self {
+        if self.transaction_type == U64::from(TxType::Eip4844 as u64) {
+            return self.build_eip4844();
+        }
+
         let tx = TransactionRequest::new()
             .from(self.from.address())
             .nonce(self.nonce)
@@ -375,4 +408,32 @@ impl MockTransaction {
 
         self.to_owned()
     }
+
+    /// Same as [`MockTransaction::build`] for a blob tx, which ethers can neither sign nor
+    /// hash.
+    fn build_eip4844(&mut self) -> Self {
+        match (self.v, self.r, self.s) {
+            (None, None, None) => {
+                if self.from.is_wallet() && self.hash.is_none() {
+                    let sig_hash = keccak256(get_rlp_unsigned(&Transaction::from(self.to_owned())));
+                    let sig = self
+                        .from
+                        .as_wallet()
+                        .sign_hash(H256(sig_hash))
+                        .expect("sign mock tx");
+                    // blob txs carry the y parity instead of v
+                    self.sig_data((sig.v - 27, sig.r, sig.s));
+                }
+            }
+            (Some(_), Some(_), Some(_)) => (),
+            _ => panic!("Either all or none of the SigData params have to be set"),
+        }
+
+        if self.hash.is_none() {
+            let tmp_tx = Transaction::from(self.to_owned());
+            self.hash(H256(keccak256(get_rlp_signed(&tmp_tx))));
+        }
+
+        self.to_owned()
+    }
 }
@@ -1,15 +1,16 @@
 use crate::{
     common,
-    config::{LayerId, AGG_DEGREES},
+    config::{bundle_degrees, LayerId, AGG_DEGREES},
     consts::{AGG_KECCAK_ROW, AGG_VK_FILENAME, CHUNK_PROTOCOL_FILENAME},
     io::{force_to_read, try_to_read},
-    BatchProof, ChunkProof,
+    utils::{chunk_trace_to_witness_block, pipeline_chunk_witness_blocks, read_env_var},
+    zkevm, BatchProof, BlockTrace, ChunkProof,
 };
-use aggregator::{ChunkHash, MAX_AGG_SNARKS};
-use anyhow::{bail, Result};
+use aggregator::{BatchError, BatchHash, ChunkHash, MAX_AGG_BATCHES, MAX_AGG_SNARKS};
+use anyhow::{anyhow, bail, Result};
 use sha2::{Digest, Sha256};
 use snark_verifier_sdk::Snark;
-use std::{env, iter::repeat};
+use std::{env, thread};
 
 #[derive(Debug)]
 pub struct Prover {
@@ -17,6 +18,8 @@ pub struct Prover {
     pub inner: common::Prover,
     pub chunk_protocol: Vec<u8>,
     raw_vk: Option<Vec<u8>>,
+    // To load the params of the bundle layers when bundling.
+    params_dir: String,
 }
 
 impl Prover {
@@ -39,6 +42,7 @@ impl Prover {
             inner,
             chunk_protocol,
             raw_vk,
+            params_dir: params_dir.to_string(),
         }
     }
 
@@ -65,6 +69,10 @@ impl Prover {
             .or_else(|| self.raw_vk.clone())
     }
 
+    pub fn get_bundle_vk(&self) -> Option<Vec<u8>> {
+        self.inner.raw_vk(LayerId::Layer6.id())
+    }
+
     // Return the EVM proof for verification.
     pub fn gen_agg_evm_proof(
         &mut self,
@@ -72,12 +80,13 @@ impl Prover {
         name: Option<&str>,
         output_dir: Option<&str>,
     ) -> Result<BatchProof> {
+        let last_chunk_hash = match chunk_hashes_proofs.last() {
+            Some((chunk_hash, _)) => chunk_hash,
+            None => return Err(BatchError::NoRealChunk.into()),
+        };
         let name = name.map_or_else(
             || {
-                chunk_hashes_proofs
-                    .last()
-                    .unwrap()
-                    .0
+                last_chunk_hash
                     .public_input_hash()
                     .to_low_u64_le()
                     .to_string()
@@ -109,6 +118,57 @@ impl Prover {
         Ok(batch_proof)
     }
 
+    // Return the EVM proof of a batch generated from the chunk traces. The witness blocks of chunks
+    // are generated concurrently by `CHUNK_WITNESS_WORKERS` threads, while each chunk is proved by
+    // the chunk prover (reusing its loaded params and PKs) as soon as its witness block is ready.
+    pub fn gen_agg_evm_proof_by_chunk_traces(
+        &mut self,
+        chunk_prover: &mut zkevm::Prover,
+        chunk_traces: Vec<Vec<BlockTrace>>,
+        name: Option<&str>,
+        output_dir: Option<&str>,
+    ) -> Result<BatchProof> {
+        let chunk_count = chunk_traces.len();
+        if chunk_count == 0 {
+            return Err(BatchError::NoRealChunk.into());
+        }
+        if chunk_count > MAX_AGG_SNARKS {
+            return Err(BatchError::InvalidNumberOfChunks {
+                expected: MAX_AGG_SNARKS,
+                actual: chunk_count,
+            }
+            .into());
+        }
+
+        let num_workers = read_env_var(
+            "CHUNK_WITNESS_WORKERS",
+            thread::available_parallelism().map_or(1, |n| n.get()),
+        );
+        let chunk_hashes_proofs = pipeline_chunk_witness_blocks(
+            chunk_traces,
+            num_workers,
+            chunk_trace_to_witness_block,
+            |index, witness_block| {
+                let chunk_name = name.map(|name| format!("{name}_chunk_{index}"));
+                let chunk_proof = chunk_prover.gen_chunk_proof_by_witness_block(
+                    witness_block,
+                    chunk_name.as_deref(),
+                    None,
+                    output_dir,
+                )?;
+                log::info!("Got chunk proof of chunk-{index}");
+
+                let chunk_hash = chunk_proof
+                    .chunk_hash
+                    .ok_or_else(|| anyhow!("No chunk hash in proof of chunk-{index}"))?;
+
+                Ok((chunk_hash, chunk_proof))
+            },
+        )?;
+
+        self.gen_agg_evm_proof(chunk_hashes_proofs, name, output_dir)
+    }
+
     // Generate previous snark before the final one.
     // Then it could be used to generate a normal or EVM proof for verification.
     pub fn load_or_gen_last_agg_snark(
@@ -118,7 +178,16 @@ impl Prover {
         output_dir: Option<&str>,
     ) -> Result<Snark> {
         let real_chunk_count = chunk_hashes_proofs.len();
-        assert!((1..=MAX_AGG_SNARKS).contains(&real_chunk_count));
+        if real_chunk_count == 0 {
+            return Err(BatchError::NoRealChunk.into());
+        }
+        if real_chunk_count > MAX_AGG_SNARKS {
+            return Err(BatchError::InvalidNumberOfChunks {
+                expected: MAX_AGG_SNARKS,
+                actual: real_chunk_count,
+            }
+            .into());
+        }
 
         check_chunk_hashes(name, &chunk_hashes_proofs)?;
         let (mut chunk_hashes, chunk_proofs): (Vec<_>, Vec<_>) =
@@ -130,18 +199,12 @@ impl Prover {
 
         let mut layer2_snarks: Vec<_> = chunk_proofs.into_iter().map(|p| p.to_snark()).collect();
 
-        if real_chunk_count < MAX_AGG_SNARKS {
-            let padding_snark = layer2_snarks.last().unwrap().clone();
-            let mut padding_chunk_hash = *chunk_hashes.last().unwrap();
-            padding_chunk_hash.is_padding = true;
-
-            // Extend to MAX_AGG_SNARKS for both chunk hashes and layer-2 snarks.
-            chunk_hashes.extend(repeat(padding_chunk_hash).take(MAX_AGG_SNARKS - real_chunk_count));
-            layer2_snarks.extend(repeat(padding_snark).take(MAX_AGG_SNARKS - real_chunk_count));
-        }
+        // Extend to MAX_AGG_SNARKS for both chunk hashes and layer-2 snarks.
+        pad_chunk_hashes(&mut chunk_hashes, MAX_AGG_SNARKS);
+        pad_snarks(&mut layer2_snarks, MAX_AGG_SNARKS);
 
         // Load or generate aggregation snark (layer-3).
-        let layer3_snark = self.inner.load_or_gen_agg_snark(
+        let layer3_snark = self.inner.load_or_gen_agg_snark::<MAX_AGG_SNARKS>(
             name,
             LayerId::Layer3.id(),
             LayerId::Layer3.degree(),
@@ -154,6 +217,106 @@ impl Prover {
         Ok(layer3_snark)
     }
 
+    // Return the compression thin snark (layer-4) of the batch, which could be aggregated into a
+    // bundle, together with the batch as a chunk of the bundle.
+    pub fn load_or_gen_batch_snark(
+        &mut self,
+        name: &str,
+        chunk_hashes_proofs: Vec<(ChunkHash, ChunkProof)>,
+        output_dir: Option<&str>,
+    ) -> Result<(ChunkHash, Snark)> {
+        let mut chunk_hashes: Vec<_> = chunk_hashes_proofs.iter().map(|(hash, _)| *hash).collect();
+
+        let layer3_snark =
+            self.load_or_gen_last_agg_snark(name, chunk_hashes_proofs, output_dir)?;
+
+        pad_chunk_hashes(&mut chunk_hashes, MAX_AGG_SNARKS);
+        let batch_hash = BatchHash::<MAX_AGG_SNARKS>::construct(&chunk_hashes)?.to_chunk_hash();
+
+        // Load or generate compression thin snark (layer-4).
+        let layer4_snark = self.inner.load_or_gen_comp_snark(
+            name,
+            LayerId::Layer4.id(),
+            true,
+            LayerId::Layer4.degree(),
+            layer3_snark,
+            output_dir,
+        )?;
+        log::info!("Got compression thin snark (layer-4): {name}");
+
+        Ok((batch_hash, layer4_snark))
+    }
+
+    // Return the EVM proof of a bundle, which aggregates the snarks of continuous batches
+    // generated by `load_or_gen_batch_snark`.
+    pub fn gen_bundle_evm_proof(
+        &mut self,
+        batch_hashes_snarks: Vec<(ChunkHash, Snark)>,
+        name: Option<&str>,
+        output_dir: Option<&str>,
+    ) -> Result<BatchProof> {
+        let real_batch_count = batch_hashes_snarks.len();
+        let last_batch_hash = match batch_hashes_snarks.last() {
+            Some((batch_hash, _)) => batch_hash,
+            None => return Err(BatchError::NoRealBatch.into()),
+        };
+        if real_batch_count > MAX_AGG_BATCHES {
+            return Err(BatchError::InvalidNumberOfBatches {
+                expected: MAX_AGG_BATCHES,
+                actual: real_batch_count,
+            }
+            .into());
+        }
+        let name = name.map_or_else(
+            || {
+                last_batch_hash
+                    .public_input_hash()
+                    .to_low_u64_le()
+                    .to_string()
+            },
+            |name| name.to_string(),
+        );
+
+        self.inner
+            .load_params_of_degrees(&self.params_dir, &bundle_degrees()?);
+
+        let (mut batch_hashes, mut layer4_snarks): (Vec<_>, Vec<_>) =
+            batch_hashes_snarks.into_iter().unzip();
+
+        // Extend to MAX_AGG_BATCHES for both batch hashes and layer-4 snarks.
+        pad_chunk_hashes(&mut batch_hashes, MAX_AGG_BATCHES);
+        pad_snarks(&mut layer4_snarks, MAX_AGG_BATCHES);
+
+        // Load or generate aggregation snark of batches (layer-5).
+        let layer5_snark = self.inner.load_or_gen_agg_snark::<MAX_AGG_BATCHES>(
+            &name,
+            LayerId::Layer5.id(),
+            LayerId::Layer5.degree(),
+            &batch_hashes,
+            &layer4_snarks,
+            output_dir,
+        )?;
+        log::info!("Got aggregation snark of batches (layer-5): {name}");
+
+        // Load or generate final compression thin EVM proof (layer-6).
+        let evm_proof = self.inner.load_or_gen_comp_evm_proof(
+            &name,
+            LayerId::Layer6.id(),
+            true,
+            LayerId::Layer6.degree(),
+            layer5_snark,
+            output_dir,
+        )?;
+        log::info!("Got final compression thin EVM proof (layer-6): {name}");
+
+        let bundle_proof = BatchProof::from(evm_proof.proof);
+        if let Some(output_dir) = output_dir {
+            bundle_proof.dump(output_dir, "bundle")?;
+        }
+
+        Ok(bundle_proof)
+    }
+
     fn check_and_clear_raw_vk(&mut self) {
         if self.raw_vk.is_some() {
             // Check VK is same with the init one, and take (clear) init VK.
@@ -171,6 +334,22 @@ impl Prover {
     }
 }
 
+// Extend the (real) chunk hashes to the given size by repeating the last one as padding.
+fn pad_chunk_hashes(chunk_hashes: &mut Vec<ChunkHash>, size: usize) {
+    if let Some(last_chunk_hash) = chunk_hashes.last() {
+        let mut padding_chunk_hash = *last_chunk_hash;
+        padding_chunk_hash.is_padding = true;
+        chunk_hashes.resize(size, padding_chunk_hash);
+    }
+}
+
+// Extend the (real) snarks to the given size by repeating the last one.
+fn pad_snarks(snarks: &mut Vec<Snark>, size: usize) {
+    if let Some(padding_snark) = snarks.last().cloned() {
+        snarks.resize(size, padding_snark);
+    }
+}
+
 macro_rules! compare_field {
     ($name:expr, $idx:expr, $field:ident, $lhs:ident, $rhs:ident) => {
         if $lhs.$field != $rhs.$field {
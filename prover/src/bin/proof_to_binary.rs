@@ -0,0 +1,42 @@
+use anyhow::Result;
+use clap::Parser;
+use prover::proof::json_file_to_binary;
+use std::{fs, path::Path};
+
+/// Convert a proof JSON file dumped by the provers to the binary container.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Path of the proof JSON file
+    proof: String,
+
+    /// Path of the binary output, the JSON path with extension `bin` by default
+    #[clap(long)]
+    output: Option<String>,
+
+    /// Layer id of EVM and batch proofs, layer4 by default (layer6 for bundle proofs)
+    #[clap(long)]
+    layer: Option<String>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let buf = json_file_to_binary(&args.proof, args.layer.as_deref())?;
+    let output = args.output.unwrap_or_else(|| {
+        Path::new(&args.proof)
+            .with_extension("bin")
+            .to_string_lossy()
+            .into_owned()
+    });
+    fs::write(&output, &buf)?;
+
+    let json_len = fs::metadata(&args.proof)?.len();
+    println!(
+        "converted {} ({json_len} bytes) to {output} ({} bytes)",
+        args.proof,
+        buf.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use clap::Parser;
+use prover::{service::Service, utils::init_env_and_log};
+use std::{env, time::Duration};
+
+/// Run the chunk and batch provers as a service of the job queue in the work dir.
+///
+/// Submit a job by writing `<work_dir>/inbox/<id>.json.tmp` and renaming it to
+/// `<work_dir>/inbox/<id>.json` once written, as
+/// `{"type": "chunk", "traces": ["<block trace file>", ...]}` or
+/// `{"type": "batch", "chunks": ["<chunk job id>", ...]}`.
+/// The proofs are written to `<work_dir>/outputs/<id>/`.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Work dir of the job queue
+    #[clap(long, default_value = "./prover_service")]
+    work_dir: String,
+
+    /// Dir of the setup params
+    #[clap(long, default_value = "./test_params")]
+    params_dir: String,
+
+    /// Dir of the VKs and chunk protocol
+    #[clap(long, default_value = "./test_assets")]
+    assets_dir: String,
+
+    /// Seconds to wait before polling the inbox again
+    #[clap(long, default_value_t = 10)]
+    poll_interval: u64,
+
+    /// Max times to start a job interrupted by crashes
+    #[clap(long, default_value_t = 3)]
+    max_attempts: usize,
+
+    /// Exit when no job is runnable, instead of polling the inbox
+    #[clap(long)]
+    once: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    // Write the log to the work dir by default.
+    if env::var("OUTPUT_DIR").is_err() {
+        env::set_var("OUTPUT_DIR", &args.work_dir);
+    }
+    init_env_and_log("prover_service");
+
+    let mut service = Service::new(
+        &args.work_dir,
+        &args.params_dir,
+        &args.assets_dir,
+        args.max_attempts,
+    )?;
+
+    if args.once {
+        let count = service.run_pending()?;
+        log::info!("prover-service: run {count} jobs");
+        Ok(())
+    } else {
+        service.run(Duration::from_secs(args.poll_interval))
+    }
+}
@@ -0,0 +1,235 @@
+use anyhow::{anyhow, bail, Result};
+use clap::Parser;
+use eth_types::H256;
+use halo2_proofs::halo2curves::bn256::Fr;
+use prover::{
+    common::Verifier,
+    config::LayerId,
+    consts::{AGG_VK_FILENAME, CHUNK_VK_FILENAME},
+    io::serialize_fr,
+    BatchProof, ChunkHash, ChunkProof, CompressionCircuit, EvmProof,
+};
+use std::{env, fs, path::Path, str::FromStr};
+
+/// Verify a proof JSON file dumped by the provers, and print its public inputs.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Path of the proof JSON file
+    proof: String,
+
+    /// Kind of the proof (chunk, batch or evm), detected from the JSON fields by default
+    #[clap(long)]
+    kind: Option<ProofKind>,
+
+    /// Layer of the proof (layer2, layer4 or layer6), layer2 for chunk proofs and layer4
+    /// for the others by default
+    #[clap(long)]
+    layer: Option<String>,
+
+    /// Dir of the setup params
+    #[clap(long, default_value = "./test_params")]
+    params_dir: String,
+
+    /// Dir of the VKs
+    #[clap(long, default_value = "./test_assets")]
+    assets_dir: String,
+
+    /// Path of the VK file, instead of the one in the assets dir
+    #[clap(long)]
+    vk: Option<String>,
+
+    /// Verify the proof even if it was generated with a VK different from the loaded one
+    #[clap(long)]
+    allow_vk_mismatch: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ProofKind {
+    Chunk,
+    Batch,
+    Evm,
+}
+
+impl ProofKind {
+    // Detect the kind by the fields of the dumped proof.
+    fn detect(json: &serde_json::Value) -> Option<Self> {
+        let fields = json.as_object()?;
+        if fields.contains_key("protocol") {
+            Some(Self::Chunk)
+        } else if fields.contains_key("num_instance") {
+            Some(Self::Evm)
+        } else if fields.contains_key("proof") && fields.contains_key("instances") {
+            Some(Self::Batch)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for ProofKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chunk" => Ok(Self::Chunk),
+            "batch" => Ok(Self::Batch),
+            "evm" => Ok(Self::Evm),
+            _ => Err(format!("unknown proof kind {s}")),
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let json: serde_json::Value = serde_json::from_slice(&fs::read(&args.proof)?)?;
+    let kind = match args.kind {
+        Some(kind) => kind,
+        None => ProofKind::detect(&json)
+            .ok_or_else(|| anyhow!("unable to detect the proof kind of {}", args.proof))?,
+    };
+
+    let layer_id = match (&args.layer, kind) {
+        (Some(id), _) => [LayerId::Layer2, LayerId::Layer4, LayerId::Layer6]
+            .into_iter()
+            .find(|layer_id| layer_id.id() == id)
+            .ok_or_else(|| anyhow!("no compression layer {id}"))?,
+        (None, ProofKind::Chunk) => LayerId::Layer2,
+        (None, _) => LayerId::Layer4,
+    };
+    println!("proof kind: {kind:?}, layer: {layer_id}");
+
+    let raw_vk = match &args.vk {
+        Some(path) => fs::read(path)?,
+        None => {
+            let filename = match kind {
+                ProofKind::Chunk => &*CHUNK_VK_FILENAME,
+                ProofKind::Batch | ProofKind::Evm => &*AGG_VK_FILENAME,
+            };
+            fs::read(Path::new(&args.assets_dir).join(filename))?
+        }
+    };
+
+    env::set_var("COMPRESSION_CONFIG", layer_id.config_path());
+    let verifier = Verifier::<CompressionCircuit>::from_params_dir(
+        &args.params_dir,
+        layer_id.degree(),
+        &raw_vk,
+    );
+
+    let verified = match kind {
+        ProofKind::Chunk => {
+            let proof: ChunkProof = serde_json::from_value(json)?;
+            check_vk(proof.proof.raw_vk(), &raw_vk, args.allow_vk_mismatch)?;
+
+            let pi_hash = print_public_inputs(&proof.proof.instances()[0]);
+            let pi_hash_matched = match &proof.chunk_hash {
+                Some(chunk_hash) => {
+                    print_chunk_hash(chunk_hash);
+                    chunk_hash.public_input_hash() == pi_hash
+                }
+                None => true,
+            };
+            if !pi_hash_matched {
+                println!("public input hash doesn't match chunk info");
+            }
+
+            pi_hash_matched && verifier.verify_snark(proof.to_snark())
+        }
+        ProofKind::Batch => {
+            let proof = serde_json::from_value::<BatchProof>(json)?.proof_to_verify();
+            check_vk(proof.raw_vk(), &raw_vk, args.allow_vk_mismatch)?;
+            print_public_inputs(&proof.instances()[0]);
+
+            let deployment_code = verifier.gen_evm_verifier(vec![proof.instances()[0].len()], None);
+            proof.evm_verify(deployment_code)
+        }
+        ProofKind::Evm => {
+            let proof: EvmProof = serde_json::from_value(json)?;
+            check_vk(proof.proof.raw_vk(), &raw_vk, args.allow_vk_mismatch)?;
+            print_public_inputs(&proof.proof.instances()[0]);
+
+            let deployment_code = verifier.gen_evm_verifier(proof.num_instance.clone(), None);
+            proof.proof.evm_verify(deployment_code)
+        }
+    };
+
+    if !verified {
+        bail!("failed to verify {}", args.proof);
+    }
+    println!("verified: {}", args.proof);
+
+    Ok(())
+}
+
+// Fail if the proof was generated with a different VK, unless the mismatch is allowed, since the
+// VK in the proof is not used to verify.
+fn check_vk(proof_vk: &[u8], raw_vk: &[u8], allow_mismatch: bool) -> Result<()> {
+    if !proof_vk.is_empty() && proof_vk != raw_vk {
+        if !allow_mismatch {
+            bail!("VK in the proof is different with the loaded one, pass --allow-vk-mismatch to verify anyway");
+        }
+        println!("warning: VK in the proof is different with the loaded one");
+    }
+
+    Ok(())
+}
+
+// Print and return the public input hash, of which each byte is an instance following the
+// accumulator.
+fn print_public_inputs(instance: &[Fr]) -> H256 {
+    let bytes: Vec<_> = instance
+        .iter()
+        .skip(instance.len().saturating_sub(32))
+        .map(|value| serialize_fr(value)[0])
+        .collect();
+    let pi_hash = H256::from_slice(&bytes);
+    println!("public input hash: {pi_hash:?}");
+
+    pi_hash
+}
+
+fn print_chunk_hash(chunk_hash: &ChunkHash) {
+    println!("chain id: {}", chunk_hash.chain_id);
+    println!("prev state root: {:?}", chunk_hash.prev_state_root);
+    println!("post state root: {:?}", chunk_hash.post_state_root);
+    println!("withdraw root: {:?}", chunk_hash.withdraw_root);
+    println!("data hash: {:?}", chunk_hash.data_hash);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prover::Proof;
+
+    #[test]
+    fn test_detect_proof_kind() {
+        let chunk_proof = serde_json::to_value(ChunkProof::default()).unwrap();
+        assert_eq!(ProofKind::detect(&chunk_proof), Some(ProofKind::Chunk));
+
+        // A batch proof is dumped as a flattened proof.
+        let batch_proof = serde_json::to_value(Proof::default()).unwrap();
+        assert_eq!(ProofKind::detect(&batch_proof), Some(ProofKind::Batch));
+
+        let evm_proof = serde_json::to_value(EvmProof {
+            proof: Proof::default(),
+            num_instance: vec![1],
+        })
+        .unwrap();
+        assert_eq!(ProofKind::detect(&evm_proof), Some(ProofKind::Evm));
+
+        assert_eq!(ProofKind::detect(&serde_json::json!({ "proof": "" })), None);
+        assert_eq!(ProofKind::detect(&serde_json::json!([])), None);
+    }
+
+    #[test]
+    fn test_check_vk() {
+        assert!(check_vk(&[1, 2], &[1, 2], false).is_ok());
+        // The VK is not dumped in the proof.
+        assert!(check_vk(&[], &[1, 2], false).is_ok());
+
+        assert!(check_vk(&[1, 3], &[1, 2], false).is_err());
+        assert!(check_vk(&[1, 3], &[1, 2], true).is_ok());
+    }
+}
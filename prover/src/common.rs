@@ -1,5 +1,8 @@
 mod prover;
 mod verifier;
 
-pub use self::{prover::Prover, verifier::Verifier};
+pub use self::{
+    prover::{Checkpoint, PkCache, PkCacheMetrics, Prover, PK_CACHE_BUDGET_MB, PK_CACHE_DIR},
+    verifier::Verifier,
+};
 pub use aggregator::{ChunkHash, CompressionCircuit};
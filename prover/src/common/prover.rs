@@ -1,51 +1,66 @@
 use crate::utils::{load_params, param_path_for_degree};
 use halo2_proofs::{
-    halo2curves::bn256::{Bn256, G1Affine},
-    plonk::ProvingKey,
+    halo2curves::bn256::Bn256,
     poly::{commitment::Params, kzg::commitment::ParamsKZG},
 };
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet};
 
 mod aggregation;
+mod checkpoint;
 mod chunk;
 mod compression;
 mod evm;
 mod inner;
 mod mock;
+mod pk_cache;
 mod utils;
 
+pub use checkpoint::{snarks_hash, witness_block_hash, Checkpoint};
+pub use pk_cache::{PkCache, PkCacheMetrics, PK_CACHE_BUDGET_MB, PK_CACHE_DIR};
+
 #[derive(Debug)]
 pub struct Prover {
     // degree -> params (use BTreeMap to find proper degree for params downsize)
     params_map: BTreeMap<u32, ParamsKZG<Bn256>>,
     // Cached id -> pk
-    pk_map: HashMap<String, ProvingKey<G1Affine>>,
+    pk_cache: PkCache,
 }
 
 impl Prover {
     pub fn from_params(params_map: BTreeMap<u32, ParamsKZG<Bn256>>) -> Self {
         Self {
             params_map,
-            pk_map: HashMap::new(),
+            pk_cache: PkCache::default(),
         }
     }
 
     pub fn from_params_dir(params_dir: &str, degrees: &[u32]) -> Self {
-        let degrees = BTreeSet::from_iter(degrees);
-        let max_degree = **degrees.last().unwrap();
+        let mut prover = Self::from_params(BTreeMap::new());
+        prover.load_params_of_degrees(params_dir, degrees);
+
+        prover
+    }
 
+    // Load the params of the degrees which are not loaded yet, e.g. of the bundle layers which
+    // are only needed when bundling.
+    pub fn load_params_of_degrees(&mut self, params_dir: &str, degrees: &[u32]) {
         // Downsize params if any params of degree doesn't exist.
-        let mut params_map = BTreeMap::new();
         for d in BTreeSet::from_iter(degrees).into_iter().rev() {
+            if self.params_map.contains_key(d) {
+                continue;
+            }
+
             let params = match load_params(params_dir, *d, None) {
                 Ok(params) => params,
                 Err(_) => {
-                    let params: &ParamsKZG<_> = params_map
-                        .first_key_value()
+                    let params: &ParamsKZG<_> = self
+                        .params_map
+                        .range(d..)
+                        .next()
                         .unwrap_or_else(|| {
                             panic!(
                                 "File `{}` must exist",
-                                param_path_for_degree(params_dir, max_degree)
+                                param_path_for_degree(params_dir, *d)
                             )
                         })
                         .1;
@@ -59,12 +74,13 @@ impl Prover {
                 }
             };
 
-            params_map.insert(*d, params);
+            self.params_map.insert(*d, params);
         }
+    }
 
-        Self {
-            params_map,
-            pk_map: HashMap::new(),
-        }
+    // Replace the PK cache, e.g. to set a memory budget other than `PK_CACHE_BUDGET_MB`.
+    pub fn with_pk_cache(mut self, pk_cache: PkCache) -> Self {
+        self.pk_cache = pk_cache;
+        self
     }
 }
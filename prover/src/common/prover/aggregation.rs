@@ -1,17 +1,17 @@
-use super::Prover;
+use super::{snarks_hash, Prover};
 use crate::{
     config::layer_config_path,
     io::{load_snark, write_snark},
     utils::gen_rng,
 };
 use aggregator::{AggregationCircuit, BatchHash, ChunkHash};
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use rand::Rng;
 use snark_verifier_sdk::Snark;
 use std::env;
 
 impl Prover {
-    pub fn gen_agg_snark(
+    pub fn gen_agg_snark<const N_SNARKS: usize>(
         &mut self,
         id: &str,
         degree: u32,
@@ -21,16 +21,15 @@ impl Prover {
     ) -> Result<Snark> {
         env::set_var("AGGREGATION_CONFIG", layer_config_path(id));
 
-        let batch_hash = BatchHash::construct(chunk_hashes);
+        let batch_hash = BatchHash::<N_SNARKS>::construct(chunk_hashes)?;
 
         let circuit =
-            AggregationCircuit::new(self.params(degree), previous_snarks, &mut rng, batch_hash)
-                .map_err(|err| anyhow!("Failed to construct aggregation circuit: {err:?}"))?;
+            AggregationCircuit::new(self.params(degree), previous_snarks, &mut rng, batch_hash)?;
 
         self.gen_snark(id, degree, &mut rng, circuit)
     }
 
-    pub fn load_or_gen_agg_snark(
+    pub fn load_or_gen_agg_snark<const N_SNARKS: usize>(
         &mut self,
         name: &str,
         id: &str,
@@ -46,13 +45,20 @@ impl Prover {
             name
         );
 
-        match output_dir.and_then(|_| load_snark(&file_path).ok().flatten()) {
+        let input_hash = snarks_hash(chunk_hashes, previous_snarks);
+
+        match output_dir
+            .filter(|output_dir| self.check_checkpoint(output_dir, name, id, &input_hash))
+            .and_then(|_| load_snark(&file_path).ok().flatten())
+        {
             Some(snark) => Ok(snark),
             None => {
                 let rng = gen_rng();
-                let result = self.gen_agg_snark(id, degree, rng, chunk_hashes, previous_snarks);
-                if let (Some(_), Ok(snark)) = (output_dir, &result) {
+                let result =
+                    self.gen_agg_snark::<N_SNARKS>(id, degree, rng, chunk_hashes, previous_snarks);
+                if let (Some(output_dir), Ok(snark)) = (output_dir, &result) {
                     write_snark(&file_path, snark);
+                    self.write_checkpoint(output_dir, name, id, &input_hash)?;
                 }
 
                 result
@@ -0,0 +1,181 @@
+use super::Prover;
+use crate::{types::base64, utils::short_git_version};
+use aggregator::ChunkHash;
+use anyhow::{anyhow, Result};
+use halo2_proofs::halo2curves::bn256::Fr;
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use snark_verifier_sdk::Snark;
+use std::fs::{self, File};
+use zkevm_circuits::evm_circuit::witness::Block;
+
+// Increase it when the format of checkpoints or the dumped snarks changes.
+const CHECKPOINT_VERSION: u32 = 2;
+
+/// Checkpoint of a layer, which is written after the snark (or EVM proof) of the layer, and
+/// decides if the dumped one could be reused. The dumped snarks (or EVM proofs) without a
+/// checkpoint, e.g. dumped by an older version, are always regenerated.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Checkpoint {
+    pub version: u32,
+    /// Short git version of the circuits.
+    pub git_version: String,
+    /// Layer ID.
+    pub id: String,
+    /// Hash of the witness block (inner layer), or the previous snarks and chunk hashes.
+    pub input_hash: String,
+    /// Raw VK of the layer, which is restored if the VK is unknown, e.g. after a restart.
+    #[serde(with = "base64")]
+    pub raw_vk: Vec<u8>,
+}
+
+impl Prover {
+    // Return true if the checkpoint of the layer exists and is generated by the same circuit
+    // version, input and VK, false otherwise. If the VK of the layer is unknown (not generated in
+    // this process), the one of the checkpoint is restored, and compared with the VK generated
+    // later (or the one of the assets by the caller).
+    pub fn check_checkpoint(
+        &mut self,
+        output_dir: &str,
+        name: &str,
+        id: &str,
+        input_hash: &str,
+    ) -> bool {
+        let file_path = checkpoint_path(output_dir, name, id);
+        let checkpoint =
+            match fs::read(&file_path).map(|data| serde_json::from_slice::<Checkpoint>(&data)) {
+                Ok(Ok(checkpoint)) => checkpoint,
+                Ok(Err(err)) => {
+                    log::warn!("Invalid checkpoint {file_path}: {err}");
+                    return false;
+                }
+                Err(_) => return false,
+            };
+
+        let stale_reason = if checkpoint.version != CHECKPOINT_VERSION {
+            Some("different checkpoint version")
+        } else if checkpoint.git_version != short_git_version() {
+            Some("different circuit version")
+        } else if checkpoint.input_hash != input_hash {
+            Some("different input")
+        } else {
+            match self.raw_vk(id) {
+                Some(raw_vk) => (raw_vk != checkpoint.raw_vk).then_some("different VK"),
+                None => {
+                    log::info!("Restore VK of {id} from checkpoint {file_path}");
+                    self.set_raw_vk(id, checkpoint.raw_vk);
+                    None
+                }
+            }
+        };
+
+        match stale_reason {
+            Some(reason) => {
+                log::warn!("Stale checkpoint {file_path}: {reason}");
+                false
+            }
+            None => {
+                log::info!("Resume from checkpoint {file_path}");
+                true
+            }
+        }
+    }
+
+    // Write the checkpoint of the layer, after its snark (or EVM proof) dumped.
+    pub fn write_checkpoint(
+        &self,
+        output_dir: &str,
+        name: &str,
+        id: &str,
+        input_hash: &str,
+    ) -> Result<()> {
+        let checkpoint = Checkpoint {
+            version: CHECKPOINT_VERSION,
+            git_version: short_git_version(),
+            id: id.to_string(),
+            input_hash: input_hash.to_string(),
+            raw_vk: self
+                .raw_vk(id)
+                .ok_or_else(|| anyhow!("Unknown VK of {id} to write checkpoint"))?,
+        };
+
+        let fd = File::create(checkpoint_path(output_dir, name, id))?;
+        serde_json::to_writer_pretty(fd, &checkpoint)?;
+
+        Ok(())
+    }
+}
+
+/// Hash of the serialized witness block, as the input of inner layer.
+pub fn witness_block_hash(witness_block: &Block<Fr>) -> Result<String> {
+    let data = serde_json::to_vec(witness_block)?;
+
+    Ok(hex::encode(Sha256::digest(data)))
+}
+
+/// Hash of the previous snarks and chunk hashes, as the input of compression and aggregation
+/// layers.
+pub fn snarks_hash(chunk_hashes: &[ChunkHash], snarks: &[Snark]) -> String {
+    let mut hasher = Sha256::new();
+    for chunk_hash in chunk_hashes {
+        hasher.update(chunk_hash.extract_hash_preimage());
+    }
+    for snark in snarks {
+        hasher.update(&snark.proof);
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+fn checkpoint_path(output_dir: &str, name: &str, id: &str) -> String {
+    format!("{output_dir}/checkpoint_{id}_{name}.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_stale_checkpoint() {
+        let output_dir = std::env::temp_dir().join("prover_checkpoint");
+        fs::create_dir_all(&output_dir).unwrap();
+        let output_dir = output_dir.to_string_lossy();
+
+        let mut prover = Prover::from_params(BTreeMap::new());
+        let input_hash = snarks_hash(&[ChunkHash::default()], &[]);
+        assert!(!prover.check_checkpoint(&output_dir, "test", "layer3", "no-checkpoint"));
+
+        // Fail closed without the VK.
+        assert!(prover
+            .write_checkpoint(&output_dir, "test", "layer3", &input_hash)
+            .is_err());
+        prover.set_raw_vk("layer3", vec![3]);
+        prover
+            .write_checkpoint(&output_dir, "test", "layer3", &input_hash)
+            .unwrap();
+
+        // Restore the unknown VK, e.g. after a restart.
+        let mut other_prover = Prover::from_params(BTreeMap::new());
+        assert!(other_prover.check_checkpoint(&output_dir, "test", "layer3", &input_hash));
+        assert_eq!(other_prover.raw_vk("layer3"), Some(vec![3]));
+
+        assert!(prover.check_checkpoint(&output_dir, "test", "layer3", &input_hash));
+        assert!(!prover.check_checkpoint(&output_dir, "test", "layer4", &input_hash));
+
+        let other_input_hash = snarks_hash(&[], &[]);
+        assert!(!prover.check_checkpoint(&output_dir, "test", "layer3", &other_input_hash));
+
+        // Checkpoint of another VK.
+        prover.set_raw_vk("layer3", vec![4]);
+        assert!(!prover.check_checkpoint(&output_dir, "test", "layer3", &input_hash));
+        prover.set_raw_vk("layer3", vec![3]);
+
+        // Checkpoint of another circuit version.
+        let path = checkpoint_path(&output_dir, "test", "layer3");
+        let mut checkpoint: Checkpoint = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        checkpoint.git_version = "0000000".to_string();
+        fs::write(&path, serde_json::to_vec(&checkpoint).unwrap()).unwrap();
+        assert!(!prover.check_checkpoint(&output_dir, "test", "layer3", &input_hash));
+    }
+}
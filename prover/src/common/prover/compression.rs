@@ -1,4 +1,4 @@
-use super::Prover;
+use super::{snarks_hash, Prover};
 use crate::{
     config::layer_config_path,
     io::{load_snark, write_snark},
@@ -8,7 +8,7 @@ use aggregator::CompressionCircuit;
 use anyhow::{anyhow, Result};
 use rand::Rng;
 use snark_verifier_sdk::Snark;
-use std::env;
+use std::{env, slice};
 
 impl Prover {
     pub fn gen_comp_snark(
@@ -44,13 +44,19 @@ impl Prover {
             name
         );
 
-        match output_dir.and_then(|_| load_snark(&file_path).ok().flatten()) {
+        let input_hash = snarks_hash(&[], slice::from_ref(&prev_snark));
+
+        match output_dir
+            .filter(|output_dir| self.check_checkpoint(output_dir, name, id, &input_hash))
+            .and_then(|_| load_snark(&file_path).ok().flatten())
+        {
             Some(snark) => Ok(snark),
             None => {
                 let rng = gen_rng();
                 let result = self.gen_comp_snark(id, has_accumulator, degree, rng, prev_snark);
-                if let (Some(_), Ok(snark)) = (output_dir, &result) {
+                if let (Some(output_dir), Ok(snark)) = (output_dir, &result) {
                     write_snark(&file_path, snark);
+                    self.write_checkpoint(output_dir, name, id, &input_hash)?;
                 }
 
                 result
@@ -1,11 +1,11 @@
-use super::Prover;
+use super::{snarks_hash, Prover};
 use crate::{config::layer_config_path, utils::gen_rng, EvmProof};
 use aggregator::CompressionCircuit;
 use anyhow::{anyhow, Result};
 use halo2_proofs::halo2curves::bn256::Fr;
 use rand::Rng;
 use snark_verifier_sdk::{gen_evm_proof_shplonk, CircuitExt, Snark};
-use std::env;
+use std::{env, slice};
 
 impl Prover {
     pub fn load_or_gen_comp_evm_proof(
@@ -17,8 +17,13 @@ impl Prover {
         prev_snark: Snark,
         output_dir: Option<&str>,
     ) -> Result<EvmProof> {
-        let name = format!("{id}_{name}");
-        match output_dir.and_then(|output_dir| EvmProof::from_json_file(output_dir, &name).ok()) {
+        let input_hash = snarks_hash(&[], slice::from_ref(&prev_snark));
+
+        let proof_name = format!("{id}_{name}");
+        match output_dir
+            .filter(|output_dir| self.check_checkpoint(output_dir, name, id, &input_hash))
+            .and_then(|output_dir| EvmProof::from_json_file(output_dir, &proof_name).ok())
+        {
             Some(proof) => Ok(proof),
             None => {
                 env::set_var("COMPRESSION_CONFIG", layer_config_path(id));
@@ -35,7 +40,8 @@ impl Prover {
                 let result = self.gen_evm_proof(id, degree, &mut rng, circuit);
 
                 if let (Some(output_dir), Ok(proof)) = (output_dir, &result) {
-                    proof.dump(output_dir, &name)?;
+                    proof.dump(output_dir, &proof_name)?;
+                    self.write_checkpoint(output_dir, name, id, &input_hash)?;
                 }
 
                 result
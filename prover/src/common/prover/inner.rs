@@ -1,4 +1,4 @@
-use super::Prover;
+use super::{witness_block_hash, Prover};
 use crate::{
     config::INNER_DEGREE,
     io::{load_snark, write_snark},
@@ -49,13 +49,19 @@ impl Prover {
             name
         );
 
-        match output_dir.and_then(|_| load_snark(&file_path).ok().flatten()) {
+        let input_hash = witness_block_hash(witness_block)?;
+
+        match output_dir
+            .filter(|output_dir| self.check_checkpoint(output_dir, name, id, &input_hash))
+            .and_then(|_| load_snark(&file_path).ok().flatten())
+        {
             Some(snark) => Ok(snark),
             None => {
                 let rng = gen_rng();
                 let result = self.gen_inner_snark::<SuperCircuit>(id, rng, witness_block);
-                if let (Some(_), Ok(snark)) = (output_dir, &result) {
+                if let (Some(output_dir), Ok(snark)) = (output_dir, &result) {
                     write_snark(&file_path, snark);
+                    self.write_checkpoint(output_dir, name, id, &input_hash)?;
                 }
 
                 result
@@ -0,0 +1,395 @@
+use crate::{io::serialize_vk, utils::read_env_var};
+use anyhow::Result;
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{keygen_pk2, Circuit, ProvingKey},
+    poly::kzg::commitment::ParamsKZG,
+    SerdeFormat,
+};
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
+};
+
+/// Memory budget of the cached PKs in MB, unlimited if 0.
+pub static PK_CACHE_BUDGET_MB: Lazy<usize> = Lazy::new(|| read_env_var("PK_CACHE_BUDGET_MB", 0));
+/// Dir to dump the PKs to reload after eviction.
+pub static PK_CACHE_DIR: Lazy<String> =
+    Lazy::new(|| read_env_var("PK_CACHE_DIR", "./pk_cache".to_string()));
+
+const PK_SERDE_FORMAT: SerdeFormat = SerdeFormat::RawBytes;
+
+#[derive(Clone, Debug, Default)]
+pub struct PkCacheMetrics {
+    /// Number of PKs found in memory.
+    pub hits: usize,
+    /// Number of PKs generated, and the total time.
+    pub keygens: usize,
+    pub keygen_time: Duration,
+    /// Number of PKs reloaded from disk, and the total time.
+    pub loads: usize,
+    pub load_time: Duration,
+    /// Number of PKs evicted from memory.
+    pub evictions: usize,
+}
+
+#[derive(Debug)]
+struct CachedPk {
+    pk: ProvingKey<G1Affine>,
+    // Serialized size, as the estimated memory usage.
+    size: usize,
+    // Tick of the last use for LRU eviction.
+    last_used: u64,
+}
+
+/// Cache of PKs, which evicts the least recently used ones when the total size exceeds the
+/// budget. With a budget, PKs are dumped to disk after generation, and lazily reloaded after
+/// eviction.
+#[derive(Debug)]
+pub struct PkCache {
+    pks: HashMap<String, CachedPk>,
+    // id -> size of the dumped PK
+    dumped: HashMap<String, usize>,
+    // Raw VKs are kept for the evicted PKs.
+    raw_vks: HashMap<String, Vec<u8>>,
+    budget: Option<usize>,
+    dir: PathBuf,
+    tick: u64,
+    metrics: PkCacheMetrics,
+}
+
+impl Default for PkCache {
+    fn default() -> Self {
+        let budget = (*PK_CACHE_BUDGET_MB > 0).then_some(*PK_CACHE_BUDGET_MB << 20);
+
+        Self::new(budget, &PK_CACHE_DIR)
+    }
+}
+
+impl Drop for PkCache {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl PkCache {
+    pub fn new(budget: Option<usize>, dir: &str) -> Self {
+        Self {
+            pks: HashMap::new(),
+            dumped: HashMap::new(),
+            raw_vks: HashMap::new(),
+            budget,
+            dir: PathBuf::from(dir),
+            tick: 0,
+            metrics: PkCacheMetrics::default(),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ProvingKey<G1Affine>> {
+        self.pks.get(id).map(|cached| &cached.pk)
+    }
+
+    pub fn raw_vk(&self, id: &str) -> Option<Vec<u8>> {
+        self.raw_vks.get(id).cloned()
+    }
+
+    // Set the raw VK of id (e.g. loaded from the assets), before its PK is generated.
+    pub fn set_raw_vk(&mut self, id: &str, raw_vk: Vec<u8>) {
+        self.raw_vks.insert(id.to_string(), raw_vk);
+    }
+
+    pub fn metrics(&self) -> &PkCacheMetrics {
+        &self.metrics
+    }
+
+    // Return the PK of id in memory, or reload it from disk, or generate it.
+    pub fn get_or_load<C: Circuit<Fr>>(
+        &mut self,
+        id: &str,
+        params: &ParamsKZG<Bn256>,
+        circuit: &C,
+    ) -> Result<&ProvingKey<G1Affine>> {
+        self.tick += 1;
+
+        if self.pks.contains_key(id) {
+            self.metrics.hits += 1;
+        } else if let Some(&size) = self.dumped.get(id) {
+            self.evict(size);
+
+            log::info!("Before load pk of {id}");
+            let start = Instant::now();
+            let mut reader = BufReader::new(File::open(self.pk_path(id))?);
+            let pk = ProvingKey::<G1Affine>::read::<_, C>(&mut reader, PK_SERDE_FORMAT)?;
+            let elapsed = start.elapsed();
+            log::info!("After load pk of {id}: {size} bytes in {elapsed:?}");
+
+            self.metrics.loads += 1;
+            self.metrics.load_time += elapsed;
+            self.insert(id, pk, size);
+        } else {
+            log::info!("Before generate pk of {id}");
+            let start = Instant::now();
+            let pk = keygen_pk2(params, circuit)?;
+            let elapsed = start.elapsed();
+            log::info!("After generate pk of {id} in {elapsed:?}");
+
+            self.metrics.keygens += 1;
+            self.metrics.keygen_time += elapsed;
+            self.raw_vks
+                .insert(id.to_string(), serialize_vk(pk.get_vk()));
+
+            let size = match self.budget {
+                Some(_) => {
+                    let size = self.dump(id, &pk)?;
+                    self.evict(size);
+                    size
+                }
+                None => 0,
+            };
+            self.insert(id, pk, size);
+        }
+
+        let cached = self.pks.get_mut(id).unwrap();
+        cached.last_used = self.tick;
+
+        Ok(&cached.pk)
+    }
+
+    // Clear the PKs in memory and on disk. The raw VKs are kept, since they're still valid (and
+    // may be restored from checkpoints or set by `set_raw_vk`) when the PKs are regenerated.
+    pub fn clear(&mut self) {
+        self.pks.clear();
+        for id in std::mem::take(&mut self.dumped).into_keys() {
+            let _ = fs::remove_file(self.pk_path(&id));
+        }
+    }
+
+    fn insert(&mut self, id: &str, pk: ProvingKey<G1Affine>, size: usize) {
+        let cached = CachedPk {
+            pk,
+            size,
+            last_used: self.tick,
+        };
+        self.pks.insert(id.to_string(), cached);
+    }
+
+    // Evict the least recently used PKs, until the one of size fits in the budget.
+    fn evict(&mut self, size: usize) {
+        let budget = match self.budget {
+            Some(budget) => budget,
+            None => return,
+        };
+
+        let mut used: usize = self.pks.values().map(|cached| cached.size).sum();
+        while used + size > budget {
+            let lru_id = match self.pks.iter().min_by_key(|(_, cached)| cached.last_used) {
+                Some((id, _)) => id.clone(),
+                None => break,
+            };
+
+            let evicted = self.pks.remove(&lru_id).unwrap();
+            used -= evicted.size;
+            self.metrics.evictions += 1;
+            log::info!(
+                "Evicted pk of {lru_id} ({} bytes), metrics: {:?}",
+                evicted.size,
+                self.metrics
+            );
+        }
+
+        if used + size > budget {
+            log::warn!("pk of {size} bytes exceeds the budget of {budget} bytes");
+        }
+    }
+
+    // Dump the PK to disk, and return its size.
+    fn dump(&mut self, id: &str, pk: &ProvingKey<G1Affine>) -> Result<usize> {
+        fs::create_dir_all(&self.dir)?;
+
+        let path = self.pk_path(id);
+        let mut writer = BufWriter::new(File::create(&path)?);
+        pk.write(&mut writer, PK_SERDE_FORMAT)?;
+        writer.flush()?;
+
+        let size = fs::metadata(&path)?.len() as usize;
+        self.dumped.insert(id.to_string(), size);
+
+        Ok(size)
+    }
+
+    // PK files are specific to the process, since they're only reloaded by the same circuits.
+    fn pk_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("pk_{}_{id}.bin", process::id()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
+        poly::{commitment::ParamsProver, Rotation},
+    };
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    const K: u32 = 4;
+
+    // Circuit of a single gate `q * (a * a - b) = 0`, to generate small PKs.
+    #[derive(Clone, Debug, Default)]
+    struct SquareCircuit;
+
+    impl Circuit<Fr> for SquareCircuit {
+        type Config = (Column<Fixed>, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let q = meta.fixed_column();
+            let a = meta.advice_column();
+            meta.create_gate("a * a = b", |meta| {
+                let q = meta.query_fixed(q, Rotation::cur());
+                let a_cur = meta.query_advice(a, Rotation::cur());
+                let a_next = meta.query_advice(a, Rotation::next());
+                vec![q * (a_cur.clone() * a_cur - a_next)]
+            });
+
+            (q, a)
+        }
+
+        fn synthesize(
+            &self,
+            (q, a): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    region.assign_fixed(|| "q", q, 0, || Value::known(Fr::from(1)))?;
+                    region.assign_advice(|| "a", a, 0, || Value::known(Fr::from(3)))?;
+                    region.assign_advice(|| "b", a, 1, || Value::known(Fr::from(9)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_params() -> ParamsKZG<Bn256> {
+        ParamsKZG::<Bn256>::setup(K, XorShiftRng::from_seed([0; 16]))
+    }
+
+    fn test_dir(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("pk_cache_{name}"))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    // Size of the dumped PK of the test circuit.
+    fn pk_size(params: &ParamsKZG<Bn256>, name: &str) -> usize {
+        let mut cache = PkCache::new(Some(usize::MAX), &test_dir(&format!("{name}_size")));
+        cache.get_or_load("pk", params, &SquareCircuit).unwrap();
+
+        cache.dumped["pk"]
+    }
+
+    fn used(cache: &PkCache) -> usize {
+        cache.pks.values().map(|cached| cached.size).sum()
+    }
+
+    #[test]
+    fn test_pk_cache_lru_eviction() {
+        let params = test_params();
+        let size = pk_size(&params, "lru");
+        let mut cache = PkCache::new(Some(2 * size), &test_dir("lru"));
+
+        for id in ["a", "b", "a", "c"] {
+            cache.get_or_load(id, &params, &SquareCircuit).unwrap();
+        }
+
+        // "b" is the least recently used one when "c" is generated.
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+        assert!(cache.raw_vk("b").is_some());
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.keygens, 3);
+        assert_eq!(metrics.loads, 0);
+        assert_eq!(metrics.evictions, 1);
+    }
+
+    #[test]
+    fn test_pk_cache_reload_after_eviction() {
+        let params = test_params();
+        let size = pk_size(&params, "reload");
+        let mut cache = PkCache::new(Some(size), &test_dir("reload"));
+
+        cache.get_or_load("a", &params, &SquareCircuit).unwrap();
+        let raw_vk = cache.raw_vk("a").unwrap();
+        cache.get_or_load("b", &params, &SquareCircuit).unwrap();
+        assert!(cache.get("a").is_none());
+
+        // Reloaded from disk instead of generated, and "b" is evicted in turn.
+        let pk = cache.get_or_load("a", &params, &SquareCircuit).unwrap();
+        assert_eq!(serialize_vk(pk.get_vk()), raw_vk);
+        assert!(cache.get("b").is_none());
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.keygens, 2);
+        assert_eq!(metrics.loads, 1);
+        assert_eq!(metrics.evictions, 2);
+
+        // The dumped PKs are removed on clear, but the raw VKs are kept.
+        let pk_path = cache.pk_path("a");
+        assert!(pk_path.exists());
+        cache.clear();
+        assert!(!pk_path.exists());
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.raw_vk("a"), Some(raw_vk));
+    }
+
+    #[test]
+    fn test_pk_cache_budget() {
+        let params = test_params();
+        let size = pk_size(&params, "budget");
+
+        // Without budget, PKs are neither dumped nor evicted.
+        let mut cache = PkCache::new(None, &test_dir("unlimited"));
+        for id in ["a", "b", "c"] {
+            cache.get_or_load(id, &params, &SquareCircuit).unwrap();
+        }
+        assert!(cache.dumped.is_empty());
+        assert_eq!(cache.pks.len(), 3);
+        assert_eq!(cache.metrics().evictions, 0);
+
+        // The used memory never exceeds the budget.
+        let budget = 2 * size + size / 2;
+        let mut cache = PkCache::new(Some(budget), &test_dir("budget"));
+        for id in ["a", "b", "c", "d", "b", "a"] {
+            cache.get_or_load(id, &params, &SquareCircuit).unwrap();
+            assert!(used(&cache) <= budget);
+        }
+        assert_eq!(cache.pks.len(), 2);
+        assert_eq!(used(&cache), 2 * size);
+        assert_eq!(cache.dumped.values().sum::<usize>(), 4 * size);
+
+        // A PK larger than the budget is still cached, alone.
+        let mut cache = PkCache::new(Some(size / 2), &test_dir("small"));
+        for id in ["a", "b"] {
+            cache.get_or_load(id, &params, &SquareCircuit).unwrap();
+            assert_eq!(cache.pks.len(), 1);
+        }
+        assert_eq!(cache.metrics().evictions, 1);
+    }
+}
@@ -1,9 +1,8 @@
-use super::Prover;
-use crate::io::serialize_vk;
+use super::{PkCacheMetrics, Prover};
 use anyhow::Result;
 use halo2_proofs::{
     halo2curves::bn256::{Bn256, Fr, G1Affine},
-    plonk::{keygen_pk2, Circuit, ProvingKey},
+    plonk::{Circuit, ProvingKey},
     poly::{commitment::Params, kzg::commitment::ParamsKZG},
 };
 use rand::Rng;
@@ -46,8 +45,9 @@ impl Prover {
         &self.params_map[&degree]
     }
 
+    // Return the PK if it's in memory (not evicted).
     pub fn pk(&self, id: &str) -> Option<&ProvingKey<G1Affine>> {
-        self.pk_map.get(id)
+        self.pk_cache.get(id)
     }
 
     pub fn params_and_pk<C: Circuit<Fr>>(
@@ -56,25 +56,31 @@ impl Prover {
         degree: u32,
         circuit: &C,
     ) -> Result<(&ParamsKZG<Bn256>, &ProvingKey<G1Affine>)> {
-        // Reuse pk.
-        if self.pk_map.contains_key(id) {
-            return Ok((&self.params_map[&degree], &self.pk_map[id]));
-        }
-
-        log::info!("Before generate pk of {}", &id);
-        let pk = keygen_pk2(self.params(degree), circuit)?;
-        log::info!("After generate pk of {}", &id);
+        self.params(degree);
+        let params = &self.params_map[&degree];
 
-        self.pk_map.insert(id.to_string(), pk);
+        // Reuse, reload or generate pk.
+        let pk = self.pk_cache.get_or_load(id, params, circuit)?;
 
-        Ok((&self.params_map[&degree], &self.pk_map[id]))
+        Ok((params, pk))
     }
 
+    // Return the raw VK, which is kept even if the PK is evicted.
     pub fn raw_vk(&self, id: &str) -> Option<Vec<u8>> {
-        self.pk_map.get(id).map(|pk| serialize_vk(pk.get_vk()))
+        self.pk_cache.raw_vk(id)
+    }
+
+    // Set the raw VK of the layer loaded from the assets, to check the checkpoints before the PK
+    // is generated. It's replaced by the generated one after keygen.
+    pub fn set_raw_vk(&mut self, id: &str, raw_vk: Vec<u8>) {
+        self.pk_cache.set_raw_vk(id, raw_vk);
+    }
+
+    pub fn pk_cache_metrics(&self) -> &PkCacheMetrics {
+        self.pk_cache.metrics()
     }
 
     pub fn clear_pks(&mut self) {
-        self.pk_map.clear();
+        self.pk_cache.clear();
     }
 }
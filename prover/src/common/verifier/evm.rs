@@ -6,8 +6,9 @@ use snark_verifier_sdk::{gen_evm_verifier, CircuitExt};
 use std::{path::PathBuf, str::FromStr};
 
 impl<C: CircuitExt<Fr>> Verifier<C> {
-    // Should panic if failed to verify.
-    pub fn evm_verify(&self, evm_proof: &EvmProof, output_dir: Option<&str>) {
+    // Generate the deployment code of the EVM verifier, and dump it with the YUL file if
+    // output dir is specified.
+    pub fn gen_evm_verifier(&self, num_instance: Vec<usize>, output_dir: Option<&str>) -> Vec<u8> {
         let yul_file_path = output_dir.map(|dir| {
             let mut path = PathBuf::from_str(dir).unwrap();
             path.push("evm_verifier.yul");
@@ -18,7 +19,7 @@ impl<C: CircuitExt<Fr>> Verifier<C> {
         let deployment_code = gen_evm_verifier::<C, Kzg<Bn256, Bdfg21>>(
             &self.params,
             &self.vk,
-            evm_proof.num_instance.clone(),
+            num_instance,
             yul_file_path.as_deref(),
         );
 
@@ -28,6 +29,13 @@ impl<C: CircuitExt<Fr>> Verifier<C> {
             write_file(&mut dir, "evm_verifier.bin", &deployment_code);
         }
 
+        deployment_code
+    }
+
+    // Should panic if failed to verify.
+    pub fn evm_verify(&self, evm_proof: &EvmProof, output_dir: Option<&str>) {
+        let deployment_code = self.gen_evm_verifier(evm_proof.num_instance.clone(), output_dir);
+
         let success = evm_proof.proof.evm_verify(deployment_code);
         assert!(success);
     }
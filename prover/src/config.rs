@@ -1,5 +1,6 @@
 use crate::utils::read_env_var;
 use aggregator::ConfigParams;
+use anyhow::{bail, Result};
 use once_cell::sync::Lazy;
 use std::{collections::HashSet, fmt, fs::File, path::Path};
 
@@ -12,11 +13,19 @@ pub static LAYER1_CONFIG_PATH: Lazy<String> = Lazy::new(|| asset_file_path("laye
 pub static LAYER2_CONFIG_PATH: Lazy<String> = Lazy::new(|| asset_file_path("layer2.config"));
 pub static LAYER3_CONFIG_PATH: Lazy<String> = Lazy::new(|| asset_file_path("layer3.config"));
 pub static LAYER4_CONFIG_PATH: Lazy<String> = Lazy::new(|| asset_file_path("layer4.config"));
+pub static LAYER5_CONFIG_PATH: Lazy<String> = Lazy::new(|| asset_file_path("layer5.config"));
+pub static LAYER6_CONFIG_PATH: Lazy<String> = Lazy::new(|| asset_file_path("layer6.config"));
+
+/// Optional row limits of the subcircuits, which override the ones of the super circuit params.
+pub static ROW_LIMITS_CONFIG_PATH: Lazy<String> =
+    Lazy::new(|| asset_file_path("row_limits.config"));
 
 pub static LAYER1_DEGREE: Lazy<u32> = Lazy::new(|| layer_degree(&LAYER1_CONFIG_PATH));
 pub static LAYER2_DEGREE: Lazy<u32> = Lazy::new(|| layer_degree(&LAYER2_CONFIG_PATH));
 pub static LAYER3_DEGREE: Lazy<u32> = Lazy::new(|| layer_degree(&LAYER3_CONFIG_PATH));
 pub static LAYER4_DEGREE: Lazy<u32> = Lazy::new(|| layer_degree(&LAYER4_CONFIG_PATH));
+pub static LAYER5_DEGREE: Lazy<u32> = Lazy::new(|| layer_degree(&LAYER5_CONFIG_PATH));
+pub static LAYER6_DEGREE: Lazy<u32> = Lazy::new(|| layer_degree(&LAYER6_CONFIG_PATH));
 
 pub static ZKEVM_DEGREES: Lazy<Vec<u32>> = Lazy::new(|| {
     Vec::from_iter(HashSet::from([
@@ -29,6 +38,22 @@ pub static ZKEVM_DEGREES: Lazy<Vec<u32>> = Lazy::new(|| {
 pub static AGG_DEGREES: Lazy<Vec<u32>> =
     Lazy::new(|| Vec::from_iter(HashSet::from([*LAYER3_DEGREE, *LAYER4_DEGREE])));
 
+/// Degrees of the bundle layers. Their configs are only read when bundling, so deployments which
+/// only prove batches don't need layer5.config and layer6.config.
+pub fn bundle_degrees() -> Result<Vec<u32>> {
+    for layer_id in [LayerId::Layer5, LayerId::Layer6] {
+        let config_path = layer_id.config_path();
+        if !Path::new(config_path).exists() {
+            bail!("Config {config_path} of bundle {layer_id} doesn't exist");
+        }
+    }
+
+    Ok(Vec::from_iter(HashSet::from([
+        *LAYER5_DEGREE,
+        *LAYER6_DEGREE,
+    ])))
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum LayerId {
     /// Super (inner) circuit layer
@@ -41,6 +66,10 @@ pub enum LayerId {
     Layer3,
     /// Compression thin layer (to generate batch-proof)
     Layer4,
+    /// Aggregation layer of batches
+    Layer5,
+    /// Compression thin layer (to generate bundle-proof)
+    Layer6,
 }
 
 impl fmt::Display for LayerId {
@@ -57,6 +86,8 @@ impl LayerId {
             Self::Layer2 => "layer2",
             Self::Layer3 => "layer3",
             Self::Layer4 => "layer4",
+            Self::Layer5 => "layer5",
+            Self::Layer6 => "layer6",
         }
     }
 
@@ -67,6 +98,8 @@ impl LayerId {
             Self::Layer2 => *LAYER2_DEGREE,
             Self::Layer3 => *LAYER3_DEGREE,
             Self::Layer4 => *LAYER4_DEGREE,
+            Self::Layer5 => *LAYER5_DEGREE,
+            Self::Layer6 => *LAYER6_DEGREE,
         }
     }
 
@@ -76,6 +109,8 @@ impl LayerId {
             Self::Layer2 => &LAYER2_CONFIG_PATH,
             Self::Layer3 => &LAYER3_CONFIG_PATH,
             Self::Layer4 => &LAYER4_CONFIG_PATH,
+            Self::Layer5 => &LAYER5_CONFIG_PATH,
+            Self::Layer6 => &LAYER6_CONFIG_PATH,
             Self::Inner => unreachable!("No config file for super (inner) circuit"),
         }
     }
@@ -94,6 +129,8 @@ pub fn layer_config_path(id: &str) -> &str {
         "layer2" => &LAYER2_CONFIG_PATH,
         "layer3" => &LAYER3_CONFIG_PATH,
         "layer4" => &LAYER4_CONFIG_PATH,
+        "layer5" => &LAYER5_CONFIG_PATH,
+        "layer6" => &LAYER6_CONFIG_PATH,
         _ => panic!("Wrong id-{id} to get layer config path"),
     }
 }
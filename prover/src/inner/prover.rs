@@ -1,13 +1,11 @@
 use crate::{
     common,
     config::INNER_DEGREE,
-    io::serialize_vk,
     utils::{chunk_trace_to_witness_block, gen_rng},
     zkevm::circuit::TargetCircuit,
-    Proof,
+    BlockTrace, Proof,
 };
 use anyhow::Result;
-use eth_types::l2_types::BlockTrace;
 use std::marker::PhantomData;
 
 mod mock;
@@ -52,7 +50,7 @@ impl<C: TargetCircuit> Prover<C> {
                     .inner
                     .gen_inner_snark::<C>(id, rng, &witness_block)
                     .map(|snark| {
-                        let raw_vk = serialize_vk(self.inner.pk(id).unwrap().get_vk());
+                        let raw_vk = self.inner.raw_vk(id).unwrap();
                         Proof::from_snark(snark, raw_vk)
                     });
 
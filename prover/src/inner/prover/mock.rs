@@ -3,9 +3,9 @@ use crate::{
     config::INNER_DEGREE,
     utils::metric_of_witness_block,
     zkevm::circuit::{block_traces_to_witness_block, TargetCircuit},
+    BlockTrace,
 };
 use anyhow::bail;
-use eth_types::l2_types::BlockTrace;
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
 use zkevm_circuits::witness::Block;
 
@@ -1,3 +1,4 @@
+use crate::types::DumpedWitnessBlock;
 use anyhow;
 use halo2_proofs::{
     halo2curves::bn256::{Fq, Fr, G1Affine},
@@ -9,7 +10,7 @@ use snark_verifier::util::arithmetic::PrimeField;
 use snark_verifier_sdk::Snark;
 use std::{
     fs::File,
-    io::{Cursor, Read, Write},
+    io::{BufReader, BufWriter, Cursor, Read, Write},
     path::{Path, PathBuf},
 };
 
@@ -173,6 +174,30 @@ pub fn load_snark(file_path: &str) -> anyhow::Result<Option<Snark>> {
     Ok(Some(snark))
 }
 
+pub fn write_witness_block(
+    file_path: &str,
+    dumped_witness_block: &DumpedWitnessBlock,
+) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(File::create(file_path)?);
+    serde_json::to_writer(&mut writer, dumped_witness_block)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+pub fn load_witness_block(file_path: &str) -> anyhow::Result<Option<DumpedWitnessBlock>> {
+    if !Path::new(file_path).exists() {
+        return Ok(None);
+    }
+
+    let reader = BufReader::new(File::open(file_path)?);
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    deserializer.disable_recursion_limit();
+    let deserializer = serde_stacker::Deserializer::new(&mut deserializer);
+    let dumped_witness_block = serde::Deserialize::deserialize(deserializer)?;
+    Ok(Some(dumped_witness_block))
+}
+
 pub fn load_instances(buf: &[u8]) -> Vec<Vec<Vec<Fr>>> {
     let instances: Vec<Vec<Vec<Vec<u8>>>> = serde_json::from_reader(buf).unwrap();
     instances
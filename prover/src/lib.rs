@@ -5,6 +5,7 @@ pub mod consts;
 pub mod inner;
 pub mod io;
 pub mod proof;
+pub mod service;
 #[cfg(feature = "test")]
 pub mod test;
 pub mod types;
@@ -12,6 +13,12 @@ pub mod utils;
 pub mod zkevm;
 
 pub use common::{ChunkHash, CompressionCircuit};
+/// Without feature `scroll`, a block to prove is a plain Ethereum block: the geth
+/// block with the execution traces of its txs and the prestate of the accessed
+/// accounts.
+#[cfg(not(feature = "scroll"))]
+pub use eth_types::geth_types::GethData as BlockTrace;
+#[cfg(feature = "scroll")]
 pub use eth_types::l2_types::BlockTrace;
 pub use proof::{BatchProof, ChunkProof, EvmProof, Proof};
 pub use snark_verifier_sdk::{CircuitExt, Snark};
@@ -23,10 +23,14 @@ use std::{
 };
 
 mod batch;
+mod binary;
 mod chunk;
 mod evm;
 
 pub use batch::BatchProof;
+pub use binary::{
+    json_file_to_binary, BinaryHeader, BinaryProofKind, BINARY_MAGIC, BINARY_VERSION,
+};
 pub use chunk::ChunkProof;
 pub use evm::EvmProof;
 
@@ -13,7 +13,7 @@ const PI_BYTES: usize = PI_LEN * 32;
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BatchProof {
     #[serde(flatten)]
-    raw: Proof,
+    pub(super) raw: Proof,
 }
 
 impl From<Proof> for BatchProof {
@@ -0,0 +1,400 @@
+//! Versioned binary container of proofs, which is more compact and faster to (de)serialize than
+//! the dumped JSON files.
+//!
+//! Layout (integers are little-endian, and byte arrays and strings are prefixed with a `u32`
+//! length):
+//! - magic `b"SCRP"`, format version (`u16`) and proof kind (`u8`)
+//! - circuit version (short git version, empty if unknown) and layer id
+//! - VK hash (SHA-256, 32 bytes) and VK
+//! - instances: number of field elements (`u32`), each in canonical (little-endian) encoding
+//! - proof
+//! - chunk proofs only: protocol, and chunk hash if any
+
+use super::{BatchProof, ChunkProof, EvmProof, Proof};
+use aggregator::ChunkHash;
+use anyhow::{anyhow, bail, Result};
+use halo2_proofs::halo2curves::bn256::Fr;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use snark_verifier::util::arithmetic::PrimeField;
+use std::fs;
+
+pub const BINARY_MAGIC: [u8; 4] = *b"SCRP";
+pub const BINARY_VERSION: u16 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryProofKind {
+    Proof = 0,
+    Chunk = 1,
+    Batch = 2,
+}
+
+impl TryFrom<u8> for BinaryProofKind {
+    type Error = anyhow::Error;
+
+    fn try_from(kind: u8) -> Result<Self> {
+        match kind {
+            0 => Ok(Self::Proof),
+            1 => Ok(Self::Chunk),
+            2 => Ok(Self::Batch),
+            _ => bail!("unknown binary proof kind {kind}"),
+        }
+    }
+}
+
+/// Header of a binary proof container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BinaryHeader {
+    pub version: u16,
+    pub kind: BinaryProofKind,
+    pub git_version: Option<String>,
+    pub layer_id: String,
+    pub vk_hash: [u8; 32],
+}
+
+impl Proof {
+    pub fn to_binary(&self, layer_id: &str) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.proof(BinaryProofKind::Proof, layer_id, self);
+
+        writer.0
+    }
+
+    pub fn from_binary(buf: &[u8]) -> Result<(BinaryHeader, Self)> {
+        let mut reader = Reader::new(buf);
+        let (header, proof) = reader.proof(BinaryProofKind::Proof)?;
+        reader.finish()?;
+
+        Ok((header, proof))
+    }
+}
+
+impl ChunkProof {
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.proof(BinaryProofKind::Chunk, "layer2", &self.proof);
+        writer.bytes(&self.protocol);
+        match &self.chunk_hash {
+            Some(chunk_hash) => {
+                writer.u8(1);
+                writer.chunk_hash(chunk_hash);
+            }
+            None => writer.u8(0),
+        }
+
+        writer.0
+    }
+
+    pub fn from_binary(buf: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(buf);
+        let (_, proof) = reader.proof(BinaryProofKind::Chunk)?;
+        let protocol = reader.bytes()?.to_vec();
+        let chunk_hash = match reader.u8()? {
+            0 => None,
+            1 => Some(reader.chunk_hash()?),
+            flag => bail!("invalid chunk hash flag {flag}"),
+        };
+        reader.finish()?;
+
+        Ok(Self {
+            protocol,
+            proof,
+            chunk_hash,
+        })
+    }
+}
+
+impl BatchProof {
+    // Layer id is `layer4` for batch proofs, and `layer6` for bundle proofs.
+    pub fn to_binary(&self, layer_id: &str) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.proof(BinaryProofKind::Batch, layer_id, &self.raw);
+
+        writer.0
+    }
+
+    pub fn from_binary(buf: &[u8]) -> Result<(BinaryHeader, Self)> {
+        let mut reader = Reader::new(buf);
+        let (header, raw) = reader.proof(BinaryProofKind::Batch)?;
+        reader.finish()?;
+
+        Ok((header, Self { raw }))
+    }
+}
+
+/// Convert a dumped JSON proof file to the binary container. The kind of the proof is detected
+/// by the JSON fields: chunk proofs have `protocol`, EVM proofs have `num_instance` (converted as
+/// `Proof` of the layer), and the others are batch (or bundle) proofs. The layer id of EVM and
+/// batch proofs is `layer4` by default, and should be `layer6` for bundle proofs.
+pub fn json_file_to_binary(json_path: &str, layer_id: Option<&str>) -> Result<Vec<u8>> {
+    let json: Value = serde_json::from_slice(&fs::read(json_path)?)?;
+    let fields = json
+        .as_object()
+        .ok_or_else(|| anyhow!("{json_path} is not a proof JSON file"))?;
+
+    let buf = if fields.contains_key("protocol") {
+        serde_json::from_value::<ChunkProof>(json)?.to_binary()
+    } else if fields.contains_key("num_instance") {
+        serde_json::from_value::<EvmProof>(json)?
+            .proof
+            .to_binary(layer_id.unwrap_or("layer4"))
+    } else {
+        serde_json::from_value::<BatchProof>(json)?.to_binary(layer_id.unwrap_or("layer4"))
+    };
+
+    Ok(buf)
+}
+
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: usize) {
+        let value = u32::try_from(value).expect("binary proof field too long");
+        self.0.extend(value.to_le_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.u32(bytes.len());
+        self.0.extend(bytes);
+    }
+
+    fn proof(&mut self, kind: BinaryProofKind, layer_id: &str, proof: &Proof) {
+        self.0.extend(BINARY_MAGIC);
+        self.0.extend(BINARY_VERSION.to_le_bytes());
+        self.u8(kind as u8);
+        self.bytes(proof.git_version.as_deref().unwrap_or_default().as_bytes());
+        self.bytes(layer_id.as_bytes());
+        self.0.extend(Sha256::digest(&proof.vk));
+        self.bytes(&proof.vk);
+
+        // Instances are dumped as 32-byte big-endian values.
+        assert_eq!(proof.instances.len() % 32, 0);
+        self.u32(proof.instances.len() / 32);
+        for value in proof.instances.chunks(32) {
+            self.0.extend(value.iter().rev());
+        }
+
+        self.bytes(&proof.proof);
+    }
+
+    fn chunk_hash(&mut self, chunk_hash: &ChunkHash) {
+        self.0.extend(chunk_hash.chain_id.to_le_bytes());
+        self.0.extend(chunk_hash.prev_state_root.as_bytes());
+        self.0.extend(chunk_hash.post_state_root.as_bytes());
+        self.0.extend(chunk_hash.withdraw_root.as_bytes());
+        self.0.extend(chunk_hash.data_hash.as_bytes());
+        self.u8(chunk_hash.is_padding.into());
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, offset: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or_else(|| anyhow!("binary proof truncated at {}", self.offset))?;
+        let bytes = &self.buf[self.offset..end];
+        self.offset = end;
+
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<usize> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.u32()?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        Ok(String::from_utf8(self.bytes()?.to_vec())?)
+    }
+
+    fn proof(&mut self, expected_kind: BinaryProofKind) -> Result<(BinaryHeader, Proof)> {
+        if self.array()? != BINARY_MAGIC {
+            bail!("not a binary proof");
+        }
+        let version = u16::from_le_bytes(self.array()?);
+        if version != BINARY_VERSION {
+            bail!("unsupported binary proof version {version}");
+        }
+        let kind = BinaryProofKind::try_from(self.u8()?)?;
+        if kind != expected_kind {
+            bail!("binary proof kind {kind:?} != {expected_kind:?}");
+        }
+        let git_version = Some(self.string()?).filter(|version| !version.is_empty());
+        let layer_id = self.string()?;
+
+        let vk_hash = self.array()?;
+        let vk = self.bytes()?.to_vec();
+        if Sha256::digest(&vk).as_slice() != vk_hash {
+            bail!("VK hash mismatch in binary proof");
+        }
+
+        let num_instances = self.u32()?;
+        let mut instances = Vec::with_capacity(num_instances.min(self.buf.len() / 32) * 32);
+        for _ in 0..num_instances {
+            let repr = self.array::<32>()?;
+            if Option::<Fr>::from(Fr::from_repr(repr)).is_none() {
+                bail!("non-canonical instance in binary proof");
+            }
+            instances.extend(repr.iter().rev());
+        }
+
+        let proof = self.bytes()?.to_vec();
+
+        let header = BinaryHeader {
+            version,
+            kind,
+            git_version: git_version.clone(),
+            layer_id,
+            vk_hash,
+        };
+
+        Ok((
+            header,
+            Proof {
+                proof,
+                instances,
+                vk,
+                git_version,
+            },
+        ))
+    }
+
+    fn chunk_hash(&mut self) -> Result<ChunkHash> {
+        let chain_id = u64::from_le_bytes(self.array()?);
+        let prev_state_root = self.array::<32>()?.into();
+        let post_state_root = self.array::<32>()?.into();
+        let withdraw_root = self.array::<32>()?.into();
+        let data_hash = self.array::<32>()?.into();
+        let is_padding = match self.u8()? {
+            0 => false,
+            1 => true,
+            flag => bail!("invalid padding flag {flag}"),
+        };
+
+        Ok(ChunkHash {
+            chain_id,
+            prev_state_root,
+            post_state_root,
+            withdraw_root,
+            data_hash,
+            is_padding,
+        })
+    }
+
+    fn finish(&self) -> Result<()> {
+        if self.offset != self.buf.len() {
+            bail!("trailing bytes in binary proof");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_proof(num_instances: u64) -> Proof {
+        let instances: Vec<_> = (0..num_instances).map(|i| Fr::from(i * 1000 + 7)).collect();
+
+        Proof {
+            vk: vec![1, 2, 3],
+            ..Proof::new(vec![9; 100], &[instances], None)
+        }
+    }
+
+    fn assert_same_json<T: serde::Serialize>(a: &T, b: &T) {
+        assert_eq!(
+            serde_json::to_string(a).unwrap(),
+            serde_json::to_string(b).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_binary_proof_round_trip() {
+        let proof = test_proof(44);
+        let buf = proof.to_binary("layer4");
+        let (header, decoded) = Proof::from_binary(&buf).unwrap();
+        assert_eq!(header.layer_id, "layer4");
+        assert_eq!(header.git_version, proof.git_version);
+        assert_eq!(
+            header.vk_hash.as_slice(),
+            Sha256::digest(&proof.vk).as_slice()
+        );
+        assert_eq!(decoded.instances(), proof.instances());
+        assert_same_json(&decoded, &proof);
+
+        let chunk_proof = ChunkProof {
+            protocol: vec![5; 10],
+            proof: test_proof(44),
+            chunk_hash: Some(ChunkHash {
+                chain_id: 534352,
+                prev_state_root: [1; 32].into(),
+                post_state_root: [2; 32].into(),
+                withdraw_root: [3; 32].into(),
+                data_hash: [4; 32].into(),
+                is_padding: false,
+            }),
+        };
+        let decoded = ChunkProof::from_binary(&chunk_proof.to_binary()).unwrap();
+        assert_same_json(&decoded, &chunk_proof);
+
+        let batch_proof = BatchProof::from(test_proof(44));
+        let (header, decoded) = BatchProof::from_binary(&batch_proof.to_binary("layer4")).unwrap();
+        assert_eq!(header.layer_id, "layer4");
+        assert_same_json(&decoded, &batch_proof);
+
+        let bundle_proof = BatchProof::from(test_proof(44));
+        let (header, decoded) = BatchProof::from_binary(&bundle_proof.to_binary("layer6")).unwrap();
+        assert_eq!(header.layer_id, "layer6");
+        assert_same_json(&decoded, &bundle_proof);
+    }
+
+    #[test]
+    fn test_invalid_binary_proof() {
+        let buf = test_proof(2).to_binary("layer2");
+
+        // Wrong kind and truncated buffer.
+        assert!(ChunkProof::from_binary(&buf).is_err());
+        assert!(Proof::from_binary(&buf[..buf.len() - 1]).is_err());
+
+        // Unsupported version.
+        let mut invalid = buf.clone();
+        invalid[4] += 1;
+        assert!(Proof::from_binary(&invalid).is_err());
+
+        // Non-canonical instance (modulus).
+        let offset = buf.len() - 4 - 100 - 2 * 32;
+        let mut invalid = buf;
+        invalid[offset..offset + 32].copy_from_slice(&[0xff; 32]);
+        assert!(Proof::from_binary(&invalid).is_err());
+    }
+}
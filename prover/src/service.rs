@@ -0,0 +1,538 @@
+//! A local job queue to run the chunk and batch provers as a long-lived service.
+//!
+//! The queue lives in a work directory:
+//! - `inbox/<id>.json`: job requests ([`JobRequest`]) submitted by clients. A client must write the
+//!   request to `inbox/<id>.json.tmp` first and then rename it, so that the queue never reads a
+//!   partially written request. A request of an existing job is only accepted to retry a failed
+//!   job, otherwise it's renamed to `inbox/<id>.json.rejected`.
+//! - `jobs/<id>.json`: the persisted state ([`Job`]) of each accepted job.
+//! - `outputs/<id>/`: the intermediate snarks and the final proof of each job.
+//!
+//! Since the state of a job is persisted before and after proving, and the provers load
+//! the intermediate snarks already generated in the output dir, the service resumes the
+//! interrupted jobs after a crash.
+
+use crate::{
+    aggregator::Prover as BatchProver,
+    utils::{get_block_trace_from_file, panic_message},
+    zkevm::Prover as ChunkProver,
+    ChunkHash, ChunkProof,
+};
+use anyhow::{anyhow, bail, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+const INBOX_DIR: &str = "inbox";
+const JOBS_DIR: &str = "jobs";
+const OUTPUTS_DIR: &str = "outputs";
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobRequest {
+    /// Generate a chunk proof of the block traces in the files.
+    Chunk { traces: Vec<String> },
+    /// Generate a batch proof of the chunks proved by the jobs.
+    Batch { chunks: Vec<String> },
+    /// A request which can't be parsed, kept as is to be inspected.
+    Invalid { raw: String },
+}
+
+impl JobRequest {
+    // Return the IDs of the jobs to finish before this one.
+    fn dependencies(&self) -> &[String] {
+        match self {
+            Self::Chunk { .. } | Self::Invalid { .. } => &[],
+            Self::Batch { chunks } => chunks,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Done,
+    Failed { error: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Job {
+    pub id: String,
+    /// Submission order of the job.
+    pub seq: u64,
+    pub request: JobRequest,
+    #[serde(flatten)]
+    pub status: JobStatus,
+    /// Number of times the job has been started.
+    pub attempts: usize,
+}
+
+#[derive(Debug)]
+pub struct JobQueue {
+    work_dir: PathBuf,
+    jobs: BTreeMap<String, Job>,
+}
+
+impl JobQueue {
+    /// Open the queue in the work dir, and reload the persisted jobs.
+    pub fn open(work_dir: &str) -> Result<Self> {
+        let work_dir = PathBuf::from(work_dir);
+        for dir in [INBOX_DIR, JOBS_DIR, OUTPUTS_DIR] {
+            fs::create_dir_all(work_dir.join(dir))?;
+        }
+
+        let mut jobs = BTreeMap::new();
+        for path in json_files(&work_dir.join(JOBS_DIR))? {
+            let job: Job = serde_json::from_slice(&fs::read(&path)?)
+                .map_err(|err| anyhow!("invalid job file {path:?}: {err}"))?;
+            jobs.insert(job.id.clone(), job);
+        }
+
+        Ok(Self { work_dir, jobs })
+    }
+
+    /// Reset the jobs interrupted by a crash to pending, or to failed if they have already
+    /// been started `max_attempts` times.
+    pub fn recover(&mut self, max_attempts: usize) -> Result<()> {
+        let interrupted: Vec<_> = self
+            .jobs
+            .values()
+            .filter(|job| job.status == JobStatus::Running)
+            .cloned()
+            .collect();
+
+        for mut job in interrupted {
+            job.status = if job.attempts < max_attempts {
+                log::warn!("prover-service: resume interrupted job {}", job.id);
+                JobStatus::Pending
+            } else {
+                JobStatus::Failed {
+                    error: format!("interrupted after {} attempts", job.attempts),
+                }
+            };
+            self.update(job)?;
+        }
+
+        Ok(())
+    }
+
+    /// Accept the requests in the inbox as pending jobs, in the order of submission. The request
+    /// of an existing job is accepted only if the job has failed, to retry it.
+    /// Return the number of accepted jobs.
+    pub fn accept_inbox(&mut self) -> Result<usize> {
+        let mut requests = vec![];
+        for path in json_files(&self.work_dir.join(INBOX_DIR))? {
+            let modified = fs::metadata(&path)?.modified()?;
+            requests.push((modified, path));
+        }
+        requests.sort();
+
+        let mut accepted = 0;
+        for (_, path) in requests {
+            let id = path.file_stem().unwrap().to_string_lossy().to_string();
+            let raw = fs::read(&path)?;
+            let (request, status) = match serde_json::from_slice(&raw) {
+                Ok(request) => (request, JobStatus::Pending),
+                Err(err) => (
+                    JobRequest::Invalid {
+                        raw: String::from_utf8_lossy(&raw).into_owned(),
+                    },
+                    JobStatus::Failed {
+                        error: format!("invalid job request: {err}"),
+                    },
+                ),
+            };
+
+            match self.jobs.get(&id) {
+                None
+                | Some(Job {
+                    status: JobStatus::Failed { .. },
+                    ..
+                }) => {
+                    let seq = self.jobs.values().map(|job| job.seq + 1).max().unwrap_or(0);
+                    self.update(Job {
+                        id: id.clone(),
+                        seq,
+                        request,
+                        status,
+                        attempts: 0,
+                    })?;
+                    log::info!("prover-service: accepted job {id}");
+                    accepted += 1;
+                }
+                // The request has been accepted before a crash, but not removed from the inbox.
+                Some(job) if job.request == request => (),
+                Some(job) => {
+                    log::error!(
+                        "prover-service: rejected request of existing job {id} ({:?})",
+                        job.status
+                    );
+                    fs::rename(&path, path.with_extension("json.rejected"))?;
+                    continue;
+                }
+            }
+
+            fs::remove_file(&path)?;
+        }
+
+        Ok(accepted)
+    }
+
+    /// Return the first pending job, of which the dependencies are not pending or running.
+    pub fn next_job(&self) -> Option<Job> {
+        let mut pending: Vec<_> = self
+            .jobs
+            .values()
+            .filter(|job| job.status == JobStatus::Pending)
+            .collect();
+        pending.sort_by_key(|job| job.seq);
+
+        pending
+            .into_iter()
+            .find(|job| {
+                job.request.dependencies().iter().all(|id| {
+                    !matches!(
+                        self.jobs.get(id).map(|dep| &dep.status),
+                        Some(JobStatus::Pending | JobStatus::Running)
+                    )
+                })
+            })
+            .cloned()
+    }
+
+    pub fn job(&self, id: &str) -> Option<&Job> {
+        self.jobs.get(id)
+    }
+
+    /// Persist the job state, then update it in the queue.
+    pub fn update(&mut self, job: Job) -> Result<()> {
+        // Write to a temporary file first, since a rename is atomic.
+        let path = self
+            .work_dir
+            .join(JOBS_DIR)
+            .join(format!("{}.json", job.id));
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&job)?)?;
+        fs::rename(&tmp_path, &path)?;
+
+        self.jobs.insert(job.id.clone(), job);
+
+        Ok(())
+    }
+
+    /// Return the output dir of the job, creating it if it doesn't exist.
+    pub fn output_dir(&self, id: &str) -> Result<String> {
+        let dir = self.work_dir.join(OUTPUTS_DIR).join(id);
+        fs::create_dir_all(&dir)?;
+
+        Ok(dir.to_string_lossy().to_string())
+    }
+}
+
+#[derive(Debug)]
+pub struct Service {
+    queue: JobQueue,
+    params_dir: String,
+    assets_dir: String,
+    // Constructed on the first job of each kind, then kept to reuse the loaded params and PKs.
+    chunk_prover: Option<ChunkProver>,
+    batch_prover: Option<BatchProver>,
+}
+
+impl Service {
+    pub fn new(
+        work_dir: &str,
+        params_dir: &str,
+        assets_dir: &str,
+        max_attempts: usize,
+    ) -> Result<Self> {
+        let mut queue = JobQueue::open(work_dir)?;
+        queue.recover(max_attempts)?;
+
+        Ok(Self {
+            queue,
+            params_dir: params_dir.to_string(),
+            assets_dir: assets_dir.to_string(),
+            chunk_prover: None,
+            batch_prover: None,
+        })
+    }
+
+    /// Poll the inbox and run the jobs forever.
+    pub fn run(&mut self, poll_interval: Duration) -> Result<()> {
+        loop {
+            self.run_pending()?;
+            thread::sleep(poll_interval);
+        }
+    }
+
+    /// Accept the requests in the inbox, then run the jobs until none of them is runnable.
+    /// Return the number of run jobs.
+    pub fn run_pending(&mut self) -> Result<usize> {
+        let mut count = 0;
+        loop {
+            self.queue.accept_inbox()?;
+            match self.queue.next_job() {
+                Some(job) => self.run_job(job)?,
+                None => return Ok(count),
+            }
+            count += 1;
+        }
+    }
+
+    fn run_job(&mut self, mut job: Job) -> Result<()> {
+        log::info!("prover-service: start job {} ({:?})", job.id, job.request);
+
+        job.status = JobStatus::Running;
+        job.attempts += 1;
+        self.queue.update(job.clone())?;
+
+        // Prover panics (e.g. unsatisfied circuits) fail the job instead of the service.
+        let result = match panic::catch_unwind(AssertUnwindSafe(|| self.prove(&job))) {
+            Ok(result) => result,
+            Err(err) => {
+                let panic_err = panic_message(&*err);
+
+                // The prover may be left inconsistent by the panic, so rebuild it for next jobs.
+                match job.request {
+                    JobRequest::Chunk { .. } => self.chunk_prover = None,
+                    JobRequest::Batch { .. } => self.batch_prover = None,
+                    JobRequest::Invalid { .. } => (),
+                }
+
+                Err(anyhow!("panicked: {panic_err}"))
+            }
+        };
+
+        job.status = match result {
+            Ok(()) => {
+                log::info!("prover-service: finished job {}", job.id);
+                JobStatus::Done
+            }
+            Err(err) => {
+                log::error!("prover-service: job {} failed: {err}", job.id);
+                JobStatus::Failed {
+                    error: err.to_string(),
+                }
+            }
+        };
+
+        self.queue.update(job)
+    }
+
+    fn prove(&mut self, job: &Job) -> Result<()> {
+        let output_dir = self.queue.output_dir(&job.id)?;
+
+        match &job.request {
+            JobRequest::Chunk { traces } => {
+                if traces.is_empty() {
+                    bail!("no block trace in chunk job");
+                }
+                let chunk_trace = traces.iter().map(get_block_trace_from_file).collect();
+
+                let prover = self.chunk_prover.get_or_insert_with(|| {
+                    ChunkProver::from_dirs(&self.params_dir, &self.assets_dir)
+                });
+                prover.gen_chunk_proof(chunk_trace, Some(&job.id), None, Some(&output_dir))?;
+            }
+            JobRequest::Batch { chunks } => {
+                let chunk_hashes_proofs = chunks
+                    .iter()
+                    .map(|id| self.load_chunk_proof(id))
+                    .collect::<Result<Vec<_>>>()?;
+
+                let prover = self.batch_prover.get_or_insert_with(|| {
+                    BatchProver::from_dirs(&self.params_dir, &self.assets_dir)
+                });
+                let batch_proof = prover.gen_agg_evm_proof(
+                    chunk_hashes_proofs,
+                    Some(&job.id),
+                    Some(&output_dir),
+                )?;
+                batch_proof.dump(&output_dir, &job.id)?;
+            }
+            JobRequest::Invalid { .. } => bail!("invalid job request"),
+        }
+
+        Ok(())
+    }
+
+    fn load_chunk_proof(&self, id: &str) -> Result<(ChunkHash, ChunkProof)> {
+        match self.queue.job(id).map(|job| &job.status) {
+            Some(JobStatus::Done) => (),
+            Some(status) => bail!("chunk job {id} is not done: {status:?}"),
+            None => bail!("chunk job {id} doesn't exist"),
+        }
+
+        let proof = ChunkProof::from_json_file(&self.queue.output_dir(id)?, id)?;
+        let chunk_hash = proof
+            .chunk_hash
+            .ok_or_else(|| anyhow!("no chunk hash in proof of chunk job {id}"))?;
+
+        Ok((chunk_hash, proof))
+    }
+}
+
+// Return the `.json` files in the dir, sorted by name.
+fn json_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        // `<id>.json.tmp` files are still being written.
+        if path.is_file() && path.extension().map_or(false, |ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_queue(name: &str) -> JobQueue {
+        let work_dir = std::env::temp_dir().join(format!("prover_service_{name}"));
+        let _ = fs::remove_dir_all(&work_dir);
+
+        JobQueue::open(&work_dir.to_string_lossy()).unwrap()
+    }
+
+    fn submit(queue: &JobQueue, id: &str, request: &str) {
+        let path = queue.work_dir.join(INBOX_DIR).join(format!("{id}.json"));
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, request).unwrap();
+        fs::rename(tmp_path, path).unwrap();
+    }
+
+    #[test]
+    fn test_skip_requests_being_written() {
+        let mut queue = test_queue("partial");
+        let path = queue.work_dir.join(INBOX_DIR).join("chunk-1.json");
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, r#"{"type": "chunk", "tra"#).unwrap();
+        assert_eq!(queue.accept_inbox().unwrap(), 0);
+        assert!(queue.job("chunk-1").is_none());
+
+        fs::write(&tmp_path, r#"{"type": "chunk", "traces": ["1.json"]}"#).unwrap();
+        fs::rename(&tmp_path, &path).unwrap();
+        assert_eq!(queue.accept_inbox().unwrap(), 1);
+        assert_eq!(queue.job("chunk-1").unwrap().status, JobStatus::Pending);
+    }
+
+    #[test]
+    fn test_batch_job_waits_for_chunk_jobs() {
+        let mut queue = test_queue("dependencies");
+        submit(
+            &queue,
+            "chunk-1",
+            r#"{"type": "chunk", "traces": ["1.json"]}"#,
+        );
+        assert_eq!(queue.accept_inbox().unwrap(), 1);
+        submit(
+            &queue,
+            "batch-1",
+            r#"{"type": "batch", "chunks": ["chunk-1"]}"#,
+        );
+        submit(&queue, "invalid", r#"{"type": "bundle"}"#);
+        assert_eq!(queue.accept_inbox().unwrap(), 2);
+        let invalid = queue.job("invalid").unwrap();
+        assert!(matches!(invalid.status, JobStatus::Failed { .. }));
+        assert_eq!(
+            invalid.request,
+            JobRequest::Invalid {
+                raw: r#"{"type": "bundle"}"#.to_string()
+            }
+        );
+
+        let mut job = queue.next_job().unwrap();
+        assert_eq!(job.id, "chunk-1");
+        job.status = JobStatus::Running;
+        queue.update(job.clone()).unwrap();
+        assert_eq!(queue.next_job(), None);
+
+        job.status = JobStatus::Done;
+        queue.update(job).unwrap();
+        assert_eq!(queue.next_job().unwrap().id, "batch-1");
+    }
+
+    #[test]
+    fn test_resubmit_existing_job() {
+        let mut queue = test_queue("resubmit");
+        let request = r#"{"type": "chunk", "traces": ["1.json"]}"#;
+        submit(&queue, "chunk-1", request);
+        assert_eq!(queue.accept_inbox().unwrap(), 1);
+
+        // The same request left in the inbox by a crash is removed.
+        submit(&queue, "chunk-1", request);
+        assert_eq!(queue.accept_inbox().unwrap(), 0);
+        let inbox_path = queue.work_dir.join(INBOX_DIR).join("chunk-1.json");
+        assert!(!inbox_path.exists());
+
+        // Another request of the pending job is rejected.
+        submit(
+            &queue,
+            "chunk-1",
+            r#"{"type": "chunk", "traces": ["2.json"]}"#,
+        );
+        assert_eq!(queue.accept_inbox().unwrap(), 0);
+        assert!(!inbox_path.exists());
+        assert!(inbox_path.with_extension("json.rejected").exists());
+        assert_eq!(queue.job("chunk-1").unwrap().status, JobStatus::Pending);
+
+        // The failed job is retried.
+        let mut job = queue.job("chunk-1").unwrap().clone();
+        job.status = JobStatus::Failed {
+            error: "failed".to_string(),
+        };
+        job.attempts = 1;
+        queue.update(job).unwrap();
+        submit(&queue, "chunk-1", request);
+        assert_eq!(queue.accept_inbox().unwrap(), 1);
+        let job = queue.job("chunk-1").unwrap();
+        assert_eq!(job.status, JobStatus::Pending);
+        assert_eq!(job.attempts, 0);
+    }
+
+    #[test]
+    fn test_recover_interrupted_jobs() {
+        let mut queue = test_queue("recover");
+        submit(
+            &queue,
+            "chunk-1",
+            r#"{"type": "chunk", "traces": ["1.json"]}"#,
+        );
+        submit(
+            &queue,
+            "chunk-2",
+            r#"{"type": "chunk", "traces": ["2.json"]}"#,
+        );
+        queue.accept_inbox().unwrap();
+
+        for (id, attempts) in [("chunk-1", 1), ("chunk-2", 3)] {
+            let mut job = queue.job(id).unwrap().clone();
+            job.status = JobStatus::Running;
+            job.attempts = attempts;
+            queue.update(job).unwrap();
+        }
+
+        // Reopen the queue as after a crash.
+        let mut queue = JobQueue::open(&queue.work_dir.to_string_lossy()).unwrap();
+        queue.recover(3).unwrap();
+        assert_eq!(queue.job("chunk-1").unwrap().status, JobStatus::Pending);
+        assert!(matches!(
+            queue.job("chunk-2").unwrap().status,
+            JobStatus::Failed { .. }
+        ));
+        assert_eq!(queue.next_job().unwrap().id, "chunk-1");
+    }
+}
@@ -68,7 +68,10 @@ pub fn chunk_prove(test: &str, witness_block: &WitnessBlock) -> ChunkProof {
     ChunkProof::new(
         snark,
         prover.pk(LayerId::Layer2.id()),
-        Some(ChunkHash::from_witness_block(witness_block, false)),
+        Some(
+            ChunkHash::from_witness_block(witness_block, false)
+                .unwrap_or_else(|err| panic!("{test}: failed to build chunk hash: {err}")),
+        ),
     )
     .unwrap_or_else(|err| panic!("{test}: failed to crate chunk proof: {err}"))
 }
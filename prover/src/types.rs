@@ -1,10 +1,21 @@
-use eth_types::l2_types::BlockTrace;
+use crate::BlockTrace;
+use bus_mapping::circuit_input_builder::CircuitsParams;
 use halo2_proofs::halo2curves::bn256::Fr;
 use serde::{Deserialize, Serialize};
 use zkevm_circuits::evm_circuit::witness::Block;
 
 pub type WitnessBlock = Block<Fr>;
 
+/// Witness block dumped together with the circuit version and params generating it, which is
+/// only reloaded by the same ones.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DumpedWitnessBlock {
+    /// Short git version of the circuits.
+    pub git_version: String,
+    pub circuits_params: CircuitsParams,
+    pub witness_block: WitnessBlock,
+}
+
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
 pub struct BlockTraceJsonRpcResult {
     pub result: BlockTrace,
@@ -1,10 +1,12 @@
 use crate::{
     types::BlockTraceJsonRpcResult,
     zkevm::circuit::{block_traces_to_witness_block, check_batch_capacity},
+    BlockTrace,
 };
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use chrono::Utc;
-use eth_types::{l2_types::BlockTrace, Address};
+#[cfg(feature = "scroll")]
+use eth_types::Address;
 use git_version::git_version;
 use halo2_proofs::{
     halo2curves::bn256::{Bn256, Fr},
@@ -22,11 +24,15 @@ use log4rs::{
 use rand::{Rng, SeedableRng};
 use rand_xorshift::XorShiftRng;
 use std::{
+    any::Any,
+    collections::BTreeMap,
     fs::{self, metadata, File},
     io::{BufReader, Read},
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
     str::FromStr,
-    sync::Once,
+    sync::{mpsc, Condvar, Mutex, Once, PoisonError},
+    thread,
 };
 use zkevm_circuits::evm_circuit::witness::Block;
 
@@ -91,6 +97,7 @@ pub fn get_block_trace_from_file<P: AsRef<Path>>(path: P) -> BlockTrace {
     let mut f = File::open(&path).unwrap();
     f.read_to_end(&mut buffer).unwrap();
 
+    #[allow(unused_mut)]
     let mut trace = serde_json::from_slice::<BlockTrace>(&buffer).unwrap_or_else(|e1| {
         serde_json::from_slice::<BlockTraceJsonRpcResult>(&buffer)
             .map_err(|e2| {
@@ -105,10 +112,12 @@ pub fn get_block_trace_from_file<P: AsRef<Path>>(path: P) -> BlockTrace {
             .result
     });
     // fill intrinsicStorageProofs into tx storage proof
+    #[cfg(feature = "scroll")]
     let addrs = vec![
         Address::from_str("0x5300000000000000000000000000000000000000").unwrap(),
         Address::from_str("0x5300000000000000000000000000000000000002").unwrap(),
     ];
+    #[cfg(feature = "scroll")]
     for tx_storage_trace in &mut trace.tx_storage_trace {
         if let Some(proof) = tx_storage_trace.proofs.as_mut() {
             for addr in &addrs {
@@ -165,6 +174,114 @@ pub fn chunk_trace_to_witness_block(mut chunk_trace: Vec<BlockTrace>) -> Result<
     block_traces_to_witness_block(chunk_trace)
 }
 
+/// Generate the witness blocks of chunks concurrently by `num_workers` threads with `gen_witness`,
+/// and handle them in order by `f` (e.g. proving) as soon as each one is ready. At most
+/// `num_workers` witness blocks are generated ahead of `f` to bound the memory usage. It stops at
+/// the first error (or panic) of `gen_witness` or `f`.
+pub fn pipeline_chunk_witness_blocks<I: Send, W: Send, T>(
+    chunk_traces: Vec<I>,
+    num_workers: usize,
+    gen_witness: impl Fn(I) -> Result<W> + Sync,
+    mut f: impl FnMut(usize, W) -> Result<T>,
+) -> Result<Vec<T>> {
+    let num_chunks = chunk_traces.len();
+    let num_workers = num_workers.clamp(1, num_chunks.max(1));
+    let chunk_traces: Vec<_> = chunk_traces
+        .into_iter()
+        .map(|chunk_trace| Mutex::new(Some(chunk_trace)))
+        .collect();
+
+    // (index of the next chunk to generate, number of handled chunks, stopped)
+    let state = Mutex::new((0, 0, false));
+    let state_changed = Condvar::new();
+
+    thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel();
+        for _ in 0..num_workers {
+            let tx = tx.clone();
+            let (chunk_traces, gen_witness) = (&chunk_traces, &gen_witness);
+            let (state, state_changed) = (&state, &state_changed);
+            scope.spawn(move || loop {
+                let index = {
+                    let mut state = state.lock().unwrap();
+                    loop {
+                        let (next, handled, stopped) = *state;
+                        if stopped || next >= num_chunks {
+                            return;
+                        }
+                        if next < handled + num_workers {
+                            break;
+                        }
+                        state = state_changed.wait(state).unwrap();
+                    }
+                    state.0 += 1;
+                    state.0 - 1
+                };
+
+                // A panic is sent as an error of the chunk, then the receiver never waits for it.
+                let chunk_trace = chunk_traces[index].lock().unwrap().take().unwrap();
+                let result = panic::catch_unwind(AssertUnwindSafe(|| gen_witness(chunk_trace)))
+                    .unwrap_or_else(|err| {
+                        Err(anyhow!(
+                            "witness generation of chunk-{index} panicked: {}",
+                            panic_message(&err)
+                        ))
+                    });
+                log::info!("Got witness block of chunk-{index}");
+                if tx.send((index, result)).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(tx);
+
+        // Stop the waiting workers when returning (or unwinding from a panic of `f`), otherwise
+        // the scope never joins them.
+        let _stop = StopOnDrop(&state, &state_changed);
+
+        let mut ready = BTreeMap::new();
+        let mut outputs = Vec::with_capacity(num_chunks);
+        for index in 0..num_chunks {
+            let witness_block = loop {
+                if let Some(result) = ready.remove(&index) {
+                    break result;
+                }
+                let (i, result) = rx
+                    .recv()
+                    .map_err(|_| anyhow!("witness workers stopped before chunk-{index}"))?;
+                ready.insert(i, result);
+            };
+
+            outputs.push(witness_block.and_then(|witness_block| f(index, witness_block))?);
+
+            state.lock().unwrap().1 += 1;
+            state_changed.notify_all();
+        }
+
+        Ok(outputs)
+    })
+}
+
+struct StopOnDrop<'a>(&'a Mutex<(usize, usize, bool)>, &'a Condvar);
+
+impl Drop for StopOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).2 = true;
+        self.1.notify_all();
+    }
+}
+
+/// Return the message of a panic payload.
+pub fn panic_message(err: &(dyn Any + Send)) -> String {
+    if let Some(s) = err.downcast_ref::<String>() {
+        s.to_string()
+    } else if let Some(s) = err.downcast_ref::<&str>() {
+        s.to_string()
+    } else {
+        "unable to get panic info".into()
+    }
+}
+
 // Return the output dir.
 pub fn init_env_and_log(id: &str) -> String {
     dotenvy::dotenv().ok();
@@ -240,3 +357,87 @@ pub fn short_git_version() -> String {
         commit_version[1..8].to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    #[test]
+    fn test_pipeline_in_order() {
+        // The later chunks are generated faster, but still handled in order.
+        let outputs = pipeline_chunk_witness_blocks(
+            (0..8).collect(),
+            4,
+            |i: u64| {
+                thread::sleep(Duration::from_millis(10 * (8 - i)));
+                Ok(i * 2)
+            },
+            |index, witness| Ok((index, witness)),
+        )
+        .unwrap();
+
+        assert_eq!(
+            outputs,
+            (0..8).map(|i| (i, i as u64 * 2)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_pipeline_stop_on_error() {
+        let generated = AtomicUsize::new(0);
+        let mut handled = vec![];
+        let result = pipeline_chunk_witness_blocks(
+            (0..32).collect(),
+            2,
+            |i: usize| {
+                generated.fetch_add(1, Ordering::SeqCst);
+                if i == 3 {
+                    bail!("invalid chunk-{i}");
+                }
+                Ok(i)
+            },
+            |index, _| {
+                handled.push(index);
+                Ok(())
+            },
+        );
+
+        assert!(result.unwrap_err().to_string().contains("invalid chunk-3"));
+        assert_eq!(handled, vec![0, 1, 2]);
+        // At most `num_workers` chunks are generated ahead of the failed one.
+        assert!(generated.load(Ordering::SeqCst) <= 5);
+
+        let result = pipeline_chunk_witness_blocks(
+            (0..32).collect(),
+            2,
+            |i: usize| Ok(i),
+            |index, _| match index {
+                1 => bail!("failed to prove chunk-{index}"),
+                _ => Ok(()),
+            },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_pipeline_worker_panic() {
+        let result = pipeline_chunk_witness_blocks(
+            (0..8).collect(),
+            4,
+            |i: usize| {
+                if i == 1 {
+                    panic!("witness panic");
+                }
+                Ok(i)
+            },
+            |_, _| Ok(()),
+        );
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("chunk-1 panicked: witness panic"), "{err}");
+    }
+}
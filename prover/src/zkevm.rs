@@ -1,10 +1,17 @@
 #[cfg(feature = "scroll")]
 mod capacity_checker;
+#[cfg(feature = "scroll")]
+mod chunk_planner;
 pub mod circuit;
 mod prover;
 mod verifier;
 
 pub use self::prover::Prover;
 #[cfg(feature = "scroll")]
-pub use capacity_checker::{CircuitCapacityChecker, RowUsage, SubCircuitRowUsage};
+pub use capacity_checker::{
+    CapacityCheckerSnapshot, CircuitCapacityChecker, RowLimit, RowLimits, RowUsage,
+    SubCircuitRowUsage, ROW_LIMITS,
+};
+#[cfg(feature = "scroll")]
+pub use chunk_planner::{plan_chunks, plan_chunks_with_limits, ChunkPlan};
 pub use verifier::Verifier;
@@ -1,20 +1,117 @@
-use super::circuit::{
-    MAX_BYTECODE, MAX_CALLDATA, MAX_EXP_STEPS, MAX_KECCAK_ROWS, MAX_MPT_ROWS, MAX_POSEIDON_ROWS,
-    MAX_RWS, MAX_VERTICAL_ROWS,
+use super::circuit::{add_mpt_poseidon_rows, get_super_circuit_params};
+use crate::config::{INNER_DEGREE, ROW_LIMITS_CONFIG_PATH};
+use anyhow::{bail, Result};
+use bus_mapping::circuit_input_builder::{
+    CircuitInputBuilder, CircuitInputBuilderSnapshot, CircuitsParams,
 };
-
-use super::circuit::{
-    block_traces_to_witness_block_with_updated_state, calculate_row_usage_of_witness_block,
-    get_super_circuit_params,
-};
-use bus_mapping::{
-    circuit_input_builder::{self, CircuitInputBuilder},
-    state_db::{CodeDB, StateDB},
-};
-use eth_types::{l2_types::BlockTrace, ToWord, H256};
+use eth_types::l2_types::BlockTrace;
 use itertools::Itertools;
-use mpt_zktrie::state::ZktrieState;
+use once_cell::sync::Lazy;
 use serde_derive::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs::File, path::Path};
+use zkevm_circuits::super_circuit::RowCounter;
+
+/// Row limits of the subcircuits, from the super circuit params, and overridden by the config
+/// file if it exists.
+pub static ROW_LIMITS: Lazy<RowLimits> = Lazy::new(|| {
+    let mut row_limits = RowLimits::from_circuits_params(&get_super_circuit_params());
+    let config_file = &*ROW_LIMITS_CONFIG_PATH;
+    if Path::new(config_file).exists() {
+        let overrides = RowLimits::from_file(config_file)
+            .unwrap_or_else(|err| panic!("Failed to parse {config_file}: {err}"));
+        row_limits.merge(overrides);
+    }
+    log::info!("row limits of subcircuits: {row_limits:?}");
+
+    row_limits
+});
+
+// Ratio of the max rows available, as the safety margin of the row estimation.
+const DEFAULT_CONFIDENCE: f32 = 0.95;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct RowLimit {
+    pub max_rows: usize,
+    /// Ratio of the max rows which could be used.
+    pub confidence: f32,
+}
+
+impl RowLimit {
+    fn new(max_rows: usize) -> Self {
+        Self {
+            max_rows,
+            confidence: DEFAULT_CONFIDENCE,
+        }
+    }
+
+    pub fn available_rows(&self) -> usize {
+        (self.max_rows as f32 * self.confidence) as usize
+    }
+}
+
+/// Row limits keyed by the subcircuit names of `SubcircuitRowUsage`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RowLimits(pub BTreeMap<String, RowLimit>);
+
+impl RowLimits {
+    pub fn from_circuits_params(params: &CircuitsParams) -> Self {
+        let vertical = RowLimit::new(params.max_vertical_circuit_rows);
+        let limits = [
+            ("evm", RowLimit::new(params.max_evm_rows)),
+            ("state", RowLimit::new(params.max_rws)),
+            ("bytecode", RowLimit::new(params.max_bytecode)),
+            ("copy", RowLimit::new(params.max_copy_rows)),
+            ("keccak", RowLimit::new(params.max_keccak_rows)),
+            ("sha256", vertical),
+            ("ripemd160", vertical),
+            ("blake2f", vertical),
+            ("tx", vertical),
+            ("rlp", RowLimit::new(params.max_calldata)),
+            ("exp", RowLimit::new(7 * params.max_exp_steps)),
+            ("mod_exp", RowLimit::new(params.max_keccak_rows)),
+            ("pi", RowLimit::new(params.max_rws)),
+            ("poseidon", RowLimit::new(params.max_poseidon_rows)),
+            ("sig", vertical),
+            (
+                "ecc",
+                RowLimit {
+                    confidence: 1.0,
+                    ..vertical
+                },
+            ),
+            ("mpt", RowLimit::new(params.max_mpt_rows)),
+        ];
+
+        Self(
+            limits
+                .into_iter()
+                .map(|(name, limit)| (name.to_string(), limit))
+                .collect(),
+        )
+    }
+
+    /// Load the row limits from a JSON file of `{"name": {"max_rows": _, "confidence": _}}`.
+    pub fn from_file(config_file: &str) -> Result<Self> {
+        let f = File::open(config_file)?;
+        Ok(serde_json::from_reader(f)?)
+    }
+
+    /// Override the row limits by the other ones.
+    pub fn merge(&mut self, other: Self) {
+        self.0.extend(other.0);
+    }
+
+    // The rows of a subcircuit without a configured limit are limited by the circuit degree.
+    pub fn available_rows(&self, name: &str) -> usize {
+        match self.0.get(name) {
+            Some(limit) => limit.available_rows(),
+            None => {
+                log::warn!("no row limit of subcircuit {name}, limited by the circuit degree");
+                RowLimit::new(1 << *INNER_DEGREE).available_rows()
+            }
+        }
+    }
+}
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SubCircuitRowUsage {
@@ -47,30 +144,17 @@ impl RowUsage {
     }
     // We treat 1M as 100%
     pub fn normalize(&self) -> Self {
-        let real_available_rows = [
-            (MAX_RWS, 0.95),           // evm
-            (MAX_RWS, 0.95),           // state
-            (MAX_BYTECODE, 0.95),      // bytecode
-            (MAX_RWS, 0.95),           // copy
-            (MAX_KECCAK_ROWS, 0.95),   // keccak
-            (MAX_VERTICAL_ROWS, 0.95), // tx
-            (MAX_CALLDATA, 0.95),      // rlp
-            (7 * MAX_EXP_STEPS, 0.95), // exp
-            (MAX_KECCAK_ROWS, 0.95),   // modexp
-            (MAX_RWS, 0.95),           // pi
-            (MAX_POSEIDON_ROWS, 0.95), // poseidon
-            (MAX_VERTICAL_ROWS, 0.95), // sig
-            (MAX_VERTICAL_ROWS, 1.0),  // ecc
-            (MAX_MPT_ROWS, 0.95),      // mpt
-        ]
-        .map(|(limit, confidence)| (limit as f32 * confidence) as usize);
+        self.normalize_with_limits(&ROW_LIMITS)
+    }
+    pub fn normalize_with_limits(&self, row_limits: &RowLimits) -> Self {
         let details = self
             .row_usage_details
             .iter()
-            .zip_eq(real_available_rows.iter())
-            .map(|(x, limit)| SubCircuitRowUsage {
+            .map(|x| SubCircuitRowUsage {
                 name: x.name.clone(),
-                row_number: (1_000_000u64 * (x.row_number as u64) / (*limit as u64)) as usize,
+                row_number: (NORMALIZED_ROW_LIMIT as u64 * (x.row_number as u64)
+                    / (row_limits.available_rows(&x.name) as u64))
+                    as usize,
             })
             .collect_vec();
         log::debug!(
@@ -115,11 +199,26 @@ impl RowUsage {
 #[derive(Debug)]
 pub struct CircuitCapacityChecker {
     /// When "light_mode" enabled, we skip zktrie subcircuit in row estimation to avoid the heavy
-    /// poseidon cost.
+    /// poseidon cost, and estimate the poseidon rows of the MPT updates instead. It's disabled by
+    /// default, where the poseidon rows are counted from the SMT traces of the new MPT updates.
     pub light_mode: bool,
     pub acc_row_usage: RowUsage,
     pub row_usages: Vec<RowUsage>,
-    pub builder_ctx: Option<(CodeDB, StateDB, Option<ZktrieState>)>,
+    /// The builder keeps the execution state and zktrie state of the handled traces, and the
+    /// counter keeps the row usage of them.
+    pub builder_ctx: Option<(CircuitInputBuilder, RowCounter)>,
+    /// State before the last estimated trace, to roll back it.
+    pub last_snapshot: Option<CapacityCheckerSnapshot>,
+}
+
+/// Snapshot of the builder state and the row usage of a `CircuitCapacityChecker`. The row
+/// counter isn't kept, but recounted from the restored builder (with the MPT updates of all the
+/// restored traces applied at once), since rollbacks are rare.
+#[derive(Debug)]
+pub struct CapacityCheckerSnapshot {
+    builder: Option<CircuitInputBuilderSnapshot>,
+    acc_row_usage: RowUsage,
+    num_row_usages: usize,
 }
 
 impl Default for CircuitCapacityChecker {
@@ -134,12 +233,14 @@ impl CircuitCapacityChecker {
         Self {
             acc_row_usage: RowUsage::new(),
             row_usages: Vec::new(),
-            light_mode: true,
+            light_mode: false,
             builder_ctx: None,
+            last_snapshot: None,
         }
     }
     pub fn reset(&mut self) {
         self.builder_ctx = None;
+        self.last_snapshot = None;
         self.acc_row_usage = RowUsage::new();
         self.row_usages = Vec::new();
     }
@@ -156,87 +257,251 @@ impl CircuitCapacityChecker {
             self.acc_row_usage.clone()
         }
     }
-    pub fn estimate_circuit_capacity(
-        &mut self,
-        trace: BlockTrace,
-    ) -> Result<RowUsage, anyhow::Error> {
-        let (mut estimate_builder, codedb_prev) =
-            if let Some((code_db, sdb, mpt_state)) = self.builder_ctx.take() {
-                // here we create a new builder for another (sealed) witness block
-                // this builder inherit the current execution state (sdb) of
-                // the previous one and do not use zktrie state,
-                // notice the prev_root in current builder may be not invalid (since the state has
-                // changed but we may not update it in light mode)
-                let mut builder_block =
-                    circuit_input_builder::Block::from_headers(&[], get_super_circuit_params());
-                builder_block.chain_id = trace.chain_id;
-                builder_block.start_l1_queue_index = trace.start_l1_queue_index;
-                builder_block.prev_state_root = mpt_state
-                    .as_ref()
-                    .map(|state| state.root())
-                    .map(|root| H256(*root))
-                    .unwrap_or(trace.header.state_root)
-                    .to_word();
-                // notice the trace has included all code required for builidng witness block,
-                // so we do not need to pick them from previous one, but we still keep the
-                // old codedb in previous run for some dedup work
-                let mut builder = if let Some(mpt_state) = mpt_state {
-                    CircuitInputBuilder::new_with_trie_state(
-                        sdb,
-                        CodeDB::new(),
-                        mpt_state,
-                        &builder_block,
-                    )
-                } else {
-                    CircuitInputBuilder::new(sdb, CodeDB::new(), &builder_block)
-                };
-                builder.add_more_l2_trace(trace, false)?;
-                (builder, Some(code_db))
-            } else {
-                (
-                    CircuitInputBuilder::new_from_l2_trace(
-                        get_super_circuit_params(),
-                        trace,
-                        false,
-                        self.light_mode,
-                    )?,
-                    None,
-                )
-            };
-        let witness_block =
-            block_traces_to_witness_block_with_updated_state(vec![], &mut estimate_builder)?;
-        let mut rows = calculate_row_usage_of_witness_block(&witness_block)?;
-
-        let mut code_db = codedb_prev.unwrap_or_else(CodeDB::new);
-        // merge current codes with previous , and dedup bytecode row usage
-        // for bytecode circuit / poseidon circuit
-        for (hash, bytes) in estimate_builder.code_db.0 {
-            let bytes_len = bytes.len();
-            // code for current run has been evaluated in previous
-            if code_db.0.insert(hash, bytes).is_some() {
-                assert_eq!(rows[2].name, "bytecode");
-                rows[2].row_num_real -= bytes_len + 1;
-                assert_eq!(rows[10].name, "poseidon");
-                rows[10].row_num_real -= bytes_len / (31 * 2) * 9;
+    pub fn snapshot(&self) -> CapacityCheckerSnapshot {
+        CapacityCheckerSnapshot {
+            builder: self
+                .builder_ctx
+                .as_ref()
+                .map(|(builder, _)| builder.snapshot()),
+            acc_row_usage: self.acc_row_usage.clone(),
+            num_row_usages: self.row_usages.len(),
+        }
+    }
+    // The snapshot must be taken from this checker since the last reset.
+    pub fn restore(&mut self, snapshot: CapacityCheckerSnapshot) -> Result<()> {
+        match (snapshot.builder, self.builder_ctx.as_mut()) {
+            (Some(builder_snapshot), Some((builder, row_counter))) => {
+                builder.restore(builder_snapshot);
+                let mut restored_counter = RowCounter::new(get_super_circuit_params());
+                restored_counter.update(
+                    &builder.block,
+                    &builder.code_db,
+                    builder.mpt_init_state.as_ref(),
+                )?;
+                *row_counter = restored_counter;
             }
+            (Some(_), None) => bail!("no builder to restore the snapshot"),
+            (None, _) => self.builder_ctx = None,
         }
+        self.acc_row_usage = snapshot.acc_row_usage;
+        self.row_usages.truncate(snapshot.num_row_usages);
+        Ok(())
+    }
+    // Restore the state before the last estimated trace (even if the estimation failed), so the
+    // sequencer could drop the tx which overflows the capacity. Return false if there is no
+    // trace to roll back.
+    pub fn rollback_last_trace(&mut self) -> Result<bool> {
+        match self.last_snapshot.take() {
+            Some(snapshot) => {
+                self.restore(snapshot)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+    // Handle the txs of the trace by the builder of the previous traces, and count only the rows
+    // of them, without building the witness block. The builder is kept even if it fails, to be
+    // rolled back.
+    pub fn estimate_circuit_capacity(&mut self, trace: BlockTrace) -> Result<RowUsage> {
+        self.last_snapshot = Some(self.snapshot());
 
-        let row_usage_details: Vec<SubCircuitRowUsage> = rows
+        let (builder, row_counter) = match &mut self.builder_ctx {
+            Some(builder_ctx) => {
+                builder_ctx.0.add_more_l2_trace(trace, true)?;
+                builder_ctx
+            }
+            None => self.builder_ctx.insert((
+                CircuitInputBuilder::new_from_l2_trace(
+                    get_super_circuit_params(),
+                    trace,
+                    true,
+                    self.light_mode,
+                )?,
+                RowCounter::new(get_super_circuit_params()),
+            )),
+        };
+        row_counter.update(
+            &builder.block,
+            &builder.code_db,
+            builder.mpt_init_state.as_ref(),
+        )?;
+
+        let mut rows = row_counter.row_usage();
+        if row_counter.is_light_mode() {
+            add_mpt_poseidon_rows(&mut rows)?;
+        }
+        let acc_row_usage_details: Vec<SubCircuitRowUsage> = rows
             .into_iter()
             .map(|x| SubCircuitRowUsage {
                 name: x.name,
                 row_number: x.row_num_real,
             })
             .collect_vec();
-        let tx_row_usage = RowUsage::from_row_usage_details(row_usage_details);
-        self.row_usages.push(tx_row_usage.clone());
-        self.acc_row_usage.add(&tx_row_usage);
-
-        self.builder_ctx.replace((
-            code_db,
-            estimate_builder.sdb,
-            estimate_builder.mpt_init_state,
-        ));
+        let tx_row_usage_details = if self.acc_row_usage.row_usage_details.is_empty() {
+            acc_row_usage_details.clone()
+        } else {
+            acc_row_usage_details
+                .iter()
+                .zip_eq(self.acc_row_usage.row_usage_details.iter())
+                .map(|(acc, prev)| SubCircuitRowUsage {
+                    name: acc.name.clone(),
+                    row_number: acc.row_number.saturating_sub(prev.row_number),
+                })
+                .collect_vec()
+        };
+        self.row_usages
+            .push(RowUsage::from_row_usage_details(tx_row_usage_details));
+        self.acc_row_usage = RowUsage::from_row_usage_details(acc_row_usage_details);
+
         Ok(self.acc_row_usage.normalize())
     }
 }
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use eth_types::Address;
+    use mock::{eth, TestContext};
+
+    // Traces of the transfers between two accounts in a block, one per trace, so they're
+    // consecutive (as the MPT updates are applied to the zktrie state) and have the same row
+    // usage.
+    pub(crate) fn transfer_traces(num: usize) -> Vec<BlockTrace> {
+        let block_trace = TestContext::<2, 5>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(Address::from_low_u64_be(0x1000))
+                    .balance(eth(10));
+                accs[1]
+                    .address(Address::from_low_u64_be(0x1001))
+                    .balance(eth(10));
+            },
+            |txs, accs| {
+                for tx in txs {
+                    tx.from(accs[0].address).to(accs[1].address).value(eth(1));
+                }
+            },
+            |block, _txs| block.number(0xcafe),
+        )
+        .unwrap()
+        .l2_trace()
+        .clone();
+
+        (0..num)
+            .map(|idx| {
+                let mut trace = block_trace.clone();
+                trace.transactions = vec![block_trace.transactions[idx].clone()];
+                trace.execution_results = vec![block_trace.execution_results[idx].clone()];
+                trace.tx_storage_trace = block_trace
+                    .tx_storage_trace
+                    .get(idx)
+                    .cloned()
+                    .into_iter()
+                    .collect();
+                trace
+            })
+            .collect()
+    }
+
+    fn row_numbers(row_usage: &RowUsage) -> Vec<(String, usize)> {
+        row_usage
+            .row_usage_details
+            .iter()
+            .map(|x| (x.name.clone(), x.row_number))
+            .collect()
+    }
+
+    #[test]
+    fn test_rollback_last_trace() {
+        let traces = transfer_traces(2);
+        let mut checker = CircuitCapacityChecker::new();
+        checker
+            .estimate_circuit_capacity(traces[0].clone())
+            .unwrap();
+        let acc_row_usage = checker.get_acc_row_usage(false);
+        let block = checker.builder_ctx.as_ref().unwrap().0.block.clone();
+
+        let row_usage = checker
+            .estimate_circuit_capacity(traces[1].clone())
+            .unwrap();
+        assert_eq!(checker.get_tx_num(), 2);
+        assert!(checker.rollback_last_trace().unwrap());
+        assert_eq!(checker.get_tx_num(), 1);
+        assert_eq!(
+            row_numbers(&checker.get_acc_row_usage(false)),
+            row_numbers(&acc_row_usage)
+        );
+        let restored_block = &checker.builder_ctx.as_ref().unwrap().0.block;
+        assert_eq!(restored_block.withdraw_root, block.withdraw_root);
+        assert_eq!(restored_block.prev_withdraw_root, block.prev_withdraw_root);
+        assert_eq!(
+            restored_block.start_l1_queue_index,
+            block.start_l1_queue_index
+        );
+        assert_eq!(restored_block.code, block.code);
+        assert_eq!(
+            format!("{:?}", restored_block.block_steps),
+            format!("{:?}", block.block_steps)
+        );
+        // Only the last trace can be rolled back.
+        assert!(!checker.rollback_last_trace().unwrap());
+
+        // The restored builder and counter handle the trace again as before.
+        let row_usage_again = checker
+            .estimate_circuit_capacity(traces[1].clone())
+            .unwrap();
+        assert_eq!(row_numbers(&row_usage_again), row_numbers(&row_usage));
+        assert_eq!(checker.get_tx_num(), 2);
+    }
+
+    #[test]
+    fn test_rollback_first_trace() {
+        let traces = transfer_traces(1);
+        let mut checker = CircuitCapacityChecker::new();
+        assert!(!checker.rollback_last_trace().unwrap());
+
+        let row_usage = checker
+            .estimate_circuit_capacity(traces[0].clone())
+            .unwrap();
+        assert!(checker.rollback_last_trace().unwrap());
+        assert!(checker.builder_ctx.is_none());
+        assert_eq!(checker.get_tx_num(), 0);
+        assert!(checker
+            .get_acc_row_usage(false)
+            .row_usage_details
+            .is_empty());
+
+        let row_usage_again = checker
+            .estimate_circuit_capacity(traces[0].clone())
+            .unwrap();
+        assert_eq!(row_numbers(&row_usage_again), row_numbers(&row_usage));
+    }
+
+    #[test]
+    fn test_normalize_by_name() {
+        let mut row_limits = RowLimits::from_circuits_params(&get_super_circuit_params());
+        row_limits.merge(RowLimits(BTreeMap::from([(
+            "poseidon".to_string(),
+            RowLimit {
+                max_rows: 2_000,
+                confidence: 0.5,
+            },
+        )])));
+
+        let row_usage = RowUsage::from_row_usage_details(vec![
+            SubCircuitRowUsage {
+                name: "poseidon".to_string(),
+                row_number: 500,
+            },
+            SubCircuitRowUsage {
+                name: "evm".to_string(),
+                row_number: 0,
+            },
+        ]);
+        let normalized = row_usage.normalize_with_limits(&row_limits);
+        assert_eq!(normalized.row_usage_details[0].name, "poseidon");
+        assert_eq!(normalized.row_usage_details[0].row_number, 500_000);
+        assert!(normalized.is_ok);
+    }
+}
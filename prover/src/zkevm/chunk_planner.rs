@@ -0,0 +1,229 @@
+use super::{
+    circuit::{MAX_INNER_BLOCKS, MAX_TXS},
+    CircuitCapacityChecker, RowLimits, RowUsage, ROW_LIMITS,
+};
+use crate::BlockTrace;
+use anyhow::{bail, Result};
+use std::ops::Range;
+
+/// A chunk of consecutive blocks planned by `plan_chunks`.
+#[derive(Clone, Debug)]
+pub struct ChunkPlan {
+    /// Indices of the blocks in the block traces.
+    pub block_range: Range<usize>,
+    pub num_txs: usize,
+    /// Normalized row usage of the chunk.
+    pub row_usage: RowUsage,
+    /// Subcircuit with the most normalized rows, which limits the chunk.
+    pub binding_subcircuit: String,
+}
+
+impl ChunkPlan {
+    fn new(block_range: Range<usize>, num_txs: usize, row_usage: RowUsage) -> Self {
+        let binding_subcircuit = row_usage
+            .row_usage_details
+            .iter()
+            .max_by_key(|x| x.row_number)
+            .map(|x| x.name.clone())
+            .unwrap_or_default();
+        log::info!(
+            "plan chunk of blocks {block_range:?}, num_txs {num_txs}, row_number {}, bound by {binding_subcircuit}",
+            row_usage.row_number,
+        );
+
+        Self {
+            block_range,
+            num_txs,
+            row_usage,
+            binding_subcircuit,
+        }
+    }
+}
+
+/// Partition the block traces into the fewest chunks of consecutive blocks, each of which fits
+/// the super circuit params. Since the row usage of a chunk only grows with more blocks, filling
+/// each chunk greedily is optimal.
+pub fn plan_chunks(block_traces: &[BlockTrace]) -> Result<Vec<ChunkPlan>> {
+    plan_chunks_with_limits(block_traces, &ROW_LIMITS)
+}
+
+/// Same as `plan_chunks`, with the row usage normalized by `row_limits` instead of `ROW_LIMITS`.
+pub fn plan_chunks_with_limits(
+    block_traces: &[BlockTrace],
+    row_limits: &RowLimits,
+) -> Result<Vec<ChunkPlan>> {
+    let mut checker = CircuitCapacityChecker::new();
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut num_txs = 0;
+    let mut row_usage = RowUsage::new();
+    for (idx, block_trace) in block_traces.iter().enumerate() {
+        let block_num_txs = block_trace.transactions.len();
+        if idx > start {
+            let fits = idx - start < MAX_INNER_BLOCKS
+                && num_txs + block_num_txs <= MAX_TXS
+                && match estimate_row_usage(&mut checker, block_trace, row_limits) {
+                    Ok(usage) if usage.is_ok => {
+                        row_usage = usage;
+                        true
+                    }
+                    Ok(_) => false,
+                    Err(e) => {
+                        log::warn!("failed to add block {idx} to the chunk: {e:?}");
+                        false
+                    }
+                };
+            if fits {
+                num_txs += block_num_txs;
+                continue;
+            }
+
+            chunks.push(ChunkPlan::new(
+                start..idx,
+                num_txs,
+                std::mem::take(&mut row_usage),
+            ));
+            checker.reset();
+            start = idx;
+        }
+
+        // The first block of a chunk.
+        row_usage = estimate_row_usage(&mut checker, block_trace, row_limits)?;
+        if !row_usage.is_ok || block_num_txs > MAX_TXS {
+            bail!(
+                "block {idx} ({:?}) exceeds the capacity of a chunk, num_txs {block_num_txs}, row usage {row_usage:?}",
+                block_trace.header.number,
+            );
+        }
+        num_txs = block_num_txs;
+    }
+    if start < block_traces.len() {
+        chunks.push(ChunkPlan::new(
+            start..block_traces.len(),
+            num_txs,
+            row_usage,
+        ));
+    }
+
+    Ok(chunks)
+}
+
+// Normalized row usage of the chunk after adding the block.
+fn estimate_row_usage(
+    checker: &mut CircuitCapacityChecker,
+    block_trace: &BlockTrace,
+    row_limits: &RowLimits,
+) -> Result<RowUsage> {
+    checker.estimate_circuit_capacity(block_trace.clone())?;
+    Ok(checker
+        .get_acc_row_usage(false)
+        .normalize_with_limits(row_limits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zkevm::{
+        capacity_checker::tests::transfer_traces, circuit::get_super_circuit_params, RowLimit,
+        SubCircuitRowUsage,
+    };
+    use std::collections::BTreeMap;
+
+    // Raw rows of the subcircuit for the traces in a single chunk.
+    fn rows_of(name: &str, block_traces: &[BlockTrace]) -> usize {
+        let mut checker = CircuitCapacityChecker::new();
+        for block_trace in block_traces {
+            checker
+                .estimate_circuit_capacity(block_trace.clone())
+                .unwrap();
+        }
+        checker
+            .get_acc_row_usage(false)
+            .row_usage_details
+            .into_iter()
+            .find(|x| x.name == name)
+            .unwrap()
+            .row_number
+    }
+
+    // The default row limits, with the state circuit limited to `max_rows` exactly.
+    fn state_row_limits(max_rows: usize) -> RowLimits {
+        let mut row_limits = RowLimits::from_circuits_params(&get_super_circuit_params());
+        row_limits.merge(RowLimits(BTreeMap::from([(
+            "state".to_string(),
+            RowLimit {
+                max_rows,
+                confidence: 1.0,
+            },
+        )])));
+        row_limits
+    }
+
+    #[test]
+    fn test_plan_chunks_boundaries() {
+        let block_traces = transfer_traces(5);
+        // Two blocks fill the state circuit, so the chunks are [0, 2), [2, 4) and [4, 5).
+        let row_limits = state_row_limits(rows_of("state", &block_traces[..2]));
+
+        let chunks = plan_chunks_with_limits(&block_traces, &row_limits).unwrap();
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.block_range.clone())
+                .collect::<Vec<_>>(),
+            vec![0..2, 2..4, 4..5]
+        );
+        for chunk in &chunks {
+            assert_eq!(chunk.num_txs, chunk.block_range.len());
+            assert!(chunk.row_usage.is_ok);
+            assert_eq!(chunk.binding_subcircuit, "state");
+        }
+
+        // All the blocks fit in a chunk with the default limits.
+        let chunks = plan_chunks(&block_traces).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].block_range, 0..5);
+        assert_eq!(chunks[0].num_txs, 5);
+    }
+
+    #[test]
+    fn test_plan_chunks_overflowing_block() {
+        let block_traces = transfer_traces(2);
+        let row_limits = state_row_limits(rows_of("state", &block_traces[..1]) - 1);
+
+        let err = plan_chunks_with_limits(&block_traces, &row_limits)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("block 0 "), "{err}");
+        assert!(err.contains("exceeds the capacity of a chunk"), "{err}");
+        assert!(plan_chunks_with_limits(&[], &row_limits)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_chunk_plan_binding_subcircuit() {
+        let row_usage = |rows: &[(&str, usize)]| {
+            RowUsage::from_row_usage_details(
+                rows.iter()
+                    .map(|&(name, row_number)| SubCircuitRowUsage {
+                        name: name.to_string(),
+                        row_number,
+                    })
+                    .collect(),
+            )
+        };
+
+        let plan = ChunkPlan::new(
+            0..2,
+            3,
+            row_usage(&[("evm", 100), ("poseidon", 300), ("state", 200)]),
+        );
+        assert_eq!(plan.binding_subcircuit, "poseidon");
+        assert_eq!(plan.row_usage.row_number, 300);
+
+        let plan = ChunkPlan::new(0..0, 0, RowUsage::new());
+        assert_eq!(plan.binding_subcircuit, "");
+    }
+}
@@ -1,25 +1,32 @@
-use eth_types::l2_types::BlockTrace;
+use crate::BlockTrace;
+use bus_mapping::precompile::DEFAULT_MODEXP_SIZE_LIMIT;
 use halo2_proofs::halo2curves::bn256::Fr;
 use snark_verifier_sdk::CircuitExt;
 use zkevm_circuits::witness;
 
+mod builder;
 #[cfg(feature = "scroll")]
 mod l2_builder;
 #[cfg(feature = "scroll")]
-use l2_builder as builder;
+use l2_builder as trace_builder;
 #[cfg(not(feature = "scroll"))]
 mod l1_builder;
 #[cfg(not(feature = "scroll"))]
-use l1_builder as builder;
+use l1_builder as trace_builder;
 mod super_circuit;
-pub use self::builder::{
-    block_trace_to_witness_block, block_traces_to_witness_block,
-    block_traces_to_witness_block_with_updated_state, calculate_row_usage_of_trace,
-    calculate_row_usage_of_witness_block, check_batch_capacity, get_super_circuit_params,
-    validite_block_traces,
+pub use self::{
+    builder::{
+        block_trace_to_witness_block, block_traces_to_witness_block, calculate_row_usage_of_trace,
+        calculate_row_usage_of_witness_block, check_batch_capacity, get_super_circuit_params,
+        validate_block_traces,
+    },
+    trace_builder::block_traces_to_witness_block_with_updated_state,
 };
 pub use super_circuit::SuperCircuit;
 
+#[cfg(feature = "scroll")]
+pub(crate) use builder::add_mpt_poseidon_rows;
+
 ////// params for Super Circuit of degree = 20 ////////////
 pub const MAX_TXS: usize = 100;
 pub const MAX_INNER_BLOCKS: usize = 100;
@@ -35,6 +42,7 @@ pub const MAX_RWS: usize = 1_000_000;
 pub const MAX_PRECOMPILE_EC_ADD: usize = 50;
 pub const MAX_PRECOMPILE_EC_MUL: usize = 50;
 pub const MAX_PRECOMPILE_EC_PAIRING: usize = 2;
+pub const MAX_MODEXP_SIZE: usize = DEFAULT_MODEXP_SIZE_LIMIT;
 
 /// A target circuit trait is a wrapper of inner circuit, with convenient APIs for building
 /// circuits from traces.
@@ -0,0 +1,253 @@
+use super::{
+    trace_builder::{self, DEFAULT_CHAIN_ID},
+    TargetCircuit, MAX_BYTECODE, MAX_CALLDATA, MAX_EXP_STEPS, MAX_INNER_BLOCKS, MAX_KECCAK_ROWS,
+    MAX_MODEXP_SIZE, MAX_MPT_ROWS, MAX_POSEIDON_ROWS, MAX_PRECOMPILE_EC_ADD, MAX_PRECOMPILE_EC_MUL,
+    MAX_PRECOMPILE_EC_PAIRING, MAX_RLP_ROWS, MAX_RWS, MAX_TXS, MAX_VERTICAL_ROWS,
+};
+use crate::{config::INNER_DEGREE, utils::read_env_var, BlockTrace};
+#[cfg(feature = "scroll")]
+use anyhow::anyhow;
+use anyhow::{bail, Result};
+use bus_mapping::circuit_input_builder::{CircuitsParams, PrecompileEcParams};
+use eth_types::U64;
+use halo2_proofs::halo2curves::bn256::Fr;
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use std::time::Instant;
+use zkevm_circuits::{evm_circuit::witness::Block, util::SubCircuit};
+
+pub(super) static CHAIN_ID: Lazy<u64> = Lazy::new(|| read_env_var("CHAIN_ID", DEFAULT_CHAIN_ID));
+static AUTO_TRUNCATE: Lazy<bool> = Lazy::new(|| read_env_var("AUTO_TRUNCATE", false));
+
+/// Accessors of the block trace, which is a scroll l2 trace with feature `scroll` and a geth
+/// block with its execution traces otherwise.
+pub(super) trait BlockTraceExt {
+    /// Number of the block, `None` if pending.
+    fn number(&self) -> Option<U64>;
+
+    /// Number of the txs in the block.
+    fn num_txs(&self) -> usize;
+
+    /// Total calldata length of the txs in the block.
+    fn tx_data_len(&self) -> usize;
+
+    /// Check the trace is consistent in itself and with `parent`, the trace of the previous
+    /// block of the chunk if any.
+    fn check(&self, _parent: Option<&Self>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// default params for super circuit
+pub fn get_super_circuit_params() -> CircuitsParams {
+    CircuitsParams {
+        max_evm_rows: MAX_RWS,
+        max_rws: MAX_RWS,
+        max_copy_rows: MAX_RWS,
+        max_txs: MAX_TXS,
+        max_calldata: MAX_CALLDATA,
+        max_bytecode: MAX_BYTECODE,
+        max_inner_blocks: MAX_INNER_BLOCKS,
+        max_keccak_rows: MAX_KECCAK_ROWS,
+        max_poseidon_rows: MAX_POSEIDON_ROWS,
+        max_vertical_circuit_rows: MAX_VERTICAL_ROWS,
+        max_exp_steps: MAX_EXP_STEPS,
+        max_mpt_rows: MAX_MPT_ROWS,
+        max_rlp_rows: MAX_RLP_ROWS,
+        max_ec_ops: PrecompileEcParams {
+            ec_add: MAX_PRECOMPILE_EC_ADD,
+            ec_mul: MAX_PRECOMPILE_EC_MUL,
+            ec_pairing: MAX_PRECOMPILE_EC_PAIRING,
+        },
+        max_modexp_size: MAX_MODEXP_SIZE,
+    }
+}
+
+pub fn calculate_row_usage_of_trace(
+    block_trace: &BlockTrace,
+) -> Result<Vec<zkevm_circuits::super_circuit::SubcircuitRowUsage>> {
+    let witness_block = block_traces_to_witness_block(vec![block_trace.clone()])?;
+    calculate_row_usage_of_witness_block(&witness_block)
+}
+
+pub fn calculate_row_usage_of_witness_block(
+    witness_block: &Block<Fr>,
+) -> Result<Vec<zkevm_circuits::super_circuit::SubcircuitRowUsage>> {
+    #[allow(unused_mut)]
+    let mut rows = <super::SuperCircuit as TargetCircuit>::Inner::min_num_rows_block_subcircuits(
+        witness_block,
+    );
+
+    #[cfg(feature = "scroll")]
+    if witness_block.mpt_updates.smt_traces.is_empty() {
+        add_mpt_poseidon_rows(&mut rows)?;
+    } else {
+        log::debug!("calculate_row_usage_of_witness_block normal mode, skip adding poseidon rows");
+    }
+
+    log::debug!(
+        "row usage of block {:?}, tx num {:?}, tx calldata len sum {}, rows needed {:?}",
+        witness_block
+            .context
+            .ctxs
+            .first_key_value()
+            .map_or(0.into(), |(_, ctx)| ctx.number),
+        witness_block.txs.len(),
+        witness_block
+            .txs
+            .iter()
+            .map(|t| t.call_data_length)
+            .sum::<usize>(),
+        rows,
+    );
+    Ok(rows)
+}
+
+// The zktrie updates are hashed by the poseidon circuit, whose rows are estimated without the SMT
+// traces (light mode).
+#[cfg(feature = "scroll")]
+pub(crate) fn add_mpt_poseidon_rows(
+    rows: &mut [zkevm_circuits::super_circuit::SubcircuitRowUsage],
+) -> Result<()> {
+    let row_index = |name: &str| {
+        rows.iter()
+            .position(|x| x.name == name)
+            .ok_or_else(|| anyhow!("no row usage of subcircuit {name}"))
+    };
+    let (poseidon_idx, mpt_idx) = (row_index("poseidon")?, row_index("mpt")?);
+    // empirical estimation is each row in mpt cost 1.5 hash (aka 12 rows)
+    let mpt_poseidon_rows = rows[mpt_idx].row_num_real * 12;
+    rows[poseidon_idx].row_num_real += mpt_poseidon_rows;
+    log::debug!("light mode, adding {mpt_poseidon_rows} poseidon rows");
+
+    Ok(())
+}
+
+// FIXME: we need better API name for this.
+// This function also mutates the block trace.
+// See `crate::zkevm::plan_chunks` to partition the block traces into chunks instead.
+pub fn check_batch_capacity(block_traces: &mut Vec<BlockTrace>) -> Result<()> {
+    let block_traces_len = block_traces.len();
+    let total_tx_count = block_traces.iter().map(|b| b.num_txs()).sum::<usize>();
+    let total_tx_len_sum = block_traces.iter().map(|b| b.tx_data_len()).sum::<usize>();
+    log::info!(
+        "check capacity of block traces, num_block {}, num_tx {}, tx total len {}",
+        block_traces_len,
+        total_tx_count,
+        total_tx_len_sum
+    );
+
+    if block_traces_len > MAX_INNER_BLOCKS {
+        bail!("too many blocks");
+    }
+
+    if !*AUTO_TRUNCATE {
+        log::debug!("AUTO_TRUNCATE=false, keep batch as is");
+        return Ok(());
+    }
+
+    let t = Instant::now();
+    let mut acc: Vec<usize> = Vec::new();
+    let mut n_txs = 0;
+    let mut truncate_idx = block_traces.len();
+    for (idx, block) in block_traces.iter().enumerate() {
+        let usage = calculate_row_usage_of_trace(block)?
+            .into_iter()
+            .map(|x| x.row_num_real)
+            .collect_vec();
+        if acc.is_empty() {
+            acc = usage.clone();
+        } else {
+            acc.iter_mut()
+                .zip(usage.iter())
+                .for_each(|(acc, usage)| *acc += usage);
+        }
+        let rows: usize = itertools::max(acc.iter().copied()).unwrap();
+        log::debug!(
+            "row usage after block {}({:?}): {}, {:?}",
+            idx,
+            block.number(),
+            rows,
+            usage
+        );
+        n_txs += block.num_txs();
+        if rows > (1 << *INNER_DEGREE) - 256 || n_txs > MAX_TXS {
+            log::warn!(
+                "truncate blocks [{}..{}), n_txs {}, rows {}",
+                idx,
+                block_traces_len,
+                n_txs,
+                rows
+            );
+            truncate_idx = idx;
+            break;
+        }
+    }
+    log::debug!("check_batch_capacity takes {:?}", t.elapsed());
+    block_traces.truncate(truncate_idx);
+    let total_tx_count2 = block_traces.iter().map(|b| b.num_txs()).sum::<usize>();
+    if total_tx_count != 0 && total_tx_count2 == 0 {
+        // the circuit cannot even prove the first non-empty block...
+        bail!("circuit capacity not enough");
+    }
+    Ok(())
+}
+
+/// check if block traces match preset parameters
+pub fn validate_block_traces(block_traces: &[BlockTrace]) -> Result<()> {
+    let chain_id = block_traces
+        .iter()
+        .map(|block_trace| block_trace.chain_id)
+        .next()
+        .unwrap_or(*CHAIN_ID);
+    if *CHAIN_ID != chain_id {
+        bail!(
+            "CHAIN_ID env var is wrong. chain id in trace {chain_id}, CHAIN_ID {}",
+            *CHAIN_ID
+        );
+    }
+    for (idx, block_trace) in block_traces.iter().enumerate() {
+        if block_trace.chain_id != chain_id {
+            bail!(
+                "inconsistent chain id {} and {} in block traces",
+                chain_id,
+                block_trace.chain_id
+            );
+        }
+        let parent = idx
+            .checked_sub(1)
+            .map(|parent_idx| &block_traces[parent_idx]);
+        block_trace.check(parent)?;
+    }
+    Ok(())
+}
+
+pub fn block_trace_to_witness_block(block_trace: BlockTrace) -> Result<Block<Fr>> {
+    block_traces_to_witness_block(vec![block_trace])
+}
+
+pub fn block_traces_to_witness_block(block_traces: Vec<BlockTrace>) -> Result<Block<Fr>> {
+    validate_block_traces(&block_traces)?;
+    let block_num = block_traces.len();
+    let total_tx_num = block_traces.iter().map(|b| b.num_txs()).sum::<usize>();
+    if total_tx_num > MAX_TXS {
+        bail!(
+            "tx num overflow {}, block range {:?} to {:?}",
+            total_tx_num,
+            block_traces[0].number(),
+            block_traces[block_num - 1].number()
+        );
+    }
+    log::info!(
+        "block_traces_to_witness_block, block num {}, tx num {}",
+        block_num,
+        total_tx_num,
+    );
+
+    let (mut builder, block_traces) = trace_builder::prepare_builder(block_traces)?;
+    let witness =
+        trace_builder::block_traces_to_witness_block_with_updated_state(block_traces, &mut builder);
+    // send to other thread to drop
+    std::thread::spawn(move || drop(builder.block));
+    witness
+}
@@ -1,45 +1,238 @@
-use anyhow::Result;
-use bus_mapping::circuit_input_builder::{CircuitInputBuilder, CircuitsParams};
-use eth_types::l2_types::BlockTrace;
+use super::{
+    builder::{BlockTraceExt, CHAIN_ID},
+    get_super_circuit_params,
+};
+use crate::BlockTrace;
+use anyhow::{bail, Result};
+use bus_mapping::{
+    circuit_input_builder::{get_state_accesses, BlockHead, CircuitInputBuilder},
+    state_db::{self, CodeDB, StateDB},
+};
+use eth_types::{ToWord, H256, U64};
+use ethers_core::utils::keccak256;
 use halo2_proofs::halo2curves::bn256::Fr;
-use zkevm_circuits::evm_circuit::witness::Block;
+use zkevm_circuits::{evm_circuit::witness::Block, witness::block_convert};
 
-pub fn get_super_circuit_params() -> CircuitsParams {
-    unimplemented!("Must build with feature scroll")
-}
+pub(super) const DEFAULT_CHAIN_ID: u64 = 1;
 
-pub fn validite_block_traces(_block_traces: &[BlockTrace]) -> Result<()> {
-    unimplemented!("Must build with feature scroll")
-}
+impl BlockTraceExt for BlockTrace {
+    fn number(&self) -> Option<U64> {
+        self.eth_block.number
+    }
 
-pub fn calculate_row_usage_of_trace(
-    _block_trace: &BlockTrace,
-) -> Result<Vec<zkevm_circuits::super_circuit::SubcircuitRowUsage>> {
-    unimplemented!("Must build with feature scroll")
-}
+    fn num_txs(&self) -> usize {
+        self.eth_block.transactions.len()
+    }
 
-pub fn calculate_row_usage_of_witness_block(
-    _witness_block: &Block<Fr>,
-) -> Result<Vec<zkevm_circuits::super_circuit::SubcircuitRowUsage>> {
-    unimplemented!("Must build with feature scroll")
-}
+    fn tx_data_len(&self) -> usize {
+        self.eth_block
+            .transactions
+            .iter()
+            .map(|t| t.input.len())
+            .sum()
+    }
 
-pub fn check_batch_capacity(_block_traces: &mut Vec<BlockTrace>) -> Result<()> {
-    unimplemented!("Must build with feature scroll")
+    fn check(&self, parent: Option<&Self>) -> Result<()> {
+        if let Some(parent) = parent {
+            if self.eth_block.parent_hash != parent.eth_block.hash.unwrap_or_default() {
+                bail!(
+                    "block {:?} is not the parent of block {:?}",
+                    parent.eth_block.number,
+                    self.eth_block.number
+                );
+            }
+        }
+        if self.geth_traces.len() != self.eth_block.transactions.len() {
+            bail!(
+                "block {:?} has {} txs but {} traces",
+                self.eth_block.number,
+                self.eth_block.transactions.len(),
+                self.geth_traces.len()
+            );
+        }
+        Ok(())
+    }
 }
 
-pub fn block_trace_to_witness_block(_block_traces: BlockTrace) -> Result<Block<Fr>> {
-    unimplemented!("Must build with feature scroll")
+/// Prepare an empty builder from the default settings, to which all the block traces are added
+/// by [`block_traces_to_witness_block_with_updated_state`].
+pub(super) fn prepare_builder(
+    block_traces: Vec<BlockTrace>,
+) -> Result<(CircuitInputBuilder, Vec<BlockTrace>)> {
+    let mut builder = CircuitInputBuilder::new_from_headers(
+        get_super_circuit_params(),
+        StateDB::new(),
+        CodeDB::new(),
+        &[],
+    );
+    builder.block.chain_id = *CHAIN_ID;
+    Ok((builder, block_traces))
 }
 
-pub fn block_traces_to_witness_block(_block_traces: Vec<BlockTrace>) -> Result<Block<Fr>> {
-    unimplemented!("Must build with feature scroll")
+/// Add the prestate of a block to the builder. The accounts and storage slots
+/// already in the state db have been updated by the previous blocks of the
+/// chunk, so only the ones first accessed in this block are inserted.
+fn add_block_prestate(builder: &mut CircuitInputBuilder, block_trace: &BlockTrace) -> Result<()> {
+    let access_set = get_state_accesses(&block_trace.eth_block, &block_trace.geth_traces)?;
+    for addr in access_set.state.keys() {
+        if !builder.sdb.get_account(addr).0 {
+            builder.sdb.set_account(addr, state_db::Account::zero());
+        }
+    }
+
+    for account in &block_trace.accounts {
+        let (found, acc) = builder.sdb.get_account_mut(&account.address);
+        if found && !acc.is_empty() {
+            for (key, value) in &account.storage {
+                acc.storage.entry(*key).or_insert(*value);
+            }
+            continue;
+        }
+        let code_hash = builder.code_db.insert(account.code.to_vec());
+        *acc = state_db::Account {
+            nonce: account.nonce,
+            balance: account.balance,
+            storage: account.storage.clone(),
+            code_hash,
+            keccak_code_hash: H256(keccak256(&account.code)),
+            code_size: account.code.len().to_word(),
+        };
+    }
+    Ok(())
 }
 
+/// update the builder with another batch of trace and then *FINALIZE* it
+/// (so the buidler CAN NOT be update any more)
 pub fn block_traces_to_witness_block_with_updated_state(
-    _block_traces: Vec<BlockTrace>,
-    _builder: &mut CircuitInputBuilder,
-    _light_mode: bool,
+    block_traces: Vec<BlockTrace>,
+    builder: &mut CircuitInputBuilder,
 ) -> Result<Block<Fr>> {
-    unimplemented!("Must build with feature scroll")
+    let block_traces_len = block_traces.len();
+    for (idx, block_trace) in block_traces.into_iter().enumerate() {
+        let is_last = idx == block_traces_len - 1;
+        log::debug!(
+            "handle_block idx {idx}, block num {:?}",
+            block_trace.eth_block.number
+        );
+        add_block_prestate(builder, &block_trace)?;
+        let header = BlockHead::new(
+            block_trace.chain_id,
+            block_trace.history_hashes.clone(),
+            &block_trace.eth_block,
+        )?;
+        builder.block.headers.insert(header.number.as_u64(), header);
+        builder.handle_block_inner(
+            &block_trace.eth_block,
+            &block_trace.geth_traces,
+            is_last,
+            is_last,
+        )?;
+    }
+    if block_traces_len == 0 {
+        builder.set_value_ops_call_context_rwc_eor();
+        builder.set_end_block()?;
+    }
+
+    log::debug!("converting builder.block to witness block");
+
+    let witness_block = block_convert(&builder.block, &builder.code_db)?;
+    log::debug!(
+        "witness_block built with circuits_params {:?}",
+        witness_block.circuits_params
+    );
+
+    Ok(witness_block)
+}
+
+#[cfg(test)]
+mod l1_builder_tests {
+    use super::*;
+    use crate::{
+        inner::Prover,
+        zkevm::circuit::{block_traces_to_witness_block, validate_block_traces, SuperCircuit},
+    };
+    use eth_types::{bytecode, Hash};
+    use mock::{test_ctx::helpers::account_0_code_wallet_0_no_code, TestContext, MOCK_WALLETS};
+
+    fn block_trace(number: u64, hash: u64, parent_hash: u64) -> BlockTrace {
+        let mut block_trace = BlockTrace {
+            chain_id: *CHAIN_ID,
+            ..Default::default()
+        };
+        block_trace.eth_block.number = Some(number.into());
+        block_trace.eth_block.hash = Some(Hash::from_low_u64_be(hash));
+        block_trace.eth_block.parent_hash = Hash::from_low_u64_be(parent_hash);
+        block_trace
+    }
+
+    fn l1_block_trace() -> BlockTrace {
+        let bytecode = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        TestContext::<2, 1>::new(
+            None,
+            account_0_code_wallet_0_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0]
+                    .from(MOCK_WALLETS[0].clone())
+                    .to(accs[0].address)
+                    .chain_id(*CHAIN_ID);
+            },
+            |block, _txs| block.number(0xcafeu64).chain_id(*CHAIN_ID),
+        )
+        .unwrap()
+        .into()
+    }
+
+    #[test]
+    fn test_validate_block_traces() {
+        let chunk = vec![block_trace(1, 0x11, 0x10), block_trace(2, 0x12, 0x11)];
+        assert!(validate_block_traces(&chunk).is_ok());
+
+        let gap = vec![block_trace(1, 0x11, 0x10), block_trace(3, 0x13, 0x12)];
+        assert!(validate_block_traces(&gap).is_err());
+
+        let mut wrong_chain = chunk.clone();
+        wrong_chain[1].chain_id += 1;
+        assert!(validate_block_traces(&wrong_chain).is_err());
+
+        let mut missing_traces = chunk;
+        missing_traces[0]
+            .eth_block
+            .transactions
+            .push(Default::default());
+        assert!(validate_block_traces(&missing_traces).is_err());
+    }
+
+    #[test]
+    fn test_l1_trace_to_witness_block() {
+        let block_trace = l1_block_trace();
+        let witness_block = block_traces_to_witness_block(vec![block_trace.clone()]).unwrap();
+
+        assert_eq!(witness_block.chain_id, *CHAIN_ID);
+        assert_eq!(witness_block.txs.len(), 1);
+        assert_eq!(
+            witness_block
+                .context
+                .ctxs
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![0xcafe]
+        );
+        assert!(witness_block.txs[0].steps.len() > 2);
+        assert_eq!(
+            witness_block.txs[0].hash,
+            block_trace.eth_block.transactions[0].hash
+        );
+    }
+
+    #[ignore]
+    #[test]
+    fn test_l1_trace_mock_prove() {
+        Prover::<SuperCircuit>::mock_prove_target_circuit(l1_block_trace()).unwrap();
+    }
 }
@@ -1,10 +1,11 @@
-use super::{TargetCircuit, MAX_CALLDATA, MAX_INNER_BLOCKS, MAX_TXS};
+use super::{TargetCircuit, MAX_CALLDATA, MAX_INNER_BLOCKS, MAX_MODEXP_SIZE, MAX_TXS};
 use crate::config::INNER_DEGREE;
 use anyhow::bail;
 use halo2_proofs::halo2curves::bn256::Fr;
 use zkevm_circuits::{super_circuit::SuperCircuit as SuperCircuitTpl, util::SubCircuit, witness};
 
-type SuperCircuitImpl = SuperCircuitTpl<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, 0x1000>;
+type SuperCircuitImpl =
+    SuperCircuitTpl<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, 0x1000, MAX_MODEXP_SIZE>;
 
 pub struct SuperCircuit {}
 
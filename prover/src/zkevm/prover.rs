@@ -2,13 +2,14 @@ use crate::{
     common,
     config::{LayerId, ZKEVM_DEGREES},
     consts::CHUNK_VK_FILENAME,
-    io::try_to_read,
-    utils::chunk_trace_to_witness_block,
-    ChunkProof,
+    io::{load_witness_block, try_to_read, write_witness_block},
+    types::DumpedWitnessBlock,
+    utils::{chunk_trace_to_witness_block, short_git_version},
+    zkevm::circuit::{get_super_circuit_params, MAX_MODEXP_SIZE},
+    BlockTrace, ChunkProof, WitnessBlock,
 };
 use aggregator::ChunkHash;
-use anyhow::Result;
-use eth_types::l2_types::BlockTrace;
+use anyhow::{bail, Result};
 
 #[derive(Debug)]
 pub struct Prover {
@@ -48,9 +49,24 @@ impl Prover {
     ) -> Result<ChunkProof> {
         assert!(!chunk_trace.is_empty());
 
-        let witness_block = chunk_trace_to_witness_block(chunk_trace)?;
+        let witness_block = match (name, output_dir) {
+            (Some(name), Some(output_dir)) => {
+                load_or_gen_witness_block(chunk_trace, name, output_dir)?
+            }
+            _ => chunk_trace_to_witness_block(chunk_trace)?,
+        };
         log::info!("Got witness block");
 
+        self.gen_chunk_proof_by_witness_block(witness_block, name, inner_id, output_dir)
+    }
+
+    pub fn gen_chunk_proof_by_witness_block(
+        &mut self,
+        witness_block: WitnessBlock,
+        name: Option<&str>,
+        inner_id: Option<&str>,
+        output_dir: Option<&str>,
+    ) -> Result<ChunkProof> {
         let name = name.map_or_else(
             || {
                 witness_block
@@ -64,6 +80,15 @@ impl Prover {
             |name| name.to_string(),
         );
 
+        // The modexp size limit is baked into the chunk circuit, so a witness block of another
+        // limit can't be proved with the chunk VK.
+        if witness_block.circuits_params.max_modexp_size != MAX_MODEXP_SIZE {
+            bail!(
+                "modexp size limit {} of the witness block differs from {MAX_MODEXP_SIZE} of the chunk VK",
+                witness_block.circuits_params.max_modexp_size,
+            );
+        }
+
         let snark = self.inner.load_or_gen_final_chunk_snark(
             &name,
             &witness_block,
@@ -73,10 +98,15 @@ impl Prover {
 
         self.check_and_clear_raw_vk();
 
-        match output_dir.and_then(|output_dir| ChunkProof::from_json_file(output_dir, &name).ok()) {
+        // Reuse the dumped chunk proof only if it's of the same snark, which may be regenerated
+        // from a stale checkpoint.
+        match output_dir
+            .and_then(|output_dir| ChunkProof::from_json_file(output_dir, &name).ok())
+            .filter(|proof| proof.proof.proof() == snark.proof)
+        {
             Some(proof) => Ok(proof),
             None => {
-                let chunk_hash = ChunkHash::from_witness_block(&witness_block, false);
+                let chunk_hash = ChunkHash::from_witness_block(&witness_block, false)?;
 
                 let result =
                     ChunkProof::new(snark, self.inner.pk(LayerId::Layer2.id()), Some(chunk_hash));
@@ -106,3 +136,67 @@ impl Prover {
         }
     }
 }
+
+// Reload the witness block dumped by a previous try, if it's generated by the same circuit
+// version and params, and of the same blocks and txs, to skip running bus-mapping again.
+// Otherwise generate and dump it.
+fn load_or_gen_witness_block(
+    chunk_trace: Vec<BlockTrace>,
+    name: &str,
+    output_dir: &str,
+) -> Result<WitnessBlock> {
+    let file_path = format!("{output_dir}/witness_block_{name}.json");
+    let git_version = short_git_version();
+    let circuits_params = get_super_circuit_params();
+    let block_hashes: Vec<_> = chunk_trace.iter().map(|trace| trace.header.hash).collect();
+    let tx_hashes: Vec<_> = chunk_trace
+        .iter()
+        .flat_map(|trace| trace.transactions.iter().map(|tx| tx.tx_hash))
+        .collect();
+
+    match load_witness_block(&file_path) {
+        Ok(Some(dumped)) => {
+            let stale_reason = if dumped.git_version != git_version {
+                Some("different circuit version")
+            } else if dumped.circuits_params != circuits_params {
+                Some("different circuits params")
+            } else if !dumped
+                .witness_block
+                .context
+                .ctxs
+                .values()
+                .map(|ctx| ctx.eth_block.hash)
+                .eq(block_hashes.iter().copied())
+                || !dumped
+                    .witness_block
+                    .txs
+                    .iter()
+                    .map(|tx| tx.hash)
+                    .eq(tx_hashes.iter().copied())
+            {
+                Some("different blocks or txs")
+            } else {
+                None
+            };
+
+            match stale_reason {
+                Some(reason) => log::warn!("Stale witness block {file_path}: {reason}"),
+                None => {
+                    log::info!("Reload witness block {file_path}");
+                    return Ok(dumped.witness_block);
+                }
+            }
+        }
+        Ok(None) => (),
+        Err(err) => log::warn!("Invalid witness block {file_path}: {err}"),
+    }
+
+    let dumped = DumpedWitnessBlock {
+        git_version,
+        circuits_params,
+        witness_block: chunk_trace_to_witness_block(chunk_trace)?,
+    };
+    write_witness_block(&file_path, &dumped)?;
+
+    Ok(dumped.witness_block)
+}
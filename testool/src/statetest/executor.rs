@@ -2,6 +2,7 @@ use super::{AccountMatch, StateTest, StateTestResult};
 use crate::config::TestSuite;
 use bus_mapping::{
     circuit_input_builder::{CircuitInputBuilder, CircuitsParams, PrecompileEcParams},
+    precompile::DEFAULT_MODEXP_SIZE_LIMIT,
     state_db::CodeDB,
 };
 use eth_types::{
@@ -20,9 +21,10 @@ use once_cell::sync::Lazy;
 use std::{collections::HashMap, env, str::FromStr};
 use thiserror::Error;
 use zkevm_circuits::{
-    bytecode_circuit::circuit::BytecodeCircuit, ecc_circuit::EccCircuit,
-    modexp_circuit::ModExpCircuit, sig_circuit::SigCircuit, super_circuit::SuperCircuit,
-    test_util::CircuitTestBuilder, util::SubCircuit, witness::Block,
+    blake2f_circuit::Blake2fCircuit, bytecode_circuit::circuit::BytecodeCircuit,
+    ecc_circuit::EccCircuit, modexp_circuit::ModExpCircuit, ripemd160_circuit::Ripemd160Circuit,
+    sig_circuit::SigCircuit, super_circuit::SuperCircuit, test_util::CircuitTestBuilder,
+    util::SubCircuit, witness::Block,
 };
 
 /// Read env var with default value
@@ -195,6 +197,8 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
                 gas_tip_cap: U256::zero(),
                 call_data: st.data,
                 access_list: None,
+                max_fee_per_blob_gas: U256::zero(),
+                blob_versioned_hashes: vec![],
                 v: sig.v,
                 r: sig.r,
                 s: sig.s,
@@ -210,10 +214,9 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
                 disable_storage: !cfg!(feature = "enable-storage"),
                 ..Default::default()
             },
-            #[cfg(feature = "shanghai")]
-            chain_config: Some(external_tracer::ChainConfig::shanghai()),
-            #[cfg(not(feature = "shanghai"))]
-            chain_config: None,
+            chain_config: external_tracer::ChainConfig::from_fork_schedule(
+                &eth_types::evm_types::FORK_SCHEDULE,
+            ),
             #[cfg(feature = "scroll")]
             l1_queue_index: 0,
         },
@@ -484,6 +487,7 @@ fn get_params_for_super_circuit_test_l2() -> CircuitsParams {
             ec_mul: MAX_PRECOMPILE_EC_MUL,
             ec_pairing: MAX_PRECOMPILE_EC_PAIRING,
         },
+        max_modexp_size: DEFAULT_MODEXP_SIZE_LIMIT,
     }
 }
 
@@ -507,6 +511,7 @@ fn get_params_for_super_circuit_test() -> CircuitsParams {
             ec_mul: 50,
             ec_pairing: 2,
         },
+        max_modexp_size: DEFAULT_MODEXP_SIZE_LIMIT,
     }
 }
 
@@ -530,6 +535,7 @@ fn get_params_for_sub_circuit_test() -> CircuitsParams {
             ec_mul: 50,
             ec_pairing: 2,
         },
+        max_modexp_size: DEFAULT_MODEXP_SIZE_LIMIT,
     }
 }
 
@@ -652,6 +658,8 @@ pub fn run_test(
         } else {
             match (*CIRCUIT).as_str() {
                 "modexp" => test_with::<ModExpCircuit<Fr>>(&witness_block),
+                "blake2f" => test_with::<Blake2fCircuit<Fr>>(&witness_block),
+                "ripemd160" => test_with::<Ripemd160Circuit<Fr>>(&witness_block),
                 "bytecode" => test_with::<BytecodeCircuit<Fr>>(&witness_block),
                 "ecc" => test_with::<EccCircuit<Fr, 9>>(&witness_block),
                 "sig" => {
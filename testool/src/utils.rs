@@ -11,6 +11,7 @@ use eth_types::U256;
 
 #[derive(Debug, Eq, PartialEq, PartialOrd)]
 pub enum MainnetFork {
+    Cancun = 16,
     Shanghai = 15,
     Merge = 14,
     GrayGlacier = 13,
@@ -28,7 +29,9 @@ pub enum MainnetFork {
     Frontier = 1,
 }
 
-#[cfg(feature = "shanghai")]
+#[cfg(feature = "cancun")]
+pub const TEST_FORK: MainnetFork = MainnetFork::Cancun;
+#[cfg(all(feature = "shanghai", not(feature = "cancun")))]
 pub const TEST_FORK: MainnetFork = MainnetFork::Shanghai;
 #[cfg(not(feature = "shanghai"))]
 pub const TEST_FORK: MainnetFork = MainnetFork::Merge;
@@ -38,6 +41,7 @@ impl FromStr for MainnetFork {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
+            "Cancun" => Self::Cancun,
             "Shanghai" => Self::Shanghai,
             "Merge" => Self::Merge,
             "Gray Glacier" => Self::GrayGlacier,
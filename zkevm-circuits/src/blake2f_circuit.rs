@@ -0,0 +1,1317 @@
+//! The Blake2F circuit verifies the BLAKE2b compression function F (EIP-152) called through
+//! the precompile at address 0x09.
+//!
+//! A compression is laid out as a sequence of blocks of `BLOCK_ROWS` rows, each block
+//! evaluating up to `ROUNDS_PER_BLOCK` rounds with one 64-bit operation per row. The first
+//! block of a compression initializes the working vector from its input, the following ones
+//! continue from the state left by the previous block and the last one computes the output.
+//! Every 64-bit word is decomposed in 16 nibbles, which are checked against a 4-bit XOR table.
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod test;
+
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    table::Blake2fTable,
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness,
+};
+use bus_mapping::circuit_input_builder::Blake2F;
+use eth_types::Field;
+use gadgets::util::{not, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, TableColumn, VirtualCells,
+    },
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// Number of rounds evaluated in one block.
+const ROUNDS_PER_BLOCK: usize = 10;
+/// Rows used by one round, i.e. 8 G functions of 8 operations each.
+const ROUND_ROWS: usize = 64;
+/// First row of the rounds in a block, after the 28 input rows and the 2 initialization rows.
+const ROUNDS_START: usize = 30;
+/// First row of the finalization `v[i] ^= v[i + 8]`.
+const TAIL_START: usize = ROUNDS_START + ROUNDS_PER_BLOCK * ROUND_ROWS;
+/// First row of the output `h[i] ^ v[i]`.
+const OUTPUT_START: usize = TAIL_START + 8;
+/// Row holding the result of the block, exposed to the blake2f table.
+const FINAL_ROW: usize = OUTPUT_START + 8;
+/// Rows used by one block.
+pub(crate) const BLOCK_ROWS: usize = FINAL_ROW + 1;
+
+/// Number of 4-bit limbs of a 64-bit word.
+const NUM_LIMBS: usize = 16;
+/// Rotations (right) used by the XOR operations, the first one is a plain XOR.
+const ROTATIONS: [u32; 5] = [0, 16, 24, 32, 63];
+
+/// Initialization vector of BLAKE2b.
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// Message word schedule of BLAKE2b.
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Indices `(a, b, c, d)` of the working vector used by the 8 G functions of a round.
+const G_INDICES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// Second operand of an operation.
+#[derive(Clone, Copy, Debug)]
+enum Operand {
+    V(usize),
+    H(usize),
+    Const(u64),
+}
+
+/// Third operand of an operation, only used by additions and input rows.
+#[derive(Clone, Copy, Debug)]
+enum Message {
+    M(usize),
+    T(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RowKind {
+    /// Input bytes 0..4, the big-endian number of rounds.
+    InputRounds,
+    /// Input bytes of a little-endian word of `h`, `m` or `t`.
+    InputWord,
+    /// Input byte 212, the final block indicator flag.
+    InputF,
+    /// `v[12] ^= IV[4]` and `v[13] ^= IV[5]`, only applied in the first block.
+    Init,
+    /// Operation of the round `round` of the block, only applied when the round is active.
+    Round {
+        round: usize,
+        is_round_end: bool,
+    },
+    /// `v[i] ^= v[i + 8]`, only applied in the last block.
+    Tail,
+    /// `v[i] ^= h[i]`, only applied in the last block, the result is the output word `i`.
+    Output,
+    Final,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OpKind {
+    None,
+    /// `v[target] + second + third`
+    Add,
+    /// `rotr(v[target] ^ second, n)`
+    Xor(u32),
+}
+
+/// Static layout of a row in a block.
+#[derive(Clone, Copy, Debug)]
+struct RowSpec {
+    kind: RowKind,
+    op: OpKind,
+    target: Option<usize>,
+    second: Option<Operand>,
+    third: Option<Message>,
+}
+
+impl RowSpec {
+    fn new(kind: RowKind, op: OpKind) -> Self {
+        Self {
+            kind,
+            op,
+            target: None,
+            second: None,
+            third: None,
+        }
+    }
+
+    /// Layout of the row at `row` in a block.
+    fn at(row: usize) -> Self {
+        if row == 0 {
+            Self::new(RowKind::InputRounds, OpKind::None)
+        } else if row <= 8 {
+            Self {
+                second: Some(Operand::H(row - 1)),
+                ..Self::new(RowKind::InputWord, OpKind::None)
+            }
+        } else if row <= 24 {
+            Self {
+                third: Some(Message::M(row - 9)),
+                ..Self::new(RowKind::InputWord, OpKind::None)
+            }
+        } else if row <= 26 {
+            Self {
+                third: Some(Message::T(row - 25)),
+                ..Self::new(RowKind::InputWord, OpKind::None)
+            }
+        } else if row == 27 {
+            Self::new(RowKind::InputF, OpKind::None)
+        } else if row < ROUNDS_START {
+            let i = row - 28;
+            Self {
+                target: Some(12 + i),
+                second: Some(Operand::Const(IV[4 + i])),
+                ..Self::new(RowKind::Init, OpKind::Xor(0))
+            }
+        } else if row < TAIL_START {
+            let round = (row - ROUNDS_START) / ROUND_ROWS;
+            let g = (row - ROUNDS_START) % ROUND_ROWS / 8;
+            let step = (row - ROUNDS_START) % 8;
+            let kind = RowKind::Round {
+                round,
+                is_round_end: (row - ROUNDS_START) % ROUND_ROWS == ROUND_ROWS - 1,
+            };
+            let [a, b, c, d] = G_INDICES[g];
+            let (x, y) = (SIGMA[round][2 * g], SIGMA[round][2 * g + 1]);
+            let (op, target, second, third) = match step {
+                0 => (OpKind::Add, a, b, Some(Message::M(x))),
+                1 => (OpKind::Xor(32), d, a, None),
+                2 => (OpKind::Add, c, d, None),
+                3 => (OpKind::Xor(24), b, c, None),
+                4 => (OpKind::Add, a, b, Some(Message::M(y))),
+                5 => (OpKind::Xor(16), d, a, None),
+                6 => (OpKind::Add, c, d, None),
+                _ => (OpKind::Xor(63), b, c, None),
+            };
+            Self {
+                kind,
+                op,
+                target: Some(target),
+                second: Some(Operand::V(second)),
+                third,
+            }
+        } else if row < OUTPUT_START {
+            let i = row - TAIL_START;
+            Self {
+                target: Some(i),
+                second: Some(Operand::V(i + 8)),
+                ..Self::new(RowKind::Tail, OpKind::Xor(0))
+            }
+        } else if row < FINAL_ROW {
+            let i = row - OUTPUT_START;
+            Self {
+                target: Some(i),
+                second: Some(Operand::H(i)),
+                ..Self::new(RowKind::Output, OpKind::Xor(0))
+            }
+        } else {
+            Self::new(RowKind::Final, OpKind::None)
+        }
+    }
+}
+
+/// Number of blocks used by a compression of `rounds` rounds.
+fn num_blocks(rounds: u32) -> usize {
+    ((rounds as usize + ROUNDS_PER_BLOCK - 1) / ROUNDS_PER_BLOCK).max(1)
+}
+
+/// Witness of a row of a block.
+#[derive(Clone, Debug, Default)]
+struct RowWitness {
+    v: [u64; 16],
+    h: [u64; 8],
+    m: [u64; 16],
+    t: [u64; 2],
+    f: bool,
+    rounds: u32,
+    is_first: bool,
+    is_last: bool,
+    cnt: u64,
+    is_active: bool,
+    act: u64,
+    in1: u64,
+    in2: u64,
+    in3: u64,
+    out: u64,
+    res: u64,
+    carry: u64,
+    a: u64,
+    b: u64,
+    x: u64,
+    hb: u64,
+    lo3: u64,
+}
+
+/// Generate the witness of all the blocks of a compression.
+fn compression_rows(event: &Blake2F) -> Vec<RowWitness> {
+    let num_blocks = num_blocks(event.rounds);
+    let rounds = event.rounds as u64;
+    let mut v = [0u64; 16];
+    let mut rows = Vec::with_capacity(num_blocks * BLOCK_ROWS);
+
+    for block in 0..num_blocks {
+        let is_first = block == 0;
+        let is_last = block == num_blocks - 1;
+        let cnt = (block * ROUNDS_PER_BLOCK) as u64;
+        let active_rounds = rounds.saturating_sub(cnt).min(ROUNDS_PER_BLOCK as u64);
+        if is_first {
+            v[..8].copy_from_slice(&event.h);
+            v[8..12].copy_from_slice(&IV[..4]);
+            v[12] = event.t[0];
+            v[13] = event.t[1];
+            v[14] = if event.f { !IV[6] } else { IV[6] };
+            v[15] = IV[7];
+        }
+
+        for row in 0..BLOCK_ROWS {
+            let spec = RowSpec::at(row);
+            let mut w = RowWitness {
+                v,
+                h: event.h,
+                m: event.m,
+                t: event.t,
+                f: event.f,
+                rounds: event.rounds,
+                is_first,
+                is_last,
+                cnt,
+                ..Default::default()
+            };
+            let gate = match spec.kind {
+                RowKind::Init => is_first,
+                RowKind::Round { round, .. } => {
+                    w.is_active = (round as u64) < active_rounds;
+                    w.act = (round as u64).min(active_rounds);
+                    w.is_active
+                }
+                RowKind::Tail | RowKind::Output => {
+                    w.act = active_rounds;
+                    is_last
+                }
+                RowKind::Final => {
+                    w.act = active_rounds;
+                    false
+                }
+                _ => false,
+            };
+
+            w.in1 = spec.target.map_or(0, |i| v[i]);
+            w.in2 = match spec.second {
+                Some(Operand::V(i)) => v[i],
+                Some(Operand::H(i)) => event.h[i],
+                Some(Operand::Const(c)) => c,
+                None => 0,
+            };
+            w.in3 = match spec.third {
+                Some(Message::M(i)) => event.m[i],
+                Some(Message::T(i)) => event.t[i],
+                None => 0,
+            };
+            match spec.op {
+                OpKind::Add => {
+                    let sum = w.in1 as u128 + w.in2 as u128 + w.in3 as u128;
+                    w.out = sum as u64;
+                    w.carry = (sum >> 64) as u64;
+                    w.b = w.out;
+                    w.x = w.out;
+                }
+                OpKind::Xor(n) => {
+                    w.a = w.in1;
+                    w.b = w.in2;
+                    w.x = w.in1 ^ w.in2;
+                    w.out = w.x.rotate_right(n);
+                    if n == 63 {
+                        w.hb = w.x >> 63;
+                        w.lo3 = (w.x >> 60) & 7;
+                    }
+                }
+                OpKind::None => {
+                    let value = match spec.kind {
+                        RowKind::InputRounds => rounds,
+                        RowKind::InputWord => w.in2 + w.in3,
+                        RowKind::InputF => event.f as u64,
+                        _ => 0,
+                    };
+                    w.b = value;
+                    w.x = value;
+                }
+            }
+            w.res = if gate { w.out } else { w.in1 };
+            if let Some(i) = spec.target {
+                v[i] = w.res;
+            }
+            rows.push(w);
+        }
+    }
+
+    rows
+}
+
+/// Little-endian 4-bit limbs of a word.
+fn limbs(value: u64) -> [u64; NUM_LIMBS] {
+    array_init::array_init(|i| (value >> (4 * i)) & 0xf)
+}
+
+/// Compose little-endian limbs of `2^bits` into a value.
+fn compose<F: Field>(limbs: &[Expression<F>], bits: usize) -> Expression<F> {
+    limbs.iter().rev().fold(0.expr(), |acc, limb| {
+        acc * Expression::Constant(F::from(1u64 << bits)) + limb.clone()
+    })
+}
+
+/// Bytes of the little-endian limbs of a word.
+fn bytes<F: Field>(limbs: &[Expression<F>]) -> Vec<Expression<F>> {
+    limbs
+        .chunks(2)
+        .map(|pair| pair[0].clone() + pair[1].clone() * 16.expr())
+        .collect()
+}
+
+/// Blake2f circuit config
+#[derive(Clone, Debug)]
+pub struct Blake2fCircuitConfig<F> {
+    /// Enabled at the first row of the first block.
+    q_first: Column<Fixed>,
+    /// Enabled at the first row of every block.
+    q_start: Column<Fixed>,
+    /// Enabled at every row of a block but the final one.
+    q_trans: Column<Fixed>,
+    /// Enabled at the initialization rows.
+    q_init: Column<Fixed>,
+    /// Enabled at the round rows.
+    q_round: Column<Fixed>,
+    /// Enabled at the first row of the first round.
+    q_round_begin: Column<Fixed>,
+    /// Enabled at the round rows followed by a row of the same round.
+    q_round_cont: Column<Fixed>,
+    /// Enabled at the last row of every round but the last one.
+    q_round_end: Column<Fixed>,
+    /// Enabled at the last row of the last round.
+    q_round_end_last: Column<Fixed>,
+    /// Enabled at the finalization and output rows.
+    q_tail: Column<Fixed>,
+    /// Enabled at the output rows.
+    q_output: Column<Fixed>,
+    /// Enabled at the first output row.
+    q_output_first: Column<Fixed>,
+    /// Enabled at the addition rows.
+    q_add: Column<Fixed>,
+    /// Enabled at the XOR rows, one column per rotation in `ROTATIONS`.
+    q_rot: [Column<Fixed>; 5],
+    /// Enabled at the input row of the number of rounds.
+    q_input_rounds: Column<Fixed>,
+    /// Enabled at the input rows of the words.
+    q_input_word: Column<Fixed>,
+    /// Enabled at the input row of the final block indicator flag.
+    q_input_f: Column<Fixed>,
+    /// Enabled at the rows after the input whose input RLC is kept.
+    q_keep_input: Column<Fixed>,
+    /// Selection of the word of `v` used as first operand and updated by the operation.
+    sel_v: [Column<Fixed>; 16],
+    /// Selection of the word of `v` used as second operand.
+    sel2_v: [Column<Fixed>; 16],
+    /// Selection of the word of `h` used as second operand.
+    sel2_h: [Column<Fixed>; 8],
+    /// Constant second operand.
+    c2: Column<Fixed>,
+    /// Selection of the word of `m` used as third operand.
+    sel3_m: [Column<Fixed>; 16],
+    /// Selection of the word of `t` used as third operand.
+    sel3_t: [Column<Fixed>; 2],
+
+    /// Working vector before the operation of the row.
+    v: [Column<Advice>; 16],
+    /// State vector.
+    h: [Column<Advice>; 8],
+    /// Message block vector.
+    m: [Column<Advice>; 16],
+    /// Offset counters.
+    t: [Column<Advice>; 2],
+    /// Final block indicator flag.
+    f: Column<Advice>,
+    /// Whether the block is the first one of a compression.
+    is_first: Column<Advice>,
+    /// Number of rounds evaluated by the previous blocks of the compression.
+    cnt: Column<Advice>,
+    /// Whether the round of the row is evaluated.
+    is_active: Column<Advice>,
+    /// Number of rounds evaluated before the row in the block.
+    act: Column<Advice>,
+    in1: Column<Advice>,
+    in2: Column<Advice>,
+    in3: Column<Advice>,
+    /// Result of the operation.
+    out: Column<Advice>,
+    /// Value of the updated word of `v`, `out` if the operation is applied else `in1`.
+    res: Column<Advice>,
+    /// Carry of the addition.
+    carry: Column<Advice>,
+    /// Limbs of the first XOR operand.
+    a: [Column<Advice>; NUM_LIMBS],
+    /// Limbs of the second XOR operand.
+    b: [Column<Advice>; NUM_LIMBS],
+    /// Limbs of the XOR, of the addition result or of the input word.
+    x: [Column<Advice>; NUM_LIMBS],
+    /// Highest bit of the XOR for the rotation by 63.
+    hb: Column<Advice>,
+    /// Lowest 3 bits of the highest limb of the XOR for the rotation by 63.
+    lo3: Column<Advice>,
+
+    /// 4-bit XOR table.
+    xor_table: [TableColumn; 3],
+    blake2f_table: Blake2fTable,
+    _marker: PhantomData<F>,
+}
+
+/// Config args for Blake2f circuit
+#[derive(Clone, Debug)]
+pub struct Blake2fCircuitConfigArgs<F: Field> {
+    /// Blake2f Table
+    pub blake2f_table: Blake2fTable,
+    /// Challenges randomness
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for Blake2fCircuitConfig<F> {
+    type ConfigArgs = Blake2fCircuitConfigArgs<F>;
+
+    /// Return a new Blake2fCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            blake2f_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_first = meta.fixed_column();
+        let q_start = meta.fixed_column();
+        let q_trans = meta.fixed_column();
+        let q_init = meta.fixed_column();
+        let q_round = meta.fixed_column();
+        let q_round_begin = meta.fixed_column();
+        let q_round_cont = meta.fixed_column();
+        let q_round_end = meta.fixed_column();
+        let q_round_end_last = meta.fixed_column();
+        let q_tail = meta.fixed_column();
+        let q_output = meta.fixed_column();
+        let q_output_first = meta.fixed_column();
+        let q_add = meta.fixed_column();
+        let q_rot: [Column<Fixed>; 5] = array_init::array_init(|_| meta.fixed_column());
+        let q_input_rounds = meta.fixed_column();
+        let q_input_word = meta.fixed_column();
+        let q_input_f = meta.fixed_column();
+        let q_keep_input = meta.fixed_column();
+        let sel_v: [Column<Fixed>; 16] = array_init::array_init(|_| meta.fixed_column());
+        let sel2_v: [Column<Fixed>; 16] = array_init::array_init(|_| meta.fixed_column());
+        let sel2_h: [Column<Fixed>; 8] = array_init::array_init(|_| meta.fixed_column());
+        let c2 = meta.fixed_column();
+        let sel3_m: [Column<Fixed>; 16] = array_init::array_init(|_| meta.fixed_column());
+        let sel3_t: [Column<Fixed>; 2] = array_init::array_init(|_| meta.fixed_column());
+
+        let v: [Column<Advice>; 16] = array_init::array_init(|_| meta.advice_column());
+        let h: [Column<Advice>; 8] = array_init::array_init(|_| meta.advice_column());
+        let m: [Column<Advice>; 16] = array_init::array_init(|_| meta.advice_column());
+        let t: [Column<Advice>; 2] = array_init::array_init(|_| meta.advice_column());
+        let f = meta.advice_column();
+        let is_first = meta.advice_column();
+        let cnt = meta.advice_column();
+        let is_active = meta.advice_column();
+        let act = meta.advice_column();
+        let in1 = meta.advice_column();
+        let in2 = meta.advice_column();
+        let in3 = meta.advice_column();
+        let out = meta.advice_column();
+        let res = meta.advice_column();
+        let carry = meta.advice_column();
+        let a: [Column<Advice>; NUM_LIMBS] = array_init::array_init(|_| meta.advice_column());
+        let b: [Column<Advice>; NUM_LIMBS] = array_init::array_init(|_| meta.advice_column());
+        let x: [Column<Advice>; NUM_LIMBS] = array_init::array_init(|_| meta.advice_column());
+        let hb = meta.advice_column();
+        let lo3 = meta.advice_column();
+        let xor_table: [TableColumn; 3] = array_init::array_init(|_| meta.lookup_table_column());
+
+        let is_last = blake2f_table.is_final;
+        let rounds = blake2f_table.rounds;
+        let input_rlc = blake2f_table.input_rlc;
+        let output_rlc = blake2f_table.output_rlc;
+
+        let fixed = |meta: &mut VirtualCells<F>, column: Column<Fixed>| {
+            meta.query_fixed(column, Rotation::cur())
+        };
+        let advice = |meta: &mut VirtualCells<F>, column: Column<Advice>, rotation: i32| {
+            meta.query_advice(column, Rotation(rotation))
+        };
+        let select =
+            |meta: &mut VirtualCells<F>, sels: &[Column<Fixed>], cols: &[Column<Advice>]| {
+                sels.iter().zip(cols).fold(0.expr(), |acc, (&sel, &col)| {
+                    acc + meta.query_fixed(sel, Rotation::cur())
+                        * meta.query_advice(col, Rotation::cur())
+                })
+            };
+        let r_pows = {
+            let r = challenges.keccak_input();
+            let mut pows = vec![1.expr()];
+            for _ in 0..8 {
+                pows.push(pows.last().unwrap().clone() * r.clone());
+            }
+            pows
+        };
+
+        meta.create_gate("blake2f operation", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let in1_expr = advice(meta, in1, 0);
+            let out_expr = advice(meta, out, 0);
+            let res_expr = advice(meta, res, 0);
+            let v_cols: Vec<Column<Advice>> = v.iter().chain(h.iter()).copied().collect();
+            let sel2: Vec<Column<Fixed>> = sel2_v.iter().chain(sel2_h.iter()).copied().collect();
+            let mt_cols: Vec<Column<Advice>> = m.iter().chain(t.iter()).copied().collect();
+            let sel3: Vec<Column<Fixed>> = sel3_m.iter().chain(sel3_t.iter()).copied().collect();
+
+            cb.require_equal(
+                "in1 = v[target]",
+                in1_expr.clone(),
+                select(meta, &sel_v[..], &v[..]),
+            );
+            cb.require_equal(
+                "in2 = v[i] or h[i] or constant",
+                advice(meta, in2, 0),
+                select(meta, &sel2[..], &v_cols[..]) + fixed(meta, c2),
+            );
+            cb.require_equal(
+                "in3 = m[i] or t[i]",
+                advice(meta, in3, 0),
+                select(meta, &sel3[..], &mt_cols[..]),
+            );
+
+            // The operation is applied at the initialization rows of the first block, at the
+            // active rounds, and at the finalization rows of the last block.
+            let gate = fixed(meta, q_init) * advice(meta, is_first, 0)
+                + fixed(meta, q_round) * advice(meta, is_active, 0)
+                + fixed(meta, q_tail) * advice(meta, is_last, 0);
+            cb.require_equal(
+                "res = gate ? out : in1",
+                res_expr.clone(),
+                in1_expr + gate * (out_expr - advice(meta, in1, 0)),
+            );
+
+            for (i, &column) in v.iter().enumerate() {
+                let v_cur = advice(meta, column, 0);
+                cb.require_equal(
+                    "v[i]::next = sel_v[i] ? res : v[i]",
+                    advice(meta, column, 1),
+                    v_cur.clone() + fixed(meta, sel_v[i]) * (res_expr.clone() - v_cur),
+                );
+            }
+            for column in h
+                .iter()
+                .chain(m.iter())
+                .chain(t.iter())
+                .chain([f, rounds, is_first, is_last, cnt].iter())
+            {
+                cb.require_equal(
+                    "block constant is kept",
+                    advice(meta, *column, 1),
+                    advice(meta, *column, 0),
+                );
+            }
+
+            cb.gate(fixed(meta, q_trans))
+        });
+
+        meta.create_gate("blake2f addition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let x_limbs = x.map(|column| advice(meta, column, 0));
+            let carry_expr = advice(meta, carry, 0);
+            let two_pow_64 = Expression::Constant(F::from(1u64 << 32).square());
+            cb.require_equal(
+                "in1 + in2 + in3 = out + carry * 2^64",
+                advice(meta, in1, 0) + advice(meta, in2, 0) + advice(meta, in3, 0),
+                advice(meta, out, 0) + carry_expr.clone() * two_pow_64,
+            );
+            cb.require_in_set(
+                "carry in [0, 1, 2]",
+                carry_expr,
+                vec![0.expr(), 1.expr(), 2.expr()],
+            );
+            cb.require_equal("out = limbs(x)", advice(meta, out, 0), compose(&x_limbs, 4));
+
+            cb.gate(fixed(meta, q_add))
+        });
+
+        meta.create_gate("blake2f xor", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let a_limbs = a.map(|column| advice(meta, column, 0));
+            let b_limbs = b.map(|column| advice(meta, column, 0));
+            let x_limbs = x.map(|column| advice(meta, column, 0));
+            let q_xor = q_rot
+                .iter()
+                .fold(0.expr(), |acc, &column| acc + fixed(meta, column));
+            cb.condition(q_xor, |cb| {
+                cb.require_equal("in1 = limbs(a)", advice(meta, in1, 0), compose(&a_limbs, 4));
+                cb.require_equal("in2 = limbs(b)", advice(meta, in2, 0), compose(&b_limbs, 4));
+            });
+
+            for (&n, &q) in ROTATIONS.iter().zip(q_rot.iter()) {
+                let out_expr = advice(meta, out, 0);
+                if n == 63 {
+                    // rotr(x, 63) = 2 * (x mod 2^63) + x >> 63
+                    let hb_expr = advice(meta, hb, 0);
+                    let lo3_expr = advice(meta, lo3, 0);
+                    cb.condition(fixed(meta, q), |cb| {
+                        cb.require_boolean("hb is boolean", hb_expr.clone());
+                        cb.require_equal(
+                            "x[15] = hb * 8 + lo3",
+                            x_limbs[NUM_LIMBS - 1].clone(),
+                            hb_expr.clone() * 8.expr() + lo3_expr.clone(),
+                        );
+                        let low = compose(&x_limbs[..NUM_LIMBS - 1], 4)
+                            + lo3_expr * Expression::Constant(F::from(1u64 << 60));
+                        cb.require_equal("out = rotr(x, 63)", out_expr, low * 2.expr() + hb_expr);
+                    });
+                } else {
+                    let shift = (n / 4) as usize;
+                    let rotated: Vec<Expression<F>> = (0..NUM_LIMBS)
+                        .map(|i| x_limbs[(i + shift) % NUM_LIMBS].clone())
+                        .collect();
+                    cb.condition(fixed(meta, q), |cb| {
+                        cb.require_equal("out = rotr(x, n)", out_expr, compose(&rotated, 4));
+                    });
+                }
+            }
+
+            cb.gate(1.expr())
+        });
+
+        meta.create_gate("blake2f input and output", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let x_limbs = x.map(|column| advice(meta, column, 0));
+            let x_bytes = bytes(&x_limbs);
+            let word_rlc = x_bytes.iter().enumerate().fold(0.expr(), |acc, (i, byte)| {
+                acc + byte.clone() * r_pows[7 - i].clone()
+            });
+            let input_rlc_cur = advice(meta, input_rlc, 0);
+            let input_rlc_prev = advice(meta, input_rlc, -1);
+
+            // The number of rounds is encoded in big-endian on the first 4 bytes.
+            cb.condition(fixed(meta, q_input_rounds), |cb| {
+                cb.require_equal(
+                    "rounds = limbs(x)",
+                    advice(meta, rounds, 0),
+                    compose(&x_limbs[..8], 4),
+                );
+                for limb in x_limbs[8..].iter() {
+                    cb.require_zero("rounds is 4 bytes", limb.clone());
+                }
+                cb.require_equal(
+                    "input_rlc = rlc(rounds bytes)",
+                    input_rlc_cur.clone(),
+                    x_bytes[..4]
+                        .iter()
+                        .enumerate()
+                        .fold(0.expr(), |acc, (i, byte)| {
+                            acc + byte.clone() * r_pows[i].clone()
+                        }),
+                );
+            });
+            // The words are encoded in little-endian.
+            cb.condition(fixed(meta, q_input_word), |cb| {
+                cb.require_equal(
+                    "in2 + in3 = limbs(x)",
+                    advice(meta, in2, 0) + advice(meta, in3, 0),
+                    compose(&x_limbs, 4),
+                );
+                cb.require_equal(
+                    "input_rlc = input_rlc::prev * r^8 + rlc(word bytes)",
+                    input_rlc_cur.clone(),
+                    input_rlc_prev.clone() * r_pows[8].clone() + word_rlc.clone(),
+                );
+            });
+            cb.condition(fixed(meta, q_input_f), |cb| {
+                let f_expr = advice(meta, f, 0);
+                cb.require_boolean("f is boolean", f_expr.clone());
+                cb.require_equal("f = x[0]", f_expr.clone(), x_limbs[0].clone());
+                for limb in x_limbs[1..].iter() {
+                    cb.require_zero("f is 1 byte", limb.clone());
+                }
+                cb.require_equal(
+                    "input_rlc = input_rlc::prev * r + f",
+                    input_rlc_cur.clone(),
+                    input_rlc_prev * r_pows[1].clone() + f_expr,
+                );
+            });
+            cb.condition(fixed(meta, q_keep_input), |cb| {
+                cb.require_equal(
+                    "input_rlc::next = input_rlc",
+                    advice(meta, input_rlc, 1),
+                    input_rlc_cur,
+                );
+            });
+
+            cb.condition(fixed(meta, q_output_first), |cb| {
+                cb.require_zero("output_rlc starts at 0", advice(meta, output_rlc, 0));
+            });
+            cb.condition(fixed(meta, q_output), |cb| {
+                cb.require_equal(
+                    "output_rlc::next = output_rlc * r^8 + rlc(word bytes)",
+                    advice(meta, output_rlc, 1),
+                    advice(meta, output_rlc, 0) * r_pows[8].clone() + word_rlc,
+                );
+            });
+
+            cb.gate(1.expr())
+        });
+
+        meta.create_gate("blake2f block start", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_start_expr = fixed(meta, q_start);
+            let q_first_expr = fixed(meta, q_first);
+            let is_first_expr = advice(meta, is_first, 0);
+            let is_last_expr = advice(meta, is_last, 0);
+
+            cb.condition(q_start_expr.clone(), |cb| {
+                cb.require_boolean("is_first is boolean", is_first_expr.clone());
+                cb.require_boolean("is_last is boolean", is_last_expr);
+                cb.require_equal(
+                    "cnt = is_first ? 0 : cnt::prev + ROUNDS_PER_BLOCK",
+                    advice(meta, cnt, 0),
+                    not::expr(is_first_expr.clone())
+                        * (advice(meta, cnt, -1) + ROUNDS_PER_BLOCK.expr()),
+                );
+            });
+            cb.condition(q_first_expr.clone(), |cb| {
+                cb.require_equal("first block is_first", is_first_expr.clone(), 1.expr());
+            });
+            // A new compression starts after the last block of the previous one.
+            cb.condition(q_start_expr.clone() - q_first_expr, |cb| {
+                cb.require_equal(
+                    "is_first = is_last::prev",
+                    is_first_expr.clone(),
+                    advice(meta, is_last, -1),
+                );
+            });
+            cb.condition(
+                q_start_expr.clone() * not::expr(is_first_expr.clone()),
+                |cb| {
+                    for column in v
+                        .iter()
+                        .chain(h.iter())
+                        .chain(m.iter())
+                        .chain(t.iter())
+                        .chain([f, rounds].iter())
+                    {
+                        cb.require_equal(
+                            "continued block keeps the state",
+                            advice(meta, *column, 0),
+                            advice(meta, *column, -1),
+                        );
+                    }
+                },
+            );
+            cb.condition(q_start_expr * is_first_expr, |cb| {
+                let f_expr = advice(meta, f, 0);
+                let init = (0..16)
+                    .map(|i| match i {
+                        0..=7 => advice(meta, h[i], 0),
+                        8..=11 => Expression::Constant(F::from(IV[i - 8])),
+                        12 | 13 => advice(meta, t[i - 12], 0),
+                        // v[14] = f ? !IV[6] : IV[6]
+                        14 => {
+                            Expression::Constant(F::from(IV[6]))
+                                + f_expr.clone()
+                                    * Expression::Constant(
+                                        F::from(u64::MAX) - F::from(IV[6]) - F::from(IV[6]),
+                                    )
+                        }
+                        _ => Expression::Constant(F::from(IV[7])),
+                    })
+                    .collect::<Vec<_>>();
+                for (column, value) in v.iter().zip(init) {
+                    cb.require_equal("v is initialized", advice(meta, *column, 0), value);
+                }
+            });
+
+            cb.gate(1.expr())
+        });
+
+        meta.create_gate("blake2f rounds", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_active_cur = advice(meta, is_active, 0);
+            let is_active_next = advice(meta, is_active, 1);
+            let act_cur = advice(meta, act, 0);
+            let act_next = advice(meta, act, 1);
+
+            cb.condition(fixed(meta, q_round), |cb| {
+                cb.require_boolean("is_active is boolean", is_active_cur.clone());
+            });
+            cb.condition(fixed(meta, q_round_begin), |cb| {
+                cb.require_zero("act starts at 0", act_cur.clone());
+            });
+            cb.condition(fixed(meta, q_round_cont), |cb| {
+                cb.require_equal(
+                    "is_active is the same in a round",
+                    is_active_next.clone(),
+                    is_active_cur.clone(),
+                );
+                cb.require_equal(
+                    "act is the same in a round",
+                    act_next.clone(),
+                    act_cur.clone(),
+                );
+            });
+            // The active rounds are a prefix of the rounds of the block.
+            cb.condition(fixed(meta, q_round_end), |cb| {
+                cb.require_equal(
+                    "act::next = act + is_active",
+                    act_next.clone(),
+                    act_cur.clone() + is_active_cur.clone(),
+                );
+                cb.require_zero(
+                    "inactive round is followed by inactive round",
+                    is_active_next * not::expr(is_active_cur.clone()),
+                );
+            });
+            // All rounds are active in a block that is not the last one.
+            cb.condition(fixed(meta, q_round_end_last), |cb| {
+                cb.require_equal(
+                    "act::next = act + is_active",
+                    act_next.clone(),
+                    act_cur.clone() + is_active_cur.clone(),
+                );
+                cb.require_zero(
+                    "last round is active if not last block",
+                    not::expr(advice(meta, is_last, 0)) * not::expr(is_active_cur),
+                );
+            });
+            cb.condition(fixed(meta, q_tail), |cb| {
+                cb.require_equal("act is kept", act_next, act_cur.clone());
+            });
+            cb.condition(fixed(meta, blake2f_table.q_enable), |cb| {
+                cb.require_zero(
+                    "rounds = cnt + act in the last block",
+                    advice(meta, is_last, 0)
+                        * (advice(meta, rounds, 0) - advice(meta, cnt, 0) - act_cur),
+                );
+            });
+
+            cb.gate(1.expr())
+        });
+
+        for i in 0..NUM_LIMBS {
+            meta.lookup("blake2f nibble xor", |meta| {
+                vec![
+                    (meta.query_advice(a[i], Rotation::cur()), xor_table[0]),
+                    (meta.query_advice(b[i], Rotation::cur()), xor_table[1]),
+                    (meta.query_advice(x[i], Rotation::cur()), xor_table[2]),
+                ]
+            });
+        }
+        // 2 * lo3 is a nibble iff lo3 < 8
+        meta.lookup("blake2f lo3 range", |meta| {
+            let lo3_doubled = meta.query_advice(lo3, Rotation::cur()) * 2.expr();
+            vec![
+                (lo3_doubled.clone(), xor_table[0]),
+                (0.expr(), xor_table[1]),
+                (lo3_doubled, xor_table[2]),
+            ]
+        });
+
+        Self {
+            q_first,
+            q_start,
+            q_trans,
+            q_init,
+            q_round,
+            q_round_begin,
+            q_round_cont,
+            q_round_end,
+            q_round_end_last,
+            q_tail,
+            q_output,
+            q_output_first,
+            q_add,
+            q_rot,
+            q_input_rounds,
+            q_input_word,
+            q_input_f,
+            q_keep_input,
+            sel_v,
+            sel2_v,
+            sel2_h,
+            c2,
+            sel3_m,
+            sel3_t,
+            v,
+            h,
+            m,
+            t,
+            f,
+            is_first,
+            cnt,
+            is_active,
+            act,
+            in1,
+            in2,
+            in3,
+            out,
+            res,
+            carry,
+            a,
+            b,
+            x,
+            hb,
+            lo3,
+            xor_table,
+            blake2f_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Blake2fCircuitConfig<F> {
+    fn load_xor_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "blake2f xor table",
+            |mut table| {
+                for (offset, (lhs, rhs)) in (0..16u64)
+                    .flat_map(|lhs| (0..16u64).map(move |rhs| (lhs, rhs)))
+                    .enumerate()
+                {
+                    for (column, value) in self.xor_table.iter().zip([lhs, rhs, lhs ^ rhs]) {
+                        table.assign_cell(
+                            || "blake2f xor table",
+                            *column,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn assign_fixed_row(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        row: usize,
+    ) -> Result<(), Error> {
+        let spec = RowSpec::at(row);
+        let mut enabled = vec![];
+        if row == 0 {
+            enabled.push(self.q_start);
+        }
+        if row != FINAL_ROW {
+            enabled.push(self.q_trans);
+        }
+        if (27..FINAL_ROW).contains(&row) {
+            enabled.push(self.q_keep_input);
+        }
+        match spec.kind {
+            RowKind::InputRounds => enabled.push(self.q_input_rounds),
+            RowKind::InputWord => enabled.push(self.q_input_word),
+            RowKind::InputF => enabled.push(self.q_input_f),
+            RowKind::Init => enabled.push(self.q_init),
+            RowKind::Round {
+                round,
+                is_round_end,
+            } => {
+                enabled.push(self.q_round);
+                if row == ROUNDS_START {
+                    enabled.push(self.q_round_begin);
+                }
+                if !is_round_end {
+                    enabled.push(self.q_round_cont);
+                } else if round + 1 < ROUNDS_PER_BLOCK {
+                    enabled.push(self.q_round_end);
+                } else {
+                    enabled.push(self.q_round_end_last);
+                }
+            }
+            RowKind::Tail => enabled.push(self.q_tail),
+            RowKind::Output => {
+                enabled.extend([self.q_tail, self.q_output]);
+                if row == OUTPUT_START {
+                    enabled.push(self.q_output_first);
+                }
+            }
+            RowKind::Final => enabled.push(self.blake2f_table.q_enable),
+        }
+        match spec.op {
+            OpKind::Add => enabled.push(self.q_add),
+            OpKind::Xor(n) => {
+                let idx = ROTATIONS.iter().position(|&r| r == n).unwrap();
+                enabled.push(self.q_rot[idx]);
+            }
+            OpKind::None => {}
+        }
+        if let Some(i) = spec.target {
+            enabled.push(self.sel_v[i]);
+        }
+        match spec.second {
+            Some(Operand::V(i)) => enabled.push(self.sel2_v[i]),
+            Some(Operand::H(i)) => enabled.push(self.sel2_h[i]),
+            Some(Operand::Const(c)) => {
+                region.assign_fixed(|| "c2", self.c2, offset, || Value::known(F::from(c)))?;
+            }
+            None => {}
+        }
+        match spec.third {
+            Some(Message::M(i)) => enabled.push(self.sel3_m[i]),
+            Some(Message::T(i)) => enabled.push(self.sel3_t[i]),
+            None => {}
+        }
+
+        for column in enabled {
+            region.assign_fixed(
+                || format!("blake2f fixed row {offset}"),
+                column,
+                offset,
+                || Value::known(F::one()),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn assign_row(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        w: &RowWitness,
+        input_rlc: Value<F>,
+        output_rlc: Value<F>,
+    ) -> Result<(), Error> {
+        let words =
+            w.v.iter()
+                .zip(self.v.iter())
+                .chain(w.h.iter().zip(self.h.iter()))
+                .chain(w.m.iter().zip(self.m.iter()))
+                .chain(w.t.iter().zip(self.t.iter()))
+                .map(|(value, column)| (*column, *value));
+        let limb_cells = limbs(w.a)
+            .into_iter()
+            .zip(self.a)
+            .chain(limbs(w.b).into_iter().zip(self.b))
+            .chain(limbs(w.x).into_iter().zip(self.x))
+            .map(|(value, column)| (column, value));
+        let scalars = [
+            (self.f, w.f as u64),
+            (self.blake2f_table.rounds, w.rounds as u64),
+            (self.is_first, w.is_first as u64),
+            (self.blake2f_table.is_final, w.is_last as u64),
+            (self.cnt, w.cnt),
+            (self.is_active, w.is_active as u64),
+            (self.act, w.act),
+            (self.in1, w.in1),
+            (self.in2, w.in2),
+            (self.in3, w.in3),
+            (self.out, w.out),
+            (self.res, w.res),
+            (self.carry, w.carry),
+            (self.hb, w.hb),
+            (self.lo3, w.lo3),
+        ];
+        for (column, value) in words.chain(limb_cells).chain(scalars) {
+            region.assign_advice(
+                || format!("blake2f row {offset}"),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        region.assign_advice(
+            || format!("blake2f input_rlc {offset}"),
+            self.blake2f_table.input_rlc,
+            offset,
+            || input_rlc,
+        )?;
+        region.assign_advice(
+            || format!("blake2f output_rlc {offset}"),
+            self.blake2f_table.output_rlc,
+            offset,
+            || output_rlc,
+        )?;
+        Ok(())
+    }
+
+    /// Assign the blocks of a compression starting at `offset`, returns the next offset.
+    fn assign_compression(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        rows: &[RowWitness],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<usize, Error> {
+        let r = challenges.keccak_input();
+        let mut input_rlc = Value::known(F::zero());
+        let mut output_rlc = Value::known(F::zero());
+
+        for (i, w) in rows.iter().enumerate() {
+            let row = i % BLOCK_ROWS;
+            let spec = RowSpec::at(row);
+            let word_bytes = w.x.to_le_bytes();
+            match spec.kind {
+                RowKind::InputRounds => {
+                    input_rlc = r.map(|r| {
+                        w.rounds
+                            .to_be_bytes()
+                            .iter()
+                            .fold(F::zero(), |acc, byte| acc * r + F::from(*byte as u64))
+                    });
+                }
+                RowKind::InputWord => {
+                    input_rlc = input_rlc.zip(r).map(|(acc, r)| {
+                        word_bytes
+                            .iter()
+                            .fold(acc, |acc, byte| acc * r + F::from(*byte as u64))
+                    });
+                }
+                RowKind::InputF => {
+                    input_rlc = input_rlc
+                        .zip(r)
+                        .map(|(acc, r)| acc * r + F::from(w.f as u64));
+                }
+                _ => {}
+            }
+            if row == OUTPUT_START {
+                output_rlc = Value::known(F::zero());
+            }
+
+            self.assign_fixed_row(region, offset + i, row)?;
+            self.assign_row(region, offset + i, w, input_rlc, output_rlc)?;
+
+            if spec.kind == RowKind::Output {
+                output_rlc = output_rlc.zip(r).map(|(acc, r)| {
+                    word_bytes
+                        .iter()
+                        .fold(acc, |acc, byte| acc * r + F::from(*byte as u64))
+                });
+            }
+        }
+
+        Ok(offset + rows.len())
+    }
+
+    /// Assign the all-zero row at `offset`.
+    fn assign_zero_row(&self, region: &mut Region<F>, offset: usize) -> Result<(), Error> {
+        self.assign_row(
+            region,
+            offset,
+            &RowWitness::default(),
+            Value::known(F::zero()),
+            Value::known(F::zero()),
+        )
+    }
+}
+
+/// Blake2f circuit for precompile blake2f
+#[derive(Clone, Debug, Default)]
+pub struct Blake2fCircuit<F: Field>(Vec<Blake2F>, std::marker::PhantomData<F>);
+
+impl<F: Field> Blake2fCircuit<F> {
+    /// Number of rows used by the compressions.
+    fn rows_needed(events: &[Blake2F]) -> usize {
+        1 + events
+            .iter()
+            .map(|event| num_blocks(event.rounds) * BLOCK_ROWS)
+            .sum::<usize>()
+    }
+}
+
+impl<F: Field> SubCircuit<F> for Blake2fCircuit<F> {
+    type Config = Blake2fCircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // No column queried at more than 3 distinct rotations, so returns 6 as
+        // minimum unusable rows.
+        6
+    }
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        let block_limit = block.circuits_params.max_keccak_rows / BLOCK_ROWS;
+
+        let mut events = block.get_blake2f();
+        if block_limit != 0 {
+            let blocks: usize = events.iter().map(|event| num_blocks(event.rounds)).sum();
+            assert!(
+                blocks <= block_limit,
+                "no enough rows for blake2f circuit, expected {}, limit {}",
+                blocks,
+                block_limit,
+            );
+            // every padding compression of 0 round uses 1 block
+            events.resize(events.len() + block_limit - blocks, Default::default());
+            log::info!("blake2f circuit work with maxium {} blocks", block_limit);
+        }
+
+        Self(events, Default::default())
+    }
+
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let real_len = Self::rows_needed(&block.get_blake2f());
+        (
+            real_len,
+            real_len
+                .max(block.circuits_params.max_keccak_rows)
+                .max(4096),
+        )
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load_xor_table(layouter)?;
+
+        layouter.assign_region(
+            || "blake2f circuit",
+            |mut region| {
+                config.assign_zero_row(&mut region, 0)?;
+                region.assign_fixed(
+                    || "blake2f q_first",
+                    config.q_first,
+                    1,
+                    || Value::known(F::one()),
+                )?;
+                let mut offset = 1;
+                for event in self.0.iter() {
+                    offset = config.assign_compression(
+                        &mut region,
+                        offset,
+                        &compression_rows(event),
+                        challenges,
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,160 @@
+#![allow(unused_imports)]
+use super::*;
+
+use crate::util::MockChallenges;
+use halo2_proofs::{
+    circuit::SimpleFloorPlanner,
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+// EIP-152 test vector 5 without the number of rounds and the final block indicator flag
+const H: &str = "48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b";
+const M: &str = "6162630000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000";
+const T: &str = "03000000000000000000000000000000";
+
+fn construct_blake2f(rounds: u32, f: bool, output: &str) -> Blake2F {
+    let input = [
+        hex::encode(rounds.to_be_bytes()),
+        H.to_string(),
+        M.to_string(),
+        T.to_string(),
+        hex::encode([f as u8]),
+    ]
+    .concat();
+    Blake2F::new(&hex::decode(input).unwrap(), &hex::decode(output).unwrap())
+}
+
+fn compression_output(event: &Blake2F) -> [u64; 8] {
+    let rows = compression_rows(event);
+    let last_block = &rows[rows.len() - BLOCK_ROWS..];
+    array_init::array_init(|i| last_block[OUTPUT_START + i].out)
+}
+
+fn test_events() -> Vec<Blake2F> {
+    vec![
+        construct_blake2f(
+            12,
+            true,
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        ),
+        construct_blake2f(
+            0,
+            true,
+            "08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d282e6ad7f520e511f6c3e2b8c68059b9442be0454267ce079217e1319cde05b",
+        ),
+        construct_blake2f(
+            12,
+            false,
+            "75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d2875298743e7f6d5ea2f2d3e8d226039cd31b4e426ac4f2d3d666a610c2116fde4735",
+        ),
+    ]
+}
+
+#[test]
+fn test_blake2f_witness_output() {
+    for event in test_events() {
+        assert_eq!(compression_output(&event), event.output);
+    }
+}
+
+#[test]
+fn test_blake2f_circuit_00() {
+    let test_circuit = Blake2fCircuit::<Fr>(test_events(), Default::default());
+    let prover = MockProver::run(13, &test_circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+// test the padding compression (all zeros input)
+#[test]
+fn test_blake2f_circuit_01() {
+    let test_circuit = Blake2fCircuit::<Fr>(vec![Default::default()], Default::default());
+    let prover = MockProver::run(12, &test_circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+/// Blake2f circuit assigning a single compression whose rows are tampered.
+#[cfg(test)]
+#[derive(Clone)]
+struct TamperedBlake2fCircuit {
+    event: Blake2F,
+    tamper: fn(&mut [RowWitness]),
+}
+
+#[cfg(test)]
+impl Circuit<Fr> for TamperedBlake2fCircuit {
+    type Config = (Blake2fCircuitConfig<Fr>, MockChallenges);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        <Blake2fCircuit<Fr> as Circuit<Fr>>::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenge): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let challenges = challenge.values(&layouter);
+        config.load_xor_table(&mut layouter)?;
+
+        let mut rows = compression_rows(&self.event);
+        (self.tamper)(&mut rows);
+        layouter.assign_region(
+            || "blake2f circuit",
+            |mut region| {
+                config.assign_zero_row(&mut region, 0)?;
+                region.assign_fixed(
+                    || "blake2f q_first",
+                    config.q_first,
+                    1,
+                    || Value::known(Fr::one()),
+                )?;
+                config.assign_compression(&mut region, 1, &rows, &challenges)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+fn verify_tampered(tamper: fn(&mut [RowWitness])) -> Result<(), Vec<VerifyFailure>> {
+    let test_circuit = TamperedBlake2fCircuit {
+        event: test_events().remove(0),
+        tamper,
+    };
+    let prover = MockProver::run(13, &test_circuit, vec![]).unwrap();
+    prover.verify()
+}
+
+#[test]
+fn test_blake2f_circuit_untampered() {
+    assert_eq!(verify_tampered(|_| {}), Ok(()));
+}
+
+// a wrong digest in the output rows of the last block must fail
+#[test]
+fn test_blake2f_circuit_wrong_digest() {
+    assert!(verify_tampered(|rows| {
+        let last_block = rows.len() - BLOCK_ROWS;
+        rows[last_block + OUTPUT_START].out ^= 1;
+    })
+    .is_err());
+}
+
+// a wrong intermediate state in a round must fail
+#[test]
+fn test_blake2f_circuit_tampered_round() {
+    assert!(verify_tampered(|rows| {
+        let row = (OUTPUT_START / 2..OUTPUT_START)
+            .find(|row| rows[*row].is_active)
+            .unwrap();
+        rows[row].res ^= 1;
+    })
+    .is_err());
+}
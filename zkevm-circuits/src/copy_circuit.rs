@@ -62,6 +62,13 @@ const UNUSED_ROWS: usize = 2;
 // Rows to disable, so they do not query into Halo2 reserved rows.
 const DISABLED_ROWS: usize = 2;
 
+/// Minimum number of rows required by the copy events of the total length, as
+/// `min_num_rows_block` of the copy circuit.
+pub(crate) fn copy_min_num_rows(copy_events_len: usize, max_copy_rows: usize) -> (usize, usize) {
+    let row_num = copy_events_len * 2 + UNUSED_ROWS + DISABLED_ROWS;
+    (row_num, row_num.max(max_copy_rows))
+}
+
 /// The rw table shared between evm circuit and state circuit
 #[derive(Clone, Debug)]
 pub struct CopyCircuitConfig<F> {
@@ -95,6 +102,9 @@ pub struct CopyCircuitConfig<F> {
     pub is_bytecode: Column<Advice>,
     /// Booleans to indicate what copy data type exists at the current row.
     pub is_memory: Column<Advice>,
+    /// Whether the row belongs to a memory copy within a call, where all reads happen before
+    /// all writes. Such rows are also memory rows.
+    pub is_memory_copy: Column<Advice>,
     /// Booleans to indicate what copy data type exists at the current row.
     pub is_tx_log: Column<Advice>,
     /// Whether the row is enabled or not.
@@ -159,7 +169,8 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
         let value_word_rlc_prev = meta.advice_column_in(SecondPhase);
         let value_acc = meta.advice_column_in(SecondPhase);
 
-        let (is_tx_calldata, is_bytecode, is_memory, is_tx_log) = (
+        let (is_tx_calldata, is_bytecode, is_memory, is_memory_copy, is_tx_log) = (
+            meta.advice_column(),
             meta.advice_column(),
             meta.advice_column(),
             meta.advice_column(),
@@ -209,6 +220,7 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             is_tx_calldata,
             is_bytecode,
             is_memory,
+            is_memory_copy,
             is_tx_log,
         );
 
@@ -325,13 +337,16 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
 
             {
                 let is_rw_type = meta.query_advice(is_memory, CURRENT) + is_tx_log.expr();
+                let is_memory_copy = meta.query_advice(is_memory_copy, CURRENT);
 
                 constrain_rw_counter(
                     cb,
                     meta,
+                    is_first.expr(),
                     is_last.expr(),
                     is_last_step.expr(),
                     is_rw_type.expr(),
+                    is_memory_copy.expr(),
                     is_word_end.expr(),
                     rw_counter,
                     rwc_inc_left,
@@ -447,6 +462,7 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             is_tx_calldata,
             is_bytecode,
             is_memory,
+            is_memory_copy,
             is_tx_log,
             q_enable,
             is_src_end,
@@ -577,7 +593,18 @@ impl<F: Field> CopyCircuitConfig<F> {
                 || format!("is_memory at row: {}", *offset),
                 self.is_memory,
                 *offset,
-                || Value::known(F::from(tag.eq(&CopyDataType::Memory))),
+                || {
+                    Value::known(F::from(matches!(
+                        tag,
+                        CopyDataType::Memory | CopyDataType::MemoryCopy
+                    )))
+                },
+            )?;
+            region.assign_advice(
+                || format!("is_memory_copy at row: {}", *offset),
+                self.is_memory_copy,
+                *offset,
+                || Value::known(F::from(tag.eq(&CopyDataType::MemoryCopy))),
             )?;
             region.assign_advice(
                 || format!("is_tx_log at row: {}", *offset),
@@ -864,6 +891,7 @@ impl<F: Field> CopyCircuitConfig<F> {
             self.is_tx_calldata,
             self.is_bytecode,
             self.is_memory,
+            self.is_memory_copy,
             self.is_tx_log,
         ] {
             region.assign_advice(
@@ -972,14 +1000,14 @@ impl<F: Field> SubCircuit<F> for CopyCircuit<F> {
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let row_num = block
-            .copy_events
-            .iter()
-            .map(|c| c.full_length() as usize * 2)
-            .sum::<usize>()
-            + UNUSED_ROWS
-            + DISABLED_ROWS;
-        (row_num, row_num.max(block.circuits_params.max_copy_rows))
+        copy_min_num_rows(
+            block
+                .copy_events
+                .iter()
+                .map(|c| c.full_length() as usize)
+                .sum::<usize>(),
+            block.circuits_params.max_copy_rows,
+        )
     }
 
     /// Make the assignments to the CopyCircuit
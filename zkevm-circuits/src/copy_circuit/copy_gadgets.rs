@@ -18,6 +18,7 @@ pub fn constrain_tag<F: Field>(
     is_tx_calldata: Column<Advice>,
     is_bytecode: Column<Advice>,
     is_memory: Column<Advice>,
+    is_memory_copy: Column<Advice>,
     is_tx_log: Column<Advice>,
 ) {
     meta.create_gate("decode tag", |meta| {
@@ -25,14 +26,21 @@ pub fn constrain_tag<F: Field>(
         let is_tx_calldata = meta.query_advice(is_tx_calldata, CURRENT);
         let is_bytecode = meta.query_advice(is_bytecode, CURRENT);
         let is_memory = meta.query_advice(is_memory, CURRENT);
+        let is_memory_copy = meta.query_advice(is_memory_copy, CURRENT);
         let is_tx_log = meta.query_advice(is_tx_log, CURRENT);
+        let tag_is_memory_copy = tag.value_equals(CopyDataType::MemoryCopy, CURRENT)(meta);
         vec![
             // Match boolean indicators to their respective tag values.
             enabled.expr()
                 * (is_tx_calldata - tag.value_equals(CopyDataType::TxCalldata, CURRENT)(meta)),
             enabled.expr()
                 * (is_bytecode - tag.value_equals(CopyDataType::Bytecode, CURRENT)(meta)),
-            enabled.expr() * (is_memory - tag.value_equals(CopyDataType::Memory, CURRENT)(meta)),
+            // A MemoryCopy row is also a memory row, only the order of its RWs differs.
+            enabled.expr()
+                * (is_memory
+                    - tag.value_equals(CopyDataType::Memory, CURRENT)(meta)
+                    - tag_is_memory_copy.expr()),
+            enabled.expr() * (is_memory_copy - tag_is_memory_copy),
             enabled.expr() * (is_tx_log - tag.value_equals(CopyDataType::TxLog, CURRENT)(meta)),
         ]
     });
@@ -484,9 +492,11 @@ pub fn constrain_address<F: Field>(
 pub fn constrain_rw_counter<F: Field>(
     cb: &mut BaseConstraintBuilder<F>,
     meta: &mut VirtualCells<'_, F>,
+    is_first: Expression<F>,     // The first row.
     is_last: Expression<F>,      // The last row.
     is_last_step: Expression<F>, // Both the last reader and writer rows.
     is_rw_type: Expression<F>,
+    is_memory_copy: Expression<F>,
     is_word_end: Expression<F>,
     rw_counter: Column<Advice>,
     rwc_inc_left: Column<Advice>,
@@ -494,17 +504,46 @@ pub fn constrain_rw_counter<F: Field>(
     // Decrement rwc_inc_left for the next row, when an RW operation happens.
     let rwc_diff = is_rw_type.expr() * is_word_end.expr();
     let new_value = meta.query_advice(rwc_inc_left, CURRENT) - rwc_diff;
-    // At the end, it must reach 0.
-    let update_or_finish = select::expr(
-        not::expr(is_last.expr()),
-        meta.query_advice(rwc_inc_left, NEXT_ROW),
-        0.expr(),
-    );
-    cb.require_equal(
-        "rwc_inc_left[2] == rwc_inc_left[0] - rwc_diff, or 0 at the end",
-        new_value,
-        update_or_finish,
-    );
+
+    // Reads and writes are interleaved.
+    cb.condition(not::expr(is_memory_copy.expr()), |cb| {
+        // At the end, it must reach 0.
+        let update_or_finish = select::expr(
+            not::expr(is_last.expr()),
+            meta.query_advice(rwc_inc_left, NEXT_ROW),
+            0.expr(),
+        );
+        cb.require_equal(
+            "rwc_inc_left[2] == rwc_inc_left[0] - rwc_diff, or 0 at the end",
+            new_value.expr(),
+            update_or_finish,
+        );
+    });
+
+    // All the reads happen before all the writes. The reader and writer rows each count their
+    // own RWs, and the writer starts with half of the total, after the last read.
+    cb.condition(is_memory_copy.expr(), |cb| {
+        cb.condition(is_first.expr(), |cb| {
+            cb.require_equal(
+                "rwc_inc_left[0] == 2 * rwc_inc_left[1] at the start of a memory copy",
+                meta.query_advice(rwc_inc_left, CURRENT),
+                2.expr() * meta.query_advice(rwc_inc_left, NEXT_ROW),
+            );
+        });
+        cb.condition(not::expr(is_last_step.expr()), |cb| {
+            cb.require_equal(
+                "rwc_inc_left[2] == rwc_inc_left[0] - rwc_diff in a memory copy",
+                new_value.expr(),
+                meta.query_advice(rwc_inc_left, NEXT_STEP),
+            );
+        });
+        cb.condition(is_last.expr(), |cb| {
+            cb.require_zero(
+                "rwc_inc_left reaches 0 at the end of a memory copy",
+                new_value,
+            );
+        });
+    });
 
     // Maintain rw_counter based on rwc_inc_left. Their sum remains constant in all cases.
     cb.condition(not::expr(is_last.expr()), |cb| {
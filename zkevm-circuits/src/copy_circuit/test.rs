@@ -301,6 +301,31 @@ fn gen_return_data() -> CircuitInputBuilder {
     builder
 }
 
+#[cfg(feature = "cancun")]
+fn gen_mcopy_data() -> CircuitInputBuilder {
+    // The destination overlaps the end of the source.
+    let code = bytecode! {
+        PUSH32(Word::from_big_endian(&rand_bytes(32)))
+        PUSH1(0x00)
+        MSTORE
+        PUSH32(Word::from_big_endian(&rand_bytes(32)))
+        PUSH1(0x20)
+        MSTORE
+        PUSH1(0x40) // length
+        PUSH1(0x00) // offset
+        PUSH1(0x11) // dest_offset
+        MCOPY
+        STOP
+    };
+    let test_ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
+    let block: GethData = test_ctx.into();
+    let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    builder
+}
+
 #[test]
 fn copy_circuit_valid_calldatacopy() {
     let builder = gen_calldatacopy_data();
@@ -357,6 +382,14 @@ fn copy_circuit_valid_return() {
     assert_eq!(test_copy_circuit_from_block(block), Ok(()));
 }
 
+#[cfg(feature = "cancun")]
+#[test]
+fn copy_circuit_valid_mcopy() {
+    let builder = gen_mcopy_data();
+    let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+    assert_eq!(test_copy_circuit_from_block(block), Ok(()));
+}
+
 #[test]
 fn copy_circuit_invalid_calldatacopy() {
     let mut builder = gen_calldatacopy_data();
@@ -449,6 +482,27 @@ fn copy_circuit_invalid_tx_log() {
         .expect("there should be a lookup error");
 }
 
+#[cfg(feature = "cancun")]
+#[test]
+fn copy_circuit_invalid_mcopy() {
+    let mut builder = gen_mcopy_data();
+
+    // Interleave the reads and writes of the memory copy, as for a copy between calls.
+    for copy_event in builder.block.copy_events.iter_mut() {
+        copy_event.src_type = CopyDataType::Memory;
+        copy_event.dst_type = CopyDataType::Memory;
+    }
+
+    let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+    let result = test_copy_circuit_from_block(block);
+
+    let errors = result.expect_err("result is not an error");
+    errors
+        .iter()
+        .find(|err| matches!(err, VerifyFailure::Lookup { .. }))
+        .expect("there should be a lookup error");
+}
+
 // todo: add invalid create/return/returndatacopy tests
 
 #[test]
@@ -22,18 +22,25 @@ pub use crate::witness;
 use crate::{
     evm_circuit::param::{MAX_STEP_HEIGHT, STEP_STATE_HEIGHT},
     table::{
-        BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable, LookupTable,
-        ModExpTable, PowOfRandTable, RwTable, SHA256Table, SigTable, TxTable,
+        Blake2fTable, BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable,
+        LookupTable, ModExpTable, PowOfRandTable, Ripemd160Table, RwTable, SHA256Table, SigTable,
+        TxTable,
     },
     util::{SubCircuit, SubCircuitConfig},
 };
-use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use bus_mapping::{
+    evm::OpcodeId,
+    precompile::{modexp_input_limit, DEFAULT_MODEXP_SIZE_LIMIT, MAX_MODEXP_SIZE_LIMIT},
+};
+use eth_types::{
+    evm_types::{ForkSchedule, FORK_SCHEDULE},
+    Field,
+};
 use execution::ExecutionConfig;
 use itertools::Itertools;
 use strum::IntoEnumIterator;
 use table::FixedTableTag;
-use witness::Block;
+use witness::{Block, ExecStep};
 
 /// EvmCircuitConfig implements verification of execution trace of a block.
 #[derive(Clone, Debug)]
@@ -49,11 +56,15 @@ pub struct EvmCircuitConfig<F> {
     copy_table: CopyTable,
     keccak_table: KeccakTable,
     sha256_table: SHA256Table,
+    ripemd160_table: Ripemd160Table,
+    blake2f_table: Blake2fTable,
     exp_table: ExpTable,
     sig_table: SigTable,
     modexp_table: ModExpTable,
     ecc_table: EccTable,
     pow_of_rand_table: PowOfRandTable,
+    fork_schedule: ForkSchedule,
+    modexp_size_limit: usize,
 }
 
 /// Circuit configuration arguments
@@ -74,6 +85,10 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub keccak_table: KeccakTable,
     /// SHA256Table
     pub sha256_table: SHA256Table,
+    /// Ripemd160Table
+    pub ripemd160_table: Ripemd160Table,
+    /// Blake2fTable
+    pub blake2f_table: Blake2fTable,
     /// ExpTable
     pub exp_table: ExpTable,
     /// SigTable
@@ -84,6 +99,11 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub ecc_table: EccTable,
     // Power of Randomness Table.
     pub pow_of_rand_table: PowOfRandTable,
+    /// Hard fork schedule of the blocks to prove
+    pub fork_schedule: ForkSchedule,
+    /// Size limit of modexp operands, i.e. `CircuitsParams::max_modexp_size` of the blocks to
+    /// prove
+    pub modexp_size_limit: usize,
 }
 
 /// Circuit exported cells after synthesis, used for subcircuit
@@ -109,13 +129,21 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             sha256_table,
+            ripemd160_table,
+            blake2f_table,
             exp_table,
             sig_table,
             modexp_table,
             ecc_table,
             pow_of_rand_table,
+            fork_schedule,
+            modexp_size_limit,
         }: Self::ConfigArgs,
     ) -> Self {
+        assert!(
+            modexp_size_limit <= MAX_MODEXP_SIZE_LIMIT,
+            "modexp size limit {modexp_size_limit} exceeds the maximum {MAX_MODEXP_SIZE_LIMIT}"
+        );
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
         let byte_table = [(); 1].map(|_| meta.fixed_column());
         let execution = Box::new(ExecutionConfig::configure(
@@ -130,11 +158,15 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             &copy_table,
             &keccak_table,
             &sha256_table,
+            &ripemd160_table,
+            &blake2f_table,
             &exp_table,
             &sig_table,
             &modexp_table,
             &ecc_table,
             &pow_of_rand_table,
+            &fork_schedule,
+            modexp_size_limit,
         ));
 
         meta.annotate_lookup_any_column(byte_table[0], || "byte_range");
@@ -147,6 +179,8 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
         block_table.annotate_columns(meta);
         copy_table.annotate_columns(meta);
         keccak_table.annotate_columns(meta);
+        ripemd160_table.annotate_columns(meta);
+        blake2f_table.annotate_columns(meta);
         exp_table.annotate_columns(meta);
         sig_table.annotate_columns(meta);
         modexp_table.annotate_columns(meta);
@@ -164,11 +198,15 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             sha256_table,
+            ripemd160_table,
+            blake2f_table,
             exp_table,
             sig_table,
             modexp_table,
             ecc_table,
             pow_of_rand_table,
+            fork_schedule,
+            modexp_size_limit,
         }
     }
 }
@@ -184,7 +222,11 @@ impl<F: Field> EvmCircuitConfig<F> {
             || "fixed table",
             |mut region| {
                 for (offset, row) in std::iter::once([F::zero(); 4])
-                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build()))
+                    .chain(
+                        fixed_table_tags
+                            .iter()
+                            .flat_map(|tag| tag.build(&self.fork_schedule)),
+                    )
                     .enumerate()
                 {
                     for (column, value) in self.fixed_table.iter().zip_eq(row) {
@@ -255,15 +297,7 @@ impl<F: Field> EvmCircuit<F> {
     }
 
     pub fn get_num_rows_required_no_padding(block: &Block<F>) -> usize {
-        // Start at 1 so we can be sure there is an unused `next` row available
-        let mut num_rows = 1;
-        for transaction in &block.txs {
-            for step in &transaction.steps {
-                num_rows += step.execution_state.get_step_height();
-            }
-        }
-        num_rows += 1; // EndBlock
-        num_rows
+        num_rows_required_no_padding(steps_height(block))
     }
 
     pub fn get_num_rows_required(block: &Block<F>) -> usize {
@@ -307,20 +341,11 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let num_rows_required_for_execution_steps: usize =
-            Self::get_num_rows_required_no_padding(block);
-        let mut total_rows = num_rows_required_for_execution_steps;
-        total_rows = total_rows.max(block.circuits_params.max_evm_rows);
-
-        if total_rows <= FIXED_TABLE_ROWS {
-            // for many test cases, there is no need for bitwise table.
-            // So using `detect_fixed_table_tags` can greatly improve CI time.
-            let num_rows_required_for_fixed_table =
-                get_fixed_table_row_num(need_bitwise_lookup(block));
-            total_rows = total_rows.max(num_rows_required_for_fixed_table)
-        }
-
-        (num_rows_required_for_execution_steps, total_rows)
+        evm_min_num_rows(
+            steps_height(block),
+            need_bitwise_lookup(block),
+            block.circuits_params.max_evm_rows,
+        )
     }
 
     /// Make the assignments to the EvmCircuit
@@ -331,16 +356,71 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         let block = self.block.as_ref().unwrap();
+        if block.fork_schedule != config.fork_schedule {
+            log::error!(
+                "fork schedule of the block {:?} differs from the one of the circuit {:?}",
+                block.fork_schedule,
+                config.fork_schedule
+            );
+            return Err(Error::Synthesis);
+        }
+        if block.circuits_params.max_modexp_size != config.modexp_size_limit {
+            log::error!(
+                "modexp size limit of the block {} differs from the one of the circuit {}",
+                block.circuits_params.max_modexp_size,
+                config.modexp_size_limit
+            );
+            return Err(Error::Synthesis);
+        }
 
         config.load_fixed_table(layouter, self.fixed_table_tags.clone())?;
         config.load_byte_table(layouter)?;
-        config.pow_of_rand_table.assign(layouter, challenges)?;
+        config.pow_of_rand_table.assign(
+            layouter,
+            challenges,
+            modexp_input_limit(config.modexp_size_limit),
+        )?;
         let export = config.execution.assign_block(layouter, block, challenges)?;
         self.exports.borrow_mut().replace(export);
         Ok(())
     }
 }
 
+/// Minimum number of rows required by the execution steps of the total height, and the fixed table
+/// with or without the bitwise lookups, as `min_num_rows_block` of the EVM circuit.
+pub(crate) fn evm_min_num_rows(
+    steps_height: usize,
+    need_bitwise_lookup: bool,
+    max_evm_rows: usize,
+) -> (usize, usize) {
+    let num_rows_required_for_execution_steps = num_rows_required_no_padding(steps_height);
+    let mut total_rows = num_rows_required_for_execution_steps;
+    total_rows = total_rows.max(max_evm_rows);
+
+    if total_rows <= FIXED_TABLE_ROWS {
+        // for many test cases, there is no need for bitwise table.
+        // So using `detect_fixed_table_tags` can greatly improve CI time.
+        let num_rows_required_for_fixed_table = get_fixed_table_row_num(need_bitwise_lookup);
+        total_rows = total_rows.max(num_rows_required_for_fixed_table)
+    }
+
+    (num_rows_required_for_execution_steps, total_rows)
+}
+
+fn num_rows_required_no_padding(steps_height: usize) -> usize {
+    // Start at 1 so we can be sure there is an unused `next` row available, and 1 for EndBlock
+    steps_height + 2
+}
+
+fn steps_height<F: Field>(block: &Block<F>) -> usize {
+    block
+        .txs
+        .iter()
+        .flat_map(|tx| &tx.steps)
+        .map(|step| step.execution_state.get_step_height())
+        .sum()
+}
+
 fn get_fixed_table_row_num(need_bitwise_lookup: bool) -> usize {
     if need_bitwise_lookup {
         FIXED_TABLE_ROWS
@@ -350,17 +430,18 @@ fn get_fixed_table_row_num(need_bitwise_lookup: bool) -> usize {
 }
 
 fn need_bitwise_lookup<F: Field>(block: &Block<F>) -> bool {
-    block.txs.iter().any(|tx| {
-        tx.steps.iter().any(|step| {
-            matches!(
-                step.opcode,
-                Some(OpcodeId::AND)
-                    | Some(OpcodeId::OR)
-                    | Some(OpcodeId::XOR)
-                    | Some(OpcodeId::NOT)
-            )
-        })
-    })
+    block
+        .txs
+        .iter()
+        .any(|tx| tx.steps.iter().any(is_bitwise_step))
+}
+
+/// Return true if the step needs the bitwise lookups of the fixed table.
+pub(crate) fn is_bitwise_step(step: &ExecStep) -> bool {
+    matches!(
+        step.opcode,
+        Some(OpcodeId::AND) | Some(OpcodeId::OR) | Some(OpcodeId::XOR) | Some(OpcodeId::NOT)
+    )
 }
 /// create fixed_table_tags needed given witness block
 pub(crate) fn detect_fixed_table_tags<F: Field>(block: &Block<F>) -> Vec<FixedTableTag> {
@@ -463,6 +544,8 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
         let copy_table = CopyTable::construct(meta, q_copy_table);
         let keccak_table = KeccakTable::construct(meta);
         let sha256_table = SHA256Table::construct(meta);
+        let ripemd160_table = Ripemd160Table::construct(meta);
+        let blake2f_table = Blake2fTable::construct(meta);
         let exp_table = ExpTable::construct(meta);
         let sig_table = SigTable::construct(meta);
         let modexp_table = ModExpTable::construct(meta);
@@ -480,11 +563,15 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                     copy_table,
                     keccak_table,
                     sha256_table,
+                    ripemd160_table,
+                    blake2f_table,
                     exp_table,
                     sig_table,
                     modexp_table,
                     ecc_table,
                     pow_of_rand_table,
+                    fork_schedule: FORK_SCHEDULE.clone(),
+                    modexp_size_limit: DEFAULT_MODEXP_SIZE_LIMIT,
                 },
             ),
             challenges,
@@ -536,13 +623,24 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                 .map(|evt| (&evt.input, &evt.digest)),
             &challenges,
         )?;
+        config.ripemd160_table.dev_load(
+            &mut layouter,
+            block
+                .get_ripemd160()
+                .iter()
+                .map(|evt| (&evt.input, &evt.digest)),
+            &challenges,
+        )?;
+        config
+            .blake2f_table
+            .dev_load(&mut layouter, &block.get_blake2f(), &challenges)?;
         config.exp_table.dev_load(&mut layouter, block)?;
         config
             .sig_table
             .dev_load(&mut layouter, block, &challenges)?;
         config
             .modexp_table
-            .dev_load(&mut layouter, &block.get_big_modexp())?;
+            .dev_load(&mut layouter, &block.get_big_modexp(), &challenges)?;
         config.ecc_table.dev_load(
             &mut layouter,
             block.circuits_params.max_ec_ops,
@@ -575,7 +673,12 @@ mod evm_circuit_stats {
     };
     use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
     use cli_table::{print_stdout, Cell, Style, Table};
-    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord};
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, FORK_SCHEDULE},
+        geth_types::GethData,
+        ToWord,
+    };
     use halo2_proofs::{
         dev::MockProver,
         halo2curves::bn256::Fr,
@@ -596,7 +699,7 @@ mod evm_circuit_stats {
         let row_num_by_tags = |tags: Vec<FixedTableTag>| -> usize {
             tags.iter()
                 .map(|tag| {
-                    let count = tag.build::<Fr>().count();
+                    let count = tag.build::<Fr>(&FORK_SCHEDULE).count();
                     log::debug!("fixed tab {tag:?} needs {count} rows");
                     count
                 })
@@ -6,8 +6,8 @@ use crate::{
         util::{
             and,
             common_gadget::{
-                TransferGadgetInfo, TransferWithGasFeeGadget, TxEip2930Gadget, TxL1FeeGadget,
-                TxL1MsgGadget,
+                IsForkActiveGadget, TransferGadgetInfo, TransferWithGasFeeGadget, TxEip2930Gadget,
+                TxEip4844Gadget, TxL1FeeGadget, TxL1MsgGadget,
             },
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
@@ -27,19 +27,13 @@ use crate::{
         TxFieldTag as TxContextFieldTag,
     },
 };
-use bus_mapping::circuit_input_builder::CopyDataType;
-use eth_types::{Address, Field, ToLittleEndian, ToScalar, U256};
+use bus_mapping::{circuit_input_builder::CopyDataType, precompile::LAST_PRECOMPILE_ADDRESS};
+use eth_types::{evm_types::Fork, Address, Field, ToLittleEndian, ToScalar, U256};
 use ethers_core::utils::{get_contract_address, keccak256, rlp::RlpStream};
 use gadgets::util::{expr_from_bytes, not, select, Expr};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
-// For Shanghai, EIP-3651 (Warm COINBASE) adds 1 write op for coinbase.
-#[cfg(feature = "shanghai")]
-const SHANGHAI_RW_DELTA: u8 = 1;
-#[cfg(not(feature = "shanghai"))]
-const SHANGHAI_RW_DELTA: u8 = 0;
-
-const PRECOMPILE_COUNT: usize = 9;
+const PRECOMPILE_COUNT: usize = LAST_PRECOMPILE_ADDRESS as usize;
 
 #[derive(Clone, Debug)]
 pub(crate) struct BeginTxGadget<F> {
@@ -84,6 +78,7 @@ pub(crate) struct BeginTxGadget<F> {
     /// RLP gadget for CREATE address.
     create: ContractCreateGadget<F, false>,
     is_caller_callee_equal: Cell<F>,
+    is_shanghai: IsForkActiveGadget<F>,
     // EIP-3651 (Warm COINBASE) for Shanghai
     coinbase: Cell<F>,
     // Caller, callee and a list addresses are added to the access list before
@@ -93,6 +88,7 @@ pub(crate) struct BeginTxGadget<F> {
     tx_l1_fee: TxL1FeeGadget<F>,
     tx_l1_msg: TxL1MsgGadget<F>,
     tx_eip2930: TxEip2930Gadget<F>,
+    tx_eip4844: TxEip4844Gadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
@@ -183,6 +179,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         });
         let [tx_gas_price, tx_value] = [TxContextFieldTag::GasPrice, TxContextFieldTag::Value]
             .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
+        let tx_eip4844 =
+            TxEip4844Gadget::construct(cb, tx_id.expr(), tx_type.expr(), &tx_gas_price);
 
         let call_callee_address = cb.query_cell();
         cb.condition(not::expr(tx_is_create.expr()), |cb| {
@@ -225,8 +223,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             from_bytes::expr(&tx_fee.cells[..16]),
         );
 
-        // a valid precompile address is: 1 <= addr <= 9 (addr != 0 && addr < 0xA)
-        let is_precompile_lt = LtGadget::construct(cb, tx_callee_address.expr(), 0xA.expr());
+        // a valid precompile address is: 1 <= addr <= PRECOMPILE_COUNT
+        // (addr != 0 && addr < PRECOMPILE_COUNT + 1)
+        let is_precompile_lt =
+            LtGadget::construct(cb, tx_callee_address.expr(), (PRECOMPILE_COUNT + 1).expr());
         let is_precompile = and::expr([
             not::expr(tx_callee_address_is_zero.expr()),
             is_precompile_lt.expr(),
@@ -239,18 +239,18 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         // Use intrinsic gas
         // TODO2: contrain calling precompile directly
 
+        let is_shanghai = IsForkActiveGadget::construct(cb, Fork::Shanghai);
+
         let intrinsic_gas_cost = cb.query_cell();
         cb.condition(not::expr(is_precompile.expr()), |cb| {
             // Calculate gas cost of init code only for EIP-3860 of Shanghai.
-            #[cfg(feature = "shanghai")]
-            let init_code_gas_cost = select::expr(
-                tx_is_create.expr(),
-                tx_call_data_word_length.quotient().expr()
-                    * eth_types::evm_types::INIT_CODE_WORD_GAS.expr(),
-                0.expr(),
-            );
-            #[cfg(not(feature = "shanghai"))]
-            let init_code_gas_cost = 0.expr();
+            let init_code_gas_cost = is_shanghai.expr()
+                * select::expr(
+                    tx_is_create.expr(),
+                    tx_call_data_word_length.quotient().expr()
+                        * eth_types::evm_types::INIT_CODE_WORD_GAS.expr(),
+                    0.expr(),
+                );
 
             cb.require_equal(
                 "calculate intrinsic gas cost",
@@ -304,14 +304,15 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             coinbase.expr(),
         );
 
-        #[cfg(feature = "shanghai")]
-        cb.account_access_list_write(
-            tx_id.expr(),
-            coinbase.expr(),
-            1.expr(),
-            is_coinbase_warm.expr(),
-            None,
-        ); // rwc_delta += 1
+        cb.condition(is_shanghai.expr(), |cb| {
+            cb.account_access_list_write(
+                tx_id.expr(),
+                coinbase.expr(),
+                1.expr(),
+                is_coinbase_warm.expr(),
+                None,
+            );
+        }); // rwc_delta += is_shanghai
 
         let account_code_hash = cb.query_cell_phase2();
         let account_code_hash_is_empty =
@@ -426,6 +427,13 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 0.expr(),
                 Some(&mut reversion_info),
             );
+            // EIP 6780, mark the contract as created in this tx for SELFDESTRUCT
+            cb.account_created_write(
+                tx_id.expr(),
+                call_callee_address.expr(),
+                0.expr(),
+                Some(&mut reversion_info),
+            );
             for (field_tag, value) in [
                 (CallContextFieldTag::Depth, 1.expr()),
                 (CallContextFieldTag::CallerAddress, tx_caller_address.expr()),
@@ -451,7 +459,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             }
 
             cb.require_step_state_transition(StepStateTransition {
-                // 21 + a reads and writes:
+                // 22 + a reads and writes:
                 //   - a TxL1FeeGadget
                 //   - Write CallContext TxId
                 //   - Write CallContext RwCounterEndOfReversion
@@ -465,6 +473,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Read Account CodeHash
                 //   - a TransferWithGasFeeGadget
                 //   - Write Account (Callee) Nonce (Reversible)
+                //   - Write TransientStorage (Callee) AccountCreated (Reversible)
                 //   - Write CallContext Depth
                 //   - Write CallContext CallerAddress
                 //   - Write CallContext CalleeAddress
@@ -479,10 +488,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write CallContext IsCreate
                 //   - Write CallContext CodeHash
                 rw_counter: Delta(
-                    22.expr()
+                    23.expr()
                         + l1_rw_delta.expr()
                         + transfer_with_gas_fee.rw_delta()
-                        + SHANGHAI_RW_DELTA.expr()
+                        + is_shanghai.expr()
                         + PRECOMPILE_COUNT.expr(),
                 ),
                 call_id: To(call_id.expr()),
@@ -490,10 +499,11 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 is_create: To(tx_is_create.expr()),
                 code_hash: To(cb.curr.state.code_hash.expr()),
                 gas_left: To(gas_left.clone()),
-                // There are a + 1 reversible writes:
+                // There are a + 2 reversible writes:
                 //  - a TransferWithGasFeeGadget
                 //  - Callee Account Nonce
-                reversible_write_counter: To(transfer_with_gas_fee.reversible_w_delta() + 1.expr()),
+                //  - Callee TransientStorage AccountCreated
+                reversible_write_counter: To(transfer_with_gas_fee.reversible_w_delta() + 2.expr()),
                 log_id: To(0.expr()),
                 ..StepStateTransition::new_context()
             });
@@ -533,7 +543,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     8.expr()
                         + l1_rw_delta.expr()
                         + transfer_with_gas_fee.rw_delta()
-                        + SHANGHAI_RW_DELTA.expr()
+                        + is_shanghai.expr()
                         + PRECOMPILE_COUNT.expr()
                         // TRICKY:
                         // Process the reversion only for Precompile in begin TX. Since no
@@ -588,7 +598,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                         8.expr()
                             + l1_rw_delta.expr()
                             + transfer_with_gas_fee.rw_delta()
-                            + SHANGHAI_RW_DELTA.expr()
+                            + is_shanghai.expr()
                             + PRECOMPILE_COUNT.expr(),
                     ),
                     call_id: To(call_id.expr()),
@@ -661,7 +671,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                         21.expr()
                             + l1_rw_delta.expr()
                             + transfer_with_gas_fee.rw_delta()
-                            + SHANGHAI_RW_DELTA.expr()
+                            + is_shanghai.expr()
                             + PRECOMPILE_COUNT.expr(),
                     ),
                     call_id: To(call_id.expr()),
@@ -715,11 +725,13 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             keccak_code_hash,
             create,
             is_caller_callee_equal,
+            is_shanghai,
             coinbase,
             is_coinbase_warm,
             tx_l1_fee,
             tx_l1_msg,
             tx_eip2930,
+            tx_eip4844,
         }
     }
 
@@ -807,10 +819,14 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
 
         rws.offset_add(PRECOMPILE_COUNT + 2);
 
-        #[cfg(feature = "shanghai")]
-        let is_coinbase_warm = rws.next().tx_access_list_value_pair().1;
-        #[cfg(not(feature = "shanghai"))]
-        let is_coinbase_warm = false;
+        let is_shanghai = self
+            .is_shanghai
+            .assign(region, offset, block, tx.block_number)?;
+        let is_coinbase_warm = if is_shanghai {
+            rws.next().tx_access_list_value_pair().1
+        } else {
+            false
+        };
 
         let account_code_hash = rws.next().account_codehash_pair().1;
         let transfer_assign_result = self.transfer_with_gas_fee.assign_from_rws(
@@ -891,8 +907,12 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             .assign(region, offset, Value::known(callee_address))?;
         self.tx_callee_address_is_zero
             .assign(region, offset, callee_address)?;
-        self.is_precompile_lt
-            .assign(region, offset, callee_address, F::from(0xA))?;
+        self.is_precompile_lt.assign(
+            region,
+            offset,
+            callee_address,
+            F::from(PRECOMPILE_COUNT as u64 + 1),
+        )?;
         self.call_callee_address.assign(
             region,
             offset,
@@ -1057,7 +1077,13 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx.tx_data_gas_cost,
         )?;
 
-        self.tx_eip2930.assign(region, offset, tx)
+        self.tx_eip2930.assign(region, offset, tx)?;
+        self.tx_eip4844.assign(
+            region,
+            offset,
+            tx,
+            block.context.ctxs[&tx.block_number].base_fee,
+        )
     }
 }
 
@@ -0,0 +1,113 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{EVMConstraintBuilder, StepStateTransition, Transition::Delta},
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::BlockContextFieldTag,
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::Field;
+use halo2_proofs::plonk::Error;
+
+#[derive(Clone, Debug)]
+pub(crate) struct BlobBaseFeeGadget<F> {
+    same_context: SameContextGadget<F>,
+    blob_base_fee: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BlobBaseFeeGadget<F> {
+    const NAME: &'static str = "BLOBBASEFEE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BLOBBASEFEE;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let blob_base_fee = cb.query_cell_phase2();
+        cb.block_lookup(
+            BlockContextFieldTag::BlobBaseFee.expr(),
+            cb.curr.state.block_number.expr(),
+            blob_base_fee.expr(),
+        );
+
+        cb.stack_push(blob_base_fee.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(1.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            gas_left: Delta(-OpcodeId::BLOBBASEFEE.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+
+        let opcode = cb.query_cell();
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            blob_base_fee,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let blob_base_fee = block.rws[step.rw_indices[0]].stack_value();
+        self.blob_base_fee
+            .assign(region, offset, region.word_rlc(blob_base_fee))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::bytecode;
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn test_ok(excess_blob_gas: Option<u64>) {
+        let bytecode = bytecode! {
+            BLOBBASEFEE
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            tx_from_1_to_0,
+            |block, _txs| {
+                if let Some(excess_blob_gas) = excess_blob_gas {
+                    block.excess_blob_gas(excess_blob_gas);
+                }
+                block
+            },
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn blobbasefee_gadget_without_excess_blob_gas() {
+        test_ok(None);
+    }
+
+    #[test]
+    fn blobbasefee_gadget_with_excess_blob_gas() {
+        test_ok(Some(10485760));
+    }
+}
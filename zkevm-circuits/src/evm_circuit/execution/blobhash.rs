@@ -0,0 +1,160 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        util::{
+            common_gadget::{SameContextGadget, WordByteCapGadget},
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
+                Transition::Delta,
+            },
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{CallContextFieldTag, TxContextFieldTag},
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{evm_types::block_utils::MAX_BLOBS_PER_TX, Field};
+use gadgets::util::not;
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct BlobHashGadget<F> {
+    same_context: SameContextGadget<F>,
+    tx_id: Cell<F>,
+    /// The index of the blob versioned hash, any index out of
+    /// [0, MAX_BLOBS_PER_TX) results in a zero hash.
+    index: WordByteCapGadget<F, N_BYTES_U64>,
+    blob_hash: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BlobHashGadget<F> {
+    const NAME: &'static str = "BLOBHASH";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BLOBHASH;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+
+        let index = WordByteCapGadget::construct(cb, MAX_BLOBS_PER_TX.expr());
+        cb.stack_pop(index.original_word());
+
+        // The tx table always has MAX_BLOBS_PER_TX blob hash rows for each tx, the
+        // ones after the last blob of the tx are zeros.
+        let blob_hash = cb.query_cell_phase2();
+        cb.condition(index.lt_cap(), |cb| {
+            cb.tx_context_lookup(
+                tx_id.expr(),
+                TxContextFieldTag::BlobVersionedHash,
+                Some(index.valid_value()),
+                blob_hash.expr(),
+            );
+        });
+        cb.condition(not::expr(index.lt_cap()), |cb| {
+            cb.require_zero("blob hash == 0 if index is out of range", blob_hash.expr());
+        });
+
+        cb.stack_push(blob_hash.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(3.expr()),
+            program_counter: Delta(1.expr()),
+            gas_left: Delta(-OpcodeId::BLOBHASH.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+
+        let opcode = cb.query_cell();
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            tx_id,
+            index,
+            blob_hash,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+
+        let [index, blob_hash] = [1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        self.index
+            .assign(region, offset, index, F::from(MAX_BLOBS_PER_TX as u64))?;
+        self.blob_hash
+            .assign(region, offset, region.word_rlc(blob_hash))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{bytecode, geth_types::TxType, Word, H256};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn test_ok(index: Word, num_blobs: u8) {
+        let bytecode = bytecode! {
+            PUSH32(index)
+            BLOBHASH
+            STOP
+        };
+        let blob_hashes = (0..num_blobs)
+            .map(|i| {
+                let mut hash = [i + 1; 32];
+                hash[0] = 0x01;
+                H256(hash)
+            })
+            .collect::<Vec<_>>();
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .transaction_type(TxType::Eip4844 as u64)
+                    .blob_versioned_hashes(blob_hashes);
+            },
+            |block, _txs| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn blobhash_gadget_in_range() {
+        test_ok(Word::zero(), 2);
+        test_ok(Word::one(), 2);
+        test_ok(Word::from(5), 6);
+    }
+
+    #[test]
+    fn blobhash_gadget_no_blob_at_index() {
+        test_ok(Word::from(2), 2);
+        test_ok(Word::from(5), 1);
+    }
+
+    #[test]
+    fn blobhash_gadget_out_of_range() {
+        test_ok(Word::from(6), 6);
+        test_ok(Word::from(u64::MAX), 1);
+        test_ok(Word::MAX, 1);
+    }
+}
@@ -26,7 +26,7 @@ use crate::{
 use bus_mapping::{
     circuit_input_builder::CopyDataType,
     evm::OpcodeId,
-    precompile::{is_precompiled, PrecompileCalls},
+    precompile::{is_precompiled, ModExpAuxData, PrecompileCalls, LAST_PRECOMPILE_ADDRESS},
 };
 use eth_types::{
     evm_types::{memory::MemoryWordRange, GAS_STIPEND_CALL_WITH_VALUE},
@@ -214,10 +214,13 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         });
 
         // whether the call is to a precompiled contract.
-        // precompile contracts are stored from address 0x01 to 0x09.
+        // precompile contracts are stored from address 0x01 to LAST_PRECOMPILE_ADDRESS.
         let is_code_address_zero = IsZeroGadget::construct(cb, call_gadget.callee_address_expr());
-        let is_precompile_lt =
-            LtGadget::construct(cb, call_gadget.callee_address_expr(), 0x0A.expr());
+        let is_precompile_lt = LtGadget::construct(
+            cb,
+            call_gadget.callee_address_expr(),
+            (LAST_PRECOMPILE_ADDRESS + 1).expr(),
+        );
         let is_precompile = and::expr([
             not::expr(is_code_address_zero.expr()),
             is_precompile_lt.expr(),
@@ -947,8 +950,12 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         let code_address: F = callee_address.to_address().to_scalar().unwrap();
         self.is_code_address_zero
             .assign(region, offset, code_address)?;
-        self.is_precompile_lt
-            .assign(region, offset, code_address, 0x0Au64.into())?;
+        self.is_precompile_lt.assign(
+            region,
+            offset,
+            code_address,
+            (LAST_PRECOMPILE_ADDRESS as u64 + 1).into(),
+        )?;
         log::trace!("callop is precompile call {}", is_precompile_call);
         let precompile_return_length = if is_precompile_call && is_precheck_ok {
             rws.offset_add(14); // skip
@@ -989,7 +996,28 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             return_rws,
         ) = if is_precheck_ok && is_precompiled(&callee_address.to_address()) {
             let precompile_call: PrecompileCalls = precompile_addr.0[19].into();
-            let input_len = if let Some(input_len) = precompile_call.input_len() {
+            let input_len = if precompile_call == PrecompileCalls::Modexp {
+                // peek the length header of modexp from the input memory words.
+                let header_len = min(96, cd_length.as_usize());
+                let header = if header_len == 0 {
+                    vec![]
+                } else {
+                    let begin = cd_offset.as_usize();
+                    let range = MemoryWordRange::align_range(begin, header_len);
+                    let mut header_rws = rws.clone();
+                    (0..range.word_count())
+                        .map(|_| header_rws.next().memory_word_pair().0)
+                        .flat_map(|word| word.to_be_bytes())
+                        .skip(begin - range.start_slot().0)
+                        .take(header_len)
+                        .collect::<Vec<_>>()
+                };
+                ModExpAuxData::input_len(
+                    &header,
+                    cd_length.as_usize(),
+                    block.circuits_params.max_modexp_size,
+                )
+            } else if let Some(input_len) = precompile_call.input_len() {
                 min(input_len, cd_length.as_usize())
             } else {
                 cd_length.as_usize()
@@ -1830,9 +1858,6 @@ mod test_precompiles {
             address: Word::from(0x3),
             stack_value: vec![(
                 Word::from(0x20),
-                #[cfg(feature = "scroll")]
-                Word::zero(),
-                #[cfg(not(feature = "scroll"))]
                 word!("2c0c45d3ecab80fe060e5f1d7057cd2f8de5e557"),
             )],
             ..Default::default()
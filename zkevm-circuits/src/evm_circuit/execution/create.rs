@@ -7,7 +7,7 @@ use crate::{
         },
         step::ExecutionState,
         util::{
-            common_gadget::{get_copy_bytes, TransferGadget},
+            common_gadget::{get_copy_bytes, IsForkActiveGadget, TransferGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
@@ -18,7 +18,7 @@ use crate::{
             memory_gadget::{
                 CommonMemoryAddressGadget, MemoryAddressGadget, MemoryExpansionGadget,
             },
-            not, CachedRegion, Cell, StepRws, Word,
+            not, select, CachedRegion, Cell, StepRws, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -27,7 +27,10 @@ use crate::{
 };
 use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId, state_db::CodeDB};
 use eth_types::{
-    evm_types::{GasCost, CREATE2_GAS_PER_CODE_WORD, CREATE_GAS_PER_CODE_WORD, MAX_INIT_CODE_SIZE},
+    evm_types::{
+        create2_gas_per_code_word, create_gas_per_code_word, max_init_code_size, Fork, GasCost,
+        INIT_CODE_WORD_GAS, MAX_CODE_SIZE, MAX_EXPANDED_MEMORY_ADDRESS,
+    },
     Field, ToBigEndian, ToLittleEndian, ToScalar, ToWord, H256, KECCAK_CODE_HASH_EMPTY, U256,
 };
 use ethers_core::utils::keccak256;
@@ -51,12 +54,16 @@ pub(crate) struct CreateGadget<F, const IS_CREATE2: bool, const S: ExecutionStat
     caller_balance: Word<F>,
     callee_reversion_info: ReversionInfo<F>,
     callee_nonce: Cell<F>,
+    // Whether the callee is already marked as created in this tx (EIP-6780)
+    callee_is_created_prev: Cell<F>,
     prev_code_hash: Cell<F>,
     prev_code_hash_is_zero: IsZeroGadget<F>,
     transfer: TransferGadget<F>,
     create: ContractCreateGadget<F, IS_CREATE2>,
 
     init_code: MemoryAddressGadget<F>,
+    // EIP-3860 limits and meters the init code since Shanghai.
+    is_shanghai: IsForkActiveGadget<F>,
     init_code_word_size: ConstantDivisionGadget<F, N_BYTES_MEMORY_ADDRESS>,
     // Init code size must be less than or equal to 49152
     // (maximum init code size) if Shanghai, otherwise should be less than or
@@ -138,8 +145,14 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
 
         let init_code =
             MemoryAddressGadget::construct(cb, init_code_memory_offset, init_code_length);
+        let is_shanghai = IsForkActiveGadget::construct(cb, Fork::Shanghai);
+        let max_init_code_size = select::expr(
+            is_shanghai.expr(),
+            (2 * MAX_CODE_SIZE).expr(),
+            MAX_EXPANDED_MEMORY_ADDRESS.expr(),
+        );
         let init_code_size_not_overflow =
-            LtGadget::construct(cb, init_code.length(), MAX_INIT_CODE_SIZE.expr() + 1.expr());
+            LtGadget::construct(cb, init_code.length(), max_init_code_size + 1.expr());
 
         // Init code size overflow is checked before ErrDepth, ErrInsufficientBalance,
         // ErrNonceUintOverflow and ErrContractAddressCollision.
@@ -238,12 +251,12 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             N_BYTES_WORD as u64,
         );
         let keccak_gas_cost = init_code_word_size.quotient()
-            * if IS_CREATE2 {
-                CREATE2_GAS_PER_CODE_WORD
-            } else {
-                CREATE_GAS_PER_CODE_WORD
-            }
-            .expr();
+            * (is_shanghai.expr() * INIT_CODE_WORD_GAS.expr()
+                + if IS_CREATE2 {
+                    GasCost::COPY_SHA3.expr()
+                } else {
+                    0.expr()
+                });
 
         let gas_cost = GasCost::CREATE.expr() + memory_expansion.gas_cost() + keccak_gas_cost;
         let gas_remaining = cb.curr.state.gas_left.expr() - gas_cost.clone();
@@ -255,6 +268,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
         #[cfg(feature = "scroll")]
         let prev_keccak_code_hash = cb.query_cell_phase2();
         let callee_nonce = cb.query_cell();
+        let callee_is_created_prev = cb.query_bool();
 
         // callee address's nonce
         let (prev_code_hash_is_zero, not_address_collision) =
@@ -424,6 +438,13 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                     0.expr(),
                     Some(&mut callee_reversion_info),
                 );
+                // EIP 6780, mark the contract as created in this tx for SELFDESTRUCT
+                cb.account_created_write(
+                    tx_id.expr(),
+                    contract_addr.clone(),
+                    callee_is_created_prev.expr(),
+                    Some(&mut callee_reversion_info),
+                );
 
                 cb.condition(init_code.has_length(), |cb| {
                     for (field_tag, value) in [
@@ -461,7 +482,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                         is_create: To(true.expr()),
                         code_hash: To(create.code_hash_word_rlc()),
                         gas_left: To(callee_gas_left),
-                        reversible_write_counter: To(1.expr() + transfer.reversible_w_delta()),
+                        reversible_write_counter: To(2.expr() + transfer.reversible_w_delta()),
                         ..StepStateTransition::new_context()
                     });
                 });
@@ -484,7 +505,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                         program_counter: Delta(1.expr()),
                         stack_pointer: Delta(2.expr() + IS_CREATE2.expr()),
                         gas_left: Delta(-gas_cost.expr()),
-                        reversible_write_counter: Delta(3.expr() + transfer.reversible_w_delta()),
+                        reversible_write_counter: Delta(4.expr() + transfer.reversible_w_delta()),
                         ..Default::default()
                     })
                 });
@@ -542,6 +563,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             callee_reversion_info,
             transfer,
             init_code,
+            is_shanghai,
             init_code_rlc,
             memory_expansion,
             gas_left,
@@ -553,6 +575,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             is_insufficient_balance,
             is_nonce_in_range,
             callee_nonce,
+            callee_is_created_prev,
             keccak_code_hash,
             keccak_output,
             not_address_collision,
@@ -687,18 +710,21 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             offset,
             (31u64 + init_code_length.as_u64()).into(),
         )?;
+        self.is_shanghai
+            .assign(region, offset, block, tx.block_number)?;
+        let fork_rules = block.fork_rules(tx.block_number);
         self.init_code_size_not_overflow.assign(
             region,
             offset,
             F::from(init_code_length.as_u64()),
-            F::from(MAX_INIT_CODE_SIZE + 1),
+            F::from(max_init_code_size(&fork_rules) + 1),
         )?;
 
         let keccak_gas_cost = u64::try_from(init_code_word_size).unwrap()
             * if IS_CREATE2 {
-                CREATE2_GAS_PER_CODE_WORD
+                create2_gas_per_code_word(&fork_rules)
             } else {
-                CREATE_GAS_PER_CODE_WORD
+                create_gas_per_code_word(&fork_rules)
             };
         let gas_left =
             step.gas_left - GasCost::CREATE.as_u64() - memory_expansion_gas_cost - keccak_gas_cost;
@@ -733,6 +759,14 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                     offset,
                     region.word_rlc(_transfer_assign_result.account_keccak_code_hash.unwrap()),
                 )?;
+
+                rws.next(); // callee nonce += 1
+                let (_, is_created_prev) = rws.next().transient_storage_value_pair();
+                self.callee_is_created_prev.assign(
+                    region,
+                    offset,
+                    Value::known(F::from(!is_created_prev.is_zero())),
+                )?;
             }
 
             let code_hash = CodeDB::hash(&values);
@@ -790,7 +824,6 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             } else if init_code_length.as_usize() == 0 {
                 F::one()
             } else {
-                rws.next(); // callee nonce += 1
                 rws.next(); // caller id
                 let rw = rws.next();
                 debug_assert_eq!(rw.tag(), RwTableTag::CallContext);
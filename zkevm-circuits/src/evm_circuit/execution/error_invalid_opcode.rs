@@ -140,6 +140,31 @@ mod test {
         test_internal_ok(0x20, 0x00, &[push0]);
     }
 
+    #[cfg(not(feature = "cancun"))]
+    #[test]
+    fn invalid_opcode_tload_tstore_for_not_cancun() {
+        for opcode in [0x5c, 0x5d] {
+            test_root_ok(&[opcode]);
+            test_internal_ok(0x20, 0x00, &[opcode]);
+        }
+    }
+
+    #[cfg(not(feature = "cancun"))]
+    #[test]
+    fn invalid_opcode_mcopy_for_not_cancun() {
+        test_root_ok(&[0x5e]);
+        test_internal_ok(0x20, 0x00, &[0x5e]);
+    }
+
+    #[cfg(not(feature = "cancun"))]
+    #[test]
+    fn invalid_opcode_blobhash_blobbasefee_for_not_cancun() {
+        for opcode in [0x49, 0x4a] {
+            test_root_ok(&[opcode]);
+            test_internal_ok(0x20, 0x00, &[opcode]);
+        }
+    }
+
     // for scroll feature, treat selfdestruct_opcode as invalidcode. even this test construct oog
     // case for self_destruct, expected to meet invalid opcode error.
     #[cfg(feature = "scroll")]
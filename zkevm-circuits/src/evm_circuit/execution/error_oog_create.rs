@@ -4,7 +4,7 @@ use crate::{
         param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
         step::ExecutionState,
         util::{
-            common_gadget::CommonErrorGadget,
+            common_gadget::{CommonErrorGadget, IsForkActiveGadget},
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::{LtGadget, PairSelectGadget},
             memory_gadget::{
@@ -18,7 +18,8 @@ use crate::{
 };
 use eth_types::{
     evm_types::{
-        GasCost, OpcodeId, CREATE2_GAS_PER_CODE_WORD, CREATE_GAS_PER_CODE_WORD, MAX_INIT_CODE_SIZE,
+        create2_gas_per_code_word, create_gas_per_code_word, max_init_code_size, Fork, GasCost,
+        OpcodeId, INIT_CODE_WORD_GAS, MAX_CODE_SIZE, MAX_EXPANDED_MEMORY_ADDRESS,
     },
     Field, ToLittleEndian, U256,
 };
@@ -38,6 +39,7 @@ pub(crate) struct ErrorOOGCreateGadget<F> {
     // (maximum init code size) if Shanghai, otherwise when it is greater than
     // 0x1FFFFFFFE0 (maximum value of offset + size).
     // Uint64 overflow is checked in `memory_address` (offset + length).
+    is_shanghai: IsForkActiveGadget<F>,
     init_code_size_overflow: LtGadget<F, { N_BYTES_MEMORY_ADDRESS }>,
     insufficient_gas: LtGadget<F, N_BYTES_GAS>,
     common_error_gadget: CommonErrorGadget<F>,
@@ -68,18 +70,21 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreateGadget<F> {
         cb.stack_pop(memory_address.length_rlc());
         cb.condition(is_create2.expr().0, |cb| cb.stack_pop(salt.expr()));
 
+        let is_shanghai = IsForkActiveGadget::construct(cb, Fork::Shanghai);
+        let max_init_code_size = select::expr(
+            is_shanghai.expr(),
+            (2 * MAX_CODE_SIZE).expr(),
+            MAX_EXPANDED_MEMORY_ADDRESS.expr(),
+        );
         let init_code_size_overflow =
-            LtGadget::construct(cb, MAX_INIT_CODE_SIZE.expr(), memory_address.length());
+            LtGadget::construct(cb, max_init_code_size, memory_address.length());
 
         let minimum_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
         let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.end_offset()]);
 
         let keccak_gas_cost = minimum_word_size.expr()
-            * select::expr(
-                is_create2.expr().0,
-                CREATE2_GAS_PER_CODE_WORD.expr(),
-                CREATE_GAS_PER_CODE_WORD.expr(),
-            );
+            * (is_shanghai.expr() * INIT_CODE_WORD_GAS.expr()
+                + is_create2.expr().0 * GasCost::COPY_SHA3.expr());
         let gas_cost = GasCost::CREATE.expr() + memory_expansion.gas_cost() + keccak_gas_cost;
         let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
 
@@ -107,6 +112,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreateGadget<F> {
             minimum_word_size,
             memory_address,
             memory_expansion,
+            is_shanghai,
             init_code_size_overflow,
             insufficient_gas,
             common_error_gadget,
@@ -118,7 +124,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreateGadget<F> {
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
         block: &Block<F>,
-        _: &Transaction,
+        tx: &Transaction,
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
@@ -166,18 +172,21 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreateGadget<F> {
             .assign(region, offset, step.memory_word_size(), [memory_address])?
             .1;
 
+        self.is_shanghai
+            .assign(region, offset, block, tx.block_number)?;
+        let fork_rules = block.fork_rules(tx.block_number);
         self.init_code_size_overflow.assign(
             region,
             offset,
-            F::from(MAX_INIT_CODE_SIZE),
+            F::from(max_init_code_size(&fork_rules)),
             F::from(init_code_size),
         )?;
 
         let keccak_gas_cost = minimum_word_size
             * if is_create2 {
-                CREATE2_GAS_PER_CODE_WORD
+                create2_gas_per_code_word(&fork_rules)
             } else {
-                CREATE_GAS_PER_CODE_WORD
+                create_gas_per_code_word(&fork_rules)
             };
         self.insufficient_gas.assign(
             region,
@@ -203,7 +212,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreateGadget<F> {
 mod tests {
     use super::*;
     use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, word, Bytecode, ToWord};
+    use eth_types::{bytecode, evm_types::MAX_INIT_CODE_SIZE, word, Bytecode, ToWord};
     use mock::{
         eth,
         test_ctx::{helpers::account_0_code_account_1_no_code, LoggerConfig},
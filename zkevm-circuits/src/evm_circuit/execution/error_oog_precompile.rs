@@ -71,6 +71,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGPrecompileGadget<F> {
             // These are handled in PrecompileFailedGadget
             // addr_bits.value_equals(PrecompileCalls::Sha256),
             // addr_bits.value_equals(PrecompileCalls::Ripemd160),
+            // blake2f is handled in Blake2FGadget
             // addr_bits.value_equals(PrecompileCalls::Blake2F),
             (
                 addr_bits.value_equals(PrecompileCalls::Identity),
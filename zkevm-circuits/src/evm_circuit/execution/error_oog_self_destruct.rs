@@ -0,0 +1,338 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            from_bytes,
+            math_gadget::{IsZeroGadget, LtGadget},
+            not, select, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget to implement the corresponding out of gas errors for
+/// [`OpcodeId::SELFDESTRUCT`].
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGSelfDestructGadget<F> {
+    opcode: Cell<F>,
+    beneficiary_word: Word<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    is_warm: Cell<F>,
+    beneficiary_code_hash: Cell<F>,
+    beneficiary_not_exists: IsZeroGadget<F>,
+    balance: Word<F>,
+    balance_is_zero: IsZeroGadget<F>,
+    insufficient_gas_cost: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGSelfDestructGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasSELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasSELFDESTRUCT;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasSELFDESTRUCT opcode must be SELFDESTRUCT",
+            opcode.expr(),
+            OpcodeId::SELFDESTRUCT.expr(),
+        );
+
+        let beneficiary_word = cb.query_word_rlc();
+        let beneficiary = from_bytes::expr(&beneficiary_word.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        cb.stack_pop(beneficiary_word.expr());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let is_warm = cb.query_bool();
+        // read is_warm
+        cb.account_access_list_read(tx_id.expr(), beneficiary.expr(), is_warm.expr());
+
+        // For non-existing accounts the code_hash must be 0 in the rw_table.
+        let beneficiary_code_hash = cb.query_cell_phase2();
+        cb.account_read(
+            beneficiary.expr(),
+            AccountFieldTag::CodeHash,
+            beneficiary_code_hash.expr(),
+        );
+        let beneficiary_not_exists = IsZeroGadget::construct(cb, beneficiary_code_hash.expr());
+
+        let balance = cb.query_word_rlc();
+        cb.account_read(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            balance.expr(),
+        );
+        let balance_is_zero = IsZeroGadget::construct(cb, balance.expr());
+
+        // A new account is charged only when value is sent to an empty
+        // beneficiary.
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + select::expr(
+                is_warm.expr(),
+                0.expr(),
+                GasCost::COLD_ACCOUNT_ACCESS.expr(),
+            )
+            + beneficiary_not_exists.expr()
+                * not::expr(balance_is_zero.expr())
+                * GasCost::NEW_ACCOUNT.expr();
+
+        let insufficient_gas_cost =
+            LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+
+        cb.require_equal(
+            "Gas left is less than gas cost",
+            insufficient_gas_cost.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 8.expr());
+        Self {
+            opcode,
+            beneficiary_word,
+            tx_id,
+            callee_address,
+            is_warm,
+            beneficiary_code_hash,
+            beneficiary_not_exists,
+            balance,
+            balance_is_zero,
+            insufficient_gas_cost,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let beneficiary = block.rws[step.rw_indices[0]].stack_value();
+        self.beneficiary_word
+            .assign(region, offset, Some(beneficiary.to_le_bytes()))?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(
+                call.callee_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+
+        let (_, is_warm) = block.rws[step.rw_indices[3]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm)))?;
+
+        let beneficiary_code_hash = block.rws[step.rw_indices[4]].account_codehash_pair().0;
+        self.beneficiary_code_hash
+            .assign(region, offset, region.code_hash(beneficiary_code_hash))?;
+        self.beneficiary_not_exists.assign_value(
+            region,
+            offset,
+            region.code_hash(beneficiary_code_hash),
+        )?;
+
+        let balance = block.rws[step.rw_indices[5]].account_balance_pair().0;
+        self.balance
+            .assign(region, offset, Some(balance.to_le_bytes()))?;
+        self.balance_is_zero
+            .assign_value(region, offset, region.word_rlc(balance))?;
+
+        let gas_cost = GasCost::SELFDESTRUCT.as_u64()
+            + if is_warm {
+                0
+            } else {
+                GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+            }
+            + if beneficiary_code_hash.is_zero() && !balance.is_zero() {
+                GasCost::NEW_ACCOUNT.as_u64()
+            } else {
+                0
+            };
+
+        self.insufficient_gas_cost.assign_value(
+            region,
+            offset,
+            Value::known(F::from(step.gas_left)),
+            Value::known(F::from(gas_cost)),
+        )?;
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 8)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{
+        address, bytecode,
+        evm_types::{GasCost, OpcodeId},
+        Address, Bytecode, ToWord, Word,
+    };
+    use lazy_static::lazy_static;
+    use mock::TestContext;
+
+    lazy_static! {
+        static ref BENEFICIARY: Address = address!("0xaabbccddee000000000000000000000000000000");
+    }
+
+    fn selfdestruct_code(is_warm: bool) -> Bytecode {
+        let mut code = Bytecode::default();
+        if is_warm {
+            code.append(&bytecode! {
+                PUSH20(BENEFICIARY.to_word())
+                BALANCE
+                POP
+            });
+        }
+        code.append(&bytecode! {
+            PUSH20(BENEFICIARY.to_word())
+            SELFDESTRUCT
+        });
+        code
+    }
+
+    /// Gas needed by the opcodes before SELFDESTRUCT plus its own cost.
+    fn selfdestruct_gas(is_warm: bool, exists: bool, balance: Word) -> u64 {
+        let mut gas = OpcodeId::PUSH20.constant_gas_cost().0 + GasCost::SELFDESTRUCT.as_u64();
+        if is_warm {
+            gas += OpcodeId::PUSH20.constant_gas_cost().0
+                + GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+                + OpcodeId::POP.constant_gas_cost().0;
+        } else {
+            gas += GasCost::COLD_ACCOUNT_ACCESS.as_u64();
+        }
+        if !exists && !balance.is_zero() {
+            gas += GasCost::NEW_ACCOUNT.as_u64();
+        }
+        gas
+    }
+
+    #[test]
+    fn oog_self_destruct_root() {
+        for (is_warm, exists, balance) in [
+            (false, true, Word::from(1u64 << 20)),
+            (true, true, Word::from(1u64 << 20)),
+            (false, false, Word::from(1u64 << 20)),
+            (true, false, Word::from(1u64 << 20)),
+            (false, false, Word::zero()),
+        ] {
+            test_root_ok(is_warm, exists, balance);
+        }
+    }
+
+    #[test]
+    fn oog_self_destruct_internal() {
+        for (is_warm, exists) in [(false, true), (true, true), (false, false), (true, false)] {
+            test_internal_ok(is_warm, exists);
+        }
+    }
+
+    fn test_root_ok(is_warm: bool, exists: bool, balance: Word) {
+        let gas = GasCost::TX.0 + selfdestruct_gas(is_warm, exists, balance) - 1;
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(balance)
+                    .code(selfdestruct_code(is_warm));
+                if exists {
+                    accs[1].address(*BENEFICIARY).balance(Word::from(900u64));
+                } else {
+                    accs[1]
+                        .address(address!("0x0000000000000000000000000000000000000010"))
+                        .balance(Word::from(1_u64 << 20));
+                }
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000000020"))
+                    .balance(Word::from(1_u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[2].address)
+                    .gas(gas.into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_internal_ok(is_warm: bool, exists: bool) {
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+        let balance = Word::from(1_u64 << 20);
+        let gas = selfdestruct_gas(is_warm, exists, balance) - 1;
+
+        // code A calls code B with insufficient gas for SELFDESTRUCT.
+        let code_a = bytecode! {
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(addr_b.to_word()) // addr
+            PUSH32(Word::from(gas)) // gas
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(addr_b)
+                    .balance(balance)
+                    .code(selfdestruct_code(is_warm));
+                accs[1].address(addr_a).code(code_a);
+                if exists {
+                    accs[2].address(*BENEFICIARY).balance(Word::from(900u64));
+                } else {
+                    accs[2]
+                        .address(mock::MOCK_ACCOUNTS[2])
+                        .balance(Word::from(1_u64 << 20));
+                }
+                accs[3]
+                    .address(mock::MOCK_ACCOUNTS[3])
+                    .balance(Word::from(1_u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[3].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            memory_gadget::{
+                CommonMemoryAddressGadget, MemoryAddressGadget, MemoryCopierGasGadget,
+                MemoryExpansionGadget,
+            },
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{evm_types::GasCost, Field, ToScalar};
+use gadgets::util::not;
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct McopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    /// The memory range from which we read. It shares the length with the
+    /// destination range.
+    src_memory_addr: MemoryAddressGadget<F>,
+    /// The memory range to which we write.
+    dst_memory_addr: MemoryAddressGadget<F>,
+    /// Opcode MCOPY has a dynamic gas cost:
+    /// gas_code = static_gas * minimum_word_size + memory_expansion_cost
+    /// where the memory expansion covers both the source and the destination.
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
+    /// Opcode MCOPY needs to copy data within memory. We account for the
+    /// copying costs using the memory copier gas gadget.
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
+    /// RW inverse counter from the copy table at the start of related copy
+    /// steps.
+    copy_rwc_inc: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for McopyGadget<F> {
+    const NAME: &'static str = "MCOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::MCOPY;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let dst_offset = cb.query_cell_phase2();
+        let src_offset = cb.query_cell_phase2();
+        let length = cb.query_word_rlc();
+
+        // Pop dest_offset, offset and length from the stack.
+        cb.stack_pop(dst_offset.expr());
+        cb.stack_pop(src_offset.expr());
+        cb.stack_pop(length.expr());
+
+        let src_memory_addr = MemoryAddressGadget::construct(cb, src_offset, length.clone());
+        let dst_memory_addr = MemoryAddressGadget::construct(cb, dst_offset, length);
+
+        // Calculate the next memory size and the gas cost for this memory
+        // access. This also accounts for the dynamic gas required to copy bytes to
+        // memory.
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            [src_memory_addr.end_offset(), dst_memory_addr.end_offset()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            dst_memory_addr.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // The source and the destination may overlap, so the copy circuit reads
+        // all the source words before writing to the destination.
+        let copy_rwc_inc = cb.query_cell();
+        cb.condition(dst_memory_addr.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::MemoryCopy.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::MemoryCopy.expr(),
+                src_memory_addr.offset(),
+                src_memory_addr.end_offset(),
+                dst_memory_addr.offset(),
+                dst_memory_addr.length(),
+                0.expr(), // for MCOPY rlc_acc is 0
+                copy_rwc_inc.expr(),
+            );
+        });
+        cb.condition(not::expr(dst_memory_addr.has_length()), |cb| {
+            cb.require_zero(
+                "if no bytes to copy, copy table rwc inc == 0",
+                copy_rwc_inc.expr(),
+            );
+        });
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(3.expr()),
+            gas_left: Delta(
+                -(OpcodeId::MCOPY.constant_gas_cost().expr() + memory_copier_gas.gas_cost()),
+            ),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            src_memory_addr,
+            dst_memory_addr,
+            memory_expansion,
+            memory_copier_gas,
+            copy_rwc_inc,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        _call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [dst_offset, src_offset, length] =
+            [0, 1, 2].map(|i| block.rws[step.rw_indices[i]].stack_value());
+
+        let src_memory_address = self
+            .src_memory_addr
+            .assign(region, offset, src_offset, length)?;
+        let dst_memory_address = self
+            .dst_memory_addr
+            .assign(region, offset, dst_offset, length)?;
+
+        // assign to gadgets handling memory expansion cost and copying cost.
+        let (_, memory_expansion_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [src_memory_address, dst_memory_address],
+        )?;
+        self.memory_copier_gas
+            .assign(region, offset, length.as_u64(), memory_expansion_cost)?;
+
+        self.copy_rwc_inc.assign(
+            region,
+            offset,
+            Value::known(
+                step.copy_rw_counter_delta
+                    .to_scalar()
+                    .expect("unexpected U256 -> Scalar conversion failure"),
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod test {
+    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use eth_types::{bytecode, Word};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn test_ok(dst_offset: u64, src_offset: u64, length: u64) {
+        // Fill the first 3 words of memory with random data before copying.
+        let bytecode = bytecode! {
+            PUSH32(Word::from_big_endian(&rand_bytes(32)))
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(Word::from_big_endian(&rand_bytes(32)))
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(Word::from_big_endian(&rand_bytes(32)))
+            PUSH1(0x40)
+            MSTORE
+            PUSH32(length)
+            PUSH32(src_offset)
+            PUSH32(dst_offset)
+            MCOPY
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            tx_from_1_to_0,
+            |block, _txs| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn mcopy_gadget_simple() {
+        test_ok(0x60, 0x00, 0x20);
+    }
+
+    #[test]
+    fn mcopy_gadget_unaligned() {
+        test_ok(0x65, 0x03, 0x31);
+    }
+
+    #[test]
+    fn mcopy_gadget_forward_overlap() {
+        test_ok(0x10, 0x00, 0x40);
+        test_ok(0x01, 0x00, 0x5f);
+    }
+
+    #[test]
+    fn mcopy_gadget_backward_overlap() {
+        test_ok(0x00, 0x10, 0x40);
+        test_ok(0x00, 0x01, 0x5f);
+    }
+
+    #[test]
+    fn mcopy_gadget_same_offset() {
+        test_ok(0x08, 0x08, 0x40);
+    }
+
+    #[test]
+    fn mcopy_gadget_expand_by_source() {
+        // Reading beyond the memory size expands it, the copied bytes are zeros.
+        test_ok(0x00, 0x80, 0x40);
+    }
+
+    #[test]
+    fn mcopy_gadget_zero_length() {
+        test_ok(0x00, 0x00, 0x00);
+        test_ok(u64::MAX, u64::MAX, 0x00);
+    }
+}
@@ -0,0 +1,548 @@
+use bus_mapping::precompile::{PrecompileAuxData, BLAKE2F_INPUT_LEN, BLAKE2F_OUTPUT_LEN};
+use eth_types::{evm_types::GasCost, Field, ToScalar};
+use gadgets::util::{and, not, select, Expr};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+            rlc, CachedRegion, Cell,
+        },
+    },
+    table::CallContextFieldTag,
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+#[derive(Clone, Debug)]
+pub struct Blake2FGadget<F> {
+    input_bytes_rlc: Cell<F>,
+    output_bytes_rlc: Cell<F>,
+    return_bytes_rlc: Cell<F>,
+
+    // The input is parsed as rounds (4 bytes, little-endian cells) | h, m and t (208 bytes) | f
+    rounds_bytes: [Cell<F>; 4],
+    hmt_bytes_rlc: Cell<F>,
+    flag: Cell<F>,
+    input_len_valid: IsEqualGadget<F>,
+    flag_valid: IsZeroGadget<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_U64>,
+
+    is_success: Cell<F>,
+    callee_address: Cell<F>,
+    caller_id: Cell<F>,
+    call_data_offset: Cell<F>,
+    call_data_length: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for Blake2FGadget<F> {
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileBlake2f;
+
+    const NAME: &'static str = "BLAKE2F";
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let (input_bytes_rlc, output_bytes_rlc, return_bytes_rlc) = (
+            cb.query_cell_phase2(),
+            cb.query_cell_phase2(),
+            cb.query_cell_phase2(),
+        );
+        let rounds_bytes = cb.query_bytes();
+        let rounds = from_bytes::expr(&rounds_bytes);
+        let hmt_bytes_rlc = cb.query_cell_phase2();
+        let flag = cb.query_byte();
+        let [is_success, callee_address, caller_id, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+            [
+                CallContextFieldTag::IsSuccess,
+                CallContextFieldTag::CalleeAddress,
+                CallContextFieldTag::CallerId,
+                CallContextFieldTag::CallDataOffset,
+                CallContextFieldTag::CallDataLength,
+                CallContextFieldTag::ReturnDataOffset,
+                CallContextFieldTag::ReturnDataLength,
+            ]
+            .map(|tag| cb.call_context(None, tag));
+
+        cb.precompile_info_lookup(
+            cb.execution_state().as_u64().expr(),
+            callee_address.expr(),
+            cb.execution_state().precompile_base_gas_cost().expr(),
+        );
+
+        // With a valid length, the rounds and the final block indicator flag are
+        // the first 4 bytes and the last byte of the input.
+        let input_len_valid =
+            IsEqualGadget::construct(cb, call_data_length.expr(), BLAKE2F_INPUT_LEN.expr());
+        cb.condition(input_len_valid.expr(), |cb| {
+            let powers_of_randomness = cb.challenges().keccak_powers_of_randomness::<16>();
+            let r = powers_of_randomness[0].clone();
+            let r_pow_208 = (0..12).fold(powers_of_randomness[15].clone(), |acc, _| {
+                acc * powers_of_randomness[15].clone()
+            });
+            let rounds_bytes_rlc = cb.keccak_rlc::<4>(rounds_bytes.clone().map(|byte| byte.expr()));
+            cb.require_equal(
+                "input bytes (RLC) = [rounds | h | m | t | f]",
+                input_bytes_rlc.expr(),
+                rounds_bytes_rlc * r_pow_208 * r.clone() + hmt_bytes_rlc.expr() * r + flag.expr(),
+            );
+        });
+        let flag_valid = IsZeroGadget::construct(cb, flag.expr() * (1.expr() - flag.expr()));
+        let gas_required = GasCost::PRECOMPILE_BLAKE2F.expr()
+            + rounds.expr() * GasCost::PRECOMPILE_BLAKE2F_PER_ROUND.expr();
+        let insufficient_gas =
+            LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_required.expr());
+
+        // The call fails (consuming all gas) on an invalid input length, an invalid final block
+        // indicator flag or insufficient gas for the rounds, and succeeds otherwise. The
+        // compression of a successful call is verified in the blake2f circuit.
+        cb.require_equal(
+            "is_success = valid input length, valid flag and sufficient gas",
+            is_success.expr(),
+            and::expr([
+                input_len_valid.expr(),
+                flag_valid.expr(),
+                not::expr(insufficient_gas.expr()),
+            ]),
+        );
+        cb.condition(is_success.expr(), |cb| {
+            cb.blake2f_table_lookup(
+                rounds.expr(),
+                input_bytes_rlc.expr(),
+                output_bytes_rlc.expr(),
+            );
+        });
+
+        let gas_cost = select::expr(
+            is_success.expr(),
+            gas_required,
+            cb.curr.state.gas_left.expr(),
+        );
+
+        let restore_context = RestoreContextGadget::construct2(
+            cb,
+            is_success.expr(),
+            gas_cost,
+            0.expr(),
+            0x00.expr(), // ReturnDataOffset
+            select::expr(is_success.expr(), BLAKE2F_OUTPUT_LEN.expr(), 0x00.expr()), /* ReturnDataLength */
+            0.expr(),
+            0.expr(),
+        );
+
+        Self {
+            input_bytes_rlc,
+            output_bytes_rlc,
+            return_bytes_rlc,
+
+            rounds_bytes,
+            hmt_bytes_rlc,
+            flag,
+            input_len_valid,
+            flag_valid,
+            insufficient_gas,
+
+            is_success,
+            callee_address,
+            caller_id,
+            call_data_offset,
+            call_data_length,
+            return_data_offset,
+            return_data_length,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        if let Some(PrecompileAuxData::Blake2F {
+            input_bytes,
+            output_bytes,
+            return_bytes,
+        }) = &step.aux_data
+        {
+            for (col, bytes) in [
+                (&self.input_bytes_rlc, input_bytes),
+                (&self.output_bytes_rlc, output_bytes),
+                (&self.return_bytes_rlc, return_bytes),
+            ] {
+                col.assign(
+                    region,
+                    offset,
+                    region
+                        .challenges()
+                        .keccak_input()
+                        .map(|r| rlc::value(bytes.iter().rev(), r)),
+                )?;
+            }
+
+            // The rounds and flag cells are only bound to the input with a valid length.
+            let input_len_valid = input_bytes.len() == BLAKE2F_INPUT_LEN;
+            let (rounds, flag) = if input_len_valid {
+                (
+                    u32::from_be_bytes(input_bytes[0..4].try_into().unwrap()),
+                    input_bytes[BLAKE2F_INPUT_LEN - 1],
+                )
+            } else {
+                (0, 0)
+            };
+            for (cell, byte) in self.rounds_bytes.iter().zip(rounds.to_le_bytes()) {
+                cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+            }
+            self.hmt_bytes_rlc.assign(
+                region,
+                offset,
+                if input_len_valid {
+                    region
+                        .challenges()
+                        .keccak_input()
+                        .map(|r| rlc::value(input_bytes[4..BLAKE2F_INPUT_LEN - 1].iter().rev(), r))
+                } else {
+                    Value::known(F::zero())
+                },
+            )?;
+            self.flag
+                .assign(region, offset, Value::known(F::from(flag as u64)))?;
+            self.input_len_valid.assign(
+                region,
+                offset,
+                F::from(call.call_data_length),
+                F::from(BLAKE2F_INPUT_LEN as u64),
+            )?;
+            let flag = F::from(flag as u64);
+            self.flag_valid
+                .assign(region, offset, flag * (F::one() - flag))?;
+            let gas_required = GasCost::PRECOMPILE_BLAKE2F.as_u64()
+                + rounds as u64 * GasCost::PRECOMPILE_BLAKE2F_PER_ROUND.as_u64();
+            self.insufficient_gas.assign(
+                region,
+                offset,
+                F::from(step.gas_left),
+                F::from(gas_required),
+            )?;
+        } else {
+            log::error!("unexpected aux_data {:?} for blake2f", step.aux_data);
+            return Err(Error::Synthesis);
+        }
+
+        self.is_success.assign(
+            region,
+            offset,
+            Value::known(F::from(u64::from(call.is_success))),
+        )?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
+        )?;
+        self.caller_id
+            .assign(region, offset, Value::known(F::from(call.caller_id as u64)))?;
+        self.call_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_offset)),
+        )?;
+        self.call_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_length)),
+        )?;
+        self.return_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_offset)),
+        )?;
+        self.return_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_length)),
+        )?;
+        self.restore_context
+            .assign(region, offset, block, call, step, 7)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bus_mapping::{
+        evm::{OpcodeId, PrecompileCallArgs},
+        precompile::PrecompileCalls,
+    };
+    use eth_types::{bytecode, word, Bytecode, ToWord, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use itertools::Itertools;
+    use mock::TestContext;
+
+    use crate::{
+        table::{CallContextFieldTag, RwTableTag},
+        test_util::CircuitTestBuilder,
+        witness::{Block, Rw},
+    };
+
+    /// Store the EIP-152 test vector 5 (h = blake2b IV ^ param block, m = "abc", t = 3) with
+    /// the given rounds and final block indicator flag at memory offset 0x00.
+    fn setup_code(rounds: u32, f: u8) -> Bytecode {
+        bytecode! {
+            // rounds (4 bytes big-endian) followed by the first 28 bytes of h
+            PUSH32((Word::from(rounds) << 224)
+                | word!("0x48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f"))
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(word!("0x3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e13"))
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(word!("0x19cde05b61626300000000000000000000000000000000000000000000000000"))
+            PUSH1(0x40)
+            MSTORE
+            // t followed by f at byte 0xd4
+            PUSH32(word!("0x0000000003000000000000000000000000000000000000000000000000000000")
+                | (Word::from(f) << 88))
+            PUSH1(0xc0)
+            MSTORE
+        }
+    }
+
+    lazy_static::lazy_static! {
+        static ref TEST_VECTOR: Vec<PrecompileCallArgs> = {
+            vec![
+                PrecompileCallArgs {
+                    name: "12 rounds final block",
+                    setup_code: setup_code(12, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    ret_offset: 0x100.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "12 rounds non-final block",
+                    setup_code: setup_code(12, 0),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    ret_offset: 0x100.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "0 rounds",
+                    setup_code: setup_code(0, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    ret_offset: 0x100.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "25 rounds truncated return",
+                    setup_code: setup_code(25, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    ret_offset: 0x100.into(),
+                    ret_size: 0x20.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "overlapped return",
+                    setup_code: setup_code(12, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    ret_offset: 0x00.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+            ]
+        };
+
+        static ref INVALID_TEST_VECTOR: Vec<PrecompileCallArgs> = {
+            vec![
+                PrecompileCallArgs {
+                    name: "input too short",
+                    setup_code: setup_code(12, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd4.into(),
+                    ret_offset: 0x100.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "input too long",
+                    setup_code: setup_code(12, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd6.into(),
+                    ret_offset: 0x100.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "invalid final block indicator flag",
+                    setup_code: setup_code(12, 2),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    ret_offset: 0x100.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "oog",
+                    setup_code: setup_code(12, 1),
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0xd5.into(),
+                    ret_offset: 0x100.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Blake2F.address().to_word(),
+                    gas: Word::from(11),
+                    ..Default::default()
+                },
+            ]
+        };
+    }
+
+    #[test]
+    fn precompile_blake2f_common_test() {
+        let call_kinds = vec![
+            OpcodeId::CALL,
+            OpcodeId::STATICCALL,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::CALLCODE,
+        ];
+
+        for (test_vector, &call_kind) in TEST_VECTOR.iter().cartesian_product(&call_kinds) {
+            let bytecode = test_vector.with_call_op(call_kind);
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .block_modifier(Box::new(|blk| {
+                assert_eq!(blk.get_blake2f().len(), 1);
+            }))
+            .run();
+        }
+    }
+
+    #[test]
+    fn precompile_blake2f_output_test() {
+        let bytecode = TEST_VECTOR[0].with_call_op(OpcodeId::STATICCALL);
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .block_modifier(Box::new(|blk| {
+            let events = blk.get_blake2f();
+            assert_eq!(events.len(), 1);
+            assert_eq!(
+                hex::encode(events[0].output_bytes()),
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+                 7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+            );
+        }))
+        .run();
+    }
+
+    #[test]
+    fn precompile_blake2f_invalid_test() {
+        let call_kinds = vec![
+            OpcodeId::CALL,
+            OpcodeId::STATICCALL,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::CALLCODE,
+        ];
+
+        for (test_vector, &call_kind) in INVALID_TEST_VECTOR.iter().cartesian_product(&call_kinds) {
+            let bytecode = test_vector.with_call_op(call_kind);
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .block_modifier(Box::new(|blk| {
+                assert_eq!(blk.get_blake2f().len(), 0);
+            }))
+            .run();
+        }
+    }
+
+    /// Flip the success of the blake2f call in the witness, in both the call and
+    /// its IsSuccess call context rows.
+    fn flip_is_success(block: &mut Block<Fr>) {
+        let call = block.txs[0]
+            .calls
+            .iter_mut()
+            .find(|call| call.code_address == Some(PrecompileCalls::Blake2F.address()))
+            .unwrap();
+        call.is_success = !call.is_success;
+        let (call_id, is_success) = (call.id, call.is_success);
+        for rw in block.rws.0.get_mut(&RwTableTag::CallContext).unwrap() {
+            if let Rw::CallContext {
+                call_id: id,
+                field_tag: CallContextFieldTag::IsSuccess,
+                value,
+                ..
+            } = rw
+            {
+                if *id == call_id {
+                    *value = Word::from(is_success as u64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn precompile_blake2f_tampered_success_test() {
+        // An invalid flag, an invalid length or insufficient gas can't be claimed
+        // as a successful call.
+        for test_vector in INVALID_TEST_VECTOR.iter() {
+            let bytecode = test_vector.with_call_op(OpcodeId::STATICCALL);
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .block_modifier(Box::new(flip_is_success))
+            .state_checks(None)
+            .evm_checks(Some(Box::new(|prover, gate_rows, lookup_rows| {
+                assert!(prover
+                    .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                    .is_err())
+            })))
+            .run();
+        }
+    }
+
+    #[test]
+    fn precompile_blake2f_tampered_failure_test() {
+        // A valid call can't be claimed as failed.
+        let bytecode = TEST_VECTOR[0].with_call_op(OpcodeId::STATICCALL);
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .block_modifier(Box::new(flip_is_success))
+        .state_checks(None)
+        .evm_checks(Some(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        })))
+        .run();
+    }
+}
@@ -1,7 +1,5 @@
-use bus_mapping::precompile::{
-    PrecompileAuxData, PrecompileCalls, MODEXP_INPUT_LIMIT, MODEXP_SIZE_LIMIT,
-};
-use eth_types::{evm_types::GasCost, Field, ToBigEndian, ToScalar, U256};
+use bus_mapping::precompile::{modexp_input_limit, PrecompileAuxData, MAX_MODEXP_SIZE_LIMIT};
+use eth_types::{evm_types::GasCost, Field, ToScalar, U256};
 use gadgets::util::{self, not, select, Expr};
 use halo2_proofs::{
     circuit::Value,
@@ -11,16 +9,14 @@ use halo2_proofs::{
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BITS_U8, N_BYTES_MEMORY_ADDRESS, N_BYTES_U64, N_BYTES_WORD},
+        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_U64},
         step::ExecutionState,
         util::{
             common_gadget::RestoreContextGadget,
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::{
-                BinaryNumberGadget, BitLengthGadget, ByteOrWord, ByteSizeGadget,
-                ConstantDivisionGadget, IsZeroGadget, LtGadget, MinMaxGadget,
+                BinaryNumberGadget, ConstantDivisionGadget, IsZeroGadget, LtGadget, MinMaxGadget,
             },
-            padding_gadget::PaddingGadget,
             rlc, CachedRegion, Cell,
         },
     },
@@ -116,12 +112,9 @@ impl<F: Field, const BIT_LIMIT: usize> RandPowRepresent<F, BIT_LIMIT> {
     }
 }
 
-const SIZE_LIMIT: usize = MODEXP_SIZE_LIMIT;
-const SIZE_REPRESENT_BITS: usize = 6;
-const SIZE_REPRESENT_BYTES: usize = SIZE_LIMIT / 256 + 1;
-const INPUT_LIMIT: usize = 32 * 6;
-const INPUT_REPRESENT_BYTES: usize = MODEXP_INPUT_LIMIT / 256 + 1;
-const INPUT_REPRESENT_BITS: usize = 8;
+const SIZE_REPRESENT_BITS: usize = 10;
+const SIZE_REPRESENT_BYTES: usize = MAX_MODEXP_SIZE_LIMIT / 256 + 1;
+const INPUT_REPRESENT_BITS: usize = 11;
 
 type Word<F> = [Cell<F>; 32];
 
@@ -138,11 +131,8 @@ fn assign_word<F: Field, const N: usize>(
     Ok(())
 }
 
-// rlc cells array, in the reversed byte order
-fn rlc_rev<F: Field, const N: usize>(
-    cells: &[Cell<F>; N],
-    randomness: Expression<F>,
-) -> Expression<F> {
+// rlc word, in the reversed byte order
+fn rlc_word_rev<F: Field>(cells: &[Cell<F>; 32], randomness: Expression<F>) -> Expression<F> {
     cells
         .iter()
         .map(|cell| cell.expr())
@@ -150,11 +140,6 @@ fn rlc_rev<F: Field, const N: usize>(
         .expect("values should not be empty")
 }
 
-// rlc word, in the reversed byte order
-fn rlc_word_rev<F: Field>(cells: &[Cell<F>; 32], randomness: Expression<F>) -> Expression<F> {
-    rlc_rev(cells, randomness)
-}
-
 // calc for big-endian (notice util::expr_from_bytes calc for little-endian)
 fn expr_from_bytes<F: Field, E: Expr<F>>(bytes: &[E]) -> Expression<F> {
     bytes
@@ -162,16 +147,30 @@ fn expr_from_bytes<F: Field, E: Expr<F>>(bytes: &[E]) -> Expression<F> {
         .fold(0.expr(), |acc, byte| acc * F::from(256) + byte.expr())
 }
 
+// rlc of big-endian bytes, in the reversed byte order
+fn rlc_bytes_rev<F: Field>(bytes: &[u8], randomness: Value<F>) -> Value<F> {
+    randomness.map(|r| rlc::value(bytes.iter().rev(), r))
+}
+
+// the number of significant bits in big-endian bytes
+fn bit_len(bytes: &[u8]) -> usize {
+    bytes.iter().position(|&byte| byte != 0).map_or(0, |i| {
+        8 * (bytes.len() - i) - bytes[i].leading_zeros() as usize
+    })
+}
+
 #[derive(Clone, Debug)]
 struct SizeRepresent<F> {
     len_bytes: Word<F>,
     expression: Expression<F>,
     is_rest_field_zero: IsZeroGadget<F>,
     is_not_exceed_limit: LtGadget<F, SIZE_REPRESENT_BYTES>,
+    limit: usize,
 }
 
 impl<F: Field> SizeRepresent<F> {
     pub fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let limit = cb.modexp_size_limit();
         let len_bytes = cb.query_bytes();
         let expression = rlc_word_rev(&len_bytes, cb.challenges().keccak_input());
         // we calculate at most 31 bytes so it can be fit into a field
@@ -184,16 +183,14 @@ impl<F: Field> SizeRepresent<F> {
             .iter()
             .map(Cell::expr)
             .collect::<Vec<_>>();
-        let is_not_exceed_limit = LtGadget::construct(
-            cb,
-            expr_from_bytes(&len_effect_bytes),
-            (SIZE_LIMIT + 1).expr(),
-        );
+        let is_not_exceed_limit =
+            LtGadget::construct(cb, expr_from_bytes(&len_effect_bytes), (limit + 1).expr());
         Self {
             len_bytes,
             expression,
             is_rest_field_zero,
             is_not_exceed_limit,
+            limit,
         }
     }
 
@@ -238,7 +235,7 @@ impl<F: Field> SizeRepresent<F> {
             region,
             offset,
             effect_field.to_scalar().unwrap(),
-            F::from((SIZE_LIMIT + 1) as u64),
+            F::from((self.limit + 1) as u64),
         )?;
         Ok(())
     }
@@ -247,8 +244,8 @@ impl<F: Field> SizeRepresent<F> {
 type RandPow<F> = RandPowRepresent<F, SIZE_REPRESENT_BITS>;
 
 // parse as (valid, len, value: [base, exp, modulus])
-type InputParsedResult = (bool, [U256; 3], [[u8; SIZE_LIMIT]; 3]);
-type OutputParsedResult = (usize, [u8; SIZE_LIMIT]);
+type InputParsedResult<'a> = (bool, [U256; 3], &'a [Vec<u8>; 3]);
+type OutputParsedResult<'a> = (usize, &'a [u8]);
 
 #[derive(Clone, Debug)]
 struct ModExpInputs<F> {
@@ -256,17 +253,17 @@ struct ModExpInputs<F> {
     modulus_len: SizeRepresent<F>,
     exp_len: SizeRepresent<F>,
     base_pow: RandPow<F>,
-    base: Word<F>,
+    base: Cell<F>,
     modulus_pow: RandPow<F>,
-    modulus: Word<F>,
+    modulus: Cell<F>,
     exp_pow: RandPow<F>,
-    exp: Word<F>,
+    exp: Cell<F>,
     input_valid: Cell<F>,
     input_bytes_rlc: Expression<F>,
+    base_len_expected: Expression<F>,
+    exp_len_expected: Expression<F>,
+    modulus_len_expected: Expression<F>,
     input_len_expected: Expression<F>,
-    pub base_limbs: Limbs<F>,
-    pub exp_limbs: Limbs<F>,
-    pub modulus_limbs: Limbs<F>,
 }
 
 impl<F: Field> ModExpInputs<F> {
@@ -279,13 +276,11 @@ impl<F: Field> ModExpInputs<F> {
             RandPowRepresent::<_, 6>::base_pows_expr(cb.challenges().keccak_input())[5].clone(); //r**32
         let r_pow_64 = r_pow_32.clone().square();
 
-        let base = cb.query_bytes();
-        let modulus = cb.query_bytes();
-        let exp = cb.query_bytes();
-
-        let base_limbs = Limbs::configure(cb, &base);
-        let exp_limbs = Limbs::configure(cb, &exp);
-        let modulus_limbs = Limbs::configure(cb, &modulus);
+        // the values are given by the RLC of their big-endian bytes, which are looked up from the
+        // modexp table along with their byte lengths
+        let base = cb.query_cell_phase2();
+        let modulus = cb.query_cell_phase2();
+        let exp = cb.query_cell_phase2();
 
         let input_valid = cb.query_bool();
         cb.require_equal(
@@ -297,6 +292,11 @@ impl<F: Field> ModExpInputs<F> {
                 modulus_len.is_valid(),
             ]),
         );
+        cb.condition(not::expr(input_valid.expr()), |cb| {
+            for (name, value) in [("base", &base), ("exp", &exp), ("modulus", &modulus)] {
+                cb.require_zero(name, value.expr());
+            }
+        });
 
         let base_len_expected = util::select::expr(input_valid.expr(), base_len.value(), 0.expr());
 
@@ -310,13 +310,12 @@ impl<F: Field> ModExpInputs<F> {
             + exp_len_expected.clone()
             + modulus_len_expected.clone();
 
-        // we put correct size in each input word if input is valid
-        // else we just put as most as possible bytes (32) into it
-        // so we finally handle the memory in limited sized (32*3)
+        // we take the bytes of the specified size for each input if input is valid
+        // else we only take the 96 bytes of the sizes
         let modulus_pow = RandPow::configure(
             cb,
             cb.challenges().keccak_input(),
-            modulus_len_expected,
+            modulus_len_expected.clone(),
             None,
         );
 
@@ -324,7 +323,7 @@ impl<F: Field> ModExpInputs<F> {
         let exp_pow = RandPow::configure(
             cb,
             cb.challenges().keccak_input(),
-            exp_len_expected,
+            exp_len_expected.clone(),
             Some(modulus_pow.expr()),
         );
 
@@ -332,21 +331,17 @@ impl<F: Field> ModExpInputs<F> {
         let base_pow = RandPow::configure(
             cb,
             cb.challenges().keccak_input(),
-            base_len_expected,
+            base_len_expected.clone(),
             Some(exp_pow.expr()),
         );
 
-        let input_bytes_rlc = rlc_word_rev(&modulus, cb.challenges().keccak_input()) //rlc of base
-            + modulus_pow.expr() * rlc_word_rev(&exp, cb.challenges().keccak_input()) //rlc of exp plus r**base_len
-            + exp_pow.expr() * rlc_word_rev(&base, cb.challenges().keccak_input()) //rlc of exp plus r**(base_len + exp_len)
+        let input_bytes_rlc = modulus.expr() //rlc of modulus
+            + modulus_pow.expr() * exp.expr() //rlc of exp plus r**modulus_len
+            + exp_pow.expr() * base.expr() //rlc of base plus r**(modulus_len + exp_len)
             + base_pow.expr() * modulus_len.memory_rlc()
             + base_pow.expr() * r_pow_32 * exp_len.memory_rlc()
             + base_pow.expr() * r_pow_64 * base_len.memory_rlc();
 
-        // println!("phase 2 cell used {}",
-        //     padding_pow.phase2_cell_cost() + [&modulus_pow, &exp_pow,
-        // &base_pow].iter().map(|pw|pw.phase2_cell_cost()).sum::<usize>() );
-
         Self {
             base_len,
             modulus_len,
@@ -359,10 +354,10 @@ impl<F: Field> ModExpInputs<F> {
             exp,
             input_valid,
             input_bytes_rlc,
+            base_len_expected,
+            exp_len_expected,
+            modulus_len_expected,
             input_len_expected,
-            base_limbs,
-            exp_limbs,
-            modulus_limbs,
         }
     }
 
@@ -414,16 +409,9 @@ impl<F: Field> ModExpInputs<F> {
             linked_v = Some(assigned);
         }
 
-        for (val_r, input_limbs) in
-            values
-                .iter()
-                .zip([&self.base_limbs, &self.exp_limbs, &self.modulus_limbs])
-        {
-            input_limbs.assign(region, offset, val_r)?;
-        }
-
-        for (val, input_bytes) in values.zip([&self.base, &self.exp, &self.modulus]) {
-            assign_word(region, offset, input_bytes, val)?;
+        let randomness = region.challenges().keccak_input();
+        for (val, input_rlc) in values.iter().zip([&self.base, &self.exp, &self.modulus]) {
+            input_rlc.assign(region, offset, rlc_bytes_rev(val, randomness))?;
         }
 
         Ok(())
@@ -432,10 +420,9 @@ impl<F: Field> ModExpInputs<F> {
 
 #[derive(Clone, Debug)]
 struct ModExpOutputs<F> {
-    result: Word<F>,
+    result: Cell<F>,
     is_result_zero: IsZeroGadget<F>,
     output_bytes_rlc: Expression<F>,
-    pub result_limbs: Limbs<F>,
 }
 
 impl<F: Field> ModExpOutputs<F> {
@@ -449,20 +436,14 @@ impl<F: Field> ModExpOutputs<F> {
             IsZeroGadget::construct(cb, output_len)
         });
 
-        let result = cb.query_bytes();
-        let result_limbs = Limbs::configure(cb, &result);
+        let result = cb.query_cell_phase2();
 
-        let output_bytes_rlc = util::select::expr(
-            is_result_zero.expr(),
-            0.expr(),
-            rlc_word_rev(&result, cb.challenges().keccak_input()),
-        );
+        let output_bytes_rlc = util::select::expr(is_result_zero.expr(), 0.expr(), result.expr());
 
         Self {
             result,
             is_result_zero,
             output_bytes_rlc,
-            result_limbs,
         }
     }
 
@@ -482,83 +463,25 @@ impl<F: Field> ModExpOutputs<F> {
     ) -> Result<(), Error> {
         self.is_result_zero
             .assign(region, offset, F::from(output_len as u64))?;
-        self.result_limbs.assign(region, offset, &data)?;
-        assign_word(region, offset, &self.result, data)?;
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug)]
-pub(crate) struct Limbs<F> {
-    byte14_split_lo: Cell<F>,
-    byte14_split_hi: Cell<F>,
-    limbs: [Expression<F>; 3],
-}
-
-impl<F: Field> Limbs<F> {
-    pub fn configure(cb: &mut EVMConstraintBuilder<F>, word: &Word<F>) -> Self {
-        let byte14_split_lo = cb.query_byte();
-        let byte14_split_hi = cb.query_byte();
-
-        cb.require_equal(
-            "split 14th byte in word into half",
-            word[MODEXP_SIZE_LIMIT - 14].expr(),
-            byte14_split_lo.expr() + byte14_split_hi.expr(),
-        );
-
-        let inv_16 = Expression::Constant(F::from(16u64).invert().unwrap());
-
-        let limbs = [
-            expr_from_bytes(
-                &std::iter::once(&byte14_split_lo)
-                    .chain(&word[MODEXP_SIZE_LIMIT - 13..])
-                    .collect::<Vec<_>>(),
-            ),
-            expr_from_bytes(
-                &word[MODEXP_SIZE_LIMIT - 27..MODEXP_SIZE_LIMIT - 14]
-                    .iter()
-                    .chain(std::iter::once(&byte14_split_hi))
-                    .collect::<Vec<_>>(),
-            ) * inv_16,
-            expr_from_bytes(&word[..MODEXP_SIZE_LIMIT - 27]),
-        ];
-
-        Self {
-            byte14_split_hi,
-            byte14_split_lo,
-            limbs,
-        }
-    }
-
-    pub fn limbs(&self) -> [Expression<F>; 3] {
-        self.limbs.clone()
-    }
-
-    pub fn assign(
-        &self,
-        region: &mut CachedRegion<'_, '_, F>,
-        offset: usize,
-        big_int: &[u8; MODEXP_SIZE_LIMIT],
-    ) -> Result<(), Error> {
-        let byte14_lo = big_int[MODEXP_SIZE_LIMIT - 14] & 0xf;
-        let byte14_hi = big_int[MODEXP_SIZE_LIMIT - 14] & 0xf0;
-
-        self.byte14_split_lo
-            .assign(region, offset, Value::known(F::from(byte14_lo as u64)))?;
-        self.byte14_split_hi
-            .assign(region, offset, Value::known(F::from(byte14_hi as u64)))?;
+        self.result.assign(
+            region,
+            offset,
+            rlc_bytes_rev(data, region.challenges().keccak_input()),
+        )?;
         Ok(())
     }
 }
 
+/// The gas cost of modexp specified in EIP-2565, where the iteration count
+/// `max(adjusted_exp_len, 1)` is calculated as
+/// `max(exp_bit_len + 255, 8 * exp_len, 257) - 256`, which covers both cases of exponent no
+/// longer and longer than 32 bytes.
 #[derive(Clone, Debug)]
 pub(crate) struct ModExpGasCost<F> {
-    max_length: MinMaxGadget<F, 1>,
-    words: ConstantDivisionGadget<F, 1>,
-    exp_is_zero: IsZeroGadget<F>,
-    exp_byte_size: ByteSizeGadget<F>,
-    exp_msb_bit_length: BitLengthGadget<F>,
-    exp_msb: BinaryNumberGadget<F, N_BITS_U8>,
+    max_length: MinMaxGadget<F, 2>,
+    words: ConstantDivisionGadget<F, 2>,
+    adjusted_exp_len: MinMaxGadget<F, 2>,
+    iteration_count: MinMaxGadget<F, 2>,
     calc_gas: ConstantDivisionGadget<F, N_BYTES_U64>,
     dynamic_gas: MinMaxGadget<F, N_BYTES_U64>,
 }
@@ -566,56 +489,24 @@ pub(crate) struct ModExpGasCost<F> {
 impl<F: Field> ModExpGasCost<F> {
     fn construct(
         cb: &mut EVMConstraintBuilder<F>,
-        b_size: &SizeRepresent<F>,
-        exp: &[Cell<F>; MODEXP_SIZE_LIMIT],
-        m_size: &SizeRepresent<F>,
+        b_size: Expression<F>,
+        e_size: Expression<F>,
+        m_size: Expression<F>,
+        exp_bit_len: Expression<F>,
     ) -> Self {
-        let max_length = MinMaxGadget::construct(cb, b_size.value(), m_size.value());
+        let max_length = MinMaxGadget::construct(cb, b_size, m_size);
         let words = ConstantDivisionGadget::construct(cb, max_length.max() + 7.expr(), 8);
         let multiplication_complexity = words.quotient() * words.quotient();
-        let exp_is_zero = IsZeroGadget::construct(
-            cb,
-            rlc::expr(&exp.clone().map(|c| c.expr()), cb.challenges().evm_word()),
-        );
 
-        let (exp_byte_size, exp_msb, exp_msb_bit_length) =
-            cb.condition(not::expr(exp_is_zero.expr()), |cb| {
-                let exp_byte_size = ByteSizeGadget::construct(
-                    cb,
-                    exp.iter()
-                        .rev()
-                        .map(Expr::expr)
-                        .collect::<Vec<Expression<F>>>()
-                        .try_into()
-                        .unwrap(),
-                );
-                let exp_msb =
-                    BinaryNumberGadget::construct(cb, exp_byte_size.most_significant_value.expr());
-                let exp_msb_bit_length = BitLengthGadget::construct(
-                    cb,
-                    exp_msb
-                        .bits
-                        .iter()
-                        .rev()
-                        .map(Expr::expr)
-                        .collect::<Vec<Expression<F>>>()
-                        .try_into()
-                        .unwrap(),
-                );
-                (exp_byte_size, exp_msb, exp_msb_bit_length)
-            });
-        let exp_bit_length =
-            (exp_byte_size.size() - 1.expr()) * N_BITS_U8.expr() + exp_msb_bit_length.size();
-
-        // We already restrict Esize <= 32. So we can completely ignore the branch concerning
-        // Esize > 32. We only care about whether or not exponent is zero.
-        let iteration_count = select::expr(
-            exp_is_zero.expr(),
-            0.expr(),
-            exp_bit_length.expr() - 1.expr(),
+        let adjusted_exp_len =
+            MinMaxGadget::construct(cb, exp_bit_len + 255.expr(), e_size * 8.expr());
+        let iteration_count = MinMaxGadget::construct(cb, adjusted_exp_len.max(), 257.expr());
+
+        let calc_gas = ConstantDivisionGadget::construct(
+            cb,
+            multiplication_complexity * (iteration_count.max() - 256.expr()),
+            3,
         );
-        let calc_gas =
-            ConstantDivisionGadget::construct(cb, multiplication_complexity * iteration_count, 3);
         let dynamic_gas = MinMaxGadget::construct(
             cb,
             GasCost::PRECOMPILE_MODEXP_MIN.expr(),
@@ -625,10 +516,8 @@ impl<F: Field> ModExpGasCost<F> {
         Self {
             max_length,
             words,
-            exp_is_zero,
-            exp_byte_size,
-            exp_msb,
-            exp_msb_bit_length,
+            adjusted_exp_len,
+            iteration_count,
             calc_gas,
             dynamic_gas,
         }
@@ -638,65 +527,39 @@ impl<F: Field> ModExpGasCost<F> {
         &self,
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
-        b_size: &U256,
-        m_size: &U256,
-        exponent: &[u8; MODEXP_SIZE_LIMIT],
+        b_size: u64,
+        e_size: u64,
+        m_size: u64,
+        exp_bit_len: u64,
     ) -> Result<u64, Error> {
-        let mut base_len = [0u8; 32];
-        base_len[(32 - SIZE_REPRESENT_BYTES)..]
-            .copy_from_slice(&b_size.to_be_bytes()[(32 - SIZE_REPRESENT_BYTES)..]);
-        let mut mod_len = [0u8; 32];
-        mod_len[(32 - SIZE_REPRESENT_BYTES)..]
-            .copy_from_slice(&m_size.to_be_bytes()[(32 - SIZE_REPRESENT_BYTES)..]);
-        let b_size = U256::from_big_endian(&base_len);
-        let m_size = U256::from_big_endian(&mod_len);
-
-        self.max_length.assign(
-            region,
-            offset,
-            b_size.to_scalar().expect("Bsize is within scalar field"),
-            m_size.to_scalar().expect("Msize is within scalar field"),
-        )?;
-        self.words
-            .assign(region, offset, b_size.max(m_size).as_u128() + 7u128)?;
-        let exp_word = U256::from_big_endian(exponent);
-        self.exp_is_zero.assign_value(
+        self.max_length
+            .assign(region, offset, F::from(b_size), F::from(m_size))?;
+        let max_length = b_size.max(m_size);
+        let (words, _) = self
+            .words
+            .assign(region, offset, (max_length + 7) as u128)?;
+        let multiplication_complexity = (words * words) as u64;
+
+        self.adjusted_exp_len.assign(
             region,
             offset,
-            region
-                .challenges()
-                .evm_word()
-                .map(|r| rlc::value(exponent, r)),
+            F::from(exp_bit_len + 255),
+            F::from(e_size * 8),
         )?;
-        self.exp_byte_size
-            .assign(region, offset, ByteOrWord::Word(exp_word))?;
-        let exp_byte_size = (exp_word.bits() + 7) / 8;
-        let exp_msb = if exp_byte_size > 0 {
-            exponent[N_BYTES_WORD - exp_byte_size]
-        } else {
-            0
-        };
-        self.exp_msb.assign(region, offset, exp_msb)?;
-        self.exp_msb_bit_length
-            .assign(region, offset, ByteOrWord::Byte(exp_msb))?;
-        let exp_bit_length = exp_word.bits();
-        let max_length = b_size.max(m_size);
-        let words = (max_length + 7) / 8;
-        let multiplication_complexity = words * words;
-        let iteration_count = if exp_word.is_zero() {
-            0
-        } else {
-            exp_bit_length - 1
-        };
+        let adjusted_exp_len = (exp_bit_len + 255).max(e_size * 8);
+        self.iteration_count
+            .assign(region, offset, F::from(adjusted_exp_len), F::from(257))?;
+        let iteration_count = adjusted_exp_len.max(257) - 256;
+
         let numerator = multiplication_complexity * iteration_count;
-        self.calc_gas.assign(region, offset, numerator.as_u128())?;
+        self.calc_gas.assign(region, offset, numerator as u128)?;
         self.dynamic_gas.assign(
             region,
             offset,
             F::from(GasCost::PRECOMPILE_MODEXP_MIN.0),
-            F::from((numerator / 3).as_u64()),
+            F::from(numerator / 3),
         )?;
-        let gas_cost = std::cmp::max(GasCost::PRECOMPILE_MODEXP_MIN.0, (numerator / 3).as_u64());
+        let gas_cost = std::cmp::max(GasCost::PRECOMPILE_MODEXP_MIN.0, numerator / 3);
 
         Ok(gas_cost)
     }
@@ -708,9 +571,6 @@ pub struct ModExpGadget<F> {
     output_bytes_rlc: Cell<F>,
     return_bytes_rlc: Cell<F>,
 
-    pad_right: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
-    padding: PaddingGadget<F>,
-
     is_success: Cell<F>,
     callee_address: Cell<F>,
     caller_id: Cell<F>,
@@ -721,13 +581,13 @@ pub struct ModExpGadget<F> {
     restore_context_gadget: RestoreContextGadget<F>,
 
     input: ModExpInputs<F>,
+    input_copy_len: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
     padding_zero: RandPowRepresent<F, INPUT_REPRESENT_BITS>,
     output: ModExpOutputs<F>,
 
-    input_bytes_acc: Cell<F>,
+    exp_bit_len: Cell<F>,
     is_gas_insufficient: LtGadget<F, N_BYTES_U64>,
     gas_cost_gadget: ModExpGasCost<F>,
-    garbage_bytes_holder: [Cell<F>; INPUT_LIMIT - 96],
 }
 
 impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
@@ -741,8 +601,6 @@ impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
             cb.query_cell_phase2(),
             cb.query_cell_phase2(),
         );
-        // we 'copy' the acc_bytes cell inside call_op step, so it must be the first query cells
-        let input_bytes_acc = cb.query_cell_phase2();
 
         let [is_success, callee_address, caller_id, call_data_offset, call_data_length, return_data_offset, return_data_length] =
             [
@@ -763,15 +621,31 @@ impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
         );
 
         let input = ModExpInputs::configure(cb);
+
+        // only the first `min(call_data_length, input_len_expected)` bytes are copied to the
+        // precompile, the rest of the expected input being zero.
+        let input_copy_len =
+            MinMaxGadget::construct(cb, call_data_length.expr(), input.len_expected());
         let padding_zero = RandPowRepresent::configure(
             cb,
             cb.challenges().keccak_input(),
-            MODEXP_INPUT_LIMIT.expr() - input.len_expected(),
+            input.len_expected() - input_copy_len.min(),
             None,
         );
+        cb.require_equal(
+            "input bytes with padding must equal",
+            padding_zero.expr() * input_bytes_rlc.expr(),
+            input.bytes_rlc(),
+        );
 
-        let gas_cost_gadget =
-            ModExpGasCost::construct(cb, &input.base_len, &input.exp, &input.modulus_len);
+        let exp_bit_len = cb.query_cell();
+        let gas_cost_gadget = ModExpGasCost::construct(
+            cb,
+            input.base_len_expected.clone(),
+            input.exp_len_expected.clone(),
+            input.modulus_len_expected.clone(),
+            exp_bit_len.expr(),
+        );
         let is_gas_insufficient = LtGadget::construct(
             cb,
             cb.curr.state.gas_left.expr(),
@@ -787,32 +661,18 @@ impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
             call_success.clone(),
         );
 
-        let output = ModExpOutputs::configure(
-            cb,
-            //FIXME: there may be still some edge cases lead to nil output (even modulus_len is
-            // not 0)
-            call_success,
-            input.modulus_len(),
-        );
+        let output = ModExpOutputs::configure(cb, call_success, input.modulus_len());
 
-        cb.condition(util::not::expr(output.is_nil()), |cb| {
+        cb.condition(input.is_valid(), |cb| {
             cb.modexp_table_lookup(
-                input.base_limbs.limbs(),
-                input.exp_limbs.limbs(),
-                input.modulus_limbs.limbs(),
-                output.result_limbs.limbs(),
+                input.base.expr(),
+                input.exp.expr(),
+                exp_bit_len.expr(),
+                input.modulus.expr(),
+                output.result.expr(),
             );
         });
 
-        let garbage_bytes_holder = cb.query_bytes();
-
-        cb.require_equal(
-            "input acc bytes with padding must equal",
-            input_bytes_acc.expr(),
-            padding_zero.expr() * input.bytes_rlc()
-                + rlc_rev(&garbage_bytes_holder, cb.challenges().keccak_input()),
-        );
-
         cb.require_equal(
             "output acc bytes must equal",
             output_bytes_rlc.expr(),
@@ -825,29 +685,6 @@ impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
             cb.curr.state.gas_left.expr(),
         );
 
-        let required_input_len = 192.expr();
-        let pad_right = LtGadget::construct(cb, call_data_length.expr(), required_input_len.expr());
-        let padding = cb.condition(pad_right.expr(), |cb| {
-            PaddingGadget::construct(
-                cb,
-                input_bytes_rlc.expr(),
-                call_data_length.expr(),
-                required_input_len,
-            )
-        });
-        cb.condition(not::expr(pad_right.expr()), |cb| {
-            cb.require_equal(
-                "no padding implies padded bytes == input bytes",
-                padding.padded_rlc(),
-                input_bytes_rlc.expr(),
-            );
-        });
-        cb.require_equal(
-            "copy padded input bytes",
-            padding.padded_rlc(),
-            input_bytes_acc.expr(),
-        );
-
         let restore_context_gadget = RestoreContextGadget::construct2(
             cb,
             is_success.expr(),
@@ -864,9 +701,6 @@ impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
             output_bytes_rlc,
             return_bytes_rlc,
 
-            pad_right,
-            padding,
-
             is_success,
             callee_address,
             caller_id,
@@ -876,12 +710,12 @@ impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
             return_data_length,
             restore_context_gadget,
             input,
+            input_copy_len,
             padding_zero,
             output,
-            input_bytes_acc,
+            exp_bit_len,
             is_gas_insufficient,
             gas_cost_gadget,
-            garbage_bytes_holder,
         }
     }
 
@@ -896,78 +730,62 @@ impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
     ) -> Result<(), Error> {
         if let Some(PrecompileAuxData::Modexp(data)) = &step.aux_data {
             self.input
-                .assign(region, offset, (data.valid, data.input_lens, data.inputs))?;
-
-            let input_expected_len = 96
-                + if data.valid {
-                    data.input_lens.iter().map(U256::as_usize).sum::<usize>()
-                } else {
-                    0
-                };
-
-            let garbage_bytes = if call.call_data_length as usize > input_expected_len {
-                let mut bts = Vec::new();
-                bts.resize(input_expected_len - 96, 0); //front prefix zero
-                bts.append(&mut Vec::from(&data.input_bytes[input_expected_len..]));
-                bts.resize(96, 0); //padding zero
-                bts
+                .assign(region, offset, (data.valid, data.input_lens, &data.inputs))?;
+
+            let [base_len, exp_len, modulus_len] = if data.valid {
+                data.input_lens.map(|len| len.as_u64())
             } else {
-                Vec::from([0u8; 96])
+                [0; 3]
             };
-
-            self.padding_zero
-                .assign(region, offset, INPUT_LIMIT - input_expected_len, None)?;
-
-            for (cell, bt) in self.garbage_bytes_holder.iter().zip(garbage_bytes) {
-                cell.assign(region, offset, Value::known(F::from(bt as u64)))?;
-            }
+            let input_expected_len = 96 + base_len + exp_len + modulus_len;
+            debug_assert!(
+                input_expected_len as usize
+                    <= modexp_input_limit(block.circuits_params.max_modexp_size)
+            );
+            let input_copy_len = call.call_data_length.min(input_expected_len);
+            self.input_copy_len.assign(
+                region,
+                offset,
+                F::from(call.call_data_length),
+                F::from(input_expected_len),
+            )?;
+            self.padding_zero.assign(
+                region,
+                offset,
+                (input_expected_len - input_copy_len) as usize,
+                None,
+            )?;
 
             self.output
-                .assign(region, offset, (data.output_len, data.output))?;
+                .assign(region, offset, (data.output_len, &data.output))?;
 
-            let input_rlc = region
-                .challenges()
-                .keccak_input()
-                .map(|randomness| rlc::value(data.input_bytes.iter().rev(), randomness));
-
-            self.input_bytes_rlc.assign(region, offset, input_rlc)?;
+            let randomness = region.challenges().keccak_input();
+            self.input_bytes_rlc.assign(
+                region,
+                offset,
+                rlc_bytes_rev(&data.input_bytes, randomness),
+            )?;
+            self.output_bytes_rlc.assign(
+                region,
+                offset,
+                rlc_bytes_rev(&data.output_bytes, randomness),
+            )?;
             self.return_bytes_rlc.assign(
                 region,
                 offset,
-                region
-                    .challenges()
-                    .keccak_input()
-                    .map(|r| rlc::value(data.return_bytes.iter().rev(), r)),
+                rlc_bytes_rev(&data.return_bytes, randomness),
             )?;
 
-            // if the input to modexp has more than 192 bytes, then we only keep the first 192 bytes
-            // and discard the remaining bytes
-            let input_len_limit = INPUT_LIMIT as u64;
-            let n_padded_zeros = if call.call_data_length > input_len_limit {
-                0
-            } else {
-                input_len_limit - call.call_data_length
-            };
-            let n_padded_zeroes_pow = region
-                .challenges()
-                .keccak_input()
-                .map(|r| r.pow(&[n_padded_zeros, 0, 0, 0]));
-
-            let output_rlc = region
-                .challenges()
-                .keccak_input()
-                .map(|randomness| rlc::value(data.output_bytes.iter().rev(), randomness));
-
-            self.input_bytes_acc
-                .assign(region, offset, n_padded_zeroes_pow * input_rlc)?;
-            self.output_bytes_rlc.assign(region, offset, output_rlc)?;
-
+            let exp_bit_len = bit_len(&data.inputs[1]) as u64;
+            self.exp_bit_len
+                .assign(region, offset, Value::known(F::from(exp_bit_len)))?;
             let required_gas_cost = self.gas_cost_gadget.assign(
                 region,
                 offset,
-                &data.input_lens[0],
-                &data.input_lens[2],
-                &data.inputs[1],
+                base_len,
+                exp_len,
+                modulus_len,
+                exp_bit_len,
             )?;
             self.is_gas_insufficient.assign(
                 region,
@@ -975,19 +793,6 @@ impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
                 F::from(step.gas_left),
                 F::from(required_gas_cost),
             )?;
-            self.pad_right
-                .assign(region, offset, call.call_data_length.into(), 192.into())?;
-            self.padding.assign(
-                region,
-                offset,
-                PrecompileCalls::Modexp,
-                region
-                    .challenges()
-                    .keccak_input()
-                    .map(|r| rlc::value(data.input_bytes.iter().rev(), r)),
-                call.call_data_length,
-                region.challenges().keccak_input(),
-            )?;
         } else {
             log::error!("unexpected aux_data {:?} for modexp", step.aux_data);
             return Err(Error::Synthesis);
@@ -1036,8 +841,9 @@ impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
 mod test {
     use super::*;
     use bus_mapping::{
+        circuit_input_builder::CircuitsParams,
         evm::{OpcodeId, PrecompileCallArgs},
-        precompile::PrecompileCalls,
+        precompile::{PrecompileCalls, DEFAULT_MODEXP_SIZE_LIMIT},
     };
     use eth_types::{bytecode, word, ToWord};
     use itertools::Itertools;
@@ -1045,64 +851,6 @@ mod test {
 
     use crate::test_util::CircuitTestBuilder;
 
-    #[test]
-    fn test_limbs() {
-        use crate::table::ModExpTable;
-        use halo2_proofs::{arithmetic::FieldExt, halo2curves::bn256::Fr};
-        use misc_precompiled_circuit::circuits::modexp::Number;
-        use num_bigint::BigUint;
-
-        // simply take an hash for test
-        let bi = BigUint::parse_bytes(
-            b"fcb51a0695d8f838b1ee009b3fbf66bda078cd64590202a864a8f3e8c4315c47",
-            16,
-        )
-        .unwrap();
-        let n = Number::<Fr>::from_bn(&bi);
-        let w = word!("0xfcb51a0695d8f838b1ee009b3fbf66bda078cd64590202a864a8f3e8c4315c47");
-        let mut bytes = [0u8; 32];
-        w.to_big_endian(&mut bytes);
-        assert_eq!(BigUint::from_bytes_be(&bytes), bi);
-
-        let byte14_lo = bytes[MODEXP_SIZE_LIMIT - 14] & 0xf;
-        let byte14_hi = bytes[MODEXP_SIZE_LIMIT - 14] & 0xf0;
-
-        let limb0: Fr = U256::from_big_endian(
-            &(std::iter::once(byte14_lo))
-                .chain(bytes[MODEXP_SIZE_LIMIT - 13..].iter().copied())
-                .collect::<Vec<_>>(),
-        )
-        .to_scalar()
-        .unwrap();
-
-        let limb1: Fr = U256::from_big_endian(
-            &bytes[MODEXP_SIZE_LIMIT - 27..MODEXP_SIZE_LIMIT - 14]
-                .iter()
-                .copied()
-                .chain(std::iter::once(byte14_hi))
-                .collect::<Vec<_>>(),
-        )
-        .to_scalar()
-        .unwrap();
-
-        let limb2: Fr = U256::from_big_endian(&bytes[..MODEXP_SIZE_LIMIT - 27])
-            .to_scalar()
-            .unwrap();
-
-        assert_eq!(limb0, n.limbs[0].value);
-        assert_eq!(limb1, n.limbs[1].value * Fr::from(16_u64));
-        assert_eq!(limb2, n.limbs[2].value);
-
-        let nt: Fr = ModExpTable::native_u256(&w);
-
-        let table_split = ModExpTable::split_u256_108bit_limbs(&w);
-        assert_eq!(Fr::from_u128(table_split[0]), n.limbs[0].value);
-        assert_eq!(Fr::from_u128(table_split[1]), n.limbs[1].value);
-        assert_eq!(Fr::from_u128(table_split[2]), n.limbs[2].value);
-        assert_eq!(nt, n.limbs[3].value);
-        //Limb::new(None, value)
-    }
-
     lazy_static::lazy_static! {
         static ref TEST_VECTOR: Vec<PrecompileCallArgs> = {
             vec![
@@ -1471,6 +1219,120 @@ mod test {
             ]
         };
 
+        static ref TEST_LONG_VECTOR: Vec<PrecompileCallArgs> = {
+            vec![
+                PrecompileCallArgs {
+                    name: "modexp 512-bit base and modulus",
+                    setup_code: bytecode! {
+                        // Base size
+                        PUSH1(0x40)
+                        PUSH1(0x00)
+                        MSTORE
+                        // Esize
+                        PUSH1(0x3)
+                        PUSH1(0x20)
+                        MSTORE
+                        // Msize
+                        PUSH1(0x40)
+                        PUSH1(0x40)
+                        MSTORE
+                        // B
+                        PUSH32(word!("0xc1b7f27d5a1e6f0d2c9a3b8e4f61d07a9b2c3d4e5f60718293a4b5c6d7e8f901"))
+                        PUSH1(0x60)
+                        MSTORE
+                        PUSH32(word!("0x0fedcba9876543210123456789abcdef0fedcba9876543210123456789abcdef"))
+                        PUSH1(0x80)
+                        MSTORE
+                        // E
+                        PUSH32(word!("0x0100010000000000000000000000000000000000000000000000000000000000"))
+                        PUSH1(0xa0)
+                        MSTORE
+                        // M
+                        PUSH32(word!("0xf3a1c5e7092b4d6f8a0c2e4163859ba7dcfe1032547698badcfe103254769801"))
+                        PUSH1(0xa3)
+                        MSTORE
+                        PUSH32(word!("0xe5d4c3b2a1908f7e6d5c4b3a29180f1e2d3c4b5a69788796a5b4c3d2e1f00f1b"))
+                        PUSH1(0xc3)
+                        MSTORE
+                    },
+                    call_data_offset: 0x0.into(),
+                    call_data_length: 0xe3.into(),
+                    ret_offset: 0xe3.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Modexp.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "modexp 512-bit base and modulus with partial input",
+                    setup_code: bytecode! {
+                        // Base size
+                        PUSH1(0x40)
+                        PUSH1(0x00)
+                        MSTORE
+                        // Esize
+                        PUSH1(0x3)
+                        PUSH1(0x20)
+                        MSTORE
+                        // Msize
+                        PUSH1(0x40)
+                        PUSH1(0x40)
+                        MSTORE
+                        // B
+                        PUSH32(word!("0xc1b7f27d5a1e6f0d2c9a3b8e4f61d07a9b2c3d4e5f60718293a4b5c6d7e8f901"))
+                        PUSH1(0x60)
+                        MSTORE
+                        PUSH32(word!("0x0fedcba9876543210123456789abcdef0fedcba9876543210123456789abcdef"))
+                        PUSH1(0x80)
+                        MSTORE
+                        // E
+                        PUSH32(word!("0x0100010000000000000000000000000000000000000000000000000000000000"))
+                        PUSH1(0xa0)
+                        MSTORE
+                        // M
+                        PUSH32(word!("0xf3a1c5e7092b4d6f8a0c2e4163859ba7dcfe1032547698badcfe103254769801"))
+                        PUSH1(0xa3)
+                        MSTORE
+                    },
+                    call_data_offset: 0x0.into(),
+                    call_data_length: 0xb0.into(),
+                    ret_offset: 0xe3.into(),
+                    ret_size: 0x40.into(),
+                    address: PrecompileCalls::Modexp.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "modexp exponent longer than 32 bytes",
+                    setup_code: bytecode! {
+                        // Base size
+                        PUSH1(0x1)
+                        PUSH1(0x00)
+                        MSTORE
+                        // Esize
+                        PUSH1(0x21)
+                        PUSH1(0x20)
+                        MSTORE
+                        // Msize
+                        PUSH1(0x1)
+                        PUSH1(0x40)
+                        MSTORE
+                        // B, E and M
+                        PUSH32(word!("0x08ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff12"))
+                        PUSH1(0x60)
+                        MSTORE
+                        PUSH32(word!("0x3405000000000000000000000000000000000000000000000000000000000000"))
+                        PUSH1(0x80)
+                        MSTORE
+                    },
+                    call_data_offset: 0x0.into(),
+                    call_data_length: 0x83.into(),
+                    ret_offset: 0xa0.into(),
+                    ret_size: 0x01.into(),
+                    address: PrecompileCalls::Modexp.address().to_word(),
+                    ..Default::default()
+                },
+            ]
+        };
+
         static ref TEST_INVALID_VECTOR: Vec<PrecompileCallArgs> = {
             vec![
                 PrecompileCallArgs {
@@ -1485,7 +1347,7 @@ mod test {
                         PUSH1(0x20)
                         MSTORE
                         // Msize
-                        PUSH1(0x21)
+                        PUSH2(0x0201)
                         PUSH1(0x40)
                         MSTORE
                         // B, E and M
@@ -1505,15 +1367,15 @@ mod test {
                     name: "modexp Base&Esize&Msize length too large invalid",
                     setup_code: bytecode! {
                         // Base size
-                        PUSH1(0x21)
+                        PUSH2(0x0201)
                         PUSH1(0x00)
                         MSTORE
                         // Esize
-                        PUSH1(0x21)
+                        PUSH2(0x0201)
                         PUSH1(0x20)
                         MSTORE
                         // Msize
-                        PUSH1(0x21)
+                        PUSH2(0x0201)
                         PUSH1(0x40)
                         MSTORE
                         // B, E and M
@@ -1591,6 +1453,35 @@ mod test {
         }
     }
 
+    #[test]
+    fn precompile_modexp_test_long() {
+        for test_vector in TEST_LONG_VECTOR.iter() {
+            let bytecode = test_vector.with_call_op(OpcodeId::STATICCALL);
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .run();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Synthesis")]
+    fn precompile_modexp_test_size_limit_mismatch() {
+        // the test circuit is configured with the default limit, so a block built with another
+        // limit can't be synthesized
+        let bytecode = TEST_VECTOR[0].with_call_op(OpcodeId::STATICCALL);
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .params(CircuitsParams {
+            max_modexp_size: DEFAULT_MODEXP_SIZE_LIMIT / 2,
+            ..Default::default()
+        })
+        .run();
+    }
+
     // notice, "invalid" test would not actuall work until bus-mapping put calling fail case being
     // handle in normal CallOp, i.e. return None in
     // bus_mapping::circuit_input_builder::input_state_ref::CircuitInputStateRef::get_step_err
@@ -0,0 +1,569 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+        step::ExecutionState,
+        util::{
+            common_gadget::{IsForkActiveGadget, RestoreContextGadget, TransferToGadget},
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, Same, To},
+            },
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget, RangeCheckGadget},
+            not, select, CachedRegion, Cell, StepRws, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+use either::Either;
+use eth_types::{
+    evm_types::{Fork, GasCost, OpcodeId},
+    Field, ToAddress, ToLittleEndian, ToScalar,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for SELFDESTRUCT. The whole balance of the current account is
+/// transferred to the beneficiary (burnt when the beneficiary is the account
+/// itself), and the account is deleted by resetting its nonce and setting its
+/// code hash to 0. After Cancun (EIP-6780), the account is only deleted if it is
+/// created in the same tx, otherwise its balance is kept when the beneficiary
+/// is the account itself. The current call then ends successfully.
+#[derive(Clone, Debug)]
+pub(crate) struct SelfdestructGadget<F> {
+    opcode: Cell<F>,
+    beneficiary_word: Word<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    is_warm: Cell<F>,
+    beneficiary_code_hash: Cell<F>,
+    beneficiary_not_exists: IsZeroGadget<F>,
+    is_self: IsEqualGadget<F>,
+    balance: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    is_created: Cell<F>,
+    is_cancun: IsForkActiveGadget<F>,
+    transfer: TransferToGadget<F>,
+    nonce: Cell<F>,
+    code_hash: Cell<F>,
+    sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for SelfdestructGadget<F> {
+    const NAME: &'static str = "SELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SELFDESTRUCT;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        // We do the responsible opcode check explicitly here because we're not using
+        // the `SameContextGadget` for `SELFDESTRUCT`.
+        cb.require_equal(
+            "Opcode should be SELFDESTRUCT",
+            opcode.expr(),
+            OpcodeId::SELFDESTRUCT.expr(),
+        );
+
+        let beneficiary_word = cb.query_word_rlc();
+        let beneficiary = from_bytes::expr(&beneficiary_word.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        cb.stack_pop(beneficiary_word.expr());
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+        // Call ends with SELFDESTRUCT must be successful
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 1.expr());
+        let mut reversion_info = cb.reversion_info_read(None);
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            beneficiary.expr(),
+            1.expr(),
+            is_warm.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // For non-existing accounts the code_hash must be 0 in the rw_table.
+        let beneficiary_code_hash = cb.query_cell_phase2();
+        cb.account_read(
+            beneficiary.expr(),
+            AccountFieldTag::CodeHash,
+            beneficiary_code_hash.expr(),
+        );
+        let beneficiary_not_exists = IsZeroGadget::construct(cb, beneficiary_code_hash.expr());
+        let is_self = IsEqualGadget::construct(cb, beneficiary.expr(), callee_address.expr());
+
+        // After Cancun, the account is only deleted if it's created in this tx.
+        let is_created = cb.query_bool();
+        cb.account_created_read(tx_id.expr(), callee_address.expr(), is_created.expr());
+        let is_cancun = IsForkActiveGadget::construct(cb, Fork::Cancun);
+        let is_deleted = not::expr(is_cancun.expr() * not::expr(is_created.expr()));
+
+        // The whole balance is removed from the current account, it's only
+        // credited to the beneficiary when it's another account, and kept when
+        // the beneficiary is the account itself which isn't deleted.
+        let balance = cb.query_word_rlc();
+        let value_is_zero = IsZeroGadget::construct(cb, balance.expr());
+        cb.account_write(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            not::expr(is_deleted.expr()) * is_self.expr() * balance.expr(),
+            balance.expr(),
+            Some(&mut reversion_info),
+        );
+        let transfer = cb.condition(not::expr(is_self.expr()), |cb| {
+            TransferToGadget::construct_with_is_zero(
+                cb,
+                beneficiary.expr(),
+                not::expr(beneficiary_not_exists.expr()),
+                0.expr(),
+                beneficiary_code_hash.expr(),
+                balance.clone(),
+                Either::Right(value_is_zero.expr()),
+                Some(&mut reversion_info),
+            )
+        });
+
+        // Delete the account.
+        let nonce = cb.query_cell();
+        let code_hash = cb.query_cell_phase2();
+        cb.condition(is_deleted.expr(), |cb| {
+            cb.account_write(
+                callee_address.expr(),
+                AccountFieldTag::Nonce,
+                0.expr(),
+                nonce.expr(),
+                Some(&mut reversion_info),
+            );
+            cb.account_write(
+                callee_address.expr(),
+                AccountFieldTag::CodeHash,
+                0.expr(),
+                code_hash.expr(),
+                Some(&mut reversion_info),
+            );
+        });
+
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + select::expr(
+                is_warm.expr(),
+                0.expr(),
+                GasCost::COLD_ACCOUNT_ACCESS.expr(),
+            )
+            + beneficiary_not_exists.expr()
+                * not::expr(value_is_zero.expr())
+                * GasCost::NEW_ACCOUNT.expr();
+        let sufficient_gas_left =
+            RangeCheckGadget::construct(cb, cb.curr.state.gas_left.expr() - gas_cost.expr());
+
+        // access list + balance + nonce and code hash if deleted, plus the
+        // beneficiary creation and balance update.
+        let reversible_write_counter_increase = 2.expr()
+            + 2.expr() * is_deleted
+            + not::expr(is_self.expr())
+                * (not::expr(value_is_zero.expr()) * (1.expr() + beneficiary_not_exists.expr()));
+
+        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to EndTx only when is_root",
+            cb.curr.state.is_root.expr(),
+            is_to_end_tx,
+        );
+
+        // When it's a root call
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(cb.rw_counter_offset()),
+                gas_left: Delta(-gas_cost.expr()),
+                reversible_write_counter: Delta(reversible_write_counter_increase.expr()),
+                end_tx: To(1.expr()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call
+        let restore_context = cb.condition(not::expr(cb.curr.state.is_root.expr()), |cb| {
+            RestoreContextGadget::construct2(
+                cb,
+                true.expr(),
+                gas_cost,
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                reversible_write_counter_increase,
+            )
+        });
+
+        Self {
+            opcode,
+            beneficiary_word,
+            tx_id,
+            callee_address,
+            reversion_info,
+            is_warm,
+            beneficiary_code_hash,
+            beneficiary_not_exists,
+            is_self,
+            balance,
+            value_is_zero,
+            is_created,
+            is_cancun,
+            transfer,
+            nonce,
+            code_hash,
+            sufficient_gas_left,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let mut rws = StepRws::new(block, step);
+
+        let beneficiary = rws.next().stack_value();
+        self.beneficiary_word
+            .assign(region, offset, Some(beneficiary.to_le_bytes()))?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        let callee_address = call
+            .callee_address
+            .to_scalar()
+            .expect("unexpected Address -> Scalar conversion failure");
+        self.callee_address
+            .assign(region, offset, Value::known(callee_address))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        // tx id, callee address, is success, rw counter end of reversion and
+        // is persistent
+        rws.offset_add(5);
+
+        let (_, is_warm) = rws.next().tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm)))?;
+
+        let beneficiary_code_hash = rws.next().account_codehash_pair().0;
+        self.beneficiary_code_hash.assign(
+            region,
+            offset,
+            region.code_hash(beneficiary_code_hash),
+        )?;
+        self.beneficiary_not_exists.assign_value(
+            region,
+            offset,
+            region.code_hash(beneficiary_code_hash),
+        )?;
+        let beneficiary_exists = !beneficiary_code_hash.is_zero();
+
+        let beneficiary_address = beneficiary
+            .to_address()
+            .to_scalar()
+            .expect("unexpected Address -> Scalar conversion failure");
+        self.is_self
+            .assign(region, offset, beneficiary_address, callee_address)?;
+
+        let is_created = !rws.next().transient_storage_value_pair().0.is_zero();
+        self.is_created
+            .assign(region, offset, Value::known(F::from(is_created)))?;
+        let is_cancun = self
+            .is_cancun
+            .assign(region, offset, block, tx.block_number)?;
+        let is_deleted = !is_cancun || is_created;
+
+        let (_, balance) = rws.next().account_balance_pair();
+        self.balance
+            .assign(region, offset, Some(balance.to_le_bytes()))?;
+        self.value_is_zero
+            .assign_value(region, offset, region.word_rlc(balance))?;
+
+        if beneficiary_address != callee_address {
+            self.transfer.assign_from_rws(
+                region,
+                offset,
+                beneficiary_exists,
+                false,
+                balance,
+                &mut rws,
+            )?;
+        }
+
+        if is_deleted {
+            let (_, nonce) = rws.next().account_nonce_pair();
+            self.nonce.assign(
+                region,
+                offset,
+                Value::known(
+                    nonce
+                        .to_scalar()
+                        .expect("unexpected U256 -> Scalar conversion failure"),
+                ),
+            )?;
+            let (_, code_hash) = rws.next().account_codehash_pair();
+            self.code_hash
+                .assign(region, offset, region.code_hash(code_hash))?;
+        }
+
+        self.sufficient_gas_left
+            .assign(region, offset, F::from(step.gas_left - step.gas_cost))?;
+
+        if !call.is_root {
+            let rw_offset = 10
+                + if is_deleted { 2 } else { 0 }
+                + if beneficiary_address != callee_address && !balance.is_zero() {
+                    1 + if beneficiary_exists { 0 } else { 2 }
+                } else {
+                    0
+                };
+            self.restore_context
+                .assign(region, offset, block, call, step, rw_offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{address, bytecode, Address, Bytecode, ToWord, Word};
+    use lazy_static::lazy_static;
+    use mock::{TestContext, MOCK_ACCOUNTS};
+
+    lazy_static! {
+        static ref CALLEE: Address = address!("0x00000000000000000000000000000000000000fe");
+        static ref BENEFICIARY: Address = address!("0xaabbccddee000000000000000000000000000000");
+    }
+
+    fn selfdestruct_code(beneficiary: Address) -> Bytecode {
+        bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        }
+    }
+
+    fn test_root_ok(beneficiary: Address, beneficiary_exists: bool, balance: Word) {
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(*CALLEE)
+                    .balance(balance)
+                    .code(selfdestruct_code(beneficiary));
+                if beneficiary_exists && beneficiary != *CALLEE {
+                    accs[1].address(beneficiary).balance(Word::from(1u64 << 20));
+                } else {
+                    accs[1]
+                        .address(MOCK_ACCOUNTS[2])
+                        .balance(Word::from(1u64 << 20));
+                }
+                accs[2]
+                    .address(address!("0x0000000000000000000000000000000000000123"))
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[2].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(100_000));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_internal_ok(beneficiary: Address, beneficiary_exists: bool, is_reverted: bool) {
+        let mut code_a = bytecode! {
+            PUSH1(0) // retLength
+            PUSH1(0) // retOffset
+            PUSH1(0) // argsLength
+            PUSH1(0) // argsOffset
+            PUSH1(0) // value
+            PUSH20(CALLEE.to_word())
+            PUSH3(60_000) // gas
+            CALL
+        };
+        if is_reverted {
+            code_a.append(&bytecode! {
+                PUSH1(0)
+                PUSH1(0)
+                REVERT
+            });
+        } else {
+            code_a.append(&bytecode! { STOP });
+        }
+
+        let ctx = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(*CALLEE)
+                    .balance(Word::from(1u64 << 20))
+                    .code(selfdestruct_code(beneficiary));
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code_a);
+                if beneficiary_exists && beneficiary != *CALLEE {
+                    accs[2].address(beneficiary).balance(Word::from(1u64 << 20));
+                } else {
+                    accs[2]
+                        .address(MOCK_ACCOUNTS[2])
+                        .balance(Word::from(1u64 << 20));
+                }
+                accs[3]
+                    .address(address!("0x0000000000000000000000000000000000000123"))
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[3].address)
+                    .to(accs[1].address)
+                    .gas(Word::from(200_000));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn selfdestruct_gadget_root_existing_beneficiary() {
+        test_root_ok(*BENEFICIARY, true, Word::from(1u64 << 20));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_root_non_existing_beneficiary() {
+        test_root_ok(*BENEFICIARY, false, Word::from(1u64 << 20));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_root_zero_balance() {
+        test_root_ok(*BENEFICIARY, false, Word::zero());
+        test_root_ok(*BENEFICIARY, true, Word::zero());
+    }
+
+    #[test]
+    fn selfdestruct_gadget_root_to_self() {
+        test_root_ok(*CALLEE, true, Word::from(1u64 << 20));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_internal() {
+        test_internal_ok(*BENEFICIARY, true, false);
+        test_internal_ok(*BENEFICIARY, false, false);
+        test_internal_ok(*CALLEE, true, false);
+    }
+
+    #[test]
+    fn selfdestruct_gadget_internal_reverted() {
+        test_internal_ok(*BENEFICIARY, true, true);
+        test_internal_ok(*BENEFICIARY, false, true);
+    }
+
+    /// The init code self-destructs, so the account is created in the same tx
+    /// and still deleted after Cancun.
+    #[cfg(feature = "cancun")]
+    fn test_created_in_same_tx_ok(beneficiary: Address, is_create_op: bool) {
+        let init_code = selfdestruct_code(beneficiary).code();
+        let factory_code = bytecode! {
+            PUSH32(Word::from_big_endian(&init_code))
+            PUSH1(0)
+            MSTORE
+            PUSH1(init_code.len()) // length
+            PUSH1(32 - init_code.len()) // offset
+            PUSH1(0x10) // value
+            CREATE
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(factory_code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000000123"))
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .value(Word::from(0x10))
+                    .gas(Word::from(200_000));
+                if is_create_op {
+                    txs[0].to(accs[0].address);
+                } else {
+                    txs[0].input(init_code.clone().into());
+                }
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[cfg(feature = "cancun")]
+    #[test]
+    fn selfdestruct_gadget_created_in_same_tx() {
+        test_created_in_same_tx_ok(*BENEFICIARY, false);
+        test_created_in_same_tx_ok(*BENEFICIARY, true);
+    }
+
+    #[cfg(feature = "cancun")]
+    #[test]
+    fn selfdestruct_gadget_created_in_same_tx_to_self() {
+        // The beneficiary is the created account itself, whose address is
+        // unknown in the init code, so use ADDRESS instead.
+        let init_code = bytecode! {
+            ADDRESS
+            SELFDESTRUCT
+        };
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x0000000000000000000000000000000000000123"))
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .value(Word::from(0x10))
+                    .gas(Word::from(200_000))
+                    .input(init_code.code().into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
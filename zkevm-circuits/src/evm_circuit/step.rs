@@ -9,12 +9,13 @@ use crate::{
     witness::Transaction,
 };
 use bus_mapping::{evm::OpcodeId, precompile::PrecompileCalls};
-use eth_types::evm_types::GasCost;
+use eth_types::evm_types::{ForkSchedule, GasCost};
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::Value,
     plonk::{Advice, Column, ConstraintSystem, Error, Expression},
 };
+use serde::{Deserialize, Serialize};
 use std::{fmt::Display, iter};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
@@ -36,7 +37,7 @@ impl From<PrecompileCalls> for ExecutionState {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter, Serialize, Deserialize)]
 pub enum ExecutionState {
     // Internal state
     BeginTx,
@@ -95,6 +96,11 @@ pub enum ExecutionState {
     MSIZE,
     GAS,
     JUMPDEST,
+    TLOAD,
+    TSTORE,
+    MCOPY,
+    BLOBHASH,
+    BLOBBASEFEE,
     PUSH, // PUSH0, PUSH1, PUSH2, ..., PUSH32
     DUP,  // DUP1, DUP2, ..., DUP16
     SWAP, // SWAP1, SWAP2, ..., SWAP16
@@ -221,9 +227,11 @@ impl ExecutionState {
             || self.halts_in_exception()
     }
 
-    pub(crate) fn responsible_opcodes(&self) -> Vec<ResponsibleOp> {
+    pub(crate) fn responsible_opcodes(&self, fork_schedule: &ForkSchedule) -> Vec<ResponsibleOp> {
         if matches!(self, Self::ErrorStack) {
-            return OpcodeId::valid_opcodes()
+            // Opcodes only become valid in later forks, so the latest rules
+            // cover the valid opcodes of every block in the schedule.
+            return OpcodeId::valid_opcodes_in(&fork_schedule.latest_rules())
                 .into_iter()
                 .flat_map(|op| {
                     op.invalid_stack_ptrs()
@@ -293,6 +301,11 @@ impl ExecutionState {
             Self::MSIZE => vec![OpcodeId::MSIZE],
             Self::GAS => vec![OpcodeId::GAS],
             Self::JUMPDEST => vec![OpcodeId::JUMPDEST],
+            Self::TLOAD => vec![OpcodeId::TLOAD],
+            Self::TSTORE => vec![OpcodeId::TSTORE],
+            Self::MCOPY => vec![OpcodeId::MCOPY],
+            Self::BLOBHASH => vec![OpcodeId::BLOBHASH],
+            Self::BLOBBASEFEE => vec![OpcodeId::BLOBBASEFEE],
             Self::PUSH => vec![
                 OpcodeId::PUSH0,
                 OpcodeId::PUSH1,
@@ -381,7 +394,11 @@ impl ExecutionState {
             ],
             Self::RETURN_REVERT => vec![OpcodeId::RETURN, OpcodeId::REVERT],
             Self::SELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
-            Self::ErrorInvalidOpcode => OpcodeId::invalid_opcodes(),
+            // Opcodes only become valid in later forks, so the genesis rules
+            // cover the invalid opcodes of every block in the schedule.
+            Self::ErrorInvalidOpcode => {
+                OpcodeId::invalid_opcodes_in(&fork_schedule.genesis_rules())
+            }
             _ => vec![],
         }
         .into_iter()
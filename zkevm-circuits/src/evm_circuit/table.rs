@@ -4,7 +4,7 @@ use crate::{
     impl_expr,
 };
 use bus_mapping::{evm::OpcodeId, precompile::PrecompileCalls};
-use eth_types::Field;
+use eth_types::{evm_types::ForkSchedule, Field};
 use gadgets::util::Expr;
 use halo2_proofs::plonk::Expression;
 use strum::IntoEnumIterator;
@@ -36,7 +36,10 @@ pub enum FixedTableTag {
 impl_expr!(FixedTableTag);
 
 impl FixedTableTag {
-    pub fn build<F: Field>(&self) -> Box<dyn Iterator<Item = [F; 4]>> {
+    pub fn build<F: Field>(
+        &self,
+        fork_schedule: &ForkSchedule,
+    ) -> Box<dyn Iterator<Item = [F; 4]>> {
         let tag = F::from(*self as u64);
         match self {
             Self::Zero => Box::new((0..1).map(move |_| [tag, F::zero(), F::zero(), F::zero()])),
@@ -91,9 +94,12 @@ impl FixedTableTag {
                 (0..256).map(move |rhs| [tag, F::from(lhs), F::from(rhs), F::from(lhs ^ rhs)])
             })),
             Self::ResponsibleOpcode => {
+                let fork_schedule = fork_schedule.clone();
                 Box::new(ExecutionState::iter().flat_map(move |execution_state| {
-                    execution_state.responsible_opcodes().into_iter().map(
-                        move |responsible_opcode| {
+                    execution_state
+                        .responsible_opcodes(&fork_schedule)
+                        .into_iter()
+                        .map(move |responsible_opcode| {
                             let (op, aux) = match responsible_opcode {
                                 ResponsibleOp::Op(op) => (op, F::zero()),
                                 ResponsibleOp::InvalidStackPtr(op, stack_ptr) => {
@@ -106,8 +112,7 @@ impl FixedTableTag {
                                 F::from(op.as_u64()),
                                 aux,
                             ]
-                        },
-                    )
+                        })
                 }))
             }
             Self::Pow2 => Box::new((0..256).map(move |value| {
@@ -155,6 +160,8 @@ pub(crate) enum Table {
     Copy,
     Keccak,
     Sha256,
+    Ripemd160,
+    Blake2f,
     Exp,
     Sig,
     ModExp,
@@ -314,6 +321,25 @@ pub(crate) enum Lookup<F> {
         /// the final output sha256 hash of the input.
         output_rlc: Expression<F>,
     },
+    /// Lookup to ripemd160 table.
+    Ripemd160Table {
+        /// Accumulator to the input.
+        input_rlc: Expression<F>,
+        /// Length of input that is being hashed.
+        input_len: Expression<F>,
+        /// Output (hash) until this state. This is the RLC representation of
+        /// the final output ripemd160 hash of the input.
+        output_rlc: Expression<F>,
+    },
+    /// Lookup to blake2f table.
+    Blake2fTable {
+        /// Number of rounds of the compression.
+        rounds: Expression<F>,
+        /// RLC representation of the input to the compression function.
+        input_rlc: Expression<F>,
+        /// RLC representation of the output of the compression function.
+        output_rlc: Expression<F>,
+    },
     /// Lookup to exponentiation table.
     ExpTable {
         base_limbs: [Expression<F>; 4],
@@ -329,10 +355,11 @@ pub(crate) enum Lookup<F> {
         is_valid: Expression<F>,
     },
     ModExpTable {
-        base_limbs: [Expression<F>; 3],
-        exp_limbs: [Expression<F>; 3],
-        modulus_limbs: [Expression<F>; 3],
-        result_limbs: [Expression<F>; 3],
+        base: Expression<F>,
+        exp: Expression<F>,
+        exp_bit_len: Expression<F>,
+        modulus: Expression<F>,
+        result: Expression<F>,
     },
     EccTable {
         op_type: Expression<F>,
@@ -368,6 +395,8 @@ impl<F: Field> Lookup<F> {
             Self::CopyTable { .. } => Table::Copy,
             Self::KeccakTable { .. } => Table::Keccak,
             Self::Sha256Table { .. } => Table::Sha256,
+            Self::Ripemd160Table { .. } => Table::Ripemd160,
+            Self::Blake2fTable { .. } => Table::Blake2f,
             Self::ExpTable { .. } => Table::Exp,
             Self::SigTable { .. } => Table::Sig,
             Self::ModExpTable { .. } => Table::ModExp,
@@ -488,6 +517,28 @@ impl<F: Field> Lookup<F> {
                 input_len.clone(),
                 output_rlc.clone(),
             ],
+            Self::Ripemd160Table {
+                input_rlc,
+                input_len,
+                output_rlc,
+            } => vec![
+                1.expr(), // q_enable
+                1.expr(), // is_final
+                input_rlc.clone(),
+                input_len.clone(),
+                output_rlc.clone(),
+            ],
+            Self::Blake2fTable {
+                rounds,
+                input_rlc,
+                output_rlc,
+            } => vec![
+                1.expr(), // q_enable
+                1.expr(), // is_final
+                rounds.clone(),
+                input_rlc.clone(),
+                output_rlc.clone(),
+            ],
             Self::ExpTable {
                 base_limbs,
                 exponent_lo_hi,
@@ -521,24 +572,19 @@ impl<F: Field> Lookup<F> {
                 is_valid.clone(),
             ],
             Self::ModExpTable {
-                base_limbs,
-                exp_limbs,
-                modulus_limbs,
-                result_limbs,
+                base,
+                exp,
+                exp_bit_len,
+                modulus,
+                result,
             } => vec![
-                1.expr(), // q_head
-                base_limbs[0].clone(),
-                exp_limbs[0].clone(),
-                modulus_limbs[0].clone(),
-                result_limbs[0].clone(),
-                base_limbs[1].clone(),
-                exp_limbs[1].clone(),
-                modulus_limbs[1].clone(),
-                result_limbs[1].clone(),
-                base_limbs[2].clone(),
-                exp_limbs[2].clone(),
-                modulus_limbs[2].clone(),
-                result_limbs[2].clone(),
+                1.expr(), // q_enable
+                1.expr(), // is_final
+                base.clone(),
+                exp.clone(),
+                exp_bit_len.clone(),
+                modulus.clone(),
+                result.clone(),
             ],
             Self::EccTable {
                 op_type,
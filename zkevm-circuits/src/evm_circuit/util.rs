@@ -10,7 +10,7 @@ use crate::{
     util::{query_expression, Challenges, Expr},
     witness::{Block, ExecStep, Rw, RwMap},
 };
-use bus_mapping::state_db::CodeDB;
+use bus_mapping::{precompile::LAST_PRECOMPILE_ADDRESS, state_db::CodeDB};
 use eth_types::{Address, ToLittleEndian, ToWord, U256};
 use halo2_proofs::{
     arithmetic::FieldExt,
@@ -716,10 +716,11 @@ pub(crate) fn transpose_val_ret<F, E>(value: Value<Result<F, E>>) -> Result<Valu
 }
 
 pub(crate) fn is_precompiled(address: &Address) -> bool {
-    address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19])
+    address.0[0..19] == [0u8; 19] && (1..=LAST_PRECOMPILE_ADDRESS).contains(&address.0[19])
 }
 
 /// Helper struct to read rw operations from a step sequentially.
+#[derive(Clone)]
 pub(crate) struct StepRws<'a> {
     rws: &'a RwMap,
     rw_indices: &'a Vec<(RwTableTag, usize)>,
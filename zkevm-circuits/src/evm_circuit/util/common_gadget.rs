@@ -19,12 +19,15 @@ use crate::{
             not, or, Cell, CellType, StepRws, Word,
         },
     },
-    table::{AccountFieldTag, CallContextFieldTag},
+    table::{AccountFieldTag, BlockContextFieldTag, CallContextFieldTag},
     util::Expr,
     witness::{Block, Call, ExecStep},
 };
 use either::Either;
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, U256};
+use eth_types::{
+    evm_types::{Fork, ForkActivation, GasCost},
+    Field, ToLittleEndian, ToScalar, U256,
+};
 use gadgets::util::{select, sum};
 use halo2_proofs::{
     circuit::Value,
@@ -32,10 +35,12 @@ use halo2_proofs::{
 };
 
 mod tx_eip2930;
+mod tx_eip4844;
 mod tx_l1_fee;
 mod tx_l1_msg;
 
 pub(crate) use tx_eip2930::TxEip2930Gadget;
+pub(crate) use tx_eip4844::TxEip4844Gadget;
 pub(crate) use tx_l1_fee::TxL1FeeGadget;
 pub(crate) use tx_l1_msg::TxL1MsgGadget;
 
@@ -1827,3 +1832,68 @@ pub(crate) fn get_copy_bytes(
 
     values
 }
+
+/// Check if a fork is active for the current block, following the fork schedule
+/// which the circuit is configured with. A fork activated at genesis or never
+/// activated reduces to a constant without any cells.
+#[derive(Clone, Debug)]
+pub(crate) struct IsForkActiveGadget<F> {
+    activation: Option<ForkActivation>,
+    /// The block number or timestamp compared with the fork activation, only
+    /// for a fork activated after genesis.
+    value: Option<(Cell<F>, LtGadget<F, N_BYTES_U64>)>,
+}
+
+impl<F: Field> IsForkActiveGadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, fork: Fork) -> Self {
+        let activation = cb.fork_schedule().activation(fork);
+        let value = activation
+            .filter(|activation| !activation.is_genesis())
+            .map(|activation| {
+                let (tag, threshold) = match activation {
+                    ForkActivation::Block(number) => (BlockContextFieldTag::Number, number),
+                    ForkActivation::Timestamp(timestamp) => {
+                        (BlockContextFieldTag::Timestamp, timestamp)
+                    }
+                };
+                let value = cb.query_cell();
+                cb.block_lookup(tag.expr(), cb.curr.state.block_number.expr(), value.expr());
+                let lt = LtGadget::construct(cb, value.expr(), threshold.expr());
+                (value, lt)
+            });
+
+        Self { activation, value }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        match &self.value {
+            Some((_, lt)) => not::expr(lt.expr()),
+            None => self.activation.is_some().expr(),
+        }
+    }
+
+    /// Return true if the fork is active for the block.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        block_number: u64,
+    ) -> Result<bool, Error> {
+        let context = &block.context.ctxs[&block_number];
+        let (number, timestamp) = (context.number.low_u64(), context.timestamp.low_u64());
+
+        if let (Some(activation), Some((value, lt))) = (self.activation, &self.value) {
+            let (value_u64, threshold) = match activation {
+                ForkActivation::Block(threshold) => (number, threshold),
+                ForkActivation::Timestamp(threshold) => (timestamp, threshold),
+            };
+            value.assign(region, offset, Value::known(F::from(value_u64)))?;
+            lt.assign(region, offset, F::from(value_u64), F::from(threshold))?;
+        }
+
+        Ok(self
+            .activation
+            .map_or(false, |activation| activation.is_active(number, timestamp)))
+    }
+}
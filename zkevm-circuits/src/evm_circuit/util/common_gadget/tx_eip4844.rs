@@ -0,0 +1,158 @@
+use super::CachedRegion;
+use crate::{
+    evm_circuit::util::{
+        constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+        math_gadget::{AddWordsGadget, IsEqualGadget, LtWordGadget},
+        Word,
+    },
+    table::{BlockContextFieldTag, TxFieldTag},
+    util::Expr,
+    witness::Transaction,
+};
+use eth_types::{geth_types::TxType, Field, ToLittleEndian, U256};
+use halo2_proofs::plonk::{Error, Expression};
+
+/// Transaction EIP-4844 gadget to check the effective gas price of a blob tx,
+/// i.e. `gas_price == min(max_fee_per_gas, base_fee + max_priority_fee_per_gas)`
+#[derive(Clone, Debug)]
+pub(crate) struct TxEip4844Gadget<F> {
+    is_eip4844_tx: IsEqualGadget<F>,
+    max_fee_per_gas: Word<F>,
+    max_priority_fee_per_gas: Word<F>,
+    base_fee: Word<F>,
+    // The tip actually paid to the coinbase, `gas_price - base_fee`.
+    effective_tip: Word<F>,
+    add_base_fee_and_tip: AddWordsGadget<F, 2, true>,
+    max_fee_lt_base_fee: LtWordGadget<F>,
+    max_fee_lt_tip: LtWordGadget<F>,
+    tip_lt_effective_tip: LtWordGadget<F>,
+    max_fee_lt_gas_price: LtWordGadget<F>,
+}
+
+impl<F: Field> TxEip4844Gadget<F> {
+    pub(crate) fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        tx_id: Expression<F>,
+        tx_type: Expression<F>,
+        gas_price: &Word<F>,
+    ) -> Self {
+        let is_eip4844_tx = IsEqualGadget::construct(cb, tx_type, (TxType::Eip4844 as u64).expr());
+
+        cb.condition(is_eip4844_tx.expr(), |cb| {
+            let [max_fee_per_gas, max_priority_fee_per_gas] =
+                [TxFieldTag::MaxFeePerGas, TxFieldTag::MaxPriorityFeePerGas]
+                    .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
+
+            let base_fee = cb.query_word_rlc();
+            cb.block_lookup(
+                BlockContextFieldTag::BaseFee.expr(),
+                cb.curr.state.block_number.expr(),
+                base_fee.expr(),
+            );
+
+            // gas_price == base_fee + effective_tip, which cannot overflow since
+            // gas_price <= max_fee_per_gas.
+            let effective_tip = cb.query_word_rlc();
+            let add_base_fee_and_tip = AddWordsGadget::construct(
+                cb,
+                [base_fee.clone(), effective_tip.clone()],
+                gas_price.clone(),
+            );
+
+            let max_fee_lt_base_fee = LtWordGadget::construct(cb, &max_fee_per_gas, &base_fee);
+            cb.require_zero("max_fee_per_gas >= base_fee", max_fee_lt_base_fee.expr());
+            let max_fee_lt_tip =
+                LtWordGadget::construct(cb, &max_fee_per_gas, &max_priority_fee_per_gas);
+            cb.require_zero(
+                "max_fee_per_gas >= max_priority_fee_per_gas",
+                max_fee_lt_tip.expr(),
+            );
+            let tip_lt_effective_tip =
+                LtWordGadget::construct(cb, &max_priority_fee_per_gas, &effective_tip);
+            cb.require_zero(
+                "effective_tip <= max_priority_fee_per_gas",
+                tip_lt_effective_tip.expr(),
+            );
+            let max_fee_lt_gas_price = LtWordGadget::construct(cb, &max_fee_per_gas, gas_price);
+            cb.require_zero("gas_price <= max_fee_per_gas", max_fee_lt_gas_price.expr());
+
+            // Together with the upper bounds above, this makes gas_price the
+            // minimum of max_fee_per_gas and base_fee + max_priority_fee_per_gas.
+            cb.require_zero(
+                "effective_tip == max_priority_fee_per_gas or gas_price == max_fee_per_gas",
+                (max_priority_fee_per_gas.expr() - effective_tip.expr())
+                    * (max_fee_per_gas.expr() - gas_price.expr()),
+            );
+
+            Self {
+                is_eip4844_tx,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                base_fee,
+                effective_tip,
+                add_base_fee_and_tip,
+                max_fee_lt_base_fee,
+                max_fee_lt_tip,
+                tip_lt_effective_tip,
+                max_fee_lt_gas_price,
+            }
+        })
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        tx: &Transaction,
+        base_fee: U256,
+    ) -> Result<(), Error> {
+        self.is_eip4844_tx.assign(
+            region,
+            offset,
+            F::from(tx.tx_type as u64),
+            F::from(TxType::Eip4844 as u64),
+        )?;
+
+        // The fee fields of other tx types are zero in the tx table, so assign
+        // the base fee as zero too to keep the addition witness consistent.
+        let base_fee = if tx.tx_type.is_eip4844_tx() {
+            base_fee
+        } else {
+            U256::zero()
+        };
+        let effective_tip = tx.gas_price.saturating_sub(base_fee);
+
+        for (word, value) in [
+            (&self.max_fee_per_gas, tx.max_fee_per_gas),
+            (&self.max_priority_fee_per_gas, tx.max_priority_fee_per_gas),
+            (&self.base_fee, base_fee),
+            (&self.effective_tip, effective_tip),
+        ] {
+            word.assign(region, offset, Some(value.to_le_bytes()))?;
+        }
+        self.add_base_fee_and_tip.assign(
+            region,
+            offset,
+            [base_fee, effective_tip],
+            tx.gas_price,
+        )?;
+        self.max_fee_lt_base_fee
+            .assign(region, offset, tx.max_fee_per_gas, base_fee)?;
+        self.max_fee_lt_tip.assign(
+            region,
+            offset,
+            tx.max_fee_per_gas,
+            tx.max_priority_fee_per_gas,
+        )?;
+        self.tip_lt_effective_tip.assign(
+            region,
+            offset,
+            tx.max_priority_fee_per_gas,
+            effective_tip,
+        )?;
+        self.max_fee_lt_gas_price
+            .assign(region, offset, tx.max_fee_per_gas, tx.gas_price)?;
+
+        Ok(())
+    }
+}
@@ -6,8 +6,8 @@ use crate::{
         util::{Cell, RandomLinearCombination, Word},
     },
     table::{
-        AccountFieldTag, BytecodeFieldTag, CallContextFieldTag, RwTableTag, TxContextFieldTag,
-        TxLogFieldTag, TxReceiptFieldTag,
+        AccountFieldTag, BytecodeFieldTag, CallContextFieldTag, RwTableTag,
+        TransientStorageFieldTag, TxContextFieldTag, TxLogFieldTag, TxReceiptFieldTag,
     },
     util::{build_tx_log_expression, Challenges, Expr},
 };
@@ -15,7 +15,7 @@ use bus_mapping::{
     state_db::EMPTY_CODE_HASH_LE,
     util::{KECCAK_CODE_HASH_EMPTY, POSEIDON_CODE_HASH_EMPTY},
 };
-use eth_types::{Field, ToLittleEndian, ToScalar, ToWord};
+use eth_types::{evm_types::ForkSchedule, Field, ToLittleEndian, ToScalar, ToWord};
 use gadgets::util::{and, not};
 use halo2_proofs::{
     circuit::Value,
@@ -302,6 +302,8 @@ pub(crate) struct EVMConstraintBuilder<'a, F> {
     pub(crate) curr: Step<F>,
     pub(crate) next: Step<F>,
     challenges: &'a Challenges<Expression<F>>,
+    fork_schedule: &'a ForkSchedule,
+    modexp_size_limit: usize,
     execution_state: ExecutionState,
     constraints: Constraints<F>,
     rw_counter_offset: Expression<F>,
@@ -338,6 +340,8 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         curr: Step<F>,
         next: Step<F>,
         challenges: &'a Challenges<Expression<F>>,
+        fork_schedule: &'a ForkSchedule,
+        modexp_size_limit: usize,
         execution_state: ExecutionState,
     ) -> Self {
         Self {
@@ -345,6 +349,8 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             curr,
             next,
             challenges,
+            fork_schedule,
+            modexp_size_limit,
             execution_state,
             constraints: Constraints {
                 step: Vec::new(),
@@ -398,6 +404,14 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         self.challenges
     }
 
+    pub(crate) fn fork_schedule(&self) -> &ForkSchedule {
+        self.fork_schedule
+    }
+
+    pub(crate) fn modexp_size_limit(&self) -> usize {
+        self.modexp_size_limit
+    }
+
     pub(crate) fn execution_state(&self) -> ExecutionState {
         self.execution_state
     }
@@ -1108,6 +1122,107 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Transient Storage
+
+    pub(crate) fn transient_storage_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        key: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TransientStorage read",
+            false.expr(),
+            RwTableTag::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                TransientStorageFieldTag::Slot.expr(),
+                key,
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    pub(crate) fn transient_storage_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        key: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TransientStorage write",
+            RwTableTag::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                TransientStorageFieldTag::Slot.expr(),
+                key,
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
+    /// Reads whether the account is created in the current tx (EIP-6780).
+    pub(crate) fn account_created_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TransientStorage AccountCreated read",
+            false.expr(),
+            RwTableTag::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                TransientStorageFieldTag::AccountCreated.expr(),
+                0.expr(),
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    /// Marks the account as created in the current tx (EIP-6780).
+    pub(crate) fn account_created_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TransientStorage AccountCreated write",
+            RwTableTag::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                TransientStorageFieldTag::AccountCreated.expr(),
+                0.expr(),
+                1.expr(),
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
     // Call context
 
     pub(crate) fn call_context(
@@ -1494,21 +1609,59 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Ripemd160 Table
+
+    pub(crate) fn ripemd160_table_lookup(
+        &mut self,
+        input_rlc: Expression<F>,
+        input_len: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "ripemd160 lookup",
+            Lookup::Ripemd160Table {
+                input_rlc,
+                input_len,
+                output_rlc,
+            },
+        );
+    }
+
+    // Blake2f Table
+
+    pub(crate) fn blake2f_table_lookup(
+        &mut self,
+        rounds: Expression<F>,
+        input_rlc: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "blake2f lookup",
+            Lookup::Blake2fTable {
+                rounds,
+                input_rlc,
+                output_rlc,
+            },
+        );
+    }
+
     // ModExp table
     pub(crate) fn modexp_table_lookup(
         &mut self,
-        base_limbs: [Expression<F>; 3],
-        exp_limbs: [Expression<F>; 3],
-        modulus_limbs: [Expression<F>; 3],
-        result_limbs: [Expression<F>; 3],
+        base: Expression<F>,
+        exp: Expression<F>,
+        exp_bit_len: Expression<F>,
+        modulus: Expression<F>,
+        result: Expression<F>,
     ) {
         self.add_lookup(
-            "u256 exponentiation modulus lookup",
+            "modular exponentiation lookup",
             Lookup::ModExpTable {
-                base_limbs,
-                exp_limbs,
-                modulus_limbs,
-                result_limbs,
+                base,
+                exp,
+                exp_bit_len,
+                modulus,
+                result,
             },
         );
     }
@@ -31,7 +31,8 @@ use halo2_proofs::plonk::SecondPhase;
 #[cfg(not(feature = "onephase"))]
 use halo2_proofs::plonk::ThirdPhase;
 
-use eth_types::{Field, Word, U256};
+use bus_mapping::precompile::DEFAULT_MODEXP_SIZE_LIMIT;
+use eth_types::{evm_types::FORK_SCHEDULE, Field, Word, U256};
 pub(crate) use halo2_proofs::circuit::{Layouter, Value};
 use halo2_proofs::{
     circuit::SimpleFloorPlanner,
@@ -141,6 +142,8 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
             step_curr.clone(),
             step_next,
             &challenges_exprs,
+            &FORK_SCHEDULE,
+            DEFAULT_MODEXP_SIZE_LIMIT,
             ExecutionState::STOP,
         );
         let math_gadget_container = G::configure_gadget_container(&mut cb);
@@ -247,7 +250,7 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
                                         | FixedTableTag::Range1024
                                 )
                             })
-                            .flat_map(|tag| tag.build()),
+                            .flat_map(|tag| tag.build(&FORK_SCHEDULE)),
                     )
                     .enumerate()
                 {
@@ -994,6 +994,27 @@ impl<F: Field> KeccakCircuitConfig<F> {
     }
 }
 
+/// Rows required to hash an input of the length.
+pub(crate) fn keccak_input_rows(len: usize) -> usize {
+    let rows_per_chunk = (NUM_ROUNDS + 1) * get_num_rows_per_round();
+    (len as f64 / 136.0).ceil() as usize * rows_per_chunk
+}
+
+/// Minimum number of rows required by the inputs of the total rows (by `keccak_input_rows`), as
+/// `min_num_rows_block` of the keccak circuit.
+pub(crate) fn keccak_min_num_rows(inputs_rows: usize, max_keccak_rows: usize) -> (usize, usize) {
+    let aux_tables_rows = vec![
+        normalize_table_size(6),
+        normalize_table_size(4),
+        normalize_table_size(3),
+        lookup_table_size(CHI_BASE_LOOKUP_TABLE.len()),
+    ];
+    (
+        inputs_rows + get_num_rows_per_round(), // reserved for first 12 dummy rows
+        max(max_keccak_rows, *(aux_tables_rows.iter().max().unwrap())),
+    )
+}
+
 /// KeccakCircuit
 #[derive(Default, Clone, Debug)]
 pub struct KeccakCircuit<F: Field> {
@@ -1026,24 +1047,13 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let rows_per_chunk = (NUM_ROUNDS + 1) * get_num_rows_per_round();
-        let aux_tables_rows = vec![
-            normalize_table_size(6),
-            normalize_table_size(4),
-            normalize_table_size(3),
-            lookup_table_size(CHI_BASE_LOOKUP_TABLE.len()),
-        ];
-        (
+        keccak_min_num_rows(
             block
                 .keccak_inputs
                 .iter()
-                .map(|bytes| (bytes.len() as f64 / 136.0).ceil() as usize * rows_per_chunk)
-                .sum::<usize>()
-                + get_num_rows_per_round(), // reserved for first 12 dummy rows
-            max(
-                block.circuits_params.max_keccak_rows,
-                *(aux_tables_rows.iter().max().unwrap()),
-            ),
+                .map(|bytes| keccak_input_rows(bytes.len()))
+                .sum(),
+            block.circuits_params.max_keccak_rows,
         )
     }
 
@@ -1,173 +1,941 @@
-//! The Modexp circuit is responsible for modexp operations on big integer from precompiled contract
-//! calls ModExp, current the size of supported integer is up to 32 bytes (U256)
+//! The ModExp circuit verifies the exponentiations `base ^ exp (mod modulus)` computed by the
+//! precompile at address 0x05, for operands of any length up to `CircuitsParams::max_modexp_size`
+//! bytes.
+//!
+//! An exponentiation is computed by right-to-left binary exponentiation, as a sequence of modular
+//! multiplications `a * b = q * m + r`: the reduction of the base, the initialization of the
+//! accumulator to 1, then for every bit of the exponent the multiplication of the accumulator by
+//! the current power when the bit is set, followed by the squaring of the power. A zero modulus
+//! is replaced by 1 in the multiplications, which makes the result zero.
+//!
+//! A multiplication is laid out on `2 * L` rows, `L` being the largest length of the base and the
+//! modulus, with one byte of every operand per row, starting from the least significant one. The
+//! product is checked on the polynomials `A, B, Q, M, R` of the bytes at the random point `x`,
+//! `A(x) * B(x) - Q(x) * M(x) - R(x) == (x - 256) * C(x)`, the range checked carries `C` proving
+//! that the identity holds at 256 over the integers, and `r < m` is proven by the borrows of
+//! `m - r - 1`. The multiplications are chained through the fingerprints `R(x)` of their results,
+//! and the bits of the exponent are accumulated into the RLC of its bytes, which is exposed in the
+//! modexp table along with the RLCs of the other operands.
 
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 mod dev;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 mod test;
 
-use halo2_proofs::{
-    circuit::{Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error},
-};
-
 use crate::{
-    table::ModExpTable,
+    evm_circuit::util::{
+        constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+        rlc,
+    },
+    table::{ModExpTable, U16Table, U8Table},
     util::{Challenges, SubCircuit, SubCircuitConfig},
     witness,
 };
 use bus_mapping::circuit_input_builder::BigModExp;
-use eth_types::{Field, Word};
-
-//use misc_precompiled_circuit::value_for_assign;
-use misc_precompiled_circuit::circuits::{
-    modexp::{ModExpChip, Number},
-    range::{RangeCheckChip, RangeCheckConfig},
-    CommonGateConfig as ModExpConfig, Limb,
+use eth_types::Field;
+use gadgets::util::{not, select, sum, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, VirtualCells,
+    },
+    poly::Rotation,
 };
+use num_bigint::BigUint;
+use std::marker::PhantomData;
 
-/// ModExp circuit config
+/// Offset added to the carries of a multiplication, which are in
+/// `[-CARRY_OFFSET, CARRY_OFFSET)`, to range check them with a u16 and a u8 limb.
+const CARRY_OFFSET: u64 = 1 << 23;
+
+/// Kind of a modular multiplication of an exponentiation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MulKind {
+    /// Reduction of the base, `base * 1`.
+    Reduce,
+    /// Initialization of the accumulator, `1 * 1`.
+    Init,
+    /// Multiplication of the accumulator by the current power, for a set bit of the exponent.
+    Mul,
+    /// Squaring of the current power, moving to the next bit of the exponent.
+    Square,
+}
+
+/// A modular multiplication `a * b = q * m + r` of an exponentiation.
 #[derive(Clone, Debug)]
-pub struct ModExpCircuitConfig {
-    modexp_config: ModExpConfig,
-    rangecheck_config: RangeCheckConfig,
+struct ModMul {
+    kind: MulKind,
+    a: BigUint,
+    b: BigUint,
+    q: BigUint,
+    r: BigUint,
+}
+
+/// Whether the bit `n` of a big-endian integer is set.
+fn bit(bytes: &[u8], n: usize) -> bool {
+    let byte = n / 8;
+    byte < bytes.len() && (bytes[bytes.len() - 1 - byte] >> (n % 8)) & 1 == 1
+}
+
+/// Little-endian bytes of an integer, padded to `n` bytes.
+fn le_bytes(value: &BigUint, n: usize) -> Vec<u8> {
+    let mut bytes = value.to_bytes_le();
+    debug_assert!(bytes.len() <= n, "operand exceeds the multiplication rows");
+    bytes.resize(n, 0);
+    bytes
+}
+
+/// Number of bytes of the operands of the multiplications of an exponentiation, one per row.
+fn num_limbs(event: &BigModExp) -> usize {
+    2 * event.base.len().max(event.modulus.len()).max(1)
+}
+
+/// Number of multiplications of an exponentiation.
+fn num_muls(event: &BigModExp) -> usize {
+    let set_bits: usize = event
+        .exponent
+        .iter()
+        .map(|byte| byte.count_ones() as usize)
+        .sum();
+    2 + set_bits + event.exponent_bit_len().saturating_sub(1)
+}
+
+/// Modulus of the multiplications of an exponentiation, a zero modulus being replaced by 1.
+fn mul_modulus(event: &BigModExp) -> BigUint {
+    let modulus = BigUint::from_bytes_be(&event.modulus);
+    if modulus == BigUint::from(0u8) {
+        BigUint::from(1u8)
+    } else {
+        modulus
+    }
+}
+
+/// Generate the multiplications of an exponentiation.
+fn mod_muls(event: &BigModExp) -> Vec<ModMul> {
+    let modulus = mul_modulus(event);
+    let one = BigUint::from(1u8);
+    let mut muls = Vec::with_capacity(num_muls(event));
+    let mut mul = |kind, a: BigUint, b: BigUint| {
+        let product = &a * &b;
+        let (q, r) = (&product / &modulus, &product % &modulus);
+        muls.push(ModMul {
+            kind,
+            a,
+            b,
+            q,
+            r: r.clone(),
+        });
+        r
+    };
+
+    let mut pow = mul(
+        MulKind::Reduce,
+        BigUint::from_bytes_be(&event.base),
+        one.clone(),
+    );
+    let mut acc = mul(MulKind::Init, one.clone(), one);
+    let bit_len = event.exponent_bit_len();
+    for n in 0..bit_len {
+        if bit(&event.exponent, n) {
+            acc = mul(MulKind::Mul, acc, pow.clone());
+        }
+        if n + 1 < bit_len {
+            pow = mul(MulKind::Square, pow.clone(), pow);
+        }
+    }
+    debug_assert_eq!(
+        acc,
+        BigUint::from_bytes_be(&event.result),
+        "modexp result mismatch"
+    );
+
+    muls
+}
+
+/// Carries of `a * b - q * m - r` on little-endian bytes, the coefficient of the byte `k` being
+/// `c[k - 1] - 256 * c[k]`.
+fn carries(a: &[u8], b: &[u8], q: &[u8], m: &[u8], r: &[u8]) -> Vec<i64> {
+    let n = a.len();
+    let mut coeffs: Vec<i64> = r.iter().map(|&byte| -(byte as i64)).collect();
+    for (i, (&a_i, &q_i)) in a.iter().zip(q).enumerate() {
+        if a_i == 0 && q_i == 0 {
+            continue;
+        }
+        for (j, (&b_j, &m_j)) in b.iter().zip(m).take(n - i).enumerate() {
+            coeffs[i + j] += a_i as i64 * b_j as i64 - q_i as i64 * m_j as i64;
+        }
+    }
+
+    let mut carry = 0;
+    let carries = coeffs
+        .into_iter()
+        .map(|coeff| {
+            debug_assert_eq!((carry - coeff) % 256, 0, "inexact modexp carry");
+            carry = (carry - coeff) / 256;
+            debug_assert!(carry.unsigned_abs() < CARRY_OFFSET, "modexp carry overflow");
+            carry
+        })
+        .collect();
+    debug_assert_eq!(carry, 0, "modexp product overflows the multiplication rows");
+
+    carries
+}
+
+/// Query an advice column at the current row.
+fn cur<F: Field>(meta: &mut VirtualCells<'_, F>, column: Column<Advice>) -> Expression<F> {
+    meta.query_advice(column, Rotation::cur())
+}
+
+/// Query an advice column at the previous row.
+fn prev<F: Field>(meta: &mut VirtualCells<'_, F>, column: Column<Advice>) -> Expression<F> {
+    meta.query_advice(column, Rotation::prev())
+}
+
+/// Config for the ModExp circuit.
+#[derive(Clone, Debug)]
+pub struct ModExpCircuitConfig<F> {
+    /// Whether the row is enabled, which excludes the first row.
+    q_enable: Column<Fixed>,
+    /// Whether the row belongs to an exponentiation.
+    is_event: Column<Advice>,
+    /// Whether the row is the last row of a multiplication.
+    is_last: Column<Advice>,
+    /// Whether the multiplication reduces the base.
+    is_reduce: Column<Advice>,
+    /// Whether the multiplication initializes the accumulator.
+    is_init: Column<Advice>,
+    /// Whether the multiplication multiplies the accumulator by the current power.
+    is_mul: Column<Advice>,
+    /// Whether the multiplication squares the current power.
+    is_square: Column<Advice>,
+    /// Byte of the first operand.
+    a: Column<Advice>,
+    /// Byte of the second operand.
+    b: Column<Advice>,
+    /// Byte of the quotient.
+    q: Column<Advice>,
+    /// Byte of the modulus.
+    m: Column<Advice>,
+    /// Byte of the remainder.
+    r: Column<Advice>,
+    /// Byte of `m - r - 1`.
+    s: Column<Advice>,
+    /// Borrow of the subtraction `m - r - 1` into the next byte.
+    borrow: Column<Advice>,
+    /// Low 16 bits of the offset carry.
+    carry_lo: Column<Advice>,
+    /// High 8 bits of the offset carry.
+    carry_hi: Column<Advice>,
+    /// Powers of `x` over the rows of a multiplication.
+    pow_x: Column<Advice>,
+    /// Fingerprints of the operands and the carries, accumulated over the rows of a
+    /// multiplication, in the order `a, b, q, m, r, c`.
+    acc: [Column<Advice>; 6],
+    /// Fingerprint of the accumulator.
+    fp_acc: Column<Advice>,
+    /// Fingerprint of the current power.
+    fp_pow: Column<Advice>,
+    /// Index of the current bit of the exponent.
+    exp_bits: Column<Advice>,
+    /// `2 ^ (exp_bits % 8)`.
+    exp_pow2: Column<Advice>,
+    /// Inverse of `exp_pow2 - 128`, or 0.
+    exp_pow2_inv: Column<Advice>,
+    /// Whether the current bit is the last one of its byte, i.e. `exp_pow2 == 128`.
+    is_byte_end: Column<Advice>,
+    /// `x ^ (exp_bits / 8)`.
+    exp_pow_r: Column<Advice>,
+    /// Whether the modulus is zero.
+    is_mod_zero: Column<Advice>,
+    /// Inverse of the modulus RLC, or 0.
+    modulus_inv: Column<Advice>,
+    /// The modexp table.
     modexp_table: ModExpTable,
+    /// u8 lookup table.
+    u8_table: U8Table,
+    /// u16 lookup table.
+    u16_table: U16Table,
+    _marker: PhantomData<F>,
+}
+
+/// Arguments to configure the ModExp circuit.
+pub struct ModExpCircuitConfigArgs<F: Field> {
+    /// ModExpTable
+    pub modexp_table: ModExpTable,
+    /// u8 lookup table
+    pub u8_table: U8Table,
+    /// u16 lookup table
+    pub u16_table: U16Table,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
 }
 
-impl<F: Field> SubCircuitConfig<F> for ModExpCircuitConfig {
-    type ConfigArgs = ModExpTable;
+impl<F: Field> SubCircuitConfig<F> for ModExpCircuitConfig<F> {
+    type ConfigArgs = ModExpCircuitConfigArgs<F>;
 
     /// Return a new ModExpCircuitConfig
-    fn new(meta: &mut ConstraintSystem<F>, modexp_table: Self::ConfigArgs) -> Self {
-        let rangecheck_config = RangeCheckChip::configure(meta);
-        let modexp_config = ModExpChip::configure(meta, &rangecheck_config);
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        ModExpCircuitConfigArgs {
+            modexp_table,
+            u8_table,
+            u16_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = modexp_table.q_enable;
+        let [is_event, is_last, is_reduce, is_init, is_mul, is_square] =
+            [(); 6].map(|_| meta.advice_column());
+        let [a, b, q, m, r, s, borrow, carry_lo, carry_hi] = [(); 9].map(|_| meta.advice_column());
+        let [exp_bits, exp_pow2, exp_pow2_inv, is_byte_end, is_mod_zero] =
+            [(); 5].map(|_| meta.advice_column());
+        let pow_x = meta.advice_column_in(SecondPhase);
+        let acc = [(); 6].map(|_| meta.advice_column_in(SecondPhase));
+        let [fp_acc, fp_pow, exp_pow_r, modulus_inv] =
+            [(); 4].map(|_| meta.advice_column_in(SecondPhase));
+        let x = challenges.keccak_input();
+
+        meta.create_gate("modexp flags", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_event = cur(meta, is_event);
+            let is_last = cur(meta, is_last);
+            let is_final = cur(meta, modexp_table.is_final);
+            let kinds = [is_reduce, is_init, is_mul, is_square].map(|kind| cur(meta, kind));
+            let is_byte_end = cur(meta, is_byte_end);
+            let is_mod_zero = cur(meta, is_mod_zero);
+
+            for (name, flag) in [
+                ("is_event is boolean", is_event.clone()),
+                ("is_last is boolean", is_last.clone()),
+                ("is_final is boolean", is_final.clone()),
+                ("is_byte_end is boolean", is_byte_end.clone()),
+                ("is_mod_zero is boolean", is_mod_zero.clone()),
+                ("borrow is boolean", cur(meta, borrow)),
+            ]
+            .into_iter()
+            .chain(kinds.iter().map(|kind| ("kind is boolean", kind.clone())))
+            {
+                cb.require_boolean(name, flag);
+            }
+            cb.require_equal(
+                "an exponentiation row has exactly one multiplication kind",
+                sum::expr(kinds),
+                is_event.clone(),
+            );
+            cb.require_zero(
+                "is_last only in exponentiations",
+                is_last.clone() * not::expr(is_event.clone()),
+            );
+            cb.require_zero(
+                "is_final only on the last row of a multiplication",
+                is_final * not::expr(is_last),
+            );
+
+            let pow2_end = cur(meta, exp_pow2) - 128.expr();
+            cb.require_zero(
+                "is_byte_end => exp_pow2 == 128",
+                is_event.clone() * is_byte_end.clone() * pow2_end.clone(),
+            );
+            cb.require_zero(
+                "not is_byte_end => exp_pow2 != 128",
+                is_event.clone()
+                    * not::expr(is_byte_end)
+                    * (pow2_end * cur(meta, exp_pow2_inv) - 1.expr()),
+            );
+
+            let modulus = cur(meta, modexp_table.modulus);
+            cb.require_zero(
+                "not is_mod_zero => modulus != 0",
+                is_event
+                    * not::expr(is_mod_zero.clone())
+                    * (modulus.clone() * cur(meta, modulus_inv) - 1.expr()),
+            );
+            cb.require_zero("is_mod_zero => modulus == 0", is_mod_zero * modulus);
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("modexp multiplication", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let block_start = prev(meta, is_last) + not::expr(prev(meta, is_event));
+            let is_event = cur(meta, is_event);
+            let carry =
+                cur(meta, carry_lo) + cur(meta, carry_hi) * 65536.expr() - CARRY_OFFSET.expr();
+            let values = [a, b, q, m, r]
+                .map(|column| cur(meta, column))
+                .into_iter()
+                .chain([carry])
+                .collect::<Vec<_>>();
+            let acc_cur = acc.map(|column| cur(meta, column));
+            let acc_prev = acc.map(|column| prev(meta, column));
+            let sub = cur(meta, s) - cur(meta, borrow) * 256.expr() - cur(meta, m) + cur(meta, r);
+
+            cb.condition(is_event.clone() * block_start.clone(), |cb| {
+                cb.require_equal("first row: pow_x == 1", cur(meta, pow_x), 1.expr());
+                for (acc, value) in acc_cur.iter().zip(values.iter()) {
+                    cb.require_equal("first row: acc == value", acc.clone(), value.clone());
+                }
+                cb.require_equal(
+                    "first row: s - 256 * borrow == m - r - 1",
+                    sub.clone(),
+                    (-1).expr(),
+                );
+            });
+
+            let unchanged = [
+                is_reduce,
+                is_init,
+                is_mul,
+                is_square,
+                fp_acc,
+                fp_pow,
+                exp_bits,
+                exp_pow2,
+                exp_pow_r,
+                modexp_table.exp,
+            ]
+            .map(|column| (cur(meta, column), prev(meta, column)));
+            let pow_x_prev = prev(meta, pow_x);
+            let pow_x = cur(meta, pow_x);
+            let borrow_prev = prev(meta, borrow);
+            cb.condition(is_event * not::expr(block_start), |cb| {
+                cb.require_equal(
+                    "next row: pow_x == pow_x::prev * x",
+                    pow_x.clone(),
+                    pow_x_prev * x.clone(),
+                );
+                for ((acc, acc_prev), value) in
+                    acc_cur.iter().zip(acc_prev.iter()).zip(values.iter())
+                {
+                    cb.require_equal(
+                        "next row: acc == acc::prev + value * pow_x",
+                        acc.clone(),
+                        acc_prev.clone() + value.clone() * pow_x.clone(),
+                    );
+                }
+                cb.require_equal(
+                    "next row: s - 256 * borrow == m - r - borrow::prev",
+                    sub,
+                    -borrow_prev,
+                );
+                for (value, value_prev) in unchanged {
+                    cb.require_equal(
+                        "multiplication state is unchanged within a multiplication",
+                        value,
+                        value_prev,
+                    );
+                }
+            });
+
+            let [acc_a, acc_b, acc_q, acc_m, acc_r, acc_c] = acc_cur;
+            let is_last = cur(meta, is_last);
+            let fp_acc = cur(meta, fp_acc);
+            let fp_pow = cur(meta, fp_pow);
+            let [is_reduce, is_init, is_mul, is_square] =
+                [is_reduce, is_init, is_mul, is_square].map(|kind| cur(meta, kind));
+            let expected_a = is_reduce.clone() * cur(meta, modexp_table.base)
+                + is_init.clone()
+                + is_mul.clone() * fp_acc
+                + is_square.clone() * fp_pow.clone();
+            let expected_b = is_reduce + is_init + (is_mul + is_square) * fp_pow;
+            let modulus = cur(meta, modexp_table.modulus) + cur(meta, is_mod_zero);
+            let borrow = cur(meta, borrow);
+            cb.condition(is_last, |cb| {
+                cb.require_zero(
+                    "last row: A(x) * B(x) - Q(x) * M(x) - R(x) == (x - 256) * C(x)",
+                    acc_a.clone() * acc_b.clone()
+                        - acc_q * acc_m.clone()
+                        - acc_r
+                        - (x.clone() - 256.expr()) * acc_c,
+                );
+                cb.require_zero("last row: r < m", borrow);
+                cb.require_equal("last row: M(x) == modulus", acc_m, modulus);
+                cb.require_equal("last row: A(x) == expected a", acc_a, expected_a);
+                cb.require_equal("last row: B(x) == expected b", acc_b, expected_b);
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("modexp exponentiation", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let event_start = prev(meta, modexp_table.is_final) + not::expr(prev(meta, is_event));
+            let event = cur(meta, is_event);
+
+            cb.condition(event.clone() * event_start.clone(), |cb| {
+                cb.require_equal(
+                    "first multiplication reduces the base",
+                    cur(meta, is_reduce),
+                    1.expr(),
+                );
+                cb.require_zero(
+                    "first multiplication: exp == 0",
+                    cur(meta, modexp_table.exp),
+                );
+                cb.require_zero("first multiplication: exp_bits == 0", cur(meta, exp_bits));
+                cb.require_equal(
+                    "first multiplication: exp_pow2 == 1",
+                    cur(meta, exp_pow2),
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "first multiplication: exp_pow_r == 1",
+                    cur(meta, exp_pow_r),
+                    1.expr(),
+                );
+            });
+
+            let unchanged = [modexp_table.base, modexp_table.modulus, is_mod_zero]
+                .map(|column| (cur(meta, column), prev(meta, column)));
+            cb.condition(event * not::expr(event_start), |cb| {
+                for (value, value_prev) in unchanged {
+                    cb.require_equal(
+                        "operands are unchanged within an exponentiation",
+                        value,
+                        value_prev,
+                    );
+                }
+            });
+
+            let [is_reduce_prev, is_init_prev, is_mul_prev, is_square_prev] =
+                [is_reduce, is_init, is_mul, is_square].map(|kind| prev(meta, kind));
+            let is_mul_cur = cur(meta, is_mul);
+            let is_square_cur = cur(meta, is_square);
+            let acc_r_prev = prev(meta, acc[4]);
+            let is_byte_end_prev = prev(meta, is_byte_end);
+            let exp_pow2_prev = prev(meta, exp_pow2);
+            let exp_pow_r_prev = prev(meta, exp_pow_r);
+            let exp_pow2_cur = cur(meta, exp_pow2);
+            let exp_pow_r_cur = cur(meta, exp_pow_r);
+            let transition = prev(meta, is_last) * not::expr(prev(meta, modexp_table.is_final));
+            cb.condition(transition, |cb| {
+                cb.require_equal(
+                    "an exponentiation continues until its final row",
+                    cur(meta, is_event),
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "reduction => initialization",
+                    is_reduce_prev.clone(),
+                    is_reduce_prev.clone() * cur(meta, is_init),
+                );
+                cb.require_equal(
+                    "initialization => multiplication or squaring",
+                    is_init_prev.clone(),
+                    is_init_prev.clone() * (is_mul_cur.clone() + is_square_cur.clone()),
+                );
+                cb.require_equal(
+                    "multiplication => squaring",
+                    is_mul_prev.clone(),
+                    is_mul_prev.clone() * is_square_cur.clone(),
+                );
+                cb.require_equal(
+                    "squaring => multiplication or squaring",
+                    is_square_prev.clone(),
+                    is_square_prev.clone() * (is_mul_cur.clone() + is_square_cur),
+                );
+
+                cb.require_equal(
+                    "fp_pow is updated by reductions and squarings",
+                    cur(meta, fp_pow),
+                    select::expr(
+                        is_reduce_prev + is_square_prev.clone(),
+                        acc_r_prev.clone(),
+                        prev(meta, fp_pow),
+                    ),
+                );
+                cb.require_equal(
+                    "fp_acc is updated by initializations and multiplications",
+                    cur(meta, fp_acc),
+                    select::expr(is_init_prev + is_mul_prev, acc_r_prev, prev(meta, fp_acc)),
+                );
+
+                cb.require_equal(
+                    "squaring moves to the next bit",
+                    cur(meta, exp_bits),
+                    prev(meta, exp_bits) + is_square_prev.clone(),
+                );
+                cb.require_equal(
+                    "exp_pow2 is doubled by a squaring, modulo 256",
+                    exp_pow2_cur.clone(),
+                    select::expr(
+                        is_square_prev.clone(),
+                        select::expr(
+                            is_byte_end_prev.clone(),
+                            1.expr(),
+                            exp_pow2_prev.clone() * 2.expr(),
+                        ),
+                        exp_pow2_prev,
+                    ),
+                );
+                cb.require_equal(
+                    "exp_pow_r is multiplied by x at the end of a byte",
+                    exp_pow_r_cur.clone(),
+                    exp_pow_r_prev
+                        * (1.expr() + is_square_prev * is_byte_end_prev * (x - 1.expr())),
+                );
+                cb.require_equal(
+                    "a multiplication adds the current bit to the exponent",
+                    cur(meta, modexp_table.exp),
+                    prev(meta, modexp_table.exp)
+                        + is_mul_cur.clone() * exp_pow2_cur * exp_pow_r_cur,
+                );
+            });
+
+            let is_final = cur(meta, modexp_table.is_final);
+            cb.condition(is_final, |cb| {
+                cb.require_zero(
+                    "final multiplication is an initialization or a multiplication",
+                    cur(meta, is_reduce) + cur(meta, is_square),
+                );
+                cb.require_equal(
+                    "final row: result == R(x)",
+                    cur(meta, modexp_table.result),
+                    cur(meta, acc[4]),
+                );
+                cb.require_equal(
+                    "final row: exp_bit_len is the index of the last set bit plus 1",
+                    cur(meta, modexp_table.exp_bit_len),
+                    is_mul_cur * (cur(meta, exp_bits) + 1.expr()),
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        for (name, column) in [
+            ("modexp a byte range", a),
+            ("modexp b byte range", b),
+            ("modexp q byte range", q),
+            ("modexp m byte range", m),
+            ("modexp r byte range", r),
+            ("modexp s byte range", s),
+            ("modexp carry_hi range", carry_hi),
+        ] {
+            meta.lookup(name, |meta| {
+                let q_enable = meta.query_fixed(q_enable, Rotation::cur());
+                vec![(q_enable * cur(meta, column), u8_table.into())]
+            });
+        }
+        meta.lookup("modexp carry_lo range", |meta| {
+            let q_enable = meta.query_fixed(q_enable, Rotation::cur());
+            vec![(q_enable * cur(meta, carry_lo), u16_table.into())]
+        });
+
         Self {
-            rangecheck_config,
-            modexp_config,
+            q_enable,
+            is_event,
+            is_last,
+            is_reduce,
+            is_init,
+            is_mul,
+            is_square,
+            a,
+            b,
+            q,
+            m,
+            r,
+            s,
+            borrow,
+            carry_lo,
+            carry_hi,
+            pow_x,
+            acc,
+            fp_acc,
+            fp_pow,
+            exp_bits,
+            exp_pow2,
+            exp_pow2_inv,
+            is_byte_end,
+            exp_pow_r,
+            is_mod_zero,
+            modulus_inv,
             modexp_table,
+            u8_table,
+            u16_table,
+            _marker: PhantomData,
         }
     }
 }
 
-impl ModExpCircuitConfig {
-    pub(crate) fn assign_group<F: Field>(
+impl<F: Field> ModExpCircuitConfig<F> {
+    /// Columns of the first phase.
+    fn first_phase_columns(&self) -> [Column<Advice>; 22] {
+        [
+            self.is_event,
+            self.is_last,
+            self.modexp_table.is_final,
+            self.is_reduce,
+            self.is_init,
+            self.is_mul,
+            self.is_square,
+            self.a,
+            self.b,
+            self.q,
+            self.m,
+            self.r,
+            self.s,
+            self.borrow,
+            self.carry_lo,
+            self.carry_hi,
+            self.exp_bits,
+            self.exp_pow2,
+            self.exp_pow2_inv,
+            self.is_byte_end,
+            self.is_mod_zero,
+            self.modexp_table.exp_bit_len,
+        ]
+    }
+
+    /// Columns of the second phase.
+    fn second_phase_columns(&self) -> [Column<Advice>; 15] {
+        [
+            self.pow_x,
+            self.acc[0],
+            self.acc[1],
+            self.acc[2],
+            self.acc[3],
+            self.acc[4],
+            self.acc[5],
+            self.fp_acc,
+            self.fp_pow,
+            self.exp_pow_r,
+            self.modulus_inv,
+            self.modexp_table.base,
+            self.modexp_table.exp,
+            self.modexp_table.modulus,
+            self.modexp_table.result,
+        ]
+    }
+
+    /// Assign a row, the values being given in the order of the columns of each phase.
+    fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        first_phase: [F; 22],
+        second_phase: [Value<F>; 15],
+    ) -> Result<(), Error> {
+        for (column, value) in self.first_phase_columns().into_iter().zip(first_phase) {
+            region.assign_advice(
+                || format!("modexp circuit row {offset}"),
+                column,
+                offset,
+                || Value::known(value),
+            )?;
+        }
+        for (column, value) in self.second_phase_columns().into_iter().zip(second_phase) {
+            region.assign_advice(
+                || format!("modexp circuit row {offset}"),
+                column,
+                offset,
+                || value,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Assign a row outside of the exponentiations.
+    fn assign_zero_row(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
+        self.assign_row(
+            region,
+            offset,
+            [F::zero(); 22],
+            [(); 15].map(|_| Value::known(F::zero())),
+        )
+    }
+
+    /// Assign the multiplications of an exponentiation from `offset`, returning the offset of
+    /// the next exponentiation.
+    fn assign_event(
         &self,
-        region: &mut Region<F>,
-        table_offset: usize,
-        mut calc_offset: usize,
+        region: &mut Region<'_, F>,
+        mut offset: usize,
         event: &BigModExp,
-        modexp_chip: &ModExpChip<F>,
-        range_check_chip: &mut RangeCheckChip<F>,
+        challenges: &Challenges<Value<F>>,
     ) -> Result<usize, Error> {
-        let base = self.assign_value(region, table_offset, self.modexp_table.base, &event.base)?;
-        let exp =
-            self.assign_value(region, table_offset, self.modexp_table.exp, &event.exponent)?;
-        let modulus = self.assign_value(
-            region,
-            table_offset,
-            self.modexp_table.modulus,
-            &event.modulus,
-        )?;
-        let ret = modexp_chip.mod_exp(
-            region,
-            range_check_chip,
-            &mut calc_offset,
-            &base,
-            &exp,
-            &modulus,
-        )?;
-        for i in 0..4 {
-            region.assign_fixed(
-                || format!("modexp table head {}", table_offset + i),
-                self.modexp_table.q_head,
-                table_offset + i,
-                || Value::known(if i == 0 { F::one() } else { F::zero() }),
-            )?;
+        let x = challenges.keccak_input();
+        let rlc = |bytes: &[u8]| x.map(|x| rlc::value(bytes.iter().rev(), x));
+        let base = rlc(&event.base);
+        let exp = rlc(&event.exponent);
+        let modulus = rlc(&event.modulus);
+        let result = rlc(&event.result);
+        let modulus_inv = modulus.map(|modulus| modulus.invert().unwrap_or(F::zero()));
+        let is_mod_zero = event.modulus.iter().all(|&byte| byte == 0);
+        let exp_bit_len = F::from(event.exponent_bit_len() as u64);
+
+        let n = num_limbs(event);
+        let m = le_bytes(&mul_modulus(event), n);
+        let muls = mod_muls(event);
+        let (mut exp_bits, mut exp_pow2) = (0u64, 1u64);
+        let (mut exp_pow_r, mut exp_acc) = (Value::known(F::one()), Value::known(F::zero()));
+        let (mut fp_acc, mut fp_pow) = (Value::known(F::zero()), Value::known(F::zero()));
+
+        for (i, mul) in muls.iter().enumerate() {
+            if mul.kind == MulKind::Mul {
+                exp_acc = exp_acc + exp_pow_r * Value::known(F::from(exp_pow2));
+            }
+            let [a, b, q, r] = [&mul.a, &mul.b, &mul.q, &mul.r].map(|value| le_bytes(value, n));
+            let carries = carries(&a, &b, &q, &m, &r);
+            let kind = [
+                MulKind::Reduce,
+                MulKind::Init,
+                MulKind::Mul,
+                MulKind::Square,
+            ]
+            .map(|kind| F::from((mul.kind == kind) as u64));
+            let exp_pow2_inv = (F::from(exp_pow2) - F::from(128))
+                .invert()
+                .unwrap_or(F::zero());
+
+            let mut borrow = 1;
+            let mut pow_x = Value::known(F::one());
+            let mut acc = [(); 6].map(|_| Value::known(F::zero()));
+            for (k, &m_k) in m.iter().enumerate() {
+                let is_last = k == n - 1;
+                let diff = m_k as i64 - r[k] as i64 - borrow;
+                borrow = (diff < 0) as i64;
+                let s = diff + 256 * borrow;
+                let carry = (carries[k] + CARRY_OFFSET as i64) as u64;
+                let values = [a[k], b[k], q[k], m_k, r[k]]
+                    .map(|byte| F::from(byte as u64))
+                    .into_iter()
+                    .chain([F::from(carry) - F::from(CARRY_OFFSET)]);
+                for (acc, value) in acc.iter_mut().zip(values) {
+                    *acc = *acc + pow_x * Value::known(value);
+                }
 
-            ret.limbs[i]
-                .cell
-                .clone()
-                .expect("should has assigned after modexp")
-                .copy_advice(
-                    || "copy to result limbs",
+                self.assign_row(
                     region,
-                    self.modexp_table.result,
-                    table_offset + i,
+                    offset,
+                    [
+                        F::one(),
+                        F::from(is_last as u64),
+                        F::from((is_last && i == muls.len() - 1) as u64),
+                        kind[0],
+                        kind[1],
+                        kind[2],
+                        kind[3],
+                        F::from(a[k] as u64),
+                        F::from(b[k] as u64),
+                        F::from(q[k] as u64),
+                        F::from(m_k as u64),
+                        F::from(r[k] as u64),
+                        F::from(s as u64),
+                        F::from(borrow as u64),
+                        F::from(carry & 0xffff),
+                        F::from(carry >> 16),
+                        F::from(exp_bits),
+                        F::from(exp_pow2),
+                        exp_pow2_inv,
+                        F::from((exp_pow2 == 128) as u64),
+                        F::from(is_mod_zero as u64),
+                        exp_bit_len,
+                    ],
+                    [
+                        pow_x,
+                        acc[0],
+                        acc[1],
+                        acc[2],
+                        acc[3],
+                        acc[4],
+                        acc[5],
+                        fp_acc,
+                        fp_pow,
+                        exp_pow_r,
+                        modulus_inv,
+                        base,
+                        exp_acc,
+                        modulus,
+                        result,
+                    ],
                 )?;
-        }
-        Ok(calc_offset)
-    }
+                pow_x = pow_x * x;
+                offset += 1;
+            }
 
-    fn assign_value<F: Field>(
-        &self,
-        region: &mut Region<F>,
-        offset: usize,
-        col: Column<Advice>,
-        value: &Word,
-    ) -> Result<Number<F>, Error> {
-        let limbs_v = ModExpTable::split_u256_108bit_limbs(value);
-        let native_v = ModExpTable::native_u256(value);
-        let mut limbs = Vec::new();
-
-        for (i, limb) in limbs_v.into_iter().enumerate() {
-            let fv = F::from_u128(limb);
-            let c = region.assign_advice(
-                || "assign modexp limb",
-                col,
-                offset + i,
-                || Value::known(fv),
-            )?;
-            limbs.push(Limb::new(Some(c), fv));
+            match mul.kind {
+                MulKind::Reduce | MulKind::Square => fp_pow = acc[4],
+                MulKind::Init | MulKind::Mul => fp_acc = acc[4],
+            }
+            if mul.kind == MulKind::Square {
+                exp_bits += 1;
+                if exp_pow2 == 128 {
+                    exp_pow2 = 1;
+                    exp_pow_r = exp_pow_r * x;
+                } else {
+                    exp_pow2 *= 2;
+                }
+            }
         }
-        let c = region.assign_advice(
-            || "assign modexp native",
-            col,
-            offset + 3,
-            || Value::known(native_v),
-        )?;
-        limbs.push(Limb::new(Some(c), native_v));
-        Ok(Number {
-            limbs: limbs.try_into().expect("just 4 pushes"),
-        })
+        exp_acc
+            .zip(exp)
+            .assert_if_known(|(exp_acc, exp)| exp_acc == exp);
+
+        Ok(offset)
     }
 }
 
-const MODEXPCONFIG_EACH_CHIP_ROWS: usize = 39962;
-
 /// ModExp circuit for precompile modexp
 #[derive(Clone, Debug, Default)]
-pub struct ModExpCircuit<F: Field>(Vec<BigModExp>, std::marker::PhantomData<F>);
+pub struct ModExpCircuit<F> {
+    /// The exponentiations to verify.
+    pub events: Vec<BigModExp>,
+    /// Maximum number of rows of the circuit, or 0 to use the rows needed by the events.
+    pub max_rows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> ModExpCircuit<F> {
+    /// Return a new ModExpCircuit
+    pub fn new(events: Vec<BigModExp>, max_rows: usize) -> Self {
+        Self {
+            events,
+            max_rows,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Rows needed by the exponentiations, besides the leading all-zero row.
+    pub fn rows_needed(events: &[BigModExp]) -> usize {
+        events
+            .iter()
+            .map(|event| num_muls(event) * num_limbs(event))
+            .sum()
+    }
+}
 
 impl<F: Field> SubCircuit<F> for ModExpCircuit<F> {
-    type Config = ModExpCircuitConfig;
+    type Config = ModExpCircuitConfig<F>;
 
     fn unusable_rows() -> usize {
-        // No column queried at more than 4 distinct rotations, so returns 8 as
+        // No column queried at more than 2 distinct rotations, so returns 6 as
         // minimum unusable rows.
-        8
+        6
     }
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
-        let event_limit = block.circuits_params.max_keccak_rows / MODEXPCONFIG_EACH_CHIP_ROWS;
-
-        let mut exp_events = block.get_big_modexp();
-        if event_limit != 0 {
+        let max_rows = block.circuits_params.max_keccak_rows;
+        let events = block.get_big_modexp();
+        if max_rows != 0 {
+            let rows = Self::rows_needed(&events) + 1;
             assert!(
-                exp_events.len() <= event_limit,
+                rows <= max_rows,
                 "no enough rows for modexp circuit, expected {}, limit {}",
-                exp_events.len(),
-                event_limit,
+                rows,
+                max_rows,
             );
-            exp_events.resize(event_limit, Default::default());
-            log::info!("modexp circuit work with maxium {} entries", event_limit);
+            log::info!("modexp circuit uses {} of {} rows", rows, max_rows);
         }
 
-        Self(exp_events, Default::default())
+        Self::new(events, max_rows)
     }
 
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let exp_events = block.get_big_modexp();
-        let real_len = exp_events.len() * MODEXPCONFIG_EACH_CHIP_ROWS;
+        let real_len = Self::rows_needed(&block.get_big_modexp());
         (
             real_len,
             real_len
@@ -179,34 +947,37 @@ impl<F: Field> SubCircuit<F> for ModExpCircuit<F> {
     fn synthesize_sub(
         &self,
         config: &Self::Config,
-        _challenges: &Challenges<Value<F>>,
+        challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
-        let modexp_chip = ModExpChip::new(config.modexp_config.clone());
-        let mut range_chip = RangeCheckChip::new(config.rangecheck_config.clone());
+        let num_rows = if self.max_rows == 0 {
+            Self::rows_needed(&self.events) + 1
+        } else {
+            self.max_rows
+        };
 
         layouter.assign_region(
             || "modexp circuit",
             |mut region| {
-                range_chip.initialize(&mut region)?;
-                let _modexp_count = self.0.len();
-                let mut calc_offset = 0;
-                for (n, event) in self.0.iter().enumerate() {
-                    calc_offset = config.assign_group(
-                        &mut region,
-                        n * 4,
-                        calc_offset,
-                        event,
-                        &modexp_chip,
-                        &mut range_chip,
+                config.assign_zero_row(&mut region, 0)?;
+                for offset in 1..num_rows {
+                    region.assign_fixed(
+                        || format!("modexp circuit q_enable {offset}"),
+                        config.q_enable,
+                        offset,
+                        || Value::known(F::one()),
                     )?;
                 }
-                //assert_eq!(max(calc_offset, range_chip.offset), MODEXPCONFIG_EACH_CHIP_ROWS *
-                // modexp_count);
+
+                let mut offset = 1;
+                for event in self.events.iter() {
+                    offset = config.assign_event(&mut region, offset, event, challenges)?;
+                }
+                for offset in offset..num_rows {
+                    config.assign_zero_row(&mut region, offset)?;
+                }
                 Ok(())
             },
-        )?;
-
-        config.modexp_table.fill_blank(layouter)
+        )
     }
 }
@@ -7,7 +7,7 @@ use halo2_proofs::{
 };
 
 impl Circuit<Fr> for ModExpCircuit<Fr> {
-    type Config = (ModExpCircuitConfig, MockChallenges);
+    type Config = (ModExpCircuitConfig<Fr>, MockChallenges);
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
@@ -16,9 +16,20 @@ impl Circuit<Fr> for ModExpCircuit<Fr> {
 
     fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
         let modexp_table = ModExpTable::construct(meta);
+        let u8_table = U8Table::construct(meta);
+        let u16_table = U16Table::construct(meta);
         let challenge = MockChallenges::construct(meta);
+        let challenges = challenge.exprs(meta);
         (
-            <ModExpCircuitConfig as SubCircuitConfig<Fr>>::new(meta, modexp_table),
+            <ModExpCircuitConfig<Fr> as SubCircuitConfig<Fr>>::new(
+                meta,
+                ModExpCircuitConfigArgs {
+                    modexp_table,
+                    u8_table,
+                    u16_table,
+                    challenges,
+                },
+            ),
             challenge,
         )
     }
@@ -29,6 +40,8 @@ impl Circuit<Fr> for ModExpCircuit<Fr> {
         mut layouter: impl Layouter<Fr>,
     ) -> Result<(), Error> {
         let challenges = challenge.values(&layouter);
+        config.u8_table.load(&mut layouter)?;
+        config.u16_table.load(&mut layouter)?;
         <Self as SubCircuit<Fr>>::synthesize_sub(self, &config, &challenges, &mut layouter)
     }
 }
@@ -1,57 +1,122 @@
 #![allow(unused_imports)]
 use super::*;
 
-use eth_types::U256;
-use halo2_proofs::dev::MockProver;
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
 
-#[test]
-fn test_modexp_circuit_00() {
-    let event1 = construct_modexp(Word::from(1u128), Word::from(3u128), Word::from(7u128));
+fn construct_modexp(base: &[u8], exponent: &[u8], modulus: &[u8]) -> BigModExp {
+    let m = BigUint::from_bytes_be(modulus);
+    let result = if m == BigUint::from(0u8) {
+        BigUint::from(0u8)
+    } else {
+        BigUint::from_bytes_be(base).modpow(&BigUint::from_bytes_be(exponent), &m)
+    };
+    let result = result.to_bytes_be();
+    // the result has the length of the modulus
+    let len = result.len().min(modulus.len());
+    let mut padded = vec![0; modulus.len()];
+    padded[modulus.len() - len..].copy_from_slice(&result[result.len() - len..]);
+
+    BigModExp {
+        base: base.to_vec(),
+        exponent: exponent.to_vec(),
+        modulus: modulus.to_vec(),
+        result: padded,
+    }
+}
+
+fn pattern_bytes(len: usize, seed: u8) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i as u8).wrapping_mul(37).wrapping_add(seed))
+        .collect()
+}
 
-    let test_circuit = ModExpCircuit(vec![event1], Default::default());
-    let prover = MockProver::run(16, &test_circuit, vec![]).unwrap();
+fn test_ok(events: Vec<BigModExp>, k: u32) {
+    let test_circuit = ModExpCircuit::<Fr>::new(events, 0);
+    let prover = MockProver::run(k, &test_circuit, vec![]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
 
 #[test]
-fn test_modexp_circuit_01() {
-    let event1 = construct_modexp(Word::from(1u128), Word::from(2u128), Word::from(7u128));
+fn test_modexp_circuit_00() {
+    test_ok(vec![construct_modexp(&[1], &[3], &[7])], 17);
+}
 
-    let test_circuit = ModExpCircuit(vec![event1], Default::default());
-    let prover = MockProver::run(16, &test_circuit, vec![]).unwrap();
-    assert_eq!(prover.verify(), Ok(()));
+#[test]
+fn test_modexp_circuit_01() {
+    test_ok(vec![construct_modexp(&[1], &[2], &[7])], 17);
 }
+
 #[test]
 fn test_modexp_circuit_02() {
-    let event1 = construct_modexp(Word::from(2u128), Word::from(2u128), Word::from(7u128));
-    let event2 = construct_modexp(Word::from(3u128), Word::from(21u128), Word::from(78u128));
-
-    let test_circuit = ModExpCircuit(vec![event1, event2], Default::default());
-    let prover = MockProver::run(17, &test_circuit, vec![]).unwrap();
-    assert_eq!(prover.verify(), Ok(()));
+    test_ok(
+        vec![
+            construct_modexp(&[2], &[2], &[7]),
+            construct_modexp(&[3], &[21], &[78]),
+        ],
+        17,
+    );
 }
 
 // test all zeros case (exp == mod == base == 0)
 #[test]
 fn test_modexp_circuit_03() {
-    let event1 = construct_modexp(Word::from(0u128), Word::from(0u128), Word::from(0u128));
+    test_ok(vec![construct_modexp(&[0], &[0], &[0])], 17);
+}
 
-    let test_circuit = ModExpCircuit(vec![event1], Default::default());
-    let prover = MockProver::run(16, &test_circuit, vec![]).unwrap();
-    assert_eq!(prover.verify(), Ok(()));
+#[test]
+fn test_modexp_circuit_empty_operands() {
+    test_ok(
+        vec![
+            construct_modexp(&[], &[], &[]),
+            construct_modexp(&[], &[1], &[5]),
+        ],
+        17,
+    );
 }
 
-fn construct_modexp(base: U256, exp: U256, modulus: U256) -> BigModExp {
-    let result = if modulus == Word::zero() {
-        Word::zero()
-    } else {
-        base.pow(exp).div_mod(modulus).1
-    };
+#[test]
+fn test_modexp_circuit_zero_modulus() {
+    test_ok(vec![construct_modexp(&[3, 4], &[0x12, 0x34], &[0, 0])], 17);
+}
 
-    BigModExp {
-        base,
-        exponent: exp,
-        modulus,
-        result,
-    }
+#[test]
+fn test_modexp_circuit_multi_byte_exponent() {
+    // the bits of the exponent span several bytes, with leading zero bytes
+    test_ok(
+        vec![construct_modexp(
+            &[0xff; 3],
+            &[0, 0x81, 0x00, 0x3c],
+            &[0x01, 0x00, 0x01],
+        )],
+        17,
+    );
+}
+
+#[test]
+fn test_modexp_circuit_64_bytes() {
+    let mut modulus = pattern_bytes(64, 11);
+    modulus[63] |= 1;
+    test_ok(
+        vec![construct_modexp(
+            &pattern_bytes(64, 5),
+            &pattern_bytes(2, 3),
+            &modulus,
+        )],
+        17,
+    );
+}
+
+#[test]
+fn test_modexp_circuit_rsa_2048() {
+    let mut modulus = pattern_bytes(256, 11);
+    modulus[0] |= 0x80;
+    modulus[255] |= 1;
+    test_ok(
+        vec![construct_modexp(
+            &pattern_bytes(256, 5)[1..],
+            &[0x01, 0x00, 0x01],
+            &modulus,
+        )],
+        17,
+    );
 }
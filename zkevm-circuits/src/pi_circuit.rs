@@ -27,8 +27,8 @@ use halo2_proofs::plonk::SecondPhase;
 use crate::{
     evm_circuit::{util::constraint_builder::BaseConstraintBuilder, EvmCircuitExports},
     pi_circuit::param::{
-        BASE_FEE_OFFSET, BLOCK_HEADER_BYTES_NUM, BLOCK_LEN, BLOCK_NUM_OFFSET, BYTE_POW_BASE,
-        CHAIN_ID_OFFSET, GAS_LIMIT_OFFSET, KECCAK_DIGEST_SIZE, RPI_CELL_IDX,
+        BASE_FEE_OFFSET, BLOB_BASE_FEE_OFFSET, BLOCK_HEADER_BYTES_NUM, BLOCK_LEN, BLOCK_NUM_OFFSET,
+        BYTE_POW_BASE, CHAIN_ID_OFFSET, GAS_LIMIT_OFFSET, KECCAK_DIGEST_SIZE, RPI_CELL_IDX,
         RPI_LENGTH_ACC_CELL_IDX, RPI_RLC_ACC_CELL_IDX, TIMESTAMP_OFFSET,
     },
     state_circuit::StateCircuitExports,
@@ -49,8 +49,8 @@ use crate::{
     table::{
         BlockContextFieldTag,
         BlockContextFieldTag::{
-            BaseFee, ChainId, Coinbase, CumNumTxs, Difficulty, GasLimit, NumAllTxs, NumTxs, Number,
-            Timestamp,
+            BaseFee, BlobBaseFee, ChainId, Coinbase, CumNumTxs, Difficulty, GasLimit, NumAllTxs,
+            NumTxs, Number, Timestamp,
         },
     },
     util::rlc_be_bytes,
@@ -173,6 +173,7 @@ impl PublicData {
                     .chain(block.base_fee.to_be_bytes())
                     .chain(block.gas_limit.to_be_bytes())
                     .chain(num_all_txs.to_be_bytes())
+                    .chain(block.blob_base_fee.to_be_bytes())
             }))
             // Tx Hashes
             .chain(
@@ -309,6 +310,7 @@ impl BlockContext {
             number: Default::default(),
             timestamp: Default::default(),
             base_fee: Default::default(),
+            blob_base_fee: Default::default(),
             history_hashes: vec![],
             eth_block: Default::default(),
         }
@@ -739,9 +741,10 @@ impl<F: Field> PiCircuitConfig<F> {
     /// |          | block\[0\].base_fee    |                          |
     /// |          | block\[0\].gas_limit   |                          |
     /// |          | block\[0\].num_all_txs |                          |
+    /// |          | block\[0\].blob_base_fee |                        |
     /// |          | block\[1\].number      | <- q_block_context == 1  |
     /// | *PART 1* | ...                    |                          |
-    /// |          | block\[n\].num_all_txs |                          |
+    /// |          | block\[n\].blob_base_fee |                        |
     /// | ASSIGN   | PADDING                |                          |
     /// | DATA     | ...                    |                          |
     /// | BYTES    | PADDING                |                          |
@@ -911,6 +914,11 @@ impl<F: Field> PiCircuitConfig<F> {
                 (block.gas_limit.to_be_bytes().to_vec(), GAS_LIMIT_OFFSET),
                 // num txs in block
                 (num_all_txs.to_be_bytes().to_vec(), NUM_ALL_TXS_OFFSET),
+                // blob base fee
+                (
+                    block.blob_base_fee.to_be_bytes().to_vec(),
+                    BLOB_BASE_FEE_OFFSET,
+                ),
             ] {
                 let (tmp_offset, tmp_rpi_rlc_acc, tmp_rpi_length, cells) = self.assign_field(
                     region,
@@ -1517,8 +1525,17 @@ impl<F: Field> PiCircuitConfig<F> {
                 .cloned()
                 .unwrap_or(0);
             let tag = [
-                Coinbase, Timestamp, Number, Difficulty, GasLimit, BaseFee, ChainId, NumTxs,
-                CumNumTxs, NumAllTxs,
+                Coinbase,
+                Timestamp,
+                Number,
+                Difficulty,
+                GasLimit,
+                BaseFee,
+                ChainId,
+                NumTxs,
+                CumNumTxs,
+                NumAllTxs,
+                BlobBaseFee,
             ];
 
             // index_cells of same block are equal to block_number.
@@ -3,15 +3,17 @@ use crate::{
     bytecode_circuit::bytecode_unroller::HASHBLOCK_BYTES_IN_FIELD,
     table::PoseidonTable,
     util::{Challenges, SubCircuit, SubCircuitConfig},
-    witness::{self},
+    witness::{self, Bytecode},
 };
 //use bus_mapping::state_db::CodeDB;
-use eth_types::Field;
+use eth_types::{Field, Word};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{Circuit, ConstraintSystem, Error},
 };
 use hash_circuit::hash::{Hashable, PoseidonHashChip, PoseidonHashConfig, PoseidonHashTable};
+use mpt_zktrie::mpt_circuits::serde::SMTTrace;
+use std::collections::{BTreeMap, HashMap};
 
 /// re-wrapping for mpt circuit
 #[derive(Default, Clone, Debug)]
@@ -29,6 +31,106 @@ pub struct PoseidonCircuitConfig<F: Field>(pub(crate) PoseidonHashConfig<F>);
 
 const HASH_BLOCK_STEP_SIZE: usize = HASHBLOCK_BYTES_IN_FIELD * PoseidonTable::INPUT_WIDTH;
 
+/// Counter of the hashes in the SMT traces, which are deduplicated in the poseidon circuit.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MptHashCounter {
+    path: HashMap<[u8; 32], usize>,
+    account: HashMap<[u8; 32], usize>,
+    storage: HashMap<[u8; 32], usize>,
+    key: HashMap<[u8; 32], usize>,
+}
+
+impl MptHashCounter {
+    pub(crate) fn insert(&mut self, smt_trace: &SMTTrace) {
+        let insert = |map: &mut HashMap<[u8; 32], usize>, k| {
+            *map.entry(k).or_insert(0) += 1;
+        };
+        // for a smt trace there are mutiple sources for hashes:
+        // + account path, each layer (include the root) cost 1 hashes
+        insert(&mut self.path, smt_trace.account_path[0].root.0);
+        for node in &smt_trace.account_path[0].path {
+            insert(&mut self.path, node.value.0);
+        }
+        for node in &smt_trace.account_path[1].path {
+            insert(&mut self.path, node.value.0);
+        }
+
+        // + the hashes required for leaf is dynamic and depended
+        // on the type of mpt updates, here we suppose to count
+        // all of the 4 hashes once
+        if let Some(node) = smt_trace.account_path[0].leaf {
+            insert(&mut self.account, node.value.0);
+        }
+        if let Some(node) = smt_trace.account_path[1].leaf {
+            insert(&mut self.account, node.value.0);
+        }
+
+        // + and the address key
+        insert(&mut self.key, smt_trace.account_key.0);
+
+        // + state path, like account path
+        if let Some(path) = &smt_trace.state_path[0] {
+            for node in &path.path {
+                insert(&mut self.path, node.value.0);
+            }
+        }
+
+        if let Some(path) = &smt_trace.state_path[1] {
+            for node in &path.path {
+                insert(&mut self.path, node.value.0);
+            }
+        }
+
+        // + state leaf
+        if let Some(node) = smt_trace.state_path[0].as_ref().and_then(|pt| pt.leaf) {
+            insert(&mut self.storage, node.value.0);
+        }
+        if let Some(node) = smt_trace.state_path[1].as_ref().and_then(|pt| pt.leaf) {
+            insert(&mut self.storage, node.value.0);
+        }
+
+        // + the storage key
+        if let Some(hash) = smt_trace.state_key {
+            insert(&mut self.key, hash.0);
+        }
+    }
+
+    /// Number of the hashes before and after the deduplication.
+    pub(crate) fn num_hashes(&self) -> (usize, usize) {
+        let sum_count = |h: &HashMap<[u8; 32], usize>| h.values().sum::<usize>();
+        let prev_dedup_size = sum_count(&self.path)
+            + sum_count(&self.key)
+            + sum_count(&self.account) * 4
+            + sum_count(&self.storage);
+        let after_dedup_size =
+            self.path.len() + self.key.len() + self.account.len() * 4 + self.storage.len();
+
+        (prev_dedup_size, after_dedup_size)
+    }
+}
+
+/// Minimum number of rows required by the hashes of the SMT traces and the bytecodes, as
+/// `min_num_rows_block` of the poseidon circuit.
+pub(crate) fn poseidon_min_num_rows<F: Field>(
+    mpt_hashes: &MptHashCounter,
+    bytecodes: &BTreeMap<Word, Bytecode>,
+    max_poseidon_rows: usize,
+) -> (usize, usize) {
+    let (prev_dedup_size, after_dedup_size) = mpt_hashes.num_hashes();
+    log::debug!("poseidon circuit row num: dedup mpt from {prev_dedup_size} to {after_dedup_size}");
+    let mpt_row_num = after_dedup_size * F::hash_block_size();
+    let byte_row_num = bytecodes
+        .values()
+        .map(|bytecode| bytecode.bytes.len() / HASH_BLOCK_STEP_SIZE + 1)
+        .sum::<usize>()
+        * F::hash_block_size();
+    let total_row_num = mpt_row_num + byte_row_num;
+    log::debug!(
+        "poseidon circuit row num: {mpt_row_num}(mpt) + {byte_row_num}(bytecode) = {total_row_num}"
+    );
+    (total_row_num, max_poseidon_rows.max(total_row_num))
+}
+
 impl<F: Field> SubCircuitConfig<F> for PoseidonCircuitConfig<F> {
     type ConfigArgs = PoseidonCircuitConfigArgs;
 
@@ -94,86 +196,20 @@ impl<F: Field> SubCircuit<F> for PoseidonCircuit<F> {
     }
 
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let mut path_hash_counter: std::collections::HashMap<[u8; 32], usize> = Default::default();
-        let mut account_counter: std::collections::HashMap<[u8; 32], usize> = Default::default();
-        let mut storage_counter: std::collections::HashMap<[u8; 32], usize> = Default::default();
-        let mut key_counter: std::collections::HashMap<[u8; 32], usize> = Default::default();
-        let insert = |map: &mut std::collections::HashMap<[u8; 32], usize>, k| {
-            *map.entry(k).or_insert(0) += 1;
-        };
+        let mut mpt_hashes = MptHashCounter::default();
         for smt_trace in &block.mpt_updates.smt_traces {
-            // for a smt trace there are mutiple sources for hashes:
-            // + account path, each layer (include the root) cost 1 hashes
-            insert(&mut path_hash_counter, smt_trace.account_path[0].root.0);
-            for node in &smt_trace.account_path[0].path {
-                insert(&mut path_hash_counter, node.value.0);
-            }
-            for node in &smt_trace.account_path[1].path {
-                insert(&mut path_hash_counter, node.value.0);
-            }
-
-            // + the hashes required for leaf is dynamic and depended
-            // on the type of mpt updates, here we suppose to count
-            // all of the 4 hashes once
-            if let Some(node) = smt_trace.account_path[0].leaf {
-                insert(&mut account_counter, node.value.0);
-            }
-            if let Some(node) = smt_trace.account_path[1].leaf {
-                insert(&mut account_counter, node.value.0);
-            }
-
-            // + and the address key
-            insert(&mut key_counter, smt_trace.account_key.0);
-
-            // + state path, like account path
-            if let Some(path) = &smt_trace.state_path[0] {
-                for node in &path.path {
-                    insert(&mut path_hash_counter, node.value.0);
-                }
-            }
-
-            if let Some(path) = &smt_trace.state_path[1] {
-                for node in &path.path {
-                    insert(&mut path_hash_counter, node.value.0);
-                }
-            }
-
-            // + state leaf
-            if let Some(node) = smt_trace.state_path[0].as_ref().and_then(|pt| pt.leaf) {
-                insert(&mut storage_counter, node.value.0);
-            }
-            if let Some(node) = smt_trace.state_path[1].as_ref().and_then(|pt| pt.leaf) {
-                insert(&mut storage_counter, node.value.0);
-            }
-
-            // + the storage key
-            if let Some(hash) = smt_trace.state_key {
-                insert(&mut key_counter, hash.0);
-            }
+            mpt_hashes.insert(smt_trace);
         }
-        let sum_count = |h: &std::collections::HashMap<[u8; 32], usize>| h.values().sum::<usize>();
-        let prev_dedup_size = sum_count(&path_hash_counter)
-            + sum_count(&key_counter)
-            + sum_count(&account_counter) * 4
-            + sum_count(&storage_counter);
-        let after_dedup_size = path_hash_counter.len()
-            + key_counter.len()
-            + account_counter.len() * 4
-            + storage_counter.len();
-        log::debug!("poseidon circuit row num: dedup mpt from {prev_dedup_size} to {after_dedup_size}, mpt update len {}, smt trace len {}",
-        block.mpt_updates.len(), block.mpt_updates.smt_traces.len());
-        let mpt_row_num = after_dedup_size * F::hash_block_size();
-        let byte_row_num = block
-            .bytecodes
-            .values()
-            .map(|bytecode| bytecode.bytes.len() / HASH_BLOCK_STEP_SIZE + 1)
-            .sum::<usize>()
-            * F::hash_block_size();
-        let total_row_num = mpt_row_num + byte_row_num;
-        log::debug!("poseidon circuit row num: {mpt_row_num}(mpt) + {byte_row_num}(bytecode) = {total_row_num}");
-        (
-            total_row_num,
-            block.circuits_params.max_poseidon_rows.max(total_row_num),
+        log::debug!(
+            "poseidon circuit row num: mpt update len {}, smt trace len {}",
+            block.mpt_updates.len(),
+            block.mpt_updates.smt_traces.len()
+        );
+
+        poseidon_min_num_rows::<F>(
+            &mpt_hashes,
+            &block.bytecodes,
+            block.circuits_params.max_poseidon_rows,
         )
     }
 
@@ -0,0 +1,1178 @@
+//! The Ripemd160 circuit verifies the RIPEMD-160 hashes computed by the precompile at address
+//! 0x03.
+//!
+//! The padded input of a hash is processed in blocks of 64 bytes, each block being laid out on
+//! `BLOCK_ROWS` rows: one row per input byte, then the 5 seeds and the 80 steps of the left line,
+//! the 5 seeds and the 80 steps of the right line, and finally the 5 words of the new state.
+//! A step only queries the words of the 5 previous rows, the rotation by 10 applied to the words
+//! of the RIPEMD-160 state being folded in the way the previous words are read. Every word is
+//! decomposed in 32 bits.
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod test;
+
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    table::Ripemd160Table,
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness,
+};
+use bus_mapping::circuit_input_builder::Ripemd160;
+use eth_types::Field;
+use gadgets::util::{not, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, TableColumn, VirtualCells,
+    },
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// Number of bytes of a block.
+const BYTE_ROWS: usize = 64;
+/// Rows used by a line, i.e. the 5 seeds and the 80 steps.
+const LINE_ROWS: usize = 5 + 80;
+/// First row of the left line.
+const LEFT_START: usize = BYTE_ROWS;
+/// First row of the right line.
+const RIGHT_START: usize = LEFT_START + LINE_ROWS;
+/// First row of the new state.
+const OUTPUT_START: usize = RIGHT_START + LINE_ROWS;
+/// Row holding the result of the block, exposed to the ripemd160 table.
+const FINAL_ROW: usize = OUTPUT_START + 4;
+/// Rows used by one block.
+pub(crate) const BLOCK_ROWS: usize = FINAL_ROW + 1;
+
+/// Number of bits of a word.
+const NUM_BITS: usize = 32;
+/// Position of the byte of a block from which the padding must have started in the last block.
+const LAST_PAD_POS: usize = 55;
+/// Position of the first byte of the encoded length in the last block.
+const LEN_POS: usize = 56;
+
+/// Initialization vector of RIPEMD-160.
+const IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// Constants of the rounds of the left line.
+const K_LEFT: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+/// Constants of the rounds of the right line.
+const K_RIGHT: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// Message word selection of the left line.
+const R_LEFT: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5,
+    2, 14, 11, 8, 3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, 1, 9, 11, 10, 0, 8, 12, 4,
+    13, 3, 7, 15, 14, 5, 6, 2, 4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+/// Message word selection of the right line.
+const R_RIGHT: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, 6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12,
+    4, 9, 1, 2, 15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, 8, 6, 4, 1, 3, 11, 15, 0, 5,
+    12, 2, 13, 9, 7, 10, 14, 12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+/// Rotations (left) of the left line.
+const S_LEFT: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, 7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15,
+    9, 11, 7, 13, 12, 11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, 11, 12, 14, 15, 14,
+    15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, 9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+/// Rotations (left) of the right line.
+const S_RIGHT: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, 9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12,
+    7, 6, 15, 13, 11, 9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, 15, 5, 8, 11, 14, 14,
+    6, 14, 6, 9, 12, 9, 12, 5, 15, 8, 8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+/// Smallest rotation of a step, the rotations being in `MIN_ROTATION..MIN_ROTATION + 11`.
+const MIN_ROTATION: u32 = 5;
+
+/// Index of the word of the state used by the 5 seeds of a line, the first 3 ones being rotated
+/// right by 10.
+const SEED_WORDS: [usize; 5] = [0, 4, 3, 2, 1];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RowKind {
+    /// Byte `pos` of the padded block.
+    Byte(usize),
+    /// Seed `i` of a line.
+    Seed { is_right: bool, i: usize },
+    /// Step `j` of a line.
+    Step { is_right: bool, j: usize },
+    /// Word `k` of the new state.
+    Output(usize),
+}
+
+impl RowKind {
+    /// Layout of the row at `row` in a block.
+    fn at(row: usize) -> Self {
+        if row < LEFT_START {
+            Self::Byte(row)
+        } else if row < OUTPUT_START {
+            let is_right = row >= RIGHT_START;
+            let i = (row - LEFT_START) % LINE_ROWS;
+            if i < 5 {
+                Self::Seed { is_right, i }
+            } else {
+                Self::Step { is_right, j: i - 5 }
+            }
+        } else {
+            Self::Output(row - OUTPUT_START)
+        }
+    }
+}
+
+/// Boolean function index, constant, message word index and rotation of the step `j` of a line.
+fn step_params(is_right: bool, j: usize) -> (usize, u32, usize, u32) {
+    let round = j / 16;
+    if is_right {
+        (4 - round, K_RIGHT[round], R_RIGHT[j], S_RIGHT[j])
+    } else {
+        (round, K_LEFT[round], R_LEFT[j], S_LEFT[j])
+    }
+}
+
+/// The 5 boolean functions of RIPEMD-160.
+fn boolean_fn(idx: usize, x: u32, y: u32, z: u32) -> u32 {
+    match idx {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+/// Number of blocks used by the hash of an input of `len` bytes.
+fn num_blocks(len: usize) -> usize {
+    (len + 8) / BYTE_ROWS + 1
+}
+
+/// Witness of a word row.
+#[derive(Clone, Copy, Debug, Default)]
+struct WordRow {
+    /// Word of the row.
+    w: u32,
+    /// Sum of the step before the rotation.
+    u: u32,
+    carry_a: u64,
+    carry_b: u64,
+}
+
+/// Witness of a row of a block.
+#[derive(Clone, Debug, Default)]
+struct RowWitness {
+    byte: u64,
+    is_pad: bool,
+    word_acc: u64,
+    len_acc: u64,
+    input_len: u64,
+    x: [u32; 16],
+    h: [u32; 5],
+    hn: [u32; 5],
+    l: [u32; 5],
+    r: [u32; 5],
+    is_first: bool,
+    is_last: bool,
+    word: WordRow,
+}
+
+/// Result `(A, B, C, D, E)` of a line from its last 5 words.
+fn line_result(words: &[WordRow]) -> [u32; 5] {
+    let word = |i: usize| words[words.len() - i].w;
+    [
+        word(5).rotate_left(10),
+        word(1),
+        word(2),
+        word(3).rotate_left(10),
+        word(4).rotate_left(10),
+    ]
+}
+
+/// Generate the word rows of a line, returns them along with the result of the line.
+fn line_rows(h: &[u32; 5], x: &[u32; 16], is_right: bool) -> (Vec<WordRow>, [u32; 5]) {
+    let mut rows: Vec<WordRow> = SEED_WORDS
+        .iter()
+        .enumerate()
+        .map(|(i, &idx)| WordRow {
+            w: if i < 3 {
+                h[idx].rotate_right(10)
+            } else {
+                h[idx]
+            },
+            ..Default::default()
+        })
+        .collect();
+
+    for j in 0..80 {
+        let [a, b, c, d, e] = line_result(&rows);
+        let (f_idx, k, x_idx, s) = step_params(is_right, j);
+        let sum = a as u64 + boolean_fn(f_idx, b, c, d) as u64 + x[x_idx] as u64 + k as u64;
+        let u = sum as u32;
+        let t = u.rotate_left(s) as u64 + e as u64;
+        rows.push(WordRow {
+            w: t as u32,
+            u,
+            carry_a: sum >> 32,
+            carry_b: t >> 32,
+        });
+    }
+
+    let result = line_result(&rows);
+    (rows, result)
+}
+
+/// Generate the witness of all the blocks of a hash.
+fn hash_rows(input: &[u8]) -> Vec<RowWitness> {
+    let mut padded = input.to_vec();
+    padded.push(0x80);
+    while padded.len() % BYTE_ROWS != LEN_POS {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(8 * input.len() as u64).to_le_bytes());
+
+    let num_blocks = padded.len() / BYTE_ROWS;
+    let mut rows = Vec::with_capacity(num_blocks * BLOCK_ROWS);
+    let mut h = IV;
+    let mut input_len = 0;
+
+    for (idx, block) in padded.chunks(BYTE_ROWS).enumerate() {
+        let x: [u32; 16] = array_init::array_init(|i| {
+            u32::from_le_bytes(block[4 * i..4 * i + 4].try_into().unwrap())
+        });
+        let (left, l) = line_rows(&h, &x, false);
+        let (right, r) = line_rows(&h, &x, true);
+        let sums: [u64; 5] = array_init::array_init(|k| {
+            h[(k + 1) % 5] as u64 + l[(k + 2) % 5] as u64 + r[(k + 3) % 5] as u64
+        });
+        let hn: [u32; 5] = array_init::array_init(|k| sums[k] as u32);
+        let base = RowWitness {
+            x,
+            h,
+            hn,
+            l,
+            r,
+            is_first: idx == 0,
+            is_last: idx == num_blocks - 1,
+            ..Default::default()
+        };
+
+        let mut is_pad = false;
+        let mut word_acc = 0;
+        let mut len_acc = 0;
+        for (pos, &byte) in block.iter().enumerate() {
+            is_pad = idx * BYTE_ROWS + pos >= input.len();
+            input_len += u64::from(!is_pad);
+            if pos % 4 == 0 {
+                word_acc = 0;
+            }
+            word_acc += (byte as u64) << (8 * (pos % 4));
+            if pos >= LEN_POS {
+                len_acc += (byte as u64) << (8 * (pos - LEN_POS));
+            }
+            rows.push(RowWitness {
+                byte: byte as u64,
+                is_pad,
+                word_acc,
+                len_acc,
+                input_len,
+                ..base.clone()
+            });
+        }
+
+        let outputs = hn.iter().zip(sums.iter()).map(|(&w, &sum)| WordRow {
+            w,
+            carry_a: sum >> 32,
+            ..Default::default()
+        });
+        for word in left.into_iter().chain(right).chain(outputs) {
+            rows.push(RowWitness {
+                is_pad,
+                input_len,
+                word,
+                ..base.clone()
+            });
+        }
+
+        h = hn;
+    }
+
+    rows
+}
+
+/// Compose little-endian bits into a value.
+fn compose<F: Field>(bits: &[Expression<F>]) -> Expression<F> {
+    bits.iter()
+        .rev()
+        .fold(0.expr(), |acc, bit| acc * 2.expr() + bit.clone())
+}
+
+/// Value of the word of little-endian bits `bits` rotated left by `n`.
+fn rotl<F: Field>(bits: &[Expression<F>], n: usize) -> Expression<F> {
+    let rotated: Vec<Expression<F>> = (0..NUM_BITS)
+        .map(|i| bits[(i + NUM_BITS - n) % NUM_BITS].clone())
+        .collect();
+    compose(&rotated)
+}
+
+/// XOR of two bits.
+fn xor<F: Field>(a: Expression<F>, b: Expression<F>) -> Expression<F> {
+    a.clone() + b.clone() - 2.expr() * a * b
+}
+
+/// Ripemd160 circuit config
+#[derive(Clone, Debug)]
+pub struct Ripemd160CircuitConfig<F> {
+    /// Enabled at the first row of the first block.
+    q_first: Column<Fixed>,
+    /// Enabled at the first row of every block.
+    q_start: Column<Fixed>,
+    /// Enabled at every row of a block but the final one.
+    q_trans: Column<Fixed>,
+    /// Enabled at the byte rows.
+    q_byte: Column<Fixed>,
+    /// Enabled at the first byte row of every message word.
+    q_word_start: Column<Fixed>,
+    /// Enabled at the last byte row of every message word.
+    q_word_end: Column<Fixed>,
+    /// Weight of the byte in its little-endian message word.
+    byte_weight: Column<Fixed>,
+    /// Enabled at the byte row `LAST_PAD_POS`.
+    q_last_pad: Column<Fixed>,
+    /// Enabled at the byte rows of the encoded length.
+    q_len: Column<Fixed>,
+    /// Enabled at the first byte row of the encoded length.
+    q_len_start: Column<Fixed>,
+    /// Enabled at the last byte row.
+    q_len_end: Column<Fixed>,
+    /// Weight of the byte in the little-endian encoded length.
+    len_weight: Column<Fixed>,
+    /// Enabled at the rows after the bytes whose input is kept.
+    q_keep: Column<Fixed>,
+    /// Enabled at the seed, step and output rows.
+    q_word: Column<Fixed>,
+    /// Enabled at the seed rows.
+    q_seed: Column<Fixed>,
+    /// Enabled at the seed rows whose word is rotated.
+    q_rot10: Column<Fixed>,
+    /// Enabled at the step rows.
+    q_step: Column<Fixed>,
+    /// Selection of the boolean function of a step.
+    q_f: [Column<Fixed>; 5],
+    /// Selection of the rotation of a step, starting at `MIN_ROTATION`.
+    q_rotl: [Column<Fixed>; 11],
+    /// Constant of the round of a step.
+    k_const: Column<Fixed>,
+    /// Selection of the message word of a step or of a last byte row.
+    sel_x: [Column<Fixed>; 16],
+    /// Selection of the word of the state used by a seed or an output row.
+    sel_h: [Column<Fixed>; 5],
+    /// Enabled at the row following the left line.
+    q_left_end: Column<Fixed>,
+    /// Enabled at the row following the right line.
+    q_right_end: Column<Fixed>,
+    /// Enabled at the output rows.
+    q_output: Column<Fixed>,
+    /// Enabled at the first output row.
+    q_output_first: Column<Fixed>,
+
+    /// Byte of the padded input.
+    byte: Column<Advice>,
+    /// Whether the byte is part of the padding.
+    is_pad: Column<Advice>,
+    /// Accumulator of the message word.
+    word_acc: Column<Advice>,
+    /// Accumulator of the encoded length.
+    len_acc: Column<Advice>,
+    /// Message words of the block.
+    x: [Column<Advice>; 16],
+    /// State before the block.
+    h: [Column<Advice>; 5],
+    /// State after the block.
+    hn: [Column<Advice>; 5],
+    /// Result of the left line.
+    l: [Column<Advice>; 5],
+    /// Result of the right line.
+    r: [Column<Advice>; 5],
+    /// Whether the block is the first one of a hash.
+    is_first: Column<Advice>,
+    /// Bits of the word of the row.
+    w_bits: [Column<Advice>; NUM_BITS],
+    /// Bits of the sum of the step before the rotation.
+    u_bits: [Column<Advice>; NUM_BITS],
+    /// Carry of the sum of the step or of the new state.
+    carry_a: Column<Advice>,
+    /// Carry of the addition after the rotation.
+    carry_b: Column<Advice>,
+
+    /// Byte range table.
+    byte_table: TableColumn,
+    ripemd160_table: Ripemd160Table,
+    _marker: PhantomData<F>,
+}
+
+/// Config args for Ripemd160 circuit
+#[derive(Clone, Debug)]
+pub struct Ripemd160CircuitConfigArgs<F: Field> {
+    /// Ripemd160 Table
+    pub ripemd160_table: Ripemd160Table,
+    /// Challenges randomness
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for Ripemd160CircuitConfig<F> {
+    type ConfigArgs = Ripemd160CircuitConfigArgs<F>;
+
+    /// Return a new Ripemd160CircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            ripemd160_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_first = meta.fixed_column();
+        let q_start = meta.fixed_column();
+        let q_trans = meta.fixed_column();
+        let q_byte = meta.fixed_column();
+        let q_word_start = meta.fixed_column();
+        let q_word_end = meta.fixed_column();
+        let byte_weight = meta.fixed_column();
+        let q_last_pad = meta.fixed_column();
+        let q_len = meta.fixed_column();
+        let q_len_start = meta.fixed_column();
+        let q_len_end = meta.fixed_column();
+        let len_weight = meta.fixed_column();
+        let q_keep = meta.fixed_column();
+        let q_word = meta.fixed_column();
+        let q_seed = meta.fixed_column();
+        let q_rot10 = meta.fixed_column();
+        let q_step = meta.fixed_column();
+        let q_f: [Column<Fixed>; 5] = array_init::array_init(|_| meta.fixed_column());
+        let q_rotl: [Column<Fixed>; 11] = array_init::array_init(|_| meta.fixed_column());
+        let k_const = meta.fixed_column();
+        let sel_x: [Column<Fixed>; 16] = array_init::array_init(|_| meta.fixed_column());
+        let sel_h: [Column<Fixed>; 5] = array_init::array_init(|_| meta.fixed_column());
+        let q_left_end = meta.fixed_column();
+        let q_right_end = meta.fixed_column();
+        let q_output = meta.fixed_column();
+        let q_output_first = meta.fixed_column();
+
+        let byte = meta.advice_column();
+        let is_pad = meta.advice_column();
+        let word_acc = meta.advice_column();
+        let len_acc = meta.advice_column();
+        let x: [Column<Advice>; 16] = array_init::array_init(|_| meta.advice_column());
+        let h: [Column<Advice>; 5] = array_init::array_init(|_| meta.advice_column());
+        let hn: [Column<Advice>; 5] = array_init::array_init(|_| meta.advice_column());
+        let l: [Column<Advice>; 5] = array_init::array_init(|_| meta.advice_column());
+        let r: [Column<Advice>; 5] = array_init::array_init(|_| meta.advice_column());
+        let is_first = meta.advice_column();
+        let w_bits: [Column<Advice>; NUM_BITS] = array_init::array_init(|_| meta.advice_column());
+        let u_bits: [Column<Advice>; NUM_BITS] = array_init::array_init(|_| meta.advice_column());
+        let carry_a = meta.advice_column();
+        let carry_b = meta.advice_column();
+        let byte_table = meta.lookup_table_column();
+
+        let is_last = ripemd160_table.is_final;
+        let input_len = ripemd160_table.input_len;
+        let input_rlc = ripemd160_table.input_rlc;
+        let output_rlc = ripemd160_table.output_rlc;
+
+        let fixed = |meta: &mut VirtualCells<F>, column: Column<Fixed>| {
+            meta.query_fixed(column, Rotation::cur())
+        };
+        let advice = |meta: &mut VirtualCells<F>, column: Column<Advice>, rotation: i32| {
+            meta.query_advice(column, Rotation(rotation))
+        };
+        let select =
+            |meta: &mut VirtualCells<F>, sels: &[Column<Fixed>], cols: &[Column<Advice>]| {
+                sels.iter().zip(cols).fold(0.expr(), |acc, (&sel, &col)| {
+                    acc + meta.query_fixed(sel, Rotation::cur())
+                        * meta.query_advice(col, Rotation::cur())
+                })
+            };
+        let bits = |meta: &mut VirtualCells<F>, rotation: i32| {
+            w_bits.map(|column| meta.query_advice(column, Rotation(rotation)))
+        };
+        // Result (A, B, C, D, E) of the line ending at the previous row.
+        let line_result = |meta: &mut VirtualCells<F>| {
+            let words: Vec<[Expression<F>; NUM_BITS]> =
+                (1..=5).map(|i| bits(meta, -(i as i32))).collect();
+            [
+                rotl(&words[4], 10),
+                compose(&words[0]),
+                compose(&words[1]),
+                rotl(&words[2], 10),
+                rotl(&words[3], 10),
+            ]
+        };
+        let two_pow_32 = Expression::Constant(F::from(1u64 << 32));
+        let r_pows = {
+            let r = challenges.keccak_input();
+            let mut pows = vec![1.expr()];
+            for _ in 0..4 {
+                pows.push(pows.last().unwrap().clone() * r.clone());
+            }
+            pows
+        };
+
+        meta.create_gate("ripemd160 block constants", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            for column in x
+                .iter()
+                .chain(h.iter())
+                .chain(hn.iter())
+                .chain(l.iter())
+                .chain(r.iter())
+                .chain([is_first, is_last].iter())
+            {
+                cb.require_equal(
+                    "block constant is kept",
+                    advice(meta, *column, 1),
+                    advice(meta, *column, 0),
+                );
+            }
+
+            cb.gate(fixed(meta, q_trans))
+        });
+
+        meta.create_gate("ripemd160 block start", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_start_expr = fixed(meta, q_start);
+            let q_first_expr = fixed(meta, q_first);
+            let is_first_expr = advice(meta, is_first, 0);
+
+            cb.condition(q_start_expr.clone(), |cb| {
+                cb.require_boolean("is_first is boolean", is_first_expr.clone());
+                cb.require_boolean("is_last is boolean", advice(meta, is_last, 0));
+            });
+            cb.condition(q_first_expr.clone(), |cb| {
+                cb.require_equal("first block is_first", is_first_expr.clone(), 1.expr());
+            });
+            // A new hash starts after the last block of the previous one.
+            cb.condition(q_start_expr.clone() - q_first_expr, |cb| {
+                cb.require_equal(
+                    "is_first = is_last::prev",
+                    is_first_expr.clone(),
+                    advice(meta, is_last, -1),
+                );
+            });
+            cb.condition(q_start_expr, |cb| {
+                for (i, column) in h.iter().enumerate() {
+                    cb.require_equal(
+                        "h = is_first ? IV : hn::prev",
+                        advice(meta, *column, 0),
+                        is_first_expr.clone() * Expression::Constant(F::from(IV[i] as u64))
+                            + not::expr(is_first_expr.clone()) * advice(meta, hn[i], -1),
+                    );
+                }
+            });
+
+            cb.gate(1.expr())
+        });
+
+        meta.create_gate("ripemd160 input", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let byte_expr = advice(meta, byte, 0);
+            let is_pad_expr = advice(meta, is_pad, 0);
+            let is_last_expr = advice(meta, is_last, 0);
+            let input_len_expr = advice(meta, input_len, 0);
+            // The input of a hash starts empty in its first block.
+            let prev = |meta: &mut VirtualCells<F>, column: Column<Advice>| {
+                advice(meta, column, -1)
+                    * not::expr(fixed(meta, q_start) * advice(meta, is_first, 0))
+            };
+            let prev_pad = prev(meta, is_pad);
+            let prev_len = prev(meta, input_len);
+            let prev_rlc = prev(meta, input_rlc);
+
+            cb.condition(fixed(meta, q_byte), |cb| {
+                cb.require_boolean("is_pad is boolean", is_pad_expr.clone());
+                cb.require_zero(
+                    "padding is not followed by input",
+                    prev_pad.clone() * not::expr(is_pad_expr.clone()),
+                );
+                cb.require_zero(
+                    "padding starts with 0x80",
+                    (is_pad_expr.clone() - prev_pad.clone()) * (byte_expr.clone() - 0x80.expr()),
+                );
+                cb.require_equal(
+                    "input_len = input_len::prev + !is_pad",
+                    input_len_expr.clone(),
+                    prev_len + not::expr(is_pad_expr.clone()),
+                );
+                let rlc_with_byte = prev_rlc.clone() * r_pows[1].clone() + byte_expr.clone();
+                cb.require_equal(
+                    "input_rlc = is_pad ? input_rlc::prev : input_rlc::prev * r + byte",
+                    advice(meta, input_rlc, 0),
+                    rlc_with_byte.clone() - is_pad_expr.clone() * (rlc_with_byte - prev_rlc),
+                );
+                cb.require_equal(
+                    "word_acc = word_acc::prev + byte * byte_weight",
+                    advice(meta, word_acc, 0),
+                    not::expr(fixed(meta, q_word_start)) * advice(meta, word_acc, -1)
+                        + byte_expr.clone() * fixed(meta, byte_weight),
+                );
+            });
+            // The bytes of the padding are 0, but the encoded length in the last block.
+            cb.condition(fixed(meta, q_byte) - fixed(meta, q_len), |cb| {
+                cb.require_zero("padding continues with 0", prev_pad * byte_expr.clone());
+            });
+            // The first byte row of a block is not part of the encoded length.
+            cb.condition(fixed(meta, q_len), |cb| {
+                cb.require_zero(
+                    "padding continues with 0 before the last block",
+                    advice(meta, is_pad, -1) * not::expr(is_last_expr.clone()) * byte_expr.clone(),
+                );
+                cb.require_equal(
+                    "len_acc = len_acc::prev + byte * len_weight",
+                    advice(meta, len_acc, 0),
+                    not::expr(fixed(meta, q_len_start)) * advice(meta, len_acc, -1)
+                        + byte_expr * fixed(meta, len_weight),
+                );
+            });
+            cb.condition(fixed(meta, q_word_end), |cb| {
+                cb.require_equal(
+                    "x[i] = word_acc",
+                    select(meta, &sel_x[..], &x[..]),
+                    advice(meta, word_acc, 0),
+                );
+            });
+            // Only the last block of a hash has room for the encoded length.
+            cb.condition(fixed(meta, q_last_pad), |cb| {
+                cb.require_equal(
+                    "is_last = is_pad",
+                    is_last_expr.clone(),
+                    is_pad_expr.clone(),
+                );
+            });
+            cb.condition(fixed(meta, q_len_end), |cb| {
+                cb.require_zero(
+                    "encoded length is the number of bits of the input",
+                    is_last_expr * (advice(meta, len_acc, 0) - input_len_expr.clone() * 8.expr()),
+                );
+            });
+            cb.condition(fixed(meta, q_keep), |cb| {
+                for column in [is_pad, input_len, input_rlc] {
+                    cb.require_equal(
+                        "input is kept",
+                        advice(meta, column, 1),
+                        advice(meta, column, 0),
+                    );
+                }
+            });
+
+            cb.gate(1.expr())
+        });
+
+        meta.create_gate("ripemd160 seed", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let w_bits_expr = bits(meta, 0);
+            let word = compose(&w_bits_expr);
+            let rotated = rotl(&w_bits_expr, 10);
+            cb.condition(fixed(meta, q_word), |cb| {
+                for bit in w_bits_expr.iter() {
+                    cb.require_boolean("w_bits are boolean", bit.clone());
+                }
+            });
+            cb.condition(fixed(meta, q_seed), |cb| {
+                cb.require_equal(
+                    "h[i] = rotated ? rotl(w, 10) : w",
+                    select(meta, &sel_h[..], &h[..]),
+                    word.clone() + fixed(meta, q_rot10) * (rotated - word),
+                );
+            });
+
+            cb.gate(1.expr())
+        });
+
+        meta.create_gate("ripemd160 step", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let [a, _, _, _, e] = line_result(meta);
+            let x_bits = bits(meta, -1);
+            let y_bits = bits(meta, -2);
+            let z_bits = {
+                let d_bits = bits(meta, -3);
+                (0..NUM_BITS)
+                    .map(|i| d_bits[(i + NUM_BITS - 10) % NUM_BITS].clone())
+                    .collect::<Vec<_>>()
+            };
+            let q_f_expr = q_f.map(|column| fixed(meta, column));
+            let f_bits: Vec<Expression<F>> = (0..NUM_BITS)
+                .map(|i| {
+                    let (x, y, z) = (x_bits[i].clone(), y_bits[i].clone(), z_bits[i].clone());
+                    let f = [
+                        xor(xor(x.clone(), y.clone()), z.clone()),
+                        x.clone() * y.clone() + not::expr(x.clone()) * z.clone(),
+                        xor(not::expr(y.clone()) + x.clone() * y.clone(), z.clone()),
+                        x.clone() * z.clone() + y.clone() * not::expr(z.clone()),
+                        xor(x, not::expr(z.clone()) + y * z),
+                    ];
+                    q_f_expr
+                        .iter()
+                        .zip(f)
+                        .fold(0.expr(), |acc, (q, f)| acc + q.clone() * f)
+                })
+                .collect();
+            let u_bits_expr = u_bits.map(|column| advice(meta, column, 0));
+            let carry_a_expr = advice(meta, carry_a, 0);
+            let carry_b_expr = advice(meta, carry_b, 0);
+            let rotated = q_rotl
+                .iter()
+                .enumerate()
+                .fold(0.expr(), |acc, (i, &column)| {
+                    acc + fixed(meta, column) * rotl(&u_bits_expr, i + MIN_ROTATION as usize)
+                });
+
+            cb.condition(fixed(meta, q_step), |cb| {
+                for bit in u_bits_expr.iter() {
+                    cb.require_boolean("u_bits are boolean", bit.clone());
+                }
+                cb.require_in_set(
+                    "carry_a in [0, 1, 2, 3]",
+                    carry_a_expr.clone(),
+                    vec![0.expr(), 1.expr(), 2.expr(), 3.expr()],
+                );
+                cb.require_boolean("carry_b is boolean", carry_b_expr.clone());
+                cb.require_equal(
+                    "A + f(B, C, D) + x[i] + K = u + carry_a * 2^32",
+                    a + compose(&f_bits) + select(meta, &sel_x[..], &x[..]) + fixed(meta, k_const),
+                    compose(&u_bits_expr) + carry_a_expr * two_pow_32.clone(),
+                );
+                cb.require_equal(
+                    "rotl(u, s) + E = w + carry_b * 2^32",
+                    rotated + e,
+                    compose(&bits(meta, 0)) + carry_b_expr * two_pow_32.clone(),
+                );
+            });
+
+            cb.gate(1.expr())
+        });
+
+        meta.create_gate("ripemd160 line result", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let result = line_result(meta);
+            for (q, columns) in [(q_left_end, l), (q_right_end, r)] {
+                cb.condition(fixed(meta, q), |cb| {
+                    for (column, value) in columns.iter().zip(result.iter()) {
+                        cb.require_equal(
+                            "line result is captured",
+                            advice(meta, *column, 0),
+                            value.clone(),
+                        );
+                    }
+                });
+            }
+
+            cb.gate(1.expr())
+        });
+
+        meta.create_gate("ripemd160 output", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let w_bits_expr = bits(meta, 0);
+            let word = compose(&w_bits_expr);
+            let word_rlc = w_bits_expr
+                .chunks(8)
+                .enumerate()
+                .fold(0.expr(), |acc, (i, byte_bits)| {
+                    acc + compose(byte_bits) * r_pows[3 - i].clone()
+                });
+            let carry_a_expr = advice(meta, carry_a, 0);
+            let (sum, hn_word) = (0..5).fold((0.expr(), 0.expr()), |(sum, hn_word), j| {
+                let sel = fixed(meta, sel_h[j]);
+                (
+                    sum + sel.clone()
+                        * (advice(meta, h[j], 0)
+                            + advice(meta, l[(j + 1) % 5], 0)
+                            + advice(meta, r[(j + 2) % 5], 0)),
+                    hn_word + sel * advice(meta, hn[(j + 4) % 5], 0),
+                )
+            });
+            let output_rlc_expr = advice(meta, output_rlc, 0);
+
+            cb.condition(fixed(meta, q_output), |cb| {
+                cb.require_in_set(
+                    "carry_a in [0, 1, 2]",
+                    carry_a_expr.clone(),
+                    vec![0.expr(), 1.expr(), 2.expr()],
+                );
+                cb.require_equal(
+                    "h[k + 1] + l[k + 2] + r[k + 3] = w + carry_a * 2^32",
+                    sum,
+                    word.clone() + carry_a_expr * two_pow_32.clone(),
+                );
+                cb.require_equal("hn[k] = w", hn_word, word);
+            });
+            cb.condition(fixed(meta, q_output_first), |cb| {
+                cb.require_equal(
+                    "output_rlc = rlc(word bytes)",
+                    output_rlc_expr.clone(),
+                    word_rlc.clone(),
+                );
+            });
+            cb.condition(fixed(meta, q_output) - fixed(meta, q_output_first), |cb| {
+                cb.require_equal(
+                    "output_rlc = output_rlc::prev * r^4 + rlc(word bytes)",
+                    output_rlc_expr,
+                    advice(meta, output_rlc, -1) * r_pows[4].clone() + word_rlc,
+                );
+            });
+
+            cb.gate(1.expr())
+        });
+
+        meta.lookup("ripemd160 byte range", |meta| {
+            vec![(meta.query_advice(byte, Rotation::cur()), byte_table)]
+        });
+
+        Self {
+            q_first,
+            q_start,
+            q_trans,
+            q_byte,
+            q_word_start,
+            q_word_end,
+            byte_weight,
+            q_last_pad,
+            q_len,
+            q_len_start,
+            q_len_end,
+            len_weight,
+            q_keep,
+            q_word,
+            q_seed,
+            q_rot10,
+            q_step,
+            q_f,
+            q_rotl,
+            k_const,
+            sel_x,
+            sel_h,
+            q_left_end,
+            q_right_end,
+            q_output,
+            q_output_first,
+            byte,
+            is_pad,
+            word_acc,
+            len_acc,
+            x,
+            h,
+            hn,
+            l,
+            r,
+            is_first,
+            w_bits,
+            u_bits,
+            carry_a,
+            carry_b,
+            byte_table,
+            ripemd160_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Ripemd160CircuitConfig<F> {
+    fn load_byte_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "ripemd160 byte table",
+            |mut table| {
+                for value in 0..256 {
+                    table.assign_cell(
+                        || "ripemd160 byte table",
+                        self.byte_table,
+                        value,
+                        || Value::known(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn assign_fixed_row(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        row: usize,
+    ) -> Result<(), Error> {
+        let mut enabled = vec![];
+        let mut values = vec![];
+        if row == 0 {
+            enabled.push(self.q_start);
+        }
+        if row != FINAL_ROW {
+            enabled.push(self.q_trans);
+        }
+        if (BYTE_ROWS - 1..FINAL_ROW).contains(&row) {
+            enabled.push(self.q_keep);
+        }
+        match RowKind::at(row) {
+            RowKind::Byte(pos) => {
+                enabled.push(self.q_byte);
+                values.push((self.byte_weight, 1u64 << (8 * (pos % 4))));
+                if pos % 4 == 0 {
+                    enabled.push(self.q_word_start);
+                }
+                if pos % 4 == 3 {
+                    enabled.extend([self.q_word_end, self.sel_x[pos / 4]]);
+                }
+                if pos == LAST_PAD_POS {
+                    enabled.push(self.q_last_pad);
+                }
+                if pos >= LEN_POS {
+                    enabled.push(self.q_len);
+                    values.push((self.len_weight, 1u64 << (8 * (pos - LEN_POS))));
+                }
+                if pos == LEN_POS {
+                    enabled.push(self.q_len_start);
+                }
+                if pos == BYTE_ROWS - 1 {
+                    enabled.push(self.q_len_end);
+                }
+            }
+            RowKind::Seed { is_right, i } => {
+                enabled.extend([self.q_word, self.q_seed, self.sel_h[SEED_WORDS[i]]]);
+                if i < 3 {
+                    enabled.push(self.q_rot10);
+                }
+                if is_right && i == 0 {
+                    enabled.push(self.q_left_end);
+                }
+            }
+            RowKind::Step { is_right, j } => {
+                let (f_idx, k, x_idx, s) = step_params(is_right, j);
+                enabled.extend([
+                    self.q_word,
+                    self.q_step,
+                    self.q_f[f_idx],
+                    self.q_rotl[(s - MIN_ROTATION) as usize],
+                    self.sel_x[x_idx],
+                ]);
+                values.push((self.k_const, k as u64));
+            }
+            RowKind::Output(k) => {
+                enabled.extend([self.q_word, self.q_output, self.sel_h[(k + 1) % 5]]);
+                if k == 0 {
+                    enabled.extend([self.q_output_first, self.q_right_end]);
+                }
+                if row == FINAL_ROW {
+                    enabled.push(self.ripemd160_table.q_enable);
+                }
+            }
+        }
+
+        for (column, value) in enabled.into_iter().map(|column| (column, 1)).chain(values) {
+            region.assign_fixed(
+                || format!("ripemd160 fixed row {offset}"),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn assign_row(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        w: &RowWitness,
+        input_rlc: Value<F>,
+        output_rlc: Value<F>,
+    ) -> Result<(), Error> {
+        let words =
+            w.x.iter()
+                .zip(self.x.iter())
+                .chain(w.h.iter().zip(self.h.iter()))
+                .chain(w.hn.iter().zip(self.hn.iter()))
+                .chain(w.l.iter().zip(self.l.iter()))
+                .chain(w.r.iter().zip(self.r.iter()))
+                .map(|(value, column)| (*column, *value as u64));
+        let bit_cells = (0..NUM_BITS)
+            .map(|i| (self.w_bits[i], (w.word.w as u64 >> i) & 1))
+            .chain((0..NUM_BITS).map(|i| (self.u_bits[i], (w.word.u as u64 >> i) & 1)));
+        let scalars = [
+            (self.byte, w.byte),
+            (self.is_pad, w.is_pad as u64),
+            (self.word_acc, w.word_acc),
+            (self.len_acc, w.len_acc),
+            (self.ripemd160_table.input_len, w.input_len),
+            (self.is_first, w.is_first as u64),
+            (self.ripemd160_table.is_final, w.is_last as u64),
+            (self.carry_a, w.word.carry_a),
+            (self.carry_b, w.word.carry_b),
+        ];
+        for (column, value) in words.chain(bit_cells).chain(scalars) {
+            region.assign_advice(
+                || format!("ripemd160 row {offset}"),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        region.assign_advice(
+            || format!("ripemd160 input_rlc {offset}"),
+            self.ripemd160_table.input_rlc,
+            offset,
+            || input_rlc,
+        )?;
+        region.assign_advice(
+            || format!("ripemd160 output_rlc {offset}"),
+            self.ripemd160_table.output_rlc,
+            offset,
+            || output_rlc,
+        )?;
+        Ok(())
+    }
+
+    /// Assign the blocks of a hash starting at `offset`, returns the next offset.
+    fn assign_hash(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        rows: &[RowWitness],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<usize, Error> {
+        let r = challenges.keccak_input();
+        let mut input_rlc = Value::known(F::zero());
+        let mut output_rlc = Value::known(F::zero());
+
+        for (i, w) in rows.iter().enumerate() {
+            let row = i % BLOCK_ROWS;
+            match RowKind::at(row) {
+                RowKind::Byte(_) if !w.is_pad => {
+                    input_rlc = input_rlc.zip(r).map(|(acc, r)| acc * r + F::from(w.byte));
+                }
+                RowKind::Output(k) => {
+                    if k == 0 {
+                        output_rlc = Value::known(F::zero());
+                    }
+                    output_rlc = output_rlc.zip(r).map(|(acc, r)| {
+                        w.word
+                            .w
+                            .to_le_bytes()
+                            .iter()
+                            .fold(acc, |acc, byte| acc * r + F::from(*byte as u64))
+                    });
+                }
+                _ => {}
+            }
+
+            self.assign_fixed_row(region, offset + i, row)?;
+            self.assign_row(region, offset + i, w, input_rlc, output_rlc)?;
+        }
+
+        Ok(offset + rows.len())
+    }
+
+    /// Assign the all-zero row at `offset`.
+    fn assign_zero_row(&self, region: &mut Region<F>, offset: usize) -> Result<(), Error> {
+        self.assign_row(
+            region,
+            offset,
+            &RowWitness::default(),
+            Value::known(F::zero()),
+            Value::known(F::zero()),
+        )
+    }
+}
+
+/// Ripemd160 circuit for precompile ripemd160
+#[derive(Clone, Debug, Default)]
+pub struct Ripemd160Circuit<F: Field>(Vec<Ripemd160>, std::marker::PhantomData<F>);
+
+impl<F: Field> Ripemd160Circuit<F> {
+    /// Number of rows used by the hashes.
+    fn rows_needed(events: &[Ripemd160]) -> usize {
+        1 + events
+            .iter()
+            .map(|event| num_blocks(event.input.len()) * BLOCK_ROWS)
+            .sum::<usize>()
+    }
+}
+
+impl<F: Field> SubCircuit<F> for Ripemd160Circuit<F> {
+    type Config = Ripemd160CircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // The word bits are queried at 6 distinct rotations, so returns 9 as
+        // minimum unusable rows.
+        9
+    }
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        let block_limit = block.circuits_params.max_keccak_rows / BLOCK_ROWS;
+
+        let mut events = block.get_ripemd160();
+        if block_limit != 0 {
+            let blocks: usize = events
+                .iter()
+                .map(|event| num_blocks(event.input.len()))
+                .sum();
+            assert!(
+                blocks <= block_limit,
+                "no enough rows for ripemd160 circuit, expected {}, limit {}",
+                blocks,
+                block_limit,
+            );
+            // every padding hash of the empty input uses 1 block
+            events.resize(events.len() + block_limit - blocks, Default::default());
+            log::info!("ripemd160 circuit work with maxium {} blocks", block_limit);
+        }
+
+        Self(events, Default::default())
+    }
+
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let real_len = Self::rows_needed(&block.get_ripemd160());
+        (
+            real_len,
+            real_len
+                .max(block.circuits_params.max_keccak_rows)
+                .max(4096),
+        )
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load_byte_table(layouter)?;
+
+        layouter.assign_region(
+            || "ripemd160 circuit",
+            |mut region| {
+                config.assign_zero_row(&mut region, 0)?;
+                region.assign_fixed(
+                    || "ripemd160 q_first",
+                    config.q_first,
+                    1,
+                    || Value::known(F::one()),
+                )?;
+                let mut offset = 1;
+                for event in self.0.iter() {
+                    offset = config.assign_hash(
+                        &mut region,
+                        offset,
+                        &hash_rows(&event.input),
+                        challenges,
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,41 @@
+use super::*;
+use crate::util::MockChallenges;
+use halo2_proofs::{
+    circuit::SimpleFloorPlanner,
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+impl Circuit<Fr> for Ripemd160Circuit<Fr> {
+    type Config = (Ripemd160CircuitConfig<Fr>, MockChallenges);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let ripemd160_table = Ripemd160Table::construct(meta);
+        let challenge = MockChallenges::construct(meta);
+        let challenges = challenge.exprs(meta);
+        (
+            <Ripemd160CircuitConfig<Fr> as SubCircuitConfig<Fr>>::new(
+                meta,
+                Ripemd160CircuitConfigArgs {
+                    ripemd160_table,
+                    challenges,
+                },
+            ),
+            challenge,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenge): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let challenges = challenge.values(&layouter);
+        <Self as SubCircuit<Fr>>::synthesize_sub(self, &config, &challenges, &mut layouter)
+    }
+}
@@ -0,0 +1,153 @@
+#![allow(unused_imports)]
+use super::*;
+
+use crate::util::MockChallenges;
+use halo2_proofs::{
+    circuit::SimpleFloorPlanner,
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+fn construct_ripemd160(input: &[u8], digest: &str) -> Ripemd160 {
+    Ripemd160 {
+        input: input.to_vec(),
+        digest: hex::decode(digest).unwrap().try_into().unwrap(),
+    }
+}
+
+fn hash_output(input: &[u8]) -> [u8; 20] {
+    let rows = hash_rows(input);
+    let last_block = &rows[rows.len() - BLOCK_ROWS..];
+    let output: Vec<u8> = (0..5)
+        .flat_map(|k| last_block[OUTPUT_START + k].word.w.to_le_bytes())
+        .collect();
+    output.try_into().unwrap()
+}
+
+fn test_events() -> Vec<Ripemd160> {
+    vec![
+        construct_ripemd160(b"", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
+        construct_ripemd160(b"abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+        // the padding does not fit in the first block
+        construct_ripemd160(&[b'a'; 56], "e72334b46c83cc70bef979e15453706c95b888be"),
+        construct_ripemd160(
+            b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+            "9b752e45573d4b39f4dbd3323cab82bf63326bfb",
+        ),
+    ]
+}
+
+#[test]
+fn test_ripemd160_witness_output() {
+    for event in test_events() {
+        assert_eq!(hash_output(&event.input), event.digest);
+    }
+}
+
+#[test]
+fn test_ripemd160_circuit_00() {
+    let test_circuit = Ripemd160Circuit::<Fr>(test_events(), Default::default());
+    let prover = MockProver::run(12, &test_circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+// test the padding hash (empty input)
+#[test]
+fn test_ripemd160_circuit_01() {
+    let test_circuit = Ripemd160Circuit::<Fr>(vec![Default::default()], Default::default());
+    let prover = MockProver::run(10, &test_circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+/// Ripemd160 circuit assigning a single hash whose rows are tampered.
+#[cfg(test)]
+#[derive(Clone)]
+struct TamperedRipemd160Circuit {
+    input: Vec<u8>,
+    tamper: fn(&mut [RowWitness]),
+}
+
+#[cfg(test)]
+impl Circuit<Fr> for TamperedRipemd160Circuit {
+    type Config = (Ripemd160CircuitConfig<Fr>, MockChallenges);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        <Ripemd160Circuit<Fr> as Circuit<Fr>>::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenge): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let challenges = challenge.values(&layouter);
+        config.load_byte_table(&mut layouter)?;
+
+        let mut rows = hash_rows(&self.input);
+        (self.tamper)(&mut rows);
+        layouter.assign_region(
+            || "ripemd160 circuit",
+            |mut region| {
+                config.assign_zero_row(&mut region, 0)?;
+                region.assign_fixed(
+                    || "ripemd160 q_first",
+                    config.q_first,
+                    1,
+                    || Value::known(Fr::one()),
+                )?;
+                config.assign_hash(&mut region, 1, &rows, &challenges)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+fn verify_tampered(tamper: fn(&mut [RowWitness])) -> Result<(), Vec<VerifyFailure>> {
+    let test_circuit = TamperedRipemd160Circuit {
+        input: b"abc".to_vec(),
+        tamper,
+    };
+    let prover = MockProver::run(10, &test_circuit, vec![]).unwrap();
+    prover.verify()
+}
+
+#[test]
+fn test_ripemd160_circuit_untampered() {
+    assert_eq!(verify_tampered(|_| {}), Ok(()));
+}
+
+// a wrong digest in the output rows must fail
+#[test]
+fn test_ripemd160_circuit_wrong_digest() {
+    assert!(verify_tampered(|rows| {
+        let last_block = rows.len() - BLOCK_ROWS;
+        rows[last_block + OUTPUT_START].word.w ^= 1;
+    })
+    .is_err());
+}
+
+// a wrong word in a step of the left line must fail
+#[test]
+fn test_ripemd160_circuit_tampered_step() {
+    assert!(verify_tampered(|rows| {
+        rows[LEFT_START + LINE_ROWS / 2].word.w ^= 1;
+    })
+    .is_err());
+}
+
+// a wrong padding byte right after the input must fail
+#[test]
+fn test_ripemd160_circuit_tampered_padding() {
+    assert!(verify_tampered(|rows| {
+        rows[3].byte = 0x81;
+        rows[3].word_acc += 1 << 24;
+    })
+    .is_err());
+}
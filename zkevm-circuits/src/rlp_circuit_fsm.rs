@@ -6,7 +6,10 @@ mod dev;
 mod test;
 
 use crate::{
-    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    evm_circuit::{
+        param::N_BYTES_WORD,
+        util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    },
     table::{LookupTable, RlpFsmRlpTable, U8Table},
     util::{
         is_zero::{IsZeroChip, IsZeroConfig},
@@ -16,11 +19,11 @@ use crate::{
         Block, DataTable, Format, RlpFsmWitnessGen, RlpFsmWitnessRow, RlpTag, RomTableRow, State,
         State::{DecodeTagStart, End},
         Tag,
-        Tag::{BeginList, EndList, TxType},
+        Tag::{BeginList, BlobVersionedHash, EndList, TxType},
         Transaction,
     },
 };
-use eth_types::Field;
+use eth_types::{evm_types::block_utils::VERSIONED_HASH_VERSION_KZG, Field};
 use gadgets::{
     binary_number::{BinaryNumberChip, BinaryNumberConfig},
     comparator::{ComparatorChip, ComparatorConfig, ComparatorInstruction},
@@ -240,6 +243,7 @@ pub struct RlpCircuitConfig<F> {
     /// Booleans to reduce the circuit's degree as tag_bits's degree is 5.
     is_tag_end: Column<Advice>,
     is_tag_begin: Column<Advice>,
+    is_tag_blob_hash: Column<Advice>,
     /// Boolean to reduce the circuit's degree
     /// is_case3 = (0xc0 <= byte_value < 0xf8) && (is_tag_end == false)
     is_case3: Column<Advice>,
@@ -300,10 +304,10 @@ impl<F: Field> RlpCircuitConfig<F> {
         let (tx_id, format) = (rlp_table.tx_id, rlp_table.format);
         let tag_length = rlp_table.tag_length;
         let q_enabled = rlp_table.q_enable;
+        let byte_idx = rlp_table.byte_idx;
         let (
             q_first,
             q_last,
-            byte_idx,
             byte_rev_idx,
             byte_value,
             gas_cost_acc,
@@ -316,6 +320,7 @@ impl<F: Field> RlpCircuitConfig<F> {
             depth,
             is_tag_begin,
             is_tag_end,
+            is_tag_blob_hash,
             is_case3,
             transit_to_new_rlp_instance,
             is_same_rlp_instance,
@@ -337,7 +342,6 @@ impl<F: Field> RlpCircuitConfig<F> {
             meta.advice_column(),
             meta.advice_column(),
             meta.advice_column(),
-            meta.advice_column(),
         );
 
         let tag_value_acc = meta.advice_column_in(SecondPhase);
@@ -385,6 +389,7 @@ impl<F: Field> RlpCircuitConfig<F> {
         is_tag!(is_tag_begin_vector, BeginVector);
         is_tag!(is_tag_end_list, EndList);
         is_tag!(is_tag_end_vector, EndVector);
+        is_tag!(is_tag_blob_versioned_hash, BlobVersionedHash);
 
         //////////////////////////////////////////////////////////
         //////////// data table checks. //////////////////////////
@@ -802,6 +807,11 @@ impl<F: Field> RlpCircuitConfig<F> {
                 meta.query_advice(is_tag_begin, Rotation::cur()),
                 sum::expr([is_tag_begin_list(meta), is_tag_begin_vector(meta)]),
             );
+            cb.require_equal(
+                "is_tag_blob_hash = (tag == BlobVersionedHash)",
+                meta.query_advice(is_tag_blob_hash, Rotation::cur()),
+                is_tag_blob_versioned_hash(meta),
+            );
             cb.require_equal(
                 "is_case3 = (0xc0 <= byte_value < 0xf8) && (is_tag_end == false)",
                 meta.query_advice(is_case3, Rotation::cur()),
@@ -1119,6 +1129,28 @@ impl<F: Field> RlpCircuitConfig<F> {
             ]))
         });
 
+        // A blob versioned hash is a 32-byte string whose first byte is the version byte.
+        meta.create_gate("DecodeTagStart: blob versioned hash", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "byte_value == 0xa0",
+                byte_value_expr(meta),
+                (0x80 + N_BYTES_WORD).expr(),
+            );
+            cb.require_equal(
+                "byte_value' == VERSIONED_HASH_VERSION_KZG",
+                byte_value_next_expr(meta),
+                VERSIONED_HASH_VERSION_KZG.expr(),
+            );
+
+            cb.gate(and::expr([
+                meta.query_fixed(q_enabled, Rotation::cur()),
+                is_decode_tag_start(meta),
+                meta.query_advice(is_tag_blob_hash, Rotation::cur()),
+            ]))
+        });
+
         // Bytes => Bytes
         // Bytes => DecodeTagStart
         meta.create_gate("state transition: Bytes", |meta| {
@@ -1435,6 +1467,7 @@ impl<F: Field> RlpCircuitConfig<F> {
 
             is_tag_begin,
             is_tag_end,
+            is_tag_blob_hash,
             is_case3,
             transit_to_new_rlp_instance,
             is_same_rlp_instance,
@@ -1628,6 +1661,16 @@ impl<F: Field> RlpCircuitConfig<F> {
             row,
             || Value::known(F::from(witness.state_machine.tag.is_end() as u64)),
         )?;
+        region.assign_advice(
+            || "is_tag_blob_hash",
+            self.is_tag_blob_hash,
+            row,
+            || {
+                Value::known(F::from(
+                    (witness.state_machine.tag == BlobVersionedHash) as u64,
+                ))
+            },
+        )?;
         region.assign_advice(
             || "is_case3",
             self.is_case3,
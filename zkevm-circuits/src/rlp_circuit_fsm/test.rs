@@ -1,6 +1,9 @@
 #![allow(unused_imports)]
 use crate::{rlp_circuit_fsm::RlpCircuit, witness::Transaction};
-use eth_types::{geth_types::TxType, word, Address};
+use eth_types::{
+    geth_types::{get_rlp_signed, get_rlp_unsigned, TxType},
+    word, Address, H256,
+};
 use ethers_core::{
     types::{
         transaction::eip2718::TypedTransaction, Eip1559TransactionRequest,
@@ -10,7 +13,7 @@ use ethers_core::{
 };
 use ethers_signers::Wallet;
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
-use mock::{eth, MOCK_CHAIN_ID};
+use mock::{eth, MockTransaction, MOCK_CHAIN_ID};
 use rand::rngs::OsRng;
 
 #[cfg(test)]
@@ -166,3 +169,49 @@ fn test_eip2930_tx() {
 
     mock_prover.assert_satisfied_par();
 }
+
+#[test]
+fn test_eip4844_tx() {
+    let blob_versioned_hashes = (1..=2u8)
+        .map(|i| {
+            let mut hash = [i; 32];
+            hash[0] = 0x01;
+            H256(hash)
+        })
+        .collect::<Vec<_>>();
+    // The rlp circuit only decodes the bytes, so a dummy signature is enough.
+    let mock_tx = MockTransaction::default()
+        .to(Address::random())
+        .value(eth(10))
+        .gas(word!("0x77320"))
+        .nonce(word!("0x7f"))
+        .transaction_type(TxType::Eip4844 as u64)
+        .max_priority_fee_per_gas(word!("0x3b9aca00"))
+        .max_fee_per_gas(word!("0x4321"))
+        .max_fee_per_blob_gas(word!("0x10"))
+        .blob_versioned_hashes(blob_versioned_hashes)
+        .sig_data((1, word!("0x1234"), word!("0x5678")))
+        .build();
+    let eth_tx: EthTransaction = mock_tx.into();
+
+    let tx = Transaction::new_from_rlp_bytes(
+        TxType::Eip4844,
+        get_rlp_signed(&eth_tx),
+        get_rlp_unsigned(&eth_tx),
+    );
+    let rlp_circuit = RlpCircuit::<Fr, Transaction> {
+        txs: vec![tx],
+        max_txs: 10,
+        size: 1000,
+        _marker: Default::default(),
+    };
+
+    let mock_prover = MockProver::run(14, &rlp_circuit, vec![]);
+    assert!(mock_prover.is_ok());
+    let mock_prover = mock_prover.unwrap();
+    if let Err(errors) = mock_prover.verify_par() {
+        log::debug!("errors.len() = {}", errors.len());
+    }
+
+    mock_prover.assert_satisfied_par();
+}
@@ -874,6 +874,16 @@ pub struct StateCircuit<F> {
     _marker: PhantomData<F>,
 }
 
+/// Minimum number of rows required by the rws of the number, as `min_num_rows_block` of the state
+/// circuit.
+pub(crate) fn state_min_num_rows(num_rws: usize, max_rws: usize) -> (usize, usize) {
+    let total_rw_count = num_rws + 1;
+    (
+        total_rw_count,
+        std::cmp::max(1 << 16, max_rws.max(total_rw_count)),
+    )
+}
+
 impl<F: Field> StateCircuit<F> {
     /// make a new state circuit from an RwMap
     pub fn new(rw_map: RwMap, n_rows: usize) -> Self {
@@ -921,10 +931,9 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let total_rw_count = block.rws.0.values().flatten().count() + 1;
-        (
-            total_rw_count,
-            std::cmp::max(1 << 16, block.circuits_params.max_rws.max(total_rw_count)),
+        state_min_num_rows(
+            block.rws.0.values().flatten().count(),
+            block.circuits_params.max_rws,
         )
     }
 
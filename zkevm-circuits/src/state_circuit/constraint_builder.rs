@@ -130,6 +130,9 @@ impl<F: Field> ConstraintBuilder<F> {
         self.condition(q.tag_matches(RwTableTag::TxLog), |cb| {
             cb.build_tx_log_constraints(q)
         });
+        self.condition(q.tag_matches(RwTableTag::TransientStorage), |cb| {
+            cb.build_transient_storage_constraints(q)
+        });
     }
 
     fn build_general_constraints(&mut self, q: &Queries<F>) {
@@ -509,6 +512,35 @@ impl<F: Field> ConstraintBuilder<F> {
         );
     }
 
+    fn build_transient_storage_constraints(&mut self, q: &Queries<F>) {
+        // id is the tx_id, so every transaction starts with a fresh access group
+        // for each (address, storage_key) and transient storage is reset.
+        // field_tag is 0 for slots and 1 for the flag of accounts created in the tx
+        // (EIP-6780), which is only stored at storage_key 0.
+        self.require_boolean("field_tag is boolean for TransientStorage", q.field_tag());
+        self.condition(q.field_tag(), |cb| {
+            cb.require_zero(
+                "storage_key is 0 for AccountCreated in TransientStorage",
+                q.rw_table.storage_key.clone(),
+            );
+        });
+        self.require_zero("initial TransientStorage value is 0", q.initial_value());
+
+        self.require_equal(
+            "state_root is unchanged for TransientStorage",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_equal(
+                "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+                q.rw_table.value_prev.clone(),
+                q.value_prev_column(),
+            );
+        });
+    }
+
     fn build_tx_receipt_constraints(&mut self, q: &Queries<F>) {
         // TODO: implement TxReceipt constraints
         self.require_equal("TxReceipt rows not implemented", 1.expr(), 0.expr());
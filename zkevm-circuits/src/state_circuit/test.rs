@@ -1,7 +1,10 @@
 #![allow(unused_imports)]
 pub use super::{dev::*, *};
 use crate::{
-    table::{AccountFieldTag, CallContextFieldTag, RwTableTag, TxLogFieldTag, TxReceiptFieldTag},
+    table::{
+        AccountFieldTag, CallContextFieldTag, RwTableTag, TransientStorageFieldTag, TxLogFieldTag,
+        TxReceiptFieldTag,
+    },
     util::{unusable_rows, SubCircuit},
     witness::{MptUpdates, Rw, RwMap},
 };
@@ -372,6 +375,136 @@ fn tx_log_bad() {
     assert_error_matches(verify(rows), "is_write is always true for TxLog");
 }
 
+#[test]
+fn transient_storage_ok() {
+    let account_address = address!("0x0000000000000000000000000000000004356002");
+    let storage_key = Word::from(0x030201);
+    let rows = vec![
+        Rw::TransientStorage {
+            rw_counter: 1,
+            is_write: true,
+            tx_id: 1,
+            account_address,
+            field_tag: TransientStorageFieldTag::Slot,
+            storage_key,
+            value: Word::from(0x6f),
+            value_prev: Word::zero(),
+        },
+        Rw::TransientStorage {
+            rw_counter: 2,
+            is_write: false,
+            tx_id: 1,
+            account_address,
+            field_tag: TransientStorageFieldTag::Slot,
+            storage_key,
+            value: Word::from(0x6f),
+            value_prev: Word::from(0x6f),
+        },
+        // Transient storage starts from zero again in the next transaction.
+        Rw::TransientStorage {
+            rw_counter: 3,
+            is_write: false,
+            tx_id: 2,
+            account_address,
+            field_tag: TransientStorageFieldTag::Slot,
+            storage_key,
+            value: Word::zero(),
+            value_prev: Word::zero(),
+        },
+    ];
+
+    assert_eq!(verify(rows), Ok(()));
+}
+
+#[test]
+fn transient_storage_account_created_ok() {
+    let account_address = address!("0x0000000000000000000000000000000004356002");
+    let rows = vec![
+        // The same key is used by a slot and the created flag of the account.
+        Rw::TransientStorage {
+            rw_counter: 1,
+            is_write: true,
+            tx_id: 1,
+            account_address,
+            field_tag: TransientStorageFieldTag::Slot,
+            storage_key: Word::zero(),
+            value: Word::from(0x6f),
+            value_prev: Word::zero(),
+        },
+        Rw::TransientStorage {
+            rw_counter: 2,
+            is_write: true,
+            tx_id: 1,
+            account_address,
+            field_tag: TransientStorageFieldTag::AccountCreated,
+            storage_key: Word::zero(),
+            value: Word::one(),
+            value_prev: Word::zero(),
+        },
+        Rw::TransientStorage {
+            rw_counter: 3,
+            is_write: false,
+            tx_id: 1,
+            account_address,
+            field_tag: TransientStorageFieldTag::AccountCreated,
+            storage_key: Word::zero(),
+            value: Word::one(),
+            value_prev: Word::one(),
+        },
+    ];
+
+    assert_eq!(verify(rows), Ok(()));
+}
+
+#[test]
+fn transient_storage_account_created_nonzero_key() {
+    let rows = vec![Rw::TransientStorage {
+        rw_counter: 1,
+        is_write: true,
+        tx_id: 1,
+        account_address: address!("0x0000000000000000000000000000000004356002"),
+        field_tag: TransientStorageFieldTag::AccountCreated,
+        storage_key: Word::from(0x030201),
+        value: Word::one(),
+        value_prev: Word::zero(),
+    }];
+
+    assert_error_matches(
+        verify(rows),
+        "storage_key is 0 for AccountCreated in TransientStorage",
+    );
+}
+
+#[test]
+fn transient_storage_not_reset_between_txs() {
+    let account_address = address!("0x0000000000000000000000000000000004356002");
+    let storage_key = Word::from(0x030201);
+    let rows = vec![
+        Rw::TransientStorage {
+            rw_counter: 1,
+            is_write: true,
+            tx_id: 1,
+            account_address,
+            field_tag: TransientStorageFieldTag::Slot,
+            storage_key,
+            value: Word::from(0x6f),
+            value_prev: Word::zero(),
+        },
+        Rw::TransientStorage {
+            rw_counter: 2,
+            is_write: false,
+            tx_id: 2,
+            account_address,
+            field_tag: TransientStorageFieldTag::Slot,
+            storage_key,
+            value: Word::from(0x6f),
+            value_prev: Word::zero(),
+        },
+    ];
+
+    assert_error_matches(verify(rows), "first access reads don't change value");
+}
+
 #[test]
 fn address_limb_mismatch() {
     let rows = vec![Rw::Account {
@@ -966,6 +1099,30 @@ fn bad_initial_tx_log_value() {
     );
 }
 
+#[test]
+fn bad_initial_transient_storage_value() {
+    let rows = vec![Rw::TransientStorage {
+        rw_counter: 1,
+        is_write: true,
+        tx_id: 1,
+        account_address: address!("0x0000000000000000000000000000000004356002"),
+        field_tag: TransientStorageFieldTag::Slot,
+        storage_key: Word::from(0x030201),
+        value: Word::from(10),
+        value_prev: Word::zero(),
+    }];
+
+    let overrides = HashMap::from([
+        ((AdviceColumn::InitialValue, 0), Fr::from(10)),
+        ((AdviceColumn::ValuePrev, 0), Fr::from(10)),
+    ]);
+
+    assert_error_matches(
+        verify_with_overrides(rows, overrides),
+        "initial TransientStorage value is 0",
+    );
+}
+
 #[test]
 fn variadic_size_check() {
     let mut rows = vec![
@@ -5,7 +5,12 @@ use bus_mapping::{
     circuit_input_builder::{self, CircuitsParams, ExecState},
     mock::BlockData,
 };
-use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Address, Bytecode, ToWord};
+use eth_types::{
+    bytecode,
+    evm_types::{OpcodeId, FORK_SCHEDULE},
+    geth_types::GethData,
+    Address, Bytecode, ToWord,
+};
 use mock::{eth, test_ctx::TestContext, MOCK_ACCOUNTS};
 use strum::IntoEnumIterator;
 
@@ -167,7 +172,7 @@ pub(crate) fn print_circuit_stats_by_states(
         if !fn_filter(state) {
             continue;
         }
-        for responsible_op in state.responsible_opcodes() {
+        for responsible_op in state.responsible_opcodes(&FORK_SCHEDULE) {
             let opcode = responsible_op.opcode();
             let mut code = bytecode! {
                 PUSH2(0x00)
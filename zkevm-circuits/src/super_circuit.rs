@@ -50,6 +50,7 @@
 //!   - [x] Tx Circuit
 //!   - [ ] MPT Circuit
 pub(crate) mod precompile_block_trace;
+mod row_counter;
 #[cfg(any(feature = "test", test))]
 pub(crate) mod test;
 
@@ -60,6 +61,7 @@ use crate::bytecode_circuit::circuit::to_poseidon_hash::{
 #[cfg(not(feature = "poseidon-codehash"))]
 use crate::bytecode_circuit::circuit::BytecodeCircuitConfig;
 use crate::{
+    blake2f_circuit::{Blake2fCircuit, Blake2fCircuitConfig, Blake2fCircuitConfigArgs},
     bytecode_circuit::circuit::{BytecodeCircuit, BytecodeCircuitConfigArgs},
     copy_circuit::{CopyCircuit, CopyCircuitConfig, CopyCircuitConfigArgs},
     ecc_circuit::{EccCircuit, EccCircuitConfig, EccCircuitConfigArgs},
@@ -69,9 +71,10 @@ use crate::{
         keccak_packed_multi::get_num_rows_per_round, KeccakCircuit, KeccakCircuitConfig,
         KeccakCircuitConfigArgs,
     },
-    modexp_circuit::{ModExpCircuit, ModExpCircuitConfig},
+    modexp_circuit::{ModExpCircuit, ModExpCircuitConfig, ModExpCircuitConfigArgs},
     pi_circuit::{PiCircuit, PiCircuitConfig, PiCircuitConfigArgs},
     poseidon_circuit::{PoseidonCircuit, PoseidonCircuitConfig, PoseidonCircuitConfigArgs},
+    ripemd160_circuit::{Ripemd160Circuit, Ripemd160CircuitConfig, Ripemd160CircuitConfigArgs},
     rlp_circuit_fsm::{RlpCircuit, RlpCircuitConfig, RlpCircuitConfigArgs},
     sha256_circuit::{
         CircuitConfig as SHA256CircuitConfig, CircuitConfigArgs as SHA256CircuitConfigArgs,
@@ -80,9 +83,9 @@ use crate::{
     sig_circuit::{SigCircuit, SigCircuitConfig, SigCircuitConfigArgs},
     state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs},
     table::{
-        BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable, ModExpTable,
-        MptTable, PoseidonTable, PowOfRandTable, RlpFsmRlpTable as RlpTable, RwTable, SHA256Table,
-        SigTable, TxTable, U16Table, U8Table,
+        Blake2fTable, BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable,
+        ModExpTable, MptTable, PoseidonTable, PowOfRandTable, Ripemd160Table,
+        RlpFsmRlpTable as RlpTable, RwTable, SHA256Table, SigTable, TxTable, U16Table, U8Table,
     },
     tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
     util::{circuit_stats, log2_ceil, Challenges, SubCircuit, SubCircuitConfig},
@@ -95,8 +98,13 @@ use crate::mpt_circuit::{MptCircuit, MptCircuitConfig, MptCircuitConfigArgs};
 use bus_mapping::{
     circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
     mock::BlockData,
+    precompile::DEFAULT_MODEXP_SIZE_LIMIT,
+};
+use eth_types::{
+    evm_types::{ForkSchedule, FORK_SCHEDULE},
+    geth_types::GethData,
+    Field,
 };
-use eth_types::{geth_types::GethData, Field};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     halo2curves::bn256::Fr,
@@ -105,6 +113,8 @@ use halo2_proofs::{
 use itertools::Itertools;
 use snark_verifier_sdk::CircuitExt;
 
+pub use row_counter::RowCounter;
+
 /// Configuration of the Super Circuit
 #[derive(Clone)]
 pub struct SuperCircuitConfig<F: Field> {
@@ -119,9 +129,11 @@ pub struct SuperCircuitConfig<F: Field> {
     state_circuit: StateCircuitConfig<F>,
     tx_circuit: TxCircuitConfig<F>,
     sig_circuit: SigCircuitConfig<F>,
-    modexp_circuit: ModExpCircuitConfig,
+    modexp_circuit: ModExpCircuitConfig<F>,
     ecc_circuit: EccCircuitConfig<F>,
     sha256_circuit: SHA256CircuitConfig,
+    ripemd160_circuit: Ripemd160CircuitConfig<F>,
+    blake2f_circuit: Blake2fCircuitConfig<F>,
     #[cfg(not(feature = "poseidon-codehash"))]
     bytecode_circuit: BytecodeCircuitConfig<F>,
     #[cfg(feature = "poseidon-codehash")]
@@ -147,6 +159,10 @@ pub struct SuperCircuitConfigArgs {
     pub max_inner_blocks: usize,
     /// Mock randomness
     pub mock_randomness: u64,
+    /// Hard fork schedule the EVM circuit is configured with
+    pub fork_schedule: ForkSchedule,
+    /// Size limit of modexp operands the EVM circuit is configured with
+    pub max_modexp_size: usize,
     /// Challenges
     pub challenges: crate::util::Challenges,
 }
@@ -162,6 +178,8 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             max_calldata: _,
             max_inner_blocks: _,
             mock_randomness: _mock_randomness,
+            fork_schedule,
+            max_modexp_size,
             challenges,
         }: Self::ConfigArgs,
     ) -> Self {
@@ -196,6 +214,10 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
         log_circuit_info(meta, "keccak table");
         let sha256_table = SHA256Table::construct(meta);
         log_circuit_info(meta, "sha256 table");
+        let ripemd160_table = Ripemd160Table::construct(meta);
+        log_circuit_info(meta, "ripemd160 table");
+        let blake2f_table = Blake2fTable::construct(meta);
+        log_circuit_info(meta, "blake2f table");
         let sig_table = SigTable::construct(meta);
         log_circuit_info(meta, "sig table");
         let modexp_table = ModExpTable::construct(meta);
@@ -229,6 +251,24 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
         );
         log_circuit_info(meta, "sha256 circuit");
 
+        let ripemd160_circuit = Ripemd160CircuitConfig::new(
+            meta,
+            Ripemd160CircuitConfigArgs {
+                ripemd160_table: ripemd160_table.clone(),
+                challenges: challenges_expr.clone(),
+            },
+        );
+        log_circuit_info(meta, "ripemd160 circuit");
+
+        let blake2f_circuit = Blake2fCircuitConfig::new(
+            meta,
+            Blake2fCircuitConfigArgs {
+                blake2f_table: blake2f_table.clone(),
+                challenges: challenges_expr.clone(),
+            },
+        );
+        log_circuit_info(meta, "blake2f circuit");
+
         let poseidon_circuit =
             PoseidonCircuitConfig::new(meta, PoseidonCircuitConfigArgs { poseidon_table });
         log_circuit_info(meta, "poseidon circuit");
@@ -318,7 +358,15 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
         #[cfg(feature = "zktrie")]
         log_circuit_info(meta, "zktrie circuit");
 
-        let modexp_circuit = ModExpCircuitConfig::new(meta, modexp_table);
+        let modexp_circuit = ModExpCircuitConfig::new(
+            meta,
+            ModExpCircuitConfigArgs {
+                modexp_table,
+                u8_table,
+                u16_table,
+                challenges: challenges_expr.clone(),
+            },
+        );
         log_circuit_info(meta, "modexp circuit");
         let state_circuit = StateCircuitConfig::new(
             meta,
@@ -350,11 +398,15 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 copy_table,
                 keccak_table: keccak_table.clone(),
                 sha256_table,
+                ripemd160_table,
+                blake2f_table,
                 exp_table,
                 sig_table,
                 modexp_table,
                 ecc_table,
                 pow_of_rand_table,
+                fork_schedule,
+                modexp_size_limit: max_modexp_size,
             },
         );
         log_circuit_info(meta, "evm circuit");
@@ -400,6 +452,8 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             bytecode_circuit,
             keccak_circuit,
             sha256_circuit,
+            ripemd160_circuit,
+            blake2f_circuit,
             poseidon_circuit,
             pi_circuit,
             rlp_circuit,
@@ -434,6 +488,7 @@ pub struct SuperCircuit<
     const MAX_CALLDATA: usize,
     const MAX_INNER_BLOCKS: usize,
     const MOCK_RANDOMNESS: u64,
+    const MAX_MODEXP_SIZE: usize = DEFAULT_MODEXP_SIZE_LIMIT,
 > {
     /// EVM Circuit
     pub evm_circuit: EvmCircuit<F>,
@@ -453,6 +508,10 @@ pub struct SuperCircuit<
     pub keccak_circuit: KeccakCircuit<F>,
     /// SHA256 Circuit
     pub sha256_circuit: SHA256Circuit<F>,
+    /// Ripemd160 Circuit
+    pub ripemd160_circuit: Ripemd160Circuit<F>,
+    /// Blake2f Circuit
+    pub blake2f_circuit: Blake2fCircuit<F>,
     /// Poseidon hash Circuit
     pub poseidon_circuit: PoseidonCircuit<F>,
     /// Sig Circuit
@@ -476,7 +535,8 @@ impl<
         const MAX_CALLDATA: usize,
         const MAX_INNER_BLOCKS: usize,
         const MOCK_RANDOMNESS: u64,
-    > SuperCircuit<F, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+        const MAX_MODEXP_SIZE: usize,
+    > SuperCircuit<F, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, MAX_MODEXP_SIZE>
 {
     /// Return the number of rows required to verify a given block
     pub fn get_num_rows_required(block: &Block<Fr>) -> usize {
@@ -508,6 +568,10 @@ impl<
         push("keccak", keccak);
         let sha256 = SHA256Circuit::min_num_rows_block(block);
         push("sha256", sha256);
+        let ripemd160 = Ripemd160Circuit::min_num_rows_block(block);
+        push("ripemd160", ripemd160);
+        let blake2f = Blake2fCircuit::min_num_rows_block(block);
+        push("blake2f", blake2f);
         let tx = TxCircuit::min_num_rows_block(block);
         push("tx", tx);
         let rlp = RlpCircuit::min_num_rows_block(block);
@@ -558,8 +622,9 @@ impl<
         const MAX_CALLDATA: usize,
         const MAX_INNER_BLOCKS: usize,
         const MOCK_RANDOMNESS: u64,
+        const MAX_MODEXP_SIZE: usize,
     > SubCircuit<Fr>
-    for SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+    for SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, MAX_MODEXP_SIZE>
 {
     type Config = SuperCircuitConfig<Fr>;
 
@@ -590,13 +655,22 @@ impl<
         let modexp_circuit = ModExpCircuit::new_from_block(block);
         let keccak_circuit = KeccakCircuit::new_from_block(block);
         let sha256_circuit = SHA256Circuit::new_from_block(block);
+        let ripemd160_circuit = Ripemd160Circuit::new_from_block(block);
+        let blake2f_circuit = Blake2fCircuit::new_from_block(block);
         let poseidon_circuit = PoseidonCircuit::new_from_block(block);
         let rlp_circuit = RlpCircuit::new_from_block(block);
         let sig_circuit = SigCircuit::new_from_block(block);
         let ecc_circuit = EccCircuit::new_from_block(block);
         #[cfg(feature = "zktrie")]
         let mpt_circuit = MptCircuit::new_from_block(block);
-        SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS> {
+        SuperCircuit::<
+            Fr,
+            MAX_TXS,
+            MAX_CALLDATA,
+            MAX_INNER_BLOCKS,
+            MOCK_RANDOMNESS,
+            MAX_MODEXP_SIZE,
+        > {
             evm_circuit,
             state_circuit,
             tx_circuit,
@@ -606,6 +680,8 @@ impl<
             exp_circuit,
             keccak_circuit,
             sha256_circuit,
+            ripemd160_circuit,
+            blake2f_circuit,
             poseidon_circuit,
             rlp_circuit,
             sig_circuit,
@@ -667,6 +743,12 @@ impl<
         log::debug!("assigning sha256_circuit");
         self.sha256_circuit
             .synthesize_sub(&config.sha256_circuit, challenges, layouter)?;
+        log::debug!("assigning ripemd160_circuit");
+        self.ripemd160_circuit
+            .synthesize_sub(&config.ripemd160_circuit, challenges, layouter)?;
+        log::debug!("assigning blake2f_circuit");
+        self.blake2f_circuit
+            .synthesize_sub(&config.blake2f_circuit, challenges, layouter)?;
         log::debug!("assigning poseidon_circuit");
         self.poseidon_circuit
             .synthesize_sub(&config.poseidon_circuit, challenges, layouter)?;
@@ -728,7 +810,9 @@ impl<
         const MAX_CALLDATA: usize,
         const MAX_INNER_BLOCKS: usize,
         const MOCK_RANDOMNESS: u64,
-    > Circuit<Fr> for SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+        const MAX_MODEXP_SIZE: usize,
+    > Circuit<Fr>
+    for SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, MAX_MODEXP_SIZE>
 {
     type Config = (SuperCircuitConfig<Fr>, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
@@ -736,6 +820,7 @@ impl<
     fn without_witnesses(&self) -> Self {
         let dummy_block = Block::<Fr> {
             circuits_params: self.circuit_params,
+            fork_schedule: FORK_SCHEDULE.clone(),
             ..Default::default()
         };
         Self::new_from_block(&dummy_block)
@@ -751,6 +836,8 @@ impl<
                     max_calldata: MAX_CALLDATA,
                     max_inner_blocks: MAX_INNER_BLOCKS,
                     mock_randomness: MOCK_RANDOMNESS,
+                    fork_schedule: FORK_SCHEDULE.clone(),
+                    max_modexp_size: MAX_MODEXP_SIZE,
                     challenges,
                 },
             ),
@@ -777,8 +864,9 @@ impl<
         const MAX_CALLDATA: usize,
         const MAX_INNER_BLOCKS: usize,
         const MOCK_RANDOMNESS: u64,
+        const MAX_MODEXP_SIZE: usize,
     > CircuitExt<Fr>
-    for SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+    for SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, MAX_MODEXP_SIZE>
 {
     fn num_instance(&self) -> Vec<usize> {
         self.instances().iter().map(|l| l.len()).collect_vec()
@@ -794,7 +882,8 @@ impl<
         const MAX_CALLDATA: usize,
         const MAX_INNER_BLOCKS: usize,
         const MOCK_RANDOMNESS: u64,
-    > SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+        const MAX_MODEXP_SIZE: usize,
+    > SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, MAX_MODEXP_SIZE>
 {
     /// From the witness data, generate a SuperCircuit instance with all of the
     /// sub-circuits filled with their corresponding witnesses.
@@ -844,8 +933,14 @@ impl<
         let k = log2_ceil(Self::unusable_rows() + rows_needed);
         log::debug!("super circuit needs k = {}", k);
 
-        let circuit =
-            SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA,MAX_INNER_BLOCKS, MOCK_RANDOMNESS>::new_from_block(&block);
+        let circuit = SuperCircuit::<
+            Fr,
+            MAX_TXS,
+            MAX_CALLDATA,
+            MAX_INNER_BLOCKS,
+            MOCK_RANDOMNESS,
+            MAX_MODEXP_SIZE,
+        >::new_from_block(&block);
 
         let instance = circuit.instance();
         Ok((k, circuit, instance))
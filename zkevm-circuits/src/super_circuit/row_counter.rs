@@ -0,0 +1,318 @@
+//! Row usage of the super circuit, counted incrementally from the txs handled by a builder.
+
+use super::SubcircuitRowUsage;
+#[cfg(feature = "zktrie")]
+use crate::mpt_circuit::MptCircuit;
+use crate::{
+    blake2f_circuit::Blake2fCircuit,
+    bytecode_circuit::circuit::BytecodeCircuit,
+    copy_circuit::copy_min_num_rows,
+    ecc_circuit::EccCircuit,
+    evm_circuit::{evm_min_num_rows, is_bitwise_step},
+    exp_circuit::ExpCircuit,
+    keccak_circuit::{keccak_input_rows, keccak_min_num_rows},
+    modexp_circuit::ModExpCircuit,
+    pi_circuit::PiCircuit,
+    poseidon_circuit::{poseidon_min_num_rows, MptHashCounter},
+    ripemd160_circuit::Ripemd160Circuit,
+    sha256_circuit::SHA256Circuit,
+    sig_circuit::SigCircuit,
+    state_circuit::state_min_num_rows,
+    tx_circuit::TxCircuit,
+    util::{Challenges, SubCircuit},
+    witness::{tx_convert, Block, Bytecode, MptUpdates, RwMap},
+};
+use bus_mapping::{
+    circuit_input_builder::{
+        self, keccak_inputs_pi_circuit_lens, keccak_inputs_sign_verify, keccak_inputs_tx_circuit,
+        CircuitsParams, PrecompileEvents,
+    },
+    operation::OperationContainer,
+    state_db::CodeDB,
+    Error,
+};
+use eth_types::{geth_types, Word, U256};
+use ethers_core::utils::keccak256;
+use halo2_proofs::{circuit::Value, halo2curves::bn256::Fr};
+use mpt_zktrie::state::{witness::WitnessGenerator, ZktrieState};
+use std::collections::HashSet;
+
+/// Row usage of the super circuit, which is updated with the txs newly handled by a
+/// `CircuitInputBuilder`, instead of converting the whole witness block after each tx.
+///
+/// The rows are the same as
+/// [`SuperCircuit::min_num_rows_block_subcircuits`](super::SuperCircuit) of the witness block of
+/// the builder, since they're counted by the same formulas of the subcircuits, except the poseidon
+/// rows. The MPT updates of each `update` are applied to the zktrie state after the previous ones
+/// (instead of all the updates to the initial state as the witness block), so a key updated in
+/// several `update`s has a SMT trace for each of them, whose hashes are counted as well.
+#[derive(Debug)]
+pub struct RowCounter {
+    // Counted txs (without steps), events, bytecodes and MPT updates, for the subcircuits whose
+    // rows are cheap to count from them.
+    block: Block<Fr>,
+    // Numbers of the txs, operations, events and codes of the builder which are counted.
+    num_txs: usize,
+    num_rws: usize,
+    num_account_ops: usize,
+    num_storage_ops: usize,
+    num_copy_events: usize,
+    num_exp_events: usize,
+    num_sha3_inputs: usize,
+    num_precompile_events: usize,
+    num_codes: usize,
+    steps_height: usize,
+    need_bitwise_lookup: bool,
+    rlp_rows: usize,
+    copy_events_len: usize,
+    // Keccak rows of the deduplicated inputs, and the ones of PI circuit which are regenerated
+    // for all the txs.
+    keccak_hashes: HashSet<[u8; 32]>,
+    keccak_rows: usize,
+    pi_keccak_rows: usize,
+    // Zktrie state after the counted MPT updates, and the hashes of their SMT traces. There is no
+    // state in light mode.
+    mpt_state: Option<WitnessGenerator>,
+    mpt_hashes: MptHashCounter,
+}
+
+impl RowCounter {
+    /// Create an empty counter
+    pub fn new(circuits_params: CircuitsParams) -> Self {
+        Self {
+            block: Block {
+                circuits_params,
+                ..Default::default()
+            },
+            num_txs: 0,
+            num_rws: 0,
+            num_account_ops: 0,
+            num_storage_ops: 0,
+            num_copy_events: 0,
+            num_exp_events: 0,
+            num_sha3_inputs: 0,
+            num_precompile_events: 0,
+            num_codes: 0,
+            steps_height: 0,
+            need_bitwise_lookup: false,
+            rlp_rows: 0,
+            copy_events_len: 0,
+            keccak_hashes: HashSet::new(),
+            keccak_rows: 0,
+            pi_keccak_rows: 0,
+            mpt_state: None,
+            mpt_hashes: MptHashCounter::default(),
+        }
+    }
+
+    /// Count the rows of the txs added to the builder block since the last update.
+    /// The MPT updates of the new txs are applied to the zktrie state of the counter, which starts
+    /// from the initial zktrie state of the builder (not changed), to generate the SMT traces.
+    /// Without the zktrie state, there are no SMT traces as the light mode of the builder.
+    pub fn update(
+        &mut self,
+        builder_block: &circuit_input_builder::Block,
+        code_db: &CodeDB,
+        mpt_init_state: Option<&ZktrieState>,
+    ) -> Result<(), Error> {
+        let block = &mut self.block;
+        block.circuits_params = builder_block.circuits_params;
+        block.chain_id = builder_block.chain_id;
+
+        // Steps and RLP witness are counted per tx, and dropped then.
+        let challenges: Challenges<Value<Fr>> =
+            Challenges::mock(Value::unknown(), Value::unknown(), Value::unknown());
+        let new_txs = &builder_block.txs[self.num_txs..];
+        for (idx, builder_tx) in new_txs.iter().enumerate() {
+            let mut tx = tx_convert(
+                builder_tx,
+                self.num_txs + idx + 1,
+                builder_block.chain_id,
+                builder_tx.block_num + 1,
+            );
+            self.steps_height += tx
+                .steps
+                .iter()
+                .map(|step| step.execution_state.get_step_height())
+                .sum::<usize>();
+            self.need_bitwise_lookup |= tx.steps.iter().any(is_bitwise_step);
+            self.rlp_rows += tx.gen_sm_witness(&challenges).len();
+            tx.steps = Vec::new();
+            tx.calls = Vec::new();
+            block.txs.push(tx);
+        }
+
+        let new_precompile_events =
+            &builder_block.precompile_events.events[self.num_precompile_events..];
+        let new_ecrecover_events = PrecompileEvents {
+            events: new_precompile_events.to_vec(),
+        }
+        .get_ecrecover_events();
+
+        // The keccak inputs are deduplicated as `keccak_inputs`, except the ones of PI circuit,
+        // whose lengths only depend on the numbers of the blocks and txs.
+        let geth_txs: Vec<geth_types::Transaction> = new_txs.iter().map(|tx| tx.into()).collect();
+        let new_keccak_inputs = keccak_inputs_tx_circuit(&geth_txs)?
+            .into_iter()
+            .chain(keccak_inputs_sign_verify(&new_ecrecover_events))
+            .chain(
+                builder_block.sha3_inputs[self.num_sha3_inputs..]
+                    .iter()
+                    .cloned(),
+            );
+        for input in new_keccak_inputs {
+            if self.keccak_hashes.insert(keccak256(&input)) {
+                self.keccak_rows += keccak_input_rows(input.len());
+            }
+        }
+        self.pi_keccak_rows =
+            keccak_inputs_pi_circuit_lens(builder_block.headers.len(), builder_block.txs.len())
+                .into_iter()
+                .map(keccak_input_rows)
+                .sum();
+
+        block
+            .precompile_events
+            .events
+            .extend_from_slice(new_precompile_events);
+        block
+            .exp_events
+            .extend_from_slice(&builder_block.exp_events[self.num_exp_events..]);
+        self.copy_events_len += builder_block.copy_events[self.num_copy_events..]
+            .iter()
+            .map(|event| event.full_length() as usize)
+            .sum::<usize>();
+
+        // The codes are never removed from the code db, so it's walked only if new ones are
+        // inserted.
+        if code_db.0.len() != self.num_codes {
+            for (code_hash, bytes) in &code_db.0 {
+                let hash = Word::from_big_endian(code_hash.as_bytes());
+                block.bytecodes.entry(hash).or_insert_with(|| Bytecode {
+                    hash,
+                    bytes: bytes.clone(),
+                });
+            }
+        }
+
+        // The account and storage operations of the new txs are converted to MPT updates, whose
+        // SMT traces are generated from the zktrie state after the previous ones. Then they're
+        // merged by key with the previous ones as in the witness block.
+        let container = &builder_block.container;
+        let rws = RwMap::from(&OperationContainer {
+            account: container.account[self.num_account_ops..].to_vec(),
+            storage: container.storage[self.num_storage_ops..].to_vec(),
+            ..OperationContainer::new()
+        });
+        let mut mpt_updates = MptUpdates::from_unsorted_rws_with_mock_state_roots(
+            &rws.table_assignments_unsorted(),
+            U256::zero(),
+            U256::zero(),
+        );
+        if self.mpt_state.is_none() {
+            self.mpt_state = mpt_init_state
+                .filter(|mpt_init_state| *mpt_init_state.root() != [0u8; 32])
+                .map(WitnessGenerator::from);
+        }
+        if let Some(mpt_state) = self.mpt_state.take() {
+            self.mpt_state = Some(mpt_updates.apply_to_generator(mpt_state));
+            for smt_trace in &mpt_updates.smt_traces {
+                self.mpt_hashes.insert(smt_trace);
+            }
+        }
+        block.mpt_updates.extend(mpt_updates);
+
+        self.num_txs = builder_block.txs.len();
+        self.num_rws = num_operations(container);
+        self.num_account_ops = container.account.len();
+        self.num_storage_ops = container.storage.len();
+        self.num_copy_events = builder_block.copy_events.len();
+        self.num_exp_events = builder_block.exp_events.len();
+        self.num_sha3_inputs = builder_block.sha3_inputs.len();
+        self.num_precompile_events = builder_block.precompile_events.events.len();
+        self.num_codes = code_db.0.len();
+
+        Ok(())
+    }
+
+    /// Return true if there is no zktrie state to generate the SMT traces, whose poseidon rows
+    /// are not counted then.
+    pub fn is_light_mode(&self) -> bool {
+        self.mpt_state.is_none()
+    }
+
+    /// Return the row usage of the subcircuits, in the same order as
+    /// [`SuperCircuit::min_num_rows_block_subcircuits`](super::SuperCircuit).
+    pub fn row_usage(&self) -> Vec<SubcircuitRowUsage> {
+        let block = &self.block;
+        let params = &block.circuits_params;
+
+        let mut rows = Vec::new();
+        let mut push = |name: &str, (row_num_real, row_num_total): (usize, usize)| {
+            rows.push(SubcircuitRowUsage {
+                name: name.to_string(),
+                row_num_real,
+                row_num_total,
+            });
+        };
+        push(
+            "evm",
+            evm_min_num_rows(
+                self.steps_height,
+                self.need_bitwise_lookup,
+                params.max_evm_rows,
+            ),
+        );
+        push("state", state_min_num_rows(self.num_rws, params.max_rws));
+        push("bytecode", BytecodeCircuit::min_num_rows_block(block));
+        push(
+            "copy",
+            copy_min_num_rows(self.copy_events_len, params.max_copy_rows),
+        );
+        push(
+            "keccak",
+            keccak_min_num_rows(
+                self.keccak_rows + self.pi_keccak_rows,
+                params.max_keccak_rows,
+            ),
+        );
+        push("sha256", SHA256Circuit::min_num_rows_block(block));
+        push("ripemd160", Ripemd160Circuit::min_num_rows_block(block));
+        push("blake2f", Blake2fCircuit::min_num_rows_block(block));
+        push("tx", TxCircuit::min_num_rows_block(block));
+        // As `RlpCircuit::min_num_rows_block`, whose witness is generated per tx.
+        push("rlp", (self.rlp_rows, params.max_rlp_rows));
+        push("exp", ExpCircuit::min_num_rows_block(block));
+        push("mod_exp", ModExpCircuit::min_num_rows_block(block));
+        push("pi", PiCircuit::min_num_rows_block(block));
+        push(
+            "poseidon",
+            poseidon_min_num_rows::<Fr>(
+                &self.mpt_hashes,
+                &block.bytecodes,
+                params.max_poseidon_rows,
+            ),
+        );
+        push("sig", SigCircuit::min_num_rows_block(block));
+        push("ecc", EccCircuit::<Fr, 9>::min_num_rows_block(block));
+        #[cfg(feature = "zktrie")]
+        push("mpt", MptCircuit::<Fr>::min_num_rows_block(block));
+
+        rows
+    }
+}
+
+fn num_operations(container: &OperationContainer) -> usize {
+    container.memory.len()
+        + container.stack.len()
+        + container.storage.len()
+        + container.tx_access_list_account.len()
+        + container.tx_access_list_account_storage.len()
+        + container.tx_refund.len()
+        + container.account.len()
+        + container.call_context.len()
+        + container.tx_receipt.len()
+        + container.tx_log.len()
+        + container.transient_storage.len()
+        + container.start.len()
+}
@@ -55,15 +55,7 @@ fn super_circuit_degree() {
 }
 
 #[cfg(feature = "scroll")]
-fn test_super_circuit<
-    const MAX_TXS: usize,
-    const MAX_CALLDATA: usize,
-    const MAX_INNER_BLOCKS: usize,
-    const MOCK_RANDOMNESS: u64,
->(
-    l2_trace: BlockTrace,
-    circuits_params: CircuitsParams,
-) {
+fn witness_block_from_l2_trace(l2_trace: BlockTrace, circuits_params: CircuitsParams) -> Block<Fr> {
     set_var("COINBASE", "0x0000000000000000000000000000000000000000");
     set_var("CHAIN_ID", MOCK_CHAIN_ID.to_string());
     let mut difficulty_be_bytes = [0u8; 32];
@@ -84,6 +76,21 @@ fn test_super_circuit<
         &builder.mpt_init_state.expect("used non-light mode"),
     );
 
+    block
+}
+
+#[cfg(feature = "scroll")]
+fn test_super_circuit<
+    const MAX_TXS: usize,
+    const MAX_CALLDATA: usize,
+    const MAX_INNER_BLOCKS: usize,
+    const MOCK_RANDOMNESS: u64,
+>(
+    l2_trace: BlockTrace,
+    circuits_params: CircuitsParams,
+) {
+    let block = witness_block_from_l2_trace(l2_trace, circuits_params);
+
     let active_row_num =SuperCircuit::<
         Fr,
         MAX_TXS,
@@ -247,6 +254,255 @@ pub(crate) fn block_2tx() -> GethData {
 
 const TEST_MOCK_RANDOMNESS: u64 = 0x100;
 
+#[cfg(feature = "scroll")]
+fn builder_of_ctx<const NACC: usize, const NTX: usize>(
+    ctx: TestContext<NACC, NTX>,
+    circuits_params: CircuitsParams,
+) -> CircuitInputBuilder {
+    let mut builder = CircuitInputBuilder::new_from_l2_trace(
+        circuits_params,
+        ctx.l2_trace().clone(),
+        false,
+        false,
+    )
+    .expect("could not handle block tx");
+    builder
+        .finalize_building()
+        .expect("could not finalize building block");
+
+    builder
+}
+
+#[cfg(not(feature = "scroll"))]
+fn builder_of_ctx<const NACC: usize, const NTX: usize>(
+    ctx: TestContext<NACC, NTX>,
+    circuits_params: CircuitsParams,
+) -> CircuitInputBuilder {
+    let block: GethData = ctx.into();
+    let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), circuits_params)
+        .new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .expect("could not handle block tx");
+
+    builder
+}
+
+// The rows counted by `RowCounter` are the same as the ones of the witness block of the builder.
+fn assert_row_counter_matches_witness_block(builder: CircuitInputBuilder) {
+    let row_nums = |rows: Vec<SubcircuitRowUsage>| {
+        rows.into_iter()
+            .map(|row| (row.name, row.row_num_real, row.row_num_total))
+            .collect_vec()
+    };
+
+    let mut row_counter = RowCounter::new(builder.block.circuits_params);
+    row_counter
+        .update(
+            &builder.block,
+            &builder.code_db,
+            builder.mpt_init_state.as_ref(),
+        )
+        .unwrap();
+    let row_usage = row_nums(row_counter.row_usage());
+
+    // Nothing is counted again without new txs.
+    row_counter
+        .update(
+            &builder.block,
+            &builder.code_db,
+            builder.mpt_init_state.as_ref(),
+        )
+        .unwrap();
+    assert_eq!(row_nums(row_counter.row_usage()), row_usage);
+
+    let mut block = block_convert(&builder.block, &builder.code_db).unwrap();
+    if let Some(mpt_init_state) = &builder.mpt_init_state {
+        block_apply_mpt_state(&mut block, mpt_init_state);
+    }
+    let expected = row_nums(
+        SuperCircuit::<Fr, 4, 256, 1, TEST_MOCK_RANDOMNESS>::min_num_rows_block_subcircuits(&block),
+    );
+    assert_eq!(row_usage, expected);
+}
+
+#[test]
+fn row_counter_matches_witness_block() {
+    let circuits_params = CircuitsParams {
+        max_txs: 4,
+        max_calldata: 256,
+        max_rws: 1024,
+        max_copy_rows: 1024,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_mpt_rows: 2049,
+        max_poseidon_rows: 1024,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        max_inner_blocks: 1,
+        max_rlp_rows: 1024,
+        ..Default::default()
+    };
+
+    assert_row_counter_matches_witness_block(builder_of_ctx(block_1tx_ctx(), circuits_params));
+    assert_row_counter_matches_witness_block(builder_of_ctx(block_2tx_ctx(), circuits_params));
+}
+
+// The traces of the transfers of a block, one per trace, which are consecutive since they're
+// executed one after another.
+#[cfg(feature = "scroll")]
+fn transfer_traces() -> Vec<BlockTrace> {
+    let block_trace = TestContext::<2, 3>::new(
+        None,
+        |accs| {
+            accs[0]
+                .address(address!("0x000000000000000000000000000000000000AAAA"))
+                .balance(eth(10));
+            accs[1]
+                .address(address!("0x000000000000000000000000000000000000BBBB"))
+                .balance(eth(10));
+        },
+        |txs, accs| {
+            for tx in txs {
+                tx.from(accs[0].address).to(accs[1].address).value(eth(1));
+            }
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .l2_trace()
+    .clone();
+
+    (0..block_trace.transactions.len())
+        .map(|idx| {
+            let mut trace = block_trace.clone();
+            trace.transactions = vec![block_trace.transactions[idx].clone()];
+            trace.execution_results = vec![block_trace.execution_results[idx].clone()];
+            trace.tx_storage_trace = block_trace
+                .tx_storage_trace
+                .get(idx)
+                .cloned()
+                .into_iter()
+                .collect();
+            trace
+        })
+        .collect()
+}
+
+// The traces added to a builder one at a time are counted incrementally, the same as the witness
+// block of the builder, except the poseidon rows.
+#[cfg(feature = "scroll")]
+#[test]
+fn row_counter_counts_traces_one_at_a_time() {
+    let circuits_params = CircuitsParams {
+        max_txs: 4,
+        max_calldata: 256,
+        max_rws: 1024,
+        max_copy_rows: 1024,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_mpt_rows: 2049,
+        max_poseidon_rows: 1024,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        max_inner_blocks: 1,
+        max_rlp_rows: 1024,
+        ..Default::default()
+    };
+
+    let mut traces = transfer_traces().into_iter();
+    let mut builder = CircuitInputBuilder::new_from_l2_trace(
+        circuits_params,
+        traces.next().unwrap(),
+        true,
+        false,
+    )
+    .expect("could not handle block tx");
+    let mut row_counter = RowCounter::new(circuits_params);
+    row_counter
+        .update(
+            &builder.block,
+            &builder.code_db,
+            builder.mpt_init_state.as_ref(),
+        )
+        .unwrap();
+    for trace in traces {
+        builder
+            .add_more_l2_trace(trace, true)
+            .expect("could not handle block tx");
+        row_counter
+            .update(
+                &builder.block,
+                &builder.code_db,
+                builder.mpt_init_state.as_ref(),
+            )
+            .unwrap();
+    }
+    assert!(!row_counter.is_light_mode());
+
+    let mut block = block_convert(&builder.block, &builder.code_db).unwrap();
+    block_apply_mpt_state(&mut block, builder.mpt_init_state.as_ref().unwrap());
+    let expected =
+        SuperCircuit::<Fr, 4, 256, 1, TEST_MOCK_RANDOMNESS>::min_num_rows_block_subcircuits(&block);
+
+    for (row, expected) in row_counter.row_usage().iter().zip_eq(&expected) {
+        assert_eq!(row.name, expected.name);
+        if row.name == "poseidon" {
+            // The accounts updated by every trace have a SMT trace per trace, instead of one for
+            // all the traces as the witness block.
+            assert!(
+                row.row_num_real > expected.row_num_real,
+                "{row:?} {expected:?}"
+            );
+        } else {
+            assert_eq!(row.row_num_real, expected.row_num_real, "{}", row.name);
+            assert_eq!(row.row_num_total, expected.row_num_total, "{}", row.name);
+        }
+    }
+}
+
+// The witness block reloaded from its serialization is the same, and has the same row usage.
+#[cfg(feature = "scroll")]
+#[test]
+fn witness_block_serde_round_trip() {
+    let circuits_params = CircuitsParams {
+        max_txs: 1,
+        max_calldata: 256,
+        max_rws: 256,
+        max_copy_rows: 256,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_mpt_rows: 2049,
+        max_poseidon_rows: 512,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        max_inner_blocks: 1,
+        max_rlp_rows: 500,
+        ..Default::default()
+    };
+    let block = witness_block_from_l2_trace(block_1tx_trace(), circuits_params);
+    let reloaded_block: Block<Fr> =
+        serde_json::from_slice(&serde_json::to_vec(&block).unwrap()).unwrap();
+    // Compared as JSON values, since the maps of the block are serialized in arbitrary orders.
+    assert_eq!(
+        serde_json::to_value(&reloaded_block).unwrap(),
+        serde_json::to_value(&block).unwrap()
+    );
+
+    type TestSuperCircuit = SuperCircuit<Fr, 1, 256, 1, TEST_MOCK_RANDOMNESS>;
+    let row_usage = TestSuperCircuit::min_num_rows_block_subcircuits(&block);
+    let reloaded_row_usage = TestSuperCircuit::min_num_rows_block_subcircuits(&reloaded_block);
+    for (row, reloaded_row) in row_usage.iter().zip_eq(&reloaded_row_usage) {
+        assert_eq!(row.name, reloaded_row.name);
+        assert_eq!(row.row_num_real, reloaded_row.row_num_real, "{}", row.name);
+        assert_eq!(
+            row.row_num_total, reloaded_row.row_num_total,
+            "{}",
+            row.name
+        );
+    }
+}
+
 // High memory usage test.  Run in serial with:
 // `cargo test [...] serial_ -- --ignored --test-threads 1`
 #[ignore]
@@ -278,6 +534,46 @@ fn serial_test_super_circuit_1tx_1max_tx() {
     );
 }
 
+#[ignore]
+#[cfg(feature = "scroll")]
+#[test]
+fn serial_test_super_circuit_reloaded_witness_block() {
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 256;
+    const MAX_INNER_BLOCKS: usize = 1;
+    let circuits_params = CircuitsParams {
+        max_txs: MAX_TXS,
+        max_calldata: MAX_CALLDATA,
+        max_rws: 256,
+        max_copy_rows: 256,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_mpt_rows: 2049,
+        max_poseidon_rows: 512,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        max_inner_blocks: MAX_INNER_BLOCKS,
+        max_rlp_rows: 500,
+        ..Default::default()
+    };
+    let block = witness_block_from_l2_trace(block_1tx_trace(), circuits_params);
+    let reloaded_block: Block<Fr> =
+        serde_json::from_slice(&serde_json::to_vec(&block).unwrap()).unwrap();
+
+    type TestSuperCircuit =
+        SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>;
+    let (k, circuit, instance) = TestSuperCircuit::build_from_witness_block(block).unwrap();
+    let (reloaded_k, reloaded_circuit, reloaded_instance) =
+        TestSuperCircuit::build_from_witness_block(reloaded_block).unwrap();
+    assert_eq!(k, reloaded_k);
+    assert_eq!(instance, reloaded_instance);
+
+    let prover = MockProver::run(k, &circuit, instance).unwrap();
+    let reloaded_prover = MockProver::run(k, &reloaded_circuit, reloaded_instance).unwrap();
+    assert!(prover.advice() == reloaded_prover.advice());
+    assert!(prover.fixed() == reloaded_prover.fixed());
+}
+
 #[ignore]
 #[cfg(feature = "scroll")]
 #[test]
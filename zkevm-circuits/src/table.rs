@@ -16,8 +16,8 @@ use crate::{
 };
 use bus_mapping::{
     circuit_input_builder::{
-        BigModExp, CopyDataType, CopyEvent, CopyStep, EcAddOp, EcMulOp, EcPairingOp, ExpEvent,
-        PrecompileEcParams, N_BYTES_PER_PAIR, N_PAIRING_PER_OP,
+        BigModExp, Blake2F, CopyDataType, CopyEvent, CopyStep, EcAddOp, EcMulOp, EcPairingOp,
+        ExpEvent, PrecompileEcParams, N_BYTES_PER_PAIR, N_PAIRING_PER_OP,
     },
     precompile::PrecompileCalls,
 };
@@ -34,6 +34,7 @@ use halo2_proofs::{
     plonk::{Advice, Any, Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
     poly::Rotation,
 };
+use serde::{Deserialize, Serialize};
 use snark_verifier::util::arithmetic::PrimeCurveAffine;
 
 use std::iter::repeat;
@@ -181,6 +182,14 @@ pub enum TxFieldTag {
     AccessListStorageKeysLen,
     /// RLC of access list (EIP-2930)
     AccessListRLC,
+    /// Max fee per gas (EIP-1559), only set for blob txs
+    MaxFeePerGas,
+    /// Max priority fee per gas (EIP-1559), only set for blob txs
+    MaxPriorityFeePerGas,
+    /// Max fee per blob gas (EIP-4844)
+    MaxFeePerBlobGas,
+    /// Blob versioned hash at the position given by the index (EIP-4844)
+    BlobVersionedHash,
     /// The block number in which this tx is included.
     BlockNumber,
 }
@@ -391,7 +400,7 @@ impl<F: Field> LookupTable<F> for TxTable {
 }
 
 /// Tag to identify the operation type in a RwTable row
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter, Serialize, Deserialize)]
 pub enum RwTableTag {
     /// Start (used for padding)
     Start = 1,
@@ -415,6 +424,8 @@ pub enum RwTableTag {
     TxLog,
     /// Tx Receipt operation
     TxReceipt,
+    /// Transient Storage operation
+    TransientStorage,
 }
 impl_expr!(RwTableTag);
 
@@ -428,6 +439,7 @@ impl RwTableTag {
                 | RwTableTag::TxRefund
                 | RwTableTag::Account
                 | RwTableTag::AccountStorage
+                | RwTableTag::TransientStorage
         )
     }
 }
@@ -439,7 +451,9 @@ impl From<RwTableTag> for usize {
 }
 
 /// Tag for an AccountField in RwTable
-#[derive(Clone, Copy, Debug, EnumIter, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(
+    Clone, Copy, Debug, EnumIter, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
 pub enum AccountFieldTag {
     /// Nonce field
     Nonce,
@@ -457,7 +471,7 @@ pub enum AccountFieldTag {
 impl_expr!(AccountFieldTag);
 
 /// Tag for a TxLogField in RwTable
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
 pub enum TxLogFieldTag {
     /// Address field
     Address = 1,
@@ -469,7 +483,7 @@ pub enum TxLogFieldTag {
 impl_expr!(TxLogFieldTag);
 
 /// Tag for a TxReceiptField in RwTable
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, EnumCount)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, EnumCount, Serialize, Deserialize)]
 pub enum TxReceiptFieldTag {
     /// Tx result
     PostStateOrStatus = 1,
@@ -480,8 +494,18 @@ pub enum TxReceiptFieldTag {
 }
 impl_expr!(TxReceiptFieldTag);
 
+/// Tag for a TransientStorageField in RwTable
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
+pub enum TransientStorageFieldTag {
+    /// Transient storage slot (EIP-1153)
+    Slot = 0,
+    /// Whether the account is created in the current tx (EIP-6780), stored at key 0
+    AccountCreated,
+}
+impl_expr!(TransientStorageFieldTag);
+
 /// Tag for a CallContextField in RwTable
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
 pub enum CallContextFieldTag {
     /// RwCounterEndOfReversion
     RwCounterEndOfReversion = 1,
@@ -1225,6 +1249,8 @@ pub enum BlockContextFieldTag {
     /// included in this block which also taking skipped l1 msgs into account.
     /// This could possibly be larger than NumTxs.
     NumAllTxs,
+    /// Blob base fee (EIP-4844), derived from the block's excess blob gas.
+    BlobBaseFee,
 }
 impl_expr!(BlockContextFieldTag);
 
@@ -1614,6 +1640,248 @@ impl SHA256Table {
     }
 }
 
+/// Ripemd160 Table, used to verify RIPEMD-160 hashing from RLC'ed input in precompile.
+#[derive(Clone, Debug)]
+pub struct Ripemd160Table {
+    /// True when the row is enabled
+    pub q_enable: Column<Fixed>,
+    /// True when the row is final
+    pub is_final: Column<Advice>,
+    /// Byte array input as `RLC(reversed(input))`
+    pub input_rlc: Column<Advice>, // RLC of input bytes
+    /// Byte array input length
+    pub input_len: Column<Advice>,
+    /// RLC of the hash result
+    pub output_rlc: Column<Advice>, // RLC of hash of input bytes
+}
+
+impl<F: Field> LookupTable<F> for Ripemd160Table {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.is_final.into(),
+            self.input_rlc.into(),
+            self.input_len.into(),
+            self.output_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("is_final"),
+            String::from("input_rlc"),
+            String::from("input_len"),
+            String::from("output_rlc"),
+        ]
+    }
+}
+
+impl Ripemd160Table {
+    /// Construct a new Ripemd160Table
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            q_enable: meta.fixed_column(),
+            is_final: meta.advice_column(),
+            input_len: meta.advice_column(),
+            input_rlc: meta.advice_column_in(SecondPhase),
+            output_rlc: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    /// Generate the ripemd160 table assignments from a byte array pair of input/output.
+    /// Used only for dev_load
+    pub fn assignments<F: Field>(
+        entry: (&[u8], &[u8; 20]),
+        challenges: &Challenges<Value<F>>,
+    ) -> Vec<[Value<F>; 4]> {
+        let (input, output) = entry;
+        let input_len = Value::known(F::from(input.len() as u64));
+        let input_rlc = challenges
+            .keccak_input()
+            .map(|challenge| rlc::value(input.iter().rev(), challenge));
+        let output_rlc = challenges
+            .keccak_input()
+            .map(|challenge| rlc::value(output.iter().rev(), challenge));
+
+        vec![[Value::known(F::one()), input_rlc, input_len, output_rlc]]
+    }
+
+    /// Provide this function for the case that we want to consume a ripemd160
+    /// table but without running the full ripemd160 circuit
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: impl IntoIterator<Item = (&'a Vec<u8>, &'a [u8; 20])> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "ripemd160 table dev",
+            |mut region| {
+                let mut offset = 0;
+                for column in <Self as LookupTable<F>>::advice_columns(self) {
+                    region.assign_fixed(
+                        || "ripemd160 table all-zero row",
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    region.assign_advice(
+                        || "ripemd160 table all-zero row",
+                        column,
+                        offset,
+                        || Value::known(F::zero()),
+                    )?;
+                }
+                offset += 1;
+
+                let table_columns = <Self as LookupTable<F>>::advice_columns(self);
+                for (input, digest) in entries.clone() {
+                    for row in Self::assignments((input, digest), challenges) {
+                        region.assign_fixed(
+                            || format!("table row {offset}"),
+                            self.q_enable,
+                            offset,
+                            || Value::known(F::one()),
+                        )?;
+                        for (&column, value) in table_columns.iter().zip_eq(row) {
+                            region.assign_advice(
+                                || format!("table row {offset}"),
+                                column,
+                                offset,
+                                || value,
+                            )?;
+                        }
+                        offset += 1;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Blake2f Table, used to verify the BLAKE2 compression function F from RLC'ed input and output
+/// in precompile.
+#[derive(Clone, Debug)]
+pub struct Blake2fTable {
+    /// True when the row is enabled
+    pub q_enable: Column<Fixed>,
+    /// True when the row holds the result of a compression
+    pub is_final: Column<Advice>,
+    /// Number of rounds of the compression
+    pub rounds: Column<Advice>,
+    /// Byte array input as `RLC(reversed(input))`
+    pub input_rlc: Column<Advice>,
+    /// Byte array output as `RLC(reversed(output))`
+    pub output_rlc: Column<Advice>,
+}
+
+impl<F: Field> LookupTable<F> for Blake2fTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.is_final.into(),
+            self.rounds.into(),
+            self.input_rlc.into(),
+            self.output_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("is_final"),
+            String::from("rounds"),
+            String::from("input_rlc"),
+            String::from("output_rlc"),
+        ]
+    }
+}
+
+impl Blake2fTable {
+    /// Construct a new Blake2fTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            q_enable: meta.fixed_column(),
+            is_final: meta.advice_column(),
+            rounds: meta.advice_column(),
+            input_rlc: meta.advice_column_in(SecondPhase),
+            output_rlc: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    /// Generate the blake2f table assignments from a compression event.
+    /// Used only for dev_load
+    pub fn assignments<F: Field>(
+        event: &Blake2F,
+        challenges: &Challenges<Value<F>>,
+    ) -> Vec<[Value<F>; 4]> {
+        let rounds = Value::known(F::from(event.rounds as u64));
+        let input_rlc = challenges
+            .keccak_input()
+            .map(|challenge| rlc::value(event.input_bytes().iter().rev(), challenge));
+        let output_rlc = challenges
+            .keccak_input()
+            .map(|challenge| rlc::value(event.output_bytes().iter().rev(), challenge));
+
+        vec![[Value::known(F::one()), rounds, input_rlc, output_rlc]]
+    }
+
+    /// Provide this function for the case that we want to consume a blake2f
+    /// table but without running the full blake2f circuit
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        events: impl IntoIterator<Item = &'a Blake2F> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "blake2f table dev",
+            |mut region| {
+                let mut offset = 0;
+                for column in <Self as LookupTable<F>>::advice_columns(self) {
+                    region.assign_fixed(
+                        || "blake2f table all-zero row",
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    region.assign_advice(
+                        || "blake2f table all-zero row",
+                        column,
+                        offset,
+                        || Value::known(F::zero()),
+                    )?;
+                }
+                offset += 1;
+
+                let table_columns = <Self as LookupTable<F>>::advice_columns(self);
+                for event in events.clone() {
+                    for row in Self::assignments(event, challenges) {
+                        region.assign_fixed(
+                            || format!("table row {offset}"),
+                            self.q_enable,
+                            offset,
+                            || Value::known(F::one()),
+                        )?;
+                        for (&column, value) in table_columns.iter().zip_eq(row) {
+                            region.assign_advice(
+                                || format!("table row {offset}"),
+                                column,
+                                offset,
+                                || value,
+                            )?;
+                        }
+                        offset += 1;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
 /// Copy Table, used to verify copies of byte chunks between Memory, Bytecode,
 /// TxLogs and TxCallData.
 #[derive(Clone, Copy, Debug)]
@@ -1624,7 +1892,7 @@ pub struct CopyTable {
     pub is_first: Column<Advice>,
     /// The relevant ID for the read-write row, represented as a random linear
     /// combination. The ID may be one of the below:
-    /// 1. Call ID/Caller ID for CopyDataType::Memory
+    /// 1. Call ID/Caller ID for CopyDataType::Memory and CopyDataType::MemoryCopy
     /// 2. RLC encoding of bytecode hash for CopyDataType::Bytecode
     /// 3. Transaction ID for CopyDataType::TxCalldata, CopyDataType::TxLog
     pub id: Column<Advice>,
@@ -1735,6 +2003,9 @@ impl CopyTable {
 
         let mut rw_counter = copy_event.rw_counter_start();
         let mut rwc_inc_left = copy_event.rw_counter_delta();
+        // A memory copy reads all the source words, then writes all the destination words.
+        let is_memory_copy = copy_event.src_type == CopyDataType::MemoryCopy;
+        let word_count = copy_event.full_length() / 32;
 
         let mut reader = CopyThread {
             tag: copy_event.src_type,
@@ -1822,6 +2093,20 @@ impl CopyTable {
 
             let word_index = (step_idx as u64 / 2) % 32;
 
+            let (row_rw_counter, row_rwc_inc_left) = if is_memory_copy {
+                let word_offset = step_idx as u64 / 64;
+                if is_read_step {
+                    (rw_counter + word_offset, 2 * word_count - word_offset)
+                } else {
+                    (
+                        rw_counter + word_count + word_offset,
+                        word_count - word_offset,
+                    )
+                }
+            } else {
+                (rw_counter, rwc_inc_left)
+            };
+
             // For LOG, format the address including the log_id.
             let addr = if thread.tag == CopyDataType::TxLog {
                 build_tx_log_address(thread.addr, TxLogFieldTag::Data, copy_event.log_id.unwrap())
@@ -1840,8 +2125,8 @@ impl CopyTable {
                     (Value::known(F::from(thread.addr_end)), "src_addr_end"),
                     (Value::known(F::from(thread.bytes_left)), "real_bytes_left"),
                     (rlc_acc, "rlc_acc"),
-                    (Value::known(F::from(rw_counter)), "rw_counter"),
-                    (Value::known(F::from(rwc_inc_left)), "rwc_inc_left"),
+                    (Value::known(F::from(row_rw_counter)), "rw_counter"),
+                    (Value::known(F::from(row_rwc_inc_left)), "rwc_inc_left"),
                 ],
                 [
                     (Value::known(F::from(is_last)), "is_last"),
@@ -1867,7 +2152,7 @@ impl CopyTable {
             }
             // Update the RW counter.
             let is_word_end = (step_idx / 2) % 32 == 31;
-            if is_word_end && thread.is_rw {
+            if is_word_end && thread.is_rw && !is_memory_copy {
                 rw_counter += 1;
                 rwc_inc_left -= 1;
             }
@@ -2203,6 +2488,10 @@ pub struct RlpFsmRlpTable {
     pub is_output: Column<Advice>,
     /// Whether or not the current tag's value was nil.
     pub is_none: Column<Advice>,
+    /// The index (starting from 1) of the RLP byte at which the current tag is decoded. It's
+    /// not one of the lookup columns, the lookups binding the position of a tag query it
+    /// explicitly.
+    pub byte_idx: Column<Advice>,
 }
 
 impl<F: Field> LookupTable<F> for RlpFsmRlpTable {
@@ -2248,6 +2537,7 @@ impl RlpFsmRlpTable {
             tag_length: meta.advice_column(),
             is_output: meta.advice_column(),
             is_none: meta.advice_column(),
+            byte_idx: meta.advice_column(),
         }
     }
 
@@ -2262,7 +2552,7 @@ impl RlpFsmRlpTable {
             .into_iter()
             .flat_map(|tx| tx.gen_sm_witness(challenges))
             .filter(|row| row.rlp_table.is_output)
-            .map(|row| row.rlp_table)
+            .map(|row| (row.rlp_table, row.state_machine.byte_idx))
             .collect::<Vec<_>>();
 
         let assign_any = |region: &mut Region<'_, F>,
@@ -2284,7 +2574,7 @@ impl RlpFsmRlpTable {
         layouter.assign_region(
             || "RLP dev table",
             |mut region| {
-                for (i, row) in rows.iter().enumerate() {
+                for (i, (row, byte_idx)) in rows.iter().enumerate() {
                     let cells: Vec<(&'static str, Column<Any>, Value<F>)> = vec![
                         ("q_enable", self.q_enable.into(), Value::known(F::one())),
                         ("tx_id", self.tx_id.into(), Value::known(F::from(row.tx_id))),
@@ -2315,6 +2605,11 @@ impl RlpFsmRlpTable {
                             self.is_none.into(),
                             Value::known(F::from(row.is_none as u64)),
                         ),
+                        (
+                            "byte_idx",
+                            self.byte_idx.into(),
+                            Value::known(F::from(*byte_idx as u64)),
+                        ),
                     ];
 
                     for cell in cells.into_iter() {
@@ -2673,82 +2968,61 @@ impl EccTable {
     }
 }
 
-/// Lookup table embedded in the modexp circuit for precompile.
+/// Lookup table embedded in the modexp circuit for precompile. Every entry holds an
+/// exponentiation `base ^ exp = result (mod modulus)`, the operands being given by the RLC of
+/// their big-endian bytes, which does not depend on the leading zero bytes of an operand.
 #[derive(Clone, Copy, Debug)]
 pub struct ModExpTable {
-    /// Use for indicate beginning of a limbs group
-    pub q_head: Column<Fixed>,
-    /// base represented by limbs
+    /// True when the row is enabled
+    pub q_enable: Column<Fixed>,
+    /// True when the row is the final row of an exponentiation, which holds the table entry
+    pub is_final: Column<Advice>,
+    /// Base as `RLC(reversed(base))`
     pub base: Column<Advice>,
-    /// exp represented by limbs
+    /// Exponent as `RLC(reversed(exp))`
     pub exp: Column<Advice>,
-    /// modulus represented by limbs
+    /// Bit length of the exponent value, required by the gas cost of the precompile
+    pub exp_bit_len: Column<Advice>,
+    /// Modulus as `RLC(reversed(modulus))`
     pub modulus: Column<Advice>,
-    /// result represented by limbs
+    /// Result as `RLC(reversed(result))`, the result being zero for a zero modulus
     pub result: Column<Advice>,
 }
 
 impl ModExpTable {
     /// Construct the modexp table.
     pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
-        let ret = Self {
-            q_head: meta.fixed_column(),
-            base: meta.advice_column(),
-            exp: meta.advice_column(),
-            modulus: meta.advice_column(),
-            result: meta.advice_column(),
-        };
-        meta.enable_equality(ret.base);
-        meta.enable_equality(ret.exp);
-        meta.enable_equality(ret.modulus);
-        meta.enable_equality(ret.result);
-        ret
-    }
-
-    /// helper for devide a U256 into 3 108bit limbs
-    pub fn split_u256_108bit_limbs(word: &Word) -> [u128; 3] {
-        let bit108 = 1u128 << 108;
-        let (next, limb0) = word.div_mod(U256::from(bit108));
-        let (limb2, limb1) = next.div_mod(U256::from(bit108));
-        [limb0.as_u128(), limb1.as_u128(), limb2.as_u128()]
+        Self {
+            q_enable: meta.fixed_column(),
+            is_final: meta.advice_column(),
+            base: meta.advice_column_in(SecondPhase),
+            exp: meta.advice_column_in(SecondPhase),
+            exp_bit_len: meta.advice_column(),
+            modulus: meta.advice_column_in(SecondPhase),
+            result: meta.advice_column_in(SecondPhase),
+        }
     }
 
-    /// helper for obtain the modulus of a U256 in Fr
-    pub fn native_u256<F: Field>(word: &Word) -> F {
-        let minus1 = -F::one();
-        let div = Word::from_little_endian(minus1.to_repr().as_ref()) + Word::from(1u64);
-        let (_, remainder) = word.div_mod(div);
-
-        let mut bytes = [0u8; 64];
-        remainder.to_little_endian(&mut bytes[..32]);
-        F::from_bytes_wide(&bytes)
-    }
+    /// Generate the modexp table assignments of an exponentiation, in the order of the advice
+    /// columns of the table.
+    pub fn assignments<F: Field>(
+        event: &BigModExp,
+        challenges: &Challenges<Value<F>>,
+    ) -> [Value<F>; 6] {
+        let rlc = |bytes: &[u8]| {
+            challenges
+                .keccak_input()
+                .map(|challenge| rlc::value(bytes.iter().rev(), challenge))
+        };
 
-    /// fill a blank 4-row region start from offset for empty lookup
-    pub fn fill_blank<F: Field>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
-        layouter.assign_region(
-            || "modexp table blank region",
-            |mut region| {
-                for i in 0..4 {
-                    // fill last totally 0 row
-                    region.assign_fixed(
-                        || "modexp table blank head row",
-                        self.q_head,
-                        i,
-                        || Value::known(F::zero()),
-                    )?;
-                    for &col in [&self.base, &self.exp, &self.modulus, &self.result] {
-                        region.assign_advice(
-                            || "modexp table blank limb row",
-                            col,
-                            i,
-                            || Value::known(F::zero()),
-                        )?;
-                    }
-                }
-                Ok(())
-            },
-        )
+        [
+            Value::known(F::one()),
+            rlc(&event.base),
+            rlc(&event.exponent),
+            Value::known(F::from(event.exponent_bit_len() as u64)),
+            rlc(&event.modulus),
+            rlc(&event.result),
+        ]
     }
 
     /// Get assignments to the modexp table. Meant to be used for dev purposes.
@@ -2756,73 +3030,49 @@ impl ModExpTable {
         &self,
         layouter: &mut impl Layouter<F>,
         events: &[BigModExp],
+        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         layouter.assign_region(
-            || "modexp table",
+            || "modexp table dev",
             |mut region| {
-                let mut offset = 0usize;
-
-                for event in events {
-                    for i in 0..4 {
-                        region.assign_fixed(
-                            || format!("modexp table head {}", offset + i),
-                            self.q_head,
-                            offset + i,
-                            || Value::known(if i == 0 { F::one() } else { F::zero() }),
-                        )?;
-                    }
-
-                    let base_limbs = Self::split_u256_108bit_limbs(&event.base);
-                    let exp_limbs = Self::split_u256_108bit_limbs(&event.exponent);
-                    let modulus_limbs = Self::split_u256_108bit_limbs(&event.modulus);
-                    let result_limbs = Self::split_u256_108bit_limbs(&event.result);
-
-                    for i in 0..3 {
-                        for (limbs, &col) in [base_limbs, exp_limbs, modulus_limbs, result_limbs]
-                            .zip([&self.base, &self.exp, &self.modulus, &self.result])
-                        {
-                            region.assign_advice(
-                                || format!("modexp table limb row {}", offset + i),
-                                col,
-                                offset + i,
-                                || Value::known(F::from_u128(limbs[i])),
-                            )?;
-                        }
-                    }
+                let table_columns = <Self as LookupTable<F>>::advice_columns(self);
+                let all_zero_row = [(); 6].map(|_| Value::known(F::zero()));
+                let rows = once(all_zero_row).chain(
+                    events
+                        .iter()
+                        .map(|event| Self::assignments(event, challenges)),
+                );
 
-                    // native is not used by lookup (and in fact it can be omitted in dev)
-                    for (word, &col) in [
-                        &event.base,
-                        &event.exponent,
-                        &event.modulus,
-                        &event.result,
-                    ]
-                    .zip([&self.base, &self.exp, &self.modulus, &self.result])
-                    {
+                for (offset, row) in rows.enumerate() {
+                    region.assign_fixed(
+                        || format!("modexp table row {offset}"),
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    for (&column, value) in table_columns.iter().zip_eq(row) {
                         region.assign_advice(
-                            || format!("modexp table native row {}", offset + 3),
-                            col,
-                            offset + 3,
-                            || Value::<F>::known(Self::native_u256(word)),
+                            || format!("modexp table row {offset}"),
+                            column,
+                            offset,
+                            || value,
                         )?;
                     }
-
-                    offset += 4;
                 }
-
                 Ok(())
             },
-        )?;
-        self.fill_blank(layouter)
+        )
     }
 }
 
 impl<F: Field> LookupTable<F> for ModExpTable {
     fn columns(&self) -> Vec<Column<Any>> {
         vec![
-            self.q_head.into(),
+            self.q_enable.into(),
+            self.is_final.into(),
             self.base.into(),
             self.exp.into(),
+            self.exp_bit_len.into(),
             self.modulus.into(),
             self.result.into(),
         ]
@@ -2830,44 +3080,26 @@ impl<F: Field> LookupTable<F> for ModExpTable {
 
     fn annotations(&self) -> Vec<String> {
         vec![
-            String::from("is_head"),
+            String::from("q_enable"),
+            String::from("is_final"),
             String::from("base"),
             String::from("exp"),
+            String::from("exp_bit_len"),
             String::from("modulus"),
             String::from("result"),
         ]
     }
-
-    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> Vec<Expression<F>> {
-        vec![
-            // ignore the is_valid field as the EVM circuit's use-case (Ecrecover precompile) does
-            // not care whether the signature is valid or not. It only cares about the recovered
-            // address.
-            meta.query_fixed(self.q_head, Rotation::cur()),
-            meta.query_advice(self.base, Rotation::cur()),
-            meta.query_advice(self.exp, Rotation::cur()),
-            meta.query_advice(self.modulus, Rotation::cur()),
-            meta.query_advice(self.result, Rotation::cur()),
-            meta.query_advice(self.base, Rotation::next()),
-            meta.query_advice(self.exp, Rotation::next()),
-            meta.query_advice(self.modulus, Rotation::next()),
-            meta.query_advice(self.result, Rotation::next()),
-            meta.query_advice(self.base, Rotation(2)),
-            meta.query_advice(self.exp, Rotation(2)),
-            meta.query_advice(self.modulus, Rotation(2)),
-            meta.query_advice(self.result, Rotation(2)),
-        ]
-    }
 }
 
-/// Lookup table for powers of keccak randomness up to exponent in [0, 128)
+/// Lookup table for powers of keccak randomness up to the maximum input length of the
+/// precompiles
 #[derive(Clone, Copy, Debug)]
 pub struct PowOfRandTable {
     /// Whether the row is enabled.
     pub q_enable: Column<Fixed>,
     /// Whether the row is the first enabled row.
     pub is_first: Column<Fixed>,
-    /// exponent = [0, 1, 2, ..., 126, 127] for enabled rows.
+    /// exponent = [0, 1, 2, ...] for enabled rows.
     /// exponent = 0 for all other rows (disabled).
     pub exponent: Column<Fixed>,
     /// power of keccak randomness.
@@ -2916,11 +3148,12 @@ impl PowOfRandTable {
         table
     }
 
-    /// Assign values to the table.
+    /// Assign values to the table, with the powers up to the input limit of modexp.
     pub fn assign<F: Field>(
         &self,
         layouter: &mut impl Layouter<F>,
         challenges: &Challenges<Value<F>>,
+        modexp_input_limit: usize,
     ) -> Result<(), Error> {
         let r = challenges.keccak_input();
         layouter.assign_region(
@@ -2928,7 +3161,7 @@ impl PowOfRandTable {
             |mut region| {
                 let pows_of_rand =
                     std::iter::successors(Some(Value::known(F::one())), |&v| Some(v * r))
-                        .take(N_PAIRING_PER_OP * N_BYTES_PER_PAIR);
+                        .take((N_PAIRING_PER_OP * N_BYTES_PER_PAIR).max(modexp_input_limit));
 
                 for (idx, pow_of_rand) in pows_of_rand.enumerate() {
                     region.assign_fixed(
@@ -13,16 +13,20 @@ mod test;
 pub use dev::TxCircuitTester as TestTxCircuit;
 
 use crate::{
-    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    evm_circuit::{
+        param::N_BYTES_WORD,
+        util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    },
     sig_circuit::SigCircuit,
     table::{
         BlockContextFieldTag::{CumNumTxs, NumAllTxs, NumTxs},
         BlockTable, KeccakTable, LookupTable, RlpFsmRlpTable as RlpTable, SigTable, TxFieldTag,
         TxFieldTag::{
-            AccessListAddressesLen, AccessListRLC, AccessListStorageKeysLen, BlockNumber, CallData,
-            CallDataGasCost, CallDataLength, CallDataRLC, CalleeAddress, CallerAddress, ChainID,
-            Gas, GasPrice, IsCreate, Nonce, SigR, SigS, SigV, TxDataGasCost, TxHashLength,
-            TxHashRLC, TxSignHash, TxSignLength, TxSignRLC,
+            AccessListAddressesLen, AccessListRLC, AccessListStorageKeysLen, BlobVersionedHash,
+            BlockNumber, CallData, CallDataGasCost, CallDataLength, CallDataRLC, CalleeAddress,
+            CallerAddress, ChainID, Gas, GasPrice, IsCreate, MaxFeePerBlobGas, MaxFeePerGas,
+            MaxPriorityFeePerGas, Nonce, SigR, SigS, SigV, TxDataGasCost, TxHashLength, TxHashRLC,
+            TxSignHash, TxSignLength, TxSignRLC,
         },
         TxTable, U16Table, U8Table,
     },
@@ -33,7 +37,10 @@ use crate::{
     witness,
     witness::{
         rlp_fsm::{Tag, ValueTagLength},
-        Format::{L1MsgHash, TxHashEip155, TxHashPreEip155, TxSignEip155, TxSignPreEip155},
+        Format::{
+            L1MsgHash, TxHashEip155, TxHashEip4844, TxHashPreEip155, TxSignEip155, TxSignEip4844,
+            TxSignPreEip155,
+        },
         RlpTag,
         RlpTag::{GasCost, Len, Null, RLC},
         Tag::TxType as RLPTxType,
@@ -42,9 +49,10 @@ use crate::{
 };
 use bus_mapping::circuit_input_builder::keccak_inputs_sign_verify;
 use eth_types::{
+    evm_types::block_utils::MAX_BLOBS_PER_TX,
     geth_types::{
         access_list_size, TxType,
-        TxType::{Eip155, L1Msg, PreEip155},
+        TxType::{Eip155, Eip4844, L1Msg, PreEip155},
     },
     sign_types::SignData,
     Address, Field, ToAddress, ToBigEndian, ToScalar,
@@ -80,7 +88,7 @@ use halo2_proofs::plonk::SecondPhase;
 use itertools::Itertools;
 
 /// Number of rows of one tx occupies in the fixed part of tx table
-pub const TX_LEN: usize = 26;
+pub const TX_LEN: usize = 29 + MAX_BLOBS_PER_TX;
 /// Offset of TxHash tag in the tx table
 pub const TX_HASH_OFFSET: usize = 21;
 /// Offset of ChainID tag in the tx table
@@ -94,6 +102,8 @@ enum LookupCondition {
     L1MsgHash,
     RlpSignTag,
     RlpHashTag,
+    RlpSignPos,
+    RlpHashPos,
     // lookup into keccak table
     Keccak,
 }
@@ -145,9 +155,20 @@ pub struct TxCircuitConfig<F: Field> {
     is_calldata: Column<Advice>,
     is_caller_address: Column<Advice>,
     is_l1_msg: Column<Advice>,
+    is_eip4844: Column<Advice>,
     is_chain_id: Column<Advice>,
+    is_blob_hash: Column<Advice>,
     lookup_conditions: HashMap<LookupCondition, Column<Advice>>,
 
+    /// Columns for binding the positions of the blob hashes in the RLP encodings of a blob tx.
+    /// The index of the RLP byte at which the tag is decoded, for signing and hashing.
+    rlp_sign_byte_idx: Column<Advice>,
+    rlp_hash_byte_idx: Column<Advice>,
+    /// Length of the RLP encoding for signing
+    rlp_sign_len: Column<Advice>,
+    /// The index of the RLP byte at which SigV is decoded, for hashing
+    sig_v_byte_idx: Column<Advice>,
+
     /// Columns for computing num_all_txs
     tx_nonce: Column<Advice>,
     block_num: Column<Advice>,
@@ -279,21 +300,31 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
         // booleans to reduce degree
         let is_l1_msg = meta.advice_column();
+        let is_eip4844 = meta.advice_column();
         let is_calldata = meta.advice_column();
         let is_caller_address = meta.advice_column();
         let is_chain_id = meta.advice_column();
+        let is_blob_hash = meta.advice_column();
         let is_tag_block_num = meta.advice_column();
         let lookup_conditions = [
             LookupCondition::TxCalldata,
             LookupCondition::L1MsgHash,
             LookupCondition::RlpSignTag,
             LookupCondition::RlpHashTag,
+            LookupCondition::RlpSignPos,
+            LookupCondition::RlpHashPos,
             LookupCondition::Keccak,
         ]
         .into_iter()
         .map(|condition| (condition, meta.advice_column()))
         .collect::<HashMap<LookupCondition, Column<Advice>>>();
 
+        // columns for binding the positions of blob hashes
+        let rlp_sign_byte_idx = meta.advice_column();
+        let rlp_hash_byte_idx = meta.advice_column();
+        let rlp_sign_len = meta.advice_column();
+        let sig_v_byte_idx = meta.advice_column();
+
         // TODO: add lookup to SignVerify table for sv_address
         let sv_address = meta.advice_column();
         meta.enable_equality(tx_table.value);
@@ -349,6 +380,10 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         is_tx_tag!(is_access_list_addresses_len, AccessListAddressesLen);
         is_tx_tag!(is_access_list_storage_keys_len, AccessListStorageKeysLen);
         is_tx_tag!(is_access_list_rlc, AccessListRLC);
+        is_tx_tag!(is_max_fee_per_gas, MaxFeePerGas);
+        is_tx_tag!(is_max_priority_fee_per_gas, MaxPriorityFeePerGas);
+        is_tx_tag!(is_max_fee_per_blob_gas, MaxFeePerBlobGas);
+        is_tx_tag!(is_blob_hash_expr, BlobVersionedHash);
 
         let tx_id_unchanged = IsEqualChip::configure(
             meta,
@@ -370,6 +405,8 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                         is_data_length(meta),
                         // if call data byte is zero, then gas_cost = 4 (16 otherwise)
                         is_data(meta),
+                        // if blob versioned hash is zero, then it's a padding blob hash row
+                        meta.query_advice(is_blob_hash, Rotation::cur()),
                     ]),
                 ])
             },
@@ -422,11 +459,16 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                         ("num_txs", num_txs),
                         ("cum_num_txs", cum_num_txs),
                         ("num_all_txs_acc", num_all_txs_acc),
-                        // is_l1_msg does not need to spread out as it's extracted from tx_type
-
-                        // these do not need to spread out as they are related to tx_table.tag
-                        // (which is fixed col) is_chain_id,
-                        // is_caller_address, is_tag_block_num, is_calldata
+                        ("rlp_sign_len", rlp_sign_len), // extracted at TxSignLength row
+                        ("sig_v_byte_idx", sig_v_byte_idx), /* extracted at SigV row
+                                                         * is_l1_msg does not need to spread out
+                                                         * as it's extracted from tx_type */
+
+                                                        /* these do not need to spread out as
+                                                         * they are related to tx_table.tag
+                                                         * (which is fixed col) is_chain_id,
+                                                         * is_caller_address, is_tag_block_num,
+                                                         * is_calldata */
                     ];
                     for (col_name, meta_info) in tx_meta_info_fields {
                         cb.require_equal(
@@ -479,6 +521,13 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 (is_access_list_addresses_len(meta), Null),
                 (is_access_list_storage_keys_len(meta), Null),
                 (is_access_list_rlc(meta), RLC),
+                (is_max_fee_per_gas(meta), Tag::MaxFeePerGas.into()),
+                (
+                    is_max_priority_fee_per_gas(meta),
+                    Tag::MaxPriorityFeePerGas.into(),
+                ),
+                (is_max_fee_per_blob_gas(meta), Tag::MaxFeePerBlobGas.into()),
+                (is_blob_hash_expr(meta), Tag::BlobVersionedHash.into()),
             ];
 
             cb.require_boolean(
@@ -493,6 +542,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                     usize::from(PreEip155).expr(),
                     usize::from(Eip155).expr(),
                     usize::from(L1Msg).expr(),
+                    usize::from(Eip4844).expr(),
                 ],
             );
 
@@ -567,6 +617,158 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         // TODO: add constraints for AccessListAddressesLen, AccessListStorageKeysLen
         // and AccessListRLC.
 
+        meta.create_gate("blob fields", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_blob_hash_cur = meta.query_advice(is_blob_hash, Rotation::cur());
+            let is_blob_hash_next = meta.query_advice(is_blob_hash, Rotation::next());
+
+            // the fee fields of EIP-1559 and the blob fields are zero for non-blob txs
+            cb.condition(
+                and::expr([
+                    sum::expr([
+                        is_max_fee_per_gas(meta),
+                        is_max_priority_fee_per_gas(meta),
+                        is_max_fee_per_blob_gas(meta),
+                        is_blob_hash_cur.expr(),
+                    ]),
+                    not::expr(meta.query_advice(is_eip4844, Rotation::cur())),
+                ]),
+                |cb| {
+                    cb.require_zero(
+                        "blob field == 0 for non-blob tx",
+                        meta.query_advice(tx_table.value, Rotation::cur()),
+                    );
+                },
+            );
+
+            // we rely on the assumption that the BlobVersionedHash rows are next to
+            // MaxFeePerBlobGas
+            cb.condition(is_max_fee_per_blob_gas(meta), |cb| {
+                cb.require_zero(
+                    "index of 1st blob hash == 0",
+                    meta.query_advice(tx_table.index, Rotation::next()),
+                );
+            });
+
+            cb.condition(and::expr([is_blob_hash_cur, is_blob_hash_next]), |cb| {
+                cb.require_equal(
+                    "index of blob hash increments",
+                    meta.query_advice(tx_table.index, Rotation::next()),
+                    meta.query_advice(tx_table.index, Rotation::cur()) + 1.expr(),
+                );
+                // the non-zero blob hashes are followed by the zero ones
+                cb.condition(value_is_zero.expr(Rotation::cur())(meta), |cb| {
+                    cb.require_zero(
+                        "blob hash' == 0 if blob hash == 0",
+                        meta.query_advice(tx_table.value, Rotation::next()),
+                    );
+                });
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        // The RLP lookups of the blob hashes only prove that each hash is in the blob list.
+        // Together with the lookups of their positions, the constraints below make the
+        // non-zero blob hashes the whole blob list in the same order. The RLP circuit checks
+        // the version byte of each hash.
+        meta.create_gate("positions of blob hashes in RLP", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_next_hash_non_zero = and::expr([
+                meta.query_advice(is_blob_hash, Rotation::next()),
+                not::expr(value_is_zero.expr(Rotation::next())(meta)),
+            ]);
+            // rlp(blob_hash) = 0xa0 || blob_hash
+            let blob_hash_rlp_len = (N_BYTES_WORD + 1).expr();
+
+            // we rely on the assumption that the BlobVersionedHash rows are next to
+            // MaxFeePerBlobGas
+            cb.condition(is_max_fee_per_blob_gas(meta), |cb| {
+                cb.require_zero(
+                    "number of blobs >= 1",
+                    value_is_zero.expr(Rotation::next())(meta),
+                );
+                // the header of the blob list takes 1 byte for a single blob and 2 bytes
+                // otherwise, as MAX_BLOBS_PER_TX * 33 < 256
+                let list_header_len = 2.expr() - value_is_zero.expr(Rotation(2))(meta);
+                for byte_idx in [rlp_sign_byte_idx, rlp_hash_byte_idx] {
+                    cb.require_equal(
+                        "byte_idx of 1st blob hash",
+                        meta.query_advice(byte_idx, Rotation::next()),
+                        meta.query_advice(byte_idx, Rotation::cur())
+                            + list_header_len.expr()
+                            + blob_hash_rlp_len.expr(),
+                    );
+                }
+            });
+
+            cb.condition(
+                and::expr([
+                    meta.query_advice(is_blob_hash, Rotation::cur()),
+                    is_next_hash_non_zero.expr(),
+                ]),
+                |cb| {
+                    for byte_idx in [rlp_sign_byte_idx, rlp_hash_byte_idx] {
+                        cb.require_equal(
+                            "byte_idx of blob hash' == byte_idx of blob hash + 33",
+                            meta.query_advice(byte_idx, Rotation::next()),
+                            meta.query_advice(byte_idx, Rotation::cur()) + blob_hash_rlp_len.expr(),
+                        );
+                    }
+                },
+            );
+
+            // the blob list is the last field of the RLP encoding for signing and it's
+            // followed by the signature in the RLP encoding for hashing
+            cb.condition(
+                and::expr([
+                    meta.query_advice(is_blob_hash, Rotation::cur()),
+                    not::expr(value_is_zero.expr(Rotation::cur())(meta)),
+                    not::expr(is_next_hash_non_zero),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "byte_idx of last blob hash == tx sign length",
+                        meta.query_advice(rlp_sign_byte_idx, Rotation::cur()),
+                        meta.query_advice(rlp_sign_len, Rotation::cur()),
+                    );
+                    cb.require_equal(
+                        "byte_idx of SigV == byte_idx of last blob hash + 1",
+                        meta.query_advice(sig_v_byte_idx, Rotation::cur()),
+                        meta.query_advice(rlp_hash_byte_idx, Rotation::cur()) + 1.expr(),
+                    );
+                },
+            );
+
+            cb.gate(and::expr([
+                meta.query_fixed(q_enable, Rotation::cur()),
+                meta.query_advice(is_eip4844, Rotation::cur()),
+            ]))
+        });
+
+        meta.create_gate("copy rlp_sign_len and sig_v_byte_idx", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.condition(is_sign_length(meta), |cb| {
+                cb.require_equal(
+                    "rlp_sign_len = tx_table.value if tag == TxSignLength",
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                    meta.query_advice(rlp_sign_len, Rotation::cur()),
+                );
+            });
+            cb.condition(is_sig_v(meta), |cb| {
+                cb.require_equal(
+                    "sig_v_byte_idx = rlp_hash_byte_idx if tag == SigV",
+                    meta.query_advice(rlp_hash_byte_idx, Rotation::cur()),
+                    meta.query_advice(sig_v_byte_idx, Rotation::cur()),
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
         //////////////////////////////////////////////////////////
         ///// Constraints for booleans that reducing degree  /////
         //////////////////////////////////////////////////////////
@@ -606,6 +808,18 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
 
+        meta.create_gate("is_blob_hash", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "is_blob_hash = (tag == BlobVersionedHash)",
+                is_blob_hash_expr(meta),
+                meta.query_advice(is_blob_hash, Rotation::cur()),
+            );
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
         meta.create_gate("is_tag_block_num", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -630,6 +844,18 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
 
+        meta.create_gate("is_eip4844", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "is_eip4844 = (tx_type == Eip4844)",
+                meta.query_advice(is_eip4844, Rotation::cur()),
+                tx_type_bits.value_equals(Eip4844, Rotation::cur())(meta),
+            );
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
         meta.create_gate("calldata lookup into tx table condition", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -651,16 +877,34 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         meta.create_gate("sign tag lookup into RLP table condition", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
+            let is_eip4844 = meta.query_advice(is_eip4844, Rotation::cur());
             let is_tag_in_tx_sign = sum::expr([
                 is_nonce(meta),
-                is_gas_price(meta),
+                // blob tx does not have gas_price in its RLP encoding
+                and::expr([is_gas_price(meta), not::expr(is_eip4844.expr())]),
                 is_gas(meta),
                 is_to(meta),
                 is_value(meta),
                 is_data_rlc(meta),
                 and::expr([
                     meta.query_advice(is_chain_id, Rotation::cur()),
-                    tx_type_bits.value_equals(Eip155, Rotation::cur())(meta),
+                    sum::expr([
+                        tx_type_bits.value_equals(Eip155, Rotation::cur())(meta),
+                        is_eip4844.expr(),
+                    ]),
+                ]),
+                and::expr([
+                    sum::expr([
+                        is_max_fee_per_gas(meta),
+                        is_max_priority_fee_per_gas(meta),
+                        is_max_fee_per_blob_gas(meta),
+                    ]),
+                    is_eip4844.expr(),
+                ]),
+                and::expr([
+                    meta.query_advice(is_blob_hash, Rotation::cur()),
+                    is_eip4844.expr(),
+                    not::expr(value_is_zero.expr(Rotation::cur())(meta)),
                 ]),
                 is_sign_length(meta),
                 is_sign_rlc(meta),
@@ -684,14 +928,29 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         meta.create_gate("hash tag lookup into RLP table condition", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
+            let is_eip4844 = meta.query_advice(is_eip4844, Rotation::cur());
             let is_tag_in_tx_hash = sum::expr([
                 is_nonce(meta),
-                is_gas_price(meta),
+                // blob tx does not have gas_price in its RLP encoding
+                and::expr([is_gas_price(meta), not::expr(is_eip4844.expr())]),
                 is_gas(meta),
                 is_to(meta),
                 is_value(meta),
                 is_tx_gas_cost(meta),
                 is_data_rlc(meta),
+                and::expr([
+                    sum::expr([
+                        is_max_fee_per_gas(meta),
+                        is_max_priority_fee_per_gas(meta),
+                        is_max_fee_per_blob_gas(meta),
+                    ]),
+                    is_eip4844.expr(),
+                ]),
+                and::expr([
+                    meta.query_advice(is_blob_hash, Rotation::cur()),
+                    is_eip4844.expr(),
+                    not::expr(value_is_zero.expr(Rotation::cur())(meta)),
+                ]),
                 is_sig_v(meta),
                 is_sig_r(meta),
                 is_sig_s(meta),
@@ -714,6 +973,38 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             ]))
         });
 
+        meta.create_gate("position lookup into RLP table condition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_eip4844 = meta.query_advice(is_eip4844, Rotation::cur());
+            let is_blob_field = sum::expr([
+                is_max_fee_per_blob_gas(meta),
+                and::expr([
+                    meta.query_advice(is_blob_hash, Rotation::cur()),
+                    not::expr(value_is_zero.expr(Rotation::cur())(meta)),
+                ]),
+            ]);
+
+            cb.require_equal(
+                "sign condition",
+                is_eip4844.expr() * is_blob_field.expr(),
+                meta.query_advice(
+                    lookup_conditions[&LookupCondition::RlpSignPos],
+                    Rotation::cur(),
+                ),
+            );
+            cb.require_equal(
+                "hash condition",
+                is_eip4844 * (is_blob_field + is_sig_v(meta)),
+                meta.query_advice(
+                    lookup_conditions[&LookupCondition::RlpHashPos],
+                    Rotation::cur(),
+                ),
+            );
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
         meta.create_gate("l1 msg lookup into RLP table condition", |meta| {
             let mut cb = BaseConstraintBuilder::default();
             let is_tag_in_l1_msg_hash = sum::expr([
@@ -768,6 +1059,8 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             rlp_tag,
             tx_value_rlc,
             tx_value_length,
+            rlp_sign_byte_idx,
+            rlp_hash_byte_idx,
             tx_type_bits,
             tx_id_is_zero.clone(),
             is_none,
@@ -1313,9 +1606,21 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 },
             );
 
+            //  4. eip4844 tx: v Є {0, 1}
+            cb.condition(
+                and::expr([
+                    is_chain_id.expr(),
+                    meta.query_advice(is_eip4844, Rotation::cur()),
+                ]),
+                |cb| {
+                    let v = meta.query_advice(tx_table.value, Rotation::next());
+                    cb.require_boolean("V Є {0, 1}", v);
+                },
+            );
+
             // TODO:
-            //  4. eip1559 tx: v Є {0, 1}
-            //  5. eip2930 tx: v Є {0, 1}
+            //  5. eip1559 tx: v Є {0, 1}
+            //  6. eip2930 tx: v Є {0, 1}
 
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
@@ -1367,13 +1672,19 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             cum_num_txs,
             is_padding_tx,
             lookup_conditions,
+            rlp_sign_byte_idx,
+            rlp_hash_byte_idx,
+            rlp_sign_len,
+            sig_v_byte_idx,
             tx_nonce,
             block_num,
             block_num_unchanged,
             num_all_txs_acc,
             total_l1_popped_before,
             is_l1_msg,
+            is_eip4844,
             is_chain_id,
+            is_blob_hash,
             is_final,
             calldata_gas_cost_acc,
             calldata_rlc,
@@ -1399,6 +1710,8 @@ impl<F: Field> TxCircuitConfig<F> {
         rlp_tag: Column<Advice>,
         tx_value_rlc: Column<Advice>,
         tx_value_length: Column<Advice>,
+        rlp_sign_byte_idx: Column<Advice>,
+        rlp_hash_byte_idx: Column<Advice>,
         tx_type_bits: BinaryNumberConfig<TxType, 3>,
         tx_id_is_zero: IsZeroConfig<F>,
         is_none: Column<Advice>,
@@ -1526,6 +1839,7 @@ impl<F: Field> TxCircuitConfig<F> {
         is_tx_type!(is_pre_eip155, PreEip155);
         is_tx_type!(is_eip155, Eip155);
         is_tx_type!(is_l1_msg, L1Msg);
+        is_tx_type!(is_eip4844, Eip4844);
 
         // lookup tx type in RLP table for L1Msg only
         meta.lookup_any("lookup tx type in RLP table", |meta| {
@@ -1567,7 +1881,8 @@ impl<F: Field> TxCircuitConfig<F> {
             let rlp_tag = meta.query_advice(rlp_tag, Rotation::cur());
             let is_none = meta.query_advice(is_none, Rotation::cur());
             let sign_format = is_pre_eip155(meta) * TxSignPreEip155.expr()
-                + is_eip155(meta) * TxSignEip155.expr();
+                + is_eip155(meta) * TxSignEip155.expr()
+                + is_eip4844(meta) * TxSignEip4844.expr();
 
             // q_enable, tx_id, format, rlp_tag, tag_value, is_output, is_none
             vec![
@@ -1606,6 +1921,7 @@ impl<F: Field> TxCircuitConfig<F> {
             let is_none = meta.query_advice(is_none, Rotation::cur());
             let hash_format = is_pre_eip155(meta) * TxHashPreEip155.expr()
                 + is_eip155(meta) * TxHashEip155.expr()
+                + is_eip4844(meta) * TxHashEip4844.expr()
                 + is_l1_msg(meta) * L1MsgHash.expr();
 
             vec![
@@ -1625,6 +1941,65 @@ impl<F: Field> TxCircuitConfig<F> {
             .collect()
         });
 
+        // lookup the positions of the blob fields (and SigV) of blob txs in RLP table
+        for (name, condition, format, byte_idx) in [
+            (
+                "lookup position of tx tag in RLP Table for signing",
+                LookupCondition::RlpSignPos,
+                TxSignEip4844,
+                rlp_sign_byte_idx,
+            ),
+            (
+                "lookup position of tx tag in RLP Table for hashing",
+                LookupCondition::RlpHashPos,
+                TxHashEip4844,
+                rlp_hash_byte_idx,
+            ),
+        ] {
+            meta.lookup_any(name, |meta| {
+                let enable = and::expr([
+                    meta.query_fixed(q_enable, Rotation::cur()),
+                    meta.query_advice(lookup_conditions[&condition], Rotation::cur()),
+                ]);
+
+                // RlpTable::table_exprs is not used here since `byte_idx` is not one of the
+                // lookup columns of RLP table.
+                vec![
+                    (
+                        1.expr(), // q_enable = true
+                        meta.query_fixed(rlp_table.q_enable, Rotation::cur()),
+                    ),
+                    (
+                        meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                        meta.query_advice(rlp_table.tx_id, Rotation::cur()),
+                    ),
+                    (
+                        format.expr(),
+                        meta.query_advice(rlp_table.format, Rotation::cur()),
+                    ),
+                    (
+                        meta.query_advice(rlp_tag, Rotation::cur()),
+                        meta.query_advice(rlp_table.rlp_tag, Rotation::cur()),
+                    ),
+                    (
+                        meta.query_advice(tx_table.value, Rotation::cur()),
+                        meta.query_advice(rlp_table.tag_value, Rotation::cur()),
+                    ),
+                    (
+                        1.expr(), // is_output = true
+                        meta.query_advice(rlp_table.is_output, Rotation::cur()),
+                    ),
+                    (
+                        meta.query_advice(byte_idx, Rotation::cur()),
+                        meta.query_advice(rlp_table.byte_idx, Rotation::cur()),
+                    ),
+                ]
+                .into_iter()
+                .map(|(arg, table)| (enable.expr() * arg, table))
+                .collect()
+            });
+        }
+
         ////////////////////////////////////////////////////////////////////
         /////////////////    Sig table lookups     //////////////////////
         ///////////////// //////////////////////////////////////////////////
@@ -1646,7 +2021,8 @@ impl<F: Field> TxCircuitConfig<F> {
             let sv_address = meta.query_advice(sv_address, Rotation::cur());
 
             let v = is_eip155(meta) * (sig_v.expr() - 2.expr() * chain_id - 35.expr())
-                + is_pre_eip155(meta) * (sig_v.expr() - 27.expr());
+                + is_pre_eip155(meta) * (sig_v.expr() - 27.expr())
+                + is_eip4844(meta) * sig_v.expr();
 
             let input_exprs = vec![
                 1.expr(),     // q_enable = true
@@ -1752,9 +2128,22 @@ impl<F: Field> TxCircuitConfig<F> {
         };
         let (access_list_address_size, access_list_storage_key_size) =
             access_list_size(&tx.access_list);
+        // positions of the RLP tags in the RLP encodings of a blob tx
+        let (rlp_sign_byte_idx, rlp_hash_byte_idx) = if tx.tx_type.is_eip4844_tx() {
+            (
+                tx.rlp_tag_byte_idx(false, challenges),
+                tx.rlp_tag_byte_idx(true, challenges),
+            )
+        } else {
+            (vec![], vec![])
+        };
+        let sig_v_byte_idx = rlp_hash_byte_idx
+            .iter()
+            .find(|(tag, _)| *tag == Tag::SigV.into())
+            .map_or(0, |(_, byte_idx)| *byte_idx);
 
         // fixed_rows of a tx
-        let fixed_rows = vec![
+        let mut fixed_rows = vec![
             // need to be in same order as that tx table load function uses
             (
                 Nonce, // tx field tag
@@ -1971,9 +2360,64 @@ impl<F: Field> TxCircuitConfig<F> {
                     keccak_input,
                 ),
             ),
-            (BlockNumber, None, Value::known(F::from(tx.block_number))),
+            (
+                MaxFeePerGas,
+                Some(RlpTableInputValue {
+                    tag: Tag::MaxFeePerGas.into(),
+                    is_none: tx.max_fee_per_gas.is_zero(),
+                    be_bytes_len: tx.max_fee_per_gas.tag_length(),
+                    be_bytes_rlc: rlc_be_bytes(&tx.max_fee_per_gas.to_be_bytes(), keccak_input),
+                }),
+                rlc_be_bytes(&tx.max_fee_per_gas.to_be_bytes(), evm_word),
+            ),
+            (
+                MaxPriorityFeePerGas,
+                Some(RlpTableInputValue {
+                    tag: Tag::MaxPriorityFeePerGas.into(),
+                    is_none: tx.max_priority_fee_per_gas.is_zero(),
+                    be_bytes_len: tx.max_priority_fee_per_gas.tag_length(),
+                    be_bytes_rlc: rlc_be_bytes(
+                        &tx.max_priority_fee_per_gas.to_be_bytes(),
+                        keccak_input,
+                    ),
+                }),
+                rlc_be_bytes(&tx.max_priority_fee_per_gas.to_be_bytes(), evm_word),
+            ),
+            (
+                MaxFeePerBlobGas,
+                Some(RlpTableInputValue {
+                    tag: Tag::MaxFeePerBlobGas.into(),
+                    is_none: tx.max_fee_per_blob_gas.is_zero(),
+                    be_bytes_len: tx.max_fee_per_blob_gas.tag_length(),
+                    be_bytes_rlc: rlc_be_bytes(
+                        &tx.max_fee_per_blob_gas.to_be_bytes(),
+                        keccak_input,
+                    ),
+                }),
+                rlc_be_bytes(&tx.max_fee_per_blob_gas.to_be_bytes(), evm_word),
+            ),
         ];
+        // blob versioned hashes are padded with zeros to MAX_BLOBS_PER_TX rows
+        fixed_rows.extend((0..MAX_BLOBS_PER_TX).map(|idx| {
+            let blob_hash = tx.blob_versioned_hashes.get(idx);
+            (
+                BlobVersionedHash,
+                Some(RlpTableInputValue {
+                    tag: Tag::BlobVersionedHash.into(),
+                    is_none: blob_hash.is_none(),
+                    be_bytes_len: blob_hash.map_or(0, |_| 32),
+                    be_bytes_rlc: blob_hash.map_or(zero_rlc, |hash| {
+                        rlc_be_bytes(&hash.to_fixed_bytes(), keccak_input)
+                    }),
+                }),
+                blob_hash.map_or(Value::known(F::zero()), |hash| {
+                    rlc_be_bytes(&hash.to_fixed_bytes(), evm_word)
+                }),
+            )
+        }));
+        fixed_rows.push((BlockNumber, None, Value::known(F::from(tx.block_number))));
 
+        let mut blob_hash_idx = 0;
         for (tx_tag, rlp_input, tx_value) in fixed_rows {
             let rlp_tag = rlp_input.clone().map_or(Null, |input| input.tag);
             let rlp_is_none = rlp_input.clone().map_or(false, |input| input.is_none);
@@ -1982,6 +2426,31 @@ impl<F: Field> TxCircuitConfig<F> {
                 .clone()
                 .map_or(zero_rlc, |input| input.be_bytes_rlc);
             let is_l1_msg = tx.tx_type.is_l1_msg();
+            let is_eip4844 = tx.tx_type.is_eip4844_tx();
+            // blob hashes are indexed by their position in the blob list
+            let index = if tx_tag == BlobVersionedHash {
+                blob_hash_idx += 1;
+                blob_hash_idx - 1
+            } else {
+                0
+            };
+            // the position of the row's tag, only used for the blob fields and SigV of blob txs
+            let (sign_byte_idx, hash_byte_idx) =
+                if matches!(tx_tag, MaxFeePerBlobGas | BlobVersionedHash | SigV) {
+                    let nth_byte_idx = |byte_idx: &[(RlpTag, usize)]| {
+                        byte_idx
+                            .iter()
+                            .filter(|(tag, _)| *tag == rlp_tag)
+                            .nth(index as usize)
+                            .map_or(0, |(_, byte_idx)| *byte_idx)
+                    };
+                    (
+                        nth_byte_idx(&rlp_sign_byte_idx),
+                        nth_byte_idx(&rlp_hash_byte_idx),
+                    )
+                } else {
+                    (0, 0)
+                };
             // it's the tx_id of next row
             let tx_id_next = if tx_tag == BlockNumber {
                 next_tx.map_or(0, |tx| tx.id)
@@ -1995,7 +2464,7 @@ impl<F: Field> TxCircuitConfig<F> {
                 Some(tx),
                 tx_id_next,
                 tx_tag,
-                0,
+                index,
                 tx_value,
             )?);
 
@@ -2028,6 +2497,27 @@ impl<F: Field> TxCircuitConfig<F> {
                 ),
                 ("num_txs", self.num_txs, F::from(num_txs)),
                 ("cum_num_txs", self.cum_num_txs, F::from(cum_num_txs)),
+                // positions of blob hashes related assignment
+                (
+                    "rlp_sign_byte_idx",
+                    self.rlp_sign_byte_idx,
+                    F::from(sign_byte_idx as u64),
+                ),
+                (
+                    "rlp_hash_byte_idx",
+                    self.rlp_hash_byte_idx,
+                    F::from(hash_byte_idx as u64),
+                ),
+                (
+                    "rlp_sign_len",
+                    self.rlp_sign_len,
+                    F::from(tx.rlp_unsigned.len() as u64),
+                ),
+                (
+                    "sig_v_byte_idx",
+                    self.sig_v_byte_idx,
+                    F::from(sig_v_byte_idx as u64),
+                ),
                 // tx meta info
                 (
                     "is_padding_tx",
@@ -2060,6 +2550,11 @@ impl<F: Field> TxCircuitConfig<F> {
                     self.is_caller_address,
                     F::from((tx_tag == CallerAddress) as u64),
                 ),
+                (
+                    "is_tag_blob_hash",
+                    self.is_blob_hash,
+                    F::from((tx_tag == BlobVersionedHash) as u64),
+                ),
             ] {
                 region.assign_advice(|| col_anno, col, *offset, || Value::known(col_val))?;
             }
@@ -2083,6 +2578,13 @@ impl<F: Field> TxCircuitConfig<F> {
                 }
             });
             // 2. lookup to RLP table for signing (non L1 msg)
+            // whether the tag is part of the RLP encoding of this tx type
+            let is_tag_in_rlp = match tx_tag {
+                GasPrice => !is_eip4844,
+                MaxFeePerGas | MaxPriorityFeePerGas | MaxFeePerBlobGas => is_eip4844,
+                BlobVersionedHash => is_eip4844 && index < tx.blob_versioned_hashes.len() as u64,
+                _ => true,
+            };
             conditions.insert(LookupCondition::RlpSignTag, {
                 let sign_set = [
                     Nonce,
@@ -2091,12 +2593,16 @@ impl<F: Field> TxCircuitConfig<F> {
                     CalleeAddress,
                     TxFieldTag::Value,
                     CallDataRLC,
+                    MaxFeePerGas,
+                    MaxPriorityFeePerGas,
+                    MaxFeePerBlobGas,
+                    BlobVersionedHash,
                     TxSignLength,
                     TxSignRLC,
                 ];
                 let is_tag_in_set = sign_set.into_iter().filter(|tag| tx_tag == *tag).count() == 1;
-                let case1 = is_tag_in_set && !is_l1_msg;
-                let case2 = tx.tx_type.is_eip155_tx() && (tx_tag == ChainID);
+                let case1 = is_tag_in_set && !is_l1_msg && is_tag_in_rlp;
+                let case2 = (tx.tx_type.is_eip155_tx() || is_eip4844) && (tx_tag == ChainID);
                 F::from((case1 || case2) as u64)
             });
             // 3. lookup to RLP table for hashing (non L1 msg)
@@ -2109,6 +2615,10 @@ impl<F: Field> TxCircuitConfig<F> {
                     TxFieldTag::Value,
                     CallDataRLC,
                     TxDataGasCost,
+                    MaxFeePerGas,
+                    MaxPriorityFeePerGas,
+                    MaxFeePerBlobGas,
+                    BlobVersionedHash,
                     SigV,
                     SigR,
                     SigS,
@@ -2116,9 +2626,23 @@ impl<F: Field> TxCircuitConfig<F> {
                     TxHashRLC,
                 ];
                 let is_tag_in_set = hash_set.into_iter().filter(|tag| tx_tag == *tag).count() == 1;
-                F::from((!is_l1_msg && is_tag_in_set) as u64)
+                F::from((!is_l1_msg && is_tag_in_set && is_tag_in_rlp) as u64)
             });
-            // 4. lookup to RLP table for hashing (L1 msg)
+            // 4. lookup positions of the blob fields (and SigV) to RLP table (blob tx)
+            let is_blob_field = match tx_tag {
+                MaxFeePerBlobGas => true,
+                BlobVersionedHash => index < tx.blob_versioned_hashes.len() as u64,
+                _ => false,
+            };
+            conditions.insert(
+                LookupCondition::RlpSignPos,
+                F::from((is_eip4844 && is_blob_field) as u64),
+            );
+            conditions.insert(
+                LookupCondition::RlpHashPos,
+                F::from((is_eip4844 && (is_blob_field || tx_tag == SigV)) as u64),
+            );
+            // 5. lookup to RLP table for hashing (L1 msg)
             conditions.insert(LookupCondition::L1MsgHash, {
                 let hash_set = [
                     Nonce,
@@ -2134,7 +2658,7 @@ impl<F: Field> TxCircuitConfig<F> {
                 let is_tag_in_set = hash_set.into_iter().filter(|tag| tx_tag == *tag).count() == 1;
                 F::from((is_l1_msg && is_tag_in_set) as u64)
             });
-            // 5. lookup to Keccak table for tx_sign_hash and tx_hash
+            // 6. lookup to Keccak table for tx_sign_hash and tx_hash
             conditions.insert(LookupCondition::Keccak, {
                 let case1 = (tx_tag == TxSignLength) && !is_l1_msg;
                 let case2 = tx_tag == TxHashLength;
@@ -2301,6 +2825,11 @@ impl<F: Field> TxCircuitConfig<F> {
                 self.is_l1_msg,
                 F::from(tx_type.is_l1_msg() as u64),
             ),
+            (
+                "is_eip4844",
+                self.is_eip4844,
+                F::from(tx_type.is_eip4844_tx() as u64),
+            ),
         ] {
             region.assign_advice(|| col_anno, col, offset, || Value::known(col_val))?;
         }
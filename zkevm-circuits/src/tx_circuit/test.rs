@@ -108,6 +108,33 @@ fn build_l1_msg_tx() -> Transaction {
     tx
 }
 
+#[cfg(test)]
+fn build_eip4844_tx() -> Transaction {
+    let blob_versioned_hashes = (1..=2u8)
+        .map(|i| {
+            let mut hash = [i; 32];
+            hash[0] = 0x01;
+            H256(hash)
+        })
+        .collect();
+
+    MockTransaction::default()
+        .transaction_idx(1)
+        .from(mock::MOCK_WALLETS[0].clone())
+        .to(mock::MOCK_ACCOUNTS[0])
+        .nonce(word!("0x1"))
+        .value(word!("0x3e8"))
+        .gas(word!("0x5208"))
+        .transaction_type(3)
+        .max_priority_fee_per_gas(word!("0x3b9aca00"))
+        .max_fee_per_gas(word!("0x77359400"))
+        .gas_price(word!("0x77359400"))
+        .max_fee_per_blob_gas(word!("0x3b9aca00"))
+        .blob_versioned_hashes(blob_versioned_hashes)
+        .build()
+        .into()
+}
+
 fn run<F: Field>(
     txs: Vec<Transaction>,
     chain_id: u64,
@@ -204,6 +231,20 @@ fn tx_circuit_1tx_2max_tx() {
     );
 }
 
+#[test]
+#[cfg(feature = "scroll")]
+fn tx_circuit_eip4844_tx() {
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 32;
+
+    let tx = build_eip4844_tx();
+
+    assert_eq!(
+        run::<Fr>(vec![tx], *mock::MOCK_CHAIN_ID, MAX_TXS, MAX_CALLDATA, 0),
+        Ok(())
+    );
+}
+
 #[test]
 #[cfg(feature = "scroll")]
 fn tx_circuit_l1_msg_tx() {
@@ -35,4 +35,5 @@ pub use step::ExecStep;
 mod l1_msg;
 mod tx;
 
+pub(crate) use tx::tx_convert;
 pub use tx::Transaction;
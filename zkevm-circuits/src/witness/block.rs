@@ -11,14 +11,19 @@ use crate::{
 };
 use bus_mapping::{
     circuit_input_builder::{
-        self, BigModExp, CircuitsParams, CopyEvent, EcAddOp, EcMulOp, EcPairingOp, ExpEvent,
-        PrecompileEvents, SHA256,
+        self, BigModExp, Blake2F, CircuitsParams, CopyEvent, EcAddOp, EcMulOp, EcPairingOp,
+        ExpEvent, PrecompileEvents, Ripemd160, SHA256,
     },
     Error,
 };
-use eth_types::{sign_types::SignData, Address, Field, ToLittleEndian, ToScalar, Word, U256};
+use eth_types::{
+    evm_types::{ForkRules, ForkSchedule},
+    sign_types::SignData,
+    Address, Field, ToLittleEndian, ToScalar, Word, U256,
+};
 use halo2_proofs::circuit::Value;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
 use super::{
     mpt::ZktrieState as MptState, step::step_convert, tx::tx_convert, Bytecode, ExecStep,
@@ -29,7 +34,7 @@ use crate::util::Challenges;
 // TODO: Remove fields that are duplicated in`eth_block`
 /// Block is the struct used by all circuits, which contains all the needed
 /// data for witness generation.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Block<F> {
     /// For historical reasons..
     pub _marker: std::marker::PhantomData<F>,
@@ -72,12 +77,14 @@ pub struct Block<F> {
     pub chain_id: u64,
     /// StartL1QueueIndex
     pub start_l1_queue_index: u64,
+    /// Hard fork schedule deciding the EVM rules of each block
+    pub fork_schedule: ForkSchedule,
     /// IO to/from precompile calls.
     pub precompile_events: PrecompileEvents,
 }
 
 /// ...
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct BlockContexts {
     /// Hashmap that maps block number to its block context.
     pub ctxs: BTreeMap<u64, BlockContext>,
@@ -100,6 +107,13 @@ impl<F: Field> Block<F> {
         }
     }
 
+    /// Return the rules in effect for the block with the given number.
+    pub(crate) fn fork_rules(&self, block_number: u64) -> ForkRules {
+        let context = &self.context.ctxs[&block_number];
+        self.fork_schedule
+            .rules(context.number.low_u64(), context.timestamp.low_u64())
+    }
+
     /// Get signature (witness) from the block for tx signatures and ecRecover calls.
     pub(crate) fn get_sign_data(&self, padding: bool) -> Vec<SignData> {
         let mut signatures: Vec<SignData> = self
@@ -143,6 +157,16 @@ impl<F: Field> Block<F> {
         self.precompile_events.get_sha256_events()
     }
 
+    /// Get ripemd160 operations from all precompiled contract calls in this block.
+    pub(crate) fn get_ripemd160(&self) -> Vec<Ripemd160> {
+        self.precompile_events.get_ripemd160_events()
+    }
+
+    /// Get blake2f compressions from all precompiled contract calls in this block.
+    pub(crate) fn get_blake2f(&self) -> Vec<Blake2F> {
+        self.precompile_events.get_blake2f_events()
+    }
+
     pub(crate) fn print_evm_circuit_row_usage(&self) {
         let mut num_rows = 0;
         let mut counter = HashMap::new();
@@ -235,6 +259,10 @@ impl<F: Field> Block<F> {
         );
         log::debug!("tx_receipt num: {}", self.rws.rw_num(RwTableTag::TxReceipt));
         log::debug!("tx_log num: {}", self.rws.rw_num(RwTableTag::TxLog));
+        log::debug!(
+            "transient_storage num: {}",
+            self.rws.rw_num(RwTableTag::TransientStorage)
+        );
         log::debug!("start num: {}", self.rws.rw_num(RwTableTag::Start));
     }
 }
@@ -256,7 +284,7 @@ impl<F: Field> Block<F> {
         let num_rows_required_for_rw_table: usize = self.circuits_params.max_rws;
         let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(self)
             .iter()
-            .map(|tag| tag.build::<F>().count())
+            .map(|tag| tag.build::<F>(&self.fork_schedule).count())
             .sum();
         let num_rows_required_for_bytecode_table: usize = self
             .bytecodes
@@ -308,7 +336,7 @@ impl<F: Field> Block<F> {
 }
 
 /// Block context for execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockContext {
     /// The address of the miner for the block
     pub coinbase: Address,
@@ -322,6 +350,8 @@ pub struct BlockContext {
     pub difficulty: Word,
     /// The base fee, the minimum amount of gas fee for a transaction
     pub base_fee: Word,
+    /// The blob base fee (EIP-4844)
+    pub blob_base_fee: Word,
     /// The hash of previous blocks
     pub history_hashes: Vec<Word>,
     /// The chain id
@@ -394,6 +424,13 @@ impl BlockContext {
                     Value::known(current_block_number),
                     Value::known(F::from(num_all_txs)),
                 ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::BlobBaseFee as u64)),
+                    Value::known(current_block_number),
+                    randomness.map(|randomness| {
+                        rlc::value(&self.blob_base_fee.to_le_bytes(), randomness)
+                    }),
+                ],
             ],
             self.block_hash_assignments(randomness),
         ]
@@ -448,6 +485,7 @@ impl From<&circuit_input_builder::Block> for BlockContexts {
                             timestamp: block.timestamp,
                             difficulty: block.difficulty,
                             base_fee: block.base_fee,
+                            blob_base_fee: block.blob_base_fee,
                             history_hashes: block.history_hashes.clone(),
                             chain_id: block.chain_id,
                             eth_block: block.eth_block.clone(),
@@ -575,6 +613,7 @@ pub fn block_convert<F: Field>(
         mpt_updates,
         chain_id,
         start_l1_queue_index: block.start_l1_queue_index,
+        fork_schedule: block.fork_schedule.clone(),
         precompile_events: block.precompile_events.clone(),
     })
 }
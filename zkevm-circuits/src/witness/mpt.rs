@@ -10,9 +10,9 @@ use mpt_zktrie::{
     state,
     state::{builder::HASH_SCHEME_DONE, witness::WitnessGenerator},
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 pub use state::ZktrieState;
-use std::collections::BTreeMap;
+use std::collections::{btree_map::Entry, BTreeMap};
 
 /// Used to store withdraw proof
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -26,7 +26,7 @@ pub struct WithdrawProof {
 }
 
 /// An MPT update whose validity is proved by the MptCircuit
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MptUpdate {
     key: Key,
     old_value: Word,
@@ -35,6 +35,7 @@ pub struct MptUpdate {
     new_root: Word,
     // for debugging
     #[cfg(debug_assertions)]
+    #[serde(skip)]
     original_rws: Vec<Rw>,
 }
 
@@ -89,19 +90,61 @@ impl Default for MptUpdate {
 }
 
 /// All the MPT updates in the MptCircuit, accessible by their key
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "MptUpdatesData")]
 pub struct MptUpdates {
     old_root: Word,
     new_root: Word,
+    #[serde(serialize_with = "serialize_updates")]
     updates: BTreeMap<Key, MptUpdate>,
     /// TODO: is here the best place for this?
     /// Withdraw proof after this block
     pub withdraw_proof: WithdrawProof,
     /// The detailed mpt witness
     pub smt_traces: Vec<SMTTrace>,
+    #[serde(skip_serializing)]
     pub(crate) proof_types: Vec<MPTProofType>,
 }
 
+// Serialized MptUpdates, whose updates are a list since the keys can't be map keys of JSON. The
+// proof types are not serialized, which are recomputed from the updates.
+#[derive(Deserialize)]
+struct MptUpdatesData {
+    old_root: Word,
+    new_root: Word,
+    updates: Vec<(Key, MptUpdate)>,
+    withdraw_proof: WithdrawProof,
+    smt_traces: Vec<SMTTrace>,
+}
+
+impl From<MptUpdatesData> for MptUpdates {
+    fn from(data: MptUpdatesData) -> Self {
+        let updates: BTreeMap<_, _> = data.updates.into_iter().collect();
+        // The proof types are filled along with the SMT traces.
+        let proof_types = if data.smt_traces.is_empty() {
+            Vec::new()
+        } else {
+            updates.values().map(MptUpdate::proof_type).collect()
+        };
+
+        Self {
+            old_root: data.old_root,
+            new_root: data.new_root,
+            updates,
+            withdraw_proof: data.withdraw_proof,
+            smt_traces: data.smt_traces,
+            proof_types,
+        }
+    }
+}
+
+fn serialize_updates<S: Serializer>(
+    updates: &BTreeMap<Key, MptUpdate>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(updates)
+}
+
 /// The field element encoding of an MPT update, which is used by the MptTable
 #[derive(Debug, Clone, Copy)]
 pub struct MptUpdateRow<F>(pub(crate) [F; 7]);
@@ -218,6 +261,32 @@ impl MptUpdates {
         self.pretty_print();
     }
 
+    /// Merge the updates of the later rws, i.e. the ones of the existing keys keep the old values
+    /// and take the new values. The state roots need to be filled again.
+    pub(crate) fn extend(&mut self, other: Self) {
+        for (key, update) in other.updates {
+            match self.updates.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    let merged = entry.get_mut();
+                    merged.key = key.set_non_exists(merged.old_value, update.new_value);
+                    merged.new_value = update.new_value;
+                    #[cfg(debug_assertions)]
+                    merged.original_rws.extend(update.original_rws);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(update);
+                }
+            }
+        }
+    }
+
+    /// Fill the state roots and SMT traces by applying the updates to the trie state of the
+    /// witness generator, which is returned at the new root to apply the later updates.
+    pub(crate) fn apply_to_generator(&mut self, wit_gen: WitnessGenerator) -> WitnessGenerator {
+        self.old_root = U256::from_big_endian(wit_gen.root().as_bytes());
+        self.fill_state_roots_from_generator(wit_gen)
+    }
+
     fn fill_state_roots_from_generator(
         &mut self,
         mut wit_gen: WitnessGenerator,
@@ -425,7 +494,7 @@ impl MptUpdate {
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Debug, Copy, PartialOrd, Ord)]
+#[derive(Eq, PartialEq, Hash, Clone, Debug, Copy, PartialOrd, Ord, Serialize, Deserialize)]
 enum Key {
     Account {
         address: Address,
@@ -2,7 +2,9 @@
 use std::collections::HashMap;
 
 use bus_mapping::{
-    operation::{self, AccountField, CallContextField, TxLogField, TxReceiptField},
+    operation::{
+        self, AccountField, CallContextField, TransientStorageField, TxLogField, TxReceiptField,
+    },
     Error,
 };
 use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word, U256};
@@ -10,10 +12,14 @@ use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word, U256};
 use halo2_proofs::{circuit::Value, halo2curves::bn256::Fr};
 use itertools::Itertools;
 use rayon::prelude::{ParallelBridge, ParallelIterator};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     evm_circuit::util::rlc,
-    table::{AccountFieldTag, CallContextFieldTag, RwTableTag, TxLogFieldTag, TxReceiptFieldTag},
+    table::{
+        AccountFieldTag, CallContextFieldTag, RwTableTag, TransientStorageFieldTag, TxLogFieldTag,
+        TxReceiptFieldTag,
+    },
     util::build_tx_log_address,
 };
 
@@ -23,7 +29,7 @@ const ERR_MSG_FIRST: &str = "first access reads don't change value";
 const ERR_MSG_NON_FIRST: &str = "non-first access reads don't change value";
 
 /// Rw constainer for a witness block
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct RwMap(pub HashMap<RwTableTag, Vec<Rw>>);
 
 impl std::ops::Index<(RwTableTag, usize)> for RwMap {
@@ -221,7 +227,7 @@ pub type RwKey = (u64, usize, Address, u64, Word);
 
 /// Read-write records in execution. Rws are used for connecting evm circuit and
 /// state circuits.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Rw {
     /// Start
     Start { rw_counter: usize },
@@ -319,6 +325,17 @@ pub enum Rw {
         field_tag: TxReceiptFieldTag,
         value: u64,
     },
+    /// TransientStorage
+    TransientStorage {
+        rw_counter: usize,
+        is_write: bool,
+        tx_id: usize,
+        account_address: Address,
+        field_tag: TransientStorageFieldTag,
+        storage_key: Word,
+        value: Word,
+        value_prev: Word,
+    },
 }
 
 /// Rw table row assignment
@@ -456,6 +473,15 @@ impl Rw {
         }
     }
 
+    pub fn transient_storage_value_pair(&self) -> (Word, Word) {
+        match self {
+            Self::TransientStorage {
+                value, value_prev, ..
+            } => (*value, *value_prev),
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
     pub fn call_context_value(&self) -> Word {
         match self {
             Self::CallContext { value, .. } => *value,
@@ -555,7 +581,8 @@ impl Rw {
             | Self::Account { rw_counter, .. }
             | Self::CallContext { rw_counter, .. }
             | Self::TxLog { rw_counter, .. }
-            | Self::TxReceipt { rw_counter, .. } => *rw_counter,
+            | Self::TxReceipt { rw_counter, .. }
+            | Self::TransientStorage { rw_counter, .. } => *rw_counter,
         }
     }
 
@@ -571,7 +598,8 @@ impl Rw {
             | Self::Account { is_write, .. }
             | Self::CallContext { is_write, .. }
             | Self::TxLog { is_write, .. }
-            | Self::TxReceipt { is_write, .. } => *is_write,
+            | Self::TxReceipt { is_write, .. }
+            | Self::TransientStorage { is_write, .. } => *is_write,
         }
     }
 
@@ -588,6 +616,7 @@ impl Rw {
             Self::CallContext { .. } => RwTableTag::CallContext,
             Self::TxLog { .. } => RwTableTag::TxLog,
             Self::TxReceipt { .. } => RwTableTag::TxReceipt,
+            Self::TransientStorage { .. } => RwTableTag::TransientStorage,
         }
     }
 
@@ -599,7 +628,8 @@ impl Rw {
             | Self::TxAccessListAccountStorage { tx_id, .. }
             | Self::TxRefund { tx_id, .. }
             | Self::TxLog { tx_id, .. }
-            | Self::TxReceipt { tx_id, .. } => Some(*tx_id),
+            | Self::TxReceipt { tx_id, .. }
+            | Self::TransientStorage { tx_id, .. } => Some(*tx_id),
             Self::CallContext { call_id, .. }
             | Self::Stack { call_id, .. }
             | Self::Memory { call_id, .. } => Some(*call_id),
@@ -621,6 +651,9 @@ impl Rw {
             }
             | Self::AccountStorage {
                 account_address, ..
+            }
+            | Self::TransientStorage {
+                account_address, ..
             } => Some(*account_address),
             Self::Memory { memory_address, .. } => Some(Address::from_low_u64_be(*memory_address)),
             Self::Stack { stack_pointer, .. } => {
@@ -648,6 +681,7 @@ impl Rw {
             Self::Account { field_tag, .. } => Some(*field_tag as u64),
             Self::CallContext { field_tag, .. } => Some(*field_tag as u64),
             Self::TxReceipt { field_tag, .. } => Some(*field_tag as u64),
+            Self::TransientStorage { field_tag, .. } => Some(*field_tag as u64),
             // See comment above configure for is_non_exist in state_circuit.rs for the explanation
             // for why the field tag for AccountStorage is CodeHash instead of None.
             Self::AccountStorage { .. } => Some(AccountFieldTag::CodeHash as u64),
@@ -665,7 +699,8 @@ impl Rw {
     pub fn storage_key(&self) -> Option<Word> {
         match self {
             Self::AccountStorage { storage_key, .. }
-            | Self::TxAccessListAccountStorage { storage_key, .. } => Some(*storage_key),
+            | Self::TxAccessListAccountStorage { storage_key, .. }
+            | Self::TransientStorage { storage_key, .. } => Some(*storage_key),
             Self::Start { .. }
             | Self::CallContext { .. }
             | Self::Stack { .. }
@@ -715,9 +750,9 @@ impl Rw {
                 | AccountFieldTag::NonExisting
                 | AccountFieldTag::CodeSize => value.to_scalar().unwrap(),
             },
-            Self::AccountStorage { value, .. } | Self::Stack { value, .. } => {
-                rlc::value(&value.to_le_bytes(), randomness)
-            }
+            Self::AccountStorage { value, .. }
+            | Self::TransientStorage { value, .. }
+            | Self::Stack { value, .. } => rlc::value(&value.to_le_bytes(), randomness),
 
             Self::TxLog {
                 field_tag, value, ..
@@ -742,7 +777,8 @@ impl Rw {
             | Self::AccountStorage { value, .. }
             | Self::Stack { value, .. }
             | Self::Memory { value, .. }
-            | Self::TxLog { value, .. } => *value,
+            | Self::TxLog { value, .. }
+            | Self::TransientStorage { value, .. } => *value,
             Self::TxAccessListAccount { is_warm, .. }
             | Self::TxAccessListAccountStorage { is_warm, .. } => U256::from(*is_warm as u64),
             Self::TxRefund { value, .. } | Self::TxReceipt { value, .. } => U256::from(*value),
@@ -770,7 +806,7 @@ impl Rw {
                 | AccountFieldTag::NonExisting
                 | AccountFieldTag::CodeSize => value_prev.to_scalar().unwrap(),
             }),
-            Self::AccountStorage { value_prev, .. } => {
+            Self::AccountStorage { value_prev, .. } | Self::TransientStorage { value_prev, .. } => {
                 Some(rlc::value(&value_prev.to_le_bytes(), randomness))
             }
             Self::Memory { value_prev, .. } => {
@@ -1025,6 +1061,28 @@ impl From<&operation::OperationContainer> for RwMap {
                 })
                 .collect(),
         );
+        rws.insert(
+            RwTableTag::TransientStorage,
+            container
+                .transient_storage
+                .iter()
+                .map(|op| Rw::TransientStorage {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    tx_id: op.op().tx_id,
+                    account_address: op.op().address,
+                    field_tag: match op.op().field {
+                        TransientStorageField::Slot => TransientStorageFieldTag::Slot,
+                        TransientStorageField::AccountCreated => {
+                            TransientStorageFieldTag::AccountCreated
+                        }
+                    },
+                    storage_key: op.op().key,
+                    value: op.op().value,
+                    value_prev: op.op().value_prev,
+                })
+                .collect(),
+        );
 
         Self(rws)
     }
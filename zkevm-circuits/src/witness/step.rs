@@ -8,6 +8,7 @@ use bus_mapping::{
     operation,
     precompile::{PrecompileAuxData, PrecompileCalls},
 };
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{
     evm_circuit::{
@@ -18,7 +19,7 @@ use crate::{
 };
 
 /// Step executed in a transaction
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExecStep {
     /// The index in the Transaction calls
     pub call_index: usize,
@@ -47,6 +48,7 @@ pub struct ExecStep {
     /// The counter for log index within tx
     pub log_id: usize,
     /// The opcode corresponds to the step
+    #[serde(with = "opcode_serde")]
     pub opcode: Option<OpcodeId>,
     /// The block number in which this step exists.
     pub block_num: u64,
@@ -54,6 +56,34 @@ pub struct ExecStep {
     pub aux_data: Option<PrecompileAuxData>,
 }
 
+// OpcodeId is serialized as its byte and whether it's invalid, since `INVALID(_)` can't be
+// deserialized from its name, and some bytes are parsed as `INVALID(_)` by feature.
+mod opcode_serde {
+    use super::*;
+
+    pub(super) fn serialize<S: Serializer>(
+        opcode: &Option<OpcodeId>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        opcode
+            .map(|opcode| (opcode.as_u8(), matches!(opcode, OpcodeId::INVALID(_))))
+            .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<OpcodeId>, D::Error> {
+        let opcode = Option::<(u8, bool)>::deserialize(deserializer)?;
+        Ok(opcode.map(|(byte, is_invalid)| {
+            if is_invalid {
+                OpcodeId::INVALID(byte)
+            } else {
+                OpcodeId::from(byte)
+            }
+        }))
+    }
+}
+
 impl ExecStep {
     /// The memory size in word **before** this step
     pub fn memory_word_size(&self) -> u64 {
@@ -143,13 +173,6 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     return ExecutionState::LOG;
                 }
 
-                macro_rules! dummy {
-                    ($name:expr) => {{
-                        log::warn!("{:?} is implemented with DummyGadget", $name);
-                        $name
-                    }};
-                }
-
                 match op {
                     OpcodeId::ADD | OpcodeId::SUB => ExecutionState::ADD_SUB,
                     OpcodeId::ADDMOD => ExecutionState::ADDMOD,
@@ -199,6 +222,11 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::SHL | OpcodeId::SHR => ExecutionState::SHL_SHR,
                     OpcodeId::SLOAD => ExecutionState::SLOAD,
                     OpcodeId::SSTORE => ExecutionState::SSTORE,
+                    OpcodeId::TLOAD => ExecutionState::TLOAD,
+                    OpcodeId::TSTORE => ExecutionState::TSTORE,
+                    OpcodeId::MCOPY => ExecutionState::MCOPY,
+                    OpcodeId::BLOBHASH => ExecutionState::BLOBHASH,
+                    OpcodeId::BLOBBASEFEE => ExecutionState::BLOBBASEFEE,
                     OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
                     OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
                     OpcodeId::CHAINID => ExecutionState::CHAINID,
@@ -217,8 +245,7 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::RETURNDATACOPY => ExecutionState::RETURNDATACOPY,
                     OpcodeId::CREATE => ExecutionState::CREATE,
                     OpcodeId::CREATE2 => ExecutionState::CREATE2,
-                    // dummy ops
-                    OpcodeId::SELFDESTRUCT => dummy!(ExecutionState::SELFDESTRUCT),
+                    OpcodeId::SELFDESTRUCT => ExecutionState::SELFDESTRUCT,
                     _ => unimplemented!("unimplemented opcode {:?}", op),
                 }
             }
@@ -260,6 +287,7 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
                     operation::Target::CallContext => RwTableTag::CallContext,
                     operation::Target::TxReceipt => RwTableTag::TxReceipt,
                     operation::Target::TxLog => RwTableTag::TxLog,
+                    operation::Target::TransientStorage => RwTableTag::TransientStorage,
                     operation::Target::Start => RwTableTag::Start,
                 };
                 (tag, x.as_usize())
@@ -6,8 +6,8 @@ use crate::{
         rlp_fsm::SmState,
         DataTable, Format,
         Format::{
-            L1MsgHash, TxHashEip155, TxHashEip1559, TxHashEip2930, TxHashPreEip155, TxSignEip155,
-            TxSignEip1559, TxSignEip2930, TxSignPreEip155,
+            L1MsgHash, TxHashEip155, TxHashEip1559, TxHashEip2930, TxHashEip4844, TxHashPreEip155,
+            TxSignEip155, TxSignEip1559, TxSignEip2930, TxSignEip4844, TxSignPreEip155,
         },
         RlpFsmWitnessGen, RlpFsmWitnessRow, RlpTable, RlpTag, State,
         State::DecodeTagStart,
@@ -17,13 +17,13 @@ use crate::{
 };
 use bus_mapping::circuit_input_builder::{self, get_dummy_tx_hash, TxL1Fee};
 use eth_types::{
-    evm_types::gas_utils::tx_data_gas_cost,
-    geth_types::{access_list_size, TxType, TxType::PreEip155},
+    evm_types::{block_utils::MAX_BLOBS_PER_TX, gas_utils::tx_data_gas_cost},
+    geth_types::{access_list_size, get_rlp_signed, get_rlp_unsigned, TxType, TxType::PreEip155},
     sign_types::{
         biguint_to_32bytes_le, ct_option_ok_or, get_dummy_tx, recover_pk2, SignData, SECP256K1_Q,
     },
     AccessList, Address, Error, Field, Signature, ToBigEndian, ToLittleEndian, ToScalar, ToWord,
-    Word, H256,
+    Word, H256, U64,
 };
 use ethers_core::{
     types::TransactionRequest,
@@ -36,12 +36,13 @@ use halo2_proofs::{
 use mock::MockTransaction;
 use num::Integer;
 use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
 use std::{cmp::Ordering, collections::BTreeMap};
 
 use super::{step::step_convert, Call, ExecStep};
 
 /// Transaction in a witness block
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Transaction {
     /// The block number in which this tx is included in
     pub block_number: u64,
@@ -91,6 +92,14 @@ pub struct Transaction {
     pub l1_fee_committed: TxL1Fee,
     /// Optional access list for EIP-2930
     pub access_list: Option<AccessList>,
+    /// The max fee per gas (EIP-1559), only set for blob txs
+    pub max_fee_per_gas: Word,
+    /// The max priority fee per gas (EIP-1559), only set for blob txs
+    pub max_priority_fee_per_gas: Word,
+    /// The max fee per blob gas (EIP-4844)
+    pub max_fee_per_blob_gas: Word,
+    /// The blob versioned hashes (EIP-4844)
+    pub blob_versioned_hashes: Vec<H256>,
     /// The calls made in the transaction
     pub calls: Vec<Call>,
     /// The steps executioned in the transaction
@@ -164,7 +173,7 @@ impl Transaction {
         let (access_list_address_size, access_list_storage_key_size) =
             access_list_size(&self.access_list);
 
-        let ret = vec![
+        let mut ret = vec![
             [
                 Value::known(F::from(self.id as u64)),
                 Value::known(F::from(TxContextFieldTag::Nonce as u64)),
@@ -334,11 +343,49 @@ impl Transaction {
             ],
             [
                 Value::known(F::from(self.id as u64)),
-                Value::known(F::from(TxContextFieldTag::BlockNumber as u64)),
+                Value::known(F::from(TxContextFieldTag::MaxFeePerGas as u64)),
                 Value::known(F::zero()),
-                Value::known(F::from(self.block_number)),
+                rlc_be_bytes(&self.max_fee_per_gas.to_be_bytes(), challenges.evm_word()),
+            ],
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::MaxPriorityFeePerGas as u64)),
+                Value::known(F::zero()),
+                rlc_be_bytes(
+                    &self.max_priority_fee_per_gas.to_be_bytes(),
+                    challenges.evm_word(),
+                ),
+            ],
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::MaxFeePerBlobGas as u64)),
+                Value::known(F::zero()),
+                rlc_be_bytes(
+                    &self.max_fee_per_blob_gas.to_be_bytes(),
+                    challenges.evm_word(),
+                ),
             ],
         ];
+        // The blob versioned hashes always occupy MAX_BLOBS_PER_TX rows, the
+        // missing ones are padded with zeros.
+        ret.extend((0..MAX_BLOBS_PER_TX).map(|idx| {
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::BlobVersionedHash as u64)),
+                Value::known(F::from(idx as u64)),
+                self.blob_versioned_hashes
+                    .get(idx)
+                    .map_or(Value::known(F::zero()), |hash| {
+                        rlc_be_bytes(&hash.to_fixed_bytes(), challenges.evm_word())
+                    }),
+            ]
+        }));
+        ret.push([
+            Value::known(F::from(self.id as u64)),
+            Value::known(F::from(TxContextFieldTag::BlockNumber as u64)),
+            Value::known(F::zero()),
+            Value::known(F::from(self.block_number)),
+        ]);
 
         ret
     }
@@ -376,6 +423,7 @@ impl Transaction {
                     TxType::Eip1559 => TxHashEip1559,
                     TxType::L1Msg => L1MsgHash,
                     TxType::Eip2930 => TxHashEip2930,
+                    TxType::Eip4844 => TxHashEip4844,
                 },
             )
         } else {
@@ -386,6 +434,7 @@ impl Transaction {
                     TxType::PreEip155 => TxSignPreEip155,
                     TxType::Eip1559 => TxSignEip1559,
                     TxType::Eip2930 => TxSignEip2930,
+                    TxType::Eip4844 => TxSignEip4844,
                     _ => unreachable!("tx type {:?} not supported", self.tx_type),
                 },
             )
@@ -746,6 +795,20 @@ impl Transaction {
         witness
     }
 
+    /// The RLP tags decoded from the RLP encoding for hashing (or signing) together with the
+    /// index (starting from 1) of the byte at which each tag is decoded.
+    pub(crate) fn rlp_tag_byte_idx<F: Field>(
+        &self,
+        is_hash: bool,
+        challenges: &Challenges<Value<F>>,
+    ) -> Vec<(RlpTag, usize)> {
+        self.gen_rlp_witness(is_hash, challenges)
+            .into_iter()
+            .filter(|row| row.rlp_table.is_output)
+            .map(|row| (row.rlp_table.rlp_tag, row.state_machine.byte_idx))
+            .collect()
+    }
+
     #[cfg(test)]
     pub(crate) fn new_from_rlp_bytes(
         tx_type: TxType,
@@ -813,6 +876,7 @@ impl<F: Field> RlpFsmWitnessGen<F> for Transaction {
             TxType::PreEip155 => (TxHashPreEip155, Some(TxSignPreEip155)),
             TxType::Eip1559 => (TxHashEip1559, Some(TxSignEip1559)),
             TxType::Eip2930 => (TxHashEip2930, Some(TxSignEip2930)),
+            TxType::Eip4844 => (TxHashEip4844, Some(TxSignEip4844)),
             TxType::L1Msg => (L1MsgHash, None),
         };
 
@@ -859,7 +923,15 @@ impl From<MockTransaction> for Transaction {
             s: mock_tx.s.expect("tx expected to be signed"),
             v: mock_tx.v.expect("tx expected to be signed").as_u64(),
         };
-        let (rlp_unsigned, rlp_signed) = {
+        let tx_type = if mock_tx.transaction_type == U64::from(TxType::Eip4844 as u64) {
+            TxType::Eip4844
+        } else {
+            TxType::Eip155
+        };
+        let (rlp_unsigned, rlp_signed) = if tx_type.is_eip4844_tx() {
+            let eth_tx = eth_types::Transaction::from(mock_tx.clone());
+            (get_rlp_unsigned(&eth_tx), get_rlp_signed(&eth_tx))
+        } else {
             let mut legacy_tx = TransactionRequest::new()
                 .from(mock_tx.from.address())
                 .nonce(mock_tx.nonce)
@@ -877,12 +949,17 @@ impl From<MockTransaction> for Transaction {
 
             (unsigned, signed)
         };
+        let (max_fee_per_gas, max_priority_fee_per_gas) = if tx_type.is_eip4844_tx() {
+            (mock_tx.max_fee_per_gas, mock_tx.max_priority_fee_per_gas)
+        } else {
+            (Word::zero(), Word::zero())
+        };
         let access_list = Some(mock_tx.access_list);
         Self {
             block_number: 1,
             id: mock_tx.transaction_index.as_usize(),
             hash: mock_tx.hash.unwrap_or_default(),
-            tx_type: TxType::Eip155,
+            tx_type,
             nonce: mock_tx.nonce.as_u64(),
             gas: mock_tx.gas.as_u64(),
             gas_price: mock_tx.gas_price,
@@ -903,13 +980,17 @@ impl From<MockTransaction> for Transaction {
             l1_fee: Default::default(),
             l1_fee_committed: Default::default(),
             access_list,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            max_fee_per_blob_gas: mock_tx.max_fee_per_blob_gas,
+            blob_versioned_hashes: mock_tx.blob_versioned_hashes,
             calls: vec![],
             steps: vec![],
         }
     }
 }
 
-pub(super) fn tx_convert(
+pub(crate) fn tx_convert(
     tx: &circuit_input_builder::Transaction,
     id: usize,
     chain_id: u64,
@@ -929,6 +1010,12 @@ pub(super) fn tx_convert(
     } else {
         tx_data_gas_cost(&tx.rlp_bytes)
     };
+    // Only blob txs have the EIP-1559 fee fields in the tx table.
+    let (max_fee_per_gas, max_priority_fee_per_gas) = if tx.tx_type.is_eip4844_tx() {
+        (tx.gas_fee_cap, tx.gas_tip_cap)
+    } else {
+        (Word::zero(), Word::zero())
+    };
 
     Transaction {
         block_number: tx.block_num,
@@ -955,6 +1042,10 @@ pub(super) fn tx_convert(
         l1_fee: tx.l1_fee,
         l1_fee_committed: tx.l1_fee_committed,
         access_list: tx.access_list.clone(),
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        max_fee_per_blob_gas: tx.max_fee_per_blob_gas,
+        blob_versioned_hashes: tx.blob_versioned_hashes.clone(),
         calls: tx
             .calls()
             .iter()
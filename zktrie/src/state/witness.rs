@@ -44,6 +44,12 @@ pub struct WitnessGenerator {
     storages_cache: HashMap<Address, ZkTrie>,
 }
 
+impl fmt::Debug for WitnessGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WitnessGenerator: {:x?}", self.root())
+    }
+}
+
 impl From<&ZktrieState> for WitnessGenerator {
     fn from(state: &ZktrieState) -> Self {
         Self {